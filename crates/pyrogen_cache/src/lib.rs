@@ -1,5 +1,9 @@
+use std::hash::Hasher;
 use std::path::{Path, PathBuf};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 pub use cache_key::{CacheKey, CacheKeyHasher};
 
 mod cache_key;
@@ -8,7 +12,81 @@ pub mod globset;
 
 pub const CACHE_DIR_NAME: &str = ".pyrogen_cache";
 
+/// Where the cache directory of a project is placed by default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum CacheLocation {
+    /// In a `.pyrogen_cache` directory in the project root.
+    #[default]
+    Project,
+    /// In the cache directory of the platform (like `$XDG_CACHE_HOME` or `~/.cache` on Linux),
+    /// in a directory named after a hash of the project root.
+    Platform,
+}
+
+/// What decides whether the cached results of a file are still valid.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum CacheInvalidation {
+    /// The last-modified time and the permissions of the file.
+    #[default]
+    Metadata,
+    /// A hash of the contents of the file, which stays valid when tools like `git checkout` or
+    /// build systems change the modification time without changing the file.
+    Content,
+}
+
 /// Return the cache directory for a given project root.
-pub fn cache_dir(project_root: &Path) -> PathBuf {
-    project_root.join(CACHE_DIR_NAME)
+///
+/// Falls back to the project root if the platform has no cache directory.
+pub fn cache_dir(project_root: &Path, location: CacheLocation) -> PathBuf {
+    match location {
+        CacheLocation::Project => project_root.join(CACHE_DIR_NAME),
+        CacheLocation::Platform => {
+            platform_cache_dir(project_root).unwrap_or_else(|| project_root.join(CACHE_DIR_NAME))
+        }
+    }
+}
+
+fn platform_cache_dir(project_root: &Path) -> Option<PathBuf> {
+    let mut hasher = CacheKeyHasher::new();
+    project_root.cache_key(&mut hasher);
+    Some(
+        dirs::cache_dir()?
+            .join("pyrogen")
+            .join(format!("{:016x}", hasher.finish())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{cache_dir, CacheLocation, CACHE_DIR_NAME};
+
+    #[test]
+    fn cache_locations() {
+        let project = Path::new("/home/user/project");
+        assert_eq!(
+            cache_dir(project, CacheLocation::Project),
+            project.join(CACHE_DIR_NAME)
+        );
+
+        let platform = cache_dir(project, CacheLocation::Platform);
+        if let Some(platform_cache) = dirs::cache_dir() {
+            assert!(platform.starts_with(platform_cache.join("pyrogen")));
+            assert_ne!(
+                platform,
+                cache_dir(Path::new("/home/user/other"), CacheLocation::Platform)
+            );
+        }
+    }
 }
@@ -0,0 +1,3 @@
+﻿_a = {1: 1, 1: 2}
+_b = 1  # type: ignore
+_c = {'ä': 1, 'ä': 2}_d = 3
@@ -0,0 +1,57 @@
+//! Cooperative cancellation of checks, so that a check whose results are no longer needed (like
+//! the check of a document that was edited again) stops early instead of running to completion.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A flag that the caller of a check sets to cancel it. The check looks at it between files and
+/// between its passes over a file, and returns [`Cancelled`] once it's set.
+///
+/// Clones share the flag, so a clone can be handed to the check while the original is kept to
+/// cancel it.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the checks that use this token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Returns [`Cancelled`] if the token was cancelled, to stop a check with `?`.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The error of a check that was cancelled with a [`CancellationToken`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("The check was cancelled")]
+pub struct Cancelled;
+
+#[cfg(test)]
+mod tests {
+    use super::{CancellationToken, Cancelled};
+
+    #[test]
+    fn clones_share_the_flag() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert_eq!(clone.check(), Ok(()));
+        token.cancel();
+        assert!(clone.is_cancelled());
+        assert_eq!(clone.check(), Err(Cancelled));
+    }
+}
@@ -1,3 +1,5 @@
 pub(crate) mod filesystem;
+pub(crate) mod static_conditions;
 pub(crate) mod type_ignore;
 pub(crate) mod typecheck;
+pub(crate) mod unicode;
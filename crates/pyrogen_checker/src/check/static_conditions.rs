@@ -0,0 +1,380 @@
+//! Static evaluation of `sys.version_info` and `sys.platform` checks.
+//!
+//! Branches guarded by such checks are pruned from the AST before any rules run, based on the
+//! configured `target-version` and `platform`, so that code written for other versions or
+//! platforms doesn't produce spurious diagnostics.
+//!
+//! The `target-version` is the *minimum* supported version: a version check is only decided if
+//! it has the same outcome on the target version and every later one.
+
+use std::cmp::Ordering;
+
+use rustpython_ast::{
+    self as ast, BoolOp, CmpOp, Constant, ExceptHandler, Expr, Ranged, Stmt, UnaryOp,
+};
+use rustpython_parser::text_size::TextRange;
+
+use crate::settings::types::{PythonPlatform, PythonVersion};
+
+/// The interpreter that static conditions are evaluated against.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct StaticTarget {
+    pub(crate) version: PythonVersion,
+    pub(crate) platform: PythonPlatform,
+}
+
+/// Remove the branches of `if` statements that can never be taken on the given target.
+///
+/// A branch that is always taken keeps its body and loses its `else`; a branch that is never
+/// taken is replaced by its `else` (which may be empty).
+///
+/// Returns the ranges of the removed statements.
+pub(crate) fn prune_static_branches(body: &mut [Stmt], target: StaticTarget) -> Vec<TextRange> {
+    let mut pruned = vec![];
+    prune_body(body, target, &mut pruned);
+    pruned
+}
+
+fn prune_body(body: &mut [Stmt], target: StaticTarget, pruned: &mut Vec<TextRange>) {
+    for stmt in body {
+        prune_stmt(stmt, target, pruned);
+    }
+}
+
+/// The range from the start of the first to the end of the last statement of `body`.
+fn body_range(body: &[Stmt]) -> Option<TextRange> {
+    let (first, last) = (body.first()?, body.last()?);
+    Some(TextRange::new(first.start(), last.end()))
+}
+
+fn prune_stmt(stmt: &mut Stmt, target: StaticTarget, pruned: &mut Vec<TextRange>) {
+    match stmt {
+        Stmt::If(ast::StmtIf {
+            test, body, orelse, ..
+        }) => {
+            match evaluate(test, target) {
+                Some(true) => {
+                    pruned.extend(body_range(orelse));
+                    orelse.clear();
+                }
+                Some(false) => {
+                    pruned.extend(body_range(body));
+                    *body = std::mem::take(orelse);
+                }
+                None => {}
+            }
+            prune_body(body, target, pruned);
+            prune_body(orelse, target, pruned);
+        }
+        Stmt::FunctionDef(ast::StmtFunctionDef { body, .. })
+        | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { body, .. })
+        | Stmt::ClassDef(ast::StmtClassDef { body, .. })
+        | Stmt::With(ast::StmtWith { body, .. })
+        | Stmt::AsyncWith(ast::StmtAsyncWith { body, .. }) => {
+            prune_body(body, target, pruned);
+        }
+        Stmt::For(ast::StmtFor { body, orelse, .. })
+        | Stmt::AsyncFor(ast::StmtAsyncFor { body, orelse, .. })
+        | Stmt::While(ast::StmtWhile { body, orelse, .. }) => {
+            prune_body(body, target, pruned);
+            prune_body(orelse, target, pruned);
+        }
+        Stmt::Try(ast::StmtTry {
+            body,
+            handlers,
+            orelse,
+            finalbody,
+            ..
+        })
+        | Stmt::TryStar(ast::StmtTryStar {
+            body,
+            handlers,
+            orelse,
+            finalbody,
+            ..
+        }) => {
+            prune_body(body, target, pruned);
+            for handler in handlers {
+                let ExceptHandler::ExceptHandler(handler) = handler;
+                prune_body(&mut handler.body, target, pruned);
+            }
+            prune_body(orelse, target, pruned);
+            prune_body(finalbody, target, pruned);
+        }
+        Stmt::Match(ast::StmtMatch { cases, .. }) => {
+            for case in cases {
+                prune_body(&mut case.body, target, pruned);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Evaluate `expr` on the given target, returning `None` if it can't be determined statically.
+pub(crate) fn evaluate(expr: &Expr, target: StaticTarget) -> Option<bool> {
+    match expr {
+        Expr::UnaryOp(ast::ExprUnaryOp {
+            op: UnaryOp::Not,
+            operand,
+            ..
+        }) => evaluate(operand, target).map(|value| !value),
+        Expr::BoolOp(ast::ExprBoolOp { op, values, .. }) => {
+            // `and` short-circuits on `False`, `or` on `True`.
+            let short_circuit = matches!(op, BoolOp::Or);
+            let mut result = Some(!short_circuit);
+            for value in values {
+                match evaluate(value, target) {
+                    Some(value) if value == short_circuit => return Some(short_circuit),
+                    Some(_) => {}
+                    None => result = None,
+                }
+            }
+            result
+        }
+        Expr::Compare(ast::ExprCompare {
+            left,
+            ops,
+            comparators,
+            ..
+        }) => {
+            let ([op], [right]) = (ops.as_slice(), comparators.as_slice()) else {
+                return None;
+            };
+            compare(left, op, right, target)
+                .or_else(|| compare(right, &reversed(op)?, left, target))
+        }
+        Expr::Call(ast::ExprCall {
+            func,
+            args,
+            keywords,
+            ..
+        }) if keywords.is_empty() => {
+            // `sys.platform.startswith("linux")`
+            let Expr::Attribute(ast::ExprAttribute { value, attr, .. }) = func.as_ref() else {
+                return None;
+            };
+            let [prefix] = args.as_slice() else {
+                return None;
+            };
+            if attr.as_str() != "startswith" || !is_sys_attribute(value, "platform") {
+                return None;
+            }
+            let platform = target.platform.sys_platform()?;
+            Some(platform.starts_with(as_str(prefix)?))
+        }
+        _ => None,
+    }
+}
+
+/// Evaluate `left <op> right`, where `left` is the `sys` attribute.
+fn compare(left: &Expr, op: &CmpOp, right: &Expr, target: StaticTarget) -> Option<bool> {
+    if is_sys_attribute(left, "version_info") {
+        // `sys.version_info >= (3, 11)`
+        let Expr::Tuple(ast::ExprTuple { elts, .. }) = right else {
+            return None;
+        };
+        let version = elts.iter().map(as_int).collect::<Option<Vec<u32>>>()?;
+        let (major, minor) = target.version.as_tuple();
+        let target_version = [u32::from(major), u32::from(minor)];
+        if version.is_empty() || version.len() > target_version.len() {
+            return None;
+        }
+        apply_from(op, target_version[..version.len()].cmp(version.as_slice()))
+    } else if let Expr::Subscript(ast::ExprSubscript { value, slice, .. }) = left {
+        // `sys.version_info[0] >= 3`
+        if !is_sys_attribute(value, "version_info") {
+            return None;
+        }
+        let (major, minor) = target.version.as_tuple();
+        let component = match as_int(slice)? {
+            0 => major,
+            1 => minor,
+            _ => return None,
+        };
+        apply_from(op, u32::from(component).cmp(&as_int(right)?))
+    } else if is_sys_attribute(left, "platform") {
+        // `sys.platform == "win32"`
+        let platform = target.platform.sys_platform()?;
+        let value = as_str(right)?;
+        match op {
+            CmpOp::Eq => Some(platform == value),
+            CmpOp::NotEq => Some(platform != value),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+/// Evaluate `version <op> value` for every supported version, given how the minimum supported
+/// version compares to `value`, returning `None` unless all of them agree.
+///
+/// Later versions only compare greater, so e.g. `sys.version_info >= (3, 12)` is undecidable
+/// when targeting 3.11, while `sys.version_info < (3, 10)` is always false.
+fn apply_from(op: &CmpOp, minimum: Ordering) -> Option<bool> {
+    let orderings: &[Ordering] = match minimum {
+        Ordering::Less => &[Ordering::Less, Ordering::Equal, Ordering::Greater],
+        Ordering::Equal => &[Ordering::Equal, Ordering::Greater],
+        Ordering::Greater => &[Ordering::Greater],
+    };
+    let first = apply(op, orderings[0])?;
+    orderings[1..]
+        .iter()
+        .all(|ordering| apply(op, *ordering) == Some(first))
+        .then_some(first)
+}
+
+fn apply(op: &CmpOp, ordering: Ordering) -> Option<bool> {
+    match op {
+        CmpOp::Eq => Some(ordering.is_eq()),
+        CmpOp::NotEq => Some(ordering.is_ne()),
+        CmpOp::Lt => Some(ordering.is_lt()),
+        CmpOp::LtE => Some(ordering.is_le()),
+        CmpOp::Gt => Some(ordering.is_gt()),
+        CmpOp::GtE => Some(ordering.is_ge()),
+        _ => None,
+    }
+}
+
+/// The operator to use when swapping the operands of a comparison.
+fn reversed(op: &CmpOp) -> Option<CmpOp> {
+    match op {
+        CmpOp::Eq => Some(CmpOp::Eq),
+        CmpOp::NotEq => Some(CmpOp::NotEq),
+        CmpOp::Lt => Some(CmpOp::Gt),
+        CmpOp::LtE => Some(CmpOp::GtE),
+        CmpOp::Gt => Some(CmpOp::Lt),
+        CmpOp::GtE => Some(CmpOp::LtE),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `expr` is `sys.<attribute>`.
+fn is_sys_attribute(expr: &Expr, attribute: &str) -> bool {
+    let Expr::Attribute(ast::ExprAttribute { value, attr, .. }) = expr else {
+        return false;
+    };
+    attr.as_str() == attribute
+        && matches!(value.as_ref(), Expr::Name(ast::ExprName { id, .. }) if id.as_str() == "sys")
+}
+
+fn as_int(expr: &Expr) -> Option<u32> {
+    match expr {
+        // Version components are small, so going through the decimal representation avoids
+        // depending on the big-integer backend used by the parser.
+        Expr::Constant(ast::ExprConstant {
+            value: Constant::Int(value),
+            ..
+        }) => value.to_string().parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_str(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Constant(ast::ExprConstant {
+            value: Constant::Str(value),
+            ..
+        }) => Some(value.as_str()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_ast::{self as ast, Expr, Stmt, Suite};
+    use rustpython_parser::Parse;
+    use test_case::test_case;
+
+    use crate::settings::types::{PythonPlatform, PythonVersion};
+
+    use super::{evaluate, prune_static_branches, StaticTarget};
+
+    const TARGET: StaticTarget = StaticTarget {
+        version: PythonVersion::Py311,
+        platform: PythonPlatform::Linux,
+    };
+
+    #[test_case("sys.version_info >= (3, 11)", Some(true); "version at least")]
+    #[test_case("sys.version_info >= (3, 10)", Some(true); "version at least older")]
+    #[test_case("sys.version_info > (3, 11)", None; "version above target")]
+    #[test_case("sys.version_info >= (3, 12)", None; "version at least newer")]
+    #[test_case("sys.version_info < (3, 11)", Some(false); "version below")]
+    #[test_case("sys.version_info < (3, 10)", Some(false); "version below older")]
+    #[test_case("sys.version_info < (3, 12)", None; "version below newer")]
+    #[test_case("sys.version_info <= (3, 11)", None; "version at most")]
+    #[test_case("sys.version_info == (3, 10)", Some(false); "version equal older")]
+    #[test_case("sys.version_info != (3, 10)", Some(true); "version not equal older")]
+    #[test_case("sys.version_info >= (3,)", Some(true); "major only")]
+    #[test_case("(3, 12) <= sys.version_info", None; "reversed")]
+    #[test_case("(3, 10) > sys.version_info", Some(false); "reversed older")]
+    #[test_case("sys.version_info[1] == 11", None; "subscript")]
+    #[test_case("sys.version_info[1] >= 8", Some(true); "subscript older")]
+    #[test_case("sys.version_info[0] == 2", Some(false); "subscript major")]
+    #[test_case("sys.version_info >= (3, 11, 2)", None; "micro is unknown")]
+    #[test_case("sys.platform == 'win32'", Some(false); "platform eq")]
+    #[test_case("sys.platform != 'win32'", Some(true); "platform not eq")]
+    #[test_case("sys.platform.startswith('lin')", Some(true); "platform startswith")]
+    #[test_case("not sys.platform == 'linux'", Some(false); "not")]
+    #[test_case("sys.platform == 'linux' and sys.version_info < (3, 10)", Some(false); "and")]
+    #[test_case("sys.platform == 'linux' or foo", Some(true); "or")]
+    #[test_case("foo", None; "unknown")]
+    fn evaluate_condition(source: &str, expected: Option<bool>) {
+        let expr = Expr::parse(source, "<test>").unwrap();
+        assert_eq!(evaluate(&expr, TARGET), expected);
+    }
+
+    #[test]
+    fn unknown_platform() {
+        let expr = Expr::parse("sys.platform == 'win32'", "<test>").unwrap();
+        let target = StaticTarget {
+            platform: PythonPlatform::All,
+            ..TARGET
+        };
+        assert_eq!(evaluate(&expr, target), None);
+    }
+
+    #[test]
+    fn prune_only_unreachable_versions() {
+        let mut body = Suite::parse(
+            "\
+if sys.version_info >= (3, 12):
+    new = 1
+else:
+    old = 1
+if sys.version_info < (3, 10):
+    legacy = 1
+else:
+    current = 1
+",
+            "<test>",
+        )
+        .unwrap();
+        prune_static_branches(&mut body, TARGET);
+
+        // Code for later versions is kept, since the target is only the minimum version.
+        let Stmt::If(ast::StmtIf {
+            body: new, orelse, ..
+        }) = &body[0]
+        else {
+            panic!("expected an `if` statement");
+        };
+        assert_eq!((new.len(), orelse.len()), (1, 1));
+        // Code for earlier versions is removed.
+        let Stmt::If(ast::StmtIf {
+            body: current,
+            orelse,
+            ..
+        }) = &body[1]
+        else {
+            panic!("expected an `if` statement");
+        };
+        assert!(orelse.is_empty());
+        let Stmt::Assign(ast::StmtAssign { targets, .. }) = &current[0] else {
+            panic!("expected an assignment");
+        };
+        assert!(targets[0]
+            .as_name_expr()
+            .is_some_and(|name| name.id.as_str() == "current"));
+    }
+}
@@ -3,16 +3,20 @@
 use std::path::Path;
 use std::str::FromStr;
 
+use chrono::NaiveDate;
 use itertools::Itertools;
 use rustpython_parser::ast::Ranged;
+use rustpython_parser::text_size::{TextLen, TextRange};
 
 use pyrogen_python_trivia::CommentRanges;
 use pyrogen_source_file::Locator;
 
-use crate::registry::{AsErrorCode, Diagnostic, DiagnosticKind, ErrorCode};
+use crate::fix::{Edit, Fix};
+use crate::registry::{AsErrorCode, Diagnostic, ErrorCode};
 use crate::settings::CheckerSettings;
 use crate::type_ignore;
 use crate::type_ignore::{Directive, FileExemption, TypeIgnoreMapping, TypeIgnores};
+use crate::violation::Violation;
 
 #[derive(Debug, PartialEq, Eq)]
 struct UnusedCodes {
@@ -21,29 +25,41 @@ struct UnusedCodes {
     pub unmatched: Vec<ErrorCode>,
 }
 
-fn unused_type_ignore(codes: Option<Vec<ErrorCode>>) -> DiagnosticKind {
-    if let Some(codes) = codes {
-        DiagnosticKind {
-            body: format!(
-                "Type ignore directive has unused codes: {}",
-                collect_rule_codes(codes)
-            ),
-            error_code: ErrorCode::UnusedTypeIgnore,
-        }
-    } else {
-        DiagnosticKind {
-            body: "Unused type ignore directive".to_string(),
-            error_code: ErrorCode::UnusedTypeIgnore,
-        }
-    }
+#[derive(Violation)]
+#[violation(code = UnusedTypeIgnore)]
+struct UnusedTypeIgnore;
+
+#[derive(Violation)]
+#[violation(code = UnusedTypeIgnore, message = "unused-type-ignore-codes")]
+struct UnusedTypeIgnoreCodes {
+    codes: String,
+}
+
+#[derive(Violation)]
+#[violation(code = ExpiredSuppression)]
+struct ExpiredSuppression {
+    date: NaiveDate,
+}
+
+#[derive(Violation)]
+#[violation(code = GeneralTypeError, message = "unknown-type-ignore-codes")]
+struct UnknownTypeIgnoreCodes {
+    codes: String,
 }
 
+/// Remove the diagnostics suppressed by `type: ignore` directives, and report the directives that
+/// are unused.
+///
+/// The directives in `pruned_ranges`, the branches that were skipped on the target version and
+/// platform, are never reported as unused: they may well be needed on other targets.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn check_type_ignore(
     diagnostics: &mut Vec<Diagnostic>,
     path: &Path,
     locator: &Locator,
     comment_ranges: &CommentRanges,
     noqa_line_for: &TypeIgnoreMapping,
+    pruned_ranges: &[TextRange],
     analyze_directives: bool,
     settings: &CheckerSettings,
 ) -> Vec<usize> {
@@ -53,6 +69,8 @@ pub(crate) fn check_type_ignore(
     // Extract all `noqa` directives.
     let mut noqa_directives = TypeIgnores::from_commented_ranges(comment_ranges, path, locator);
 
+    let today = chrono::Local::now().date_naive();
+
     // Indices of diagnostics that were ignored by a `type: ignore` directive.
     let mut ignored_diagnostics = vec![];
 
@@ -84,6 +102,11 @@ pub(crate) fn check_type_ignore(
         for noqa_offset in noqa_offsets {
             if let Some(directive_line) = noqa_directives.find_line_with_directive_mut(noqa_offset)
             {
+                // An expired directive no longer suppresses anything.
+                if directive_line.is_expired(today) {
+                    continue;
+                }
+
                 let suppressed = match &directive_line.directive {
                     Directive::All(_) => {
                         directive_line.matches.push(diagnostic.kind.error_code());
@@ -108,6 +131,24 @@ pub(crate) fn check_type_ignore(
         }
     }
 
+    // Report the directives that expired.
+    if settings.table.enabled(ErrorCode::ExpiredSuppression)
+        && !exemption.as_ref().is_some_and(|exemption| match exemption {
+            FileExemption::All => true,
+            FileExemption::Codes(codes) => codes.contains(&ErrorCode::ExpiredSuppression),
+        })
+    {
+        for line in noqa_directives.lines() {
+            if let Some(expires) = line.annotations.expires.filter(|_| line.is_expired(today)) {
+                let range = match &line.directive {
+                    Directive::All(directive) => directive.range(),
+                    Directive::Codes(directive) => directive.range(),
+                };
+                diagnostics.push(Diagnostic::new(ExpiredSuppression { date: expires }, range));
+            }
+        }
+    }
+
     // Enforce that the `type: ignore` directive was actually used.
     if settings.table.enabled(ErrorCode::UnusedTypeIgnore)
         && analyze_directives
@@ -116,12 +157,25 @@ pub(crate) fn check_type_ignore(
             FileExemption::Codes(codes) => codes.contains(&ErrorCode::UnusedTypeIgnore),
         })
     {
-        for line in noqa_directives.lines() {
+        let pruned_lines = pruned_ranges
+            .iter()
+            .map(|range| locator.full_lines_range(*range))
+            .collect::<Vec<_>>();
+        // Expired directives were reported above.
+        for line in noqa_directives.lines().iter().filter(|line| {
+            !line.is_expired(today)
+                && !pruned_lines
+                    .iter()
+                    .any(|pruned| pruned.contains(line.range.start()))
+        }) {
             match &line.directive {
                 Directive::All(directive) => {
                     if line.matches.is_empty() {
-                        let diagnostic =
-                            Diagnostic::new(unused_type_ignore(None), directive.range());
+                        let diagnostic = Diagnostic::new(UnusedTypeIgnore, directive.range())
+                            .with_fix(Fix::new(
+                                "Remove unused `type: ignore` directive",
+                                [delete_directive(directive.range(), locator)],
+                            ));
                         diagnostics.push(diagnostic);
                     }
                 }
@@ -154,19 +208,36 @@ pub(crate) fn check_type_ignore(
                     }
 
                     if !unmatched_codes.is_empty() {
-                        diagnostics.push(Diagnostic::new(
-                            unused_type_ignore(Some(unmatched_codes)),
-                            directive.range(),
-                        ));
+                        let kept_codes = directive
+                            .codes()
+                            .iter()
+                            .filter(|&&code| {
+                                ErrorCode::from_str(code)
+                                    .map_or(true, |rule| !unmatched_codes.contains(&rule))
+                            })
+                            .join(", ");
+                        let edit = if kept_codes.is_empty() {
+                            delete_directive(directive.range(), locator)
+                        } else {
+                            Edit::range_replacement(
+                                format!("# type: ignore[{kept_codes}]"),
+                                directive.range(),
+                            )
+                        };
+                        diagnostics.push(
+                            Diagnostic::new(
+                                UnusedTypeIgnoreCodes {
+                                    codes: collect_rule_codes(unmatched_codes),
+                                },
+                                directive.range(),
+                            )
+                            .with_fix(Fix::new("Remove unused codes", [edit])),
+                        );
                     }
                     if !unknown_codes.is_empty() {
                         diagnostics.push(Diagnostic::new(
-                            DiagnosticKind {
-                                body: format!(
-                                    "Type ignore directive has unknown codes: {}",
-                                    unknown_codes.iter().map(|code| code.to_string()).join(", ")
-                                ),
-                                error_code: ErrorCode::GeneralTypeError,
+                            UnknownTypeIgnoreCodes {
+                                codes: unknown_codes.iter().map(|code| code.to_string()).join(", "),
                             },
                             directive.range(),
                         ));
@@ -180,6 +251,28 @@ pub(crate) fn check_type_ignore(
     ignored_diagnostics
 }
 
+/// Delete the directive at `range`, along with the whitespace that separates it from the code
+/// before it, or the whole line if the directive is the only thing on it.
+fn delete_directive(range: TextRange, locator: &Locator) -> Edit {
+    let line_start = locator.line_start(range.start());
+    let line_end = locator.line_end(range.end());
+    let before = locator.slice(TextRange::new(line_start, range.start()));
+    let after = locator.slice(TextRange::new(range.end(), line_end));
+
+    if after.trim().is_empty() {
+        if before.trim().is_empty() {
+            Edit::range_deletion(locator.full_line_range(range.start()))
+        } else {
+            let trimmed = before.trim_end().text_len();
+            Edit::range_deletion(TextRange::new(line_start + trimmed, line_end))
+        }
+    } else {
+        // Keep the comment that follows the directive.
+        let trailing = after.text_len() - after.trim_start().text_len();
+        Edit::range_deletion(TextRange::new(range.start(), range.end() + trailing))
+    }
+}
+
 pub fn collect_rule_codes(rules: impl IntoIterator<Item = ErrorCode>) -> String {
     rules
         .into_iter()
@@ -188,3 +281,50 @@ pub fn collect_rule_codes(rules: impl IntoIterator<Item = ErrorCode>) -> String
         .dedup()
         .join(", ")
 }
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::ast::Ranged;
+    use rustpython_parser::text_size::{TextRange, TextSize};
+
+    use pyrogen_source_file::Locator;
+
+    use super::delete_directive;
+
+    /// Apply the deletion of the first occurrence of `directive` in `source`.
+    fn without_directive(source: &str, directive: &str) -> String {
+        let start = source.find(directive).unwrap();
+        let range = TextRange::at(
+            TextSize::try_from(start).unwrap(),
+            TextSize::try_from(directive.len()).unwrap(),
+        );
+        let edit = delete_directive(range, &Locator::new(source));
+        let mut fixed = source.to_string();
+        fixed.replace_range(std::ops::Range::<usize>::from(edit.range()), "");
+        fixed
+    }
+
+    #[test]
+    fn delete_trailing_directive() {
+        assert_eq!(
+            without_directive("x = 1  # type: ignore\ny = 2\n", "# type: ignore"),
+            "x = 1\ny = 2\n"
+        );
+    }
+
+    #[test]
+    fn delete_directive_before_comment() {
+        assert_eq!(
+            without_directive("x = 1  # type: ignore  # reason\n", "# type: ignore"),
+            "x = 1  # reason\n"
+        );
+    }
+
+    #[test]
+    fn delete_own_line_directive() {
+        assert_eq!(
+            without_directive("x = 1\n    # type: ignore\ny = 2\n", "# type: ignore"),
+            "x = 1\ny = 2\n"
+        );
+    }
+}
@@ -0,0 +1,363 @@
+//! Checks for missing type annotations, to ratchet up annotation coverage.
+//!
+//! Parameters and return types are checked in all functions, including methods and nested
+//! functions. Variables are only checked at the top level of a module (including the control flow
+//! around them) and only if they're public.
+
+use rustc_hash::FxHashSet;
+use rustpython_ast::{self as ast, Expr, Ranged, Stmt};
+
+use pyrogen_python_ast::visitor::{self, Visitor};
+
+use crate::definitions::identifier_range;
+use crate::registry::{Diagnostic, ErrorCode};
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = MissingParameterAnnotation)]
+struct MissingParameterAnnotation<'a> {
+    parameter: &'a str,
+    function: &'a str,
+}
+
+#[derive(Violation)]
+#[violation(code = MissingReturnAnnotation)]
+struct MissingReturnAnnotation<'a> {
+    function: &'a str,
+}
+
+#[derive(Violation)]
+#[violation(code = MissingVariableAnnotation)]
+struct MissingVariableAnnotation<'a> {
+    name: &'a str,
+}
+
+/// Calls whose result is given a name without being a variable in the usual sense, like
+/// `T = TypeVar("T")`.
+const TYPE_CONSTRUCTORS: &[&str] = &[
+    "TypeVar",
+    "ParamSpec",
+    "TypeVarTuple",
+    "NewType",
+    "NamedTuple",
+    "TypedDict",
+];
+
+fn is_type_constructor(value: &Expr) -> bool {
+    let Expr::Call(ast::ExprCall { func, .. }) = value else {
+        return false;
+    };
+    let name = match func.as_ref() {
+        Expr::Name(ast::ExprName { id, .. }) => id.as_str(),
+        Expr::Attribute(ast::ExprAttribute { attr, .. }) => attr.as_str(),
+        _ => return false,
+    };
+    TYPE_CONSTRUCTORS.contains(&name)
+}
+
+fn is_staticmethod(decorator_list: &[Expr]) -> bool {
+    decorator_list.iter().any(|decorator| match decorator {
+        Expr::Name(ast::ExprName { id, .. }) => id.as_str() == "staticmethod",
+        Expr::Attribute(ast::ExprAttribute { attr, .. }) => attr.as_str() == "staticmethod",
+        _ => false,
+    })
+}
+
+struct AnnotationChecker<'a> {
+    /// The code to report: `MissingParameterAnnotation` or `MissingReturnAnnotation`.
+    code: ErrorCode,
+    source: &'a str,
+    /// Whether the statements being visited are directly in a class body.
+    in_class: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl AnnotationChecker<'_> {
+    fn check_function(
+        &mut self,
+        stmt: &Stmt,
+        name: &str,
+        args: &ast::Arguments,
+        returns: Option<&Expr>,
+        decorator_list: &[Expr],
+    ) {
+        if self.code == ErrorCode::MissingParameterAnnotation {
+            // The type of `self` and `cls` is implied.
+            let skip = usize::from(self.in_class && !is_staticmethod(decorator_list));
+            let parameters = args
+                .posonlyargs
+                .iter()
+                .chain(&args.args)
+                .map(|parameter| &parameter.def)
+                .skip(skip)
+                .chain(args.vararg.as_deref())
+                .chain(args.kwonlyargs.iter().map(|parameter| &parameter.def))
+                .chain(args.kwarg.as_deref());
+            for parameter in parameters {
+                if parameter.annotation.is_none() {
+                    self.diagnostics.push(Diagnostic::new(
+                        MissingParameterAnnotation {
+                            parameter: parameter.arg.as_str(),
+                            function: name,
+                        },
+                        parameter.range(),
+                    ));
+                }
+            }
+        }
+        // `__init__` always returns `None`.
+        if returns.is_none()
+            && !(self.in_class && name == "__init__")
+            && self.code == ErrorCode::MissingReturnAnnotation
+        {
+            self.diagnostics.push(Diagnostic::new(
+                MissingReturnAnnotation { function: name },
+                identifier_range(stmt, name, self.source),
+            ));
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for AnnotationChecker<'_> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef {
+                name,
+                args,
+                returns,
+                decorator_list,
+                ..
+            })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+                name,
+                args,
+                returns,
+                decorator_list,
+                ..
+            }) => {
+                self.check_function(
+                    stmt,
+                    name.as_str(),
+                    args,
+                    returns.as_deref(),
+                    decorator_list,
+                );
+                let in_class = std::mem::replace(&mut self.in_class, false);
+                visitor::walk_stmt(self, stmt);
+                self.in_class = in_class;
+            }
+            Stmt::ClassDef(_) => {
+                let in_class = std::mem::replace(&mut self.in_class, true);
+                visitor::walk_stmt(self, stmt);
+                self.in_class = in_class;
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+}
+
+/// Collects the module-level assignments to names, and the names that are declared with an
+/// annotation anywhere at the module level.
+#[derive(Default)]
+struct VariableCollector<'a> {
+    annotated: FxHashSet<&'a str>,
+    assignments: Vec<&'a ast::ExprName>,
+}
+
+impl<'a> VariableCollector<'a> {
+    fn visit_body(&mut self, body: &'a [Stmt]) {
+        for stmt in body {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::Assign(ast::StmtAssign { targets, value, .. }) => {
+                if is_type_constructor(value) {
+                    return;
+                }
+                for target in targets {
+                    if let Expr::Name(name) = target {
+                        self.assignments.push(name);
+                    }
+                }
+            }
+            Stmt::AnnAssign(ast::StmtAnnAssign { target, .. }) => {
+                if let Expr::Name(ast::ExprName { id, .. }) = target.as_ref() {
+                    self.annotated.insert(id.as_str());
+                }
+            }
+            Stmt::If(ast::StmtIf { body, orelse, .. })
+            | Stmt::While(ast::StmtWhile { body, orelse, .. })
+            | Stmt::For(ast::StmtFor { body, orelse, .. })
+            | Stmt::AsyncFor(ast::StmtAsyncFor { body, orelse, .. }) => {
+                self.visit_body(body);
+                self.visit_body(orelse);
+            }
+            Stmt::With(ast::StmtWith { body, .. })
+            | Stmt::AsyncWith(ast::StmtAsyncWith { body, .. }) => self.visit_body(body),
+            Stmt::Try(ast::StmtTry {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            })
+            | Stmt::TryStar(ast::StmtTryStar {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            }) => {
+                self.visit_body(body);
+                for handler in handlers {
+                    let ast::ExceptHandler::ExceptHandler(handler) = handler;
+                    self.visit_body(&handler.body);
+                }
+                self.visit_body(orelse);
+                self.visit_body(finalbody);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_public(name: &str) -> bool {
+    !name.starts_with('_')
+}
+
+fn check_functions(python_ast: &[Stmt], source: &str, code: ErrorCode) -> Vec<Diagnostic> {
+    let mut checker = AnnotationChecker {
+        code,
+        source,
+        in_class: false,
+        diagnostics: vec![],
+    };
+    checker.visit_body(python_ast);
+    checker.diagnostics
+}
+
+/// Report the parameters without annotations.
+pub(crate) fn missing_parameter_annotations(python_ast: &[Stmt], source: &str) -> Vec<Diagnostic> {
+    check_functions(python_ast, source, ErrorCode::MissingParameterAnnotation)
+}
+
+/// Report the functions without a return type annotation.
+pub(crate) fn missing_return_annotations(python_ast: &[Stmt], source: &str) -> Vec<Diagnostic> {
+    check_functions(python_ast, source, ErrorCode::MissingReturnAnnotation)
+}
+
+/// Report the public module-level variables without annotations.
+pub(crate) fn missing_variable_annotations(python_ast: &[Stmt]) -> Vec<Diagnostic> {
+    let mut collector = VariableCollector::default();
+    collector.visit_body(python_ast);
+    let mut diagnostics = vec![];
+    let mut reported = FxHashSet::default();
+    for name in collector.assignments {
+        let id = name.id.as_str();
+        // Only the first assignment to a name is reported.
+        if is_public(id) && !collector.annotated.contains(id) && reported.insert(id) {
+            diagnostics.push(Diagnostic::new(
+                MissingVariableAnnotation { name: id },
+                name.range,
+            ));
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::{parse, Mode};
+
+    use crate::registry::{Diagnostic, ErrorCode};
+
+    use super::{
+        missing_parameter_annotations, missing_return_annotations, missing_variable_annotations,
+    };
+
+    fn check(source: &str, code: ErrorCode) -> Vec<String> {
+        let python_ast = parse(source, Mode::Module, "<test>")
+            .unwrap()
+            .expect_module()
+            .body;
+        let diagnostics: Vec<Diagnostic> = match code {
+            ErrorCode::MissingParameterAnnotation => {
+                missing_parameter_annotations(&python_ast, source)
+            }
+            ErrorCode::MissingReturnAnnotation => missing_return_annotations(&python_ast, source),
+            _ => missing_variable_annotations(&python_ast),
+        };
+        diagnostics
+            .into_iter()
+            .map(|diagnostic| diagnostic.kind.body)
+            .collect()
+    }
+
+    #[test]
+    fn functions() {
+        let source = r#"
+def annotated(a: int, *args: str, b: int = 0, **kwargs: str) -> None: ...
+
+def f(a, /, b: int, *args, c, **kwargs): ...
+
+class C:
+    def __init__(self, x): ...
+
+    @staticmethod
+    def create(x: int): ...
+
+    @classmethod
+    def build(cls) -> "C":
+        def inner(y) -> None: ...
+"#;
+        assert_eq!(
+            check(source, ErrorCode::MissingParameterAnnotation),
+            [
+                "Parameter `a` of `f` is missing a type annotation",
+                "Parameter `args` of `f` is missing a type annotation",
+                "Parameter `c` of `f` is missing a type annotation",
+                "Parameter `kwargs` of `f` is missing a type annotation",
+                "Parameter `x` of `__init__` is missing a type annotation",
+                "Parameter `y` of `inner` is missing a type annotation",
+            ]
+        );
+        assert_eq!(
+            check(source, ErrorCode::MissingReturnAnnotation),
+            [
+                "Function `f` is missing a return type annotation",
+                "Function `create` is missing a return type annotation",
+            ]
+        );
+    }
+
+    #[test]
+    fn variables() {
+        let source = r#"
+from typing import TypeVar
+
+T = TypeVar("T")
+VERSION = "1.0"
+_private = 1
+declared: int
+if True:
+    declared = 1
+    other = 2
+else:
+    other = 3
+a, b = 1, 2
+
+def f():
+    local = 1
+"#;
+        assert_eq!(
+            check(source, ErrorCode::MissingVariableAnnotation),
+            [
+                "Public variable `VERSION` is missing a type annotation",
+                "Public variable `other` is missing a type annotation",
+            ]
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! Calls to functions imported from other modules of the project.
+//!
+//! Only names bound by a module-level `from module import name` are checked, and only if the
+//! name isn't bound anywhere else in the module, so that the callee is known to be the function
+//! described by the other module's interface. Names with several alternative definitions (see
+//! [`Symbol::Union`]) are checked against all of them.
+
+use rustc_hash::FxHashMap;
+use rustpython_ast::{self as ast, Expr, ExprContext, Pattern, Stmt};
+
+use pyrogen_python_ast::visitor::{self, Visitor};
+
+use crate::interface::{ModuleContext, Signature, Symbol};
+use crate::registry::Diagnostic;
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = CallArgument, message = "call-argument")]
+struct CallArgument<'a> {
+    name: &'a str,
+    problem: &'a str,
+}
+
+/// Counts how often every name is bound, in any scope of the module.
+#[derive(Default)]
+struct BindingCounter<'a> {
+    bindings: FxHashMap<&'a str, usize>,
+}
+
+impl<'a> BindingCounter<'a> {
+    fn bind(&mut self, name: &'a str) {
+        *self.bindings.entry(name).or_default() += 1;
+    }
+}
+
+impl<'a> Visitor<'a> for BindingCounter<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef { name, .. })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { name, .. })
+            | Stmt::ClassDef(ast::StmtClassDef { name, .. }) => self.bind(name.as_str()),
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                for alias in names {
+                    match &alias.asname {
+                        Some(asname) => self.bind(asname.as_str()),
+                        None => {
+                            self.bind(alias.name.as_str().split('.').next().unwrap_or_default())
+                        }
+                    }
+                }
+            }
+            Stmt::ImportFrom(ast::StmtImportFrom { names, .. }) => {
+                for alias in names {
+                    self.bind(alias.asname.as_ref().unwrap_or(&alias.name).as_str());
+                }
+            }
+            _ => {}
+        }
+        visitor::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let Expr::Name(ast::ExprName { id, ctx, .. }) = expr {
+            if matches!(ctx, ExprContext::Store | ExprContext::Del) {
+                self.bind(id.as_str());
+            }
+        }
+        visitor::walk_expr(self, expr);
+    }
+
+    fn visit_parameter(&mut self, parameter: &'a ast::Arg) {
+        self.bind(parameter.arg.as_str());
+        visitor::walk_parameter(self, parameter);
+    }
+
+    fn visit_except_handler(&mut self, except_handler: &'a ast::ExceptHandler) {
+        let ast::ExceptHandler::ExceptHandler(handler) = except_handler;
+        if let Some(name) = &handler.name {
+            self.bind(name.as_str());
+        }
+        visitor::walk_except_handler(self, except_handler);
+    }
+
+    fn visit_pattern(&mut self, pattern: &'a Pattern) {
+        match pattern {
+            Pattern::MatchAs(ast::PatternMatchAs {
+                name: Some(name), ..
+            })
+            | Pattern::MatchStar(ast::PatternMatchStar {
+                name: Some(name), ..
+            })
+            | Pattern::MatchMapping(ast::PatternMatchMapping {
+                rest: Some(name), ..
+            }) => self.bind(name.as_str()),
+            _ => {}
+        }
+        visitor::walk_pattern(self, pattern);
+    }
+}
+
+/// The signatures that a call to `symbol` has to match: its own if it's a function, or those of
+/// all alternatives if it's a union of functions.
+fn signatures(symbol: &Symbol) -> Vec<&Signature> {
+    match symbol {
+        Symbol::Function(signature) => vec![signature],
+        Symbol::Union(alternatives) => alternatives
+            .iter()
+            .map(|alternative| match alternative {
+                Symbol::Function(signature) => Some(signature),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .unwrap_or_default(),
+        _ => vec![],
+    }
+}
+
+struct CallChecker<'a> {
+    /// The names imported from other modules of the project, along with their symbols.
+    imported: FxHashMap<&'a str, &'a Symbol>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Visitor<'a> for CallChecker<'a> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let Expr::Call(ast::ExprCall {
+            func,
+            args,
+            keywords,
+            range,
+        }) = expr
+        {
+            if let Expr::Name(ast::ExprName { id, .. }) = func.as_ref() {
+                let signatures = self
+                    .imported
+                    .get(id.as_str())
+                    .map_or_else(Vec::new, |symbol| signatures(symbol));
+                // The number of arguments passed with `*args` or `**kwargs` isn't known.
+                let unpacked = args.iter().any(|arg| matches!(arg, Expr::Starred(_)))
+                    || keywords.iter().any(|keyword| keyword.arg.is_none());
+                if !signatures.is_empty() && !unpacked {
+                    let keywords = keywords
+                        .iter()
+                        .filter_map(|keyword| keyword.arg.as_ref().map(ast::Identifier::as_str))
+                        .collect::<Vec<_>>();
+                    if let Some(problem) = signatures
+                        .iter()
+                        .find_map(|signature| signature.check_call(args.len(), &keywords))
+                    {
+                        self.diagnostics.push(Diagnostic::new(
+                            CallArgument {
+                                name: id.as_str(),
+                                problem: &problem,
+                            },
+                            *range,
+                        ));
+                    }
+                }
+            }
+        }
+        visitor::walk_expr(self, expr);
+    }
+}
+
+/// Check the arguments of calls to functions imported from other modules of the project against
+/// their signatures.
+pub(crate) fn call_arguments(python_ast: &[Stmt], module: &ModuleContext) -> Vec<Diagnostic> {
+    let mut counter = BindingCounter::default();
+    counter.visit_body(python_ast);
+
+    let mut imported = FxHashMap::default();
+    for stmt in python_ast {
+        let Stmt::ImportFrom(ast::StmtImportFrom {
+            module: from,
+            names,
+            level,
+            ..
+        }) = stmt
+        else {
+            continue;
+        };
+        let Some(from) = module.name.resolve_import(
+            module.is_package,
+            level.map_or(0, |level| level.to_u32()),
+            from.as_ref().map(ast::Identifier::as_str),
+        ) else {
+            continue;
+        };
+        for alias in names {
+            let name = alias.asname.as_ref().unwrap_or(&alias.name).as_str();
+            if counter.bindings.get(name) != Some(&1) {
+                continue;
+            }
+            if let Some(symbol) = module.interfaces.symbol(&from, alias.name.as_str()) {
+                imported.insert(name, symbol);
+            }
+        }
+    }
+    if imported.is_empty() {
+        return vec![];
+    }
+
+    let mut checker = CallChecker {
+        imported,
+        diagnostics: vec![],
+    };
+    checker.visit_body(python_ast);
+    checker.diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrogen_module_resolver::ModuleName;
+    use pyrogen_source_file::Locator;
+    use rustpython_parser::{parse, Mode};
+
+    use crate::interface::{ModuleContext, ModuleInterface, ModuleInterfaces};
+    use crate::references::ProjectReferences;
+
+    use super::call_arguments;
+
+    #[test]
+    fn imported_signatures() {
+        let helpers = "def helper(a, b=1, *, key=None): ...\n";
+        let helpers_name = ModuleName::new("pkg.helpers").unwrap();
+        let mut interfaces = ModuleInterfaces::default();
+        interfaces.insert(
+            helpers_name.clone(),
+            ModuleInterface::from_ast(
+                &parse(helpers, Mode::Module, "<test>")
+                    .unwrap()
+                    .expect_module()
+                    .body,
+                &Locator::new(helpers),
+                &helpers_name,
+                false,
+            ),
+        );
+
+        let source = r#"
+from .helpers import helper
+from .helpers import helper as shadowed
+
+helper(1)
+helper(1, 2, 3)
+helper(1, key=2, other=3)
+helper(*args)
+
+def f(shadowed):
+    shadowed()
+"#;
+        let python_ast = parse(source, Mode::Module, "<test>")
+            .unwrap()
+            .expect_module()
+            .body;
+        let name = ModuleName::new("pkg.main").unwrap();
+        let diagnostics = call_arguments(
+            &python_ast,
+            &ModuleContext {
+                name: &name,
+                is_package: false,
+                interfaces: &interfaces,
+                references: &ProjectReferences::default(),
+            },
+        );
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.kind.body.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "Invalid arguments in call to `helper`: expected at most 2 positional arguments, got 3",
+                "Invalid arguments in call to `helper`: got an unexpected keyword argument `other`",
+            ]
+        );
+    }
+}
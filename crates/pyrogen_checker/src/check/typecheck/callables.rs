@@ -0,0 +1,718 @@
+//! Lambdas that are assigned or passed where a `Callable[[...], ...]` is expected.
+//!
+//! The parameters of such a lambda take their types from the `Callable` annotation (or from their
+//! literal defaults, for the parameters it doesn't pass), which is enough to infer the type of
+//! simple bodies like `lambda x: x + 1`. Only builtin scalar types (`int`, `str`, ...) are
+//! understood; anything else is treated as unknown and not reported.
+//!
+//! Lambdas are checked where they're the value of an annotated assignment, assigned to a name
+//! that is annotated in the same scope (like a parameter), the default of an annotated
+//! parameter, or an argument to a call of a function defined once at the top level of the module.
+
+use rustc_hash::FxHashMap;
+use rustpython_ast::{self as ast, Constant, Expr, Operator, Ranged, Stmt, UnaryOp};
+
+use pyrogen_python_ast::visitor::{self, Visitor};
+use pyrogen_source_file::Locator;
+
+use crate::registry::{Diagnostic, DiagnosticKind};
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = IncompatibleCallable, message = "unsupported-operand-types")]
+struct UnsupportedOperandTypes {
+    operator: &'static str,
+    left: &'static str,
+    right: &'static str,
+}
+
+#[derive(Violation)]
+#[violation(code = IncompatibleCallable, message = "lambda-positional-argument")]
+struct LambdaPositionalArgument<'a> {
+    count: usize,
+    annotation: &'a str,
+}
+
+#[derive(Violation)]
+#[violation(code = IncompatibleCallable, message = "lambda-positional-arguments")]
+struct LambdaPositionalArguments<'a> {
+    count: usize,
+    annotation: &'a str,
+}
+
+#[derive(Violation)]
+#[violation(code = IncompatibleCallable, message = "lambda-return-type")]
+struct LambdaReturnType<'a> {
+    returns: &'static str,
+    annotation: &'a str,
+    expected: &'static str,
+}
+
+/// The builtin types that lambda bodies are inferred as.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Builtin {
+    Bool,
+    Int,
+    Float,
+    Complex,
+    Str,
+    Bytes,
+    None,
+}
+
+impl Builtin {
+    fn from_annotation(annotation: &Expr) -> Option<Self> {
+        match annotation {
+            Expr::Name(ast::ExprName { id, .. }) => match id.as_str() {
+                "bool" => Some(Self::Bool),
+                "int" => Some(Self::Int),
+                "float" => Some(Self::Float),
+                "complex" => Some(Self::Complex),
+                "str" => Some(Self::Str),
+                "bytes" => Some(Self::Bytes),
+                _ => None,
+            },
+            Expr::Constant(ast::ExprConstant {
+                value: Constant::None,
+                ..
+            }) => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    fn from_constant(constant: &Constant) -> Option<Self> {
+        match constant {
+            Constant::Bool(_) => Some(Self::Bool),
+            Constant::Int(_) => Some(Self::Int),
+            Constant::Float(_) => Some(Self::Float),
+            Constant::Complex { .. } => Some(Self::Complex),
+            Constant::Str(_) => Some(Self::Str),
+            Constant::Bytes(_) => Some(Self::Bytes),
+            Constant::None => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Bool => "bool",
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::Complex => "complex",
+            Self::Str => "str",
+            Self::Bytes => "bytes",
+            Self::None => "None",
+        }
+    }
+
+    /// The position of a numeric type in the `bool` < `int` < `float` < `complex` promotion
+    /// order.
+    const fn numeric_rank(self) -> Option<u8> {
+        match self {
+            Self::Bool => Some(0),
+            Self::Int => Some(1),
+            Self::Float => Some(2),
+            Self::Complex => Some(3),
+            Self::Str | Self::Bytes | Self::None => None,
+        }
+    }
+
+    fn from_numeric_rank(rank: u8) -> Self {
+        match rank {
+            0 => Self::Bool,
+            1 => Self::Int,
+            2 => Self::Float,
+            _ => Self::Complex,
+        }
+    }
+
+    /// Whether a value of type `self` can be used where `expected` is expected, following the
+    /// numeric promotions of PEP 484.
+    fn is_assignable_to(self, expected: Self) -> bool {
+        match (self.numeric_rank(), expected.numeric_rank()) {
+            (Some(actual), Some(expected)) => actual <= expected,
+            _ => self == expected,
+        }
+    }
+}
+
+/// A `Callable[[...], ...]` annotation.
+struct CallableType {
+    /// The types of the positional parameters, or `None` for `Callable[..., T]`.
+    parameters: Option<Vec<Option<Builtin>>>,
+    returns: Option<Builtin>,
+}
+
+const fn operator_symbol(op: Operator) -> &'static str {
+    match op {
+        Operator::Add => "+",
+        Operator::Sub => "-",
+        Operator::Mult => "*",
+        Operator::MatMult => "@",
+        Operator::Div => "/",
+        Operator::Mod => "%",
+        Operator::Pow => "**",
+        Operator::LShift => "<<",
+        Operator::RShift => ">>",
+        Operator::BitOr => "|",
+        Operator::BitXor => "^",
+        Operator::BitAnd => "&",
+        Operator::FloorDiv => "//",
+    }
+}
+
+/// The result of applying `op` to operands of the given types. `Err(())` if the operation is
+/// unsupported, `Ok(None)` if the result isn't known.
+fn binary_operation(left: Builtin, op: Operator, right: Builtin) -> Result<Option<Builtin>, ()> {
+    if let (Some(left), Some(right)) = (left.numeric_rank(), right.numeric_rank()) {
+        return match op {
+            Operator::Add
+            | Operator::Sub
+            | Operator::Mult
+            | Operator::Pow
+            | Operator::Mod
+            | Operator::FloorDiv => Ok(Some(Builtin::from_numeric_rank(left.max(right).max(1)))),
+            Operator::Div => Ok(Some(Builtin::from_numeric_rank(left.max(right).max(2)))),
+            _ => Ok(None),
+        };
+    }
+    match (left, op, right) {
+        (Builtin::Str, Operator::Add, Builtin::Str) => Ok(Some(Builtin::Str)),
+        (Builtin::Bytes, Operator::Add, Builtin::Bytes) => Ok(Some(Builtin::Bytes)),
+        (Builtin::Str | Builtin::Bytes, Operator::Mult, Builtin::Int | Builtin::Bool) => {
+            Ok(Some(left))
+        }
+        (Builtin::Int | Builtin::Bool, Operator::Mult, Builtin::Str | Builtin::Bytes) => {
+            Ok(Some(right))
+        }
+        // `%`-formatting.
+        (Builtin::Str | Builtin::Bytes, Operator::Mod, _) => Ok(Some(left)),
+        (
+            _,
+            Operator::Add
+            | Operator::Sub
+            | Operator::Mult
+            | Operator::Div
+            | Operator::FloorDiv
+            | Operator::Mod
+            | Operator::Pow,
+            _,
+        ) => Err(()),
+        _ => Ok(None),
+    }
+}
+
+/// Infers the types of expressions in the body of a lambda, from the types of its parameters.
+struct LambdaInference<'a> {
+    parameters: FxHashMap<&'a str, Builtin>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> LambdaInference<'a> {
+    fn infer(&mut self, expr: &'a Expr) -> Option<Builtin> {
+        match expr {
+            Expr::Constant(ast::ExprConstant { value, .. }) => Builtin::from_constant(value),
+            Expr::Name(ast::ExprName { id, .. }) => self.parameters.get(id.as_str()).copied(),
+            Expr::BinOp(ast::ExprBinOp {
+                left,
+                op,
+                right,
+                range,
+            }) => {
+                let (left, right) = (self.infer(left)?, self.infer(right)?);
+                binary_operation(left, *op, right).unwrap_or_else(|()| {
+                    self.diagnostics.push(Diagnostic::new(
+                        UnsupportedOperandTypes {
+                            operator: operator_symbol(*op),
+                            left: left.name(),
+                            right: right.name(),
+                        },
+                        *range,
+                    ));
+                    None
+                })
+            }
+            Expr::UnaryOp(ast::ExprUnaryOp {
+                op: UnaryOp::Not,
+                operand,
+                ..
+            }) => {
+                self.infer(operand);
+                Some(Builtin::Bool)
+            }
+            Expr::Compare(ast::ExprCompare {
+                left, comparators, ..
+            }) => {
+                self.infer(left);
+                for comparator in comparators {
+                    self.infer(comparator);
+                }
+                Some(Builtin::Bool)
+            }
+            Expr::UnaryOp(ast::ExprUnaryOp {
+                op: UnaryOp::USub | UnaryOp::UAdd,
+                operand,
+                ..
+            }) => {
+                let operand = self.infer(operand)?;
+                operand
+                    .numeric_rank()
+                    .map(|rank| Builtin::from_numeric_rank(rank.max(1)))
+            }
+            Expr::IfExp(ast::ExprIfExp {
+                test, body, orelse, ..
+            }) => {
+                self.infer(test);
+                let (body, orelse) = (self.infer(body), self.infer(orelse));
+                (body == orelse).then_some(body).flatten()
+            }
+            _ => None,
+        }
+    }
+}
+
+struct CallableChecker<'a> {
+    locator: &'a Locator<'a>,
+    /// The qualified names of the imported modules and members, by the name they're bound to.
+    imports: FxHashMap<&'a str, String>,
+    /// The functions defined (once) at the top level of the module.
+    functions: FxHashMap<&'a str, &'a ast::Arguments>,
+    /// The annotations of the names declared in each enclosing scope, innermost last.
+    declarations: Vec<FxHashMap<&'a str, &'a Expr>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> CallableChecker<'a> {
+    fn qualified_name(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Name(ast::ExprName { id, .. }) => self.imports.get(id.as_str()).cloned(),
+            Expr::Attribute(ast::ExprAttribute { value, attr, .. }) => {
+                Some(format!("{}.{}", self.qualified_name(value)?, attr.as_str()))
+            }
+            _ => None,
+        }
+    }
+
+    fn callable_type(&self, annotation: &Expr) -> Option<CallableType> {
+        let Expr::Subscript(ast::ExprSubscript { value, slice, .. }) = annotation else {
+            return None;
+        };
+        if !matches!(
+            self.qualified_name(value).as_deref(),
+            Some("typing.Callable" | "typing_extensions.Callable" | "collections.abc.Callable")
+        ) {
+            return None;
+        }
+        let Expr::Tuple(ast::ExprTuple { elts, .. }) = slice.as_ref() else {
+            return None;
+        };
+        let [parameters, returns] = elts.as_slice() else {
+            return None;
+        };
+        let parameters = match parameters {
+            Expr::List(ast::ExprList { elts, .. }) => {
+                Some(elts.iter().map(Builtin::from_annotation).collect())
+            }
+            Expr::Constant(ast::ExprConstant {
+                value: Constant::Ellipsis,
+                ..
+            }) => None,
+            _ => return None,
+        };
+        Some(CallableType {
+            parameters,
+            returns: Builtin::from_annotation(returns),
+        })
+    }
+
+    /// Check `value`, if it's a lambda, against `annotation`, if it's a `Callable`.
+    fn check(&mut self, annotation: &Expr, value: &'a Expr) {
+        let Expr::Lambda(ast::ExprLambda { args, body, range }) = value else {
+            return;
+        };
+        let Some(callable) = self.callable_type(annotation) else {
+            return;
+        };
+        let annotation = self.locator.slice(annotation.range());
+
+        let positional = args
+            .posonlyargs
+            .iter()
+            .chain(&args.args)
+            .collect::<Vec<_>>();
+        let mut parameters = FxHashMap::default();
+        if let Some(expected) = &callable.parameters {
+            let required = positional
+                .iter()
+                .filter(|parameter| parameter.default.is_none())
+                .count();
+            let accepts = expected.len() >= required
+                && (expected.len() <= positional.len() || args.vararg.is_some())
+                && args
+                    .kwonlyargs
+                    .iter()
+                    .all(|parameter| parameter.default.is_some());
+            if !accepts {
+                let count = expected.len();
+                let kind: DiagnosticKind = if count == 1 {
+                    LambdaPositionalArgument { count, annotation }.into()
+                } else {
+                    LambdaPositionalArguments { count, annotation }.into()
+                };
+                self.diagnostics.push(Diagnostic::new(kind, *range));
+                return;
+            }
+            for (parameter, expected) in positional.iter().zip(expected) {
+                if let Some(expected) = expected {
+                    parameters.insert(parameter.def.arg.as_str(), *expected);
+                }
+            }
+            // The parameters that aren't passed keep their default, which is evaluated in the
+            // enclosing scope.
+            for parameter in positional
+                .iter()
+                .skip(expected.len())
+                .copied()
+                .chain(&args.kwonlyargs)
+            {
+                if let Some(Expr::Constant(ast::ExprConstant { value, .. })) =
+                    parameter.default.as_deref()
+                {
+                    if let Some(default) = Builtin::from_constant(value) {
+                        parameters.insert(parameter.def.arg.as_str(), default);
+                    }
+                }
+            }
+        }
+
+        let mut inference = LambdaInference {
+            parameters,
+            diagnostics: vec![],
+        };
+        let returns = inference.infer(body);
+        self.diagnostics.append(&mut inference.diagnostics);
+        // Any return value is accepted where `None` is expected, as in callbacks whose result is
+        // discarded.
+        if let (Some(returns), Some(expected)) = (returns, callable.returns) {
+            if expected != Builtin::None && !returns.is_assignable_to(expected) {
+                self.diagnostics.push(Diagnostic::new(
+                    LambdaReturnType {
+                        returns: returns.name(),
+                        annotation,
+                        expected: expected.name(),
+                    },
+                    body.range(),
+                ));
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &'a str, annotation: &'a Expr) {
+        if let Some(declarations) = self.declarations.last_mut() {
+            declarations.insert(name, annotation);
+        }
+    }
+
+    fn check_parameter_defaults(&mut self, arguments: &'a ast::Arguments) {
+        for parameter in arguments
+            .posonlyargs
+            .iter()
+            .chain(&arguments.args)
+            .chain(&arguments.kwonlyargs)
+        {
+            if let (Some(annotation), Some(default)) =
+                (&parameter.def.annotation, &parameter.default)
+            {
+                self.check(annotation, default);
+            }
+        }
+    }
+
+    fn check_call(
+        &mut self,
+        parameters: &'a ast::Arguments,
+        args: &'a [Expr],
+        keywords: &'a [ast::Keyword],
+    ) {
+        let positional = parameters
+            .posonlyargs
+            .iter()
+            .chain(&parameters.args)
+            .collect::<Vec<_>>();
+        // The parameters after a `*args` argument aren't known.
+        for (arg, parameter) in args
+            .iter()
+            .take_while(|arg| !matches!(arg, Expr::Starred(_)))
+            .zip(&positional)
+        {
+            if let Some(annotation) = &parameter.def.annotation {
+                self.check(annotation, arg);
+            }
+        }
+        for keyword in keywords {
+            let Some(name) = &keyword.arg else {
+                continue;
+            };
+            let parameter = parameters
+                .args
+                .iter()
+                .chain(&parameters.kwonlyargs)
+                .find(|parameter| parameter.def.arg.as_str() == name.as_str());
+            if let Some(annotation) =
+                parameter.and_then(|parameter| parameter.def.annotation.as_ref())
+            {
+                self.check(annotation, &keyword.value);
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for CallableChecker<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::AnnAssign(ast::StmtAnnAssign {
+                target,
+                annotation,
+                value,
+                ..
+            }) => {
+                if let Expr::Name(ast::ExprName { id, .. }) = target.as_ref() {
+                    self.declare(id.as_str(), annotation);
+                }
+                if let Some(value) = value {
+                    self.check(annotation, value);
+                }
+            }
+            Stmt::Assign(ast::StmtAssign { targets, value, .. }) => {
+                for target in targets {
+                    let Expr::Name(ast::ExprName { id, .. }) = target else {
+                        continue;
+                    };
+                    let declared = self
+                        .declarations
+                        .last()
+                        .and_then(|declarations| declarations.get(id.as_str()).copied());
+                    if let Some(annotation) = declared {
+                        self.check(annotation, value);
+                    }
+                }
+            }
+            Stmt::FunctionDef(ast::StmtFunctionDef { args, .. })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { args, .. }) => {
+                self.check_parameter_defaults(args);
+                let declarations = args
+                    .posonlyargs
+                    .iter()
+                    .chain(&args.args)
+                    .chain(&args.kwonlyargs)
+                    .filter_map(|parameter| {
+                        let annotation = parameter.def.annotation.as_deref()?;
+                        Some((parameter.def.arg.as_str(), annotation))
+                    })
+                    .collect();
+                self.declarations.push(declarations);
+                visitor::walk_stmt(self, stmt);
+                self.declarations.pop();
+                return;
+            }
+            Stmt::ClassDef(_) => {
+                self.declarations.push(FxHashMap::default());
+                visitor::walk_stmt(self, stmt);
+                self.declarations.pop();
+                return;
+            }
+            _ => {}
+        }
+        visitor::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let Expr::Call(ast::ExprCall {
+            func,
+            args,
+            keywords,
+            ..
+        }) = expr
+        {
+            if let Expr::Name(ast::ExprName { id, .. }) = func.as_ref() {
+                if let Some(parameters) = self.functions.get(id.as_str()).copied() {
+                    self.check_call(parameters, args, keywords);
+                }
+            }
+        }
+        visitor::walk_expr(self, expr);
+    }
+}
+
+/// Collects the names bound by imports, in any scope.
+#[derive(Default)]
+struct ImportCollector<'a> {
+    imports: FxHashMap<&'a str, String>,
+}
+
+impl<'a> Visitor<'a> for ImportCollector<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                for alias in names {
+                    match &alias.asname {
+                        Some(asname) => {
+                            self.imports.insert(asname.as_str(), alias.name.to_string());
+                        }
+                        None => {
+                            let name = alias.name.as_str().split('.').next().unwrap_or_default();
+                            self.imports.insert(name, name.to_string());
+                        }
+                    }
+                }
+            }
+            Stmt::ImportFrom(ast::StmtImportFrom {
+                module: Some(module),
+                names,
+                level,
+                ..
+            }) if level.map_or(0, |level| level.to_u32()) == 0 => {
+                for alias in names {
+                    let name = alias.asname.as_ref().unwrap_or(&alias.name);
+                    self.imports.insert(
+                        name.as_str(),
+                        format!("{}.{}", module.as_str(), alias.name.as_str()),
+                    );
+                }
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+}
+
+/// Check lambdas against the `Callable` types they're assigned or passed to.
+pub(crate) fn callables(python_ast: &[Stmt], locator: &Locator) -> Vec<Diagnostic> {
+    let mut collector = ImportCollector::default();
+    collector.visit_body(python_ast);
+
+    let mut functions = FxHashMap::default();
+    let mut definitions = FxHashMap::<&str, usize>::default();
+    for stmt in python_ast {
+        if let Stmt::FunctionDef(ast::StmtFunctionDef {
+            name,
+            args,
+            decorator_list,
+            ..
+        })
+        | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+            name,
+            args,
+            decorator_list,
+            ..
+        }) = stmt
+        {
+            *definitions.entry(name.as_str()).or_default() += 1;
+            if decorator_list.is_empty() {
+                functions.insert(name.as_str(), args.as_ref());
+            }
+        }
+    }
+    functions.retain(|name, _| definitions.get(name) == Some(&1));
+
+    let mut checker = CallableChecker {
+        locator,
+        imports: collector.imports,
+        functions,
+        declarations: vec![FxHashMap::default()],
+        diagnostics: vec![],
+    };
+    checker.visit_body(python_ast);
+    checker.diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrogen_source_file::Locator;
+    use rustpython_parser::{parse, Mode};
+
+    use super::callables;
+
+    fn check(source: &str) -> Vec<String> {
+        let python_ast = parse(source, Mode::Module, "<test>")
+            .unwrap()
+            .expect_module()
+            .body;
+        callables(&python_ast, &Locator::new(source))
+            .into_iter()
+            .map(|diagnostic| diagnostic.kind.body)
+            .collect()
+    }
+
+    #[test]
+    fn annotated_lambdas() {
+        let source = r#"
+from typing import Callable
+import collections.abc
+
+ok: Callable[[int, str], bool] = lambda x, s: x > 0
+promoted: Callable[[int], float] = lambda x: x * 2
+anything: Callable[..., int] = lambda *args: 1
+arity: Callable[[int, str], bool] = lambda x: True
+returns: collections.abc.Callable[[int], str] = lambda x: x + 1
+operands: Callable[[int], int] = lambda x: x + "a"
+discarded: Callable[[str], None] = lambda s: s * 2
+"#;
+        assert_eq!(
+            check(source),
+            [
+                "Lambda can't be called with 2 positional arguments, as `Callable[[int, str], bool]` expects",
+                "Lambda returns `int`, but `collections.abc.Callable[[int], str]` expects `str`",
+                "Unsupported operand types for +: `int` and `str`",
+            ]
+        );
+    }
+
+    #[test]
+    fn declared_names_and_defaults() {
+        let source = r#"
+from typing import Callable
+
+handler: Callable[[int], str]
+handler = lambda x: x * 2
+handler = lambda x, suffix="!": str(x) + suffix
+handler = lambda x, *, scale=1.5: x * scale
+
+def register(callback: Callable[[], int]) -> None:
+    callback = lambda: "none"
+
+class Config:
+    handler = lambda x: x
+"#;
+        assert_eq!(
+            check(source),
+            [
+                "Lambda returns `int`, but `Callable[[int], str]` expects `str`",
+                "Lambda returns `float`, but `Callable[[int], str]` expects `str`",
+                "Lambda returns `str`, but `Callable[[], int]` expects `int`",
+            ]
+        );
+    }
+
+    #[test]
+    fn lambda_arguments() {
+        let source = r#"
+import typing as t
+
+def apply(f: t.Callable[[str], str], value: str) -> str:
+    return f(value)
+
+def apply_default(f: t.Callable[[int], int] = lambda x: -x) -> int: ...
+
+apply(lambda s: s.upper(), "a")
+apply(lambda s: s + 1, "a")
+apply(value="a", f=lambda: "b")
+"#;
+        assert_eq!(
+            check(source),
+            [
+                "Unsupported operand types for +: `str` and `int`",
+                "Lambda can't be called with 1 positional argument, as `t.Callable[[str], str]` expects",
+            ]
+        );
+    }
+}
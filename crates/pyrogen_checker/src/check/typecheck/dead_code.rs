@@ -0,0 +1,37 @@
+//! Top-level functions and classes that are never used in the project.
+
+use crate::definitions::{Definition, DefinitionKind};
+use crate::interface::ModuleContext;
+use crate::registry::{Diagnostic, DiagnosticKind};
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = DeadCode, message = "dead-class")]
+struct DeadClass<'a> {
+    name: &'a str,
+}
+
+#[derive(Violation)]
+#[violation(code = DeadCode, message = "dead-function")]
+struct DeadFunction<'a> {
+    name: &'a str,
+}
+
+fn dead_code(definition: &Definition) -> DiagnosticKind {
+    let name = definition.qualified_name.as_str();
+    match definition.kind {
+        DefinitionKind::Class => DeadClass { name }.into(),
+        _ => DeadFunction { name }.into(),
+    }
+}
+
+/// Report the top-level functions and classes of `module` that are never used (see
+/// [`ProjectReferences`](crate::references::ProjectReferences)).
+pub(crate) fn unreferenced_definitions(module: &ModuleContext) -> Vec<Diagnostic> {
+    module
+        .references
+        .unreferenced(module.name)
+        .iter()
+        .map(|definition| Diagnostic::new(dead_code(definition), definition.range))
+        .collect()
+}
@@ -0,0 +1,326 @@
+//! Checks for the names that a module exports through `__all__`.
+//!
+//! `__all__` has to be a literal list or tuple of strings for the names to be known statically.
+//! It may be extended with `__all__ += [...]`, `__all__.extend([...])` and
+//! `__all__.append("...")`.
+
+use std::path::Path;
+
+use rustc_hash::FxHashSet;
+use rustpython_ast::{self as ast, Constant, Expr, ExprContext, Operator, Pattern, Ranged, Stmt};
+use rustpython_parser::text_size::TextRange;
+
+use pyrogen_python_ast::visitor::{self, Visitor};
+
+use crate::registry::Diagnostic;
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = UndefinedExport)]
+struct UndefinedExport<'a> {
+    name: &'a str,
+}
+
+#[derive(Violation)]
+#[violation(code = InvalidAll)]
+struct InvalidAll;
+
+#[derive(Violation)]
+#[violation(code = PrivateExport)]
+struct PrivateExport<'a> {
+    name: &'a str,
+}
+
+/// Collects the names bound at the top level of a module, and the contents of `__all__`.
+#[derive(Default)]
+pub(crate) struct ExportCollector<'a> {
+    bindings: FxHashSet<&'a str>,
+    /// Whether the module contains a `from ... import *`, which binds unknown names.
+    star_import: bool,
+    exports: Vec<(&'a str, TextRange)>,
+    /// The values assigned to (or added to) `__all__` that aren't literal lists of strings.
+    invalid: Vec<TextRange>,
+}
+
+impl<'a> ExportCollector<'a> {
+    fn add_exports(&mut self, value: &'a Expr) {
+        let (Expr::List(ast::ExprList { elts, .. }) | Expr::Tuple(ast::ExprTuple { elts, .. })) =
+            value
+        else {
+            self.invalid.push(value.range());
+            return;
+        };
+        for elt in elts {
+            self.add_export(elt);
+        }
+    }
+
+    fn add_export(&mut self, value: &'a Expr) {
+        match value {
+            Expr::Constant(ast::ExprConstant {
+                value: Constant::Str(name),
+                range,
+                ..
+            }) => self.exports.push((name.as_str(), *range)),
+            _ => self.invalid.push(value.range()),
+        }
+    }
+}
+
+fn is_dunder_all(expr: &Expr) -> bool {
+    matches!(expr, Expr::Name(ast::ExprName { id, .. }) if id.as_str() == "__all__")
+}
+
+/// Match a call `__all__.method(arg)`, returning the name of the method and the argument.
+fn dunder_all_method_call(expr: &Expr) -> Option<(&str, &Expr)> {
+    let Expr::Call(ast::ExprCall {
+        func,
+        args,
+        keywords,
+        ..
+    }) = expr
+    else {
+        return None;
+    };
+    let Expr::Attribute(ast::ExprAttribute { value, attr, .. }) = func.as_ref() else {
+        return None;
+    };
+    match args.as_slice() {
+        [arg] if keywords.is_empty() && is_dunder_all(value) => Some((attr.as_str(), arg)),
+        _ => None,
+    }
+}
+
+impl<'a> Visitor<'a> for ExportCollector<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            // Don't descend into functions and classes, whose bindings are local to them.
+            Stmt::FunctionDef(ast::StmtFunctionDef {
+                name,
+                decorator_list,
+                ..
+            })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+                name,
+                decorator_list,
+                ..
+            })
+            | Stmt::ClassDef(ast::StmtClassDef {
+                name,
+                decorator_list,
+                ..
+            }) => {
+                self.bindings.insert(name.as_str());
+                for decorator in decorator_list {
+                    self.visit_decorator(decorator);
+                }
+            }
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                for alias in names {
+                    self.bindings.insert(match &alias.asname {
+                        Some(asname) => asname.as_str(),
+                        // `import a.b` binds `a`.
+                        None => alias.name.as_str().split('.').next().unwrap_or_default(),
+                    });
+                }
+            }
+            Stmt::ImportFrom(ast::StmtImportFrom { names, .. }) => {
+                for alias in names {
+                    if alias.name.as_str() == "*" {
+                        self.star_import = true;
+                    } else {
+                        self.bindings
+                            .insert(alias.asname.as_ref().unwrap_or(&alias.name).as_str());
+                    }
+                }
+            }
+            Stmt::Assign(ast::StmtAssign { targets, value, .. })
+                if targets.iter().any(is_dunder_all) =>
+            {
+                self.bindings.insert("__all__");
+                self.add_exports(value);
+            }
+            Stmt::AnnAssign(ast::StmtAnnAssign {
+                target,
+                value: Some(value),
+                ..
+            }) if is_dunder_all(target) => {
+                self.bindings.insert("__all__");
+                self.add_exports(value);
+            }
+            Stmt::AugAssign(ast::StmtAugAssign {
+                target,
+                op: Operator::Add,
+                value,
+                ..
+            }) if is_dunder_all(target) => self.add_exports(value),
+            Stmt::Expr(ast::StmtExpr { value, .. }) => match dunder_all_method_call(value) {
+                Some(("extend", arg)) => self.add_exports(arg),
+                Some(("append", arg)) => self.add_export(arg),
+                _ => visitor::walk_stmt(self, stmt),
+            },
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::Name(ast::ExprName {
+                id,
+                ctx: ExprContext::Store,
+                ..
+            }) => {
+                self.bindings.insert(id.as_str());
+            }
+            // Lambda bodies have their own scope.
+            Expr::Lambda(_) => {}
+            _ => visitor::walk_expr(self, expr),
+        }
+    }
+
+    fn visit_except_handler(&mut self, except_handler: &'a ast::ExceptHandler) {
+        let ast::ExceptHandler::ExceptHandler(handler) = except_handler;
+        if let Some(name) = &handler.name {
+            self.bindings.insert(name.as_str());
+        }
+        visitor::walk_except_handler(self, except_handler);
+    }
+
+    fn visit_pattern(&mut self, pattern: &'a Pattern) {
+        match pattern {
+            Pattern::MatchAs(ast::PatternMatchAs {
+                name: Some(name), ..
+            })
+            | Pattern::MatchStar(ast::PatternMatchStar {
+                name: Some(name), ..
+            })
+            | Pattern::MatchMapping(ast::PatternMatchMapping {
+                rest: Some(name), ..
+            }) => {
+                self.bindings.insert(name.as_str());
+            }
+            _ => {}
+        }
+        visitor::walk_pattern(self, pattern);
+    }
+}
+
+/// Returns `true` if `name` is a submodule of the package whose `__init__` is at `path`.
+fn is_submodule(path: &Path, name: &str) -> bool {
+    let Some(directory) = path.parent() else {
+        return false;
+    };
+    directory.join(name).is_dir()
+        || directory.join(format!("{name}.py")).is_file()
+        || directory.join(format!("{name}.pyi")).is_file()
+}
+
+/// Collect the top-level bindings and the contents of `__all__`, which the checks of `__all__`
+/// share.
+pub(crate) fn collect_exports(python_ast: &[Stmt]) -> ExportCollector<'_> {
+    let mut collector = ExportCollector::default();
+    collector.visit_body(python_ast);
+    collector
+}
+
+/// Check that `__all__` is a literal list of strings.
+pub(crate) fn invalid_all(exports: &ExportCollector) -> Vec<Diagnostic> {
+    exports
+        .invalid
+        .iter()
+        .map(|range| Diagnostic::new(InvalidAll, *range))
+        .collect()
+}
+
+/// Check that the names that `__all__` lists are defined in the module at `path`.
+pub(crate) fn undefined_exports(exports: &ExportCollector, path: &Path) -> Vec<Diagnostic> {
+    // A star import or a module-level `__getattr__` can make any name available.
+    if exports.star_import || exports.bindings.contains("__getattr__") {
+        return vec![];
+    }
+    let is_package = path.file_stem().is_some_and(|stem| stem == "__init__");
+    exports
+        .exports
+        .iter()
+        .filter(|(name, _)| {
+            !exports.bindings.contains(name) && !(is_package && is_submodule(path, name))
+        })
+        .map(|(name, range)| Diagnostic::new(UndefinedExport { name }, *range))
+        .collect()
+}
+
+/// Check that the names that `__all__` lists aren't private.
+pub(crate) fn private_exports(exports: &ExportCollector) -> Vec<Diagnostic> {
+    exports
+        .exports
+        .iter()
+        .filter(|(name, _)| name.starts_with('_') && !name.ends_with("__"))
+        .map(|(name, range)| Diagnostic::new(PrivateExport { name }, *range))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use rustpython_parser::{parse, Mode};
+
+    use super::{collect_exports, invalid_all, private_exports, undefined_exports};
+
+    fn check(source: &str) -> Vec<String> {
+        let python_ast = parse(source, Mode::Module, "<test>")
+            .unwrap()
+            .expect_module()
+            .body;
+        let exports = collect_exports(&python_ast);
+        let mut diagnostics = invalid_all(&exports);
+        diagnostics.extend(undefined_exports(&exports, Path::new("module.py")));
+        diagnostics.extend(private_exports(&exports));
+        diagnostics.sort_by_key(|diagnostic| diagnostic.range.start());
+        diagnostics
+            .into_iter()
+            .map(|diagnostic| diagnostic.kind.body)
+            .collect()
+    }
+
+    #[test]
+    fn exports() {
+        let source = r#"
+import os.path
+from typing import List as L
+
+def f(): ...
+
+class C: ...
+
+if True:
+    x = 1
+else:
+    (y, z) = 2, 3
+
+__all__ = ["os", "L", "f", "C", "x", "y", "missing"]
+__all__ += ("z", "_private", "__version__")
+__all__.append("g")
+__all__.extend(["h"])
+__all__ += other.__all__
+__all__.append(name)
+"#;
+        assert_eq!(
+            check(source),
+            [
+                "`missing` is listed in `__all__` but not defined in the module",
+                "Private name `_private` is listed in `__all__`",
+                "`__version__` is listed in `__all__` but not defined in the module",
+                "`g` is listed in `__all__` but not defined in the module",
+                "`h` is listed in `__all__` but not defined in the module",
+                "`__all__` should be a literal list or tuple of strings",
+                "`__all__` should be a literal list or tuple of strings",
+            ]
+        );
+    }
+
+    #[test]
+    fn star_imports() {
+        assert!(check("from os import *\n__all__ = ['anything']\n").is_empty());
+    }
+}
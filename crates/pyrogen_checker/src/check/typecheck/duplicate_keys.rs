@@ -0,0 +1,168 @@
+//! Literal keys that occur more than once in a dict display, and literal elements that occur more
+//! than once in a set display. At runtime, the later entry silently replaces the earlier one,
+//! which is rarely intended.
+//!
+//! Only constants (and tuples of constants) are compared. They're compared the way Python does,
+//! so `1`, `1.0` and `True` are the same key.
+
+use rustc_hash::FxHashSet;
+use rustpython_ast::{self as ast, Constant, Expr, Ranged, Stmt};
+
+use pyrogen_python_ast::visitor::{self, Visitor};
+use pyrogen_source_file::Locator;
+
+use crate::registry::{Diagnostic, DiagnosticKind};
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = DuplicateKey)]
+struct DuplicateKey<'a> {
+    key: &'a str,
+}
+
+#[derive(Violation)]
+#[violation(code = DuplicateKey, message = "duplicate-set-element")]
+struct DuplicateElement<'a> {
+    element: &'a str,
+}
+
+/// A representation of a literal that is equal for literals that compare (and hash) equal at
+/// runtime.
+fn literal_key(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Constant(ast::ExprConstant { value, .. }) => match value {
+            Constant::Str(value) => Some(format!("str:{value}")),
+            Constant::Bytes(value) => Some(format!("bytes:{value:?}")),
+            Constant::Int(value) => Some(format!("int:{value}")),
+            Constant::Bool(value) => Some(format!("int:{}", u8::from(*value))),
+            // Integral floats are equal to the corresponding integers.
+            #[allow(clippy::cast_possible_truncation)]
+            Constant::Float(value) if value.fract() == 0.0 && value.abs() < 1e15 => {
+                Some(format!("int:{}", *value as i64))
+            }
+            // `nan` is never equal to itself.
+            Constant::Float(value) if !value.is_nan() => Some(format!("float:{value}")),
+            Constant::None => Some("None".to_string()),
+            Constant::Ellipsis => Some("...".to_string()),
+            _ => None,
+        },
+        Expr::Tuple(ast::ExprTuple { elts, .. }) => {
+            let elts = elts.iter().map(literal_key).collect::<Option<Vec<_>>>()?;
+            Some(format!("tuple:({})", elts.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+struct DuplicateKeyChecker<'a> {
+    locator: &'a Locator<'a>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DuplicateKeyChecker<'_> {
+    /// Report the entries of `entries` that repeat an earlier one.
+    fn check<'b>(
+        &mut self,
+        entries: impl Iterator<Item = &'b Expr>,
+        kind: fn(&str) -> DiagnosticKind,
+    ) {
+        let mut seen = FxHashSet::default();
+        for entry in entries {
+            if let Some(key) = literal_key(entry) {
+                if !seen.insert(key) {
+                    self.diagnostics.push(Diagnostic::new(
+                        kind(self.locator.slice(entry.range())),
+                        entry.range(),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for DuplicateKeyChecker<'_> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match expr {
+            // `None` keys are `**mapping` unpackings.
+            Expr::Dict(ast::ExprDict { keys, .. }) => {
+                self.check(keys.iter().flatten(), |key| DuplicateKey { key }.into());
+            }
+            Expr::Set(ast::ExprSet { elts, .. }) => {
+                self.check(elts.iter(), |element| DuplicateElement { element }.into());
+            }
+            _ => {}
+        }
+        visitor::walk_expr(self, expr);
+    }
+}
+
+/// Report the repeated literal keys of dict displays and elements of set displays.
+pub(crate) fn duplicate_keys(python_ast: &[Stmt], locator: &Locator) -> Vec<Diagnostic> {
+    let mut checker = DuplicateKeyChecker {
+        locator,
+        diagnostics: vec![],
+    };
+    checker.visit_body(python_ast);
+    checker.diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrogen_source_file::Locator;
+    use rustpython_parser::{parse, Mode};
+
+    use super::duplicate_keys;
+
+    #[test]
+    fn duplicates() {
+        let source = r#"
+config = {
+    "name": 1,
+    'name': 2,
+    1: "one",
+    True: "true",
+    1.0: "float",
+    (1, "a"): None,
+    (1, "a"): None,
+    **other,
+    **other,
+    name: 1,
+    name: 2,
+}
+
+flags = {1, 2, 1, float("nan"), float("nan")}
+
+def f():
+    return {b"x": {"nested": 1, "nested": 2}, b"x": 0}
+"#;
+        let python_ast = parse(source, Mode::Module, "<test>")
+            .unwrap()
+            .expect_module()
+            .body;
+        let locator = Locator::new(source);
+        let diagnostics = duplicate_keys(&python_ast, &locator);
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.kind.body.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "Dictionary key `'name'` is repeated",
+                "Dictionary key `True` is repeated",
+                "Dictionary key `1.0` is repeated",
+                "Dictionary key `(1, \"a\")` is repeated",
+                "Set element `1` is repeated",
+                "Dictionary key `b\"x\"` is repeated",
+                "Dictionary key `\"nested\"` is repeated",
+            ]
+        );
+        // The range is that of the repeated entry, not the first one.
+        assert_eq!(&source[diagnostics[0].range], "'name'");
+        assert_eq!(
+            source[..diagnostics[0].range.start().into()]
+                .lines()
+                .count(),
+            4
+        );
+    }
+}
@@ -0,0 +1,455 @@
+//! Comparisons of enum members with string, integer or bytes literals, which are always unequal
+//! (a common bug after replacing constants with an `Enum`).
+//!
+//! Only enums defined at the top level of the module are recognized, and only those whose bases
+//! are `Enum`, `Flag` or other such enums: mixins like `str` in `class Color(str, Enum)` or
+//! `IntEnum` make the members compare equal to the corresponding literals.
+//!
+//! An expression is known to be an enum member if it's a member access like `Color.RED`, or a
+//! name annotated with the enum, like a parameter `color: Color`.
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use rustpython_ast::{self as ast, text_size::TextRange, CmpOp, Constant, Expr, Pattern, Stmt};
+
+use pyrogen_python_ast::visitor::{self, Visitor};
+
+use crate::registry::Diagnostic;
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = ComparisonOverlap)]
+struct ComparisonOverlap<'a> {
+    r#enum: &'a str,
+    literal_type: &'a str,
+    result: &'static str,
+}
+
+/// The bases that enums without mixins derive from.
+const ENUM_BASES: &[&str] = &["enum.Enum", "enum.Flag"];
+
+/// The enums defined at the top level of a module, with their member names.
+fn collect_enums(python_ast: &[Stmt]) -> FxHashMap<&str, FxHashSet<&str>> {
+    let mut imports: FxHashMap<&str, String> = FxHashMap::default();
+    let mut enums: FxHashMap<&str, FxHashSet<&str>> = FxHashMap::default();
+    for stmt in python_ast {
+        match stmt {
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                for alias in names {
+                    let name = alias.asname.as_ref().unwrap_or(&alias.name).as_str();
+                    imports.insert(name, alias.name.to_string());
+                }
+            }
+            Stmt::ImportFrom(ast::StmtImportFrom {
+                module: Some(module),
+                names,
+                level,
+                ..
+            }) if level.map_or(true, |level| level.to_u32() == 0) => {
+                for alias in names {
+                    let name = alias.asname.as_ref().unwrap_or(&alias.name).as_str();
+                    imports.insert(name, format!("{}.{}", module.as_str(), alias.name.as_str()));
+                }
+            }
+            Stmt::ClassDef(ast::StmtClassDef {
+                name, bases, body, ..
+            }) => {
+                let is_enum = !bases.is_empty()
+                    && bases.iter().all(|base| {
+                        let qualified_name = match base {
+                            Expr::Name(ast::ExprName { id, .. }) => {
+                                if enums.contains_key(id.as_str()) {
+                                    return true;
+                                }
+                                imports.get(id.as_str()).cloned()
+                            }
+                            Expr::Attribute(ast::ExprAttribute { value, attr, .. }) => {
+                                match value.as_ref() {
+                                    Expr::Name(ast::ExprName { id, .. }) => imports
+                                        .get(id.as_str())
+                                        .map(|module| format!("{module}.{}", attr.as_str())),
+                                    _ => None,
+                                }
+                            }
+                            _ => None,
+                        };
+                        qualified_name.is_some_and(|qualified_name| {
+                            ENUM_BASES.contains(&qualified_name.as_str())
+                        })
+                    });
+                if is_enum {
+                    enums.insert(name.as_str(), enum_members(body));
+                } else {
+                    enums.remove(name.as_str());
+                }
+            }
+            _ => {}
+        }
+    }
+    enums
+}
+
+/// The names assigned in the body of an enum, other than private and dunder names.
+fn enum_members(body: &[Stmt]) -> FxHashSet<&str> {
+    let mut members = FxHashSet::default();
+    for stmt in body {
+        let targets = match stmt {
+            Stmt::Assign(ast::StmtAssign { targets, .. }) => targets.as_slice(),
+            Stmt::AnnAssign(ast::StmtAnnAssign {
+                target,
+                value: Some(_),
+                ..
+            }) => std::slice::from_ref(target.as_ref()),
+            _ => continue,
+        };
+        for target in targets {
+            if let Expr::Name(ast::ExprName { id, .. }) = target {
+                if !id.as_str().starts_with('_') {
+                    members.insert(id.as_str());
+                }
+            }
+        }
+    }
+    members
+}
+
+/// The type name of a `str`, `int` or `bytes` literal.
+fn literal_type(expr: &Expr) -> Option<&'static str> {
+    match expr {
+        Expr::Constant(ast::ExprConstant { value, .. }) => match value {
+            Constant::Str(_) => Some("str"),
+            Constant::Int(_) => Some("int"),
+            Constant::Bytes(_) => Some("bytes"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+struct EnumComparisonChecker<'a> {
+    enums: FxHashMap<&'a str, FxHashSet<&'a str>>,
+    /// The names bound in each enclosing scope, outermost first, with the enum they're annotated
+    /// with (or `None` if they're bound to something else).
+    scopes: Vec<FxHashMap<&'a str, Option<&'a str>>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> EnumComparisonChecker<'a> {
+    /// The enum that `annotation` refers to.
+    fn annotated_enum(&self, annotation: &'a Expr) -> Option<&'a str> {
+        let name = match annotation {
+            Expr::Name(ast::ExprName { id, .. }) => id.as_str(),
+            Expr::Constant(ast::ExprConstant {
+                value: Constant::Str(value),
+                ..
+            }) => value.as_str(),
+            _ => return None,
+        };
+        self.enums.get_key_value(name).map(|(name, _)| *name)
+    }
+
+    fn bind(&mut self, name: &'a str, annotated: Option<&'a str>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name, annotated);
+        }
+    }
+
+    fn bind_target(&mut self, target: &'a Expr) {
+        match target {
+            Expr::Name(ast::ExprName { id, .. }) => {
+                // An assignment doesn't change the declared type of an annotated name.
+                let declared = self
+                    .scopes
+                    .last()
+                    .and_then(|scope| scope.get(id.as_str()))
+                    .copied()
+                    .flatten();
+                self.bind(id.as_str(), declared);
+            }
+            Expr::Tuple(ast::ExprTuple { elts, .. }) | Expr::List(ast::ExprList { elts, .. }) => {
+                for elt in elts {
+                    self.bind_target(elt);
+                }
+            }
+            Expr::Starred(ast::ExprStarred { value, .. }) => self.bind_target(value),
+            _ => {}
+        }
+    }
+
+    /// The enum that `expr` is known to be a member of.
+    fn enum_type(&self, expr: &Expr) -> Option<&'a str> {
+        match expr {
+            Expr::Attribute(ast::ExprAttribute { value, attr, .. }) => {
+                let Expr::Name(ast::ExprName { id, .. }) = value.as_ref() else {
+                    return None;
+                };
+                let (name, members) = self.enums.get_key_value(id.as_str())?;
+                // The enum itself may be shadowed by a local variable.
+                if self.lookup(id.as_str()).is_some() {
+                    return None;
+                }
+                members.contains(attr.as_str()).then_some(*name)
+            }
+            Expr::Name(ast::ExprName { id, .. }) => self.lookup(id.as_str()).flatten(),
+            _ => None,
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<Option<&'a str>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    fn check_comparison(&mut self, left: &Expr, op: CmpOp, right: &Expr, range: TextRange) {
+        let (enum_name, literal) = match (self.enum_type(left), self.enum_type(right)) {
+            (Some(enum_name), _) => (enum_name, right),
+            (None, Some(enum_name)) => (enum_name, left),
+            (None, None) => return,
+        };
+        let (literal_type, result) = match op {
+            CmpOp::Eq | CmpOp::NotEq => {
+                let Some(literal_type) = literal_type(literal) else {
+                    return;
+                };
+                (literal_type, op == CmpOp::NotEq)
+            }
+            // `color in ("red", "green")`
+            CmpOp::In | CmpOp::NotIn if !std::ptr::eq(literal, left) => {
+                let (Expr::Tuple(ast::ExprTuple { elts, .. })
+                | Expr::List(ast::ExprList { elts, .. })
+                | Expr::Set(ast::ExprSet { elts, .. })) = literal
+                else {
+                    return;
+                };
+                let Some(literal_type) = elts.first().and_then(literal_type) else {
+                    return;
+                };
+                if !elts
+                    .iter()
+                    .all(|elt| literal_type(elt) == Some(literal_type))
+                {
+                    return;
+                }
+                (literal_type, op == CmpOp::NotIn)
+            }
+            _ => return,
+        };
+        self.diagnostics.push(Diagnostic::new(
+            ComparisonOverlap {
+                r#enum: enum_name,
+                literal_type,
+                result: if result { "True" } else { "False" },
+            },
+            range,
+        ));
+    }
+}
+
+impl<'a> Visitor<'a> for EnumComparisonChecker<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef {
+                name,
+                args,
+                body,
+                decorator_list,
+                returns,
+                ..
+            })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+                name,
+                args,
+                body,
+                decorator_list,
+                returns,
+                ..
+            }) => {
+                for decorator in decorator_list {
+                    self.visit_expr(decorator);
+                }
+                if let Some(returns) = returns {
+                    self.visit_expr(returns);
+                }
+                self.bind(name.as_str(), None);
+
+                let mut scope = FxHashMap::default();
+                for parameter in args
+                    .posonlyargs
+                    .iter()
+                    .chain(&args.args)
+                    .chain(&args.kwonlyargs)
+                {
+                    if let Some(default) = &parameter.default {
+                        self.visit_expr(default);
+                    }
+                    let annotated = parameter
+                        .def
+                        .annotation
+                        .as_deref()
+                        .and_then(|annotation| self.annotated_enum(annotation));
+                    scope.insert(parameter.def.arg.as_str(), annotated);
+                }
+                for parameter in args.vararg.iter().chain(&args.kwarg) {
+                    scope.insert(parameter.arg.as_str(), None);
+                }
+                self.scopes.push(scope);
+                self.visit_body(body);
+                self.scopes.pop();
+            }
+            Stmt::ClassDef(ast::StmtClassDef { name, .. }) => {
+                // The annotations in a class body are attributes, not variables of the
+                // enclosing scope. Classes at the top level are the only ones that can be enums,
+                // so they don't shadow them.
+                self.scopes.push(FxHashMap::default());
+                visitor::walk_stmt(self, stmt);
+                self.scopes.pop();
+                if self.scopes.len() > 1 {
+                    self.bind(name.as_str(), None);
+                }
+            }
+            Stmt::AnnAssign(ast::StmtAnnAssign {
+                target,
+                annotation,
+                value,
+                ..
+            }) => {
+                if let Some(value) = value {
+                    self.visit_expr(value);
+                }
+                if let Expr::Name(ast::ExprName { id, .. }) = target.as_ref() {
+                    self.bind(id.as_str(), self.annotated_enum(annotation));
+                }
+            }
+            Stmt::Assign(ast::StmtAssign { targets, value, .. }) => {
+                self.visit_expr(value);
+                for target in targets {
+                    self.bind_target(target);
+                }
+            }
+            Stmt::For(ast::StmtFor { target, .. })
+            | Stmt::AsyncFor(ast::StmtAsyncFor { target, .. }) => {
+                self.bind_target(target);
+                visitor::walk_stmt(self, stmt);
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &'a Pattern) {
+        match pattern {
+            Pattern::MatchAs(ast::PatternMatchAs {
+                name: Some(name), ..
+            })
+            | Pattern::MatchStar(ast::PatternMatchStar {
+                name: Some(name), ..
+            })
+            | Pattern::MatchMapping(ast::PatternMatchMapping {
+                rest: Some(name), ..
+            }) => self.bind(name.as_str(), None),
+            _ => {}
+        }
+        visitor::walk_pattern(self, pattern);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let Expr::Compare(ast::ExprCompare {
+            left,
+            ops,
+            comparators,
+            range,
+        }) = expr
+        {
+            let operands = std::iter::once(left.as_ref()).chain(comparators);
+            for ((left, right), op) in operands.zip(comparators).zip(ops) {
+                self.check_comparison(left, *op, right, *range);
+            }
+        }
+        visitor::walk_expr(self, expr);
+    }
+}
+
+/// Report comparisons of members of the enums defined in the module with literals.
+pub(crate) fn enum_comparisons(python_ast: &[Stmt]) -> Vec<Diagnostic> {
+    let enums = collect_enums(python_ast);
+    if enums.is_empty() {
+        return vec![];
+    }
+    let mut checker = EnumComparisonChecker {
+        enums,
+        scopes: vec![FxHashMap::default()],
+        diagnostics: vec![],
+    };
+    checker.visit_body(python_ast);
+    checker.diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::{parse, Mode};
+
+    use super::enum_comparisons;
+
+    #[test]
+    fn comparisons() {
+        let source = r#"
+import enum
+from enum import Enum, IntEnum
+
+class Color(Enum):
+    RED = "red"
+    GREEN = "green"
+
+    def describe(self) -> str: ...
+
+class Status(enum.Flag):
+    ACTIVE = 1
+
+class Mixed(str, Enum):
+    A = "a"
+
+class Level(IntEnum):
+    LOW = 1
+
+def paint(color: Color, name: str, status: "Status"):
+    if color == "red": ...
+    if color != Color.GREEN: ...
+    if color in ("red", "green"): ...
+    if "red" in (color,): ...
+    if status == 1: ...
+    if name == "red": ...
+    if Color.RED.value == "red": ...
+    if Mixed.A == "a" or Level.LOW == 1: ...
+
+current: Color = Color.RED
+current = Color.GREEN
+if current == "green": ...
+if Color.RED != b"red": ...
+
+def shadowed(Color):
+    return Color.RED == "red"
+
+def captured(color: Color, value):
+    match value:
+        case str() as color:
+            return color == "red"
+"#;
+        let python_ast = parse(source, Mode::Module, "<test>")
+            .unwrap()
+            .expect_module()
+            .body;
+        assert_eq!(
+            enum_comparisons(&python_ast)
+                .into_iter()
+                .map(|diagnostic| diagnostic.kind.body)
+                .collect::<Vec<_>>(),
+            [
+                "Comparison of a member of enum `Color` with a `str` literal is always `False`",
+                "Comparison of a member of enum `Color` with a `str` literal is always `False`",
+                "Comparison of a member of enum `Status` with a `int` literal is always `False`",
+                "Comparison of a member of enum `Color` with a `str` literal is always `False`",
+                "Comparison of a member of enum `Color` with a `bytes` literal is always `True`",
+            ]
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Checks of `try`/`except*` statements for exception groups (PEP 654).
+//!
+//! The parser accepts `except*` for any target version and anywhere in a function, but it needs
+//! Python 3.11, and its handlers can't `return`, `break` or `continue` out of the block. A handler
+//! also can't catch `ExceptionGroup` or `BaseExceptionGroup` themselves, since it matches the
+//! exceptions inside a group: that's a `TypeError` when the handler is reached.
+
+use rustpython_ast::{self as ast, ExceptHandler, Expr, Ranged, Stmt};
+
+use pyrogen_python_ast::visitor::{self, Visitor};
+
+use crate::registry::{Diagnostic, ErrorCode};
+use crate::settings::types::PythonVersion;
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = SyntaxError, message = "except-star-unsupported")]
+struct ExceptStarUnsupported {
+    version: String,
+}
+
+#[derive(Violation)]
+#[violation(code = SyntaxError, message = "except-star-jump")]
+struct ExceptStarJump {
+    keyword: &'static str,
+}
+
+#[derive(Violation)]
+#[violation(code = GeneralTypeError, message = "except-star-group")]
+struct ExceptStarGroup<'a> {
+    name: &'a str,
+}
+
+const EXCEPTION_GROUPS: &[&str] = &["ExceptionGroup", "BaseExceptionGroup"];
+
+/// The name of the exception group that `expr` (or an element of the tuple `expr`) refers to.
+fn exception_group(expr: &Expr) -> Option<(&str, &Expr)> {
+    match expr {
+        Expr::Name(ast::ExprName { id, .. }) => EXCEPTION_GROUPS
+            .contains(&id.as_str())
+            .then_some((id.as_str(), expr)),
+        Expr::Attribute(ast::ExprAttribute { value, attr, .. }) => {
+            let Expr::Name(ast::ExprName { id, .. }) = value.as_ref() else {
+                return None;
+            };
+            (id.as_str() == "builtins" && EXCEPTION_GROUPS.contains(&attr.as_str()))
+                .then_some((attr.as_str(), expr))
+        }
+        Expr::Tuple(ast::ExprTuple { elts, .. }) => elts.iter().find_map(exception_group),
+        _ => None,
+    }
+}
+
+/// Report the `return`, `break` and `continue` statements in `body` that leave an `except*` block.
+fn jumps(body: &[Stmt], in_loop: bool, diagnostics: &mut Vec<Diagnostic>) {
+    for stmt in body {
+        match stmt {
+            Stmt::Return(_) => {
+                diagnostics.push(Diagnostic::new(
+                    ExceptStarJump { keyword: "return" },
+                    stmt.range(),
+                ));
+            }
+            Stmt::Break(_) if !in_loop => {
+                diagnostics.push(Diagnostic::new(
+                    ExceptStarJump { keyword: "break" },
+                    stmt.range(),
+                ));
+            }
+            Stmt::Continue(_) if !in_loop => {
+                diagnostics.push(Diagnostic::new(
+                    ExceptStarJump {
+                        keyword: "continue",
+                    },
+                    stmt.range(),
+                ));
+            }
+            // Nested functions and classes have their own control flow.
+            Stmt::FunctionDef(_) | Stmt::AsyncFunctionDef(_) | Stmt::ClassDef(_) => {}
+            Stmt::For(ast::StmtFor { body, orelse, .. })
+            | Stmt::AsyncFor(ast::StmtAsyncFor { body, orelse, .. })
+            | Stmt::While(ast::StmtWhile { body, orelse, .. }) => {
+                jumps(body, true, diagnostics);
+                // The `else` of a loop isn't part of the loop.
+                jumps(orelse, in_loop, diagnostics);
+            }
+            Stmt::If(ast::StmtIf { body, orelse, .. }) => {
+                jumps(body, in_loop, diagnostics);
+                jumps(orelse, in_loop, diagnostics);
+            }
+            Stmt::With(ast::StmtWith { body, .. })
+            | Stmt::AsyncWith(ast::StmtAsyncWith { body, .. }) => {
+                jumps(body, in_loop, diagnostics);
+            }
+            Stmt::Try(ast::StmtTry {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            })
+            | Stmt::TryStar(ast::StmtTryStar {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            }) => {
+                jumps(body, in_loop, diagnostics);
+                for handler in handlers {
+                    let ExceptHandler::ExceptHandler(handler) = handler;
+                    jumps(&handler.body, in_loop, diagnostics);
+                }
+                jumps(orelse, in_loop, diagnostics);
+                jumps(finalbody, in_loop, diagnostics);
+            }
+            Stmt::Match(ast::StmtMatch { cases, .. }) => {
+                for case in cases {
+                    jumps(&case.body, in_loop, diagnostics);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+struct ExceptStarChecker {
+    /// The code to report: `SyntaxError` for the version and the jumps, or `GeneralTypeError` for
+    /// the exception groups.
+    code: ErrorCode,
+    target_version: PythonVersion,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Visitor<'a> for ExceptStarChecker {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if let Stmt::TryStar(ast::StmtTryStar { handlers, .. }) = stmt {
+            for (index, handler) in handlers.iter().enumerate() {
+                let ExceptHandler::ExceptHandler(handler) = handler;
+                if self.code == ErrorCode::GeneralTypeError {
+                    if let Some((name, expr)) = handler.type_.as_deref().and_then(exception_group) {
+                        self.diagnostics
+                            .push(Diagnostic::new(ExceptStarGroup { name }, expr.range()));
+                    }
+                    continue;
+                }
+                if index == 0 && self.target_version < PythonVersion::Py311 {
+                    let (major, minor) = self.target_version.as_tuple();
+                    self.diagnostics.push(Diagnostic::new(
+                        ExceptStarUnsupported {
+                            version: format!("{major}.{minor}"),
+                        },
+                        handler.range,
+                    ));
+                }
+                jumps(&handler.body, false, &mut self.diagnostics);
+            }
+        }
+        visitor::walk_stmt(self, stmt);
+    }
+}
+
+fn check_handlers(
+    python_ast: &[Stmt],
+    target_version: PythonVersion,
+    code: ErrorCode,
+) -> Vec<Diagnostic> {
+    let mut checker = ExceptStarChecker {
+        code,
+        target_version,
+        diagnostics: vec![],
+    };
+    checker.visit_body(python_ast);
+    checker.diagnostics
+}
+
+/// Report the `except*` handlers that are invalid on the target version, and the jumps out of
+/// them.
+pub(crate) fn except_star_syntax(
+    python_ast: &[Stmt],
+    target_version: PythonVersion,
+) -> Vec<Diagnostic> {
+    check_handlers(python_ast, target_version, ErrorCode::SyntaxError)
+}
+
+/// Report the `except*` handlers that catch exception groups, which fails at runtime.
+pub(crate) fn except_star_groups(
+    python_ast: &[Stmt],
+    target_version: PythonVersion,
+) -> Vec<Diagnostic> {
+    check_handlers(python_ast, target_version, ErrorCode::GeneralTypeError)
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::{parse, Mode};
+
+    use crate::settings::types::PythonVersion;
+
+    use super::{except_star_groups, except_star_syntax};
+
+    fn check(source: &str, target_version: PythonVersion) -> Vec<String> {
+        let python_ast = parse(source, Mode::Module, "<test>")
+            .unwrap()
+            .expect_module()
+            .body;
+        except_star_groups(&python_ast, target_version)
+            .into_iter()
+            .chain(except_star_syntax(&python_ast, target_version))
+            .map(|diagnostic| diagnostic.kind.body)
+            .collect()
+    }
+
+    #[test]
+    fn handlers() {
+        let source = r#"
+def f(items):
+    try:
+        ...
+    except* (ValueError, ExceptionGroup):
+        ...
+    except* builtins.BaseExceptionGroup:
+        ...
+    except* KeyError:
+        for item in items:
+            if item:
+                break
+            continue
+        else:
+            return 1
+    except* TypeError:
+        def inner():
+            return 2
+        while True:
+            break
+        continue
+
+try:
+    ...
+except ExceptionGroup:
+    pass
+"#;
+        assert_eq!(
+            check(source, PythonVersion::Py311),
+            [
+                "`except*` can't catch `ExceptionGroup`, since it matches the exceptions inside a group",
+                "`except*` can't catch `BaseExceptionGroup`, since it matches the exceptions inside a group",
+                "`return` can't be used in an `except*` block",
+                "`continue` can't be used in an `except*` block",
+            ]
+        );
+    }
+
+    #[test]
+    fn target_version() {
+        let source = "try:\n    ...\nexcept* ValueError:\n    ...\nexcept* KeyError:\n    ...\n";
+        assert_eq!(
+            check(source, PythonVersion::Py310),
+            ["`except*` requires Python 3.11 or newer, but the target version is 3.10"]
+        );
+        assert!(check(source, PythonVersion::Py312).is_empty());
+    }
+}
@@ -6,19 +6,67 @@ use pyrogen_source_file::Locator;
 use rustpython_ast::{text_size::TextRange, Constant, Expr, Stmt, StmtAnnAssign, Suite};
 
 use crate::{
-    registry::{Diagnostic, DiagnosticKind, ErrorCode},
+    interface::ModuleContext,
+    registry::{Diagnostic, ErrorCode},
     settings::{flags, CheckerSettings},
+    timing::RULE_TIMINGS,
     type_ignore::TypeIgnoreMapping,
+    violation::Violation,
 };
 
-fn type_mismatch(var_type: String, value_type: String) -> DiagnosticKind {
-    DiagnosticKind {
-        body: format!(
-            "Type mismatch: variable is of type {}, but value is of type {}",
-            var_type, value_type
-        ),
-        error_code: ErrorCode::GeneralTypeError,
-    }
+use self::annotations::{
+    missing_parameter_annotations, missing_return_annotations, missing_variable_annotations,
+};
+use self::call_arguments::call_arguments;
+use self::callables::callables;
+use self::dead_code::unreferenced_definitions;
+use self::dunder_all::{collect_exports, invalid_all, private_exports, undefined_exports};
+use self::duplicate_keys::duplicate_keys;
+use self::enum_comparisons::enum_comparisons;
+use self::except_star::{except_star_groups, except_star_syntax};
+use self::named_exprs::named_exprs;
+use self::overrides::overrides;
+use self::stubs::invalid_stub_content;
+use self::type_checking::type_checking_only_imports;
+use self::undeclared_dependencies::undeclared_dependencies;
+use self::unresolved_imports::unresolved_imports;
+use self::untyped_imports::untyped_imports;
+
+mod annotations;
+mod call_arguments;
+mod callables;
+mod dead_code;
+mod dunder_all;
+mod duplicate_keys;
+mod enum_comparisons;
+mod except_star;
+mod named_exprs;
+mod overrides;
+mod stubs;
+pub(crate) mod type_checking;
+mod undeclared_dependencies;
+mod unresolved_imports;
+mod untyped_imports;
+
+#[derive(Violation)]
+#[violation(code = GeneralTypeError, message = "type-mismatch")]
+struct TypeMismatch {
+    variable_type: &'static str,
+    value_type: &'static str,
+}
+
+/// Run rule `code` on the file at `path`, in a span so that profiles can attribute time to
+/// individual rules, and record its timing.
+fn run_rule(
+    code: ErrorCode,
+    path: &Path,
+    settings: &CheckerSettings,
+    rule: impl FnOnce() -> Vec<Diagnostic>,
+) -> Vec<Diagnostic> {
+    let _span = tracing::trace_span!("rule", code = %code).entered();
+    RULE_TIMINGS
+        .run(code, path, settings.rule_timeout, rule)
+        .unwrap_or_default()
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -32,11 +80,171 @@ pub(crate) fn check_ast(
     path: &Path,
     package: Option<&Path>,
     source_type: PySourceType,
+    module: Option<&ModuleContext>,
 ) -> Vec<Diagnostic> {
     let mut diagnostics: Vec<Diagnostic> = vec![];
-    if !settings.table.enabled(ErrorCode::GeneralTypeError) {
-        return diagnostics;
+    if settings.table.enabled(ErrorCode::TypeCheckingOnlyImport) {
+        diagnostics.extend(run_rule(
+            ErrorCode::TypeCheckingOnlyImport,
+            path,
+            settings,
+            || type_checking_only_imports(python_ast),
+        ));
+    }
+    if source_type.is_stub() && settings.table.enabled(ErrorCode::InvalidStubContent) {
+        diagnostics.extend(run_rule(
+            ErrorCode::InvalidStubContent,
+            path,
+            settings,
+            || invalid_stub_content(python_ast),
+        ));
+    }
+    if settings.disallow_untyped_imports && settings.table.enabled(ErrorCode::UntypedImport) {
+        diagnostics.extend(run_rule(ErrorCode::UntypedImport, path, settings, || {
+            untyped_imports(python_ast, settings.module_resolver())
+        }));
+    }
+    if settings.table.enabled(ErrorCode::ImportNotFound) {
+        diagnostics.extend(run_rule(ErrorCode::ImportNotFound, path, settings, || {
+            unresolved_imports(
+                python_ast,
+                settings.module_resolver(),
+                &settings.extra_modules,
+            )
+        }));
+    }
+    if let Some(declared) = &settings.declared_dependencies {
+        if settings.table.enabled(ErrorCode::UndeclaredDependency) {
+            diagnostics.extend(run_rule(
+                ErrorCode::UndeclaredDependency,
+                path,
+                settings,
+                || {
+                    undeclared_dependencies(
+                        python_ast,
+                        settings.module_resolver(),
+                        declared,
+                        &settings.site_packages,
+                    )
+                },
+            ));
+        }
+    }
+    if let Some(module) = module {
+        if settings.table.enabled(ErrorCode::CallArgument) {
+            diagnostics.extend(run_rule(ErrorCode::CallArgument, path, settings, || {
+                call_arguments(python_ast, module)
+            }));
+        }
+        if settings.table.enabled(ErrorCode::DeadCode) {
+            diagnostics.extend(run_rule(ErrorCode::DeadCode, path, settings, || {
+                unreferenced_definitions(module)
+            }));
+        }
     }
+    if settings.table.any_enabled(&[
+        ErrorCode::UndefinedExport,
+        ErrorCode::InvalidAll,
+        ErrorCode::PrivateExport,
+    ]) {
+        // The checks share the walk that collects `__all__`, but each is timed under its own code.
+        let exports = collect_exports(python_ast);
+        if settings.table.enabled(ErrorCode::InvalidAll) {
+            diagnostics.extend(run_rule(ErrorCode::InvalidAll, path, settings, || {
+                invalid_all(&exports)
+            }));
+        }
+        if settings.table.enabled(ErrorCode::UndefinedExport) {
+            diagnostics.extend(run_rule(ErrorCode::UndefinedExport, path, settings, || {
+                undefined_exports(&exports, path)
+            }));
+        }
+        if settings.table.enabled(ErrorCode::PrivateExport) {
+            diagnostics.extend(run_rule(ErrorCode::PrivateExport, path, settings, || {
+                private_exports(&exports)
+            }));
+        }
+    }
+    if settings
+        .table
+        .enabled(ErrorCode::MissingParameterAnnotation)
+    {
+        diagnostics.extend(run_rule(
+            ErrorCode::MissingParameterAnnotation,
+            path,
+            settings,
+            || missing_parameter_annotations(python_ast, locator.contents()),
+        ));
+    }
+    if settings.table.enabled(ErrorCode::MissingReturnAnnotation) {
+        diagnostics.extend(run_rule(
+            ErrorCode::MissingReturnAnnotation,
+            path,
+            settings,
+            || missing_return_annotations(python_ast, locator.contents()),
+        ));
+    }
+    if settings.table.enabled(ErrorCode::MissingVariableAnnotation) {
+        diagnostics.extend(run_rule(
+            ErrorCode::MissingVariableAnnotation,
+            path,
+            settings,
+            || missing_variable_annotations(python_ast),
+        ));
+    }
+    if settings.table.enabled(ErrorCode::IncompatibleCallable) {
+        diagnostics.extend(run_rule(
+            ErrorCode::IncompatibleCallable,
+            path,
+            settings,
+            || callables(python_ast, locator),
+        ));
+    }
+    if settings.table.enabled(ErrorCode::DuplicateKey) {
+        diagnostics.extend(run_rule(ErrorCode::DuplicateKey, path, settings, || {
+            duplicate_keys(python_ast, locator)
+        }));
+    }
+    if settings.table.enabled(ErrorCode::ComparisonOverlap) {
+        diagnostics.extend(run_rule(
+            ErrorCode::ComparisonOverlap,
+            path,
+            settings,
+            || enum_comparisons(python_ast),
+        ));
+    }
+    if settings.table.enabled(ErrorCode::SyntaxError) {
+        diagnostics.extend(run_rule(ErrorCode::SyntaxError, path, settings, || {
+            except_star_syntax(python_ast, settings.target_version)
+        }));
+        diagnostics.extend(run_rule(ErrorCode::SyntaxError, path, settings, || {
+            named_exprs(python_ast)
+        }));
+    }
+    if settings.table.enabled(ErrorCode::Override) {
+        diagnostics.extend(run_rule(ErrorCode::Override, path, settings, || {
+            overrides(python_ast, locator.contents())
+        }));
+    }
+    if settings.table.enabled(ErrorCode::GeneralTypeError) {
+        diagnostics.extend(run_rule(
+            ErrorCode::GeneralTypeError,
+            path,
+            settings,
+            || except_star_groups(python_ast, settings.target_version),
+        ));
+        diagnostics.extend(run_rule(
+            ErrorCode::GeneralTypeError,
+            path,
+            settings,
+            || general_type_errors(python_ast),
+        ));
+    }
+    diagnostics
+}
+
+fn general_type_errors(python_ast: &Suite<TextRange>) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = vec![];
     for stmt in python_ast {
         match stmt {
             Stmt::AnnAssign(StmtAnnAssign {
@@ -62,7 +270,10 @@ pub(crate) fn check_ast(
                                     };
                                     if let Some(value_type) = value_type {
                                         diagnostics.push(Diagnostic::new(
-                                            type_mismatch("int".into(), value_type.into()),
+                                            TypeMismatch {
+                                                variable_type: "int",
+                                                value_type,
+                                            },
                                             *range,
                                         ))
                                     }
@@ -0,0 +1,224 @@
+//! Checks of assignment expressions (`:=`) in comprehensions (PEP 572).
+//!
+//! The parser only checks that the target is a name. An assignment expression in a comprehension
+//! binds in the enclosing scope, which CPython rejects when compiling the module if the target is
+//! an iteration variable of the comprehension, if it's in the iterable of a `for` clause, or if the
+//! enclosing scope is a class body.
+
+use rustpython_ast::{self as ast, Comprehension, Expr, Ranged, Stmt};
+
+use pyrogen_python_ast::visitor::{self, Visitor};
+
+use crate::registry::{Diagnostic, DiagnosticKind};
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = SyntaxError, message = "named-expr-rebinds-iteration-variable")]
+struct RebindsIterationVariable<'a> {
+    name: &'a str,
+}
+
+#[derive(Violation)]
+#[violation(code = SyntaxError, message = "named-expr-in-comprehension-iterable")]
+struct InComprehensionIterable;
+
+#[derive(Violation)]
+#[violation(code = SyntaxError, message = "named-expr-in-class-comprehension")]
+struct InClassComprehension;
+
+/// Collect the names bound by a comprehension target.
+fn collect_names<'a>(target: &'a Expr, names: &mut Vec<&'a str>) {
+    match target {
+        Expr::Name(ast::ExprName { id, .. }) => names.push(id.as_str()),
+        Expr::Tuple(ast::ExprTuple { elts, .. }) | Expr::List(ast::ExprList { elts, .. }) => {
+            for elt in elts {
+                collect_names(elt, names);
+            }
+        }
+        Expr::Starred(ast::ExprStarred { value, .. }) => collect_names(value, names),
+        _ => {}
+    }
+}
+
+#[derive(Default)]
+struct NamedExprChecker<'a> {
+    /// Whether the innermost function, lambda or class scope is a class body.
+    in_class: bool,
+    /// The iteration variables of the comprehensions enclosing the current expression, innermost
+    /// last, up to the innermost function, lambda or class scope.
+    comprehensions: Vec<Vec<&'a str>>,
+    /// Whether the current expression is in the iterable of a comprehension.
+    in_iterable: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> NamedExprChecker<'a> {
+    /// Visit `generators` and then `elements` in a new comprehension.
+    fn visit_comprehension_expr(&mut self, generators: &'a [Comprehension], elements: &[&'a Expr]) {
+        let mut names = vec![];
+        for generator in generators {
+            collect_names(&generator.target, &mut names);
+        }
+        self.comprehensions.push(names);
+        for generator in generators {
+            self.visit_comprehension(generator);
+        }
+        for element in elements {
+            self.visit_expr(element);
+        }
+        self.comprehensions.pop();
+    }
+
+    /// Visit a nested scope, which starts without enclosing comprehensions.
+    fn visit_scope(&mut self, in_class: bool, visit: impl FnOnce(&mut Self)) {
+        let comprehensions = std::mem::take(&mut self.comprehensions);
+        let in_iterable = std::mem::replace(&mut self.in_iterable, false);
+        let outer = std::mem::replace(&mut self.in_class, in_class);
+        visit(self);
+        self.in_class = outer;
+        self.in_iterable = in_iterable;
+        self.comprehensions = comprehensions;
+    }
+}
+
+impl<'a> Visitor<'a> for NamedExprChecker<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::FunctionDef(_) | Stmt::AsyncFunctionDef(_) => {
+                self.visit_scope(false, |checker| visitor::walk_stmt(checker, stmt));
+            }
+            Stmt::ClassDef(_) => {
+                self.visit_scope(true, |checker| visitor::walk_stmt(checker, stmt));
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::NamedExpr(ast::ExprNamedExpr { target, .. })
+                if !self.comprehensions.is_empty() =>
+            {
+                let target_name = match target.as_ref() {
+                    Expr::Name(ast::ExprName { id, .. }) => Some(id.as_str()),
+                    _ => None,
+                };
+                let kind: Option<DiagnosticKind> = if self.in_iterable {
+                    Some(InComprehensionIterable.into())
+                } else if let Some(name) = target_name
+                    .filter(|name| self.comprehensions.iter().any(|names| names.contains(name)))
+                {
+                    Some(RebindsIterationVariable { name }.into())
+                } else if self.in_class {
+                    Some(InClassComprehension.into())
+                } else {
+                    None
+                };
+                if let Some(kind) = kind {
+                    self.diagnostics.push(Diagnostic::new(kind, expr.range()));
+                }
+                visitor::walk_expr(self, expr);
+            }
+            Expr::Lambda(_) => {
+                self.visit_scope(false, |checker| visitor::walk_expr(checker, expr));
+            }
+            Expr::ListComp(ast::ExprListComp {
+                elt, generators, ..
+            })
+            | Expr::SetComp(ast::ExprSetComp {
+                elt, generators, ..
+            })
+            | Expr::GeneratorExp(ast::ExprGeneratorExp {
+                elt, generators, ..
+            }) => self.visit_comprehension_expr(generators, &[&**elt]),
+            Expr::DictComp(ast::ExprDictComp {
+                key,
+                value,
+                generators,
+                ..
+            }) => self.visit_comprehension_expr(generators, &[&**key, &**value]),
+            _ => visitor::walk_expr(self, expr),
+        }
+    }
+
+    fn visit_comprehension(&mut self, comprehension: &'a Comprehension) {
+        let in_iterable = std::mem::replace(&mut self.in_iterable, true);
+        self.visit_expr(&comprehension.iter);
+        self.in_iterable = in_iterable;
+        self.visit_expr(&comprehension.target);
+        for expr in &comprehension.ifs {
+            self.visit_expr(expr);
+        }
+    }
+}
+
+/// Report the assignment expressions in comprehensions that can't bind their target.
+pub(crate) fn named_exprs(python_ast: &[Stmt]) -> Vec<Diagnostic> {
+    let mut checker = NamedExprChecker::default();
+    checker.visit_body(python_ast);
+    checker.diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::{parse, Mode};
+
+    use super::named_exprs;
+
+    #[test]
+    fn comprehensions() {
+        let source = r#"
+values = [(y := x) for x in range(3)]
+pairs = [(x := 0) for x, _ in items]
+nested = [[(x := y) for y in row] for x in rows]
+sources = [x for x in (data := load())]
+later = [x for x in range(3) for y in (x := rows)]
+counts = {k: (total := v) for k, v in items}
+callbacks = [lambda: (x := 1) for x in range(3)]
+
+class Config:
+    last = [(value := item) for item in items]
+    direct = (value := 1)
+
+    def method(self):
+        return [(value := item) for item in items]
+"#;
+        let python_ast = parse(source, Mode::Module, "<test>")
+            .unwrap()
+            .expect_module()
+            .body;
+        assert_eq!(
+            named_exprs(&python_ast)
+                .into_iter()
+                .map(|diagnostic| (diagnostic.kind.body, &source[diagnostic.range]))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "Assignment expression cannot rebind comprehension iteration variable `x`"
+                        .to_string(),
+                    "x := 0"
+                ),
+                (
+                    "Assignment expression cannot rebind comprehension iteration variable `x`"
+                        .to_string(),
+                    "x := y"
+                ),
+                (
+                    "Assignment expression cannot be used in a comprehension iterable expression"
+                        .to_string(),
+                    "data := load()"
+                ),
+                (
+                    "Assignment expression cannot be used in a comprehension iterable expression"
+                        .to_string(),
+                    "x := rows"
+                ),
+                (
+                    "Assignment expression within a comprehension cannot be used in a class body"
+                        .to_string(),
+                    "value := item"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Subclasses of `typing.final` classes and overrides of `typing.final` methods.
+//!
+//! Only the classes defined at the top level of the module are known as bases. A method is
+//! checked against the closest definition of its name in the bases of its class, searched depth
+//! first, so a final method that was already overridden further up is only reported there.
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use rustpython_ast::{self as ast, Expr, Ranged, Stmt};
+
+use crate::definitions::identifier_range;
+use crate::registry::Diagnostic;
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = Override, message = "final-class")]
+struct FinalClass<'a> {
+    class: &'a str,
+}
+
+#[derive(Violation)]
+#[violation(code = Override, message = "final-method")]
+struct FinalMethod<'a> {
+    method: &'a str,
+    class: &'a str,
+}
+
+/// The qualified names of the `final` decorator.
+const FINAL_DECORATORS: &[&str] = &["typing.final", "typing_extensions.final"];
+
+struct Class<'a> {
+    is_final: bool,
+    /// The bases that are classes of the module.
+    bases: Vec<&'a str>,
+    /// The methods defined in the body, and whether each of them is final.
+    methods: FxHashMap<&'a str, bool>,
+}
+
+struct OverrideChecker<'a> {
+    source: &'a str,
+    /// The qualified names of the imported names.
+    imports: FxHashMap<&'a str, String>,
+    classes: FxHashMap<&'a str, Class<'a>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> OverrideChecker<'a> {
+    fn is_final(&self, decorator_list: &[Expr]) -> bool {
+        decorator_list.iter().any(|decorator| {
+            let qualified_name = match decorator {
+                Expr::Name(ast::ExprName { id, .. }) => self.imports.get(id.as_str()).cloned(),
+                Expr::Attribute(ast::ExprAttribute { value, attr, .. }) => match value.as_ref() {
+                    Expr::Name(ast::ExprName { id, .. }) => self
+                        .imports
+                        .get(id.as_str())
+                        .map(|module| format!("{module}.{}", attr.as_str())),
+                    _ => None,
+                },
+                _ => None,
+            };
+            qualified_name
+                .is_some_and(|qualified_name| FINAL_DECORATORS.contains(&qualified_name.as_str()))
+        })
+    }
+
+    /// The closest class among `bases` and their bases that defines `method`, and whether the
+    /// method is final there.
+    fn find_method(&self, bases: &[&'a str], method: &str) -> Option<(&'a str, bool)> {
+        let mut stack: Vec<&str> = bases.iter().rev().copied().collect();
+        let mut visited = FxHashSet::default();
+        while let Some(name) = stack.pop() {
+            if !visited.insert(name) {
+                continue;
+            }
+            let Some(class) = self.classes.get(name) else {
+                continue;
+            };
+            if let Some(is_final) = class.methods.get(method) {
+                return Some((name, *is_final));
+            }
+            stack.extend(class.bases.iter().rev());
+        }
+        None
+    }
+
+    fn visit_class(&mut self, class: &'a ast::StmtClassDef) {
+        let mut bases = vec![];
+        for base in &class.bases {
+            let Expr::Name(ast::ExprName { id, .. }) = base else {
+                continue;
+            };
+            let Some((name, base_class)) = self.classes.get_key_value(id.as_str()) else {
+                continue;
+            };
+            if base_class.is_final {
+                self.diagnostics
+                    .push(Diagnostic::new(FinalClass { class: name }, base.range()));
+            }
+            bases.push(*name);
+        }
+
+        let mut methods = FxHashMap::default();
+        for stmt in &class.body {
+            let (name, decorator_list) = match stmt {
+                Stmt::FunctionDef(ast::StmtFunctionDef {
+                    name,
+                    decorator_list,
+                    ..
+                })
+                | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+                    name,
+                    decorator_list,
+                    ..
+                }) => (name.as_str(), decorator_list),
+                _ => continue,
+            };
+            // Overloads and property setters define the same name again.
+            if methods.contains_key(name) {
+                continue;
+            }
+            if let Some((base, true)) = self.find_method(&bases, name) {
+                self.diagnostics.push(Diagnostic::new(
+                    FinalMethod {
+                        method: name,
+                        class: base,
+                    },
+                    identifier_range(stmt, name, self.source),
+                ));
+            }
+            methods.insert(name, self.is_final(decorator_list));
+        }
+
+        let is_final = self.is_final(&class.decorator_list);
+        self.classes.insert(
+            class.name.as_str(),
+            Class {
+                is_final,
+                bases,
+                methods,
+            },
+        );
+    }
+}
+
+/// Report subclasses of final classes and overrides of final methods, for the classes defined at
+/// the top level of the module.
+pub(crate) fn overrides(python_ast: &[Stmt], source: &str) -> Vec<Diagnostic> {
+    let mut checker = OverrideChecker {
+        source,
+        imports: FxHashMap::default(),
+        classes: FxHashMap::default(),
+        diagnostics: vec![],
+    };
+    for stmt in python_ast {
+        match stmt {
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                for alias in names {
+                    let name = alias.asname.as_ref().unwrap_or(&alias.name).as_str();
+                    checker.imports.insert(name, alias.name.to_string());
+                }
+            }
+            Stmt::ImportFrom(ast::StmtImportFrom {
+                module: Some(module),
+                names,
+                level,
+                ..
+            }) if level.map_or(true, |level| level.to_u32() == 0) => {
+                for alias in names {
+                    let name = alias.asname.as_ref().unwrap_or(&alias.name).as_str();
+                    checker
+                        .imports
+                        .insert(name, format!("{}.{}", module.as_str(), alias.name.as_str()));
+                }
+            }
+            Stmt::ClassDef(class) => checker.visit_class(class),
+            _ => {}
+        }
+    }
+    checker.diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::{parse, Mode};
+
+    use super::overrides;
+
+    #[test]
+    fn final_classes_and_methods() {
+        let source = r#"
+import typing
+from typing import final
+from typing_extensions import final as final_ext
+
+@final
+class Leaf: ...
+
+class Child(Leaf): ...
+
+class Base:
+    @final
+    def run(self) -> None: ...
+
+    @typing.final
+    def stop(self) -> None: ...
+
+    @final_ext
+    async def wait(self) -> None: ...
+
+    def reset(self) -> None: ...
+
+class Middle(Base):
+    def stop(self) -> None: ...
+
+class Derived(Middle):
+    def run(self) -> None: ...
+    def stop(self) -> None: ...
+    async def wait(self) -> None: ...
+    def reset(self) -> None: ...
+
+class Unrelated:
+    def run(self) -> None: ...
+"#;
+        let python_ast = parse(source, Mode::Module, "<test>")
+            .unwrap()
+            .expect_module()
+            .body;
+        assert_eq!(
+            overrides(&python_ast, source)
+                .into_iter()
+                .map(|diagnostic| (diagnostic.kind.body, &source[diagnostic.range]))
+                .collect::<Vec<_>>(),
+            [
+                ("Cannot inherit from final class `Leaf`".to_string(), "Leaf"),
+                (
+                    "Cannot override final method `stop` of class `Base`".to_string(),
+                    "stop"
+                ),
+                (
+                    "Cannot override final method `run` of class `Base`".to_string(),
+                    "run"
+                ),
+                (
+                    "Cannot override final method `wait` of class `Base`".to_string(),
+                    "wait"
+                ),
+            ]
+        );
+    }
+}
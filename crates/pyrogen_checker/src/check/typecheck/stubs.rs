@@ -0,0 +1,163 @@
+//! Checks for the content of stub (`.pyi`) files.
+//!
+//! Stubs only declare the interface of a module: function bodies are `...` and there's no code
+//! that would do anything at runtime.
+
+use rustpython_ast::{self as ast, Constant, Expr, Ranged, Stmt};
+
+use crate::registry::Diagnostic;
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = InvalidStubContent, message = "stub-function-body")]
+struct NonEmptyFunctionBody;
+
+#[derive(Violation)]
+#[violation(code = InvalidStubContent, message = "stub-runtime-code")]
+struct RuntimeCode;
+
+/// Report function bodies other than `...` and statements that only make sense at runtime.
+pub(crate) fn invalid_stub_content(python_ast: &[Stmt]) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    check_declarations(python_ast, &mut diagnostics);
+    diagnostics
+}
+
+/// Check a module or class body.
+fn check_declarations(body: &[Stmt], diagnostics: &mut Vec<Diagnostic>) {
+    for stmt in body {
+        match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef { body, .. })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { body, .. }) => {
+                check_function_body(body, diagnostics);
+            }
+            Stmt::ClassDef(ast::StmtClassDef { body, .. }) => {
+                check_declarations(body, diagnostics);
+            }
+            // `sys.version_info` and `sys.platform` checks are common in stubs.
+            Stmt::If(ast::StmtIf { body, orelse, .. }) => {
+                check_declarations(body, diagnostics);
+                check_declarations(orelse, diagnostics);
+            }
+            // `__all__ += [...]` is allowed, too.
+            Stmt::Import(_)
+            | Stmt::ImportFrom(_)
+            | Stmt::Assign(_)
+            | Stmt::AnnAssign(_)
+            | Stmt::AugAssign(_)
+            | Stmt::TypeAlias(_) => {}
+            stmt if is_placeholder(stmt) || is_docstring(stmt) => {}
+            _ => diagnostics.push(Diagnostic::new(RuntimeCode, stmt.range())),
+        }
+    }
+}
+
+/// Check a function body, which may consist of a docstring followed by `...` or `pass`.
+fn check_function_body(body: &[Stmt], diagnostics: &mut Vec<Diagnostic>) {
+    let body = match body.split_first() {
+        Some((first, rest)) if is_docstring(first) => rest,
+        _ => body,
+    };
+    let invalid = match body {
+        [first, ..] if !is_placeholder(first) => Some(first),
+        [_, second, ..] => Some(second),
+        _ => None,
+    };
+    if let Some(stmt) = invalid {
+        diagnostics.push(Diagnostic::new(NonEmptyFunctionBody, stmt.range()));
+    }
+}
+
+/// Returns `true` for `...` and `pass`.
+fn is_placeholder(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::Pass(_) => true,
+        Stmt::Expr(ast::StmtExpr { value, .. }) => matches!(
+            value.as_ref(),
+            Expr::Constant(ast::ExprConstant {
+                value: Constant::Ellipsis,
+                ..
+            })
+        ),
+        _ => false,
+    }
+}
+
+fn is_docstring(stmt: &Stmt) -> bool {
+    matches!(
+        stmt,
+        Stmt::Expr(ast::StmtExpr { value, .. }) if matches!(
+            value.as_ref(),
+            Expr::Constant(ast::ExprConstant {
+                value: Constant::Str(_),
+                ..
+            })
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::{parse, Mode};
+
+    use super::invalid_stub_content;
+
+    fn check(source: &str) -> Vec<String> {
+        let module = parse(source, Mode::Module, "<test>.pyi").unwrap();
+        invalid_stub_content(&module.expect_module().body)
+            .into_iter()
+            .map(|diagnostic| diagnostic.kind.body)
+            .collect()
+    }
+
+    #[test]
+    fn valid_stub() {
+        let source = r#"
+import sys
+from typing import TypeVar
+
+__all__ = ["f", "C"]
+T = TypeVar("T")
+x: int
+
+def f(x: T) -> T: ...
+def g() -> None:
+    """Docstring."""
+
+class C:
+    attr: str
+    def method(self) -> None: ...
+    if sys.version_info >= (3, 11):
+        def new_method(self) -> None: ...
+
+class Empty: ...
+"#;
+        assert!(check(source).is_empty());
+    }
+
+    #[test]
+    fn invalid_stub() {
+        let source = r#"
+print("hello")
+
+def f() -> int:
+    return 1
+
+class C:
+    def method(self) -> None:
+        ...
+        ...
+    for x in range(3):
+        pass
+"#;
+        assert_eq!(
+            check(source),
+            [
+                "Stubs should only contain declarations, not runtime code",
+                "Function bodies in stubs should be `...`",
+                "Function bodies in stubs should be `...`",
+                "Stubs should only contain declarations, not runtime code",
+            ]
+        );
+    }
+}
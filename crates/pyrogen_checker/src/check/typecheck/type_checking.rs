@@ -0,0 +1,252 @@
+//! Support for imports guarded by `if TYPE_CHECKING:`.
+//!
+//! Names imported under `TYPE_CHECKING` only exist for the type checker: they may be used in
+//! annotations, but referencing them in code that is evaluated at runtime raises a `NameError`.
+
+use rustc_hash::FxHashSet;
+use rustpython_ast::{self as ast, Expr, ExprContext, Stmt};
+
+use pyrogen_python_ast::visitor::{self, Visitor};
+
+use crate::registry::Diagnostic;
+use crate::violation::Violation;
+
+/// Returns `true` if `expr` is `TYPE_CHECKING`, `typing.TYPE_CHECKING` or
+/// `typing_extensions.TYPE_CHECKING`.
+pub(crate) fn is_type_checking_guard(expr: &Expr) -> bool {
+    match expr {
+        Expr::Name(ast::ExprName { id, .. }) => id.as_str() == "TYPE_CHECKING",
+        Expr::Attribute(ast::ExprAttribute { value, attr, .. }) => {
+            attr.as_str() == "TYPE_CHECKING"
+                && matches!(
+                    value.as_ref(),
+                    Expr::Name(ast::ExprName { id, .. })
+                        if matches!(id.as_str(), "typing" | "typing_extensions")
+                )
+        }
+        _ => false,
+    }
+}
+
+/// Returns the name an import alias binds in the importing scope.
+fn bound_name<'a>(alias: &'a ast::Alias, is_from_import: bool) -> &'a str {
+    match &alias.asname {
+        Some(asname) => asname.as_str(),
+        // `import a.b.c` binds `a`.
+        None if !is_from_import => alias.name.as_str().split('.').next().unwrap_or_default(),
+        None => alias.name.as_str(),
+    }
+}
+
+/// Collects the names bound under `TYPE_CHECKING` and the names bound at runtime.
+#[derive(Default)]
+struct BindingCollector<'a> {
+    in_type_checking: bool,
+    guarded: FxHashSet<&'a str>,
+    runtime: FxHashSet<&'a str>,
+}
+
+impl<'a> BindingCollector<'a> {
+    fn bind(&mut self, name: &'a str) {
+        if self.in_type_checking {
+            self.guarded.insert(name);
+        } else {
+            self.runtime.insert(name);
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for BindingCollector<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::If(ast::StmtIf {
+                test, body, orelse, ..
+            }) if is_type_checking_guard(test) => {
+                let in_type_checking = std::mem::replace(&mut self.in_type_checking, true);
+                self.visit_body(body);
+                self.in_type_checking = in_type_checking;
+                self.visit_body(orelse);
+            }
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                for alias in names {
+                    self.bind(bound_name(alias, false));
+                }
+            }
+            Stmt::ImportFrom(ast::StmtImportFrom { names, .. }) => {
+                for alias in names {
+                    if alias.name.as_str() != "*" {
+                        self.bind(bound_name(alias, true));
+                    }
+                }
+            }
+            Stmt::FunctionDef(ast::StmtFunctionDef { name, .. })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { name, .. })
+            | Stmt::ClassDef(ast::StmtClassDef { name, .. }) => {
+                self.bind(name.as_str());
+                visitor::walk_stmt(self, stmt);
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let Expr::Name(ast::ExprName {
+            id,
+            ctx: ExprContext::Store,
+            ..
+        }) = expr
+        {
+            self.bind(id.as_str());
+        }
+        visitor::walk_expr(self, expr);
+    }
+
+    fn visit_parameter(&mut self, parameter: &'a ast::Arg) {
+        self.bind(parameter.arg.as_str());
+        visitor::walk_parameter(self, parameter);
+    }
+}
+
+/// Reports loads of guarded names outside of annotations and `TYPE_CHECKING` blocks.
+struct RuntimeUseChecker<'a> {
+    guarded: FxHashSet<&'a str>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Visitor<'a> for RuntimeUseChecker<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::If(ast::StmtIf { test, orelse, .. }) if is_type_checking_guard(test) => {
+                self.visit_body(orelse);
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_annotation(&mut self, _expr: &'a Expr) {
+        // Annotations are resolved by the type checker, where guarded names are available.
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let Expr::Name(ast::ExprName {
+            id,
+            ctx: ExprContext::Load,
+            range,
+        }) = expr
+        {
+            if self.guarded.contains(id.as_str()) {
+                self.diagnostics.push(Diagnostic::new(
+                    TypeCheckingOnlyImport { name: id.as_str() },
+                    *range,
+                ));
+            }
+        }
+        visitor::walk_expr(self, expr);
+    }
+}
+
+#[derive(Violation)]
+#[violation(code = TypeCheckingOnlyImport)]
+struct TypeCheckingOnlyImport<'a> {
+    name: &'a str,
+}
+
+/// Report runtime uses of names that are only imported inside `if TYPE_CHECKING:` blocks.
+///
+/// Names that are also bound outside such a block (e.g., by a fallback in the `else` branch)
+/// are available at runtime and therefore not reported.
+pub(crate) fn type_checking_only_imports(python_ast: &[Stmt]) -> Vec<Diagnostic> {
+    let mut collector = BindingCollector::default();
+    collector.visit_body(python_ast);
+    if collector.guarded.is_empty() {
+        return vec![];
+    }
+
+    let guarded = collector
+        .guarded
+        .into_iter()
+        .filter(|name| !collector.runtime.contains(name))
+        .collect();
+    let mut checker = RuntimeUseChecker {
+        guarded,
+        diagnostics: vec![],
+    };
+    checker.visit_body(python_ast);
+    checker.diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::{parse, Mode};
+
+    use super::type_checking_only_imports;
+
+    fn check(source: &str) -> Vec<String> {
+        let module = parse(source, Mode::Module, "<test>").unwrap();
+        type_checking_only_imports(&module.expect_module().body)
+            .into_iter()
+            .map(|diagnostic| diagnostic.kind.body)
+            .collect()
+    }
+
+    #[test]
+    fn annotation_use_is_allowed() {
+        let source = r#"
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    from collections.abc import Sequence
+
+def f(x: Sequence[int]) -> Sequence[int]:
+    y: Sequence[int] = x
+    return y
+"#;
+        assert!(check(source).is_empty());
+    }
+
+    #[test]
+    fn runtime_use_is_reported() {
+        let source = r#"
+import typing
+
+if typing.TYPE_CHECKING:
+    import os.path
+    from collections.abc import Sequence as Seq
+
+isinstance([], Seq)
+os.path.join("a", "b")
+"#;
+        assert_eq!(check(source).len(), 2);
+    }
+
+    #[test]
+    fn runtime_fallback_is_allowed() {
+        let source = r#"
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    from foo import Bar
+else:
+    Bar = object
+
+class Baz(Bar): ...
+"#;
+        assert!(check(source).is_empty());
+    }
+
+    #[test]
+    fn parameters_are_bindings() {
+        let source = r#"
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    from collections import OrderedDict
+
+def f(OrderedDict):
+    return OrderedDict()
+
+g = lambda OrderedDict: OrderedDict()
+"#;
+        assert!(check(source).is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+//! Imports of installed third-party packages that the project doesn't declare as dependencies.
+//!
+//! Optional imports (in a `try` that catches `ImportError`, or under `TYPE_CHECKING`) may refer
+//! to packages that aren't always installed, so they aren't reported.
+
+use std::path::PathBuf;
+
+use rustpython_ast::Stmt;
+
+use pyrogen_module_resolver::ModuleResolver;
+
+use crate::dependencies::{third_party_imports, Distributions};
+use crate::registry::Diagnostic;
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = UndeclaredDependency)]
+struct UndeclaredDependency<'a> {
+    module: &'a str,
+    distribution: &'a str,
+}
+
+/// Report the imports of third-party modules whose distributions aren't among the `declared`
+/// dependencies.
+pub(crate) fn undeclared_dependencies(
+    python_ast: &[Stmt],
+    resolver: &ModuleResolver,
+    declared: &[String],
+    site_packages: &[PathBuf],
+) -> Vec<Diagnostic> {
+    let imports = third_party_imports(python_ast, resolver);
+    if imports.iter().all(|import| import.optional) {
+        return vec![];
+    }
+
+    let distributions = Distributions::new(site_packages);
+    imports
+        .iter()
+        .filter(|import| !import.optional)
+        .filter_map(|import| {
+            let provided_by = distributions.providing(import.module);
+            if provided_by
+                .iter()
+                .any(|distribution| declared.contains(distribution))
+            {
+                return None;
+            }
+            Some(Diagnostic::new(
+                UndeclaredDependency {
+                    module: import.module,
+                    distribution: &provided_by[0],
+                },
+                import.range,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, write};
+
+    use anyhow::Result;
+    use rustpython_parser::{parse, Mode};
+    use tempfile::TempDir;
+
+    use pyrogen_module_resolver::{ModuleResolver, ModuleResolverSettings};
+
+    use super::undeclared_dependencies;
+
+    #[test]
+    fn undeclared() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let site_packages = tmp_dir.path().join("site-packages");
+        for package in ["yaml", "attr", "requests"] {
+            create_dir_all(site_packages.join(package))?;
+            write(site_packages.join(package).join("__init__.py"), "")?;
+        }
+        create_dir_all(site_packages.join("PyYAML-6.0.1.dist-info"))?;
+        write(
+            site_packages.join("PyYAML-6.0.1.dist-info/top_level.txt"),
+            "yaml\n",
+        )?;
+        create_dir_all(site_packages.join("attrs-23.1.0.dist-info"))?;
+        write(
+            site_packages.join("attrs-23.1.0.dist-info/top_level.txt"),
+            "attr\n",
+        )?;
+
+        let resolver = ModuleResolver::new(&ModuleResolverSettings {
+            site_packages: vec![site_packages.clone()],
+            vendored_typeshed: true,
+            ..ModuleResolverSettings::default()
+        });
+        let source = r"
+import attr
+import json
+import yaml
+from requests import Session
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    import requests
+";
+        let python_ast = parse(source, Mode::Module, "<test>")?.expect_module().body;
+        let diagnostics = undeclared_dependencies(
+            &python_ast,
+            &resolver,
+            &["attrs".to_string()],
+            &[site_packages],
+        );
+        assert_eq!(
+            diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.kind.body)
+                .collect::<Vec<_>>(),
+            [
+                "Module `yaml` is imported, but `pyyaml` isn't a declared dependency",
+                "Module `requests` is imported, but `requests` isn't a declared dependency",
+            ]
+        );
+        Ok(())
+    }
+}
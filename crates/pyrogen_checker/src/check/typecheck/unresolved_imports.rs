@@ -0,0 +1,178 @@
+//! Imports of modules that can't be found.
+//!
+//! Imports in the body of a `try` statement that catches `ImportError` are optional by design,
+//! and imports of the modules listed in `extra-modules` exist at runtime without being
+//! resolvable. Neither is reported.
+
+use rustpython_ast::{self as ast, text_size::TextRange, Stmt};
+
+use pyrogen_module_resolver::{ModuleName, ModuleResolver};
+use pyrogen_python_ast::imports::catches_import_error;
+use pyrogen_python_ast::visitor::{self, Visitor};
+
+use crate::registry::Diagnostic;
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = ImportNotFound)]
+struct ImportNotFound<'a> {
+    name: &'a ModuleName,
+}
+
+struct UnresolvedImportChecker<'a> {
+    resolver: &'a ModuleResolver,
+    extra_modules: &'a [String],
+    /// Whether the statements being visited are in a `try` body that catches `ImportError`.
+    in_guarded_try: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl UnresolvedImportChecker<'_> {
+    fn is_extra_module(&self, module: &str) -> bool {
+        self.extra_modules.iter().any(|extra| {
+            module
+                .strip_prefix(extra.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    }
+
+    fn check(&mut self, module: &str, range: TextRange) {
+        if self.in_guarded_try || self.is_extra_module(module) {
+            return;
+        }
+        let Some(name) = ModuleName::new(module) else {
+            return;
+        };
+        if self.resolver.resolve(&name).is_none() {
+            self.diagnostics
+                .push(Diagnostic::new(ImportNotFound { name: &name }, range));
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for UnresolvedImportChecker<'_> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                for alias in names {
+                    self.check(alias.name.as_str(), alias.range);
+                }
+            }
+            // Relative imports aren't checked.
+            Stmt::ImportFrom(ast::StmtImportFrom {
+                module: Some(module),
+                level,
+                range,
+                ..
+            }) if level.map_or(true, |level| level.to_u32() == 0) => {
+                self.check(module.as_str(), *range);
+            }
+            Stmt::Try(ast::StmtTry {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            })
+            | Stmt::TryStar(ast::StmtTryStar {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            }) => {
+                let in_guarded_try = self.in_guarded_try;
+                self.in_guarded_try |= handlers.iter().any(catches_import_error);
+                self.visit_body(body);
+                self.in_guarded_try = in_guarded_try;
+                for handler in handlers {
+                    self.visit_except_handler(handler);
+                }
+                self.visit_body(orelse);
+                self.visit_body(finalbody);
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+}
+
+/// Report absolute imports of modules that the resolver can't find.
+pub(crate) fn unresolved_imports(
+    python_ast: &[Stmt],
+    resolver: &ModuleResolver,
+    extra_modules: &[String],
+) -> Vec<Diagnostic> {
+    let mut checker = UnresolvedImportChecker {
+        resolver,
+        extra_modules,
+        in_guarded_try: false,
+        diagnostics: vec![],
+    };
+    checker.visit_body(python_ast);
+    checker.diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, File};
+
+    use anyhow::Result;
+    use pyrogen_module_resolver::{ModuleResolver, ModuleResolverSettings};
+    use rustpython_parser::{parse, Mode};
+    use tempfile::TempDir;
+
+    use super::unresolved_imports;
+
+    #[test]
+    fn missing_modules() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let site_packages = tmp_dir.path().join("site-packages");
+        let path = site_packages.join("present/__init__.py");
+        create_dir_all(path.parent().unwrap())?;
+        File::create(path)?;
+        let resolver = ModuleResolver::new(&ModuleResolverSettings {
+            site_packages: vec![site_packages],
+            ..ModuleResolverSettings::default()
+        });
+
+        let source = r#"
+import present
+import missing
+import plugins.generated
+from . import sibling
+from missing_package import thing
+
+try:
+    import fast_json as json
+except (ImportError, AttributeError):
+    json = None
+else:
+    import also_missing
+
+try:
+    import other
+except ValueError:
+    pass
+"#;
+        let module = parse(source, Mode::Module, "<test>")?;
+        let diagnostics = unresolved_imports(
+            &module.expect_module().body,
+            &resolver,
+            &["plugins".to_string()],
+        );
+        assert_eq!(
+            diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.kind.body)
+                .collect::<Vec<_>>(),
+            [
+                "Cannot find module `missing`",
+                "Cannot find module `missing_package`",
+                "Cannot find module `also_missing`",
+                "Cannot find module `other`",
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+//! Imports of installed packages that don't ship type information.
+
+use rustpython_ast::{self as ast, text_size::TextRange, Stmt};
+
+use pyrogen_module_resolver::{ModuleName, ModuleOrigin, ModuleResolver};
+use pyrogen_python_ast::visitor::{self, Visitor};
+
+use crate::registry::Diagnostic;
+use crate::violation::Violation;
+
+#[derive(Violation)]
+#[violation(code = UntypedImport)]
+struct UntypedImport<'a> {
+    name: &'a ModuleName,
+}
+
+struct UntypedImportChecker<'a> {
+    resolver: &'a ModuleResolver,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl UntypedImportChecker<'_> {
+    fn check(&mut self, module: &str, range: TextRange) {
+        let Some(name) = ModuleName::new(module) else {
+            return;
+        };
+        if let Some(resolved) = self.resolver.resolve(&name) {
+            if resolved.origin == ModuleOrigin::ThirdParty && !resolved.is_typed {
+                self.diagnostics
+                    .push(Diagnostic::new(UntypedImport { name: &name }, range));
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for UntypedImportChecker<'_> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                for alias in names {
+                    self.check(alias.name.as_str(), alias.range);
+                }
+            }
+            Stmt::ImportFrom(ast::StmtImportFrom {
+                module: Some(module),
+                level,
+                range,
+                ..
+            }) if level.map_or(true, |level| level.to_u32() == 0) => {
+                self.check(module.as_str(), *range);
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+}
+
+/// Report imports of third-party modules that are installed without a `py.typed` marker or
+/// stubs, since everything imported from them is silently treated as `Any`.
+pub(crate) fn untyped_imports(python_ast: &[Stmt], resolver: &ModuleResolver) -> Vec<Diagnostic> {
+    let mut checker = UntypedImportChecker {
+        resolver,
+        diagnostics: vec![],
+    };
+    checker.visit_body(python_ast);
+    checker.diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, File};
+
+    use anyhow::Result;
+    use pyrogen_module_resolver::{ModuleResolver, ModuleResolverSettings};
+    use rustpython_parser::{parse, Mode};
+    use tempfile::TempDir;
+
+    use super::untyped_imports;
+
+    #[test]
+    fn untyped_packages() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let site_packages = tmp_dir.path().join("site-packages");
+        for file in ["typed/__init__.py", "typed/py.typed", "untyped/__init__.py"] {
+            let path = site_packages.join(file);
+            create_dir_all(path.parent().unwrap())?;
+            File::create(path)?;
+        }
+        let resolver = ModuleResolver::new(&ModuleResolverSettings {
+            site_packages: vec![site_packages],
+            ..ModuleResolverSettings::default()
+        });
+
+        let source = r#"
+import typed
+import untyped
+from untyped import thing
+from . import sibling
+import missing
+"#;
+        let module = parse(source, Mode::Module, "<test>")?;
+        let diagnostics = untyped_imports(&module.expect_module().body, &resolver);
+        assert_eq!(diagnostics.len(), 2);
+
+        Ok(())
+    }
+}
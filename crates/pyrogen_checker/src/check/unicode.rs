@@ -0,0 +1,322 @@
+//! Characters that make code read differently than it runs: letters that look like ASCII letters
+//! but aren't (like the Cyrillic `а` in `pаypal`), and the bidirectional control characters of
+//! "Trojan Source" attacks, which reorder how the text around them is displayed.
+//!
+//! The checks work on the token stream. Confusable letters are reported in names, and in string
+//! literals only in words that also contain ASCII letters, so that text in other scripts isn't
+//! reported. Bidirectional control characters are reported in strings and comments, the only
+//! places the lexer accepts them.
+
+use rustpython_parser::lexer::LexResult;
+use rustpython_parser::text_size::{TextLen, TextRange, TextSize};
+use rustpython_parser::Tok;
+
+use pyrogen_source_file::Locator;
+
+use crate::fix::{Edit, Fix};
+use crate::registry::{Diagnostic, ErrorCode};
+use crate::settings::CheckerSettings;
+use crate::violation::Violation;
+
+/// Letters that look like ASCII letters, and the letters they look like, sorted by code point.
+const CONFUSABLES: &[(char, char)] = &[
+    ('\u{0131}', 'i'), // LATIN SMALL LETTER DOTLESS I
+    ('\u{0251}', 'a'), // LATIN SMALL LETTER ALPHA
+    ('\u{0261}', 'g'), // LATIN SMALL LETTER SCRIPT G
+    ('\u{0391}', 'A'), // GREEK CAPITAL LETTER ALPHA
+    ('\u{0392}', 'B'), // GREEK CAPITAL LETTER BETA
+    ('\u{0395}', 'E'), // GREEK CAPITAL LETTER EPSILON
+    ('\u{0396}', 'Z'), // GREEK CAPITAL LETTER ZETA
+    ('\u{0397}', 'H'), // GREEK CAPITAL LETTER ETA
+    ('\u{0399}', 'I'), // GREEK CAPITAL LETTER IOTA
+    ('\u{039A}', 'K'), // GREEK CAPITAL LETTER KAPPA
+    ('\u{039C}', 'M'), // GREEK CAPITAL LETTER MU
+    ('\u{039D}', 'N'), // GREEK CAPITAL LETTER NU
+    ('\u{039F}', 'O'), // GREEK CAPITAL LETTER OMICRON
+    ('\u{03A1}', 'P'), // GREEK CAPITAL LETTER RHO
+    ('\u{03A4}', 'T'), // GREEK CAPITAL LETTER TAU
+    ('\u{03A5}', 'Y'), // GREEK CAPITAL LETTER UPSILON
+    ('\u{03A7}', 'X'), // GREEK CAPITAL LETTER CHI
+    ('\u{03BD}', 'v'), // GREEK SMALL LETTER NU
+    ('\u{03BF}', 'o'), // GREEK SMALL LETTER OMICRON
+    ('\u{0405}', 'S'), // CYRILLIC CAPITAL LETTER DZE
+    ('\u{0406}', 'I'), // CYRILLIC CAPITAL LETTER BYELORUSSIAN-UKRAINIAN I
+    ('\u{0408}', 'J'), // CYRILLIC CAPITAL LETTER JE
+    ('\u{0410}', 'A'), // CYRILLIC CAPITAL LETTER A
+    ('\u{0412}', 'B'), // CYRILLIC CAPITAL LETTER VE
+    ('\u{0415}', 'E'), // CYRILLIC CAPITAL LETTER IE
+    ('\u{041A}', 'K'), // CYRILLIC CAPITAL LETTER KA
+    ('\u{041C}', 'M'), // CYRILLIC CAPITAL LETTER EM
+    ('\u{041D}', 'H'), // CYRILLIC CAPITAL LETTER EN
+    ('\u{041E}', 'O'), // CYRILLIC CAPITAL LETTER O
+    ('\u{0420}', 'P'), // CYRILLIC CAPITAL LETTER ER
+    ('\u{0421}', 'C'), // CYRILLIC CAPITAL LETTER ES
+    ('\u{0422}', 'T'), // CYRILLIC CAPITAL LETTER TE
+    ('\u{0425}', 'X'), // CYRILLIC CAPITAL LETTER HA
+    ('\u{0430}', 'a'), // CYRILLIC SMALL LETTER A
+    ('\u{0435}', 'e'), // CYRILLIC SMALL LETTER IE
+    ('\u{043E}', 'o'), // CYRILLIC SMALL LETTER O
+    ('\u{0440}', 'p'), // CYRILLIC SMALL LETTER ER
+    ('\u{0441}', 'c'), // CYRILLIC SMALL LETTER ES
+    ('\u{0443}', 'y'), // CYRILLIC SMALL LETTER U
+    ('\u{0445}', 'x'), // CYRILLIC SMALL LETTER HA
+    ('\u{0455}', 's'), // CYRILLIC SMALL LETTER DZE
+    ('\u{0456}', 'i'), // CYRILLIC SMALL LETTER BYELORUSSIAN-UKRAINIAN I
+    ('\u{0458}', 'j'), // CYRILLIC SMALL LETTER JE
+    ('\u{0501}', 'd'), // CYRILLIC SMALL LETTER KOMI DE
+];
+
+/// The ASCII letter or digit that `c` can be confused with, if any.
+fn ascii_equivalent(c: char) -> Option<char> {
+    match c {
+        // The fullwidth forms of the ASCII letters and digits.
+        '\u{FF10}'..='\u{FF19}' | '\u{FF21}'..='\u{FF3A}' | '\u{FF41}'..='\u{FF5A}' => {
+            char::from_u32(u32::from(c) - 0xFEE0)
+        }
+        _ => CONFUSABLES
+            .binary_search_by_key(&c, |(confusable, _)| *confusable)
+            .ok()
+            .map(|index| CONFUSABLES[index].1),
+    }
+}
+
+/// The name of the bidirectional control character `c`, if it is one.
+fn bidi_control_name(c: char) -> Option<&'static str> {
+    match c {
+        '\u{061C}' => Some("ARABIC LETTER MARK"),
+        '\u{200E}' => Some("LEFT-TO-RIGHT MARK"),
+        '\u{200F}' => Some("RIGHT-TO-LEFT MARK"),
+        '\u{202A}' => Some("LEFT-TO-RIGHT EMBEDDING"),
+        '\u{202B}' => Some("RIGHT-TO-LEFT EMBEDDING"),
+        '\u{202C}' => Some("POP DIRECTIONAL FORMATTING"),
+        '\u{202D}' => Some("LEFT-TO-RIGHT OVERRIDE"),
+        '\u{202E}' => Some("RIGHT-TO-LEFT OVERRIDE"),
+        '\u{2066}' => Some("LEFT-TO-RIGHT ISOLATE"),
+        '\u{2067}' => Some("RIGHT-TO-LEFT ISOLATE"),
+        '\u{2068}' => Some("FIRST STRONG ISOLATE"),
+        '\u{2069}' => Some("POP DIRECTIONAL ISOLATE"),
+        _ => None,
+    }
+}
+
+#[derive(Violation)]
+#[violation(code = ConfusableCharacter, fix_title = "Replace with `{ascii}`")]
+struct ConfusableCharacter {
+    character: char,
+    code_point: String,
+    ascii: char,
+}
+
+#[derive(Violation)]
+#[violation(code = BidiControlCharacter)]
+struct BidiControlCharacter {
+    name: &'static str,
+    code_point: String,
+}
+
+fn code_point(c: char) -> String {
+    format!("U+{:04X}", u32::from(c))
+}
+
+fn confusable_character(c: char, ascii: char, range: TextRange) -> Diagnostic {
+    let violation = ConfusableCharacter {
+        character: c,
+        code_point: code_point(c),
+        ascii,
+    };
+    let fix = Fix::new(
+        violation.fix_title().unwrap_or_default(),
+        [Edit::range_replacement(ascii.to_string(), range)],
+    );
+    Diagnostic::new(violation, range).with_fix(fix)
+}
+
+/// The confusable letters in the name at `start`.
+fn check_name(name: &str, start: TextSize, diagnostics: &mut Vec<Diagnostic>) {
+    for (offset, c) in name.char_indices() {
+        if let Some(ascii) = ascii_equivalent(c) {
+            let range = TextRange::at(start + TextSize::try_from(offset).unwrap(), c.text_len());
+            diagnostics.push(confusable_character(c, ascii, range));
+        }
+    }
+}
+
+/// The confusable letters in the words of the string literal at `start` that also contain ASCII
+/// letters.
+fn check_string(string: &str, start: TextSize, diagnostics: &mut Vec<Diagnostic>) {
+    let mut word_start = None;
+    for (offset, c) in string
+        .char_indices()
+        .chain(std::iter::once((string.len(), ' ')))
+    {
+        match (word_start, c.is_alphanumeric()) {
+            (None, true) => word_start = Some(offset),
+            (Some(word), false) => {
+                let word_text = &string[word..offset];
+                if word_text.chars().any(|c| c.is_ascii_alphabetic()) {
+                    check_name(
+                        word_text,
+                        start + TextSize::try_from(word).unwrap(),
+                        diagnostics,
+                    );
+                }
+                word_start = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The bidirectional control characters in the string or comment at `start`.
+fn check_bidi(text: &str, start: TextSize, diagnostics: &mut Vec<Diagnostic>) {
+    for (offset, c) in text.char_indices() {
+        if let Some(name) = bidi_control_name(c) {
+            diagnostics.push(Diagnostic::new(
+                BidiControlCharacter {
+                    name,
+                    code_point: code_point(c),
+                },
+                TextRange::at(start + TextSize::try_from(offset).unwrap(), c.text_len()),
+            ));
+        }
+    }
+}
+
+/// Report the confusable and bidirectional control characters in `tokens`.
+pub(crate) fn check_tokens(
+    tokens: &[LexResult],
+    locator: &Locator,
+    settings: &CheckerSettings,
+) -> Vec<Diagnostic> {
+    let confusables = settings.table.enabled(ErrorCode::ConfusableCharacter);
+    let bidi = settings.table.enabled(ErrorCode::BidiControlCharacter);
+    let mut diagnostics = vec![];
+    for (token, range) in tokens.iter().flatten() {
+        let text = locator.slice(*range);
+        // Most code is ASCII, which has neither kind of character.
+        if text.is_ascii() {
+            continue;
+        }
+        match token {
+            Tok::Name { .. } if confusables => check_name(text, range.start(), &mut diagnostics),
+            Tok::String { .. } => {
+                if confusables {
+                    check_string(text, range.start(), &mut diagnostics);
+                }
+                if bidi {
+                    check_bidi(text, range.start(), &mut diagnostics);
+                }
+            }
+            Tok::Comment(_) if bidi => check_bidi(text, range.start(), &mut diagnostics),
+            _ => {}
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::lexer::lex;
+    use rustpython_parser::Mode;
+
+    use pyrogen_source_file::Locator;
+
+    use crate::registry::ErrorCode;
+    use crate::settings::CheckerSettings;
+
+    use super::{check_tokens, CONFUSABLES};
+
+    #[test]
+    fn confusables_are_sorted() {
+        assert!(CONFUSABLES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    fn check(source: &str) -> Vec<(ErrorCode, String, Option<String>)> {
+        let tokens = lex(source, Mode::Module).collect::<Vec<_>>();
+        let locator = Locator::new(source);
+        let settings = CheckerSettings::for_rules(vec![
+            ErrorCode::ConfusableCharacter,
+            ErrorCode::BidiControlCharacter,
+        ]);
+        check_tokens(&tokens, &locator, &settings)
+            .into_iter()
+            .map(|diagnostic| {
+                let fix = diagnostic
+                    .fix
+                    .as_ref()
+                    .map(|fix| fix.edits()[0].content().unwrap_or_default().to_string());
+                (
+                    diagnostic.kind.error_code,
+                    locator.slice(diagnostic.range).to_string(),
+                    fix,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn confusable_names() {
+        assert_eq!(
+            check("p\u{0430}ypal = 1\n\u{0441}ount = 2\n"),
+            [
+                (
+                    ErrorCode::ConfusableCharacter,
+                    "\u{0430}".to_string(),
+                    Some("a".to_string())
+                ),
+                (
+                    ErrorCode::ConfusableCharacter,
+                    "\u{0441}".to_string(),
+                    Some("c".to_string())
+                ),
+            ]
+        );
+        assert!(check("café = 1\n").is_empty());
+    }
+
+    #[test]
+    fn confusable_strings() {
+        // Words in other scripts are left alone, mixed ones are reported.
+        assert!(check("x = '\u{043F}\u{0440}\u{0438}\u{0432}\u{0435}\u{0442}'\n").is_empty());
+        assert_eq!(
+            check("x = 'see p\u{0430}ypal'\n"),
+            [(
+                ErrorCode::ConfusableCharacter,
+                "\u{0430}".to_string(),
+                Some("a".to_string())
+            )]
+        );
+        assert_eq!(
+            check("x = '\u{FF41}bc'\n"),
+            [(
+                ErrorCode::ConfusableCharacter,
+                "\u{FF41}".to_string(),
+                Some("a".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn bidi_controls() {
+        assert_eq!(
+            check("access = 'user\u{202E} \u{2066}# admin'  # \u{2069}\n"),
+            [
+                (
+                    ErrorCode::BidiControlCharacter,
+                    "\u{202E}".to_string(),
+                    None
+                ),
+                (
+                    ErrorCode::BidiControlCharacter,
+                    "\u{2066}".to_string(),
+                    None
+                ),
+                (
+                    ErrorCode::BidiControlCharacter,
+                    "\u{2069}".to_string(),
+                    None
+                ),
+            ]
+        );
+    }
+}
@@ -1,8 +1,10 @@
 use std::ops::Deref;
 use std::path::Path;
 
+use anyhow::{bail, Result};
+use log::warn;
 use rustpython_ast::text_size::{TextLen, TextRange};
-use rustpython_ast::TextSize;
+use rustpython_ast::{Suite, TextSize};
 use rustpython_parser::ast::Ranged;
 use rustpython_parser::lexer::LexResult;
 use rustpython_parser::ParseError;
@@ -12,15 +14,21 @@ use pyrogen_python_ast::{AsMode, PySourceType};
 use pyrogen_python_index::Indexer;
 use pyrogen_source_file::{Locator, SourceFileBuilder};
 
+use crate::cancellation::{CancellationToken, Cancelled};
 use crate::check::filesystem::check_file_path;
+use crate::check::static_conditions::{prune_static_branches, StaticTarget};
 use crate::check::type_ignore::check_type_ignore;
 use crate::check::typecheck::check_ast;
+use crate::check::unicode::check_tokens;
+use crate::fix::{apply_fixes, FixTable};
+use crate::interface::ModuleContext;
 use crate::message::Message;
-use crate::registry::{AsErrorCode, Diagnostic, DiagnosticKind, ErrorCode};
+use crate::registry::{AsErrorCode, Diagnostic, ErrorCode};
 use crate::settings::code_table::MessageKind;
 use crate::settings::{flags, CheckerSettings};
 use crate::source_kind::SourceKind;
 use crate::type_ignore::TypeIgnoreMapping;
+use crate::violation::Violation;
 use crate::{directives, fs};
 
 /// A [`Result`]-like type that returns both data and an error. Used to return
@@ -41,8 +49,23 @@ impl<T> CheckerResult<T> {
     }
 }
 
+#[derive(Violation)]
+#[violation(code = SyntaxError)]
+struct SyntaxError {
+    error: String,
+}
+
+#[derive(Violation)]
+#[violation(code = TooManyDiagnostics)]
+struct TooManyDiagnostics {
+    count: usize,
+    limit: usize,
+}
+
 /// Generate `Diagnostic`s from the source code contents at the
 /// given `Path`.
+///
+/// Returns [`Cancelled`] if `cancellation` is cancelled before the check is done.
 #[allow(clippy::too_many_arguments)]
 pub fn check_path(
     path: &Path,
@@ -55,11 +78,80 @@ pub fn check_path(
     respect_type_ignore: flags::TypeIgnore,
     source_kind: &SourceKind,
     source_type: PySourceType,
-) -> CheckerResult<(Vec<Diagnostic>, Option<ImportMap>)> {
-    // Aggregate all diagnostics.
+    module: Option<&ModuleContext>,
+    cancellation: &CancellationToken,
+) -> Result<CheckerResult<(Vec<Diagnostic>, Option<ImportMap>)>, Cancelled> {
+    cancellation.check()?;
+
+    let tokens: Vec<LexResult> = tokens.into_iter().collect();
+    let diagnostics = check_path_and_tokens(path, package, &tokens, locator, settings);
+
+    // Run the AST-based rules.
+    let parsed = tracing::debug_span!("parse").in_scope(|| {
+        rustpython_parser::parse_tokens(tokens, source_type.as_mode(), &path.to_string_lossy())
+            .map(|python_ast| python_ast.expect_module().body)
+    });
+    check_syntax_tree(
+        diagnostics,
+        parsed,
+        path,
+        package,
+        locator,
+        indexer,
+        noqa_mapping,
+        settings,
+        respect_type_ignore,
+        source_type,
+        module,
+        cancellation,
+    )
+}
+
+/// Like [`check_path`], but with the module parsed from `tokens` beforehand, so that callers that
+/// keep the syntax tree between checks don't parse it again.
+#[allow(clippy::too_many_arguments)]
+pub fn check_parsed(
+    path: &Path,
+    package: Option<&Path>,
+    tokens: &[LexResult],
+    parsed: Result<Suite, ParseError>,
+    locator: &Locator,
+    indexer: &Indexer,
+    noqa_mapping: &TypeIgnoreMapping,
+    settings: &CheckerSettings,
+    respect_type_ignore: flags::TypeIgnore,
+    source_type: PySourceType,
+    module: Option<&ModuleContext>,
+    cancellation: &CancellationToken,
+) -> Result<CheckerResult<(Vec<Diagnostic>, Option<ImportMap>)>, Cancelled> {
+    cancellation.check()?;
+
+    let diagnostics = check_path_and_tokens(path, package, tokens, locator, settings);
+    check_syntax_tree(
+        diagnostics,
+        parsed,
+        path,
+        package,
+        locator,
+        indexer,
+        noqa_mapping,
+        settings,
+        respect_type_ignore,
+        source_type,
+        module,
+        cancellation,
+    )
+}
+
+/// Run the filesystem-based and the token-based rules.
+fn check_path_and_tokens(
+    path: &Path,
+    package: Option<&Path>,
+    tokens: &[LexResult],
+    locator: &Locator,
+    settings: &CheckerSettings,
+) -> Vec<Diagnostic> {
     let mut diagnostics = vec![];
-    let imports = None;
-    let mut error = None;
 
     // Run the filesystem-based rules.
     if settings
@@ -67,14 +159,58 @@ pub fn check_path(
         .iter_enabled()
         .any(|error_code| error_code.lint_source().is_filesystem())
     {
+        let _span = tracing::debug_span!("filesystem_rules").entered();
         diagnostics.extend(check_file_path(path, package, settings));
     }
 
-    // Run the AST-based rules.
-    match rustpython_parser::parse_tokens(tokens, source_type.as_mode(), &path.to_string_lossy()) {
-        Ok(python_ast) => {
+    // Run the token-based rules.
+    if settings
+        .table
+        .iter_enabled()
+        .any(|error_code| error_code.lint_source().is_tokens())
+    {
+        let _span = tracing::debug_span!("token_rules").entered();
+        diagnostics.extend(check_tokens(tokens, locator, settings));
+    }
+
+    diagnostics
+}
+
+/// Run the AST-based rules on the parsed module, and enforce the `type: ignore` directives on all
+/// `diagnostics`.
+#[allow(clippy::too_many_arguments)]
+fn check_syntax_tree(
+    mut diagnostics: Vec<Diagnostic>,
+    parsed: Result<Suite, ParseError>,
+    path: &Path,
+    package: Option<&Path>,
+    locator: &Locator,
+    indexer: &Indexer,
+    noqa_mapping: &TypeIgnoreMapping,
+    settings: &CheckerSettings,
+    respect_type_ignore: flags::TypeIgnore,
+    source_type: PySourceType,
+    module: Option<&ModuleContext>,
+    cancellation: &CancellationToken,
+) -> Result<CheckerResult<(Vec<Diagnostic>, Option<ImportMap>)>, Cancelled> {
+    let imports = None;
+    let mut error = None;
+
+    cancellation.check()?;
+    let mut pruned_ranges = vec![];
+    match parsed {
+        Ok(mut python_ast) => {
+            // Skip branches that can't be taken on the configured version and platform.
+            pruned_ranges = prune_static_branches(
+                &mut python_ast,
+                StaticTarget {
+                    version: settings.target_version,
+                    platform: settings.platform,
+                },
+            );
+            let _span = tracing::debug_span!("check_ast").entered();
             diagnostics.extend(check_ast(
-                &python_ast.expect_module().body,
+                &python_ast,
                 locator,
                 indexer,
                 noqa_mapping,
@@ -83,6 +219,7 @@ pub fn check_path(
                 path,
                 package,
                 source_type,
+                module,
             ));
             // let (import_diagnostics, module_imports) = check_imports(
             //     &python_ast,
@@ -113,9 +250,8 @@ pub fn check_path(
                 .next()
                 .map_or(TextSize::new(0), TextLen::text_len);
             diagnostics.push(Diagnostic::new(
-                DiagnosticKind {
-                    body: format!("Syntax error: {}", parse_error.error),
-                    error_code: ErrorCode::SyntaxError,
+                SyntaxError {
+                    error: parse_error.error.to_string(),
                 },
                 TextRange::at(parse_error.offset, len),
             ));
@@ -123,6 +259,8 @@ pub fn check_path(
         }
     }
 
+    cancellation.check()?;
+
     // Ignore diagnostics based on per-file-ignores.
     if !diagnostics.is_empty() && !settings.per_file_ignores.is_empty() {
         let ignores = fs::ignores_from_path(path, &settings.per_file_ignores);
@@ -138,12 +276,14 @@ pub fn check_path(
             .iter_enabled()
             .any(|rule_code| rule_code.lint_source().is_noqa())
     {
+        let _span = tracing::debug_span!("type_ignore").entered();
         let ignored = check_type_ignore(
             &mut diagnostics,
             path,
             locator,
             indexer.comment_ranges(),
             noqa_mapping,
+            &pruned_ranges,
             error.is_none(),
             settings,
         );
@@ -171,11 +311,42 @@ pub fn check_path(
         }
     }
 
-    CheckerResult::new((diagnostics, imports), error)
+    truncate_diagnostics(&mut diagnostics, settings);
+
+    Ok(CheckerResult::new((diagnostics, imports), error))
+}
+
+/// Drop the diagnostics beyond `max-diagnostics-per-file`, keeping errors over other kinds and
+/// earlier diagnostics over later ones, and add a notice with the number of dropped diagnostics.
+fn truncate_diagnostics(diagnostics: &mut Vec<Diagnostic>, settings: &CheckerSettings) {
+    let limit = settings.max_diagnostics_per_file;
+    if limit == 0 || diagnostics.len() <= limit {
+        return;
+    }
+    diagnostics.sort_by_key(|diagnostic| {
+        (
+            settings.table.kind(diagnostic.kind.error_code) != MessageKind::Error,
+            diagnostic.start(),
+        )
+    });
+    let dropped = diagnostics.len() - limit;
+    diagnostics.truncate(limit);
+    if settings.table.enabled(ErrorCode::TooManyDiagnostics) {
+        diagnostics.push(Diagnostic::new(
+            TooManyDiagnostics {
+                count: dropped,
+                limit,
+            },
+            TextRange::default(),
+        ));
+    }
 }
 
 /// Generate a [`Message`] for each [`Diagnostic`] triggered by the given source
 /// code.
+///
+/// Returns [`Cancelled`] if `cancellation` is cancelled before the check is done.
+#[allow(clippy::too_many_arguments)]
 pub fn lint_only(
     path: &Path,
     package: Option<&Path>,
@@ -183,20 +354,27 @@ pub fn lint_only(
     noqa: flags::TypeIgnore,
     source_kind: &SourceKind,
     source_type: PySourceType,
-) -> CheckerResult<(Vec<Message>, Option<ImportMap>)> {
+    module: Option<&ModuleContext>,
+    cancellation: &CancellationToken,
+) -> Result<CheckerResult<(Vec<Message>, Option<ImportMap>)>, Cancelled> {
     // Tokenize once.
     // type Tokens = impl Iterator<Item = LexResult>;
-    let tokens = rustpython_parser::lexer::lex(source_kind.source_code(), source_type.as_mode())
-        .collect::<Vec<_>>();
+    let tokens = tracing::debug_span!("tokenize").in_scope(|| {
+        rustpython_parser::lexer::lex(source_kind.source_code(), source_type.as_mode())
+            .collect::<Vec<_>>()
+    });
 
     // Map row and column locations to byte slices (lazily).
     let locator = Locator::new(source_kind.source_code());
 
-    // Extra indices from the code.
-    let indexer = Indexer::from_tokens(&tokens, &locator);
+    let (indexer, directives) = tracing::debug_span!("index").in_scope(|| {
+        // Extra indices from the code.
+        let indexer = Indexer::from_tokens(&tokens, &locator);
 
-    // Extract the `# noqa` and `# isort: skip` directives from the source.
-    let directives = directives::extract_noqa_line_for(&tokens, &locator, &indexer);
+        // Extract the `# noqa` and `# isort: skip` directives from the source.
+        let directives = directives::extract_noqa_line_for(&tokens, &locator, &indexer);
+        (indexer, directives)
+    });
 
     // Generate diagnostics.
     let result = check_path(
@@ -210,25 +388,132 @@ pub fn lint_only(
         noqa,
         source_kind,
         source_type,
-    );
+        module,
+        cancellation,
+    )?;
 
-    result.map(|(diagnostics, imports)| {
+    Ok(result.map(|(diagnostics, imports)| {
         (
-            diagnostics_to_messages(settings, diagnostics, path, &locator, &directives),
+            diagnostics_to_messages(
+                settings,
+                diagnostics,
+                path,
+                &locator,
+                source_kind,
+                &directives,
+            ),
             imports,
         )
-    })
+    }))
+}
+
+/// The maximum number of times the source is checked and fixed before giving up on reaching a
+/// state without fixes.
+const MAX_ITERATIONS: usize = 100;
+
+/// The result of [`lint_fix`].
+pub struct FixerResult {
+    /// The messages of the fixed source.
+    pub result: CheckerResult<(Vec<Message>, Option<ImportMap>)>,
+    /// The fixed source.
+    pub transformed: SourceKind,
+    /// The number of fixes applied for each error code.
+    pub fixed: FixTable,
+}
+
+/// Check the source code and apply the fixes of the resulting messages, and repeat until there's
+/// nothing left to fix.
+///
+/// Fails with [`Cancelled`] if `cancellation` is cancelled before the fixes are done.
+#[allow(clippy::too_many_arguments)]
+pub fn lint_fix(
+    path: &Path,
+    package: Option<&Path>,
+    settings: &CheckerSettings,
+    noqa: flags::TypeIgnore,
+    source_kind: &SourceKind,
+    source_type: PySourceType,
+    module: Option<&ModuleContext>,
+    cancellation: &CancellationToken,
+) -> Result<FixerResult> {
+    let mut transformed = source_kind.clone();
+    let mut fixed = FixTable::default();
+    let mut is_valid_syntax = true;
+    let mut iteration = 0;
+
+    loop {
+        let result = lint_only(
+            path,
+            package,
+            settings,
+            noqa,
+            &transformed,
+            source_type,
+            module,
+            cancellation,
+        )?;
+
+        if iteration == 0 {
+            is_valid_syntax = result.error.is_none();
+        } else if is_valid_syntax && result.error.is_some() {
+            bail!(
+                "Fixes introduced a syntax error in {}",
+                fs::relativize_path(path)
+            );
+        }
+
+        let fix_result = apply_fixes(
+            result.data.0.iter(),
+            &Locator::new(transformed.original_source_code()),
+        );
+        if fix_result.fixes.is_empty() {
+            return Ok(FixerResult {
+                result,
+                transformed,
+                fixed,
+            });
+        }
+        if iteration == MAX_ITERATIONS {
+            warn!(
+                "Failed to converge after {MAX_ITERATIONS} iterations in {}",
+                fs::relativize_path(path)
+            );
+            return Ok(FixerResult {
+                result,
+                transformed,
+                fixed,
+            });
+        }
+
+        for (code, count) in fix_result.fixes {
+            *fixed.entry(code).or_default() += count;
+        }
+        transformed = transformed.updated(fix_result.code);
+        iteration += 1;
+    }
 }
 
 /// Convert from diagnostics to messages.
+///
+/// The diagnostics refer to the normalized source, while the messages refer to the original
+/// source of the file.
 fn diagnostics_to_messages(
     settings: &CheckerSettings,
     diagnostics: Vec<Diagnostic>,
     path: &Path,
     locator: &Locator,
+    source_kind: &SourceKind,
     noqa_mapping: &TypeIgnoreMapping,
 ) -> Vec<Message> {
     let file = once_cell::unsync::Lazy::new(|| {
+        if source_kind.is_normalized() {
+            return SourceFileBuilder::new(
+                path.to_string_lossy().as_ref(),
+                source_kind.original_source_code(),
+            )
+            .finish();
+        }
+
         let mut builder =
             SourceFileBuilder::new(path.to_string_lossy().as_ref(), locator.contents());
 
@@ -241,14 +526,265 @@ fn diagnostics_to_messages(
 
     diagnostics
         .into_iter()
-        .map(|diagnostic| {
-            let kind = if settings.table.is_warning(diagnostic.kind.error_code()) {
-                MessageKind::Warning
-            } else {
-                MessageKind::Error
-            };
-            let noqa_offset = noqa_mapping.resolve(diagnostic.start());
+        .map(|mut diagnostic| {
+            let kind = settings.table.kind(diagnostic.kind.error_code());
+            let noqa_offset =
+                source_kind.to_original_offset(noqa_mapping.resolve(diagnostic.start()));
+            diagnostic.range = source_kind.to_original_range(diagnostic.range);
+            diagnostic.fix = diagnostic.fix.map(|fix| source_kind.to_original_fix(&fix));
+            diagnostic.parent = diagnostic
+                .parent
+                .map(|parent| source_kind.to_original_offset(parent));
             Message::from_diagnostic(diagnostic, file.deref().clone(), noqa_offset, kind)
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use rustpython_parser::ast::Ranged;
+
+    use pyrogen_python_ast::PySourceType;
+
+    use crate::cancellation::{CancellationToken, Cancelled};
+    use crate::registry::ErrorCode;
+    use crate::settings::{flags, CheckerSettings};
+    use crate::source_kind::SourceKind;
+    use crate::test::test_resource_path;
+
+    use super::{lint_fix, lint_only};
+
+    #[test]
+    fn mixed_line_endings() {
+        let path = test_resource_path("fixtures/line_endings/mixed_eol.py");
+        let source_kind = SourceKind::new(std::fs::read_to_string(&path).unwrap());
+        let (mut messages, _imports) = lint_only(
+            Path::new("mixed_eol.py"),
+            None,
+            &CheckerSettings::for_rules(vec![ErrorCode::DuplicateKey, ErrorCode::UnusedTypeIgnore]),
+            flags::TypeIgnore::Enabled,
+            &source_kind,
+            PySourceType::Python,
+            None,
+            &CancellationToken::default(),
+        )
+        .unwrap()
+        .data;
+        messages.sort_by_key(|message| message.range.start());
+
+        let original = source_kind.original_source_code();
+        let locations: Vec<_> = messages
+            .iter()
+            .map(|message| {
+                let location = message.compute_start_location();
+                (
+                    message.diagnostic.error_code,
+                    &original[message.range],
+                    location.row.get(),
+                    location.column.get(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            locations,
+            [
+                (ErrorCode::DuplicateKey, "1", 1, 13),
+                (ErrorCode::UnusedTypeIgnore, "# type: ignore", 2, 9),
+                (ErrorCode::DuplicateKey, "'ä'", 3, 15),
+            ]
+        );
+
+        // The fix keeps the `\r\n` line ending of the line.
+        let fix = messages[1].fix.as_ref().unwrap();
+        let [edit] = fix.edits() else {
+            panic!("expected a single edit");
+        };
+        let mut fixed = original.to_string();
+        fixed.replace_range(
+            std::ops::Range::<usize>::from(edit.range()),
+            edit.content().unwrap_or_default(),
+        );
+        assert_eq!(
+            fixed,
+            "\u{feff}_a = {1: 1, 1: 2}\r\n_b = 1\r\n_c = {'ä': 1, 'ä': 2}\r_d = 3\n"
+        );
+    }
+
+    #[test]
+    fn fix_unused_type_ignores() {
+        let source_kind = SourceKind::new(
+            "_a = 1  # type: ignore\r\n_b = {1: 1, 1: 2}  # type: ignore\r\n".to_string(),
+        );
+        let result = lint_fix(
+            Path::new("module.py"),
+            None,
+            &CheckerSettings::for_rules(vec![ErrorCode::DuplicateKey, ErrorCode::UnusedTypeIgnore]),
+            flags::TypeIgnore::Enabled,
+            &source_kind,
+            PySourceType::Python,
+            None,
+            &CancellationToken::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            result.transformed.original_source_code(),
+            "_a = 1\r\n_b = {1: 1, 1: 2}  # type: ignore\r\n"
+        );
+        assert_eq!(result.fixed.get(&ErrorCode::UnusedTypeIgnore), Some(&1));
+        assert!(result.result.data.0.is_empty());
+    }
+
+    #[test]
+    fn expired_suppressions() {
+        let source_kind = SourceKind::new(
+            "_a = {1: 1, 1: 2}  # type: ignore[duplicate-key]  # pyrogen: expires=2000-01-01\n\
+             _b = {1: 1, 1: 2}  # type: ignore[duplicate-key]  # pyrogen: expires=2999-12-31\n\
+             _c = 1  # type: ignore  # pyrogen: expires=2000-01-01\n"
+                .to_string(),
+        );
+        let (mut messages, _imports) = lint_only(
+            Path::new("module.py"),
+            None,
+            &CheckerSettings::for_rules(vec![
+                ErrorCode::DuplicateKey,
+                ErrorCode::UnusedTypeIgnore,
+                ErrorCode::ExpiredSuppression,
+            ]),
+            flags::TypeIgnore::Enabled,
+            &source_kind,
+            PySourceType::Python,
+            None,
+            &CancellationToken::default(),
+        )
+        .unwrap()
+        .data;
+        messages.sort_by_key(|message| {
+            (
+                message.range.start(),
+                message.diagnostic.error_code.to_str(),
+            )
+        });
+
+        // Expired directives don't suppress anything, and aren't reported as unused.
+        let codes: Vec<_> = messages
+            .iter()
+            .map(|message| {
+                (
+                    message.diagnostic.error_code,
+                    message.compute_start_location().row.get(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                (ErrorCode::DuplicateKey, 1),
+                (ErrorCode::ExpiredSuppression, 1),
+                (ErrorCode::ExpiredSuppression, 3),
+            ]
+        );
+        assert_eq!(
+            messages[1].diagnostic.body,
+            "Type ignore directive is past its expiry date 2000-01-01"
+        );
+    }
+
+    #[test]
+    fn type_ignores_in_pruned_branches() {
+        let source_kind = SourceKind::new(
+            "import sys\n\
+             if sys.version_info < (3, 8):\n    \
+                 _a = {1: 1, 1: 2}  # type: ignore\n\
+             else:\n    \
+                 _b = 1  # type: ignore\n\
+             _c = 1  # type: ignore\n"
+                .to_string(),
+        );
+        let (messages, _imports) = lint_only(
+            Path::new("module.py"),
+            None,
+            &CheckerSettings::for_rules(vec![ErrorCode::DuplicateKey, ErrorCode::UnusedTypeIgnore]),
+            flags::TypeIgnore::Enabled,
+            &source_kind,
+            PySourceType::Python,
+            None,
+            &CancellationToken::default(),
+        )
+        .unwrap()
+        .data;
+
+        // The directive in the branch for older versions may be needed there.
+        let mut codes: Vec<_> = messages
+            .iter()
+            .map(|message| {
+                (
+                    message.diagnostic.error_code,
+                    message.compute_start_location().row.get(),
+                )
+            })
+            .collect();
+        codes.sort_unstable_by_key(|(_, row)| *row);
+        assert_eq!(
+            codes,
+            [
+                (ErrorCode::UnusedTypeIgnore, 5),
+                (ErrorCode::UnusedTypeIgnore, 6),
+            ]
+        );
+    }
+
+    #[test]
+    fn cancelled_check() {
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        let result = lint_only(
+            Path::new("module.py"),
+            None,
+            &CheckerSettings::for_rules(vec![ErrorCode::DuplicateKey]),
+            flags::TypeIgnore::Enabled,
+            &SourceKind::new("_a = {1: 1, 1: 2}\n".to_string()),
+            PySourceType::Python,
+            None,
+            &cancellation,
+        );
+        assert_eq!(result.err(), Some(Cancelled));
+    }
+
+    #[test]
+    fn truncated_diagnostics() {
+        let source_kind = SourceKind::new("_a = {1: 1, 1: 2}\n".repeat(4));
+        let settings = CheckerSettings {
+            max_diagnostics_per_file: 3,
+            ..CheckerSettings::for_rules(vec![
+                ErrorCode::DuplicateKey,
+                ErrorCode::TooManyDiagnostics,
+            ])
+        };
+        let (messages, _imports) = lint_only(
+            Path::new("module.py"),
+            None,
+            &settings,
+            flags::TypeIgnore::Enabled,
+            &source_kind,
+            PySourceType::Python,
+            None,
+            &CancellationToken::default(),
+        )
+        .unwrap()
+        .data;
+
+        assert_eq!(messages.len(), 4);
+        let notice = messages
+            .iter()
+            .find(|message| message.diagnostic.error_code == ErrorCode::TooManyDiagnostics)
+            .unwrap();
+        assert_eq!(
+            notice.diagnostic.body,
+            "1 more diagnostics suppressed, since the file has more than 3"
+        );
+        assert_eq!(notice.compute_start_location().row.get(), 1);
+    }
+}
@@ -5,12 +5,15 @@ use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-use crate::registry::{ErrorCode, ErrorCodeIter};
+use crate::registry::{Category, ErrorCode, ErrorCodeIter};
+use crate::settings::types::PreviewMode;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ErrorCodeSelector {
     /// Select all error codes.
     All,
+    /// Select all error codes of a category.
+    Category(Category),
     /// Select an individual error code.
     ErrorCode(ErrorCode),
 }
@@ -22,10 +25,14 @@ impl FromStr for ErrorCodeSelector {
         match s {
             "ALL" => Ok(Self::All),
             _ => {
-                // Does the selector select a single error code?
-                let error_code =
-                    ErrorCode::from_str(s).map_err(|_| ParseError::Unknown(s.to_string()))?;
-                Ok(Self::ErrorCode(error_code))
+                // Does the selector select a single error code, or a whole category?
+                if let Ok(error_code) = ErrorCode::from_str(s) {
+                    Ok(Self::ErrorCode(error_code))
+                } else {
+                    let category =
+                        Category::from_str(s).map_err(|_| ParseError::Unknown(s.to_string()))?;
+                    Ok(Self::Category(category))
+                }
             }
         }
     }
@@ -41,6 +48,7 @@ impl ErrorCodeSelector {
     pub fn code(&self) -> &'static str {
         match self {
             ErrorCodeSelector::All => "ALL",
+            ErrorCodeSelector::Category(category) => category.into(),
             ErrorCodeSelector::ErrorCode(rule) => rule.to_str(),
         }
     }
@@ -76,7 +84,7 @@ impl Visitor<'_> for SelectorVisitor {
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
         formatter.write_str(
-            "expected a string code identifying a linter or specific rule, or a partial rule code or ALL to refer to all rules",
+            "expected a string code identifying a specific rule, a category of rules, or ALL to refer to all rules",
         )
     }
 
@@ -93,6 +101,12 @@ impl ErrorCodeSelector {
     pub fn all_rules(&self) -> impl Iterator<Item = ErrorCode> + '_ {
         match self {
             ErrorCodeSelector::All => ErrorCodeSelectorIter::All(ErrorCode::iter()),
+            ErrorCodeSelector::Category(category) => ErrorCodeSelectorIter::Vec(
+                ErrorCode::iter()
+                    .filter(|rule| rule.category() == *category)
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            ),
 
             ErrorCodeSelector::ErrorCode(rule) => {
                 ErrorCodeSelectorIter::Vec(vec![*rule].into_iter())
@@ -100,9 +114,11 @@ impl ErrorCodeSelector {
         }
     }
 
-    /// Returns rules matching the selector, taking into account preview options enabled.
-    pub fn rules(&self) -> impl Iterator<Item = ErrorCode> + '_ {
+    /// Returns rules matching the selector, without the ones in preview unless `preview` is
+    /// enabled. This applies to selectors of individual codes too.
+    pub fn rules(&self, preview: PreviewMode) -> impl Iterator<Item = ErrorCode> + '_ {
         self.all_rules()
+            .filter(move |rule| preview.is_enabled() || !rule.is_preview())
     }
 }
 
@@ -125,9 +141,9 @@ impl Iterator for ErrorCodeSelectorIter {
 #[cfg(feature = "schemars")]
 mod schema {
     use itertools::Itertools;
-    use schemars::JsonSchema;
     use schemars::_serde_json::Value;
     use schemars::schema::{InstanceType, Schema, SchemaObject};
+    use schemars::JsonSchema;
     use strum::IntoEnumIterator;
 
     use crate::code_selector::{Linter, RuleCodePrefix};
@@ -184,6 +200,7 @@ impl ErrorCodeSelector {
     pub fn specificity(&self) -> Specificity {
         match self {
             ErrorCodeSelector::All => Specificity::All,
+            ErrorCodeSelector::Category(_) => Specificity::Category,
             ErrorCodeSelector::ErrorCode { .. } => Specificity::Rule,
         }
     }
@@ -193,6 +210,8 @@ impl ErrorCodeSelector {
 pub enum Specificity {
     /// The specificity when selecting all rules (e.g., `--select ALL`).
     All,
+    /// The specificity when selecting a category of rules (e.g., `--error imports`).
+    Category,
     // /// The specificity when selecting a legacy linter group (e.g., `--select C` or `--select T`).
     // LinterGroup,
     /// The specificity when selecting an individual rule (e.g., `--select PLE1205`).
@@ -204,7 +223,10 @@ pub mod clap_completion {
     use clap::builder::{PossibleValue, TypedValueParser, ValueParserFactory};
     use strum::IntoEnumIterator;
 
-    use crate::{registry::ErrorCode, ErrorCodeSelector};
+    use crate::{
+        registry::{Category, ErrorCode},
+        ErrorCodeSelector,
+    };
 
     #[derive(Clone)]
     pub struct ErrorCodeSelectorParser;
@@ -249,12 +271,14 @@ pub mod clap_completion {
 
         fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
             Some(Box::new(
-                std::iter::once(PossibleValue::new("ALL").help("all rules")).chain(
-                    ErrorCode::iter().map(|rule| {
+                std::iter::once(PossibleValue::new("ALL").help("all rules"))
+                    .chain(Category::iter().map(|category| {
+                        PossibleValue::new(category.to_string()).help("a category of rules")
+                    }))
+                    .chain(ErrorCode::iter().map(|rule| {
                         let name = rule.to_string();
                         PossibleValue::new(name)
-                    }),
-                ),
+                    })),
             ))
         }
     }
@@ -0,0 +1,272 @@
+//! An index of the functions, classes and variables that a module defines, for looking up
+//! symbols by name across a project.
+//!
+//! Definitions at the top level of a module and in class bodies (including nested classes) are
+//! indexed, along with the control flow (`if`, `try`, ...) around them. Definitions local to a
+//! function aren't, since they can't be referenced from outside of it.
+
+use std::fmt;
+
+use rustpython_ast::{self as ast, Expr, Ranged, Stmt};
+use rustpython_parser::text_size::{TextLen, TextRange, TextSize};
+
+use pyrogen_python_ast::{AsMode, PySourceType};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DefinitionKind {
+    Function,
+    /// A function defined in a class body.
+    Method,
+    Class,
+    Variable,
+}
+
+impl fmt::Display for DefinitionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Function => "function",
+            Self::Method => "method",
+            Self::Class => "class",
+            Self::Variable => "variable",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Definition {
+    pub name: String,
+    /// The name qualified by the module and the enclosing classes, like `pkg.mod.Class.method`.
+    pub qualified_name: String,
+    pub kind: DefinitionKind,
+    /// The range of the defined name.
+    pub range: TextRange,
+}
+
+struct DefinitionCollector<'a> {
+    source: &'a str,
+    /// The qualified name of the current scope.
+    scope: String,
+    in_class: bool,
+    definitions: Vec<Definition>,
+}
+
+impl DefinitionCollector<'_> {
+    fn define(&mut self, name: &str, kind: DefinitionKind, range: TextRange) {
+        let qualified_name = if self.scope.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{name}", self.scope)
+        };
+        self.definitions.push(Definition {
+            name: name.to_string(),
+            qualified_name,
+            kind,
+            range,
+        });
+    }
+
+    fn define_target(&mut self, target: &Expr) {
+        match target {
+            Expr::Name(ast::ExprName { id, range, .. }) => {
+                self.define(id.as_str(), DefinitionKind::Variable, *range);
+            }
+            Expr::Tuple(ast::ExprTuple { elts, .. }) | Expr::List(ast::ExprList { elts, .. }) => {
+                for elt in elts {
+                    self.define_target(elt);
+                }
+            }
+            Expr::Starred(ast::ExprStarred { value, .. }) => self.define_target(value),
+            _ => {}
+        }
+    }
+
+    fn visit_body(&mut self, body: &[Stmt]) {
+        for stmt in body {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef { name, .. })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { name, .. }) => {
+                let kind = if self.in_class {
+                    DefinitionKind::Method
+                } else {
+                    DefinitionKind::Function
+                };
+                self.define(
+                    name.as_str(),
+                    kind,
+                    identifier_range(stmt, name.as_str(), self.source),
+                );
+            }
+            Stmt::ClassDef(ast::StmtClassDef { name, body, .. }) => {
+                self.define(
+                    name.as_str(),
+                    DefinitionKind::Class,
+                    identifier_range(stmt, name.as_str(), self.source),
+                );
+                let scope = std::mem::take(&mut self.scope);
+                self.scope = if scope.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{scope}.{}", name.as_str())
+                };
+                let in_class = std::mem::replace(&mut self.in_class, true);
+                self.visit_body(body);
+                self.in_class = in_class;
+                self.scope = scope;
+            }
+            Stmt::Assign(ast::StmtAssign { targets, .. }) => {
+                for target in targets {
+                    self.define_target(target);
+                }
+            }
+            Stmt::AnnAssign(ast::StmtAnnAssign { target, .. }) => self.define_target(target),
+            Stmt::If(ast::StmtIf { body, orelse, .. })
+            | Stmt::While(ast::StmtWhile { body, orelse, .. })
+            | Stmt::For(ast::StmtFor { body, orelse, .. })
+            | Stmt::AsyncFor(ast::StmtAsyncFor { body, orelse, .. }) => {
+                self.visit_body(body);
+                self.visit_body(orelse);
+            }
+            Stmt::With(ast::StmtWith { body, .. })
+            | Stmt::AsyncWith(ast::StmtAsyncWith { body, .. }) => self.visit_body(body),
+            Stmt::Try(ast::StmtTry {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            })
+            | Stmt::TryStar(ast::StmtTryStar {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            }) => {
+                self.visit_body(body);
+                for handler in handlers {
+                    let ast::ExceptHandler::ExceptHandler(handler) = handler;
+                    self.visit_body(&handler.body);
+                }
+                self.visit_body(orelse);
+                self.visit_body(finalbody);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The range of the name of a function or class definition, which the AST doesn't record.
+///
+/// Only keywords separate the end of the decorators from the name, so the name is the first
+/// occurrence of the identifier after them. Falls back to the range of the whole statement.
+pub(crate) fn identifier_range(stmt: &Stmt, name: &str, source: &str) -> TextRange {
+    let range = stmt.range();
+    let decorators_end = match stmt {
+        Stmt::FunctionDef(ast::StmtFunctionDef { decorator_list, .. })
+        | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { decorator_list, .. })
+        | Stmt::ClassDef(ast::StmtClassDef { decorator_list, .. }) => {
+            decorator_list.last().map_or(range.start(), Ranged::end)
+        }
+        _ => range.start(),
+    };
+    let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_';
+    let Some(text) = source.get(usize::from(decorators_end)..usize::from(range.end())) else {
+        return range;
+    };
+    text.match_indices(name)
+        .find(|(offset, _)| {
+            !text[..*offset].ends_with(is_identifier_char)
+                && !text[offset + name.len()..].starts_with(is_identifier_char)
+        })
+        .and_then(|(offset, _)| {
+            let start = decorators_end + TextSize::try_from(offset).ok()?;
+            Some(TextRange::at(start, name.text_len()))
+        })
+        .unwrap_or(range)
+}
+
+/// Collect the definitions in `python_ast`, the body of the module `module`.
+pub fn collect_definitions(python_ast: &[Stmt], source: &str, module: &str) -> Vec<Definition> {
+    let mut collector = DefinitionCollector {
+        source,
+        scope: module.to_string(),
+        in_class: false,
+        definitions: vec![],
+    };
+    collector.visit_body(python_ast);
+    collector.definitions
+}
+
+/// Parse `source` and collect its definitions. Returns `None` if `source` contains syntax errors.
+pub fn find_definitions(
+    source: &str,
+    source_type: PySourceType,
+    module: &str,
+) -> Option<Vec<Definition>> {
+    let python_ast = rustpython_parser::parse(source, source_type.as_mode(), module)
+        .ok()?
+        .expect_module()
+        .body;
+    Some(collect_definitions(&python_ast, source, module))
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrogen_python_ast::PySourceType;
+
+    use super::{find_definitions, DefinitionKind};
+
+    #[test]
+    fn definitions() {
+        let source = r#"
+import os
+
+VERSION = "1.0"
+
+@decorator(name)
+class name:
+    attribute: int = 0
+
+    def method(self):
+        local = 1
+
+    class Nested:
+        async def method(self): ...
+
+if os.name == "nt":
+    def helper(): ...
+else:
+    helper, other = None, None
+"#;
+        let definitions = find_definitions(source, PySourceType::Python, "pkg.mod").unwrap();
+        assert_eq!(
+            definitions
+                .iter()
+                .map(|definition| (definition.kind, definition.qualified_name.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                (DefinitionKind::Variable, "pkg.mod.VERSION"),
+                (DefinitionKind::Class, "pkg.mod.name"),
+                (DefinitionKind::Variable, "pkg.mod.name.attribute"),
+                (DefinitionKind::Method, "pkg.mod.name.method"),
+                (DefinitionKind::Class, "pkg.mod.name.Nested"),
+                (DefinitionKind::Method, "pkg.mod.name.Nested.method"),
+                (DefinitionKind::Function, "pkg.mod.helper"),
+                (DefinitionKind::Variable, "pkg.mod.helper"),
+                (DefinitionKind::Variable, "pkg.mod.other"),
+            ]
+        );
+        // The range of the class name skips the decorator, which mentions the same name.
+        let class = &definitions[1];
+        assert_eq!(&source[class.range], "name");
+        assert_eq!(
+            source[..class.range.start().into()].lines().last(),
+            Some("class ")
+        );
+    }
+}
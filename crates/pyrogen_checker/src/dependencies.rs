@@ -0,0 +1,336 @@
+//! The third-party packages that a project imports, compared with the dependencies that its
+//! `pyproject.toml` declares.
+//!
+//! Imports are mapped to distributions with the metadata of the installed packages, so
+//! `import yaml` is provided by `pyyaml`. Modules without metadata (like stubs) are assumed to be
+//! provided by the distribution of the same name.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use rustc_hash::FxHashSet;
+use rustpython_ast::text_size::{TextRange, TextSize};
+use rustpython_ast::{self as ast, Stmt};
+use serde::Deserialize;
+use toml::Spanned;
+
+use pyrogen_module_resolver::{
+    installed_distributions, normalize_distribution_name, ModuleName, ModuleOrigin, ModuleResolver,
+};
+use pyrogen_python_ast::imports::catches_import_error;
+use pyrogen_python_ast::visitor::{self, Visitor};
+use pyrogen_python_ast::PySourceType;
+use pyrogen_source_file::SourceFile;
+
+use crate::check::typecheck::type_checking::is_type_checking_guard;
+use crate::message::Message;
+use crate::registry::{Diagnostic, ErrorCode};
+use crate::settings::CheckerSettings;
+use crate::violation::Violation;
+
+/// An absolute import of an installed third-party module.
+#[derive(Debug)]
+pub struct ThirdPartyImport<'a> {
+    /// The first component of the imported module.
+    pub module: &'a str,
+    pub range: TextRange,
+    /// Whether the import is optional at runtime: guarded by a `try` that catches `ImportError`,
+    /// or only made under `TYPE_CHECKING`.
+    pub optional: bool,
+}
+
+struct ImportCollector<'a, 'r> {
+    resolver: &'r ModuleResolver,
+    optional: bool,
+    imports: Vec<ThirdPartyImport<'a>>,
+}
+
+impl<'a> ImportCollector<'a, '_> {
+    fn add(&mut self, module: &'a str, range: TextRange) {
+        let module = module.split('.').next().unwrap_or(module);
+        let Some(name) = ModuleName::new(module) else {
+            return;
+        };
+        if self
+            .resolver
+            .resolve(&name)
+            .is_some_and(|resolved| resolved.origin == ModuleOrigin::ThirdParty)
+        {
+            self.imports.push(ThirdPartyImport {
+                module,
+                range,
+                optional: self.optional,
+            });
+        }
+    }
+
+    fn visit_optional(&mut self, optional: bool, body: &'a [Stmt]) {
+        let outer = self.optional;
+        self.optional |= optional;
+        self.visit_body(body);
+        self.optional = outer;
+    }
+}
+
+impl<'a> Visitor<'a> for ImportCollector<'a, '_> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                for alias in names {
+                    self.add(alias.name.as_str(), alias.range);
+                }
+            }
+            Stmt::ImportFrom(ast::StmtImportFrom {
+                module: Some(module),
+                level,
+                range,
+                ..
+            }) if level.map_or(true, |level| level.to_u32() == 0) => {
+                self.add(module.as_str(), *range);
+            }
+            Stmt::If(ast::StmtIf {
+                test, body, orelse, ..
+            }) if is_type_checking_guard(test) => {
+                self.visit_optional(true, body);
+                self.visit_body(orelse);
+            }
+            Stmt::Try(ast::StmtTry {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            })
+            | Stmt::TryStar(ast::StmtTryStar {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            }) => {
+                self.visit_optional(handlers.iter().any(catches_import_error), body);
+                for handler in handlers {
+                    self.visit_except_handler(handler);
+                }
+                self.visit_body(orelse);
+                self.visit_body(finalbody);
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+}
+
+/// The absolute imports in `python_ast` of modules that `resolver` finds outside of the project
+/// and the standard library.
+pub fn third_party_imports<'a>(
+    python_ast: &'a [Stmt],
+    resolver: &ModuleResolver,
+) -> Vec<ThirdPartyImport<'a>> {
+    let mut collector = ImportCollector {
+        resolver,
+        optional: false,
+        imports: vec![],
+    };
+    collector.visit_body(python_ast);
+    collector.imports
+}
+
+/// The normalized name of the distribution that the PEP 508 `requirement` refers to, e.g.
+/// `requests` for `requests[socks]>=2.31`.
+pub fn requirement_name(requirement: &str) -> Option<String> {
+    let end = requirement
+        .find(|char: char| !(char.is_ascii_alphanumeric() || "-_.".contains(char)))
+        .unwrap_or(requirement.len());
+    let name = &requirement[..end];
+    (!name.is_empty()).then(|| normalize_distribution_name(name))
+}
+
+/// The distributions that provide the top-level modules installed into `site-packages`.
+#[derive(Debug, Default)]
+pub struct Distributions {
+    modules: BTreeMap<String, Vec<String>>,
+}
+
+impl Distributions {
+    pub fn new(site_packages: &[PathBuf]) -> Self {
+        Self {
+            modules: installed_distributions(site_packages),
+        }
+    }
+
+    /// The normalized names of the distributions that provide the top-level `module`.
+    pub fn providing(&self, module: &str) -> Vec<String> {
+        self.modules
+            .get(module)
+            .cloned()
+            .unwrap_or_else(|| vec![normalize_distribution_name(module)])
+    }
+}
+
+/// The distributions of the third-party modules that `source` imports. Returns `None` if
+/// `source` contains syntax errors.
+pub fn used_distributions(
+    source: &str,
+    source_type: PySourceType,
+    settings: &CheckerSettings,
+    distributions: &Distributions,
+) -> Option<Vec<String>> {
+    let python_ast = rustpython_parser::parse(source, source_type.as_mode(), "<dependencies>")
+        .ok()?
+        .expect_module()
+        .body;
+    Some(
+        third_party_imports(&python_ast, settings.module_resolver())
+            .iter()
+            .flat_map(|import| distributions.providing(import.module))
+            .collect(),
+    )
+}
+
+#[derive(Deserialize)]
+struct DeclaredDependencies {
+    project: Option<ProjectDependencies>,
+}
+
+#[derive(Deserialize)]
+struct ProjectDependencies {
+    dependencies: Option<Vec<Spanned<String>>>,
+}
+
+#[derive(Violation)]
+#[violation(code = UnusedDependency)]
+struct UnusedDependency<'a> {
+    name: &'a str,
+}
+
+/// Report the entries of `project.dependencies` in the `pyproject.toml` `source_file` that
+/// aren't among the `used` distributions.
+pub fn unused_dependencies(
+    source_file: &SourceFile,
+    used: &FxHashSet<String>,
+    settings: &CheckerSettings,
+) -> Vec<Message> {
+    let Some(kind) = settings.table.entry(ErrorCode::UnusedDependency) else {
+        return vec![];
+    };
+    let Ok(declared) = toml::from_str::<DeclaredDependencies>(source_file.source_text()) else {
+        return vec![];
+    };
+    let requirements = declared
+        .project
+        .and_then(|project| project.dependencies)
+        .unwrap_or_default();
+
+    let mut messages = vec![];
+    for requirement in &requirements {
+        let Some(name) = requirement_name(requirement.get_ref()) else {
+            continue;
+        };
+        if used.contains(&name) {
+            continue;
+        }
+        let span = requirement.span();
+        let (Ok(start), Ok(end)) = (TextSize::try_from(span.start), TextSize::try_from(span.end))
+        else {
+            continue;
+        };
+        let diagnostic =
+            Diagnostic::new(UnusedDependency { name: &name }, TextRange::new(start, end));
+        messages.push(Message::from_diagnostic(
+            diagnostic,
+            source_file.clone(),
+            TextSize::default(),
+            kind,
+        ));
+    }
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, write};
+
+    use anyhow::Result;
+    use rustc_hash::FxHashSet;
+    use rustpython_parser::{parse, Mode};
+    use tempfile::TempDir;
+
+    use pyrogen_module_resolver::{ModuleResolver, ModuleResolverSettings};
+    use pyrogen_source_file::SourceFileBuilder;
+
+    use crate::registry::ErrorCode;
+    use crate::settings::CheckerSettings;
+
+    use super::{third_party_imports, unused_dependencies, Distributions};
+
+    #[test]
+    fn imports_and_distributions() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let site_packages = tmp_dir.path().join("site-packages");
+        create_dir_all(site_packages.join("yaml"))?;
+        write(site_packages.join("yaml/__init__.py"), "")?;
+        write(site_packages.join("six.py"), "")?;
+        create_dir_all(site_packages.join("PyYAML-6.0.1.dist-info"))?;
+        write(
+            site_packages.join("PyYAML-6.0.1.dist-info/top_level.txt"),
+            "yaml\n",
+        )?;
+
+        let resolver = ModuleResolver::new(&ModuleResolverSettings {
+            site_packages: vec![site_packages.clone()],
+            vendored_typeshed: true,
+            ..ModuleResolverSettings::default()
+        });
+        let source = r"
+import os
+import yaml.constructor
+from six import moves
+import missing
+
+try:
+    import six
+except ImportError:
+    six = None
+";
+        let python_ast = parse(source, Mode::Module, "<test>")?.expect_module().body;
+        let imports = third_party_imports(&python_ast, &resolver);
+        assert_eq!(
+            imports
+                .iter()
+                .map(|import| (import.module, import.optional))
+                .collect::<Vec<_>>(),
+            [("yaml", false), ("six", false), ("six", true)]
+        );
+
+        let distributions = Distributions::new(&[site_packages]);
+        assert_eq!(distributions.providing("yaml"), ["pyyaml"]);
+        assert_eq!(distributions.providing("six"), ["six"]);
+        Ok(())
+    }
+
+    #[test]
+    fn unused() {
+        let source = r#"
+[project]
+name = "pkg"
+dependencies = ["PyYAML>=6", "click"]
+"#;
+        let source_file = SourceFileBuilder::new("pyproject.toml", source).finish();
+        let used = FxHashSet::from_iter(["pyyaml".to_string()]);
+        let messages = unused_dependencies(
+            &source_file,
+            &used,
+            &CheckerSettings::for_rule(ErrorCode::UnusedDependency),
+        );
+        assert_eq!(
+            messages
+                .iter()
+                .map(|message| (message.diagnostic.body.as_str(), &source[message.range]))
+                .collect::<Vec<_>>(),
+            [(
+                "Dependency `click` isn't imported by any checked file",
+                "\"click\""
+            )]
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! Explain which diagnostics fire on a given line and what happens to each of them, to help
+//! debug configuration and `type: ignore` comments.
+
+use std::path::Path;
+
+use itertools::Itertools;
+use rustpython_parser::ast::Ranged;
+use strum::IntoEnumIterator;
+
+use pyrogen_python_ast::{AsMode, PySourceType};
+use pyrogen_python_index::Indexer;
+use pyrogen_source_file::{Locator, OneIndexed};
+
+use crate::cancellation::CancellationToken;
+use crate::checker::check_path;
+use crate::directives;
+use crate::fs;
+use crate::interface::ModuleContext;
+use crate::registry::{AsErrorCode, ErrorCode};
+use crate::settings::code_table::{ErrorCodeTable, MessageKind};
+use crate::settings::{flags, CheckerSettings};
+use crate::source_kind::SourceKind;
+use crate::type_ignore::{self, Directive, FileExemption, TypeIgnores};
+
+/// What happens to a diagnostic when the file is checked with the actual settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The diagnostic is reported with the given severity.
+    Reported(MessageKind),
+    /// The code of the diagnostic isn't selected.
+    Disabled,
+    /// The code of the diagnostic is ignored for this file by `per-file-ignores`.
+    PerFileIgnored,
+    /// The diagnostic is suppressed by a file-level `type: ignore` comment.
+    FileExempted,
+    /// The diagnostic is suppressed by the given `type: ignore` comment.
+    Suppressed(String),
+}
+
+/// A diagnostic that starts on the explained line.
+#[derive(Debug)]
+pub struct Finding {
+    pub code: ErrorCode,
+    pub column: OneIndexed,
+    pub body: String,
+    pub outcome: Outcome,
+}
+
+/// The diagnostics on a single line of a file.
+#[derive(Debug)]
+pub struct Explanation {
+    pub line: OneIndexed,
+    /// The text of the line, without its line ending.
+    pub source_line: String,
+    /// The `type: ignore` comment that applies to the line, if any.
+    pub directive: Option<String>,
+    /// Every diagnostic on the line, including those of codes that aren't selected.
+    pub findings: Vec<Finding>,
+    /// The selected codes that didn't produce a diagnostic on the line.
+    pub not_triggered: Vec<ErrorCode>,
+}
+
+/// Check the file with every code enabled and no suppressions, and explain what happens to the
+/// diagnostics that start on `line` under the actual `settings`.
+///
+/// Returns `None` if the file has fewer than `line` lines.
+#[allow(clippy::too_many_arguments)]
+pub fn explain_line(
+    path: &Path,
+    package: Option<&Path>,
+    settings: &CheckerSettings,
+    source_kind: &SourceKind,
+    source_type: PySourceType,
+    module: Option<&ModuleContext>,
+    line: OneIndexed,
+) -> Option<Explanation> {
+    let tokens = rustpython_parser::lexer::lex(source_kind.source_code(), source_type.as_mode())
+        .collect::<Vec<_>>();
+    let locator = Locator::new(source_kind.source_code());
+    let indexer = Indexer::from_tokens(&tokens, &locator);
+    let noqa_mapping = directives::extract_noqa_line_for(&tokens, &locator, &indexer);
+
+    let line_index = locator.to_source_code();
+    if line.get() > line_index.line_count() {
+        return None;
+    }
+    let line_start = line_index.line_start(line);
+    let source_line = locator.line(line_start).to_string();
+
+    // Every code is enabled so that the diagnostics of disabled codes show up, too.
+    let permissive = CheckerSettings {
+        table: ErrorCodeTable::from_iter(ErrorCode::iter()),
+        per_file_ignores: vec![],
+        ..settings.clone()
+    };
+    let diagnostics = check_path(
+        path,
+        package,
+        tokens,
+        &locator,
+        &indexer,
+        &noqa_mapping,
+        &permissive,
+        flags::TypeIgnore::Disabled,
+        source_kind,
+        source_type,
+        module,
+        &CancellationToken::default(),
+    )
+    .expect("the check isn't cancelled")
+    .data
+    .0;
+
+    let per_file_ignores = fs::ignores_from_path(path, &settings.per_file_ignores);
+    let exemption =
+        FileExemption::try_extract(locator.contents(), indexer.comment_ranges(), path, &locator);
+    let directives = TypeIgnores::from_commented_ranges(indexer.comment_ranges(), path, &locator);
+    let directive_text = |directive: &Directive| match directive {
+        Directive::All(all) => locator.slice(all.range()).to_string(),
+        Directive::Codes(codes) => locator.slice(codes.range()).to_string(),
+    };
+
+    let findings = diagnostics
+        .iter()
+        .filter(|diagnostic| line_index.line_index(diagnostic.start()) == line)
+        .sorted_by_key(|diagnostic| diagnostic.start())
+        .map(|diagnostic| {
+            let code = diagnostic.kind.error_code();
+            let directive = diagnostic
+                .parent
+                .into_iter()
+                .chain(std::iter::once(diagnostic.start()))
+                .filter_map(|offset| {
+                    directives.find_line_with_directive(noqa_mapping.resolve(offset))
+                })
+                .map(|line| &line.directive)
+                .find(|directive| match directive {
+                    Directive::All(_) => true,
+                    Directive::Codes(codes) => type_ignore::includes(code, codes.codes()),
+                });
+            let outcome = if !settings.table.enabled(code) {
+                Outcome::Disabled
+            } else if per_file_ignores.contains(code) {
+                Outcome::PerFileIgnored
+            } else if let Some(directive) = directive {
+                Outcome::Suppressed(directive_text(directive))
+            } else if exemption.as_ref().is_some_and(|exemption| match exemption {
+                FileExemption::All => true,
+                FileExemption::Codes(codes) => codes.contains(&code),
+            }) {
+                Outcome::FileExempted
+            } else {
+                Outcome::Reported(settings.table.kind(code))
+            };
+            Finding {
+                code,
+                column: line_index.source_location(diagnostic.start()).column,
+                body: diagnostic.kind.body.clone(),
+                outcome,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let not_triggered = settings
+        .table
+        .iter_enabled()
+        .filter(|code| findings.iter().all(|finding| finding.code != *code))
+        .collect();
+
+    Some(Explanation {
+        line,
+        source_line,
+        directive: directives
+            .find_line_with_directive(noqa_mapping.resolve(line_start))
+            .map(|line| directive_text(&line.directive)),
+        findings,
+        not_triggered,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use pyrogen_python_ast::PySourceType;
+    use pyrogen_source_file::OneIndexed;
+
+    use crate::registry::ErrorCode;
+    use crate::settings::code_table::{ErrorCodeTable, MessageKind};
+    use crate::settings::CheckerSettings;
+    use crate::source_kind::SourceKind;
+
+    use super::{explain_line, Explanation, Outcome};
+
+    fn explain(source: &str, line: usize) -> Option<Explanation> {
+        let mut settings = CheckerSettings::new(Path::new("."));
+        settings.table = ErrorCodeTable::from_iter([ErrorCode::DuplicateKey]);
+        explain_line(
+            Path::new("test.py"),
+            None,
+            &settings,
+            &SourceKind::new(source.to_string()),
+            PySourceType::Python,
+            None,
+            OneIndexed::from_zero_indexed(line - 1),
+        )
+    }
+
+    fn outcomes(explanation: &Explanation) -> Vec<(ErrorCode, Outcome)> {
+        explanation
+            .findings
+            .iter()
+            .map(|finding| (finding.code, finding.outcome.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn reported_and_disabled() {
+        let source = r#"
+_a = {1: 1, 1: 2}
+def f(): ...
+"#;
+        let explanation = explain(source, 2).unwrap();
+        assert_eq!(explanation.source_line, "_a = {1: 1, 1: 2}");
+        assert_eq!(explanation.directive, None);
+        assert_eq!(
+            outcomes(&explanation),
+            [(
+                ErrorCode::DuplicateKey,
+                Outcome::Reported(MessageKind::Error)
+            )]
+        );
+        assert!(explanation.not_triggered.is_empty());
+
+        let explanation = explain(source, 3).unwrap();
+        assert_eq!(
+            outcomes(&explanation),
+            [(ErrorCode::MissingReturnAnnotation, Outcome::Disabled)]
+        );
+        assert_eq!(explanation.not_triggered, [ErrorCode::DuplicateKey]);
+
+        assert!(explain(source, 100).is_none());
+    }
+
+    #[test]
+    fn suppressed() {
+        let source = "_b = {1: 1, 1: 2}  # type: ignore[duplicate-key]\n";
+        let explanation = explain(source, 1).unwrap();
+        assert_eq!(
+            explanation.directive.as_deref(),
+            Some("# type: ignore[duplicate-key]")
+        );
+        assert_eq!(
+            outcomes(&explanation),
+            [(
+                ErrorCode::DuplicateKey,
+                Outcome::Suppressed("# type: ignore[duplicate-key]".to_string())
+            )]
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! Suggested fixes for diagnostics.
+
+use itertools::Itertools;
+use rustc_hash::{FxHashMap, FxHashSet};
+use rustpython_parser::ast::Ranged;
+use rustpython_parser::text_size::{TextRange, TextSize};
+use serde::{Deserialize, Serialize};
+
+use pyrogen_source_file::Locator;
+
+use crate::message::Message;
+use crate::registry::ErrorCode;
+
+/// A single change to the source: the text in `range` is replaced by `content`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+pub struct Edit {
+    range: TextRange,
+    /// The replacement text, or `None` if the range is deleted.
+    content: Option<Box<str>>,
+}
+
+impl Edit {
+    /// Delete the text in `range`.
+    pub const fn range_deletion(range: TextRange) -> Self {
+        Self {
+            range,
+            content: None,
+        }
+    }
+
+    /// Replace the text in `range` with `content`.
+    pub fn range_replacement(content: String, range: TextRange) -> Self {
+        debug_assert!(!content.is_empty(), "Prefer `Edit::range_deletion`");
+        Self {
+            range,
+            content: Some(Box::from(content)),
+        }
+    }
+
+    /// Insert `content` at `at`.
+    pub fn insertion(content: String, at: TextSize) -> Self {
+        debug_assert!(!content.is_empty(), "Insert content is empty");
+        Self {
+            range: TextRange::empty(at),
+            content: Some(Box::from(content)),
+        }
+    }
+
+    /// The text that replaces the range, if any.
+    pub fn content(&self) -> Option<&str> {
+        self.content.as_deref()
+    }
+}
+
+impl Ranged for Edit {
+    fn range(&self) -> TextRange {
+        self.range
+    }
+}
+
+/// A set of edits that resolves a diagnostic, with a title to show to the user.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Fix {
+    title: String,
+    edits: Vec<Edit>,
+}
+
+impl Fix {
+    pub fn new(title: impl Into<String>, edits: impl IntoIterator<Item = Edit>) -> Self {
+        let mut edits: Vec<Edit> = edits.into_iter().collect();
+        edits.sort_by_key(Ranged::start);
+        Self {
+            title: title.into(),
+            edits,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The edits of the fix, sorted by their start offset.
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+}
+
+/// The number of fixes applied for each error code.
+pub type FixTable = FxHashMap<ErrorCode, usize>;
+
+/// The source code after applying a set of fixes.
+#[derive(Debug)]
+pub struct FixResult {
+    /// The fixed source code.
+    pub code: String,
+    /// The number of fixes applied for each error code.
+    pub fixes: FixTable,
+}
+
+/// Apply the fixes of `messages` to the source code in `locator`.
+///
+/// The fixes are applied in the order of their first edit, and then in the order of `messages`. A fix that overlaps one that was
+/// already applied is skipped, since its edits no longer refer to the fixed source; checking the
+/// fixed source again reports it with a new fix. Edits that several fixes share are applied once.
+pub fn apply_fixes<'a>(
+    messages: impl Iterator<Item = &'a Message>,
+    locator: &Locator,
+) -> FixResult {
+    let mut output = String::with_capacity(locator.len());
+    let mut last_pos: Option<TextSize> = None;
+    let mut applied: FxHashSet<&Edit> = FxHashSet::default();
+    let mut fixed = FixTable::default();
+
+    for (code, fix) in messages
+        .filter_map(|message| {
+            message
+                .fix
+                .as_ref()
+                .map(|fix| (message.diagnostic.error_code, fix))
+        })
+        .sorted_by_key(|(_, fix)| fix.edits().first().map(Ranged::start))
+    {
+        let mut edits = fix
+            .edits()
+            .iter()
+            .filter(|edit| !applied.contains(edit))
+            .peekable();
+
+        // A fix whose edits were all applied by other fixes counts as applied, too.
+        if let Some(first) = edits.peek() {
+            if last_pos.is_some_and(|last_pos| last_pos > first.start()) {
+                continue;
+            }
+        }
+
+        for edit in edits {
+            output.push_str(
+                locator.slice(TextRange::new(last_pos.unwrap_or_default(), edit.start())),
+            );
+            output.push_str(edit.content().unwrap_or_default());
+            last_pos = Some(edit.end());
+            applied.insert(edit);
+        }
+
+        *fixed.entry(code).or_default() += 1;
+    }
+
+    output.push_str(locator.after(last_pos.unwrap_or_default()));
+
+    FixResult {
+        code: output,
+        fixes: fixed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::text_size::{TextRange, TextSize};
+
+    use pyrogen_source_file::{Locator, SourceFileBuilder};
+
+    use crate::message::Message;
+    use crate::registry::{Diagnostic, DiagnosticKind, ErrorCode};
+    use crate::settings::code_table::MessageKind;
+
+    use super::{apply_fixes, Edit, Fix};
+
+    fn message(source: &str, edits: Vec<Edit>) -> Message {
+        let range = edits
+            .first()
+            .map_or_else(TextRange::default, |edit| edit.range);
+        let diagnostic = Diagnostic::new(
+            DiagnosticKind::new(
+                ErrorCode::UnusedTypeIgnore,
+                "Unused `type: ignore` directive",
+            ),
+            range,
+        )
+        .with_fix(Fix::new("Fix", edits));
+        Message::from_diagnostic(
+            diagnostic,
+            SourceFileBuilder::new("module.py", source).finish(),
+            TextSize::default(),
+            MessageKind::Error,
+        )
+    }
+
+    fn range(start: u32, end: u32) -> TextRange {
+        TextRange::new(TextSize::new(start), TextSize::new(end))
+    }
+
+    #[test]
+    fn apply_fixes_in_order() {
+        let source = "a = 1\nb = 2\nc = 3\n";
+        let messages = [
+            message(
+                source,
+                vec![Edit::range_replacement("z".to_string(), range(12, 13))],
+            ),
+            message(source, vec![Edit::range_deletion(range(0, 6))]),
+        ];
+        let result = apply_fixes(messages.iter(), &Locator::new(source));
+        assert_eq!(result.code, "b = 2\nz = 3\n");
+        assert_eq!(result.fixes.get(&ErrorCode::UnusedTypeIgnore), Some(&2));
+    }
+
+    #[test]
+    fn skip_overlapping_fixes() {
+        let source = "a = 1\nb = 2\n";
+        let messages = [
+            message(source, vec![Edit::range_deletion(range(0, 6))]),
+            message(
+                source,
+                vec![Edit::range_replacement("x".to_string(), range(4, 5))],
+            ),
+            // The same edit as the first fix: it's applied once.
+            message(source, vec![Edit::range_deletion(range(0, 6))]),
+        ];
+        let result = apply_fixes(messages.iter(), &Locator::new(source));
+        assert_eq!(result.code, "b = 2\n");
+        assert_eq!(result.fixes.get(&ErrorCode::UnusedTypeIgnore), Some(&2));
+    }
+}
@@ -7,7 +7,7 @@ use path_absolutize::Absolutize;
 use crate::registry::ErrorCodeSet;
 
 /// Create a set with codes matching the pattern/code pairs.
-pub(crate) fn ignores_from_path(
+pub fn ignores_from_path(
     path: &Path,
     pattern_code_pairs: &[(GlobMatcher, GlobMatcher, ErrorCodeSet)],
 ) -> ErrorCodeSet {
@@ -39,6 +39,23 @@ pub(crate) fn ignores_from_path(
         .collect()
 }
 
+/// Convert `path` to a glob pattern with `/` as its separator.
+///
+/// Globs treat `\` as a literal character (or an escape) rather than a separator, while
+/// `globset` matches paths with `/` separators on every platform. Patterns built from Windows
+/// paths, or written with either separator on Windows, need the same form to match.
+pub fn glob_pattern<P: AsRef<Path>>(path: P) -> String {
+    with_slashes(&path.as_ref().to_string_lossy(), std::path::MAIN_SEPARATOR)
+}
+
+fn with_slashes(path: &str, separator: char) -> String {
+    if separator == '/' {
+        path.to_string()
+    } else {
+        path.replace(separator, "/")
+    }
+}
+
 /// Convert any path to an absolute path (based on the current working
 /// directory).
 pub fn normalize_path<P: AsRef<Path>>(path: P) -> PathBuf {
@@ -82,3 +99,68 @@ pub fn relativize_path_to<P: AsRef<Path>, R: AsRef<Path>>(path: P, project_root:
             .display()
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::registry::ErrorCode;
+    use crate::settings::resolve_per_file_ignores;
+    use crate::settings::types::PerFileIgnore;
+    use crate::ErrorCodeSelector;
+
+    use super::{ignores_from_path, with_slashes};
+
+    #[test]
+    fn windows_separators() {
+        assert_eq!(
+            with_slashes(r"C:\project\tests\*.py", '\\'),
+            "C:/project/tests/*.py"
+        );
+        // Patterns written with `/` are unchanged.
+        assert_eq!(
+            with_slashes(r"C:\project/tests/*.py", '\\'),
+            "C:/project/tests/*.py"
+        );
+        assert_eq!(
+            with_slashes("/project/tests/*.py", '/'),
+            "/project/tests/*.py"
+        );
+    }
+
+    #[test]
+    fn per_file_ignores() {
+        let project_root = if cfg!(windows) {
+            Path::new(r"C:\project")
+        } else {
+            Path::new("/project")
+        };
+        let per_file_ignores = resolve_per_file_ignores(vec![PerFileIgnore::new(
+            "tests/*.py".to_string(),
+            &[ErrorCodeSelector::ErrorCode(ErrorCode::DuplicateKey)],
+            Some(project_root),
+        )])
+        .unwrap();
+
+        let ignores = |path: &Path| ignores_from_path(path, &per_file_ignores);
+        assert!(ignores(&project_root.join("tests").join("test_a.py"))
+            .contains(ErrorCode::DuplicateKey));
+        assert!(ignores(&project_root.join("src").join("a.py")).is_empty());
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn per_file_ignores_with_backslashes() {
+        let per_file_ignores = resolve_per_file_ignores(vec![PerFileIgnore::new(
+            r"tests\*.py".to_string(),
+            &[ErrorCodeSelector::ErrorCode(ErrorCode::DuplicateKey)],
+            Some(Path::new(r"C:\project")),
+        )])
+        .unwrap();
+
+        assert!(
+            ignores_from_path(Path::new(r"C:\project\tests\test_a.py"), &per_file_ignores)
+                .contains(ErrorCode::DuplicateKey)
+        );
+    }
+}
@@ -0,0 +1,1075 @@
+//! The type and documentation of the symbol under the cursor, for hovers in editors.
+//!
+//! Names are resolved within their file, to the function, class, parameter, assignment or import
+//! that binds them in the enclosing scopes. The type of a variable is its annotation or, for an
+//! unannotated assignment, the type of the assigned literal, or of a call to a class or to a
+//! function with a return annotation.
+
+use rustpython_ast::{self as ast, Constant, Expr, Pattern, Ranged, Stmt};
+use rustpython_parser::text_size::{TextLen, TextRange, TextSize};
+
+use pyrogen_python_ast::visitor::{self, Visitor};
+use pyrogen_python_ast::{AsMode, PySourceType};
+use pyrogen_source_file::Locator;
+
+use crate::definitions::{identifier_range, DefinitionKind};
+use crate::interface::Signature;
+
+/// How many assignments of one name to another are followed to infer a type.
+const MAX_ALIAS_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hover {
+    pub name: String,
+    pub kind: DefinitionKind,
+    /// The range of the name under the cursor.
+    pub range: TextRange,
+    /// The signature of a function, like `(x: int) -> str`, or the type of a variable or class.
+    /// `Unknown` if it can't be inferred.
+    pub inferred_type: String,
+    pub docstring: Option<String>,
+}
+
+impl Hover {
+    /// The hover as Markdown: the declaration of the symbol as a Python code block, followed by
+    /// its docstring.
+    pub fn markdown(&self) -> String {
+        let declaration = match self.kind {
+            DefinitionKind::Function | DefinitionKind::Method => {
+                format!("def {}{}", self.name, self.inferred_type)
+            }
+            DefinitionKind::Class => format!("class {}", self.name),
+            DefinitionKind::Variable => format!("{}: {}", self.name, self.inferred_type),
+        };
+        match &self.docstring {
+            Some(docstring) => format!("```python\n{declaration}\n```\n\n{docstring}"),
+            None => format!("```python\n{declaration}\n```"),
+        }
+    }
+}
+
+/// The type and docstring of the name at `offset` in `source`. Returns `None` if there's no name
+/// at `offset`, if it isn't bound in the file (e.g. because it's a builtin), or if `source`
+/// contains syntax errors.
+pub fn type_at_position(
+    source: &str,
+    source_type: PySourceType,
+    offset: TextSize,
+) -> Option<Hover> {
+    let python_ast = rustpython_parser::parse(source, source_type.as_mode(), "<hover>")
+        .ok()?
+        .expect_module()
+        .body;
+
+    let mut finder = NameFinder {
+        source,
+        offset,
+        scopes: vec![Scope::module(&python_ast)],
+        found: None,
+    };
+    finder.visit_body(&python_ast);
+    let found = finder.found?;
+
+    let resolver = Resolver {
+        locator: &Locator::new(source),
+        scopes: &found.scopes,
+        offset,
+    };
+    let (binding, scope) = resolver.resolve(&found.name)?;
+    let is_method = found.scopes[scope].is_class;
+    let (kind, inferred_type, docstring) = match binding {
+        Binding::Function {
+            args,
+            returns,
+            body,
+        } => (
+            if is_method {
+                DefinitionKind::Method
+            } else {
+                DefinitionKind::Function
+            },
+            Signature::from_arguments(args, returns, resolver.locator).to_string(),
+            docstring(body),
+        ),
+        Binding::Class(class) => (
+            DefinitionKind::Class,
+            format!("type[{}]", class.name.as_str()),
+            docstring(&class.body),
+        ),
+        binding => (
+            DefinitionKind::Variable,
+            resolver
+                .binding_type(&binding, scope, 0)
+                .unwrap_or_else(|| "Unknown".to_string()),
+            None,
+        ),
+    };
+
+    Some(Hover {
+        name: found.name,
+        kind,
+        range: found.range,
+        inferred_type,
+        docstring,
+    })
+}
+
+/// A scope in which names are bound: the module, a function, a lambda or a class body.
+#[derive(Debug, Clone, Copy)]
+struct Scope<'a> {
+    body: &'a [Stmt],
+    /// The body of a lambda, which can only bind names with assignment expressions.
+    lambda_body: Option<&'a Expr>,
+    /// The parameters of a function or lambda.
+    parameters: Option<&'a ast::Arguments>,
+    is_class: bool,
+}
+
+impl<'a> Scope<'a> {
+    const fn module(body: &'a [Stmt]) -> Self {
+        Self {
+            body,
+            lambda_body: None,
+            parameters: None,
+            is_class: false,
+        }
+    }
+}
+
+struct FoundName<'a> {
+    name: String,
+    range: TextRange,
+    /// The scopes enclosing the name, outermost first.
+    scopes: Vec<Scope<'a>>,
+}
+
+/// Finds the name at an offset: a name in an expression, a parameter, or the name of a function
+/// or class definition.
+struct NameFinder<'a> {
+    source: &'a str,
+    offset: TextSize,
+    scopes: Vec<Scope<'a>>,
+    found: Option<FoundName<'a>>,
+}
+
+impl<'a> NameFinder<'a> {
+    fn check(&mut self, name: &str, range: TextRange) {
+        if self.found.is_none() && range.contains_inclusive(self.offset) {
+            self.found = Some(FoundName {
+                name: name.to_string(),
+                range,
+                scopes: self.scopes.clone(),
+            });
+        }
+    }
+
+    /// Visit the defaults of a function or lambda, which are evaluated in the enclosing scope.
+    fn visit_defaults(&mut self, arguments: &'a ast::Arguments) {
+        for default in arguments
+            .posonlyargs
+            .iter()
+            .chain(&arguments.args)
+            .chain(&arguments.kwonlyargs)
+            .filter_map(|arg| arg.default.as_deref())
+        {
+            self.visit_expr(default);
+        }
+    }
+
+    /// Visit the parameters of a function or lambda (with their annotations), but not their
+    /// defaults.
+    fn visit_parameter_names(&mut self, arguments: &'a ast::Arguments) {
+        for parameter in arguments
+            .posonlyargs
+            .iter()
+            .chain(&arguments.args)
+            .map(|arg| &arg.def)
+            .chain(arguments.vararg.as_deref())
+            .chain(arguments.kwonlyargs.iter().map(|arg| &arg.def))
+            .chain(arguments.kwarg.as_deref())
+        {
+            self.visit_parameter(parameter);
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for NameFinder<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if self.found.is_some() || !stmt.range().contains_inclusive(self.offset) {
+            return;
+        }
+        match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef {
+                name,
+                args,
+                body,
+                decorator_list,
+                returns,
+                ..
+            })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+                name,
+                args,
+                body,
+                decorator_list,
+                returns,
+                ..
+            }) => {
+                self.check(
+                    name.as_str(),
+                    identifier_range(stmt, name.as_str(), self.source),
+                );
+                for decorator in decorator_list {
+                    self.visit_decorator(decorator);
+                }
+                if let Some(returns) = returns {
+                    self.visit_annotation(returns);
+                }
+                self.visit_defaults(args);
+                self.scopes.push(Scope {
+                    body,
+                    lambda_body: None,
+                    parameters: Some(&**args),
+                    is_class: false,
+                });
+                self.visit_parameter_names(args);
+                self.visit_body(body);
+                self.scopes.pop();
+            }
+            Stmt::ClassDef(ast::StmtClassDef {
+                name,
+                bases,
+                keywords,
+                body,
+                decorator_list,
+                ..
+            }) => {
+                self.check(
+                    name.as_str(),
+                    identifier_range(stmt, name.as_str(), self.source),
+                );
+                for decorator in decorator_list {
+                    self.visit_decorator(decorator);
+                }
+                for base in bases {
+                    self.visit_expr(base);
+                }
+                for keyword in keywords {
+                    self.visit_keyword(keyword);
+                }
+                self.scopes.push(Scope {
+                    body,
+                    lambda_body: None,
+                    parameters: None,
+                    is_class: true,
+                });
+                self.visit_body(body);
+                self.scopes.pop();
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if self.found.is_some() || !expr.range().contains_inclusive(self.offset) {
+            return;
+        }
+        match expr {
+            Expr::Name(ast::ExprName { id, range, .. }) => self.check(id.as_str(), *range),
+            Expr::Lambda(ast::ExprLambda { args, body, .. }) => {
+                self.visit_defaults(args);
+                self.scopes.push(Scope {
+                    body: &[],
+                    lambda_body: Some(body),
+                    parameters: Some(&**args),
+                    is_class: false,
+                });
+                self.visit_parameter_names(args);
+                self.visit_expr(body);
+                self.scopes.pop();
+            }
+            _ => visitor::walk_expr(self, expr),
+        }
+    }
+
+    fn visit_parameter(&mut self, parameter: &'a ast::Arg) {
+        self.check(
+            parameter.arg.as_str(),
+            TextRange::at(parameter.start(), parameter.arg.as_str().text_len()),
+        );
+        visitor::walk_parameter(self, parameter);
+    }
+
+    fn visit_pattern(&mut self, pattern: &'a Pattern) {
+        if self.found.is_some() || !pattern.range().contains_inclusive(self.offset) {
+            return;
+        }
+        match pattern {
+            // The captured name ends the pattern: `x`, `[1, 2] as x` or `*x`.
+            Pattern::MatchAs(ast::PatternMatchAs {
+                name: Some(name),
+                range,
+                ..
+            })
+            | Pattern::MatchStar(ast::PatternMatchStar {
+                name: Some(name),
+                range,
+            }) => {
+                let name = name.as_str();
+                self.check(
+                    name,
+                    TextRange::new(range.end() - name.text_len(), range.end()),
+                );
+            }
+            // The rest of a mapping pattern follows the last `**`.
+            Pattern::MatchMapping(ast::PatternMatchMapping {
+                rest: Some(rest),
+                range,
+                ..
+            }) => {
+                let text = &self.source[*range];
+                if let Some(stars) = text.rfind("**") {
+                    let after = &text[stars + 2..];
+                    let start = range.start()
+                        + TextSize::try_from(stars + 2 + after.len() - after.trim_start().len())
+                            .unwrap();
+                    self.check(
+                        rest.as_str(),
+                        TextRange::at(start, rest.as_str().text_len()),
+                    );
+                }
+            }
+            _ => {}
+        }
+        visitor::walk_pattern(self, pattern);
+    }
+}
+
+/// A statement or parameter that binds a name.
+#[derive(Debug, Clone, Copy)]
+enum Binding<'a> {
+    Function {
+        args: &'a ast::Arguments,
+        returns: Option<&'a Expr>,
+        body: &'a [Stmt],
+    },
+    Class(&'a ast::StmtClassDef),
+    Parameter(&'a ast::Arg, Option<&'a Expr>),
+    Assignment {
+        annotation: Option<&'a Expr>,
+        value: Option<&'a Expr>,
+    },
+    /// `import module` or `import module as name`.
+    Module,
+    /// Any other binding, like a `for` loop target or an unpacking assignment.
+    Other,
+}
+
+struct Resolver<'a> {
+    locator: &'a Locator<'a>,
+    /// The scopes enclosing the name, outermost first.
+    scopes: &'a [Scope<'a>],
+    offset: TextSize,
+}
+
+impl<'a> Resolver<'a> {
+    /// The binding of `name` that applies at the offset, and the index of the scope that binds it.
+    ///
+    /// The innermost scope that binds the name is used, except that class bodies are only
+    /// searched if they're the innermost scope. Within the scope, the last binding before the
+    /// offset is used, or the first binding if there's none before it.
+    fn resolve(&self, name: &str) -> Option<(Binding<'a>, usize)> {
+        let innermost = self.scopes.len().checked_sub(1)?;
+        self.scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(index, scope)| !scope.is_class || *index == innermost)
+            .find_map(|(index, scope)| {
+                let mut bindings = vec![];
+                if let Some(parameters) = scope.parameters {
+                    collect_parameters(parameters, name, &mut bindings);
+                }
+                collect_bindings(scope.body, name, &mut bindings);
+                collect_named_exprs(scope.body, name, &mut bindings);
+                if let Some(lambda_body) = scope.lambda_body {
+                    NamedExprCollector {
+                        name,
+                        bindings: &mut bindings,
+                    }
+                    .visit_expr(lambda_body);
+                }
+                bindings.sort_by_key(|(start, _)| *start);
+                let binding = bindings
+                    .iter()
+                    .rev()
+                    .find(|(start, _)| *start <= self.offset)
+                    .or(bindings.first())?
+                    .1;
+                Some((binding, index))
+            })
+    }
+
+    /// A resolver for the names in the first `len` scopes.
+    fn outer(&self, len: usize) -> Self {
+        Self {
+            locator: self.locator,
+            scopes: &self.scopes[..len],
+            offset: self.offset,
+        }
+    }
+
+    /// The type of the value bound by `binding` in the scope at index `scope`.
+    fn binding_type(&self, binding: &Binding, scope: usize, depth: usize) -> Option<String> {
+        match binding {
+            Binding::Function { .. } => Some("Callable".to_string()),
+            Binding::Class(class) => Some(format!("type[{}]", class.name.as_str())),
+            Binding::Parameter(parameter, default) => match &parameter.annotation {
+                Some(annotation) => Some(self.locator.slice(annotation.range()).to_string()),
+                // Defaults are evaluated in the scope that encloses the function or lambda.
+                None => default.and_then(|default| self.outer(scope).expr_type(default, depth)),
+            },
+            Binding::Assignment { annotation, value } => match annotation {
+                Some(annotation) => Some(self.locator.slice(annotation.range()).to_string()),
+                None => value.and_then(|value| self.outer(scope + 1).expr_type(value, depth)),
+            },
+            Binding::Module => Some("Module".to_string()),
+            Binding::Other => None,
+        }
+    }
+
+    /// The type of `expr`, if it's a literal, a call to a class or to a function with a return
+    /// annotation, or a name whose type is known.
+    fn expr_type(&self, expr: &Expr, depth: usize) -> Option<String> {
+        let type_name = match expr {
+            Expr::Constant(ast::ExprConstant { value, .. }) => match value {
+                Constant::None => "None",
+                Constant::Bool(_) => "bool",
+                Constant::Str(_) => "str",
+                Constant::Bytes(_) => "bytes",
+                Constant::Int(_) => "int",
+                Constant::Tuple(_) => "tuple",
+                Constant::Float(_) => "float",
+                Constant::Complex { .. } => "complex",
+                Constant::Ellipsis => "EllipsisType",
+            },
+            Expr::JoinedStr(_) => "str",
+            Expr::List(_) | Expr::ListComp(_) => "list",
+            Expr::Dict(_) | Expr::DictComp(_) => "dict",
+            Expr::Set(_) | Expr::SetComp(_) => "set",
+            Expr::Tuple(_) => "tuple",
+            Expr::GeneratorExp(_) => "Generator",
+            Expr::Lambda(_) => "Callable",
+            Expr::Compare(_)
+            | Expr::UnaryOp(ast::ExprUnaryOp {
+                op: ast::UnaryOp::Not,
+                ..
+            }) => "bool",
+            Expr::Call(ast::ExprCall { func, .. }) => {
+                let Expr::Name(ast::ExprName { id, .. }) = func.as_ref() else {
+                    return None;
+                };
+                return match self.resolve(id.as_str())?.0 {
+                    Binding::Class(class) => Some(class.name.as_str().to_string()),
+                    Binding::Function {
+                        returns: Some(returns),
+                        ..
+                    } => Some(self.locator.slice(returns.range()).to_string()),
+                    _ => None,
+                };
+            }
+            Expr::Name(ast::ExprName { id, .. }) if depth < MAX_ALIAS_DEPTH => {
+                let (binding, scope) = self.resolve(id.as_str())?;
+                return self.binding_type(&binding, scope, depth + 1);
+            }
+            _ => return None,
+        };
+        Some(type_name.to_string())
+    }
+}
+
+fn collect_parameters<'a>(
+    arguments: &'a ast::Arguments,
+    name: &str,
+    bindings: &mut Vec<(TextSize, Binding<'a>)>,
+) {
+    for arg in arguments
+        .posonlyargs
+        .iter()
+        .chain(&arguments.args)
+        .chain(&arguments.kwonlyargs)
+    {
+        if arg.def.arg.as_str() == name {
+            bindings.push((
+                arg.start(),
+                Binding::Parameter(&arg.def, arg.default.as_deref()),
+            ));
+        }
+    }
+    for arg in arguments.vararg.iter().chain(&arguments.kwarg) {
+        if arg.arg.as_str() == name {
+            bindings.push((arg.start(), Binding::Other));
+        }
+    }
+}
+
+/// Collect the bindings of `name` in `body`, including those nested in control flow but not
+/// those in nested functions and classes.
+fn collect_bindings<'a>(body: &'a [Stmt], name: &str, bindings: &mut Vec<(TextSize, Binding<'a>)>) {
+    for stmt in body {
+        match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef {
+                name: function,
+                args,
+                returns,
+                body,
+                ..
+            })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+                name: function,
+                args,
+                returns,
+                body,
+                ..
+            }) => {
+                if function.as_str() == name {
+                    bindings.push((
+                        stmt.start(),
+                        Binding::Function {
+                            args,
+                            returns: returns.as_deref(),
+                            body,
+                        },
+                    ));
+                }
+            }
+            Stmt::ClassDef(class) => {
+                if class.name.as_str() == name {
+                    bindings.push((stmt.start(), Binding::Class(class)));
+                }
+            }
+            Stmt::Assign(ast::StmtAssign { targets, value, .. }) => {
+                for target in targets {
+                    let binding = Binding::Assignment {
+                        annotation: None,
+                        value: Some(&**value),
+                    };
+                    collect_target(target, name, binding, bindings);
+                }
+            }
+            Stmt::AnnAssign(ast::StmtAnnAssign {
+                target,
+                annotation,
+                value,
+                ..
+            }) => {
+                let binding = Binding::Assignment {
+                    annotation: Some(&**annotation),
+                    value: value.as_deref(),
+                };
+                collect_target(target, name, binding, bindings);
+            }
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                for alias in names {
+                    let bound = match &alias.asname {
+                        Some(asname) => asname.as_str(),
+                        None => alias.name.as_str().split('.').next().unwrap_or_default(),
+                    };
+                    if bound == name {
+                        bindings.push((stmt.start(), Binding::Module));
+                    }
+                }
+            }
+            Stmt::ImportFrom(ast::StmtImportFrom { names, .. }) => {
+                for alias in names {
+                    if alias.asname.as_ref().unwrap_or(&alias.name).as_str() == name {
+                        bindings.push((stmt.start(), Binding::Other));
+                    }
+                }
+            }
+            Stmt::If(ast::StmtIf { body, orelse, .. })
+            | Stmt::While(ast::StmtWhile { body, orelse, .. }) => {
+                collect_bindings(body, name, bindings);
+                collect_bindings(orelse, name, bindings);
+            }
+            Stmt::For(ast::StmtFor {
+                target,
+                body,
+                orelse,
+                ..
+            })
+            | Stmt::AsyncFor(ast::StmtAsyncFor {
+                target,
+                body,
+                orelse,
+                ..
+            }) => {
+                collect_target(target, name, Binding::Other, bindings);
+                collect_bindings(body, name, bindings);
+                collect_bindings(orelse, name, bindings);
+            }
+            Stmt::With(ast::StmtWith { items, body, .. })
+            | Stmt::AsyncWith(ast::StmtAsyncWith { items, body, .. }) => {
+                for item in items {
+                    if let Some(target) = &item.optional_vars {
+                        collect_target(target, name, Binding::Other, bindings);
+                    }
+                }
+                collect_bindings(body, name, bindings);
+            }
+            Stmt::Try(ast::StmtTry {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            })
+            | Stmt::TryStar(ast::StmtTryStar {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            }) => {
+                collect_bindings(body, name, bindings);
+                for handler in handlers {
+                    let ast::ExceptHandler::ExceptHandler(handler) = handler;
+                    if handler
+                        .name
+                        .as_ref()
+                        .is_some_and(|bound| bound.as_str() == name)
+                    {
+                        bindings.push((handler.start(), Binding::Other));
+                    }
+                    collect_bindings(&handler.body, name, bindings);
+                }
+                collect_bindings(orelse, name, bindings);
+                collect_bindings(finalbody, name, bindings);
+            }
+            Stmt::Match(ast::StmtMatch { cases, .. }) => {
+                for case in cases {
+                    collect_pattern(&case.pattern, name, bindings);
+                    collect_bindings(&case.body, name, bindings);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Collect the bindings of `name` by the captures in a match `pattern`. The wildcard `_` doesn't
+/// bind anything.
+fn collect_pattern<'a>(
+    pattern: &'a Pattern,
+    name: &str,
+    bindings: &mut Vec<(TextSize, Binding<'a>)>,
+) {
+    match pattern {
+        Pattern::MatchValue(_) | Pattern::MatchSingleton(_) => {}
+        Pattern::MatchSequence(ast::PatternMatchSequence { patterns, .. })
+        | Pattern::MatchOr(ast::PatternMatchOr { patterns, .. }) => {
+            for pattern in patterns {
+                collect_pattern(pattern, name, bindings);
+            }
+        }
+        Pattern::MatchMapping(ast::PatternMatchMapping { patterns, rest, .. }) => {
+            for pattern in patterns {
+                collect_pattern(pattern, name, bindings);
+            }
+            if rest.as_ref().is_some_and(|rest| rest.as_str() == name) {
+                bindings.push((pattern.start(), Binding::Other));
+            }
+        }
+        Pattern::MatchClass(ast::PatternMatchClass {
+            patterns,
+            kwd_patterns,
+            ..
+        }) => {
+            for pattern in patterns.iter().chain(kwd_patterns) {
+                collect_pattern(pattern, name, bindings);
+            }
+        }
+        Pattern::MatchStar(ast::PatternMatchStar { name: bound, .. }) => {
+            if bound.as_ref().is_some_and(|bound| bound.as_str() == name) {
+                bindings.push((pattern.start(), Binding::Other));
+            }
+        }
+        Pattern::MatchAs(ast::PatternMatchAs {
+            pattern: inner,
+            name: bound,
+            ..
+        }) => {
+            if let Some(inner) = inner {
+                collect_pattern(inner, name, bindings);
+            }
+            if bound.as_ref().is_some_and(|bound| bound.as_str() == name) {
+                bindings.push((pattern.start(), Binding::Other));
+            }
+        }
+    }
+}
+
+/// Collect the binding of `name` by an assignment to `target`. The elements of an unpacking
+/// assignment are [`Binding::Other`], since their types aren't inferred.
+fn collect_target<'a>(
+    target: &'a Expr,
+    name: &str,
+    binding: Binding<'a>,
+    bindings: &mut Vec<(TextSize, Binding<'a>)>,
+) {
+    match target {
+        Expr::Name(ast::ExprName { id, range, .. }) => {
+            if id.as_str() == name {
+                bindings.push((range.start(), binding));
+            }
+        }
+        Expr::Tuple(ast::ExprTuple { elts, .. }) | Expr::List(ast::ExprList { elts, .. }) => {
+            for elt in elts {
+                collect_target(elt, name, Binding::Other, bindings);
+            }
+        }
+        Expr::Starred(ast::ExprStarred { value, .. }) => {
+            collect_target(value, name, Binding::Other, bindings);
+        }
+        _ => {}
+    }
+}
+
+/// Collects the bindings of a name by assignment expressions (`:=`) in a scope.
+///
+/// As per PEP 572, a target in a comprehension binds in the scope that contains the
+/// comprehension, but targets in lambdas and in nested functions and classes don't.
+struct NamedExprCollector<'a, 'b> {
+    name: &'b str,
+    bindings: &'b mut Vec<(TextSize, Binding<'a>)>,
+}
+
+impl<'a> Visitor<'a> for NamedExprCollector<'a, '_> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            // The decorators, defaults and bases are evaluated in the enclosing scope.
+            Stmt::FunctionDef(ast::StmtFunctionDef {
+                args,
+                decorator_list,
+                ..
+            })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+                args,
+                decorator_list,
+                ..
+            }) => {
+                for decorator in decorator_list {
+                    self.visit_decorator(decorator);
+                }
+                self.visit_parameters(args);
+            }
+            Stmt::ClassDef(ast::StmtClassDef {
+                bases,
+                keywords,
+                decorator_list,
+                ..
+            }) => {
+                for decorator in decorator_list {
+                    self.visit_decorator(decorator);
+                }
+                for base in bases {
+                    self.visit_expr(base);
+                }
+                for keyword in keywords {
+                    self.visit_keyword(keyword);
+                }
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::NamedExpr(ast::ExprNamedExpr { target, value, .. }) => {
+                let binding = Binding::Assignment {
+                    annotation: None,
+                    value: Some(&**value),
+                };
+                collect_target(target, self.name, binding, self.bindings);
+                self.visit_expr(value);
+            }
+            Expr::Lambda(ast::ExprLambda { args, .. }) => self.visit_parameters(args),
+            _ => visitor::walk_expr(self, expr),
+        }
+    }
+}
+
+fn collect_named_exprs<'a>(
+    body: &'a [Stmt],
+    name: &str,
+    bindings: &mut Vec<(TextSize, Binding<'a>)>,
+) {
+    NamedExprCollector { name, bindings }.visit_body(body);
+}
+
+/// The docstring of a function or class `body`, with its indentation removed as by
+/// `inspect.cleandoc`.
+fn docstring(body: &[Stmt]) -> Option<String> {
+    let Some(Stmt::Expr(ast::StmtExpr { value, .. })) = body.first() else {
+        return None;
+    };
+    let Expr::Constant(ast::ExprConstant {
+        value: Constant::Str(docstring),
+        ..
+    }) = value.as_ref()
+    else {
+        return None;
+    };
+
+    let mut lines = docstring.lines();
+    let first = lines.next().unwrap_or_default().trim();
+    let rest: Vec<&str> = lines.collect();
+    let indent = rest
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or_default();
+    let mut cleaned: Vec<&str> = std::iter::once(first)
+        .chain(
+            rest.iter()
+                .map(|line| line.get(indent..).unwrap_or_default().trim_end()),
+        )
+        .collect();
+    while cleaned.last().is_some_and(|line| line.is_empty()) {
+        cleaned.pop();
+    }
+    while cleaned.first().is_some_and(|line| line.is_empty()) {
+        cleaned.remove(0);
+    }
+    Some(cleaned.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::text_size::TextSize;
+
+    use pyrogen_python_ast::PySourceType;
+
+    use crate::definitions::DefinitionKind;
+
+    use super::{type_at_position, Hover};
+
+    /// The hover at the end of the first occurrence of `at` in `source`.
+    fn hover(source: &str, at: &str) -> Option<Hover> {
+        let offset = source.find(at).unwrap() + at.len();
+        type_at_position(
+            source,
+            PySourceType::Python,
+            TextSize::try_from(offset).unwrap(),
+        )
+    }
+
+    fn inferred_type(source: &str, at: &str) -> Option<String> {
+        hover(source, at).map(|hover| hover.inferred_type)
+    }
+
+    #[test]
+    fn variables() {
+        let source = r#"
+import os
+
+count = 0
+ratio: float = 1
+label = f"{count}"
+
+class Point:
+    x: int = 0
+
+def origin() -> Point:
+    return Point()
+
+point = Point()
+other = origin()
+alias = point
+print(count, ratio, label, point, other, alias, os, unknown)
+"#;
+        assert_eq!(inferred_type(source, "(count").as_deref(), Some("int"));
+        assert_eq!(inferred_type(source, ", ratio").as_deref(), Some("float"));
+        assert_eq!(inferred_type(source, ", label").as_deref(), Some("str"));
+        assert_eq!(inferred_type(source, ", point").as_deref(), Some("Point"));
+        assert_eq!(inferred_type(source, ", other").as_deref(), Some("Point"));
+        assert_eq!(inferred_type(source, ", alias").as_deref(), Some("Point"));
+        assert_eq!(inferred_type(source, ", os").as_deref(), Some("Module"));
+        // Builtins and undefined names aren't bound in the file.
+        assert_eq!(hover(source, ", unknown"), None);
+        assert_eq!(hover(source, "print"), None);
+
+        let alias = hover(source, "alias").unwrap();
+        assert_eq!(alias.name, "alias");
+        assert_eq!(alias.kind, DefinitionKind::Variable);
+        assert_eq!(alias.markdown(), "```python\nalias: Point\n```");
+    }
+
+    #[test]
+    fn functions_and_classes() {
+        let source = r#"
+class Greeter:
+    """Greets people.
+
+    Politely.
+    """
+
+    def greet(self, name: str, *, loud: bool = False) -> str:
+        """Return a greeting."""
+        return name
+
+def main(greeter=Greeter()):
+    greeter.greet("world")
+"#;
+        let class = hover(source, "class Greeter").unwrap();
+        assert_eq!(class.kind, DefinitionKind::Class);
+        assert_eq!(class.inferred_type, "type[Greeter]");
+        assert_eq!(
+            class.markdown(),
+            "```python\nclass Greeter\n```\n\nGreets people.\n\nPolitely."
+        );
+
+        let method = hover(source, "def greet").unwrap();
+        assert_eq!(method.kind, DefinitionKind::Method);
+        assert_eq!(
+            method.inferred_type,
+            "(self, name: str, *, loud: bool = ...) -> str"
+        );
+        assert_eq!(method.docstring.as_deref(), Some("Return a greeting."));
+
+        let function = hover(source, "def main").unwrap();
+        assert_eq!(function.kind, DefinitionKind::Function);
+        assert_eq!(function.markdown(), "```python\ndef main(greeter=...)\n```");
+
+        // A parameter without an annotation takes the type of its default.
+        assert_eq!(
+            inferred_type(source, "    greeter").as_deref(),
+            Some("Greeter")
+        );
+    }
+
+    #[test]
+    fn scopes() {
+        let source = r#"
+value = 1
+
+def function(value: str):
+    return value
+
+class Class:
+    value = b""
+
+    def method(self):
+        return value
+"#;
+        let types: Vec<_> = source
+            .match_indices("value")
+            .map(|(offset, _)| {
+                type_at_position(
+                    source,
+                    PySourceType::Python,
+                    TextSize::try_from(offset).unwrap(),
+                )
+                .map(|hover| hover.inferred_type)
+            })
+            .collect();
+        assert_eq!(
+            types,
+            [
+                Some("int".to_string()),
+                Some("str".to_string()),
+                Some("str".to_string()),
+                Some("bytes".to_string()),
+                // Class bodies aren't visible from the methods in them.
+                Some("int".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn lambdas_and_defaults() {
+        let source = r#"
+scale = 2.0
+
+def build(scale=scale):
+    return scale
+
+double = lambda scale=scale: scale
+halve = lambda value: value / scale
+"#;
+        // Defaults are evaluated in the enclosing scope.
+        assert_eq!(
+            inferred_type(source, "(scale=scale").as_deref(),
+            Some("float")
+        );
+        assert_eq!(
+            inferred_type(source, "return scale").as_deref(),
+            Some("float")
+        );
+        assert_eq!(
+            inferred_type(source, "lambda scale=scale").as_deref(),
+            Some("float")
+        );
+        assert_eq!(inferred_type(source, ": scale").as_deref(), Some("float"));
+        // The parameters of a lambda are local to it.
+        let parameter = hover(source, "lambda value: value").unwrap();
+        assert_eq!(parameter.name, "value");
+        assert_eq!(parameter.inferred_type, "Unknown");
+        assert_eq!(inferred_type(source, "/ scale").as_deref(), Some("float"));
+    }
+
+    #[test]
+    fn assignment_expressions() {
+        let source = r#"
+if (count := 0) == 0:
+    print(count)
+
+labels = [(last := f"{item}") for item in range(3)]
+print(last)
+
+def function():
+    callbacks = [lambda: (inner := 1.0) for _ in range(3)]
+    return inner
+"#;
+        assert_eq!(inferred_type(source, "(count").as_deref(), Some("int"));
+        // Targets in comprehensions bind in the enclosing scope.
+        assert_eq!(inferred_type(source, "print(last").as_deref(), Some("str"));
+        // Targets in lambdas don't.
+        assert_eq!(hover(source, "return inner"), None);
+    }
+
+    #[test]
+    fn match_patterns() {
+        let source = r#"
+match command:
+    case {"size": int(size), **extra}:
+        print(size, extra)
+    case [first, *rest] | [first, *rest, _]:
+        print(first, rest)
+    case str() as text:
+        print(text)
+    case _:
+        print(_)
+"#;
+        for (at, name) in [
+            ("print(size", "size"),
+            (", extra", "extra"),
+            ("print(first", "first"),
+            (", rest", "rest"),
+            ("print(text", "text"),
+        ] {
+            let usage = hover(source, at).unwrap();
+            assert_eq!(usage.name, name);
+            assert_eq!(usage.kind, DefinitionKind::Variable);
+            assert_eq!(usage.inferred_type, "Unknown");
+        }
+        // The captures in the patterns themselves.
+        for at in ["int(size", "**extra", "[first", "*rest", "as text"] {
+            let capture = hover(source, at).unwrap();
+            assert_eq!(&source[capture.range], capture.name);
+        }
+        // The wildcard doesn't bind `_`.
+        assert_eq!(hover(source, "print(_"), None);
+    }
+}
@@ -0,0 +1,1718 @@
+//! The public interfaces of the modules of a project.
+//!
+//! A module's interface describes the names defined at its top level, such as the signatures of
+//! its functions, so that other modules can be checked against them. Interfaces are built in
+//! dependency order (see [`pyrogen_module_resolver::ModuleGraph`]): names that a module imports
+//! from another first-party module and thereby re-exports take on the symbol from the interface
+//! of that module.
+//!
+//! The effect of common decorators is modeled, so that decorated functions keep (or transform)
+//! their signatures: `functools.wraps`, `functools.lru_cache`, `contextlib.contextmanager`,
+//! `property` and its setters, and decorators defined in the project that return a
+//! `functools.wraps` wrapper of the function they decorate. The keys of `TypedDict`s are
+//! recorded, so that calls to functions with `**kwargs: Unpack[SomeTypedDict]` can be checked.
+//!
+//! The `try: from a import f` / `except ImportError: from b import f` idiom for optional
+//! dependencies is modeled as well: a name bound in the body of such a `try` and again in the
+//! handler is a [`Symbol::Union`] of both bindings, rather than an unknown redefinition.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use rustpython_ast::{self as ast, Constant, Expr, Ranged, Stmt};
+use serde::{Deserialize, Serialize};
+
+use pyrogen_cache::CacheKeyHasher;
+use pyrogen_module_resolver::ModuleName;
+use pyrogen_python_ast::imports::catches_import_error;
+use pyrogen_python_ast::visitor::{self, Visitor};
+use pyrogen_python_ast::{AsMode, PySourceType};
+use pyrogen_source_file::Locator;
+
+use crate::references::ProjectReferences;
+
+/// A module that is checked as part of a project, along with the interfaces of the other
+/// modules of the project and the references between them.
+#[derive(Debug, Clone, Copy)]
+pub struct ModuleContext<'a> {
+    pub name: &'a ModuleName,
+    /// Whether the module is the `__init__` of a package.
+    pub is_package: bool,
+    pub interfaces: &'a ModuleInterfaces,
+    pub references: &'a ProjectReferences,
+}
+
+/// The interfaces of the modules of a project, keyed by module name.
+#[derive(Debug, Default)]
+pub struct ModuleInterfaces {
+    modules: FxHashMap<ModuleName, ModuleInterface>,
+}
+
+impl ModuleInterfaces {
+    pub fn get(&self, module: &ModuleName) -> Option<&ModuleInterface> {
+        self.modules.get(module)
+    }
+
+    /// Add the interface of `module`, resolving its re-exports against the interfaces added so
+    /// far. Re-exports of modules that haven't been added (yet) become [`Symbol::Unknown`].
+    pub fn insert(&mut self, module: ModuleName, mut interface: ModuleInterface) {
+        for re_export in std::mem::take(&mut interface.re_exports) {
+            let symbol = self
+                .symbol(&re_export.module, &re_export.member)
+                .cloned()
+                .unwrap_or(Symbol::Unknown);
+            interface.symbols.insert(re_export.name, symbol);
+        }
+        for fallback in std::mem::take(&mut interface.fallbacks) {
+            let alternatives = fallback
+                .alternatives
+                .into_iter()
+                .map(|alternative| match alternative {
+                    Alternative::Symbol(symbol) => symbol,
+                    Alternative::ReExport(imported) => self
+                        .symbol(&imported.module, &imported.member)
+                        .cloned()
+                        .unwrap_or(Symbol::Unknown),
+                })
+                .collect();
+            interface
+                .symbols
+                .insert(fallback.name, Symbol::union(alternatives));
+        }
+        for function in std::mem::take(&mut interface.decorated) {
+            let preserving = function.decorators.iter().all(|decorator| {
+                matches!(
+                    self.symbol(&decorator.module, &decorator.member),
+                    Some(Symbol::Function(signature)) if signature.wraps_argument
+                )
+            });
+            if preserving {
+                interface
+                    .symbols
+                    .insert(function.name, Symbol::Function(function.signature));
+            }
+        }
+        self.modules.insert(module, interface);
+    }
+
+    /// The symbol `name` defined by `module`, if known.
+    pub fn symbol(&self, module: &ModuleName, name: &str) -> Option<&Symbol> {
+        self.modules.get(module)?.symbols.get(name)
+    }
+
+    /// A hash of the interfaces of `modules`, to invalidate cached results of modules that depend
+    /// on them.
+    pub fn fingerprint<'a>(&self, modules: impl IntoIterator<Item = &'a ModuleName>) -> u64 {
+        let mut hasher = CacheKeyHasher::new();
+        for module in modules {
+            module.as_str().hash(&mut hasher);
+            if let Some(interface) = self.modules.get(module) {
+                let mut symbols = interface.symbols.iter().collect::<Vec<_>>();
+                symbols.sort_unstable_by_key(|(name, _)| *name);
+                symbols.hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+}
+
+/// The names defined at the top level of a module.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleInterface {
+    symbols: FxHashMap<String, Symbol>,
+    /// Names imported from other modules, which are resolved when the interface is added to
+    /// [`ModuleInterfaces`].
+    re_exports: Vec<ReExport>,
+    /// Functions with decorators imported from other modules, whose signatures are known once
+    /// the interface is added to [`ModuleInterfaces`]. Until then, they're [`Symbol::Unknown`].
+    decorated: Vec<DecoratedFunction>,
+    /// Names bound both in the body of a `try` and in its `except ImportError` handler, which are
+    /// resolved to a [`Symbol::Union`] when the interface is added to [`ModuleInterfaces`].
+    fallbacks: Vec<Fallback>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ReExport {
+    name: String,
+    module: ModuleName,
+    member: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct DecoratedFunction {
+    name: String,
+    /// The signature of the function if all of the `decorators` preserve it.
+    signature: Signature,
+    decorators: Vec<ImportedName>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ImportedName {
+    module: ModuleName,
+    member: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Fallback {
+    name: String,
+    /// The bindings of the name, in the order of the `try` body and its handlers.
+    alternatives: Vec<Alternative>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+enum Alternative {
+    Symbol(Symbol),
+    ReExport(ImportedName),
+}
+
+/// What a name defined by a module refers to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Symbol {
+    /// A function, with its signature after applying its decorators.
+    Function(Signature),
+    Class {
+        /// The names defined in the body of the class.
+        members: BTreeMap<String, Symbol>,
+    },
+    /// A `property` of a class.
+    Property {
+        /// The source text of the getter's return annotation.
+        annotation: Option<String>,
+        /// Whether the property has a setter.
+        settable: bool,
+    },
+    Variable {
+        annotation: Option<String>,
+    },
+    /// A `TypedDict`, defined with the class syntax or the functional syntax.
+    TypedDict {
+        /// The keys, and whether each of them is required.
+        keys: BTreeMap<String, bool>,
+    },
+    /// A name whose meaning depends on which of several imports succeeds, as in
+    /// `try: from a import f` / `except ImportError: from b import f`.
+    Union(Vec<Symbol>),
+    /// A name whose meaning isn't known, e.g. because it has a decorator whose effect isn't
+    /// known, it's bound more than once or it's imported from a module outside of the project.
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Signature {
+    pub parameters: Vec<Parameter>,
+    /// The source text of the return annotation.
+    pub returns: Option<String>,
+    /// Whether the function is a decorator that returns a `functools.wraps` wrapper of the
+    /// function it decorates, so that decorated functions keep their signature.
+    pub wraps_argument: bool,
+    /// The keys of the `TypedDict` in a `**kwargs: Unpack[TypedDict]` parameter, and whether
+    /// each of them is required. Only `TypedDict`s defined in the same module are resolved.
+    pub unpacked_kwargs: Option<BTreeMap<String, bool>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Parameter {
+    pub name: String,
+    pub kind: ParameterKind,
+    pub has_default: bool,
+    /// The source text of the annotation.
+    pub annotation: Option<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ParameterKind {
+    PositionalOnly,
+    PositionalOrKeyword,
+    /// `*args`
+    VarPositional,
+    KeywordOnly,
+    /// `**kwargs`
+    VarKeyword,
+}
+
+impl Symbol {
+    /// The union of `alternatives`: the symbol itself if they're all the same, and
+    /// [`Symbol::Unknown`] if any of them is unknown.
+    fn union(alternatives: Vec<Symbol>) -> Self {
+        let mut symbols: Vec<Symbol> = vec![];
+        for alternative in alternatives {
+            let nested = match alternative {
+                Self::Union(nested) => nested,
+                Self::Unknown => return Self::Unknown,
+                symbol => vec![symbol],
+            };
+            for symbol in nested {
+                if !symbols.contains(&symbol) {
+                    symbols.push(symbol);
+                }
+            }
+        }
+        match symbols.len() {
+            0 => Self::Unknown,
+            1 => symbols.pop().unwrap_or(Self::Unknown),
+            _ => Self::Union(symbols),
+        }
+    }
+}
+
+impl Signature {
+    pub(crate) fn from_arguments(
+        arguments: &ast::Arguments,
+        returns: Option<&Expr>,
+        locator: &Locator,
+    ) -> Self {
+        let parameter = |arg: &ast::Arg, kind, has_default| Parameter {
+            name: arg.arg.to_string(),
+            kind,
+            has_default,
+            annotation: arg
+                .annotation
+                .as_ref()
+                .map(|annotation| locator.slice(annotation.range()).to_string()),
+        };
+
+        let mut parameters = vec![];
+        for arg in &arguments.posonlyargs {
+            parameters.push(parameter(
+                &arg.def,
+                ParameterKind::PositionalOnly,
+                arg.default.is_some(),
+            ));
+        }
+        for arg in &arguments.args {
+            parameters.push(parameter(
+                &arg.def,
+                ParameterKind::PositionalOrKeyword,
+                arg.default.is_some(),
+            ));
+        }
+        if let Some(arg) = &arguments.vararg {
+            parameters.push(parameter(arg, ParameterKind::VarPositional, false));
+        }
+        for arg in &arguments.kwonlyargs {
+            parameters.push(parameter(
+                &arg.def,
+                ParameterKind::KeywordOnly,
+                arg.default.is_some(),
+            ));
+        }
+        if let Some(arg) = &arguments.kwarg {
+            parameters.push(parameter(arg, ParameterKind::VarKeyword, false));
+        }
+
+        Self {
+            parameters,
+            returns: returns.map(|returns| locator.slice(returns.range()).to_string()),
+            wraps_argument: false,
+            unpacked_kwargs: None,
+        }
+    }
+
+    /// Check a call with `positional` positional arguments and the given keyword arguments
+    /// against the signature, returning a description of the first mismatch.
+    pub fn check_call(&self, positional: usize, keywords: &[&str]) -> Option<String> {
+        let positional_parameters = self
+            .parameters
+            .iter()
+            .filter(|parameter| {
+                matches!(
+                    parameter.kind,
+                    ParameterKind::PositionalOnly | ParameterKind::PositionalOrKeyword
+                )
+            })
+            .collect::<Vec<_>>();
+        let has_kind = |kind| {
+            self.parameters
+                .iter()
+                .any(|parameter| parameter.kind == kind)
+        };
+
+        if positional > positional_parameters.len() && !has_kind(ParameterKind::VarPositional) {
+            return Some(format!(
+                "expected at most {} positional arguments, got {positional}",
+                positional_parameters.len()
+            ));
+        }
+
+        let mut bound = positional_parameters
+            .iter()
+            .take(positional)
+            .map(|parameter| parameter.name.as_str())
+            .collect::<Vec<_>>();
+        for keyword in keywords {
+            let parameter = self.parameters.iter().find(|parameter| {
+                parameter.name == *keyword
+                    && matches!(
+                        parameter.kind,
+                        ParameterKind::PositionalOrKeyword | ParameterKind::KeywordOnly
+                    )
+            });
+            match parameter {
+                Some(parameter) if bound.contains(&parameter.name.as_str()) => {
+                    return Some(format!("got multiple values for argument `{keyword}`"));
+                }
+                Some(parameter) => bound.push(parameter.name.as_str()),
+                None => match &self.unpacked_kwargs {
+                    Some(keys) if keys.contains_key(*keyword) => bound.push(*keyword),
+                    None if has_kind(ParameterKind::VarKeyword) => {}
+                    _ => return Some(format!("got an unexpected keyword argument `{keyword}`")),
+                },
+            }
+        }
+
+        let missing = self
+            .parameters
+            .iter()
+            .filter(|parameter| {
+                !parameter.has_default
+                    && !matches!(
+                        parameter.kind,
+                        ParameterKind::VarPositional | ParameterKind::VarKeyword
+                    )
+                    && !bound.contains(&parameter.name.as_str())
+            })
+            .map(|parameter| parameter.name.as_str())
+            .chain(
+                self.unpacked_kwargs
+                    .iter()
+                    .flatten()
+                    .filter(|(key, required)| **required && !bound.contains(&key.as_str()))
+                    .map(|(key, _)| key.as_str()),
+            )
+            .map(|name| format!("`{name}`"))
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            None
+        } else {
+            Some(format!(
+                "missing required arguments: {}",
+                missing.join(", ")
+            ))
+        }
+    }
+}
+
+/// Formats the signature as in a function definition, like `(x: int, *, y=...) -> str`, with
+/// `...` in place of the default values.
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        let mut previous = None;
+        for (index, parameter) in self.parameters.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            if previous == Some(ParameterKind::PositionalOnly)
+                && parameter.kind != ParameterKind::PositionalOnly
+            {
+                f.write_str("/, ")?;
+            }
+            if parameter.kind == ParameterKind::KeywordOnly
+                && !matches!(
+                    previous,
+                    Some(ParameterKind::VarPositional | ParameterKind::KeywordOnly)
+                )
+            {
+                f.write_str("*, ")?;
+            }
+            match parameter.kind {
+                ParameterKind::VarPositional => f.write_str("*")?,
+                ParameterKind::VarKeyword => f.write_str("**")?,
+                _ => {}
+            }
+            f.write_str(&parameter.name)?;
+            match (&parameter.annotation, parameter.has_default) {
+                (Some(annotation), true) => write!(f, ": {annotation} = ...")?,
+                (Some(annotation), false) => write!(f, ": {annotation}")?,
+                (None, true) => f.write_str("=...")?,
+                (None, false) => {}
+            }
+            previous = Some(parameter.kind);
+        }
+        if previous == Some(ParameterKind::PositionalOnly) {
+            f.write_str(", /")?;
+        }
+        f.write_str(")")?;
+        if let Some(returns) = &self.returns {
+            write!(f, " -> {returns}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ModuleInterface {
+    /// Collect the names defined at the top level of `python_ast`, the body of `module`.
+    ///
+    /// Definitions nested in control flow (like `if` or `try`) are included. Names that are
+    /// bound more than once are [`Symbol::Unknown`], since which binding applies depends on the
+    /// control flow, except for fallbacks in an `except ImportError` handler for names bound in
+    /// the body of the `try`, which are a [`Symbol::Union`] of the bindings.
+    pub fn from_ast(
+        python_ast: &[Stmt],
+        locator: &Locator,
+        module: &ModuleName,
+        is_package: bool,
+    ) -> Self {
+        let mut builder = InterfaceBuilder {
+            locator,
+            module,
+            is_package,
+            imports: FxHashMap::default(),
+            enclosing: vec![],
+            interface: Self::default(),
+            import_fallbacks: FxHashSet::default(),
+        };
+        builder.visit_body(python_ast);
+        builder.interface
+    }
+
+    pub fn symbol(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.get(name)
+    }
+}
+
+struct InterfaceBuilder<'a> {
+    locator: &'a Locator<'a>,
+    module: &'a ModuleName,
+    is_package: bool,
+    /// The qualified names of the imported modules and members, by the name they're bound to.
+    imports: FxHashMap<String, String>,
+    /// The scopes enclosing the class body that is being visited, outermost first.
+    enclosing: Vec<ModuleInterface>,
+    interface: ModuleInterface,
+    /// The names bound in the body of the `try` whose `except ImportError` handler is being
+    /// visited, whose rebindings are alternatives rather than redefinitions.
+    import_fallbacks: FxHashSet<String>,
+}
+
+/// The effect of a decorator on the signature of the function it decorates.
+enum Decorator {
+    /// Returns a function with the same signature, like `functools.lru_cache`.
+    Preserving,
+    /// `classmethod`, which binds the first parameter to the class.
+    ClassMethod,
+    /// `contextlib.contextmanager` or `contextlib.asynccontextmanager`.
+    ContextManager {
+        is_async: bool,
+    },
+    /// `functools.wraps(wrapped)`, which gives the function the signature of `wrapped`.
+    Wraps(Signature),
+    Property,
+    /// The `setter`, `getter` or `deleter` of the property `property`.
+    PropertyAccessor {
+        property: String,
+        setter: bool,
+    },
+    /// A decorator imported from another module of the project.
+    Imported(ImportedName),
+    Unknown,
+}
+
+impl InterfaceBuilder<'_> {
+    /// Whether `name` is already bound in the current scope.
+    fn is_defined(&self, name: &str) -> bool {
+        self.interface.symbols.contains_key(name)
+            || self
+                .interface
+                .re_exports
+                .iter()
+                .any(|re_export| re_export.name == name)
+    }
+
+    /// The names bound in the current scope.
+    fn bound_names(&self) -> FxHashSet<String> {
+        self.interface
+            .symbols
+            .keys()
+            .cloned()
+            .chain(
+                self.interface
+                    .re_exports
+                    .iter()
+                    .map(|re_export| re_export.name.clone()),
+            )
+            .collect()
+    }
+
+    fn define(&mut self, name: &str, symbol: Symbol) {
+        if !self.is_defined(name) {
+            self.interface.symbols.insert(name.to_string(), symbol);
+        } else if self.import_fallbacks.contains(name) {
+            self.add_alternative(name, Alternative::Symbol(symbol));
+        } else {
+            let interface = &mut self.interface;
+            interface
+                .re_exports
+                .retain(|re_export| re_export.name != name);
+            interface.decorated.retain(|function| function.name != name);
+            interface.fallbacks.retain(|fallback| fallback.name != name);
+            interface.symbols.insert(name.to_string(), Symbol::Unknown);
+        }
+    }
+
+    /// Add `alternative` to the bindings of `name`, which is bound in the body of a `try` and
+    /// rebound in its `except ImportError` handler. The name is [`Symbol::Unknown`] until the
+    /// alternatives are resolved.
+    fn add_alternative(&mut self, name: &str, alternative: Alternative) {
+        let interface = &mut self.interface;
+        if let Some(fallback) = interface
+            .fallbacks
+            .iter_mut()
+            .find(|fallback| fallback.name == name)
+        {
+            fallback.alternatives.push(alternative);
+            return;
+        }
+
+        let existing = if let Some(index) = interface
+            .re_exports
+            .iter()
+            .position(|re_export| re_export.name == name)
+        {
+            let re_export = interface.re_exports.remove(index);
+            Alternative::ReExport(ImportedName {
+                module: re_export.module,
+                member: re_export.member,
+            })
+        } else if interface
+            .decorated
+            .iter()
+            .any(|function| function.name == name)
+        {
+            interface.decorated.retain(|function| function.name != name);
+            Alternative::Symbol(Symbol::Unknown)
+        } else {
+            Alternative::Symbol(
+                interface
+                    .symbols
+                    .get(name)
+                    .cloned()
+                    .unwrap_or(Symbol::Unknown),
+            )
+        };
+        interface.symbols.insert(name.to_string(), Symbol::Unknown);
+        interface.fallbacks.push(Fallback {
+            name: name.to_string(),
+            alternatives: vec![existing, alternative],
+        });
+    }
+
+    fn re_export(&mut self, name: &str, module: ModuleName, member: &str) {
+        if self.is_defined(name) && self.import_fallbacks.contains(name) {
+            self.add_alternative(
+                name,
+                Alternative::ReExport(ImportedName {
+                    module,
+                    member: member.to_string(),
+                }),
+            );
+        } else if self.is_defined(name) {
+            self.define(name, Symbol::Unknown);
+        } else {
+            self.interface.re_exports.push(ReExport {
+                name: name.to_string(),
+                module,
+                member: member.to_string(),
+            });
+        }
+    }
+
+    fn scopes(&self) -> impl Iterator<Item = &ModuleInterface> {
+        std::iter::once(&self.interface).chain(self.enclosing.iter().rev())
+    }
+
+    /// The symbol that `name` refers to from the current scope.
+    fn lookup(&self, name: &str) -> Option<&Symbol> {
+        self.scopes().find_map(|scope| scope.symbols.get(name))
+    }
+
+    fn lookup_re_export(&self, name: &str) -> Option<&ReExport> {
+        self.scopes().find_map(|scope| {
+            scope
+                .re_exports
+                .iter()
+                .find(|re_export| re_export.name == name)
+        })
+    }
+
+    /// The qualified name of the imported member or builtin that `expr` refers to, like
+    /// `functools.wraps` for `wraps` after `from functools import wraps`.
+    fn qualified_name(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Name(ast::ExprName { id, .. }) => {
+                if let Some(qualified_name) = self.imports.get(id.as_str()) {
+                    Some(qualified_name.clone())
+                } else if self.lookup(id.as_str()).is_some()
+                    || self.lookup_re_export(id.as_str()).is_some()
+                {
+                    None
+                } else {
+                    Some(format!("builtins.{}", id.as_str()))
+                }
+            }
+            Expr::Attribute(ast::ExprAttribute { value, attr, .. }) => {
+                Some(format!("{}.{}", self.qualified_name(value)?, attr.as_str()))
+            }
+            _ => None,
+        }
+    }
+
+    fn classify_decorator(&self, decorator: &Expr) -> Decorator {
+        let (callee, arguments) = match decorator {
+            Expr::Call(ast::ExprCall { func, args, .. }) => (func.as_ref(), Some(args.as_slice())),
+            _ => (decorator, None),
+        };
+        match (self.qualified_name(callee).as_deref(), arguments) {
+            (Some("functools.lru_cache"), _)
+            | (
+                Some(
+                    "functools.cache"
+                    | "typing.final"
+                    | "typing_extensions.final"
+                    | "typing.override"
+                    | "typing_extensions.override"
+                    | "builtins.staticmethod",
+                ),
+                None,
+            ) => return Decorator::Preserving,
+            (Some("builtins.classmethod"), None) => return Decorator::ClassMethod,
+            (Some("builtins.property" | "functools.cached_property"), None) => {
+                return Decorator::Property;
+            }
+            (Some("contextlib.contextmanager"), None) => {
+                return Decorator::ContextManager { is_async: false };
+            }
+            (Some("contextlib.asynccontextmanager"), None) => {
+                return Decorator::ContextManager { is_async: true };
+            }
+            (Some("functools.wraps"), Some([Expr::Name(ast::ExprName { id, .. }), ..])) => {
+                return match self.lookup(id.as_str()) {
+                    Some(Symbol::Function(wrapped)) => Decorator::Wraps(wrapped.clone()),
+                    _ => Decorator::Unknown,
+                };
+            }
+            _ => {}
+        }
+
+        match decorator {
+            Expr::Name(ast::ExprName { id, .. }) => {
+                if let Some(re_export) = self.lookup_re_export(id.as_str()) {
+                    Decorator::Imported(ImportedName {
+                        module: re_export.module.clone(),
+                        member: re_export.member.clone(),
+                    })
+                } else if matches!(
+                    self.lookup(id.as_str()),
+                    Some(Symbol::Function(signature)) if signature.wraps_argument
+                ) {
+                    Decorator::Preserving
+                } else {
+                    Decorator::Unknown
+                }
+            }
+            Expr::Attribute(ast::ExprAttribute { value, attr, .. }) => match value.as_ref() {
+                Expr::Name(ast::ExprName { id, .. })
+                    if matches!(attr.as_str(), "setter" | "getter" | "deleter")
+                        && matches!(self.lookup(id.as_str()), Some(Symbol::Property { .. })) =>
+                {
+                    Decorator::PropertyAccessor {
+                        property: id.to_string(),
+                        setter: attr.as_str() == "setter",
+                    }
+                }
+                _ => Decorator::Unknown,
+            },
+            _ => Decorator::Unknown,
+        }
+    }
+
+    /// Whether a function is a decorator that returns a `functools.wraps` wrapper of its only
+    /// parameter, like:
+    ///
+    /// ```python
+    /// def logged(func):
+    ///     @functools.wraps(func)
+    ///     def wrapper(*args, **kwargs): ...
+    ///     return wrapper
+    /// ```
+    fn returns_wrapper(&self, arguments: &ast::Arguments, body: &[Stmt]) -> bool {
+        let ([parameter], []) = (arguments.args.as_slice(), arguments.posonlyargs.as_slice())
+        else {
+            return false;
+        };
+        let parameter = parameter.def.arg.as_str();
+        let is_wrapper = |name: &str| {
+            body.iter().any(|stmt| match stmt {
+                Stmt::FunctionDef(ast::StmtFunctionDef {
+                    name: wrapper,
+                    decorator_list,
+                    ..
+                })
+                | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+                    name: wrapper,
+                    decorator_list,
+                    ..
+                }) if wrapper.as_str() == name => decorator_list.iter().any(|decorator| {
+                    matches!(
+                        decorator,
+                        Expr::Call(ast::ExprCall { func, args, .. })
+                            if self.qualified_name(func).as_deref() == Some("functools.wraps")
+                                && matches!(
+                                    args.first(),
+                                    Some(Expr::Name(ast::ExprName { id, .. }))
+                                        if id.as_str() == parameter
+                                )
+                    )
+                }),
+                _ => false,
+            })
+        };
+        body.iter().any(|stmt| {
+            matches!(
+                stmt,
+                Stmt::Return(ast::StmtReturn { value: Some(value), .. })
+                    if matches!(
+                        value.as_ref(),
+                        Expr::Name(ast::ExprName { id, .. }) if is_wrapper(id.as_str())
+                    )
+            )
+        })
+    }
+
+    fn define_function(
+        &mut self,
+        name: &str,
+        arguments: &ast::Arguments,
+        returns: Option<&Expr>,
+        decorator_list: &[Expr],
+        body: &[Stmt],
+    ) {
+        let mut signature = Signature::from_arguments(arguments, returns, self.locator);
+        signature.wraps_argument = self.returns_wrapper(arguments, body);
+        signature.unpacked_kwargs = arguments
+            .kwarg
+            .as_ref()
+            .and_then(|kwarg| kwarg.annotation.as_deref())
+            .and_then(|annotation| self.unpacked_typed_dict(annotation));
+
+        let mut imported = vec![];
+        // Decorators are applied bottom-up. Properties have to be the outermost decorator.
+        for (index, decorator) in decorator_list.iter().enumerate().rev() {
+            let outermost = index == 0;
+            match self.classify_decorator(decorator) {
+                Decorator::Preserving => {}
+                Decorator::ClassMethod => {
+                    if signature.parameters.first().is_some_and(|parameter| {
+                        matches!(
+                            parameter.kind,
+                            ParameterKind::PositionalOnly | ParameterKind::PositionalOrKeyword
+                        )
+                    }) {
+                        signature.parameters.remove(0);
+                    }
+                }
+                Decorator::ContextManager { is_async } => {
+                    signature.returns = context_manager_annotation(returns, is_async, self.locator);
+                }
+                Decorator::Wraps(wrapped) => {
+                    signature = wrapped;
+                    imported.clear();
+                }
+                Decorator::Property if outermost => {
+                    self.define(
+                        name,
+                        Symbol::Property {
+                            annotation: signature.returns,
+                            settable: false,
+                        },
+                    );
+                    return;
+                }
+                Decorator::PropertyAccessor { property, setter }
+                    if outermost && property == name =>
+                {
+                    if let Some(Symbol::Property { settable, .. }) =
+                        self.interface.symbols.get_mut(name)
+                    {
+                        *settable |= setter;
+                    } else {
+                        self.define(name, Symbol::Unknown);
+                    }
+                    return;
+                }
+                Decorator::Imported(decorator) => imported.push(decorator),
+                Decorator::Property | Decorator::PropertyAccessor { .. } | Decorator::Unknown => {
+                    self.define(name, Symbol::Unknown);
+                    return;
+                }
+            }
+        }
+
+        if imported.is_empty() {
+            self.define(name, Symbol::Function(signature));
+        } else {
+            let rebound = self.is_defined(name);
+            self.define(name, Symbol::Unknown);
+            if !rebound {
+                self.interface.decorated.push(DecoratedFunction {
+                    name: name.to_string(),
+                    signature,
+                    decorators: imported,
+                });
+            }
+        }
+    }
+
+    /// The keys of the `TypedDict` `TD` in an annotation `Unpack[TD]`.
+    fn unpacked_typed_dict(&self, annotation: &Expr) -> Option<BTreeMap<String, bool>> {
+        let Expr::Subscript(ast::ExprSubscript { value, slice, .. }) = annotation else {
+            return None;
+        };
+        if !matches!(
+            self.qualified_name(value).as_deref(),
+            Some("typing.Unpack" | "typing_extensions.Unpack")
+        ) {
+            return None;
+        }
+        let Expr::Name(ast::ExprName { id, .. }) = slice.as_ref() else {
+            return None;
+        };
+        match self.lookup(id.as_str()) {
+            Some(Symbol::TypedDict { keys }) => Some(keys.clone()),
+            _ => None,
+        }
+    }
+
+    fn is_typed_dict(&self, expr: &Expr) -> bool {
+        matches!(
+            self.qualified_name(expr).as_deref(),
+            Some("typing.TypedDict" | "typing_extensions.TypedDict")
+        )
+    }
+
+    /// Whether a key annotated with `annotation` is required, given the `total` of its
+    /// `TypedDict`.
+    fn is_required(&self, annotation: &Expr, total: bool) -> bool {
+        let Expr::Subscript(ast::ExprSubscript { value, .. }) = annotation else {
+            return total;
+        };
+        match self.qualified_name(value).as_deref() {
+            Some("typing.Required" | "typing_extensions.Required") => true,
+            Some("typing.NotRequired" | "typing_extensions.NotRequired") => false,
+            _ => total,
+        }
+    }
+
+    /// The symbol of a class that derives from `TypedDict`, or `None` if the class isn't a
+    /// `TypedDict`. The keys of `TypedDict` bases are inherited; a `TypedDict` with other bases
+    /// (like one imported from another module) is [`Symbol::Unknown`].
+    fn typed_dict_class(
+        &self,
+        bases: &[Expr],
+        keywords: &[ast::Keyword],
+        body: &[Stmt],
+    ) -> Option<Symbol> {
+        let mut keys = BTreeMap::new();
+        let mut is_typed_dict = false;
+        let mut complete = true;
+        for base in bases {
+            if self.is_typed_dict(base) {
+                is_typed_dict = true;
+                continue;
+            }
+            let inherited = match base {
+                Expr::Name(ast::ExprName { id, .. }) => match self.lookup(id.as_str()) {
+                    Some(Symbol::TypedDict { keys }) => Some(keys),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some(inherited) = inherited {
+                is_typed_dict = true;
+                keys.extend(inherited.clone());
+            } else if !matches!(
+                base,
+                Expr::Subscript(ast::ExprSubscript { value, .. })
+                    if self.qualified_name(value).as_deref() == Some("typing.Generic")
+            ) {
+                complete = false;
+            }
+        }
+        if !is_typed_dict {
+            return None;
+        }
+        if !complete {
+            return Some(Symbol::Unknown);
+        }
+
+        let total = is_total(keywords);
+        for stmt in body {
+            if let Stmt::AnnAssign(ast::StmtAnnAssign {
+                target, annotation, ..
+            }) = stmt
+            {
+                if let Expr::Name(ast::ExprName { id, .. }) = target.as_ref() {
+                    keys.insert(id.to_string(), self.is_required(annotation, total));
+                }
+            }
+        }
+        Some(Symbol::TypedDict { keys })
+    }
+
+    /// The symbol of a `TypedDict` defined with the functional syntax, like
+    /// `Movie = TypedDict("Movie", {"name": str, "year": int}, total=False)`.
+    fn typed_dict_call(&self, value: &Expr) -> Option<Symbol> {
+        let Expr::Call(ast::ExprCall {
+            func,
+            args,
+            keywords,
+            ..
+        }) = value
+        else {
+            return None;
+        };
+        if !self.is_typed_dict(func) {
+            return None;
+        }
+        let Some(Expr::Dict(ast::ExprDict {
+            keys: fields,
+            values,
+            ..
+        })) = args.get(1)
+        else {
+            return Some(Symbol::Unknown);
+        };
+        let total = is_total(keywords);
+        let mut keys = BTreeMap::new();
+        for (key, annotation) in fields.iter().zip(values) {
+            let Some(Expr::Constant(ast::ExprConstant {
+                value: Constant::Str(key),
+                ..
+            })) = key
+            else {
+                return Some(Symbol::Unknown);
+            };
+            keys.insert(key.clone(), self.is_required(annotation, total));
+        }
+        Some(Symbol::TypedDict { keys })
+    }
+
+    /// The names defined in the body of a class. Names imported in the class body (including
+    /// import fallbacks) and methods with decorators imported from other modules are
+    /// [`Symbol::Unknown`].
+    fn class_members(&mut self, body: &[Stmt]) -> BTreeMap<String, Symbol> {
+        let enclosing = std::mem::take(&mut self.interface);
+        self.enclosing.push(enclosing);
+        let import_fallbacks = std::mem::take(&mut self.import_fallbacks);
+        self.visit_body(body);
+        self.import_fallbacks = import_fallbacks;
+        let enclosing = self.enclosing.pop().unwrap_or_default();
+        let class = std::mem::replace(&mut self.interface, enclosing);
+
+        let mut members = class.symbols.into_iter().collect::<BTreeMap<_, _>>();
+        for re_export in class.re_exports {
+            members.insert(re_export.name, Symbol::Unknown);
+        }
+        members
+    }
+
+    fn define_target(&mut self, target: &Expr, symbol: &Symbol) {
+        match target {
+            Expr::Name(ast::ExprName { id, .. }) => self.define(id.as_str(), symbol.clone()),
+            Expr::Tuple(ast::ExprTuple { elts, .. }) | Expr::List(ast::ExprList { elts, .. }) => {
+                for elt in elts {
+                    self.define_target(elt, &Symbol::Unknown);
+                }
+            }
+            Expr::Starred(ast::ExprStarred { value, .. }) => {
+                self.define_target(value, &Symbol::Unknown);
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_body(&mut self, body: &[Stmt]) {
+        for stmt in body {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef {
+                name,
+                args,
+                returns,
+                decorator_list,
+                body,
+                ..
+            })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+                name,
+                args,
+                returns,
+                decorator_list,
+                body,
+                ..
+            }) => {
+                self.define_function(
+                    name.as_str(),
+                    args,
+                    returns.as_deref(),
+                    decorator_list,
+                    body,
+                );
+            }
+            Stmt::ClassDef(ast::StmtClassDef {
+                name,
+                bases,
+                keywords,
+                body,
+                ..
+            }) => {
+                if let Some(typed_dict) = self.typed_dict_class(bases, keywords, body) {
+                    self.define(name.as_str(), typed_dict);
+                } else {
+                    let members = self.class_members(body);
+                    self.define(name.as_str(), Symbol::Class { members });
+                }
+            }
+            Stmt::AnnAssign(ast::StmtAnnAssign {
+                target, annotation, ..
+            }) => {
+                let annotation = self.locator.slice(annotation.range()).to_string();
+                self.define_target(
+                    target,
+                    &Symbol::Variable {
+                        annotation: Some(annotation),
+                    },
+                );
+            }
+            Stmt::Assign(ast::StmtAssign { targets, value, .. }) => {
+                let symbol = self
+                    .typed_dict_call(value)
+                    .unwrap_or(Symbol::Variable { annotation: None });
+                for target in targets {
+                    self.define_target(target, &symbol);
+                }
+            }
+            Stmt::AugAssign(ast::StmtAugAssign { target, .. }) => {
+                self.define_target(target, &Symbol::Unknown);
+            }
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                for alias in names {
+                    let (name, qualified_name) = match &alias.asname {
+                        Some(asname) => (asname.as_str(), alias.name.as_str()),
+                        None => {
+                            let name = alias.name.as_str().split('.').next().unwrap_or_default();
+                            (name, name)
+                        }
+                    };
+                    self.imports
+                        .insert(name.to_string(), qualified_name.to_string());
+                    self.define(name, Symbol::Unknown);
+                }
+            }
+            Stmt::ImportFrom(ast::StmtImportFrom {
+                module,
+                names,
+                level,
+                ..
+            }) => {
+                let imported = self.module.resolve_import(
+                    self.is_package,
+                    level.map_or(0, |level| level.to_u32()),
+                    module.as_ref().map(ast::Identifier::as_str),
+                );
+                for alias in names {
+                    if alias.name.as_str() == "*" {
+                        continue;
+                    }
+                    let name = alias.asname.as_ref().unwrap_or(&alias.name).as_str();
+                    match &imported {
+                        Some(imported) => {
+                            self.imports.insert(
+                                name.to_string(),
+                                format!("{imported}.{}", alias.name.as_str()),
+                            );
+                            self.re_export(name, imported.clone(), alias.name.as_str());
+                        }
+                        None => self.define(name, Symbol::Unknown),
+                    }
+                }
+            }
+            Stmt::If(ast::StmtIf { body, orelse, .. })
+            | Stmt::While(ast::StmtWhile { body, orelse, .. }) => {
+                self.visit_body(body);
+                self.visit_body(orelse);
+            }
+            Stmt::For(ast::StmtFor {
+                target,
+                body,
+                orelse,
+                ..
+            })
+            | Stmt::AsyncFor(ast::StmtAsyncFor {
+                target,
+                body,
+                orelse,
+                ..
+            }) => {
+                self.define_target(target, &Symbol::Unknown);
+                self.visit_body(body);
+                self.visit_body(orelse);
+            }
+            Stmt::With(ast::StmtWith { items, body, .. })
+            | Stmt::AsyncWith(ast::StmtAsyncWith { items, body, .. }) => {
+                for item in items {
+                    if let Some(target) = &item.optional_vars {
+                        self.define_target(target, &Symbol::Unknown);
+                    }
+                }
+                self.visit_body(body);
+            }
+            Stmt::Try(ast::StmtTry {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            })
+            | Stmt::TryStar(ast::StmtTryStar {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            }) => {
+                let before = self.bound_names();
+                self.visit_body(body);
+                let mut bound = self.bound_names();
+                bound.retain(|name| !before.contains(name));
+
+                for handler in handlers {
+                    let guards_import = catches_import_error(handler);
+                    let ast::ExceptHandler::ExceptHandler(handler) = handler;
+                    if let Some(name) = &handler.name {
+                        self.define(name.as_str(), Symbol::Unknown);
+                    }
+                    if guards_import {
+                        let import_fallbacks = self.import_fallbacks.clone();
+                        self.import_fallbacks.extend(bound.iter().cloned());
+                        self.visit_body(&handler.body);
+                        self.import_fallbacks = import_fallbacks;
+                    } else {
+                        self.visit_body(&handler.body);
+                    }
+                }
+                self.visit_body(orelse);
+                self.visit_body(finalbody);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whether the keys of a `TypedDict` are required by default, which they are unless it's
+/// defined with `total=False`.
+fn is_total(keywords: &[ast::Keyword]) -> bool {
+    !keywords.iter().any(|keyword| {
+        keyword
+            .arg
+            .as_ref()
+            .is_some_and(|arg| arg.as_str() == "total")
+            && matches!(
+                keyword.value,
+                Expr::Constant(ast::ExprConstant {
+                    value: Constant::Bool(false),
+                    ..
+                })
+            )
+    })
+}
+
+/// The return annotation of a generator function decorated with `contextlib.contextmanager`: a
+/// context manager of the type that the generator yields, e.g. `Iterator[int]` becomes
+/// `contextlib.AbstractContextManager[int]`.
+fn context_manager_annotation(
+    returns: Option<&Expr>,
+    is_async: bool,
+    locator: &Locator,
+) -> Option<String> {
+    let Some(Expr::Subscript(ast::ExprSubscript { slice, .. })) = returns else {
+        return None;
+    };
+    let yielded = match slice.as_ref() {
+        Expr::Tuple(ast::ExprTuple { elts, .. }) => elts.first()?,
+        slice => slice,
+    };
+    let manager = if is_async {
+        "contextlib.AbstractAsyncContextManager"
+    } else {
+        "contextlib.AbstractContextManager"
+    };
+    Some(format!("{manager}[{}]", locator.slice(yielded.range())))
+}
+
+/// Parse `source`, the contents of `module`, and return the modules it imports along with its
+/// interface. Returns `None` if `source` contains syntax errors, which are reported when the
+/// module itself is checked.
+pub fn summarize_module(
+    source: &str,
+    source_type: PySourceType,
+    module: &ModuleName,
+    is_package: bool,
+) -> Option<(Vec<ModuleName>, ModuleInterface)> {
+    let python_ast = rustpython_parser::parse(source, source_type.as_mode(), module.as_str())
+        .ok()?
+        .expect_module()
+        .body;
+    Some((
+        module_dependencies(&python_ast, module, is_package),
+        ModuleInterface::from_ast(&python_ast, &Locator::new(source), module, is_package),
+    ))
+}
+
+/// The modules that `python_ast`, the body of `module`, imports.
+///
+/// For `from a import b`, both `a` and `a.b` are included, since `b` may be a submodule. Imports
+/// nested in functions and classes are included as well.
+pub fn module_dependencies(
+    python_ast: &[Stmt],
+    module: &ModuleName,
+    is_package: bool,
+) -> Vec<ModuleName> {
+    struct DependencyCollector<'a> {
+        module: &'a ModuleName,
+        is_package: bool,
+        dependencies: Vec<ModuleName>,
+    }
+
+    impl<'a> Visitor<'a> for DependencyCollector<'_> {
+        fn visit_stmt(&mut self, stmt: &'a Stmt) {
+            match stmt {
+                Stmt::Import(ast::StmtImport { names, .. }) => {
+                    self.dependencies.extend(
+                        names
+                            .iter()
+                            .filter_map(|alias| ModuleName::new(alias.name.as_str())),
+                    );
+                }
+                Stmt::ImportFrom(ast::StmtImportFrom {
+                    module,
+                    names,
+                    level,
+                    ..
+                }) => {
+                    let Some(imported) = self.module.resolve_import(
+                        self.is_package,
+                        level.map_or(0, |level| level.to_u32()),
+                        module.as_ref().map(ast::Identifier::as_str),
+                    ) else {
+                        return;
+                    };
+                    for alias in names {
+                        if let Some(member) = ModuleName::new(alias.name.as_str()) {
+                            self.dependencies.push(imported.join(&member));
+                        }
+                    }
+                    self.dependencies.push(imported);
+                }
+                _ => visitor::walk_stmt(self, stmt),
+            }
+        }
+    }
+
+    let mut collector = DependencyCollector {
+        module,
+        is_package,
+        dependencies: vec![],
+    };
+    collector.visit_body(python_ast);
+    collector.dependencies.sort_unstable();
+    collector.dependencies.dedup();
+    collector.dependencies
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrogen_module_resolver::ModuleName;
+    use pyrogen_source_file::Locator;
+    use rustpython_parser::{parse, Mode};
+
+    use super::{module_dependencies, ModuleInterface, ModuleInterfaces, Symbol};
+
+    fn interface(source: &str, module: &str, is_package: bool) -> ModuleInterface {
+        let python_ast = parse(source, Mode::Module, "<test>")
+            .unwrap()
+            .expect_module()
+            .body;
+        ModuleInterface::from_ast(
+            &python_ast,
+            &Locator::new(source),
+            &ModuleName::new(module).unwrap(),
+            is_package,
+        )
+    }
+
+    #[test]
+    fn signatures_and_re_exports() {
+        let mut interfaces = ModuleInterfaces::default();
+        interfaces.insert(
+            ModuleName::new("pkg.impl").unwrap(),
+            interface(
+                r#"
+def helper(a: int, /, b, *args, c: str = "", **kwargs) -> bool: ...
+
+@decorator
+def decorated(x): ...
+"#,
+                "pkg.impl",
+                false,
+            ),
+        );
+        interfaces.insert(
+            ModuleName::new("pkg").unwrap(),
+            interface(
+                "from .impl import helper as renamed, decorated\nfrom os import path\n",
+                "pkg",
+                true,
+            ),
+        );
+
+        let pkg = ModuleName::new("pkg").unwrap();
+        let Some(Symbol::Function(signature)) = interfaces.symbol(&pkg, "renamed") else {
+            panic!("expected a function");
+        };
+        assert_eq!(
+            signature
+                .parameters
+                .iter()
+                .map(|parameter| parameter.name.as_str())
+                .collect::<Vec<_>>(),
+            ["a", "b", "args", "c", "kwargs"]
+        );
+        assert_eq!(signature.returns.as_deref(), Some("bool"));
+        assert_eq!(interfaces.symbol(&pkg, "decorated"), Some(&Symbol::Unknown));
+        assert_eq!(interfaces.symbol(&pkg, "path"), Some(&Symbol::Unknown));
+    }
+
+    #[test]
+    fn check_call() {
+        let interface = interface("def f(a, b=1, *, c): ...\n", "mod", false);
+        let Some(Symbol::Function(signature)) = interface.symbol("f") else {
+            panic!("expected a function");
+        };
+        assert_eq!(signature.check_call(1, &["c"]), None);
+        assert_eq!(
+            signature.check_call(3, &["c"]).as_deref(),
+            Some("expected at most 2 positional arguments, got 3")
+        );
+        assert_eq!(
+            signature.check_call(1, &["a", "c"]).as_deref(),
+            Some("got multiple values for argument `a`")
+        );
+        assert_eq!(
+            signature.check_call(1, &["d"]).as_deref(),
+            Some("got an unexpected keyword argument `d`")
+        );
+        assert_eq!(
+            signature.check_call(0, &[]).as_deref(),
+            Some("missing required arguments: `a`, `c`")
+        );
+    }
+
+    fn parameters(symbol: Option<&Symbol>) -> Vec<&str> {
+        let Some(Symbol::Function(signature)) = symbol else {
+            panic!("expected a function, got {symbol:?}");
+        };
+        signature
+            .parameters
+            .iter()
+            .map(|parameter| parameter.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn decorators() {
+        let interface = interface(
+            r#"
+import contextlib
+from functools import lru_cache, wraps
+from typing import Iterator
+
+def logged(func):
+    @wraps(func)
+    def wrapper(*args, **kwargs):
+        return func(*args, **kwargs)
+    return wrapper
+
+@lru_cache(maxsize=None)
+def cached(a, b=1): ...
+
+@logged
+def traced(a, *, key): ...
+
+@contextlib.contextmanager
+def opened(path: str) -> Iterator[int]: ...
+
+def original(x, y): ...
+
+@wraps(original)
+def proxy(*args, **kwargs): ...
+
+class C:
+    @property
+    def value(self) -> int: ...
+
+    @value.setter
+    def value(self, value: int) -> None: ...
+
+    @classmethod
+    def create(cls, a): ...
+"#,
+            "mod",
+            false,
+        );
+
+        assert_eq!(parameters(interface.symbol("cached")), ["a", "b"]);
+        assert_eq!(parameters(interface.symbol("traced")), ["a", "key"]);
+        assert_eq!(parameters(interface.symbol("proxy")), ["x", "y"]);
+        let Some(Symbol::Function(opened)) = interface.symbol("opened") else {
+            panic!("expected a function");
+        };
+        assert_eq!(
+            opened.returns.as_deref(),
+            Some("contextlib.AbstractContextManager[int]")
+        );
+
+        let Some(Symbol::Class { members }) = interface.symbol("C") else {
+            panic!("expected a class");
+        };
+        assert_eq!(
+            members.get("value"),
+            Some(&Symbol::Property {
+                annotation: Some("int".to_string()),
+                settable: true
+            })
+        );
+        assert_eq!(parameters(members.get("create")), ["a"]);
+    }
+
+    #[test]
+    fn imported_decorators() {
+        let mut interfaces = ModuleInterfaces::default();
+        interfaces.insert(
+            ModuleName::new("pkg.decorators").unwrap(),
+            interface(
+                r#"
+import functools
+
+def logged(func):
+    @functools.wraps(func)
+    def wrapper(*args, **kwargs): ...
+    return wrapper
+
+def registered(func): ...
+"#,
+                "pkg.decorators",
+                false,
+            ),
+        );
+        interfaces.insert(
+            ModuleName::new("pkg.main").unwrap(),
+            interface(
+                r#"
+from .decorators import logged, registered
+
+@logged
+def traced(a): ...
+
+@registered
+def handler(a): ...
+"#,
+                "pkg.main",
+                false,
+            ),
+        );
+
+        let main = ModuleName::new("pkg.main").unwrap();
+        assert_eq!(parameters(interfaces.symbol(&main, "traced")), ["a"]);
+        assert_eq!(interfaces.symbol(&main, "handler"), Some(&Symbol::Unknown));
+    }
+
+    #[test]
+    fn rebinding_is_unknown() {
+        let interface = interface(
+            "try:\n    from json import loads\nexcept ImportError:\n    def loads(s): ...\n",
+            "mod",
+            false,
+        );
+        assert_eq!(interface.symbol("loads"), Some(&Symbol::Unknown));
+    }
+
+    #[test]
+    fn unpacked_kwargs() {
+        let interface = interface(
+            r#"
+from typing import NotRequired, Required, TypedDict
+from typing_extensions import Unpack
+
+class Options(TypedDict, total=False):
+    verbose: bool
+    name: Required[str]
+
+class MoreOptions(Options):
+    level: int
+    color: NotRequired[str]
+
+Movie = TypedDict("Movie", {"title": str, "year": NotRequired[int]})
+
+def configure(path, **kwargs: Unpack[MoreOptions]): ...
+def show(**kwargs: Unpack[Movie]): ...
+def untyped(**kwargs): ...
+"#,
+            "mod",
+            false,
+        );
+
+        let Some(Symbol::TypedDict { keys }) = interface.symbol("MoreOptions") else {
+            panic!("expected a TypedDict");
+        };
+        assert_eq!(
+            keys.iter()
+                .map(|(key, required)| (key.as_str(), *required))
+                .collect::<Vec<_>>(),
+            [
+                ("color", false),
+                ("level", true),
+                ("name", true),
+                ("verbose", false)
+            ]
+        );
+
+        let check = |function: &str, positional, keywords: &[&str]| {
+            let Some(Symbol::Function(signature)) = interface.symbol(function) else {
+                panic!("expected a function");
+            };
+            signature.check_call(positional, keywords)
+        };
+        assert_eq!(check("configure", 1, &["name", "level", "verbose"]), None);
+        assert_eq!(
+            check("configure", 1, &["name", "level", "other"]).as_deref(),
+            Some("got an unexpected keyword argument `other`")
+        );
+        assert_eq!(
+            check("configure", 0, &["verbose"]).as_deref(),
+            Some("missing required arguments: `path`, `level`, `name`")
+        );
+        assert_eq!(
+            check("show", 0, &["year"]).as_deref(),
+            Some("missing required arguments: `title`")
+        );
+        assert_eq!(check("untyped", 0, &["anything"]), None);
+    }
+
+    #[test]
+    fn import_fallbacks() {
+        let mut interfaces = ModuleInterfaces::default();
+        for (module, source) in [
+            (
+                "pkg._speedups",
+                "def parse(text): ...\ndef dumps(obj, *, indent=None): ...\n",
+            ),
+            ("pkg._pure", "def parse(text): ...\ndef dumps(obj): ...\n"),
+        ] {
+            interfaces.insert(
+                ModuleName::new(module).unwrap(),
+                interface(source, module, false),
+            );
+        }
+        interfaces.insert(
+            ModuleName::new("pkg.compat").unwrap(),
+            interface(
+                r#"
+try:
+    from ._speedups import parse, dumps
+    import ujson as json
+except ImportError:
+    from ._pure import parse, dumps
+    json = None
+
+try:
+    from ._speedups import parse as strict
+except ValueError:
+    from ._pure import parse as strict
+"#,
+                "pkg.compat",
+                false,
+            ),
+        );
+
+        let compat = ModuleName::new("pkg.compat").unwrap();
+        assert_eq!(parameters(interfaces.symbol(&compat, "parse")), ["text"]);
+        let Some(Symbol::Union(alternatives)) = interfaces.symbol(&compat, "dumps") else {
+            panic!("expected a union");
+        };
+        assert_eq!(
+            alternatives
+                .iter()
+                .map(|alternative| parameters(Some(alternative)).len())
+                .collect::<Vec<_>>(),
+            [2, 1]
+        );
+        // `ujson` is outside of the project.
+        assert_eq!(interfaces.symbol(&compat, "json"), Some(&Symbol::Unknown));
+        // Only handlers of `ImportError` bind fallbacks.
+        assert_eq!(interfaces.symbol(&compat, "strict"), Some(&Symbol::Unknown));
+    }
+
+    #[test]
+    fn dependencies() {
+        let source = "import os.path\nfrom . import sibling\nfrom ..base import Base\n";
+        let python_ast = parse(source, Mode::Module, "<test>")
+            .unwrap()
+            .expect_module()
+            .body;
+        let dependencies =
+            module_dependencies(&python_ast, &ModuleName::new("pkg.sub.mod").unwrap(), false);
+        assert_eq!(
+            dependencies
+                .iter()
+                .map(ModuleName::as_str)
+                .collect::<Vec<_>>(),
+            [
+                "os.path",
+                "pkg.base",
+                "pkg.base.Base",
+                "pkg.sub",
+                "pkg.sub.sibling"
+            ]
+        );
+    }
+
+    #[test]
+    fn display_signatures() {
+        let interface = interface(
+            r#"
+def positional(a: int, /, b, *args: str, c: str = "", **kwargs) -> bool: ...
+def keywords(a=1, *, b, c: int = 2): ...
+def only_positional(a, b=None, /): ...
+"#,
+            "module",
+            false,
+        );
+        let signature = |name: &str| match interface.symbol(name) {
+            Some(Symbol::Function(signature)) => signature.to_string(),
+            symbol => panic!("expected a function, got {symbol:?}"),
+        };
+        assert_eq!(
+            signature("positional"),
+            "(a: int, /, b, *args: str, c: str = ..., **kwargs) -> bool"
+        );
+        assert_eq!(signature("keywords"), "(a=..., *, b, c: int = ...)");
+        assert_eq!(signature("only_positional"), "(a, b=..., /)");
+    }
+}
@@ -1,21 +1,35 @@
+// Lets the code that `#[derive(Violation)]` generates refer to this crate by name from within it.
+extern crate self as pyrogen_checker;
+
 pub use code_selector::ErrorCodeSelector;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+pub mod cancellation;
 mod check;
 pub mod checker;
 pub mod code_selector;
+pub mod definitions;
+pub mod dependencies;
 pub mod directives;
+pub mod explain;
+pub mod fix;
 pub mod fs;
+pub mod hover;
+pub mod interface;
 pub mod line_width;
+pub mod locale;
 pub mod logging;
 pub mod message;
 pub mod packaging;
 pub mod pyproject_toml;
+pub mod references;
 pub mod registry;
 pub mod settings;
 pub mod source_kind;
-mod type_ignore;
+pub mod timing;
+pub mod type_ignore;
+pub mod violation;
 
 #[cfg(any(test, fuzzing))]
 pub mod test;
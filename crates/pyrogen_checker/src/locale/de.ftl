@@ -0,0 +1,69 @@
+# The messages of the diagnostics, in German. Code, like names and keywords, stays as it is.
+
+## Syntax
+
+syntax-error = Syntaxfehler: { $error }
+named-expr-rebinds-iteration-variable = Ein Zuweisungsausdruck kann die Iterationsvariable `{ $name }` einer Comprehension nicht neu binden
+named-expr-in-comprehension-iterable = Ein Zuweisungsausdruck kann nicht im Iterable einer Comprehension verwendet werden
+named-expr-in-class-comprehension = Ein Zuweisungsausdruck in einer Comprehension kann nicht im Rumpf einer Klasse verwendet werden
+except-star-unsupported = `except*` erfordert Python 3.11 oder neuer, aber die Zielversion ist { $version }
+except-star-jump = `{ $keyword }` kann nicht in einem `except*`-Block verwendet werden
+
+## Imports and dependencies
+
+import-not-found = Modul `{ $name }` nicht gefunden
+untyped-import = Modul `{ $name }` ist ohne Typinformationen installiert (kein `py.typed`-Marker und keine Stubs) und wird als `Any` behandelt
+type-checking-only-import = `{ $name }` wird nur unter `TYPE_CHECKING` importiert und ist zur Laufzeit nicht verfügbar
+undeclared-dependency = Modul `{ $module }` wird importiert, aber `{ $distribution }` ist keine deklarierte Abhängigkeit
+unused-dependency = Abhängigkeit `{ $name }` wird von keiner geprüften Datei importiert
+
+## Types
+
+type-mismatch = Typkonflikt: Die Variable hat den Typ { $variable_type }, aber der Wert den Typ { $value_type }
+comparison-overlap = Der Vergleich eines Members des Enums `{ $enum }` mit einem `{ $literal_type }`-Literal ist immer `{ $result }`
+except-star-group = `except*` kann `{ $name }` nicht abfangen, da es auf die Exceptions in einer Gruppe passt
+call-argument = Ungültige Argumente im Aufruf von `{ $name }`: { $problem }
+unsupported-operand-types = Nicht unterstützte Operandentypen für { $operator }: `{ $left }` und `{ $right }`
+lambda-positional-argument = Das Lambda kann nicht mit 1 Positionsargument aufgerufen werden, wie `{ $annotation }` es erwartet
+lambda-positional-arguments = Das Lambda kann nicht mit { $count } Positionsargumenten aufgerufen werden, wie `{ $annotation }` es erwartet
+lambda-return-type = Das Lambda gibt `{ $returns }` zurück, aber `{ $annotation }` erwartet `{ $expected }`
+final-class = Von der finalen Klasse `{ $class }` kann nicht geerbt werden
+final-method = Die finale Methode `{ $method }` der Klasse `{ $class }` kann nicht überschrieben werden
+
+## Annotations
+
+missing-parameter-annotation = Dem Parameter `{ $parameter }` von `{ $function }` fehlt eine Typannotation
+missing-return-annotation = Der Funktion `{ $function }` fehlt eine Annotation des Rückgabetyps
+missing-variable-annotation = Der öffentlichen Variable `{ $name }` fehlt eine Typannotation
+
+## Modules
+
+undefined-export = `{ $name }` steht in `__all__`, ist aber nicht im Modul definiert
+invalid-all = `__all__` sollte eine literale Liste oder ein literales Tupel von Strings sein
+private-export = Der private Name `{ $name }` steht in `__all__`
+duplicate-key = Der Dictionary-Schlüssel `{ $key }` kommt mehrfach vor
+duplicate-set-element = Das Set-Element `{ $element }` kommt mehrfach vor
+dead-class = Die Klasse `{ $name }` wird im Projekt nirgends verwendet
+dead-function = Die Funktion `{ $name }` wird im Projekt nirgends verwendet
+stub-function-body = Funktionsrümpfe in Stubs sollten `...` sein
+stub-runtime-code = Stubs sollten nur Deklarationen enthalten, keinen Code, der zur Laufzeit ausgeführt wird
+
+## Unicode
+
+confusable-character = Das Zeichen { $character } ({ $code_point }) kann mit dem ASCII-Zeichen { $ascii } verwechselt werden
+bidi-control-character = Das bidirektionale Steuerzeichen { $name } ({ $code_point }) kann den Code anders erscheinen lassen, als er ausgeführt wird
+
+## Type ignore comments
+
+unused-type-ignore = Unbenutzte Type-Ignore-Direktive
+unused-type-ignore-codes = Die Type-Ignore-Direktive hat unbenutzte Codes: { $codes }
+unknown-type-ignore-codes = Die Type-Ignore-Direktive hat unbekannte Codes: { $codes }
+expired-suppression = Die Type-Ignore-Direktive hat ihr Ablaufdatum { $date } überschritten
+too-many-diagnostics = { $count } weitere Diagnosen unterdrückt, da die Datei mehr als { $limit } hat
+
+## pyproject.toml
+
+invalid-pyproject-toml = pyproject.toml konnte nicht gelesen werden: { $error }
+invalid-dependency = Ungültige Abhängigkeit `{ $requirement }`: { $error }
+invalid-requires-python = Ungültiger `requires-python`-Spezifizierer `{ $specifier }`: { $error }
+invalid-classifier = Der Classifier `{ $classifier }` hat nicht die Form `Topic :: Subtopic`
@@ -0,0 +1,70 @@
+# The messages of the diagnostics, in English. Every message that a rule reports is defined here;
+# the other catalogs fall back to this one for the messages they don't translate.
+
+## Syntax
+
+syntax-error = Syntax error: { $error }
+named-expr-rebinds-iteration-variable = Assignment expression cannot rebind comprehension iteration variable `{ $name }`
+named-expr-in-comprehension-iterable = Assignment expression cannot be used in a comprehension iterable expression
+named-expr-in-class-comprehension = Assignment expression within a comprehension cannot be used in a class body
+except-star-unsupported = `except*` requires Python 3.11 or newer, but the target version is { $version }
+except-star-jump = `{ $keyword }` can't be used in an `except*` block
+
+## Imports and dependencies
+
+import-not-found = Cannot find module `{ $name }`
+untyped-import = Module `{ $name }` is installed without type information (no `py.typed` marker or stubs) and is treated as `Any`
+type-checking-only-import = `{ $name }` is only imported under `TYPE_CHECKING` and is not available at runtime
+undeclared-dependency = Module `{ $module }` is imported, but `{ $distribution }` isn't a declared dependency
+unused-dependency = Dependency `{ $name }` isn't imported by any checked file
+
+## Types
+
+type-mismatch = Type mismatch: variable is of type { $variable_type }, but value is of type { $value_type }
+comparison-overlap = Comparison of a member of enum `{ $enum }` with a `{ $literal_type }` literal is always `{ $result }`
+except-star-group = `except*` can't catch `{ $name }`, since it matches the exceptions inside a group
+call-argument = Invalid arguments in call to `{ $name }`: { $problem }
+unsupported-operand-types = Unsupported operand types for { $operator }: `{ $left }` and `{ $right }`
+lambda-positional-argument = Lambda can't be called with 1 positional argument, as `{ $annotation }` expects
+lambda-positional-arguments = Lambda can't be called with { $count } positional arguments, as `{ $annotation }` expects
+lambda-return-type = Lambda returns `{ $returns }`, but `{ $annotation }` expects `{ $expected }`
+final-class = Cannot inherit from final class `{ $class }`
+final-method = Cannot override final method `{ $method }` of class `{ $class }`
+
+## Annotations
+
+missing-parameter-annotation = Parameter `{ $parameter }` of `{ $function }` is missing a type annotation
+missing-return-annotation = Function `{ $function }` is missing a return type annotation
+missing-variable-annotation = Public variable `{ $name }` is missing a type annotation
+
+## Modules
+
+undefined-export = `{ $name }` is listed in `__all__` but not defined in the module
+invalid-all = `__all__` should be a literal list or tuple of strings
+private-export = Private name `{ $name }` is listed in `__all__`
+duplicate-key = Dictionary key `{ $key }` is repeated
+duplicate-set-element = Set element `{ $element }` is repeated
+dead-class = Class `{ $name }` is never used in the project
+dead-function = Function `{ $name }` is never used in the project
+stub-function-body = Function bodies in stubs should be `...`
+stub-runtime-code = Stubs should only contain declarations, not runtime code
+
+## Unicode
+
+confusable-character = The character { $character } ({ $code_point }) can be confused with the ASCII { $ascii }
+bidi-control-character = Bidirectional control character { $name } ({ $code_point }) can make the code read differently than it runs
+
+## Type ignore comments
+
+unused-type-ignore = Unused type ignore directive
+unused-type-ignore-codes = Type ignore directive has unused codes: { $codes }
+unknown-type-ignore-codes = Type ignore directive has unknown codes: { $codes }
+expired-suppression = Type ignore directive is past its expiry date { $date }
+too-many-diagnostics = { $count } more diagnostics suppressed, since the file has more than { $limit }
+
+## pyproject.toml
+
+invalid-pyproject-toml = Failed to parse pyproject.toml: { $error }
+invalid-dependency = Invalid dependency `{ $requirement }`: { $error }
+invalid-requires-python = Invalid `requires-python` specifier `{ $specifier }`: { $error }
+invalid-classifier = Classifier `{ $classifier }` isn't of the form `Topic :: Subtopic`
@@ -0,0 +1,199 @@
+//! Translations of the messages of diagnostics.
+//!
+//! Rules don't format their messages themselves, but refer to a [`MessageTemplate`] in a catalog
+//! of messages per locale. The catalogs are written in a subset of
+//! [Fluent](https://projectfluent.org/): a message per line, like
+//! ``import-not-found = Cannot find module `{ $name }` ``, and comments starting with `#`.
+//! The English catalog defines every message, and the others fall back to it for the messages that
+//! they don't translate.
+
+use std::fmt;
+
+use once_cell::sync::Lazy;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+static EN: Lazy<Catalog> = Lazy::new(|| Catalog::parse(include_str!("en.ftl")));
+static DE: Lazy<Catalog> = Lazy::new(|| Catalog::parse(include_str!("de.ftl")));
+
+/// The language of the messages of diagnostics.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// English.
+    #[default]
+    En,
+    /// German.
+    De,
+}
+
+impl Locale {
+    fn catalog(self) -> &'static Catalog {
+        match self {
+            Self::En => &EN,
+            Self::De => &DE,
+        }
+    }
+
+    /// Render the message of `template` in this locale, or in English if it isn't translated.
+    pub fn render(self, template: &MessageTemplate) -> String {
+        template.render_in(self.catalog())
+    }
+}
+
+/// A message of the catalog, with the values of its arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageTemplate {
+    /// The identifier of the message in the catalog, like `import-not-found`.
+    pub id: String,
+    pub args: Vec<(String, String)>,
+}
+
+impl MessageTemplate {
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            args: vec![],
+        }
+    }
+
+    /// Set the argument that the message refers to as `{ $name }`.
+    #[must_use]
+    pub fn arg(mut self, name: &str, value: impl fmt::Display) -> Self {
+        self.args.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn render_in(&self, catalog: &Catalog) -> String {
+        let pattern = catalog
+            .get(&self.id)
+            .or_else(|| EN.get(&self.id))
+            .unwrap_or(self.id.as_str());
+        render(pattern, &self.args)
+    }
+}
+
+/// The message patterns of a locale, by identifier.
+#[derive(Debug, Default)]
+struct Catalog {
+    patterns: FxHashMap<&'static str, &'static str>,
+}
+
+impl Catalog {
+    /// Parse the `id = pattern` lines of `source`, skipping blank lines and comments.
+    fn parse(source: &'static str) -> Self {
+        let patterns = source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (id, pattern) = line.split_once('=')?;
+                Some((id.trim(), pattern.trim()))
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    fn get(&self, id: &str) -> Option<&'static str> {
+        self.patterns.get(id).copied()
+    }
+}
+
+/// Replace the placeables of `pattern` with the values of the arguments they refer to, like
+/// `{ $name }`, or with the string literal they contain, like `{ "{" }`. Placeables that refer to
+/// unknown arguments are kept as they are, so that a broken translation stays readable.
+fn render(pattern: &str, args: &[(String, String)]) -> String {
+    let mut output = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end + 1) else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        let placeable = &rest[start..end];
+        let expression = placeable[1..placeable.len() - 1].trim();
+        if let Some(name) = expression.strip_prefix('$') {
+            match args.iter().find(|(arg, _)| arg == name) {
+                Some((_, value)) => output.push_str(value),
+                None => output.push_str(placeable),
+            }
+        } else if let Some(literal) = expression
+            .strip_prefix('"')
+            .and_then(|expression| expression.strip_suffix('"'))
+        {
+            output.push_str(literal);
+        } else {
+            output.push_str(placeable);
+        }
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{render, Catalog, Locale, MessageTemplate, EN};
+
+    /// The names of the arguments that `pattern` refers to.
+    fn arguments(pattern: &str) -> BTreeSet<&str> {
+        pattern
+            .split('{')
+            .skip(1)
+            .filter_map(|placeable| placeable.split('}').next()?.trim().strip_prefix('$'))
+            .collect()
+    }
+
+    #[test]
+    fn render_placeables() {
+        let args = [("name".to_string(), "os".to_string())];
+        assert_eq!(
+            render("Cannot find module `{ $name }`", &args),
+            "Cannot find module `os`"
+        );
+        assert_eq!(render("{$name}{ \"{\" }", &args), "os{");
+        assert_eq!(
+            render("{ $other } and { $name", &args),
+            "{ $other } and { $name"
+        );
+    }
+
+    #[test]
+    fn fallback_to_english() {
+        let catalog =
+            Catalog::parse("# A comment\n\nimport-not-found = Modul `{ $name }` nicht gefunden\n");
+        let template = MessageTemplate::new("import-not-found").arg("name", "os");
+        assert_eq!(template.render_in(&catalog), "Modul `os` nicht gefunden");
+
+        let template = MessageTemplate::new("final-class").arg("class", "Base");
+        assert_eq!(
+            template.render_in(&catalog),
+            "Cannot inherit from final class `Base`"
+        );
+
+        // Messages that no catalog defines are shown by their identifier.
+        assert_eq!(
+            MessageTemplate::new("missing").render_in(&catalog),
+            "missing"
+        );
+    }
+
+    #[test]
+    fn translations_match_english() {
+        for locale in [Locale::De] {
+            for (id, pattern) in &locale.catalog().patterns {
+                let english = EN
+                    .get(id)
+                    .unwrap_or_else(|| panic!("{locale:?} defines `{id}`, which English doesn't"));
+                assert_eq!(
+                    arguments(pattern),
+                    arguments(english),
+                    "{locale:?} uses other arguments in `{id}` than English"
+                );
+            }
+        }
+    }
+}
@@ -1,24 +1,47 @@
 use std::io::Write;
 
+use pyrogen_source_file::ColumnEncoding;
+
 use crate::fs::relativize_path;
 use crate::message::{Emitter, Message};
 use crate::settings::code_table::MessageKind;
+use crate::settings::types::SeverityMapping;
 
 /// Generate error workflow command in GitHub Actions format.
 /// See: [GitHub documentation](https://docs.github.com/en/actions/reference/workflow-commands-for-github-actions#setting-an-error-message)
 #[derive(Default)]
-pub struct GithubEmitter;
+pub struct GithubEmitter {
+    severity_mapping: SeverityMapping,
+    column_encoding: ColumnEncoding,
+}
+
+impl GithubEmitter {
+    #[must_use]
+    pub fn with_severity_mapping(mut self, severity_mapping: SeverityMapping) -> Self {
+        self.severity_mapping = severity_mapping;
+        self
+    }
+
+    #[must_use]
+    pub fn with_column_encoding(mut self, column_encoding: ColumnEncoding) -> Self {
+        self.column_encoding = column_encoding;
+        self
+    }
+}
 
 impl Emitter for GithubEmitter {
     fn emit(&mut self, writer: &mut dyn Write, messages: &[Message]) -> anyhow::Result<()> {
         for message in messages {
-            let source_location = message.compute_start_location();
+            let source_location =
+                message.compute_start_location_with_encoding(self.column_encoding);
             let location = source_location.clone();
 
-            let end_location = message.compute_end_location();
-            let kind: &str = match message.kind {
+            let end_location = message.compute_end_location_with_encoding(self.column_encoding);
+            let kind: &str = match self.severity_mapping.map(message.kind) {
                 MessageKind::Error => "error",
                 MessageKind::Warning => "warning",
+                // GitHub has no finer-grained levels than notices.
+                MessageKind::Info | MessageKind::Hint => "notice",
             };
 
             write!(
@@ -55,10 +78,22 @@ mod tests {
 
     use crate::message::tests::{capture_emitter_output, create_messages};
     use crate::message::GithubEmitter;
+    use crate::settings::code_table::MessageKind;
+    use crate::settings::types::SeverityMapping;
 
     #[test]
     fn output() {
-        let mut emitter = GithubEmitter;
+        let mut emitter = GithubEmitter::default();
+        let content = capture_emitter_output(&mut emitter, &create_messages());
+
+        assert_snapshot!(content);
+    }
+
+    #[test]
+    fn severity_mapping() {
+        let mut emitter = GithubEmitter::default().with_severity_mapping(
+            SeverityMapping::from_iter([(MessageKind::Warning, MessageKind::Error)]),
+        );
         let content = capture_emitter_output(&mut emitter, &create_messages());
 
         assert_snapshot!(content);
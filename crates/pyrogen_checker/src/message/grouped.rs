@@ -0,0 +1,145 @@
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+
+use colored::Colorize;
+
+use pyrogen_source_file::{ColumnEncoding, OneIndexed};
+
+use crate::fs::relativize_path;
+use crate::message::text::{MessageCodeFrame, RuleCodeAndBody};
+use crate::message::{group_messages_by_filename, Emitter, Message, MessageWithLocation};
+
+/// Prints the path of every file with messages once, followed by its messages with their
+/// locations aligned.
+#[derive(Default)]
+pub struct GroupedEmitter {
+    show_source: bool,
+    column_encoding: ColumnEncoding,
+}
+
+impl GroupedEmitter {
+    #[must_use]
+    pub fn with_show_source(mut self, show_source: bool) -> Self {
+        self.show_source = show_source;
+        self
+    }
+
+    #[must_use]
+    pub fn with_column_encoding(mut self, column_encoding: ColumnEncoding) -> Self {
+        self.column_encoding = column_encoding;
+        self
+    }
+}
+
+impl Emitter for GroupedEmitter {
+    fn emit(&mut self, writer: &mut dyn Write, messages: &[Message]) -> anyhow::Result<()> {
+        for (filename, messages) in group_messages_by_filename(messages, self.column_encoding) {
+            // Compute the maximum number of digits in the row and column, for messages in
+            // this file.
+            let mut max_row_length = OneIndexed::MIN;
+            let mut max_column_length = OneIndexed::MIN;
+            for message in &messages {
+                max_row_length = max_row_length.max(message.start_location.row);
+                max_column_length = max_column_length.max(message.start_location.column);
+            }
+
+            let row_length = num_digits(max_row_length);
+            let column_length = num_digits(max_column_length);
+
+            writeln!(writer, "{}:", relativize_path(filename).underline())?;
+
+            for message in messages {
+                write!(
+                    writer,
+                    "{}",
+                    DisplayGroupedMessage {
+                        message,
+                        show_source: self.show_source,
+                        row_length,
+                        column_length,
+                    }
+                )?;
+            }
+
+            // Separate the files by a blank line, unless the code frames already did.
+            if !self.show_source {
+                writeln!(writer)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct DisplayGroupedMessage<'a> {
+    message: MessageWithLocation<'a>,
+    show_source: bool,
+    row_length: usize,
+    column_length: usize,
+}
+
+impl Display for DisplayGroupedMessage<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let MessageWithLocation {
+            message,
+            start_location,
+        } = &self.message;
+
+        writeln!(
+            f,
+            "  {row_padding}{row}{sep}{col}{col_padding} {code_and_body}",
+            row_padding = " ".repeat(self.row_length - num_digits(start_location.row)),
+            row = start_location.row,
+            sep = ":".cyan(),
+            col = start_location.column,
+            col_padding = " ".repeat(self.column_length - num_digits(start_location.column)),
+            code_and_body = RuleCodeAndBody { message },
+        )?;
+
+        if self.show_source {
+            // Indent the code frame to line up with the messages.
+            let code_frame = MessageCodeFrame { message }.to_string();
+            for line in code_frame.lines() {
+                if line.is_empty() {
+                    writeln!(f)?;
+                } else {
+                    writeln!(f, "  {line}")?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn num_digits(n: OneIndexed) -> usize {
+    std::iter::successors(Some(n.get()), |n| Some(n / 10))
+        .take_while(|n| *n > 0)
+        .count()
+        .max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+
+    use crate::message::tests::{capture_emitter_output, create_messages};
+    use crate::message::GroupedEmitter;
+
+    #[test]
+    fn default() {
+        let mut emitter = GroupedEmitter::default();
+        let content = capture_emitter_output(&mut emitter, &create_messages());
+
+        assert_snapshot!(content);
+    }
+
+    #[test]
+    fn show_source() {
+        let mut emitter = GroupedEmitter::default().with_show_source(true);
+        let content = capture_emitter_output(&mut emitter, &create_messages());
+
+        assert_snapshot!(content);
+    }
+}
@@ -1,74 +1,186 @@
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use serde::ser::SerializeSeq;
-use serde::{Serialize, Serializer};
 use serde_json::{json, Value};
 
+use pyrogen_source_file::{ColumnEncoding, SourceCode};
+use rustpython_ast::text_size::TextSize;
 use rustpython_ast::Ranged;
 
-use crate::message::{Emitter, Message};
+use crate::fix::Fix;
+use crate::message::{fingerprints, Emitter, Message};
+use crate::settings::types::SeverityMapping;
 
 #[derive(Default)]
-pub struct JsonEmitter;
+pub struct JsonEmitter {
+    severity_mapping: SeverityMapping,
+    column_encoding: ColumnEncoding,
+    project_root: Option<PathBuf>,
+}
+
+impl JsonEmitter {
+    #[must_use]
+    pub fn with_severity_mapping(mut self, severity_mapping: SeverityMapping) -> Self {
+        self.severity_mapping = severity_mapping;
+        self
+    }
+
+    #[must_use]
+    pub fn with_column_encoding(mut self, column_encoding: ColumnEncoding) -> Self {
+        self.column_encoding = column_encoding;
+        self
+    }
+
+    /// The directory that the paths in the fingerprints are relative to.
+    #[must_use]
+    pub fn with_project_root(mut self, project_root: impl Into<PathBuf>) -> Self {
+        self.project_root = Some(project_root.into());
+        self
+    }
+}
 
 impl Emitter for JsonEmitter {
     fn emit(&mut self, writer: &mut dyn Write, messages: &[Message]) -> anyhow::Result<()> {
-        serde_json::to_writer_pretty(writer, &ExpandedMessages { messages })?;
+        serde_json::to_writer_pretty(
+            &mut *writer,
+            &to_json_values(
+                messages,
+                &self.severity_mapping,
+                self.column_encoding,
+                self.project_root.as_deref(),
+            ),
+        )?;
+        writeln!(writer)?;
 
         Ok(())
     }
 }
 
-struct ExpandedMessages<'a> {
-    messages: &'a [Message],
+/// The JSON representations of messages, as emitted by the [`JsonEmitter`].
+///
+/// The `type_ignore_location` is where a `# type: ignore` comment that suppresses the message
+/// would go (`type_ignore_row` is its row, and `kind` the unmapped severity, for existing
+/// consumers), and the `fingerprint` identifies the message across changes to the file (see
+/// [`fingerprints`]). The `codeActions` hold the message's fix, if any, in the shape of LSP code
+/// actions.
+pub fn messages_to_json_values(
+    messages: &[Message],
+    column_encoding: ColumnEncoding,
+    project_root: Option<&Path>,
+) -> Vec<Value> {
+    to_json_values(
+        messages,
+        &SeverityMapping::default(),
+        column_encoding,
+        project_root,
+    )
 }
 
-impl Serialize for ExpandedMessages<'_> {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let mut s = serializer.serialize_seq(Some(self.messages.len()))?;
+fn to_json_values(
+    messages: &[Message],
+    severity_mapping: &SeverityMapping,
+    column_encoding: ColumnEncoding,
+    project_root: Option<&Path>,
+) -> Vec<Value> {
+    messages
+        .iter()
+        .zip(fingerprints(messages, project_root))
+        .map(|(message, fingerprint)| {
+            let source_code = message.file.to_source_code();
+
+            let start_location =
+                source_code.source_location_with_encoding(message.start(), column_encoding);
+            let end_location =
+                source_code.source_location_with_encoding(message.end(), column_encoding);
+            let type_ignore_location =
+                source_code.source_location_with_encoding(message.ignore_offset, column_encoding);
+
+            json!({
+                "code": message.diagnostic.error_code.to_string(),
+                "severity": severity_mapping.map(message.kind),
+                "message": message.diagnostic.body,
+                "location": start_location,
+                "end_location": end_location,
+                "filename": message.filename(),
+                "type_ignore_row": type_ignore_location.row,
+                "type_ignore_location": type_ignore_location,
+                "kind": message.kind.to_string(),
+                "fingerprint": fingerprint,
+                "codeActions": message
+                    .fix
+                    .as_ref()
+                    .map(|fix| vec![code_action(fix, &source_code)])
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
 
-        for message in self.messages {
-            let value = message_to_json_value(message);
-            s.serialize_element(&value)?;
-        }
+fn code_action(fix: &Fix, source_code: &SourceCode) -> Value {
+    let edits = fix
+        .edits()
+        .iter()
+        .map(|edit| {
+            json!({
+                "range": {
+                    "start": lsp_position(edit.start(), source_code),
+                    "end": lsp_position(edit.end(), source_code),
+                },
+                "newText": edit.content().unwrap_or_default(),
+            })
+        })
+        .collect::<Vec<_>>();
 
-        s.end()
-    }
+    json!({
+        "title": fix.title(),
+        "edits": edits,
+    })
 }
 
-pub(crate) fn message_to_json_value(message: &Message) -> Value {
-    let source_code = message.file.to_source_code();
-
-    let start_location = source_code.source_location(message.start());
-    let end_location = source_code.source_location(message.end());
-    let type_ignore_location = source_code.source_location(message.ignore_offset);
+/// An LSP position: a zero-based line and the offset in the line in UTF-16 code units.
+fn lsp_position(offset: TextSize, source_code: &SourceCode) -> Value {
+    let location = source_code.source_location_with_encoding(offset, ColumnEncoding::Utf16);
 
     json!({
-        "code": message.diagnostic.error_code.to_string(),
-        "message": message.diagnostic.body,
-        "location": start_location,
-        "end_location": end_location,
-        "filename": message.filename(),
-        "type_ignore_row": type_ignore_location.row,
-        "kind": message.kind.to_string()
+        "line": location.row.to_zero_indexed(),
+        "character": location.column.to_zero_indexed(),
     })
 }
 
 #[cfg(test)]
 mod tests {
     use insta::assert_snapshot;
+    use serde_json::json;
+
+    use pyrogen_source_file::SourceFileBuilder;
+    use rustpython_ast::text_size::TextSize;
 
     use crate::message::tests::{capture_emitter_output, create_messages};
     use crate::message::JsonEmitter;
 
+    use super::lsp_position;
+
     #[test]
     fn output() {
-        let mut emitter = JsonEmitter;
+        let mut emitter = JsonEmitter::default();
         let content = capture_emitter_output(&mut emitter, &create_messages());
 
         assert_snapshot!(content);
     }
+
+    #[test]
+    fn utf16_positions() {
+        let file = SourceFileBuilder::new("module.py", "x = 1\nä𝔘 = 2\n").finish();
+        let source_code = file.to_source_code();
+
+        assert_eq!(
+            lsp_position(TextSize::from(6), &source_code),
+            json!({"line": 1, "character": 0})
+        );
+        // `ä` is one UTF-16 code unit (two bytes), `𝔘` two code units (four bytes).
+        assert_eq!(
+            lsp_position(TextSize::from(12), &source_code),
+            json!({"line": 1, "character": 3})
+        );
+    }
 }
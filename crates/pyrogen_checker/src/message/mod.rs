@@ -1,29 +1,44 @@
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::hash::Hasher;
 use std::io::Write;
 use std::ops::Deref;
+use std::path::Path;
 
+use itertools::Itertools;
+use rustc_hash::FxHashMap;
 use rustpython_parser::ast::Ranged;
 use rustpython_parser::text_size::{TextRange, TextSize};
 
-use pyrogen_source_file::{SourceFile, SourceLocation};
+use pyrogen_cache::{CacheKey, CacheKeyHasher};
+use pyrogen_source_file::{ColumnEncoding, SourceFile, SourceLocation};
 
 pub use github::GithubEmitter;
-pub use json::JsonEmitter;
+pub use grouped::GroupedEmitter;
+pub use json::{messages_to_json_values, JsonEmitter};
+pub use mypy::MypyEmitter;
+pub use pylint::PylintEmitter;
+pub use template::{OutputTemplate, TemplateEmitter, TemplateError};
 pub use text::TextEmitter;
 
+use crate::fix::Fix;
 use crate::registry::{Diagnostic, DiagnosticKind};
 use crate::settings::code_table::MessageKind;
 
 // mod diff;
 mod github;
+mod grouped;
 mod json;
+mod mypy;
+mod pylint;
+mod template;
 mod text;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Message {
     pub diagnostic: DiagnosticKind,
     pub range: TextRange,
+    pub fix: Option<Fix>,
     pub file: SourceFile,
     pub ignore_offset: TextSize,
     pub kind: MessageKind,
@@ -39,6 +54,7 @@ impl Message {
         Self {
             range: diagnostic.range(),
             diagnostic: diagnostic.kind,
+            fix: diagnostic.fix,
             file,
             ignore_offset,
             kind,
@@ -56,6 +72,73 @@ impl Message {
     pub fn compute_end_location(&self) -> SourceLocation {
         self.file.to_source_code().source_location(self.end())
     }
+
+    pub fn compute_start_location_with_encoding(&self, encoding: ColumnEncoding) -> SourceLocation {
+        self.file
+            .to_source_code()
+            .source_location_with_encoding(self.start(), encoding)
+    }
+
+    pub fn compute_end_location_with_encoding(&self, encoding: ColumnEncoding) -> SourceLocation {
+        self.file
+            .to_source_code()
+            .source_location_with_encoding(self.end(), encoding)
+    }
+
+    /// A hash of the code, the path relative to `project_root`, the message and the
+    /// (whitespace-normalized) source of the lines that the message covers. Unlike the location,
+    /// it stays the same when lines are added or removed elsewhere in the file, and it doesn't
+    /// depend on the directory pyrogen runs in.
+    ///
+    /// Messages from the catalog are hashed by their identifier and arguments rather than by the
+    /// body, so that translating the body doesn't change the hash.
+    fn context_hash(&self, project_root: Option<&Path>) -> u64 {
+        let source_code = self.file.to_source_code();
+        let context = source_code.slice(TextRange::new(
+            source_code.line_start(source_code.line_index(self.start())),
+            source_code.line_end(source_code.line_index(self.end())),
+        ));
+
+        let mut hasher = CacheKeyHasher::new();
+        self.diagnostic.error_code.to_str().cache_key(&mut hasher);
+        let path = Path::new(self.filename());
+        project_root
+            .and_then(|project_root| path.strip_prefix(project_root).ok())
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+            .cache_key(&mut hasher);
+        match &self.diagnostic.template {
+            Some(template) => {
+                template.id.cache_key(&mut hasher);
+                template.args.cache_key(&mut hasher);
+            }
+            None => self.diagnostic.body.cache_key(&mut hasher),
+        }
+        context.split_whitespace().join(" ").cache_key(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Stable fingerprints of the given messages, for tools that track problems across commits.
+///
+/// The paths of the files are hashed relative to `project_root`, if they're inside it. Messages
+/// with the same context hash (like the same problem on several identical lines) are told apart by
+/// their order in the file.
+pub fn fingerprints(messages: &[Message], project_root: Option<&Path>) -> Vec<String> {
+    let mut occurrences = FxHashMap::<u64, u64>::default();
+    messages
+        .iter()
+        .map(|message| {
+            let context_hash = message.context_hash(project_root);
+            let occurrence = occurrences.entry(context_hash).or_default();
+            let mut hasher = CacheKeyHasher::new();
+            context_hash.cache_key(&mut hasher);
+            occurrence.cache_key(&mut hasher);
+            *occurrence += 1;
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
 }
 
 impl Ord for Message {
@@ -89,7 +172,10 @@ impl Deref for MessageWithLocation<'_> {
     }
 }
 
-fn group_messages_by_filename(messages: &[Message]) -> BTreeMap<&str, Vec<MessageWithLocation>> {
+fn group_messages_by_filename(
+    messages: &[Message],
+    column_encoding: ColumnEncoding,
+) -> BTreeMap<&str, Vec<MessageWithLocation>> {
     let mut grouped_messages = BTreeMap::default();
     for message in messages {
         grouped_messages
@@ -97,7 +183,7 @@ fn group_messages_by_filename(messages: &[Message]) -> BTreeMap<&str, Vec<Messag
             .or_insert_with(Vec::new)
             .push(MessageWithLocation {
                 message,
-                start_location: message.compute_start_location(),
+                start_location: message.compute_start_location_with_encoding(column_encoding),
             });
     }
     grouped_messages
@@ -113,12 +199,16 @@ pub trait Emitter {
 
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
+
     use rustpython_parser::ast::Ranged;
     use rustpython_parser::text_size::{TextRange, TextSize};
 
     use pyrogen_source_file::SourceFileBuilder;
 
-    use crate::message::{Emitter, Message};
+    use crate::fix::{Edit, Fix};
+    use crate::locale::{Locale, MessageTemplate};
+    use crate::message::{fingerprints, Emitter, Message};
     use crate::registry::{Diagnostic, DiagnosticKind, ErrorCode};
     use crate::settings::code_table::MessageKind;
 
@@ -138,30 +228,31 @@ def fibonacci(n):
 "#;
 
         let unused_import = Diagnostic::new(
-            DiagnosticKind {
-                error_code: ErrorCode::UnusedImport,
-                body: "`os` imported but unused".to_string(),
-            },
+            DiagnosticKind::new(ErrorCode::UnusedImport, "`os` imported but unused"),
             TextRange::new(TextSize::from(7), TextSize::from(9)),
-        );
+        )
+        .with_fix(Fix::new(
+            "Remove unused import: `os`",
+            [Edit::range_deletion(TextRange::new(
+                TextSize::from(0),
+                TextSize::from(10),
+            ))],
+        ));
 
         let fib_source = SourceFileBuilder::new("fib.py", fib).finish();
 
         let unused_variable = Diagnostic::new(
-            DiagnosticKind {
-                error_code: ErrorCode::UnusedVariable,
-                body: "Local variable `x` is assigned to but never used".to_string(),
-            },
+            DiagnosticKind::new(
+                ErrorCode::UnusedVariable,
+                "Local variable `x` is assigned to but never used",
+            ),
             TextRange::new(TextSize::from(94), TextSize::from(95)),
         );
 
         let file_2 = r#"if a == 1: pass"#;
 
         let undefined_name = Diagnostic::new(
-            DiagnosticKind {
-                error_code: ErrorCode::UndefinedName,
-                body: "Undefined name `a`".to_string(),
-            },
+            DiagnosticKind::new(ErrorCode::UndefinedName, "Undefined name `a`"),
             TextRange::new(TextSize::from(3), TextSize::from(4)),
         );
 
@@ -201,4 +292,88 @@ def fibonacci(n):
 
         String::from_utf8(output).expect("Output to be valid UTF-8")
     }
+
+    /// Messages about the `x` at each of the given offsets of `source`.
+    fn messages_at(source: &str, offsets: &[u32]) -> Vec<Message> {
+        let file = SourceFileBuilder::new("module.py", source).finish();
+        offsets
+            .iter()
+            .map(|&offset| {
+                let diagnostic = Diagnostic::new(
+                    DiagnosticKind::new(
+                        ErrorCode::UnusedVariable,
+                        "Local variable `x` is assigned to but never used",
+                    ),
+                    TextRange::at(TextSize::from(offset), TextSize::from(1)),
+                );
+                let start = diagnostic.start();
+                Message::from_diagnostic(diagnostic, file.clone(), start, MessageKind::Warning)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fingerprints_ignore_line_shifts() {
+        let original = fingerprints(&messages_at("def f():\n    x = 1\n", &[13]), None);
+        let shifted = fingerprints(&messages_at("\n\ndef f():\n        x = 1\n", &[19]), None);
+        assert_eq!(original, shifted);
+
+        let changed = fingerprints(&messages_at("def f():\n    x = 2\n", &[13]), None);
+        assert_ne!(original, changed);
+    }
+
+    #[test]
+    fn fingerprints_of_identical_lines_differ() {
+        let fingerprints = fingerprints(&messages_at("x = 1\nx = 1\n", &[0, 6]), None);
+        assert_ne!(fingerprints[0], fingerprints[1]);
+    }
+
+    #[test]
+    fn fingerprints_use_project_relative_paths() {
+        let at = |filename: &str| {
+            let file = SourceFileBuilder::new(filename, "x = 1\n").finish();
+            let diagnostic = Diagnostic::new(
+                DiagnosticKind::new(ErrorCode::UnusedVariable, "Unused"),
+                TextRange::at(TextSize::from(0), TextSize::from(1)),
+            );
+            vec![Message::from_diagnostic(
+                diagnostic,
+                file,
+                TextSize::from(0),
+                MessageKind::Warning,
+            )]
+        };
+        let checkout = fingerprints(
+            &at("/home/ci/checkout/pkg/module.py"),
+            Some(Path::new("/home/ci/checkout")),
+        );
+        let clone = fingerprints(
+            &at("/tmp/clone/pkg/module.py"),
+            Some(Path::new("/tmp/clone")),
+        );
+        assert_eq!(checkout, clone);
+        assert_eq!(checkout, fingerprints(&at("pkg/module.py"), None));
+    }
+
+    #[test]
+    fn fingerprints_ignore_locale() {
+        let file = SourceFileBuilder::new("module.py", "import yaml\n").finish();
+        let diagnostic = Diagnostic::new(
+            DiagnosticKind::from_template(
+                ErrorCode::ImportNotFound,
+                MessageTemplate::new("import-not-found").arg("name", "yaml"),
+            ),
+            TextRange::at(TextSize::from(7), TextSize::from(4)),
+        );
+        let mut messages = vec![Message::from_diagnostic(
+            diagnostic,
+            file,
+            TextSize::from(0),
+            MessageKind::Error,
+        )];
+        let english = fingerprints(&messages, None);
+        messages[0].diagnostic.localize(Locale::De);
+        assert_ne!(messages[0].diagnostic.body, "Cannot find module `yaml`");
+        assert_eq!(fingerprints(&messages, None), english);
+    }
 }
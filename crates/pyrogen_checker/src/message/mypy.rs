@@ -0,0 +1,61 @@
+use std::io::Write;
+
+use crate::fs::relativize_path;
+use crate::message::{Emitter, Message};
+use crate::settings::code_table::MessageKind;
+use crate::settings::types::SeverityMapping;
+
+/// Generate violations in mypy's format, `path:line: error: message  [code]`, for tools that
+/// already parse mypy's output.
+#[derive(Default)]
+pub struct MypyEmitter {
+    severity_mapping: SeverityMapping,
+}
+
+impl MypyEmitter {
+    #[must_use]
+    pub fn with_severity_mapping(mut self, severity_mapping: SeverityMapping) -> Self {
+        self.severity_mapping = severity_mapping;
+        self
+    }
+}
+
+impl Emitter for MypyEmitter {
+    fn emit(&mut self, writer: &mut dyn Write, messages: &[Message]) -> anyhow::Result<()> {
+        for message in messages {
+            let severity = match self.severity_mapping.map(message.kind) {
+                MessageKind::Error => "error",
+                MessageKind::Warning => "warning",
+                // mypy reports everything else as notes.
+                MessageKind::Info | MessageKind::Hint => "note",
+            };
+
+            writeln!(
+                writer,
+                "{path}:{row}: {severity}: {body}  [{code}]",
+                path = relativize_path(message.filename()),
+                row = message.compute_start_location().row,
+                body = message.diagnostic.body,
+                code = message.diagnostic.error_code,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+
+    use crate::message::tests::{capture_emitter_output, create_messages};
+    use crate::message::MypyEmitter;
+
+    #[test]
+    fn output() {
+        let mut emitter = MypyEmitter::default();
+        let content = capture_emitter_output(&mut emitter, &create_messages());
+
+        assert_snapshot!(content);
+    }
+}
@@ -0,0 +1,42 @@
+use std::io::Write;
+
+use crate::fs::relativize_path;
+use crate::message::{Emitter, Message};
+
+/// Generate violations in Pylint's `parseable` format, `path:line: [code] message`.
+/// See: [Pylint documentation](https://pylint.readthedocs.io/en/latest/user_guide/usage/output.html)
+#[derive(Default)]
+pub struct PylintEmitter;
+
+impl Emitter for PylintEmitter {
+    fn emit(&mut self, writer: &mut dyn Write, messages: &[Message]) -> anyhow::Result<()> {
+        for message in messages {
+            writeln!(
+                writer,
+                "{path}:{row}: [{code}] {body}",
+                path = relativize_path(message.filename()),
+                row = message.compute_start_location().row,
+                code = message.diagnostic.error_code,
+                body = message.diagnostic.body,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+
+    use crate::message::tests::{capture_emitter_output, create_messages};
+    use crate::message::PylintEmitter;
+
+    #[test]
+    fn output() {
+        let mut emitter = PylintEmitter;
+        let content = capture_emitter_output(&mut emitter, &create_messages());
+
+        assert_snapshot!(content);
+    }
+}
@@ -0,0 +1,194 @@
+use std::io::Write;
+use std::str::FromStr;
+
+use pyrogen_source_file::ColumnEncoding;
+
+use crate::fs::relativize_path;
+use crate::message::{Emitter, Message};
+
+/// A line format for messages, like `{path}:{row}: {code} {message}`.
+///
+/// The placeholders are `path`, `row`, `column`, `end_row`, `end_column`, `code`, `message` and
+/// `severity`. Literal braces are written as `{{` and `}}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTemplate {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Placeholder {
+    Path,
+    Row,
+    Column,
+    EndRow,
+    EndColumn,
+    Code,
+    Message,
+    Severity,
+}
+
+impl FromStr for Placeholder {
+    type Err = TemplateError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "path" => Ok(Self::Path),
+            "row" => Ok(Self::Row),
+            "column" => Ok(Self::Column),
+            "end_row" => Ok(Self::EndRow),
+            "end_column" => Ok(Self::EndColumn),
+            "code" => Ok(Self::Code),
+            "message" => Ok(Self::Message),
+            "severity" => Ok(Self::Severity),
+            _ => Err(TemplateError::UnknownPlaceholder(name.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum TemplateError {
+    #[error("Unknown placeholder `{{{0}}}` in output template")]
+    UnknownPlaceholder(String),
+    #[error("Unclosed `{{` in output template (write `{{{{` for a literal brace)")]
+    Unclosed,
+    #[error("Unmatched `}}` in output template (write `}}}}` for a literal brace)")]
+    Unmatched,
+}
+
+impl FromStr for OutputTemplate {
+    type Err = TemplateError;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let mut segments = vec![];
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.next_if_eq(&'{').is_some() => literal.push('{'),
+                '}' if chars.next_if_eq(&'}').is_some() => literal.push('}'),
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(TemplateError::Unclosed),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Placeholder(name.trim().parse()?));
+                }
+                '}' => return Err(TemplateError::Unmatched),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Self { segments })
+    }
+}
+
+impl OutputTemplate {
+    fn render(
+        &self,
+        writer: &mut dyn Write,
+        message: &Message,
+        column_encoding: ColumnEncoding,
+    ) -> std::io::Result<()> {
+        let start_location = message.compute_start_location_with_encoding(column_encoding);
+        let end_location = message.compute_end_location_with_encoding(column_encoding);
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => write!(writer, "{literal}")?,
+                Segment::Placeholder(placeholder) => match placeholder {
+                    Placeholder::Path => write!(writer, "{}", relativize_path(message.filename()))?,
+                    Placeholder::Row => write!(writer, "{}", start_location.row)?,
+                    Placeholder::Column => write!(writer, "{}", start_location.column)?,
+                    Placeholder::EndRow => write!(writer, "{}", end_location.row)?,
+                    Placeholder::EndColumn => write!(writer, "{}", end_location.column)?,
+                    Placeholder::Code => write!(writer, "{}", message.diagnostic.error_code)?,
+                    Placeholder::Message => write!(writer, "{}", message.diagnostic.body)?,
+                    Placeholder::Severity => write!(writer, "{}", message.kind)?,
+                },
+            }
+        }
+        writeln!(writer)
+    }
+}
+
+/// Prints every message on a line of its own, in a user-defined [`OutputTemplate`].
+pub struct TemplateEmitter {
+    template: OutputTemplate,
+    column_encoding: ColumnEncoding,
+}
+
+impl TemplateEmitter {
+    pub fn new(template: OutputTemplate) -> Self {
+        Self {
+            template,
+            column_encoding: ColumnEncoding::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_column_encoding(mut self, column_encoding: ColumnEncoding) -> Self {
+        self.column_encoding = column_encoding;
+        self
+    }
+}
+
+impl Emitter for TemplateEmitter {
+    fn emit(&mut self, writer: &mut dyn Write, messages: &[Message]) -> anyhow::Result<()> {
+        for message in messages {
+            self.template
+                .render(writer, message, self.column_encoding)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+
+    use crate::message::tests::{capture_emitter_output, create_messages};
+    use crate::message::{OutputTemplate, TemplateEmitter, TemplateError};
+
+    #[test]
+    fn output() {
+        let template =
+            "{path}:{row}:{column}-{end_row}:{end_column}: {{{severity}}} {code} {message}"
+                .parse::<OutputTemplate>()
+                .unwrap();
+        let mut emitter = TemplateEmitter::new(template);
+        let content = capture_emitter_output(&mut emitter, &create_messages());
+
+        assert_snapshot!(content);
+    }
+
+    #[test]
+    fn invalid_templates() {
+        assert_eq!(
+            "{path}:{line}".parse::<OutputTemplate>(),
+            Err(TemplateError::UnknownPlaceholder("line".to_string()))
+        );
+        assert_eq!(
+            "{path".parse::<OutputTemplate>(),
+            Err(TemplateError::Unclosed)
+        );
+        assert_eq!(
+            "path}".parse::<OutputTemplate>(),
+            Err(TemplateError::Unmatched)
+        );
+    }
+}
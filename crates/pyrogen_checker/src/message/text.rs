@@ -7,7 +7,7 @@ use annotate_snippets::snippet::{AnnotationType, Slice, Snippet, SourceAnnotatio
 use bitflags::bitflags;
 use colored::Colorize;
 
-use pyrogen_source_file::OneIndexed;
+use pyrogen_source_file::{ColumnEncoding, OneIndexed};
 use rustpython_parser::text_size::{TextRange, TextSize};
 
 use crate::fs::relativize_path;
@@ -27,6 +27,7 @@ bitflags! {
 #[derive(Default)]
 pub struct TextEmitter {
     flags: EmitterFlags,
+    column_encoding: ColumnEncoding,
 }
 
 impl TextEmitter {
@@ -35,6 +36,12 @@ impl TextEmitter {
         self.flags.set(EmitterFlags::SHOW_SOURCE, show_source);
         self
     }
+
+    #[must_use]
+    pub fn with_column_encoding(mut self, column_encoding: ColumnEncoding) -> Self {
+        self.column_encoding = column_encoding;
+        self
+    }
 }
 
 impl Emitter for TextEmitter {
@@ -47,7 +54,7 @@ impl Emitter for TextEmitter {
                 sep = ":".cyan(),
             )?;
 
-            let start_location = message.compute_start_location();
+            let start_location = message.compute_start_location_with_encoding(self.column_encoding);
 
             let diagnostic_location = start_location;
 
@@ -94,6 +101,22 @@ impl Display for RuleCodeAndBody<'_> {
                     body = diagnostic.body,
                 )
             }
+            MessageKind::Info => {
+                write!(
+                    f,
+                    "info: {body} [{code}]",
+                    code = diagnostic.error_code().to_string().blue().bold(),
+                    body = diagnostic.body,
+                )
+            }
+            MessageKind::Hint => {
+                write!(
+                    f,
+                    "hint: {body} [{code}]",
+                    code = diagnostic.error_code().to_string().cyan().bold(),
+                    body = diagnostic.body,
+                )
+            }
         }
     }
 }
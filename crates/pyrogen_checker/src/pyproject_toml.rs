@@ -1,8 +1,15 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::str::FromStr;
+
 use colored::Colorize;
 use log::warn;
+use pep440_rs::VersionSpecifiers;
+use pep508_rs::Requirement;
 use pyproject_toml::{BuildSystem, Project};
 use rustpython_parser::text_size::{TextRange, TextSize};
 use serde::{Deserialize, Serialize};
+use toml::Spanned;
 
 use pyrogen_source_file::SourceFile;
 
@@ -10,6 +17,7 @@ use crate::message::Message;
 use crate::registry::{Diagnostic, DiagnosticKind, ErrorCode};
 use crate::settings::code_table::MessageKind;
 use crate::settings::CheckerSettings;
+use crate::violation::Violation;
 
 /// Unlike [`pyproject_toml::PyProjectToml`], in our case `build_system` is also optional
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -21,12 +29,154 @@ struct PyProjectToml {
     project: Option<Project>,
 }
 
+/// The fields of `pyproject.toml` that are validated beyond their type, with the spans of their
+/// values. Everything else is ignored, so that this parses whenever the validated fields have the
+/// right type.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+struct Metadata {
+    build_system: Option<BuildRequirements>,
+    project: Option<ProjectMetadata>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BuildRequirements {
+    requires: Option<Vec<Spanned<String>>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+struct ProjectMetadata {
+    requires_python: Option<Spanned<String>>,
+    classifiers: Option<Vec<Spanned<String>>>,
+    dependencies: Option<Vec<Spanned<String>>>,
+    optional_dependencies: Option<BTreeMap<String, Vec<Spanned<String>>>>,
+}
+
+#[derive(Violation)]
+#[violation(code = InvalidPyprojectToml, message = "invalid-pyproject-toml")]
+struct InvalidPyprojectToml {
+    error: String,
+}
+
+#[derive(Violation)]
+#[violation(code = PyprojectDependency, message = "invalid-dependency")]
+struct InvalidDependency<'a> {
+    requirement: &'a str,
+    error: String,
+}
+
+#[derive(Violation)]
+#[violation(code = PyprojectRequiresPython, message = "invalid-requires-python")]
+struct InvalidRequiresPython<'a> {
+    specifier: &'a str,
+    error: String,
+}
+
+#[derive(Violation)]
+#[violation(code = PyprojectClassifier, message = "invalid-classifier")]
+struct InvalidClassifier<'a> {
+    classifier: &'a str,
+}
+
+fn text_range(range: Range<usize>) -> Option<TextRange> {
+    Some(TextRange::new(
+        TextSize::try_from(range.start).ok()?,
+        TextSize::try_from(range.end).ok()?,
+    ))
+}
+
+/// Whether `classifier` consists of at least two non-empty segments separated by ` :: `.
+fn is_valid_classifier(classifier: &str) -> bool {
+    let segments = classifier.split(" :: ").collect::<Vec<_>>();
+    segments.len() >= 2
+        && segments.iter().all(|segment| {
+            !segment.is_empty() && segment.trim() == *segment && !segment.contains("::")
+        })
+}
+
+/// Report the values of `requires-python`, `classifiers` and the dependencies of the project that
+/// have the right type, but aren't valid.
+fn metadata_diagnostics(source: &str) -> Vec<Diagnostic> {
+    let Ok(metadata) = toml::from_str::<Metadata>(source) else {
+        return vec![];
+    };
+
+    let mut diagnostics = vec![];
+    let mut dependency = |requirement: &Spanned<String>| {
+        if let Err(err) = Requirement::from_str(requirement.get_ref()) {
+            if let Some(range) = text_range(requirement.span()) {
+                diagnostics.push(Diagnostic::new(
+                    InvalidDependency {
+                        requirement: requirement.get_ref(),
+                        error: err.message.to_string(),
+                    },
+                    range,
+                ));
+            }
+        }
+    };
+    if let Some(requires) = metadata
+        .build_system
+        .and_then(|build_system| build_system.requires)
+    {
+        requires.iter().for_each(&mut dependency);
+    }
+    let Some(project) = metadata.project else {
+        return diagnostics;
+    };
+    project
+        .dependencies
+        .iter()
+        .flatten()
+        .for_each(&mut dependency);
+    project
+        .optional_dependencies
+        .iter()
+        .flat_map(BTreeMap::values)
+        .flatten()
+        .for_each(&mut dependency);
+
+    if let Some(requires_python) = &project.requires_python {
+        if let Err(err) = VersionSpecifiers::from_str(requires_python.get_ref()) {
+            if let Some(range) = text_range(requires_python.span()) {
+                diagnostics.push(Diagnostic::new(
+                    InvalidRequiresPython {
+                        specifier: requires_python.get_ref(),
+                        error: err.to_string(),
+                    },
+                    range,
+                ));
+            }
+        }
+    }
+
+    for classifier in project.classifiers.iter().flatten() {
+        if is_valid_classifier(classifier.get_ref()) {
+            continue;
+        }
+        if let Some(range) = text_range(classifier.span()) {
+            diagnostics.push(Diagnostic::new(
+                InvalidClassifier {
+                    classifier: classifier.get_ref(),
+                },
+                range,
+            ));
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.range.start());
+    diagnostics
+}
+
 pub fn lint_pyproject_toml(source_file: SourceFile, settings: &CheckerSettings) -> Vec<Message> {
+    let metadata = metadata_diagnostics(source_file.source_text());
+    let mut messages = Vec::new();
     let Some(err) = toml::from_str::<PyProjectToml>(source_file.source_text()).err() else {
-        return Vec::default();
+        add_metadata_messages(metadata, &source_file, settings, &mut messages);
+        return messages;
     };
 
-    let mut messages = Vec::new();
     let range = match err.span() {
         // This is bad but sometimes toml and/or serde just don't give us spans
         // TODO(konstin,micha): https://github.com/astral-sh/ruff/issues/4571
@@ -39,10 +189,7 @@ pub fn lint_pyproject_toml(source_file: SourceFile, settings: &CheckerSettings)
                 );
                 if settings.table.enabled(ErrorCode::IOError) {
                     let diagnostic = Diagnostic::new(
-                        DiagnosticKind {
-                            error_code: ErrorCode::IOError,
-                            body: message,
-                        },
+                        DiagnosticKind::new(ErrorCode::IOError, message),
                         TextRange::default(),
                     );
                     messages.push(Message::from_diagnostic(
@@ -69,15 +216,15 @@ pub fn lint_pyproject_toml(source_file: SourceFile, settings: &CheckerSettings)
         }
     };
 
-    if settings.table.enabled(ErrorCode::InvalidPyprojectToml) {
+    // The same value was already reported with a more specific code.
+    let reported = metadata.iter().any(|diagnostic| {
+        diagnostic.range.contains_range(range) || range.contains_range(diagnostic.range)
+    });
+    add_metadata_messages(metadata, &source_file, settings, &mut messages);
+
+    if !reported && settings.table.enabled(ErrorCode::InvalidPyprojectToml) {
         let toml_err = err.message().to_string();
-        let diagnostic = Diagnostic::new(
-            DiagnosticKind {
-                error_code: ErrorCode::InvalidPyprojectToml,
-                body: format!("Failed to parse pyproject.toml: {toml_err}"),
-            },
-            range,
-        );
+        let diagnostic = Diagnostic::new(InvalidPyprojectToml { error: toml_err }, range);
         messages.push(Message::from_diagnostic(
             diagnostic,
             source_file,
@@ -88,3 +235,70 @@ pub fn lint_pyproject_toml(source_file: SourceFile, settings: &CheckerSettings)
 
     messages
 }
+
+fn add_metadata_messages(
+    diagnostics: Vec<Diagnostic>,
+    source_file: &SourceFile,
+    settings: &CheckerSettings,
+    messages: &mut Vec<Message>,
+) {
+    for diagnostic in diagnostics {
+        let Some(kind) = settings.table.entry(diagnostic.kind.error_code) else {
+            continue;
+        };
+        messages.push(Message::from_diagnostic(
+            diagnostic,
+            source_file.clone(),
+            TextSize::default(),
+            kind,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::metadata_diagnostics;
+
+    #[test]
+    fn invalid_metadata() {
+        let source = r#"
+[build-system]
+requires = ["setuptools>=61", "wheel=="]
+
+[project]
+name = "example"
+requires-python = ">=3.8,<"
+classifiers = [
+    "Programming Language :: Python :: 3",
+    "Typing::Typed",
+    "Topic :: ",
+]
+dependencies = ["requests>=2.31", "numpy >= 1.2 ;"]
+
+[project.optional-dependencies]
+test = ["pytest", "hypothesis ~"]
+"#;
+        let diagnostics = metadata_diagnostics(source)
+            .into_iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.kind.error_code.to_str(),
+                    &source[diagnostic.range],
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [
+                ("pyproject-dependency", "\"wheel==\""),
+                ("pyproject-requires-python", "\">=3.8,<\""),
+                ("pyproject-classifier", "\"Typing::Typed\""),
+                ("pyproject-classifier", "\"Topic :: \""),
+                ("pyproject-dependency", "\"numpy >= 1.2 ;\""),
+                ("pyproject-dependency", "\"hypothesis ~\""),
+            ]
+        );
+
+        assert!(metadata_diagnostics("[project]\nname = \"example\"\n").is_empty());
+    }
+}
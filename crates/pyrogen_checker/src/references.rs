@@ -0,0 +1,454 @@
+//! Project-wide references to the functions and classes defined at the top level of modules, to
+//! find the ones that are never used.
+//!
+//! A definition is used if it's reachable from a root: the module-level code of any module
+//! (including `if __name__ == "__main__":` blocks), the entry points declared in the
+//! `pyproject.toml`, and the definitions matching `dead-code-keep`. The references in the body of
+//! a function or class only count if the function or class is used itself.
+//!
+//! References are tracked by qualified name: `from pkg.mod import f` and `pkg.mod.f()` after
+//! `import pkg.mod` both reference `pkg.mod.f`. The tracking errs on the side of treating
+//! definitions as used, since a report of dead code that is in fact used is worse than a missed
+//! one:
+//!
+//! - any use of a name in a module references the definition of that name in the module, even if
+//!   a local variable shadows it;
+//! - importing a name references it, which covers re-exports;
+//! - strings that are (dotted) names reference them, which covers `__all__`, forward references
+//!   in annotations and names passed to `getattr` or `importlib`;
+//! - star imports reference every definition of the imported module;
+//! - decorated definitions are roots, since decorators commonly register them somewhere.
+//!
+//! Only the modules that are checked together are considered, so references from other parts of
+//! a project are missed when only some of its files are checked.
+
+use globset::GlobMatcher;
+use rustc_hash::{FxHashMap, FxHashSet};
+use rustpython_ast::{self as ast, Constant, Expr, ExprContext, Stmt};
+
+use pyrogen_module_resolver::ModuleName;
+use pyrogen_python_ast::visitor::{self, Visitor};
+use pyrogen_python_ast::{AsMode, PySourceType};
+
+use crate::definitions::{identifier_range, Definition, DefinitionKind};
+use crate::settings::CheckerSettings;
+
+/// A function or class at the top level of a module.
+#[derive(Debug)]
+struct TopLevelDefinition {
+    definition: Definition,
+    /// Whether the definition is used regardless of references to it, like entry points.
+    is_root: bool,
+    /// The qualified names that the body of the definition references.
+    references: FxHashSet<String>,
+}
+
+/// The top-level definitions of a module, and the names it references.
+#[derive(Debug, Default)]
+pub struct ModuleReferences {
+    definitions: Vec<TopLevelDefinition>,
+    /// The qualified names that the module-level code references, including the code in
+    /// `if __name__ == "__main__":` blocks.
+    references: FxHashSet<String>,
+    /// The modules that the module star-imports.
+    star_imports: Vec<ModuleName>,
+}
+
+struct ReferenceCollector<'a> {
+    module: &'a ModuleName,
+    is_package: bool,
+    /// The qualified names of the imported names and modules, keyed by the name they're bound to.
+    imports: FxHashMap<&'a str, String>,
+    references: FxHashSet<String>,
+    star_imports: Vec<ModuleName>,
+}
+
+impl<'a> ReferenceCollector<'a> {
+    /// Reference the name `name` followed by the attributes `attributes`, as well as the
+    /// prefixes of the resulting qualified name.
+    fn reference(&mut self, name: &str, attributes: &[&str]) {
+        let mut qualified_names = vec![format!("{}.{name}", self.module)];
+        if let Some(imported) = self.imports.get(name) {
+            qualified_names.push(imported.clone());
+        }
+        for mut qualified_name in qualified_names {
+            self.references.insert(qualified_name.clone());
+            for attribute in attributes {
+                qualified_name.push('.');
+                qualified_name.push_str(attribute);
+                self.references.insert(qualified_name.clone());
+            }
+        }
+    }
+}
+
+/// Split an attribute chain `a.b.c` into `a` and `["b", "c"]`.
+fn attribute_chain(expr: &Expr) -> Option<(&str, Vec<&str>)> {
+    match expr {
+        Expr::Name(ast::ExprName { id, .. }) => Some((id.as_str(), vec![])),
+        Expr::Attribute(ast::ExprAttribute { value, attr, .. }) => {
+            let (name, mut attributes) = attribute_chain(value)?;
+            attributes.push(attr.as_str());
+            Some((name, attributes))
+        }
+        _ => None,
+    }
+}
+
+impl<'a> Visitor<'a> for ReferenceCollector<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::Import(ast::StmtImport { names, .. }) => {
+                for alias in names {
+                    match &alias.asname {
+                        Some(asname) => {
+                            self.imports
+                                .insert(asname.as_str(), alias.name.as_str().to_string());
+                        }
+                        None => {
+                            // `import a.b` binds `a`.
+                            let name = alias.name.as_str().split('.').next().unwrap_or_default();
+                            self.imports.insert(name, name.to_string());
+                        }
+                    }
+                }
+            }
+            Stmt::ImportFrom(ast::StmtImportFrom {
+                module,
+                names,
+                level,
+                ..
+            }) => {
+                let Some(imported) = self.module.resolve_import(
+                    self.is_package,
+                    level.map_or(0, |level| level.to_u32()),
+                    module.as_ref().map(ast::Identifier::as_str),
+                ) else {
+                    return;
+                };
+                for alias in names {
+                    if alias.name.as_str() == "*" {
+                        self.star_imports.push(imported.clone());
+                        continue;
+                    }
+                    let qualified_name = format!("{imported}.{}", alias.name.as_str());
+                    self.references.insert(qualified_name.clone());
+                    self.imports.insert(
+                        alias.asname.as_ref().unwrap_or(&alias.name).as_str(),
+                        qualified_name,
+                    );
+                }
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::Name(ast::ExprName {
+                id,
+                ctx: ExprContext::Load | ExprContext::Del,
+                ..
+            }) => self.reference(id.as_str(), &[]),
+            Expr::Attribute(_) => match attribute_chain(expr) {
+                Some((name, attributes)) => self.reference(name, &attributes),
+                None => visitor::walk_expr(self, expr),
+            },
+            Expr::Constant(ast::ExprConstant {
+                value: Constant::Str(value),
+                ..
+            }) => {
+                if ModuleName::new(value).is_some() {
+                    if value.contains('.') {
+                        self.references.insert(value.to_string());
+                    } else {
+                        self.reference(value, &[]);
+                    }
+                }
+            }
+            _ => visitor::walk_expr(self, expr),
+        }
+    }
+}
+
+/// Returns `true` if `definition` matches one of the `patterns`, which are matched against the
+/// qualified name if they contain a `.`, and against the plain name otherwise.
+fn matches_any(definition: &Definition, patterns: &[GlobMatcher]) -> bool {
+    patterns.iter().any(|pattern| {
+        pattern.is_match(if pattern.glob().glob().contains('.') {
+            &definition.qualified_name
+        } else {
+            &definition.name
+        })
+    })
+}
+
+/// Collect the top-level definitions and references of `python_ast`, the body of `module`.
+pub fn module_references(
+    python_ast: &[Stmt],
+    source: &str,
+    source_type: PySourceType,
+    module: &ModuleName,
+    is_package: bool,
+    settings: &CheckerSettings,
+) -> ModuleReferences {
+    let mut collector = ReferenceCollector {
+        module,
+        is_package,
+        imports: FxHashMap::default(),
+        references: FxHashSet::default(),
+        star_imports: vec![],
+    };
+    let mut definitions = vec![];
+    for stmt in python_ast {
+        let (name, kind, decorator_list) = match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef {
+                name,
+                decorator_list,
+                ..
+            })
+            | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+                name,
+                decorator_list,
+                ..
+            }) => (name.as_str(), DefinitionKind::Function, decorator_list),
+            Stmt::ClassDef(ast::StmtClassDef {
+                name,
+                decorator_list,
+                ..
+            }) => (name.as_str(), DefinitionKind::Class, decorator_list),
+            _ => {
+                collector.visit_stmt(stmt);
+                continue;
+            }
+        };
+        let definition = Definition {
+            name: name.to_string(),
+            qualified_name: format!("{module}.{name}"),
+            kind,
+            range: identifier_range(stmt, name, source),
+        };
+        // Stubs describe modules that are defined elsewhere, and dunder names like `__getattr__`
+        // are used by the interpreter.
+        let is_root = !decorator_list.is_empty()
+            || source_type.is_stub()
+            || (name.starts_with("__") && name.ends_with("__"))
+            || settings.entry_points.contains(&definition.qualified_name)
+            || matches_any(&definition, &settings.dead_code_keep);
+
+        let module_references = std::mem::take(&mut collector.references);
+        collector.visit_stmt(stmt);
+        let references = std::mem::replace(&mut collector.references, module_references);
+        definitions.push(TopLevelDefinition {
+            definition,
+            is_root,
+            references,
+        });
+    }
+
+    ModuleReferences {
+        definitions,
+        references: collector.references,
+        star_imports: collector.star_imports,
+    }
+}
+
+/// Parse `source`, the contents of `module`, and collect its top-level definitions and
+/// references. Returns `None` if `source` contains syntax errors.
+pub fn find_references(
+    source: &str,
+    source_type: PySourceType,
+    module: &ModuleName,
+    is_package: bool,
+    settings: &CheckerSettings,
+) -> Option<ModuleReferences> {
+    let python_ast = rustpython_parser::parse(source, source_type.as_mode(), module.as_str())
+        .ok()?
+        .expect_module()
+        .body;
+    Some(module_references(
+        &python_ast,
+        source,
+        source_type,
+        module,
+        is_package,
+        settings,
+    ))
+}
+
+/// The top-level functions and classes of the modules of a project that are unreachable from
+/// the module-level code of any module.
+#[derive(Debug, Default)]
+pub struct ProjectReferences {
+    unreferenced: FxHashMap<ModuleName, Vec<Definition>>,
+}
+
+impl ProjectReferences {
+    /// Find the unreachable definitions of `modules`. If a module name occurs more than once,
+    /// the definitions of the first occurrence are checked, but the references of all of them
+    /// count.
+    pub fn new(modules: impl IntoIterator<Item = (ModuleName, ModuleReferences)>) -> Self {
+        let modules = modules.into_iter().collect::<Vec<_>>();
+        let star_imported = modules
+            .iter()
+            .flat_map(|(_, module)| &module.star_imports)
+            .collect::<FxHashSet<_>>();
+
+        let mut seen = FxHashSet::default();
+        let mut definitions = FxHashMap::default();
+        let mut live = FxHashSet::default();
+        let mut pending = vec![];
+        for (name, module) in &modules {
+            pending.extend(module.references.iter().map(String::as_str));
+            // The definitions of the later occurrences of a module are never reported, so they
+            // might as well be roots.
+            let is_root = !seen.insert(name) || star_imported.contains(name);
+            for definition in &module.definitions {
+                let qualified_name = definition.definition.qualified_name.as_str();
+                if is_root || definition.is_root {
+                    pending.push(qualified_name);
+                }
+                definitions
+                    .entry(qualified_name)
+                    .or_insert_with(Vec::new)
+                    .push(definition);
+            }
+        }
+
+        // Mark everything that the roots reference, directly or through the bodies of the
+        // definitions they reference, as live.
+        while let Some(qualified_name) = pending.pop() {
+            if !live.insert(qualified_name) {
+                continue;
+            }
+            for definition in definitions.get(qualified_name).into_iter().flatten() {
+                pending.extend(definition.references.iter().map(String::as_str));
+            }
+        }
+
+        let mut unreferenced = FxHashMap::default();
+        for (name, module) in &modules {
+            unreferenced.entry(name.clone()).or_insert_with(|| {
+                module
+                    .definitions
+                    .iter()
+                    .map(|definition| &definition.definition)
+                    .filter(|definition| !live.contains(definition.qualified_name.as_str()))
+                    .cloned()
+                    .collect()
+            });
+        }
+        Self { unreferenced }
+    }
+
+    /// The top-level functions and classes of `module` that are never used.
+    pub fn unreferenced(&self, module: &ModuleName) -> &[Definition] {
+        self.unreferenced.get(module).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use globset::Glob;
+
+    use pyrogen_module_resolver::ModuleName;
+    use pyrogen_python_ast::PySourceType;
+
+    use crate::settings::CheckerSettings;
+
+    use super::{find_references, ProjectReferences};
+
+    #[test]
+    fn unreferenced() {
+        let modules = [
+            (
+                "pkg",
+                true,
+                r#"
+from .impl import exported
+"#,
+            ),
+            (
+                "pkg.impl",
+                false,
+                r#"
+__all__ = ["listed"]
+
+def exported(): ...
+def listed(): ...
+def recursive(n):
+    return recursive(n - 1)
+def used_by_unused(): ...
+def _unused(): ...
+
+@register
+def decorated(): ...
+
+class Unused:
+    def method(self):
+        return used_by_unused()
+
+class Used: ...
+def forward(x: "Annotated"): ...
+class Annotated: ...
+"#,
+            ),
+            (
+                "main",
+                false,
+                r#"
+import pkg.impl as impl
+
+def main():
+    impl.Used()
+    forward(None)
+
+def cli():
+    helper()
+
+def helper(): ...
+
+def test_main(): ...
+
+if __name__ == "__main__":
+    main()
+"#,
+            ),
+        ];
+        let settings = CheckerSettings {
+            entry_points: vec!["main.cli".to_string()],
+            dead_code_keep: vec![Glob::new("test_*").unwrap().compile_matcher()],
+            ..CheckerSettings::default()
+        };
+        let references = ProjectReferences::new(modules.map(|(name, is_package, source)| {
+            let name = ModuleName::new(name).unwrap();
+            let references =
+                find_references(source, PySourceType::Python, &name, is_package, &settings)
+                    .unwrap();
+            (name, references)
+        }));
+
+        let unreferenced = |module: &str| {
+            references
+                .unreferenced(&ModuleName::new(module).unwrap())
+                .iter()
+                .map(|definition| definition.qualified_name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert!(unreferenced("pkg").is_empty());
+        // `Annotated` is only used by `forward`, which is unused itself.
+        assert_eq!(
+            unreferenced("pkg.impl"),
+            [
+                "pkg.impl.recursive",
+                "pkg.impl.used_by_unused",
+                "pkg.impl._unused",
+                "pkg.impl.Unused",
+                "pkg.impl.forward",
+                "pkg.impl.Annotated",
+            ]
+        );
+        // `forward` in `main` is undefined, so it doesn't reference `pkg.impl.forward`.
+        assert!(unreferenced("main").is_empty());
+    }
+}
@@ -0,0 +1,309 @@
+//! The documentation of each error code, as shown by `pyrogen explain <code>`.
+
+use std::fmt;
+
+use crate::registry::ErrorCode;
+
+/// Whether the diagnostics of a code come with a fix that `--fix` can apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixAvailability {
+    Always,
+    Sometimes,
+    None,
+}
+
+impl fmt::Display for FixAvailability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Always => "Always fixable",
+            Self::Sometimes => "Sometimes fixable",
+            Self::None => "Not fixable",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CodeDocs {
+    /// A one-line description of what the code reports.
+    pub summary: &'static str,
+    /// Why it's reported and what isn't, as a paragraph.
+    pub explanation: &'static str,
+    /// Python code that is reported with the code.
+    pub example: Option<&'static str>,
+    pub fix: FixAvailability,
+}
+
+impl CodeDocs {
+    const fn new(summary: &'static str, explanation: &'static str) -> Self {
+        Self {
+            summary,
+            explanation,
+            example: None,
+            fix: FixAvailability::None,
+        }
+    }
+
+    const fn example(mut self, example: &'static str) -> Self {
+        self.example = Some(example);
+        self
+    }
+
+    const fn fix(mut self, fix: FixAvailability) -> Self {
+        self.fix = fix;
+        self
+    }
+}
+
+impl ErrorCode {
+    /// The documentation of the code.
+    pub const fn docs(self) -> CodeDocs {
+        match self {
+            Self::InvalidPyprojectToml => CodeDocs::new(
+                "The `pyproject.toml` file can't be parsed.",
+                "A `pyproject.toml` file has invalid TOML syntax, or its `[tool.pyrogen]` \
+                 section has unknown or mistyped options. The diagnostic points at the offending \
+                 part of the file.",
+            ),
+            Self::PyprojectRequiresPython => CodeDocs::new(
+                "The `requires-python` of the project isn't a valid version specifier.",
+                "`project.requires-python` must be a comma-separated list of PEP 440 version \
+                 specifiers, like `>=3.9,<4`.",
+            ),
+            Self::PyprojectClassifier => CodeDocs::new(
+                "A trove classifier of the project is malformed.",
+                "Each entry of `project.classifiers` must consist of at least two non-empty \
+                 segments separated by ` :: `, like `Programming Language :: Python :: 3`. \
+                 Whether the classifier is known to PyPI isn't checked.",
+            ),
+            Self::PyprojectDependency => CodeDocs::new(
+                "A dependency isn't a valid PEP 508 requirement.",
+                "The entries of `project.dependencies`, `project.optional-dependencies` and \
+                 `build-system.requires` must be PEP 508 requirements, like \
+                 `requests[socks]>=2.31; python_version >= \"3.8\"`.",
+            ),
+            Self::Override => CodeDocs::new(
+                "A final class is subclassed, or a final method is overridden.",
+                "Classes and methods decorated with `typing.final` (or `typing_extensions.final`) \
+                 can't be subclassed or overridden. Only the classes defined at the top level of \
+                 a module are checked.",
+            )
+            .example(
+                "from typing import final\n\nclass Base:\n    @final\n    def run(self) -> None: ...\n\nclass Derived(Base):\n    def run(self) -> None: ...",
+            ),
+            Self::Unreachable => CodeDocs::new(
+                "Code that can never be executed.",
+                "Reserved for statements that follow a `return`, `raise`, `break` or `continue` \
+                 in the same block. This code isn't reported yet.",
+            ),
+            Self::UnusedTypeIgnore => CodeDocs::new(
+                "A `type: ignore` comment that doesn't suppress anything.",
+                "A `type: ignore` comment is reported if no diagnostic on its line was \
+                 suppressed by it, and a `type: ignore[...]` comment is also reported for each \
+                 listed code that it didn't suppress. The fix removes the comment or the unused \
+                 codes.",
+            )
+            .example("x = 1  # type: ignore")
+            .fix(FixAvailability::Always),
+            Self::ExpiredSuppression => CodeDocs::new(
+                "A `type: ignore` comment whose expiry date has passed.",
+                "A `type: ignore` comment can be followed by an expiry date, like \
+                 `# type: ignore[call-arg]  # pyrogen: expires=2025-12-31`, to make sure that a \
+                 temporary suppression gets revisited. From the day after the date on, the \
+                 comment no longer suppresses anything, and is reported instead.",
+            )
+            .example("f(1, 2)  # type: ignore[call-arg]  # pyrogen: expires=2020-01-01"),
+            Self::TooManyDiagnostics => CodeDocs::new(
+                "A file has more diagnostics than the `max-diagnostics-per-file` setting allows.",
+                "The diagnostics beyond the limit are dropped, errors last, and this notice at \
+                 the start of the file says how many. It usually points at generated code, \
+                 which is better excluded from the check.",
+            ),
+            Self::SyntaxError => CodeDocs::new(
+                "The file can't be parsed, or uses syntax that Python rejects.",
+                "Besides parse errors, this covers syntax that the parser accepts but the \
+                 Python compiler doesn't, like `except*` before Python 3.11 or an assignment \
+                 expression that rebinds a comprehension variable. A file with parse errors \
+                 isn't checked otherwise.",
+            )
+            .example("values = [(x := 0) for x in range(3)]"),
+            Self::GeneralTypeError => CodeDocs::new(
+                "A type error that doesn't have a more specific code.",
+                "Covers values that don't match the annotation of the variable they're \
+                 assigned to, and `except*` handlers that catch `ExceptionGroup`.",
+            )
+            .example("count: int = \"one\""),
+            Self::UnusedImport => CodeDocs::new(
+                "An imported name that is never used.",
+                "Reserved for imports whose names aren't referenced in the module or listed in \
+                 `__all__`. This code isn't reported yet.",
+            ),
+            Self::UnusedVariable => CodeDocs::new(
+                "A local variable that is assigned but never used.",
+                "Reserved for local variables of functions that are never read. This code isn't \
+                 reported yet.",
+            ),
+            Self::UndefinedName => CodeDocs::new(
+                "A name that isn't defined.",
+                "Reserved for names that aren't bound in any enclosing scope or as a builtin. \
+                 This code isn't reported yet.",
+            ),
+            Self::TypeCheckingOnlyImport => CodeDocs::new(
+                "A name imported under `if TYPE_CHECKING:` is used at runtime.",
+                "Names imported under `TYPE_CHECKING` only exist for the type checker. They may \
+                 be used in annotations and string annotations, but using them in code that \
+                 runs raises a `NameError`.",
+            )
+            .example(
+                "from typing import TYPE_CHECKING\n\nif TYPE_CHECKING:\n    from decimal import Decimal\n\nzero = Decimal(0)",
+            ),
+            Self::InvalidStubContent => CodeDocs::new(
+                "A stub file contains runtime code.",
+                "Stub (`.pyi`) files only declare the interface of a module: function bodies \
+                 should be `...`, and statements that do something at runtime don't belong in \
+                 them.",
+            )
+            .example("def greet(name: str) -> str:\n    return f\"Hello {name}\""),
+            Self::UntypedImport => CodeDocs::new(
+                "An imported package doesn't ship type information.",
+                "Only reported with `disallow-untyped-imports`. Everything imported from a \
+                 package without a `py.typed` marker or stubs is treated as `Any`.",
+            ),
+            Self::ImportNotFound => CodeDocs::new(
+                "An imported module can't be found.",
+                "Modules are searched in the source roots, the virtual environment and the \
+                 typeshed stubs of the standard library. Imports guarded by `try`/`except \
+                 ImportError` and the modules listed in `extra-modules` aren't reported.",
+            )
+            .example("import does_not_exist"),
+            Self::CallArgument => CodeDocs::new(
+                "A call doesn't match the signature of the function it calls.",
+                "Calls to functions imported from other modules of the project are checked for \
+                 missing, extra and unknown arguments.",
+            )
+            .example("from project.utils import greet  # def greet(name: str) -> str\n\ngreet()"),
+            Self::UndefinedExport => CodeDocs::new(
+                "A name listed in `__all__` isn't defined in the module.",
+                "`from module import *` raises an `AttributeError` for such a name.",
+            )
+            .example("__all__ = [\"missing\"]"),
+            Self::InvalidAll => CodeDocs::new(
+                "`__all__` isn't a literal list or tuple of strings.",
+                "The exported names can only be known statically if `__all__` is a literal, \
+                 optionally extended with `+=`, `.extend()` and `.append()`.",
+            )
+            .example("__all__ = [name for name in dir() if not name.startswith(\"_\")]"),
+            Self::PrivateExport => CodeDocs::new(
+                "A private name is listed in `__all__`.",
+                "Names starting with an underscore are private to the module, so exporting them \
+                 is usually a mistake.",
+            )
+            .example("__all__ = [\"_helper\"]"),
+            Self::UndeclaredDependency => CodeDocs::new(
+                "An installed third-party package is imported, but isn't a declared dependency.",
+                "Imports are mapped to the distributions that provide them using the metadata in \
+                 `site-packages`, so `import yaml` needs a dependency on `PyYAML`. Optional \
+                 dependencies count as declared. Nothing is reported if the project has no \
+                 `[project]` table or its dependencies are dynamic. This code isn't enabled by \
+                 any checking mode.",
+            )
+            .example("import yaml"),
+            Self::UnusedDependency => CodeDocs::new(
+                "A dependency of the project isn't imported by any checked file.",
+                "Reported on the entry of `project.dependencies` in `pyproject.toml`. Only the \
+                 files of the same run are considered, so check the whole project. Dependencies \
+                 that are only used as command-line tools or plugins have to be ignored. This \
+                 code isn't enabled by any checking mode.",
+            ),
+            Self::IncompatibleCallable => CodeDocs::new(
+                "A lambda doesn't match the `Callable` type it's used as.",
+                "Lambdas assigned to, or passed as, a `Callable[[...], ...]` take their \
+                 parameter types from the annotation. The lambda is reported if it has the \
+                 wrong number of parameters or if its body has the wrong type.",
+            )
+            .example("from typing import Callable\n\nf: Callable[[int], str] = lambda x: x + 1"),
+            Self::DeadCode => CodeDocs::new(
+                "A top-level function or class is never used in the project.",
+                "Definitions that aren't referenced by any module of the project are reported, \
+                 except for decorated definitions, dunder names, definitions in stubs, and the \
+                 `entry-points` and `dead-code-keep` of the configuration.",
+            ),
+            Self::DuplicateKey => CodeDocs::new(
+                "A literal key is repeated in a dict or set display.",
+                "The later entry silently replaces the earlier one. Keys are compared the way \
+                 Python compares them, so `1`, `1.0` and `True` are the same key.",
+            )
+            .example("options = {\"verbose\": True, \"verbose\": False}"),
+            Self::ComparisonOverlap => CodeDocs::new(
+                "A comparison whose result is always the same.",
+                "Members of an enum that doesn't mix in a builtin type never compare equal to \
+                 literals, so such comparisons are always `False` (or `True`, for `!=`).",
+            )
+            .example(
+                "from enum import Enum\n\nclass Color(Enum):\n    RED = \"red\"\n\ndef is_red(color: Color) -> bool:\n    return color == \"red\"",
+            ),
+            Self::MissingParameterAnnotation => CodeDocs::new(
+                "A function parameter doesn't have a type annotation.",
+                "The first parameter of a method (`self` or `cls`) doesn't need an annotation, \
+                 since its type is implied.",
+            )
+            .example("def greet(name) -> str: ..."),
+            Self::MissingReturnAnnotation => CodeDocs::new(
+                "A function doesn't have a return type annotation.",
+                "`__init__` methods don't need a return annotation, since they always return \
+                 `None`.",
+            )
+            .example("def greet(name: str): ..."),
+            Self::MissingVariableAnnotation => CodeDocs::new(
+                "A public module-level variable doesn't have a type annotation.",
+                "Only variables at the top level of a module whose names don't start with an \
+                 underscore are reported, since they're part of the module's interface.",
+            )
+            .example("timeout = compute_timeout()"),
+            Self::ConfusableCharacter => CodeDocs::new(
+                "A letter that looks like an ASCII letter, but is a different character.",
+                "Reported in names, and in the words of string literals that also contain ASCII \
+                 letters, so that text in other scripts isn't reported. Such letters, like the \
+                 Cyrillic `а` in `pаypal`, make two names or strings look the same when they \
+                 aren't. The fix replaces the letter with the ASCII letter it looks like.",
+            )
+            .example("p\u{0430}ypal_url = \"https://example.com\"")
+            .fix(FixAvailability::Always),
+            Self::BidiControlCharacter => CodeDocs::new(
+                "A bidirectional control character in a string or comment.",
+                "These characters change the order in which the text around them is displayed, \
+                 so that code can look different from what runs, like a comment that seems to \
+                 end a line before a statement that actually is part of it. They're reported \
+                 wherever they occur, since they're rarely needed in source code; use an escape \
+                 like `\\u202e` in strings that need one.",
+            )
+            .example("access = \"user\u{202e} \u{2066}# admin\"  # \u{2069}"),
+            Self::IOError => CodeDocs::new(
+                "A file can't be read.",
+                "The file doesn't exist, isn't readable, or isn't valid UTF-8.",
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::{parse, Mode};
+    use strum::IntoEnumIterator;
+
+    use crate::registry::ErrorCode;
+
+    #[test]
+    fn examples_parse() {
+        for error_code in ErrorCode::iter() {
+            let docs = error_code.docs();
+            assert!(docs.summary.ends_with('.'), "{error_code}");
+            // Syntax errors that the parser reports wouldn't make useful examples.
+            if let Some(example) = docs.example {
+                assert!(
+                    parse(example, Mode::Module, "<example>").is_ok(),
+                    "The example of `{error_code}` doesn't parse."
+                );
+            }
+        }
+    }
+}
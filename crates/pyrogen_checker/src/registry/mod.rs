@@ -1,9 +1,16 @@
+use std::borrow::Cow;
+
 use rustpython_ast::{text_size::TextRange, Ranged, TextSize};
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumIter, EnumString, IntoStaticStr};
 
+use crate::fix::Fix;
+use crate::locale::{Locale, MessageTemplate};
+
+pub use docs::{CodeDocs, FixAvailability};
 pub use rule_set::{ErrorCodeSet, ErrorCodeSetIterator};
 
+mod docs;
 mod rule_set;
 
 #[repr(u16)]
@@ -24,6 +31,15 @@ mod rule_set;
 pub enum ErrorCode {
     InvalidPyprojectToml,
 
+    #[strum(serialize = "pyproject-requires-python")]
+    PyprojectRequiresPython,
+
+    #[strum(serialize = "pyproject-classifier")]
+    PyprojectClassifier,
+
+    #[strum(serialize = "pyproject-dependency")]
+    PyprojectDependency,
+
     #[strum(serialize = "override")]
     Override,
 
@@ -33,6 +49,12 @@ pub enum ErrorCode {
     #[strum(serialize = "unused-type-ignore")]
     UnusedTypeIgnore,
 
+    #[strum(serialize = "expired-suppression")]
+    ExpiredSuppression,
+
+    #[strum(serialize = "too-many-diagnostics")]
+    TooManyDiagnostics,
+
     #[strum(serialize = "syntax-error")]
     SyntaxError,
 
@@ -48,10 +70,86 @@ pub enum ErrorCode {
     #[strum(serialize = "undefined-name")]
     UndefinedName,
 
+    #[strum(serialize = "type-checking-only-import")]
+    TypeCheckingOnlyImport,
+
+    #[strum(serialize = "invalid-stub-content")]
+    InvalidStubContent,
+
+    #[strum(serialize = "untyped-import")]
+    UntypedImport,
+
+    #[strum(serialize = "import-not-found")]
+    ImportNotFound,
+
+    #[strum(serialize = "call-arg")]
+    CallArgument,
+
+    #[strum(serialize = "undefined-export")]
+    UndefinedExport,
+
+    #[strum(serialize = "invalid-all")]
+    InvalidAll,
+
+    #[strum(serialize = "private-export")]
+    PrivateExport,
+
+    #[strum(serialize = "undeclared-dependency")]
+    UndeclaredDependency,
+
+    #[strum(serialize = "unused-dependency")]
+    UnusedDependency,
+
+    #[strum(serialize = "incompatible-callable")]
+    IncompatibleCallable,
+
+    #[strum(serialize = "dead-code")]
+    DeadCode,
+
+    #[strum(serialize = "duplicate-key")]
+    DuplicateKey,
+
+    #[strum(serialize = "comparison-overlap")]
+    ComparisonOverlap,
+
+    #[strum(serialize = "missing-parameter-annotation")]
+    MissingParameterAnnotation,
+
+    #[strum(serialize = "missing-return-annotation")]
+    MissingReturnAnnotation,
+
+    #[strum(serialize = "missing-variable-annotation")]
+    MissingVariableAnnotation,
+
+    #[strum(serialize = "confusable-character")]
+    ConfusableCharacter,
+
+    #[strum(serialize = "bidi-control-character")]
+    BidiControlCharacter,
+
     #[strum(serialize = "io-error")]
     IOError,
 }
 
+/// A group of related error codes, which can be selected as a whole (like `--error imports`).
+#[derive(Eq, Hash, Debug, Clone, Copy, PartialEq, Display, EnumString, EnumIter, IntoStaticStr)]
+#[strum(serialize_all = "lowercase")]
+pub enum Category {
+    /// Problems that prevent checking a file, like syntax errors, unreadable files and invalid
+    /// configuration.
+    Fatal,
+    /// Type errors.
+    Typecheck,
+    /// Imports that can't be resolved or aren't used, and the exports in `__all__`.
+    Imports,
+    /// Suppression comments that aren't needed, or no longer apply.
+    Suppressions,
+    /// Code that works, but could be clearer or better annotated.
+    Style,
+    /// Characters that make code read differently than it runs.
+    Security,
+}
+
 pub trait AsErrorCode {
     fn error_code(&self) -> ErrorCode;
 }
@@ -64,6 +162,44 @@ impl ErrorCode {
     pub fn to_str(&self) -> &'static str {
         self.into()
     }
+
+    pub const fn category(self) -> Category {
+        match self {
+            Self::InvalidPyprojectToml
+            | Self::PyprojectRequiresPython
+            | Self::PyprojectClassifier
+            | Self::PyprojectDependency
+            | Self::SyntaxError
+            | Self::IOError => Category::Fatal,
+            Self::Override
+            | Self::Unreachable
+            | Self::GeneralTypeError
+            | Self::UndefinedName
+            | Self::CallArgument
+            | Self::IncompatibleCallable
+            | Self::DuplicateKey
+            | Self::ComparisonOverlap => Category::Typecheck,
+            Self::UnusedImport
+            | Self::TypeCheckingOnlyImport
+            | Self::UntypedImport
+            | Self::ImportNotFound
+            | Self::UndefinedExport
+            | Self::InvalidAll
+            | Self::PrivateExport
+            | Self::UndeclaredDependency
+            | Self::UnusedDependency => Category::Imports,
+            Self::UnusedTypeIgnore | Self::ExpiredSuppression | Self::TooManyDiagnostics => {
+                Category::Suppressions
+            }
+            Self::UnusedVariable
+            | Self::InvalidStubContent
+            | Self::DeadCode
+            | Self::MissingParameterAnnotation
+            | Self::MissingReturnAnnotation
+            | Self::MissingVariableAnnotation => Category::Style,
+            Self::ConfusableCharacter | Self::BidiControlCharacter => Category::Security,
+        }
+    }
 }
 
 impl AsErrorCode for DiagnosticKind {
@@ -97,14 +233,54 @@ impl ErrorCode {
     /// physical lines).
     pub const fn lint_source(&self) -> CheckerSource {
         match self {
-            ErrorCode::InvalidPyprojectToml => CheckerSource::PyprojectToml,
-            ErrorCode::UnusedTypeIgnore => CheckerSource::Noqa,
-            ErrorCode::Override => CheckerSource::Tokens,
+            ErrorCode::InvalidPyprojectToml
+            | ErrorCode::PyprojectRequiresPython
+            | ErrorCode::PyprojectClassifier
+            | ErrorCode::PyprojectDependency => CheckerSource::PyprojectToml,
+            ErrorCode::UnusedTypeIgnore | ErrorCode::ExpiredSuppression => CheckerSource::Noqa,
             ErrorCode::Unreachable => CheckerSource::LogicalLines,
+            ErrorCode::ConfusableCharacter | ErrorCode::BidiControlCharacter => {
+                CheckerSource::Tokens
+            }
             _ => CheckerSource::Ast,
         }
     }
 
+    /// Whether the code is in preview: newly added codes that are only enabled with
+    /// `preview = true`, until they are stable enough not to change the results of existing
+    /// configurations.
+    ///
+    /// The codes that report on pyrogen itself rather than on the checked code, like
+    /// `too-many-diagnostics` and `expired-suppression`, are stable from the start.
+    pub const fn is_preview(self) -> bool {
+        matches!(
+            self,
+            ErrorCode::PyprojectRequiresPython
+                | ErrorCode::PyprojectClassifier
+                | ErrorCode::PyprojectDependency
+                | ErrorCode::Override
+                | ErrorCode::TypeCheckingOnlyImport
+                | ErrorCode::InvalidStubContent
+                | ErrorCode::UntypedImport
+                | ErrorCode::ImportNotFound
+                | ErrorCode::CallArgument
+                | ErrorCode::UndefinedExport
+                | ErrorCode::InvalidAll
+                | ErrorCode::PrivateExport
+                | ErrorCode::UndeclaredDependency
+                | ErrorCode::UnusedDependency
+                | ErrorCode::IncompatibleCallable
+                | ErrorCode::DeadCode
+                | ErrorCode::DuplicateKey
+                | ErrorCode::ComparisonOverlap
+                | ErrorCode::MissingParameterAnnotation
+                | ErrorCode::MissingReturnAnnotation
+                | ErrorCode::MissingVariableAnnotation
+                | ErrorCode::ConfusableCharacter
+                | ErrorCode::BidiControlCharacter
+        )
+    }
+
     // /// Return the URL for the rule documentation, if it exists.
     // pub fn url(&self) -> Option<String> {
     //     self.explanation()
@@ -117,14 +293,51 @@ impl ErrorCode {
 pub struct DiagnosticKind {
     /// The error code that this diagnostic is associated with.
     pub error_code: ErrorCode,
-    /// The message body to display to the user, to explain the diagnostic.
+    /// The message body to display to the user, to explain the diagnostic, in English.
     pub body: String,
+    /// The message of the catalog that `body` was rendered from, to translate it. Messages that
+    /// don't come from the catalog, like I/O errors, aren't translated.
+    pub template: Option<MessageTemplate>,
+}
+
+impl DiagnosticKind {
+    pub fn new(error_code: ErrorCode, body: impl Into<String>) -> Self {
+        Self {
+            error_code,
+            body: body.into(),
+            template: None,
+        }
+    }
+
+    pub fn from_template(error_code: ErrorCode, template: MessageTemplate) -> Self {
+        Self {
+            error_code,
+            body: Locale::En.render(&template),
+            template: Some(template),
+        }
+    }
+
+    /// The message body in `locale`.
+    pub fn localized_body(&self, locale: Locale) -> Cow<'_, str> {
+        match &self.template {
+            Some(template) if locale != Locale::En => Cow::Owned(locale.render(template)),
+            _ => Cow::Borrowed(&self.body),
+        }
+    }
+
+    /// Translate the message body into `locale`.
+    pub fn localize(&mut self, locale: Locale) {
+        if let Cow::Owned(body) = self.localized_body(locale) {
+            self.body = body;
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Diagnostic {
     pub kind: DiagnosticKind,
     pub range: TextRange,
+    pub fix: Option<Fix>,
     pub parent: Option<TextSize>,
 }
 
@@ -133,10 +346,25 @@ impl Diagnostic {
         Self {
             kind: kind.into(),
             range,
+            fix: None,
             parent: None,
         }
     }
 
+    /// Consumes `self` and returns a new `Diagnostic` with the given `fix`.
+    #[inline]
+    #[must_use]
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.set_fix(fix);
+        self
+    }
+
+    /// Set the [`Fix`] used to resolve the diagnostic.
+    #[inline]
+    pub fn set_fix(&mut self, fix: Fix) {
+        self.fix = Some(fix);
+    }
+
     /// Set the location of the diagnostic's parent node.
     #[inline]
     pub fn set_parent(&mut self, parent: TextSize) {
@@ -215,7 +443,28 @@ mod tests {
 
     use strum::IntoEnumIterator;
 
-    use super::ErrorCode;
+    use crate::locale::{Locale, MessageTemplate};
+
+    use super::{Category, DiagnosticKind, ErrorCode};
+
+    #[test]
+    fn localized_bodies() {
+        let mut kind = DiagnosticKind::from_template(
+            ErrorCode::ImportNotFound,
+            MessageTemplate::new("import-not-found").arg("name", "yaml"),
+        );
+        assert_eq!(kind.body, "Cannot find module `yaml`");
+        assert_eq!(
+            kind.localized_body(Locale::De),
+            "Modul `yaml` nicht gefunden"
+        );
+        kind.localize(Locale::De);
+        assert_eq!(kind.body, "Modul `yaml` nicht gefunden");
+
+        // Messages that don't come from the catalog stay as they are.
+        let kind = DiagnosticKind::new(ErrorCode::IOError, "Permission denied");
+        assert_eq!(kind.localized_body(Locale::De), "Permission denied");
+    }
 
     #[test]
     fn check_code_serialization() {
@@ -237,6 +486,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn categories() {
+        for category in Category::iter() {
+            let name = category.to_string();
+            assert_eq!(Category::from_str(&name).ok(), Some(category));
+            assert!(
+                ErrorCode::from_str(&name).is_err(),
+                "The category `{name}` has the same name as an error code."
+            );
+            assert!(
+                ErrorCode::iter().any(|error_code| error_code.category() == category),
+                "The category `{name}` is empty."
+            );
+        }
+    }
+
     #[test]
     fn rule_size() {
         assert_eq!(2, size_of::<ErrorCode>());
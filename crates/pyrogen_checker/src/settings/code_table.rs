@@ -6,17 +6,29 @@ use serde::{Deserialize, Serialize};
 use crate::registry::{ErrorCode, ErrorCodeSet, ErrorCodeSetIterator};
 
 /// A table to keep track of which error codes are enabled.
-#[derive(Debug, CacheKey, Default)]
+#[derive(Debug, Clone, CacheKey, Default)]
 pub struct ErrorCodeTable {
     /// Maps rule codes to a boolean indicating if the rule should be autofixed.
     enabled: ErrorCodeSet,
     warning: ErrorCodeSet,
+    info: ErrorCodeSet,
+    hint: ErrorCodeSet,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+/// The severity of a message, from most to least severe. Only errors make a check fail.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum MessageKind {
     Error,
     Warning,
+    Info,
+    Hint,
+}
+
+impl MessageKind {
+    /// All kinds, from most to least severe.
+    pub const ALL: [MessageKind; 4] = [Self::Error, Self::Warning, Self::Info, Self::Hint];
 }
 
 impl Display for MessageKind {
@@ -24,6 +36,8 @@ impl Display for MessageKind {
         match self {
             MessageKind::Error => write!(f, "error"),
             MessageKind::Warning => write!(f, "warning"),
+            MessageKind::Info => write!(f, "info"),
+            MessageKind::Hint => write!(f, "hint"),
         }
     }
 }
@@ -34,6 +48,8 @@ impl ErrorCodeTable {
         Self {
             enabled: ErrorCodeSet::empty(),
             warning: ErrorCodeSet::empty(),
+            info: ErrorCodeSet::empty(),
+            hint: ErrorCodeSet::empty(),
         }
     }
 
@@ -46,16 +62,26 @@ impl ErrorCodeTable {
     #[inline]
     pub const fn entry(&self, rule: ErrorCode) -> Option<MessageKind> {
         if self.enabled(rule) {
-            if self.is_warning(rule) {
-                Some(MessageKind::Warning)
-            } else {
-                Some(MessageKind::Error)
-            }
+            Some(self.kind(rule))
         } else {
             None
         }
     }
 
+    /// Returns the kind of message that violations of the given rule are reported as.
+    #[inline]
+    pub const fn kind(&self, rule: ErrorCode) -> MessageKind {
+        if self.warning.contains(rule) {
+            MessageKind::Warning
+        } else if self.info.contains(rule) {
+            MessageKind::Info
+        } else if self.hint.contains(rule) {
+            MessageKind::Hint
+        } else {
+            MessageKind::Error
+        }
+    }
+
     /// Returns whether any of the given rules should be checked.
     #[inline]
     pub const fn any_enabled(&self, rules: &[ErrorCode]) -> bool {
@@ -85,18 +111,37 @@ impl ErrorCodeTable {
         self.enabled.insert(rule);
     }
 
-    /// Enables the given rule.
+    /// Enables the given rule as a warning.
     #[inline]
     pub fn enable_warning(&mut self, rule: ErrorCode) {
+        self.disable(rule);
         self.enabled.insert(rule);
         self.warning.insert(rule);
     }
 
+    /// Enables the given rule as an informational message.
+    #[inline]
+    pub fn enable_info(&mut self, rule: ErrorCode) {
+        self.disable(rule);
+        self.enabled.insert(rule);
+        self.info.insert(rule);
+    }
+
+    /// Enables the given rule as a hint.
+    #[inline]
+    pub fn enable_hint(&mut self, rule: ErrorCode) {
+        self.disable(rule);
+        self.enabled.insert(rule);
+        self.hint.insert(rule);
+    }
+
     /// Disables the given rule.
     #[inline]
     pub fn disable(&mut self, rule: ErrorCode) {
         self.enabled.remove(rule);
         self.warning.remove(rule);
+        self.info.remove(rule);
+        self.hint.remove(rule);
     }
 }
 
@@ -106,6 +151,8 @@ impl FromIterator<ErrorCode> for ErrorCodeTable {
         Self {
             enabled: rules,
             warning: ErrorCodeSet::empty(),
+            info: ErrorCodeSet::empty(),
+            hint: ErrorCodeSet::empty(),
         }
     }
 }
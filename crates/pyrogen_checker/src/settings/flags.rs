@@ -12,3 +12,13 @@ pub enum Cache {
     Enabled,
     Disabled,
 }
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum FixMode {
+    /// Report the diagnostics with their fixes, without applying them.
+    Generate,
+    /// Apply the fixes and write the fixed source back.
+    Apply,
+    /// Print a diff of the fixes instead of writing the fixed source.
+    Diff,
+}
@@ -1,58 +1,221 @@
 use std::path::{Path, PathBuf};
 
 use crate::{
+    fs,
     registry::{ErrorCode, ErrorCodeSet},
-    settings::types::PythonVersion,
+    settings::types::{CheckingMode, PreviewMode, PythonPlatform, PythonVersion, RuleTimeout},
     ErrorCodeSelector,
 };
 use anyhow::Result;
 use globset::{Glob, GlobMatcher};
+use once_cell::sync::OnceCell;
 use path_absolutize::path_dedot;
 use pyrogen_macros::CacheKey;
+use pyrogen_module_resolver::{ModuleResolver, ModuleResolverSettings};
 
-use self::{code_table::ErrorCodeTable, types::PerFileIgnore};
+use self::{
+    code_table::{ErrorCodeTable, MessageKind},
+    types::PerFileIgnore,
+};
 
 pub mod code_table;
 pub mod flags;
 pub mod types;
 
-#[derive(Debug, CacheKey)]
+#[derive(Debug, Clone, CacheKey)]
 pub struct CheckerSettings {
     pub project_root: PathBuf,
     pub table: ErrorCodeTable,
     pub per_file_ignores: Vec<(GlobMatcher, GlobMatcher, ErrorCodeSet)>,
 
     pub target_version: PythonVersion,
+    pub platform: PythonPlatform,
     pub namespace_packages: Vec<PathBuf>,
     pub src: Vec<PathBuf>,
+    /// The `site-packages` directories that third-party imports are resolved in.
+    pub site_packages: Vec<PathBuf>,
+    pub disallow_untyped_imports: bool,
+    /// Modules that exist at runtime although imports of them can't be resolved.
+    pub extra_modules: Vec<String>,
+    pub rule_timeout: Option<RuleTimeout>,
+    /// Whether the codes in preview can be enabled.
+    pub preview: PreviewMode,
+    /// The number of diagnostics of a file beyond which they're truncated, or `0` for no limit.
+    pub max_diagnostics_per_file: usize,
+    /// Top-level functions and classes that are never reported as dead code.
+    pub dead_code_keep: Vec<GlobMatcher>,
+    /// The qualified names of the objects that the entry points of the project refer to.
+    pub entry_points: Vec<String>,
+    /// The normalized names of the distributions that the project declares as dependencies, or
+    /// `None` if they aren't known.
+    pub declared_dependencies: Option<Vec<String>>,
+    /// The resolver of the imports, built on first use by [`CheckerSettings::module_resolver`]
+    /// and shared by all the files that these settings apply to.
+    #[cache_key(ignore)]
+    pub module_resolver: OnceCell<ModuleResolver>,
 }
 
+/// The default of the `max-diagnostics-per-file` setting.
+pub const DEFAULT_MAX_DIAGNOSTICS_PER_FILE: usize = 1000;
+
 pub const DEFAULT_ERRORS: &[ErrorCodeSelector] = &[
     ErrorCodeSelector::ErrorCode(ErrorCode::SyntaxError),
     ErrorCodeSelector::ErrorCode(ErrorCode::GeneralTypeError),
     ErrorCodeSelector::ErrorCode(ErrorCode::InvalidPyprojectToml),
+    ErrorCodeSelector::ErrorCode(ErrorCode::PyprojectRequiresPython),
+    ErrorCodeSelector::ErrorCode(ErrorCode::PyprojectClassifier),
+    ErrorCodeSelector::ErrorCode(ErrorCode::PyprojectDependency),
+    ErrorCodeSelector::ErrorCode(ErrorCode::TypeCheckingOnlyImport),
+    ErrorCodeSelector::ErrorCode(ErrorCode::InvalidStubContent),
+    // Only reported with `disallow-untyped-imports`.
+    ErrorCodeSelector::ErrorCode(ErrorCode::UntypedImport),
+    ErrorCodeSelector::ErrorCode(ErrorCode::CallArgument),
+    ErrorCodeSelector::ErrorCode(ErrorCode::UndefinedExport),
+    ErrorCodeSelector::ErrorCode(ErrorCode::InvalidAll),
+    ErrorCodeSelector::ErrorCode(ErrorCode::IncompatibleCallable),
+    ErrorCodeSelector::ErrorCode(ErrorCode::Override),
 ];
 pub const DEFAULT_WARNINGS: &[ErrorCodeSelector] = &[
     ErrorCodeSelector::ErrorCode(ErrorCode::UnusedVariable),
     ErrorCodeSelector::ErrorCode(ErrorCode::UnusedImport),
+    ErrorCodeSelector::ErrorCode(ErrorCode::DuplicateKey),
+    ErrorCodeSelector::ErrorCode(ErrorCode::ComparisonOverlap),
+    ErrorCodeSelector::ErrorCode(ErrorCode::ExpiredSuppression),
+    ErrorCodeSelector::ErrorCode(ErrorCode::TooManyDiagnostics),
+    ErrorCodeSelector::ErrorCode(ErrorCode::ConfusableCharacter),
+    ErrorCodeSelector::ErrorCode(ErrorCode::BidiControlCharacter),
+];
+
+pub const BASIC_ERRORS: &[ErrorCodeSelector] = &[
+    ErrorCodeSelector::ErrorCode(ErrorCode::SyntaxError),
+    ErrorCodeSelector::ErrorCode(ErrorCode::GeneralTypeError),
+    ErrorCodeSelector::ErrorCode(ErrorCode::InvalidPyprojectToml),
+    ErrorCodeSelector::ErrorCode(ErrorCode::PyprojectRequiresPython),
+    ErrorCodeSelector::ErrorCode(ErrorCode::PyprojectClassifier),
+    ErrorCodeSelector::ErrorCode(ErrorCode::PyprojectDependency),
+    ErrorCodeSelector::ErrorCode(ErrorCode::InvalidStubContent),
+    ErrorCodeSelector::ErrorCode(ErrorCode::UntypedImport),
+    ErrorCodeSelector::ErrorCode(ErrorCode::CallArgument),
+    ErrorCodeSelector::ErrorCode(ErrorCode::IncompatibleCallable),
+];
+pub const BASIC_WARNINGS: &[ErrorCodeSelector] = &[
+    ErrorCodeSelector::ErrorCode(ErrorCode::TypeCheckingOnlyImport),
+    ErrorCodeSelector::ErrorCode(ErrorCode::UndefinedExport),
+    ErrorCodeSelector::ErrorCode(ErrorCode::InvalidAll),
+    ErrorCodeSelector::ErrorCode(ErrorCode::DuplicateKey),
+    ErrorCodeSelector::ErrorCode(ErrorCode::ComparisonOverlap),
+    ErrorCodeSelector::ErrorCode(ErrorCode::ExpiredSuppression),
+    ErrorCodeSelector::ErrorCode(ErrorCode::TooManyDiagnostics),
+    ErrorCodeSelector::ErrorCode(ErrorCode::ConfusableCharacter),
+    ErrorCodeSelector::ErrorCode(ErrorCode::BidiControlCharacter),
 ];
 
+pub const STRICT_ERRORS: &[ErrorCodeSelector] = &[
+    ErrorCodeSelector::ErrorCode(ErrorCode::SyntaxError),
+    ErrorCodeSelector::ErrorCode(ErrorCode::GeneralTypeError),
+    ErrorCodeSelector::ErrorCode(ErrorCode::InvalidPyprojectToml),
+    ErrorCodeSelector::ErrorCode(ErrorCode::PyprojectRequiresPython),
+    ErrorCodeSelector::ErrorCode(ErrorCode::PyprojectClassifier),
+    ErrorCodeSelector::ErrorCode(ErrorCode::PyprojectDependency),
+    ErrorCodeSelector::ErrorCode(ErrorCode::TypeCheckingOnlyImport),
+    ErrorCodeSelector::ErrorCode(ErrorCode::InvalidStubContent),
+    ErrorCodeSelector::ErrorCode(ErrorCode::UntypedImport),
+    ErrorCodeSelector::ErrorCode(ErrorCode::CallArgument),
+    ErrorCodeSelector::ErrorCode(ErrorCode::UndefinedExport),
+    ErrorCodeSelector::ErrorCode(ErrorCode::InvalidAll),
+    ErrorCodeSelector::ErrorCode(ErrorCode::PrivateExport),
+    ErrorCodeSelector::ErrorCode(ErrorCode::IncompatibleCallable),
+    ErrorCodeSelector::ErrorCode(ErrorCode::Override),
+    ErrorCodeSelector::ErrorCode(ErrorCode::UnusedVariable),
+    ErrorCodeSelector::ErrorCode(ErrorCode::UnusedImport),
+    ErrorCodeSelector::ErrorCode(ErrorCode::UnusedTypeIgnore),
+    ErrorCodeSelector::ErrorCode(ErrorCode::DuplicateKey),
+    ErrorCodeSelector::ErrorCode(ErrorCode::ComparisonOverlap),
+    ErrorCodeSelector::ErrorCode(ErrorCode::ExpiredSuppression),
+    ErrorCodeSelector::ErrorCode(ErrorCode::TooManyDiagnostics),
+    ErrorCodeSelector::ErrorCode(ErrorCode::ConfusableCharacter),
+    ErrorCodeSelector::ErrorCode(ErrorCode::BidiControlCharacter),
+];
+pub const STRICT_WARNINGS: &[ErrorCodeSelector] = &[];
+
+impl CheckingMode {
+    /// The codes that are reported as errors and as warnings before any rule selections are
+    /// applied.
+    pub const fn default_codes(
+        self,
+    ) -> (&'static [ErrorCodeSelector], &'static [ErrorCodeSelector]) {
+        match self {
+            Self::Basic => (BASIC_ERRORS, BASIC_WARNINGS),
+            Self::Standard => (DEFAULT_ERRORS, DEFAULT_WARNINGS),
+            Self::Strict => (STRICT_ERRORS, STRICT_WARNINGS),
+        }
+    }
+
+    /// The kind of message that `code` is reported as before any rule selections are applied,
+    /// or `None` if it isn't reported.
+    pub fn default_kind(self, code: ErrorCode, preview: PreviewMode) -> Option<MessageKind> {
+        let (errors, warnings) = self.default_codes();
+        let selects = |selectors: &[ErrorCodeSelector]| {
+            selectors
+                .iter()
+                .any(|selector| selector.rules(preview).any(|selected| selected == code))
+        };
+        if selects(warnings) {
+            Some(MessageKind::Warning)
+        } else if selects(errors) {
+            Some(MessageKind::Error)
+        } else {
+            None
+        }
+    }
+}
+
 impl CheckerSettings {
     pub fn new(project_root: &Path) -> Self {
         Self {
             target_version: PythonVersion::default(),
+            platform: PythonPlatform::default(),
             project_root: project_root.to_path_buf(),
             table: ErrorCodeTable::from_iter(vec![ErrorCode::SyntaxError]),
             namespace_packages: vec![],
             per_file_ignores: vec![],
 
             src: vec![path_dedot::CWD.clone()],
+            site_packages: vec![],
+            disallow_untyped_imports: false,
+            extra_modules: vec![],
+            rule_timeout: None,
+            preview: PreviewMode::Disabled,
+            max_diagnostics_per_file: DEFAULT_MAX_DIAGNOSTICS_PER_FILE,
+            dead_code_keep: vec![],
+            entry_points: vec![],
+            declared_dependencies: None,
+            module_resolver: OnceCell::new(),
+        }
+    }
+
+    /// The settings for resolving imports of the checked files to modules on disk.
+    pub fn module_resolver_settings(&self) -> ModuleResolverSettings {
+        ModuleResolverSettings {
+            src: self.src.clone(),
+            namespace_packages: self.namespace_packages.clone(),
+            site_packages: self.site_packages.clone(),
+            vendored_typeshed: true,
+            target_version: Some(self.target_version.as_tuple()),
+            ..ModuleResolverSettings::default()
         }
     }
 
+    /// The resolver of the imports of the checked files, which is only built once.
+    pub fn module_resolver(&self) -> &ModuleResolver {
+        self.module_resolver
+            .get_or_init(|| ModuleResolver::new(&self.module_resolver_settings()))
+    }
+
     #[must_use]
     pub fn with_target_version(mut self, target_version: PythonVersion) -> Self {
         self.target_version = target_version;
+        self.module_resolver = OnceCell::new();
         self
     }
 
@@ -88,10 +251,11 @@ pub fn resolve_per_file_ignores(
         .map(|per_file_ignore| {
             // Construct absolute path matcher.
             let absolute =
-                Glob::new(&per_file_ignore.absolute.to_string_lossy())?.compile_matcher();
+                Glob::new(&fs::glob_pattern(&per_file_ignore.absolute))?.compile_matcher();
 
             // Construct basename matcher.
-            let basename = Glob::new(&per_file_ignore.basename)?.compile_matcher();
+            let basename =
+                Glob::new(&fs::glob_pattern(&per_file_ignore.basename))?.compile_matcher();
 
             Ok((absolute, basename, per_file_ignore.rules))
         })
@@ -8,6 +8,7 @@ use std::{
 use anyhow::Result;
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use pep440_rs::{Version as Pep440Version, VersionSpecifiers};
+use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
@@ -15,6 +16,7 @@ use strum_macros::EnumIter;
 use pyrogen_cache::{CacheKey, CacheKeyHasher};
 use pyrogen_macros::CacheKey;
 
+use crate::settings::code_table::MessageKind;
 use crate::{fs, registry::ErrorCodeSet, ErrorCodeSelector};
 
 #[derive(
@@ -85,6 +87,120 @@ impl PythonVersion {
     }
 }
 
+/// A predefined set of the codes reported as errors and as warnings, which the rule selections
+/// (`error`, `warning`, `ignore`, ...) then adjust.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize, CacheKey, EnumIter,
+)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum CheckingMode {
+    /// Only report definite type errors.
+    Basic,
+    #[default]
+    Standard,
+    /// Report more problems, all of them as errors.
+    Strict,
+}
+
+/// The platform that `sys.platform` checks are evaluated against.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize, CacheKey, EnumIter,
+)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum PythonPlatform {
+    /// Don't assume any particular platform.
+    #[default]
+    All,
+    Linux,
+    Darwin,
+    Win32,
+}
+
+impl PythonPlatform {
+    /// The value of `sys.platform` on this platform, or `None` if it is unknown.
+    pub const fn sys_platform(&self) -> Option<&'static str> {
+        match self {
+            Self::All => None,
+            Self::Linux => Some("linux"),
+            Self::Darwin => Some("darwin"),
+            Self::Win32 => Some("win32"),
+        }
+    }
+}
+
+/// What to do when a rule takes longer than the `rule-timeout` on a single file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize, CacheKey)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum RuleTimeoutAction {
+    /// Log a warning.
+    #[default]
+    Warn,
+    /// Log a warning and stop running the rule for the rest of the run.
+    Disable,
+}
+
+/// Whether the codes in preview are enabled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, CacheKey)]
+pub enum PreviewMode {
+    #[default]
+    Disabled,
+    Enabled,
+}
+
+impl PreviewMode {
+    pub const fn is_enabled(self) -> bool {
+        matches!(self, Self::Enabled)
+    }
+}
+
+impl From<bool> for PreviewMode {
+    fn from(enabled: bool) -> Self {
+        if enabled {
+            Self::Enabled
+        } else {
+            Self::Disabled
+        }
+    }
+}
+
+/// The severity that the `severity` table assigns to a code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+    /// Don't report the code at all.
+    Ignore,
+}
+
+impl Severity {
+    /// The kind of message that the code is reported as, or `None` if it's ignored.
+    pub const fn message_kind(self) -> Option<MessageKind> {
+        match self {
+            Self::Error => Some(MessageKind::Error),
+            Self::Warning => Some(MessageKind::Warning),
+            Self::Info => Some(MessageKind::Info),
+            Self::Hint => Some(MessageKind::Hint),
+            Self::Ignore => None,
+        }
+    }
+}
+
+/// The time budget of a single rule on a single file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CacheKey)]
+pub struct RuleTimeout {
+    pub milliseconds: u64,
+    pub action: RuleTimeoutAction,
+}
+
 #[derive(Debug, Clone, CacheKey, PartialEq, PartialOrd, Eq, Ord)]
 pub enum FilePattern {
     Builtin(&'static str),
@@ -92,6 +208,14 @@ pub enum FilePattern {
 }
 
 impl FilePattern {
+    /// The pattern as written in the configuration.
+    pub fn as_str(&self) -> &str {
+        match self {
+            FilePattern::Builtin(pattern) => pattern,
+            FilePattern::User(pattern, _) => pattern,
+        }
+    }
+
     pub fn add_to(self, builder: &mut GlobSetBuilder) -> Result<()> {
         match self {
             FilePattern::Builtin(pattern) => {
@@ -99,10 +223,10 @@ impl FilePattern {
             }
             FilePattern::User(pattern, absolute) => {
                 // Add the absolute path.
-                builder.add(Glob::new(&absolute.to_string_lossy())?);
+                builder.add(Glob::new(&fs::glob_pattern(&absolute))?);
 
                 // Add basename path.
-                if !pattern.contains(std::path::MAIN_SEPARATOR) {
+                if !pattern.contains(['/', std::path::MAIN_SEPARATOR]) {
                     builder.add(Glob::from_str(&pattern)?);
                 }
             }
@@ -124,6 +248,8 @@ impl FromStr for FilePattern {
 #[derive(Debug, Clone, Default)]
 pub struct FilePatternSet {
     set: GlobSet,
+    /// The patterns of the set, as written in the configuration.
+    patterns: Vec<String>,
     cache_key: u64,
 }
 
@@ -134,9 +260,11 @@ impl FilePatternSet {
     {
         let mut builder = GlobSetBuilder::new();
         let mut hasher = CacheKeyHasher::new();
+        let mut strings = Vec::new();
 
         for pattern in patterns {
             pattern.cache_key(&mut hasher);
+            strings.push(pattern.as_str().to_string());
             pattern.add_to(&mut builder)?;
         }
 
@@ -144,9 +272,14 @@ impl FilePatternSet {
 
         Ok(FilePatternSet {
             set,
+            patterns: strings,
             cache_key: hasher.finish(),
         })
     }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
 }
 
 impl Deref for FilePatternSet {
@@ -202,8 +335,13 @@ impl PerFileIgnore {
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum SerializationFormat {
     Text,
+    Grouped,
     Json,
     Github,
+    Pylint,
+    Mypy,
+    /// The format given by `output-template`.
+    Custom,
 }
 
 impl Default for SerializationFormat {
@@ -211,3 +349,22 @@ impl Default for SerializationFormat {
         Self::Text
     }
 }
+
+/// The severities that messages are presented with by the structured output formats (JSON,
+/// GitHub and mypy), e.g. to show warnings as errors in GitHub annotations. Severities without
+/// an entry are presented as they are.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SeverityMapping(FxHashMap<MessageKind, MessageKind>);
+
+impl SeverityMapping {
+    /// The severity that a message of the given severity is presented with.
+    pub fn map(&self, kind: MessageKind) -> MessageKind {
+        self.0.get(&kind).copied().unwrap_or(kind)
+    }
+}
+
+impl FromIterator<(MessageKind, MessageKind)> for SeverityMapping {
+    fn from_iter<T: IntoIterator<Item = (MessageKind, MessageKind)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
@@ -1,19 +1,257 @@
+use rustpython_parser::ast::Ranged;
+use rustpython_parser::text_size::{TextLen, TextRange, TextSize};
+
+use pyrogen_source_file::{find_newline, LineEnding};
+
+use crate::fix::{Edit, Fix};
+
+/// The source code of a file, as read and as checked.
+///
+/// The checker sees the source without a byte order mark and with `\n` line endings. Ranges in
+/// the normalized source are mapped back to the original with [`SourceKind::to_original_range`],
+/// and fixes with [`SourceKind::to_original_fix`], which also restores the file's line endings.
 #[derive(Clone, Debug, PartialEq)]
-pub struct SourceKind(
-    /// The source contains Python source code.
-    String,
-);
+pub struct SourceKind {
+    /// The source code as read.
+    original: String,
+    /// The normalized source code, or `None` if the original is already normalized.
+    normalized: Option<Normalized>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Normalized {
+    contents: String,
+    /// The line ending to use for new lines in fixes.
+    line_ending: LineEnding,
+    offsets: OffsetMap,
+}
 
 impl SourceKind {
     pub fn new(source: String) -> Self {
-        Self(source)
+        let normalized = Normalized::from_source(&source);
+        Self {
+            original: source,
+            normalized,
+        }
     }
+
     #[must_use]
     pub(crate) fn updated(&self, new_source: String) -> Self {
-        Self(new_source)
+        Self::new(new_source)
     }
 
+    /// The normalized source code, which is what gets checked.
     pub fn source_code(&self) -> &str {
-        &self.0
+        self.normalized
+            .as_ref()
+            .map_or(&self.original, |normalized| &normalized.contents)
+    }
+
+    /// The source code as read.
+    pub fn original_source_code(&self) -> &str {
+        &self.original
+    }
+
+    /// Whether the original source differs from the normalized source.
+    pub fn is_normalized(&self) -> bool {
+        self.normalized.is_some()
+    }
+
+    /// Map an offset in the normalized source to the original source.
+    pub fn to_original_offset(&self, offset: TextSize) -> TextSize {
+        self.normalized
+            .as_ref()
+            .map_or(offset, |normalized| normalized.offsets.to_original(offset))
+    }
+
+    /// Map a range in the normalized source to the original source.
+    pub fn to_original_range(&self, range: TextRange) -> TextRange {
+        TextRange::new(
+            self.to_original_offset(range.start()),
+            self.to_original_offset(range.end()),
+        )
+    }
+
+    /// Map a fix for the normalized source to the original source, writing its new lines with
+    /// the original line ending.
+    pub fn to_original_fix(&self, fix: &Fix) -> Fix {
+        let Some(normalized) = &self.normalized else {
+            return fix.clone();
+        };
+        let edits = fix.edits().iter().map(|edit| {
+            let range = self.to_original_range(edit.range());
+            match edit.content() {
+                None => Edit::range_deletion(range),
+                Some(content) => {
+                    let content = match normalized.line_ending {
+                        LineEnding::Lf => content.to_string(),
+                        line_ending => content.replace('\n', line_ending.as_str()),
+                    };
+                    if range.is_empty() {
+                        Edit::insertion(content, range.start())
+                    } else {
+                        Edit::range_replacement(content, range)
+                    }
+                }
+            }
+        });
+        Fix::new(fix.title(), edits)
+    }
+}
+
+impl Normalized {
+    /// Strip the byte order mark of `source` and replace its `\r\n` and `\r` line endings with
+    /// `\n`. Returns `None` if there's nothing to normalize.
+    fn from_source(source: &str) -> Option<Self> {
+        let bom = source.starts_with('\u{feff}');
+        if !bom && !source.contains('\r') {
+            return None;
+        }
+
+        let line_ending = find_newline(source).map_or(LineEnding::Lf, |(_, ending)| ending);
+        let mut contents = String::with_capacity(source.len());
+        let mut offsets = OffsetMap::default();
+        let mut rest = source;
+        if bom {
+            rest = &rest['\u{feff}'.len_utf8()..];
+            offsets.push(TextSize::default(), '\u{feff}'.text_len());
+        }
+        while let Some(index) = rest.find('\r') {
+            contents.push_str(&rest[..index]);
+            contents.push('\n');
+            if rest[index + 1..].starts_with('\n') {
+                // The `\r` is dropped: offsets after the line ending shift by one.
+                offsets.push(contents.text_len(), TextSize::new(1));
+                rest = &rest[index + 2..];
+            } else {
+                rest = &rest[index + 1..];
+            }
+        }
+        contents.push_str(rest);
+
+        Some(Self {
+            contents,
+            line_ending,
+            offsets,
+        })
+    }
+}
+
+/// The bytes removed from the original source by the normalization, by the offset in the
+/// normalized source from which they apply.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct OffsetMap {
+    /// Offsets in the normalized source, in ascending order, and the number of bytes removed
+    /// before them in total.
+    shifts: Vec<(TextSize, TextSize)>,
+}
+
+impl OffsetMap {
+    fn push(&mut self, offset: TextSize, removed: TextSize) {
+        let total = self
+            .shifts
+            .last()
+            .map_or(removed, |(_, total)| *total + removed);
+        self.shifts.push((offset, total));
+    }
+
+    fn to_original(&self, offset: TextSize) -> TextSize {
+        let index = self.shifts.partition_point(|(start, _)| *start <= offset);
+        match index.checked_sub(1) {
+            Some(index) => offset + self.shifts[index].1,
+            None => offset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::ast::Ranged;
+    use rustpython_parser::text_size::{TextRange, TextSize};
+
+    use crate::fix::{Edit, Fix};
+
+    use super::SourceKind;
+
+    /// Apply `fix` to the original source of `source_kind`.
+    fn apply(source_kind: &SourceKind, fix: &Fix) -> String {
+        let mut fixed = source_kind.original_source_code().to_string();
+        for edit in fix.edits().iter().rev() {
+            fixed.replace_range(
+                std::ops::Range::<usize>::from(edit.range()),
+                edit.content().unwrap_or_default(),
+            );
+        }
+        fixed
+    }
+
+    #[test]
+    fn unchanged() {
+        let source_kind = SourceKind::new("x = 1\ny = 2\n".to_string());
+        assert!(!source_kind.is_normalized());
+        assert_eq!(source_kind.source_code(), "x = 1\ny = 2\n");
+        assert_eq!(
+            source_kind.to_original_offset(TextSize::new(8)),
+            TextSize::new(8)
+        );
+    }
+
+    #[test]
+    fn mixed_line_endings() {
+        let source_kind = SourceKind::new("\u{feff}x = 1\r\ny = 2\rz = 3\r\n".to_string());
+        assert_eq!(source_kind.source_code(), "x = 1\ny = 2\nz = 3\n");
+
+        // The start of the file is after the BOM.
+        assert_eq!(
+            source_kind.to_original_offset(TextSize::new(0)),
+            TextSize::new(3)
+        );
+        // The end of the first line is before its `\r\n`, and the second line starts after it.
+        assert_eq!(
+            source_kind.to_original_offset(TextSize::new(5)),
+            TextSize::new(8)
+        );
+        assert_eq!(
+            source_kind.to_original_offset(TextSize::new(6)),
+            TextSize::new(10)
+        );
+        // A `\r` line ending is replaced, not removed.
+        assert_eq!(
+            source_kind.to_original_range(TextRange::new(TextSize::new(12), TextSize::new(17))),
+            TextRange::new(TextSize::new(16), TextSize::new(21))
+        );
+        assert_eq!(
+            source_kind.to_original_offset(TextSize::new(18)),
+            TextSize::new(23)
+        );
+    }
+
+    #[test]
+    fn fixes_keep_line_endings() {
+        let source_kind = SourceKind::new("\u{feff}x = 1\r\ny = 2\r\nz = 3\r\n".to_string());
+        // Replace the second line by inserting a line before it and deleting it.
+        let fix = Fix::new(
+            "Fix",
+            [
+                Edit::insertion("w = 0\n".to_string(), TextSize::new(6)),
+                Edit::range_deletion(TextRange::new(TextSize::new(6), TextSize::new(12))),
+            ],
+        );
+        let original = source_kind.to_original_fix(&fix);
+        assert_eq!(
+            original
+                .edits()
+                .iter()
+                .map(Ranged::range)
+                .collect::<Vec<_>>(),
+            [
+                TextRange::empty(TextSize::new(10)),
+                TextRange::new(TextSize::new(10), TextSize::new(17)),
+            ]
+        );
+        assert_eq!(
+            apply(&source_kind, &original),
+            "\u{feff}x = 1\r\nw = 0\r\nz = 3\r\n"
+        );
     }
 }
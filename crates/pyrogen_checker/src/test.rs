@@ -1,8 +1,31 @@
 #![cfg(any(test, fuzzing))]
-//! Helper functions for the tests of rule implementations.
+//! Helper functions for the tests of rule implementations, and the hooks of the fuzz targets into
+//! crate-private code.
 
+#[cfg(not(fuzzing))]
 use std::path::Path;
 
+#[cfg(fuzzing)]
+use rustpython_parser::{ast::Ranged, text_size::TextSize};
+
+#[cfg(fuzzing)]
+use crate::type_ignore::Directive;
+
+/// Extract the `type: ignore` directive of `text`, and check that its range can be sliced out of
+/// `text`.
+#[cfg(fuzzing)]
+pub fn try_extract_type_ignore(text: &str) {
+    let range = match Directive::try_extract(text, TextSize::default()) {
+        Ok(Some(Directive::All(all))) => all.range(),
+        Ok(Some(Directive::Codes(codes))) => codes.range(),
+        Ok(None) | Err(_) => return,
+    };
+    assert!(
+        text.get(std::ops::Range::<usize>::from(range)).is_some(),
+        "{range:?} isn't a valid range of {text:?}"
+    );
+}
+
 // use anyhow::Result;
 
 #[cfg(not(fuzzing))]
@@ -0,0 +1,204 @@
+//! The time spent in every rule, and the `rule-timeout` safeguard against slow rules.
+
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::fs::relativize_path;
+use crate::registry::ErrorCode;
+use crate::settings::types::{RuleTimeout, RuleTimeoutAction};
+use crate::warn_user;
+
+/// The timings of all the rules that ran during this process.
+pub static RULE_TIMINGS: Lazy<RuleTimings> = Lazy::new(RuleTimings::default);
+
+/// The cumulative time spent in a rule, across all files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuleTiming {
+    pub code: ErrorCode,
+    pub total: Duration,
+    /// The longest time the rule took on a single file.
+    pub max: Duration,
+    /// The number of files the rule ran on.
+    pub files: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct RuleTimings {
+    timings: Mutex<FxHashMap<ErrorCode, RuleTiming>>,
+    /// Rules that exceeded their budget with [`RuleTimeoutAction::Disable`].
+    disabled: Mutex<FxHashSet<ErrorCode>>,
+}
+
+impl RuleTimings {
+    /// Run rule `code` on the file at `path` and record how long it took.
+    ///
+    /// Returns `None` without running the rule if it was disabled earlier in the run for
+    /// exceeding its `timeout`.
+    pub(crate) fn run<T>(
+        &self,
+        code: ErrorCode,
+        path: &Path,
+        timeout: Option<RuleTimeout>,
+        rule: impl FnOnce() -> T,
+    ) -> Option<T> {
+        if self.is_disabled(code) {
+            return None;
+        }
+
+        let start = Instant::now();
+        let result = rule();
+        let elapsed = start.elapsed();
+        self.record(code, elapsed);
+
+        if let Some(timeout) = timeout {
+            let budget = Duration::from_millis(timeout.milliseconds);
+            if elapsed > budget {
+                let path = relativize_path(path);
+                match timeout.action {
+                    RuleTimeoutAction::Warn => warn_user!(
+                        "`{code}` took {elapsed:.2?} on {path}, exceeding the `rule-timeout` of {budget:?}"
+                    ),
+                    RuleTimeoutAction::Disable => {
+                        if self.disable(code) {
+                            warn_user!(
+                                "`{code}` took {elapsed:.2?} on {path}, exceeding the `rule-timeout` of {budget:?}; disabling it for the rest of the run"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        Some(result)
+    }
+
+    fn record(&self, code: ErrorCode, elapsed: Duration) {
+        let mut timings = self.timings.lock().unwrap_or_else(PoisonError::into_inner);
+        let timing = timings.entry(code).or_insert(RuleTiming {
+            code,
+            total: Duration::ZERO,
+            max: Duration::ZERO,
+            files: 0,
+        });
+        timing.total += elapsed;
+        timing.max = timing.max.max(elapsed);
+        timing.files += 1;
+    }
+
+    /// Disable `code` for the rest of the run. Returns `false` if it was already disabled.
+    fn disable(&self, code: ErrorCode) -> bool {
+        self.disabled
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(code)
+    }
+
+    pub fn is_disabled(&self, code: ErrorCode) -> bool {
+        self.disabled
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&code)
+    }
+
+    /// The rules that were disabled for exceeding their budget, sorted by code.
+    pub fn disabled(&self) -> Vec<ErrorCode> {
+        let mut disabled = self
+            .disabled
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+        disabled.sort_unstable_by_key(ErrorCode::to_str);
+        disabled
+    }
+
+    /// Forget the timings and disabled rules recorded so far, e.g., between the checks of a
+    /// long-running process.
+    pub fn clear(&self) {
+        self.timings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        self.disabled
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// The `limit` rules with the most cumulative time, slowest first.
+    pub fn slowest(&self, limit: usize) -> Vec<RuleTiming> {
+        let mut timings = self
+            .timings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .copied()
+            .collect::<Vec<_>>();
+        timings.sort_unstable_by(|a, b| {
+            b.total
+                .cmp(&a.total)
+                .then_with(|| a.code.to_str().cmp(b.code.to_str()))
+        });
+        timings.truncate(limit);
+        timings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use crate::registry::ErrorCode;
+    use crate::settings::types::{RuleTimeout, RuleTimeoutAction};
+
+    use super::RuleTimings;
+
+    #[test]
+    fn slowest_rules() {
+        let timings = RuleTimings::default();
+        let path = Path::new("test.py");
+        for _ in 0..2 {
+            timings.run(ErrorCode::UnusedImport, path, None, || {
+                sleep(Duration::from_millis(2));
+            });
+            timings.run(ErrorCode::GeneralTypeError, path, None, || {});
+        }
+
+        let slowest = timings.slowest(1);
+        assert_eq!(slowest.len(), 1);
+        assert_eq!(slowest[0].code, ErrorCode::UnusedImport);
+        assert_eq!(slowest[0].files, 2);
+        assert!(slowest[0].total >= Duration::from_millis(4));
+    }
+
+    #[test]
+    fn disable_slow_rules() {
+        let timings = RuleTimings::default();
+        let path = Path::new("test.py");
+        let timeout = Some(RuleTimeout {
+            milliseconds: 1,
+            action: RuleTimeoutAction::Disable,
+        });
+
+        let first = timings.run(ErrorCode::UnusedImport, path, timeout, || {
+            sleep(Duration::from_millis(5));
+        });
+        assert_eq!(first, Some(()));
+        assert_eq!(
+            timings.run(ErrorCode::UnusedImport, path, timeout, || ()),
+            None
+        );
+        assert_eq!(
+            timings.run(ErrorCode::GeneralTypeError, path, timeout, || ()),
+            Some(())
+        );
+        assert_eq!(timings.disabled(), vec![ErrorCode::UnusedImport]);
+    }
+}
@@ -5,13 +5,18 @@ use std::path::Path;
 use std::str::FromStr;
 
 use anyhow::Result;
+use chrono::NaiveDate;
+use itertools::Itertools;
 use log::warn;
 use rustpython_parser::ast::Ranged;
 use rustpython_parser::text_size::{TextLen, TextRange, TextSize};
 
+use pyrogen_python_ast::{AsMode, PySourceType};
+use pyrogen_python_index::Indexer;
 use pyrogen_python_trivia::CommentRanges;
-use pyrogen_source_file::Locator;
+use pyrogen_source_file::{Locator, OneIndexed};
 
+use crate::fix::Edit;
 use crate::fs::relativize_path;
 use crate::registry::ErrorCode;
 
@@ -192,6 +197,50 @@ impl<'a> Directive<'a> {
     }
 }
 
+/// The metadata of a `type: ignore` directive, from a `# pyrogen: key=value ...` comment that
+/// follows it (e.g., `# type: ignore[call-arg]  # pyrogen: owner=team-infra expires=2025-12-31`).
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Annotations<'a> {
+    /// The last day on which the directive applies.
+    pub(crate) expires: Option<NaiveDate>,
+    /// The team or person that is responsible for the directive.
+    pub(crate) owner: Option<&'a str>,
+}
+
+impl<'a> Annotations<'a> {
+    /// Extract the annotations from the text that follows a `type: ignore` directive in the same
+    /// comment.
+    fn try_extract(text: &'a str) -> Result<Self, ParseError> {
+        let mut annotations = Self::default();
+        let Some(start) = text.find("pyrogen:") else {
+            return Ok(annotations);
+        };
+        // The annotations have to start a comment of their own.
+        if !text[..start].trim_end().ends_with('#') {
+            return Ok(annotations);
+        }
+        let pairs = text[start + "pyrogen:".len()..]
+            .split('#')
+            .next()
+            .unwrap_or_default();
+        for pair in pairs
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|pair| !pair.is_empty())
+        {
+            match pair.split_once('=') {
+                Some(("expires", date)) => {
+                    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                        .map_err(|_| ParseError::InvalidExpiry)?;
+                    annotations.expires = Some(date);
+                }
+                Some(("owner", owner)) if !owner.is_empty() => annotations.owner = Some(owner),
+                _ => return Err(ParseError::InvalidAnnotation),
+            }
+        }
+        Ok(annotations)
+    }
+}
+
 #[inline]
 fn skip_whitespace(line: &str) -> usize {
     line.find(|c: char| !c.is_whitespace()).unwrap_or(0)
@@ -251,6 +300,87 @@ pub(crate) fn rule_is_ignored(
     }
 }
 
+/// The [`Edit`] that suppresses `code` on the line of `offset`: the code is added to the line's
+/// `type: ignore[...]` comment, or a new comment is appended to the line.
+///
+/// Returns `None` if the line's `type: ignore` comment already covers the code.
+pub fn type_ignore_edit(code: ErrorCode, offset: TextSize, locator: &Locator) -> Option<Edit> {
+    type_ignore_codes_edit(&[code], offset, locator)
+}
+
+/// Like [`type_ignore_edit`], for several codes on the same line.
+///
+/// Returns `None` if the line's `type: ignore` comment already covers all of the codes.
+pub fn type_ignore_codes_edit(
+    codes: &[ErrorCode],
+    offset: TextSize,
+    locator: &Locator,
+) -> Option<Edit> {
+    if codes.is_empty() {
+        return None;
+    }
+    let line_range = locator.line_range(offset);
+    match Directive::try_extract(locator.slice(line_range), line_range.start()) {
+        Ok(Some(Directive::All(_))) => None,
+        Ok(Some(Directive::Codes(directive))) => {
+            let missing = codes
+                .iter()
+                .filter(|code| !includes(**code, directive.codes()))
+                .map(ErrorCode::to_str)
+                .collect::<Vec<_>>();
+            if missing.is_empty() {
+                return None;
+            }
+            let codes = directive.codes().iter().copied().chain(missing).join(", ");
+            Some(Edit::range_replacement(
+                format!("# type: ignore[{codes}]"),
+                directive.range(),
+            ))
+        }
+        // An invalid directive is left alone.
+        Ok(None) | Err(_) => Some(Edit::insertion(
+            format!(
+                "  # type: ignore[{}]",
+                codes.iter().map(ErrorCode::to_str).join(", ")
+            ),
+            line_range.end(),
+        )),
+    }
+}
+
+/// A `type: ignore` comment of a file, with its annotations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    pub line: OneIndexed,
+    /// The codes that the comment suppresses, or none if it suppresses all of them.
+    pub codes: Vec<String>,
+    /// The owner of the comment, from a `# pyrogen: owner=...` annotation.
+    pub owner: Option<String>,
+    /// The last day on which the comment applies, from a `# pyrogen: expires=...` annotation.
+    pub expires: Option<NaiveDate>,
+}
+
+/// The `type: ignore` comments of the file at `path` with the given `source`, in order.
+pub fn suppressions(path: &Path, source: &str, source_type: PySourceType) -> Vec<Suppression> {
+    let tokens = rustpython_parser::lexer::lex(source, source_type.as_mode()).collect::<Vec<_>>();
+    let locator = Locator::new(source);
+    let indexer = Indexer::from_tokens(&tokens, &locator);
+    let line_index = locator.to_source_code();
+    TypeIgnores::from_commented_ranges(indexer.comment_ranges(), path, &locator)
+        .lines()
+        .iter()
+        .map(|line| Suppression {
+            line: line_index.line_index(line.start()),
+            codes: match &line.directive {
+                Directive::All(_) => vec![],
+                Directive::Codes(codes) => codes.codes().iter().map(ToString::to_string).collect(),
+            },
+            owner: line.annotations.owner.map(ToString::to_string),
+            expires: line.annotations.expires,
+        })
+        .collect()
+}
+
 /// The file-level exemptions extracted from a given Python file.
 #[derive(Debug)]
 pub(crate) enum FileExemption {
@@ -342,6 +472,10 @@ pub(crate) enum ParseError {
     InvalidSuffix,
     NoClosingBracket,
     MissingComma,
+    /// The expiry date isn't of the form `YYYY-MM-DD` (e.g., `# pyrogen: expires=31.12.2025`).
+    InvalidExpiry,
+    /// An annotation isn't an `owner=...` or `expires=...` pair (e.g., `# pyrogen: team-infra`).
+    InvalidAnnotation,
 }
 
 impl Display for ParseError {
@@ -354,7 +488,9 @@ impl Display for ParseError {
                 fmt.write_str("after `# type: ignore` the line should continue with brackets or start a new comment with `#`.")
             }
             ParseError::MissingComma => fmt.write_str("expected a comma-separated list of codes (e.g., `# type: ignore[override,unreachable]`)."),
-            ParseError::NoClosingBracket => fmt.write_str("bracket after `ignore` directive is not closed.")
+            ParseError::NoClosingBracket => fmt.write_str("bracket after `ignore` directive is not closed."),
+            ParseError::InvalidExpiry => fmt.write_str("expected an expiry date of the form `YYYY-MM-DD` (e.g., `# pyrogen: expires=2025-12-31`); the annotations are ignored."),
+            ParseError::InvalidAnnotation => fmt.write_str("expected `owner=...` or `expires=...` annotations (e.g., `# pyrogen: owner=team-infra expires=2025-12-31`); the annotations are ignored.")
 
         }
     }
@@ -370,6 +506,17 @@ pub(crate) struct TypeIgnoreLine<'a> {
     pub(crate) directive: Directive<'a>,
     /// The codes that are ignored by the directive.
     pub(crate) matches: Vec<ErrorCode>,
+    /// The annotations that follow the directive, like `# pyrogen: expires=2025-12-31`.
+    pub(crate) annotations: Annotations<'a>,
+}
+
+impl TypeIgnoreLine<'_> {
+    /// Returns `true` if the directive expired before `today`, and no longer suppresses anything.
+    pub(crate) fn is_expired(&self, today: NaiveDate) -> bool {
+        self.annotations
+            .expires
+            .is_some_and(|expires| expires < today)
+    }
 }
 
 impl Ranged for TypeIgnoreLine<'_> {
@@ -401,11 +548,26 @@ impl<'a> TypeIgnores<'a> {
                     warn!("Invalid `# type: ignore` directive on {path_display}:{line}: {err}");
                 }
                 Ok(Some(directive)) => {
+                    let annotation = locator.slice(TextRange::new(
+                        match &directive {
+                            Directive::All(all) => all.range().end(),
+                            Directive::Codes(codes) => codes.range().end(),
+                        },
+                        range.end(),
+                    ));
+                    let annotations = Annotations::try_extract(annotation).unwrap_or_else(|err| {
+                        #[allow(deprecated)]
+                        let line = locator.compute_line_index(range.start());
+                        let path_display = relativize_path(path);
+                        warn!("Invalid `# pyrogen:` annotation on {path_display}:{line}: {err}");
+                        Annotations::default()
+                    });
                     // noqa comments are guaranteed to be single line.
                     directives.push(TypeIgnoreLine {
                         range: locator.line_range(range.start()),
                         directive,
                         matches: Vec::new(),
+                        annotations,
                     });
                 }
                 Ok(None) => {}
@@ -524,10 +686,135 @@ impl FromIterator<TextRange> for TypeIgnoreMapping {
 
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
+
     use insta::assert_debug_snapshot;
+    use rustpython_parser::ast::Ranged;
     use rustpython_parser::text_size::TextSize;
 
-    use crate::type_ignore::{Directive, ParsedFileExemption};
+    use pyrogen_python_ast::PySourceType;
+    use pyrogen_source_file::{Locator, OneIndexed};
+
+    use crate::registry::ErrorCode;
+    use crate::type_ignore::{
+        suppressions, type_ignore_codes_edit, type_ignore_edit, Annotations, Directive,
+        ParsedFileExemption, Suppression,
+    };
+
+    /// Apply the edit that suppresses `code` on the first line of `source`, if there is one.
+    fn with_type_ignore(source: &str, code: ErrorCode) -> Option<String> {
+        let edit = type_ignore_edit(code, TextSize::default(), &Locator::new(source))?;
+        let mut fixed = source.to_string();
+        fixed.replace_range(
+            std::ops::Range::<usize>::from(edit.range()),
+            edit.content().unwrap_or_default(),
+        );
+        Some(fixed)
+    }
+
+    #[test]
+    fn add_type_ignore() {
+        assert_eq!(
+            with_type_ignore("x = {1: 1, 1: 2}\ny = 1\n", ErrorCode::DuplicateKey).as_deref(),
+            Some("x = {1: 1, 1: 2}  # type: ignore[duplicate-key]\ny = 1\n")
+        );
+        assert_eq!(
+            with_type_ignore(
+                "x = {1: 1, 1: 2}  # type: ignore[call-arg]  # reason\n",
+                ErrorCode::DuplicateKey
+            )
+            .as_deref(),
+            Some("x = {1: 1, 1: 2}  # type: ignore[call-arg, duplicate-key]  # reason\n")
+        );
+        assert_eq!(
+            with_type_ignore(
+                "x = {1: 1, 1: 2}  # type: ignore\n",
+                ErrorCode::DuplicateKey
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn add_several_codes() {
+        let codes = [ErrorCode::CallArgument, ErrorCode::DuplicateKey];
+        let edit = type_ignore_codes_edit(
+            &codes,
+            TextSize::default(),
+            &Locator::new("f({1: 1, 1: 2})\n"),
+        )
+        .unwrap();
+        assert_eq!(
+            edit.content(),
+            Some("  # type: ignore[call-arg, duplicate-key]")
+        );
+
+        let source = "f({1: 1, 1: 2})  # type: ignore[call-arg]\n";
+        let edit =
+            type_ignore_codes_edit(&codes, TextSize::default(), &Locator::new(source)).unwrap();
+        assert_eq!(
+            edit.content(),
+            Some("# type: ignore[call-arg, duplicate-key]")
+        );
+        assert_eq!(&source[edit.range()], "# type: ignore[call-arg]");
+    }
+
+    #[test]
+    fn annotations() {
+        let expires = chrono::NaiveDate::from_ymd_opt(2025, 12, 31);
+        assert_eq!(
+            Annotations::try_extract("  # pyrogen: expires=2025-12-31").unwrap(),
+            Annotations {
+                expires,
+                owner: None
+            }
+        );
+        assert_eq!(
+            Annotations::try_extract(" #pyrogen:owner=team-infra, expires=2025-12-31  # reason")
+                .unwrap(),
+            Annotations {
+                expires,
+                owner: Some("team-infra")
+            }
+        );
+        assert_eq!(
+            Annotations::try_extract("  # reason").unwrap(),
+            Annotations::default()
+        );
+        // The annotations have to be a comment of their own.
+        assert_eq!(
+            Annotations::try_extract("  # see pyrogen: expires=2025-12-31").unwrap(),
+            Annotations::default()
+        );
+        assert!(Annotations::try_extract("  # pyrogen: expires=31.12.2025").is_err());
+        assert!(Annotations::try_extract("  # pyrogen: team-infra").is_err());
+    }
+
+    #[test]
+    fn suppression_report() {
+        let source = "\
+import os  # type: ignore  # pyrogen: owner=team-infra
+x = {1: 1, 1: 2}  # type: ignore[duplicate-key, call-arg]  # pyrogen: expires=2025-12-31
+# Not a suppression: type: ignore
+";
+        assert_eq!(
+            suppressions(Path::new("module.py"), source, PySourceType::Python),
+            [
+                Suppression {
+                    line: OneIndexed::from_zero_indexed(0),
+                    codes: vec![],
+                    owner: Some("team-infra".to_string()),
+                    expires: None,
+                },
+                Suppression {
+                    line: OneIndexed::from_zero_indexed(1),
+                    codes: vec!["duplicate-key".to_string(), "call-arg".to_string()],
+                    owner: None,
+                    expires: chrono::NaiveDate::from_ymd_opt(2025, 12, 31),
+                },
+            ]
+        );
+    }
 
     #[test]
     fn ignore_all() {
@@ -0,0 +1,71 @@
+//! The problems that rules report, as structs whose fields are the arguments of their message.
+
+use crate::locale::MessageTemplate;
+use crate::registry::ErrorCode;
+
+pub use pyrogen_macros::Violation;
+
+/// A problem that a rule reports, which `#[derive(Violation)]` converts into a
+/// [`DiagnosticKind`](crate::registry::DiagnosticKind) with the message rendered from the catalog.
+pub trait Violation {
+    /// The error code of the diagnostics.
+    const CODE: ErrorCode;
+
+    /// The message of the catalog, with the arguments that it refers to.
+    fn message(&self) -> MessageTemplate;
+
+    /// The title of the fix, if the problem can be fixed.
+    fn fix_title(&self) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry::{DiagnosticKind, ErrorCode};
+
+    use super::Violation;
+
+    #[derive(Violation)]
+    #[violation(code = ConfusableCharacter, fix_title = "Replace with `{ascii}`")]
+    struct Confusable {
+        character: char,
+        code_point: &'static str,
+        ascii: char,
+    }
+
+    #[derive(Violation)]
+    #[violation(code = TooManyDiagnostics)]
+    struct TooMany {
+        count: usize,
+        limit: usize,
+    }
+
+    #[test]
+    fn derived_violations() {
+        let confusable = Confusable {
+            character: '\u{0430}',
+            code_point: "U+0430",
+            ascii: 'a',
+        };
+        assert_eq!(confusable.fix_title().as_deref(), Some("Replace with `a`"));
+        let kind = DiagnosticKind::from(confusable);
+        assert_eq!(kind.error_code, ErrorCode::ConfusableCharacter);
+        assert_eq!(
+            kind.body,
+            "The character \u{0430} (U+0430) can be confused with the ASCII a"
+        );
+
+        let too_many = TooMany {
+            count: 3,
+            limit: 10,
+        };
+        assert_eq!(too_many.fix_title(), None);
+        // The message defaults to the one named like the code.
+        assert_eq!(too_many.message().id, "too-many-diagnostics");
+        assert_eq!(
+            DiagnosticKind::from(too_many).body,
+            "3 more diagnostics suppressed, since the file has more than 10"
+        );
+    }
+}
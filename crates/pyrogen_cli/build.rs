@@ -0,0 +1,54 @@
+//! Embed the commit, build date, target and enabled features in the binary, for `--version`.
+
+use std::process::Command;
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    Some(stdout.trim().to_string())
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // Builds from a source distribution don't have a repository.
+    if let Some(git_dir) = git(&["rev-parse", "--absolute-git-dir"]) {
+        println!("cargo:rerun-if-changed={git_dir}/HEAD");
+        println!("cargo:rerun-if-changed={git_dir}/refs/heads");
+        if let Some(hash) = git(&["rev-parse", "HEAD"]) {
+            println!("cargo:rustc-env=PYROGEN_COMMIT_HASH={hash}");
+        }
+        if let Some(date) = git(&["log", "-1", "--date=short", "--format=%cd"]) {
+            println!("cargo:rustc-env=PYROGEN_COMMIT_DATE={date}");
+        }
+    }
+
+    // Reproducible builds set the build date through `SOURCE_DATE_EPOCH`.
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let build_date = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<i64>().ok())
+        .and_then(|epoch| chrono::DateTime::from_timestamp(epoch, 0))
+        .unwrap_or_else(chrono::Utc::now);
+    println!(
+        "cargo:rustc-env=PYROGEN_BUILD_DATE={}",
+        build_date.format("%Y-%m-%d")
+    );
+
+    println!(
+        "cargo:rustc-env=PYROGEN_TARGET={}",
+        std::env::var("TARGET").unwrap_or_default()
+    );
+
+    let mut features = std::env::vars()
+        .filter_map(|(name, _)| {
+            name.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .collect::<Vec<_>>();
+    features.sort_unstable();
+    println!("cargo:rustc-env=PYROGEN_FEATURES={}", features.join(","));
+}
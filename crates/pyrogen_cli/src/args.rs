@@ -1,13 +1,21 @@
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use clap::{command, Parser};
 
 use pyrogen_checker::code_selector::clap_completion::ErrorCodeSelectorParser;
+use pyrogen_checker::locale::Locale;
 use pyrogen_checker::logging::LogLevel;
-use pyrogen_checker::settings::types::{FilePattern, PythonVersion, SerializationFormat};
+use pyrogen_checker::registry::ErrorCode;
+use pyrogen_checker::settings::types::{
+    FilePattern, PythonPlatform, PythonVersion, SerializationFormat,
+};
 use pyrogen_checker::ErrorCodeSelector;
 use pyrogen_workspace::configuration::{Configuration, ErrorCodeSelection};
-use pyrogen_workspace::resolver::ConfigurationTransformer;
+use pyrogen_workspace::resolver::{ConfigurationTransformer, PyprojectConfig};
+
+use crate::resolve;
+use crate::sample::Sample;
 
 #[derive(Debug, Parser)]
 #[command(
@@ -15,23 +23,369 @@ use pyrogen_workspace::resolver::ConfigurationTransformer;
     name = "pyrogen",
     about = "Pyrogen, an extremely strict Python type checker."
 )]
-#[command(version)]
+#[command(disable_version_flag = true)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Print the version, with the commit, build date, target and features of the build. Use
+    /// `--output-format json` for JSON.
+    #[arg(short = 'V', long)]
+    pub version: bool,
     #[clap(flatten)]
     pub checker_args: CheckCommand,
     #[clap(flatten)]
     pub log_level_args: LogLevelArgs,
 }
 
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+    /// List the files that would be checked, one per line and sorted by path.
+    ///
+    /// Accepts the same file selection options as a regular check.
+    Files(CheckCommand),
+    /// Validate all pyrogen configuration files without checking any Python files.
+    ///
+    /// Every `pyproject.toml` with a `[tool.pyrogen]` section below the given paths (or the
+    /// current directory) is checked for TOML syntax errors, unknown options, invalid globs and
+    /// codes that are selected with several severities.
+    CheckConfig(CheckConfigCommand),
+    /// Remove the `.pyrogen_cache` directories below the given paths (or the current directory)
+    /// and the configured `cache-dir`.
+    Clean(CleanCommand),
+    /// Serve check requests from other tools.
+    ///
+    /// With `--json-rpc`, JSON-RPC 2.0 requests are read from stdin and answered on stdout, one
+    /// JSON object per line. `{"method": "check", "params": {"path": "..."}}` checks a file on
+    /// disk, `{"method": "check", "params": {"source": "..."}}` checks the given source code, and
+    /// `{"method": "shutdown"}` ends the session.
+    Analyze(AnalyzeCommand),
+    /// Find the definitions of functions, classes and variables by name.
+    ///
+    /// The pattern is matched against the name of every definition at the top level of a module
+    /// or in a class body, or against its qualified name (like `pkg.module.Class.method`) if the
+    /// pattern contains a `.`. It may contain the wildcards `*` and `?`. Matches are printed as
+    /// `path:line:column: kind qualified-name`, and the exit code is 1 if there are none.
+    FindSymbol(FindSymbolCommand),
+    /// Explain an error code, or the diagnostics on a line of a file.
+    ///
+    /// Given an error code like `call-arg`, prints what the code reports, an example, and whether
+    /// it can be fixed.
+    ///
+    /// Given a location, the file is checked with every code enabled. Each diagnostic that starts
+    /// on the line is printed with what happens to it under the actual configuration: whether
+    /// it's reported, its code isn't selected, or it's suppressed by `per-file-ignores` or a
+    /// `type: ignore` comment. The selected codes that don't produce a diagnostic on the line are
+    /// listed as well. Rules that need the other modules of the project (like `call-arg`) aren't
+    /// run.
+    Explain(ExplainCommand),
+    /// List error codes with their category, default severity and whether the configuration
+    /// enables them.
+    Rule(RuleCommand),
+    /// Show the settings that a file or directory is checked with, after discovering the
+    /// configuration files and applying the command-line overrides.
+    Config(ConfigCommand),
+    /// Run the language server, speaking the Language Server Protocol over stdio.
+    ///
+    /// Open documents are checked when they're opened, changed or saved. The settings of each
+    /// document come from its closest `pyproject.toml`, and are reloaded when that file changes.
+    Server,
+    /// Run a daemon that checks on behalf of `pyrogen --use-daemon`, keeping its caches in
+    /// memory between checks.
+    ///
+    /// The daemon listens on a Unix domain socket, by default `daemon.sock` in the cache
+    /// directory, and checks the project in the directory it was started in. It runs until it's
+    /// stopped (e.g. with Ctrl+C).
+    Daemon(DaemonCommand),
+    /// Print the version of pyrogen.
+    ///
+    /// With `--check`, the latest release is looked up on GitHub, and upgrade
+    /// instructions are printed if it's newer. The exit code is 1 in that case.
+    Version(VersionCommand),
+    /// Manage the pyrogen binary.
+    #[cfg(feature = "self-update")]
+    #[command(name = "self", subcommand)]
+    SelfCommand(SelfCommand),
+    /// Tools for developing pyrogen.
+    #[command(hide = true, subcommand)]
+    Dev(DevCommand),
+}
+
+#[derive(Clone, Debug, clap::Subcommand)]
+pub enum DevCommand {
+    /// Write the reference of the command-line interface as Markdown (`cli.md`) and as a man page
+    /// (`pyrogen.1`).
+    GenerateCliDocs(GenerateCliDocsCommand),
+}
+
+#[derive(Clone, Debug, clap::Args)]
+pub struct GenerateCliDocsCommand {
+    /// The directory to write the files to.
+    #[arg(long, default_value = "docs")]
+    pub output_dir: PathBuf,
+    /// Check that the files are up to date instead of writing them.
+    #[arg(long)]
+    pub check: bool,
+}
+
+#[derive(Clone, Debug, clap::Args)]
+pub struct VersionCommand {
+    /// Check whether a newer version has been released.
+    #[arg(long, conflicts_with = "output_format")]
+    pub check: bool,
+    /// Output format.
+    #[arg(long, value_enum, default_value = "text")]
+    pub output_format: HelpFormat,
+}
+
+#[cfg(feature = "self-update")]
+#[derive(Clone, Debug, clap::Subcommand)]
+pub enum SelfCommand {
+    /// Replace the pyrogen binary with the one of the latest release, if that's newer.
+    Update,
+}
+
+/// The options of the subcommands that locate the configuration.
+#[derive(Clone, Debug, clap::Args)]
+pub struct ConfigArgs {
+    /// Path to the `pyproject.toml` file to use for configuration.
+    #[arg(long, conflicts_with = "isolated")]
+    pub config: Option<PathBuf>,
+    /// Ignore all configuration files.
+    #[arg(long)]
+    pub isolated: bool,
+}
+
+impl ConfigArgs {
+    /// Resolve the settings that these options point to.
+    pub fn resolve(&self, overrides: &CliOverrides) -> anyhow::Result<PyprojectConfig> {
+        resolve::resolve(self.isolated, self.config.as_deref(), overrides, None)
+    }
+}
+
+#[derive(Clone, Debug, clap::Args)]
+pub struct ExplainCommand {
+    /// The error code or the location (as `path:line`) to explain.
+    #[arg(value_parser = parse_explain_target, value_name = "CODE|FILE:LINE")]
+    pub target: ExplainTarget,
+    #[command(flatten)]
+    pub config: ConfigArgs,
+}
+
+#[derive(Clone, Debug)]
+pub enum ExplainTarget {
+    Code(ErrorCode),
+    Location(PathBuf, usize),
+}
+
+fn parse_explain_target(target: &str) -> Result<ExplainTarget, String> {
+    if let Ok(code) = ErrorCode::from_str(target) {
+        return Ok(ExplainTarget::Code(code));
+    }
+    if !target.contains(':') {
+        return Err(format!("`{target}` is not an error code"));
+    }
+    let (path, line) = parse_location(target)?;
+    Ok(ExplainTarget::Location(path, line))
+}
+
+fn parse_location(location: &str) -> Result<(PathBuf, usize), String> {
+    let (path, line) = location
+        .rsplit_once(':')
+        .ok_or_else(|| "expected a location of the form `path:line`".to_string())?;
+    match line.parse::<usize>() {
+        Ok(line) if line > 0 => Ok((PathBuf::from(path), line)),
+        _ => Err(format!("`{line}` is not a line number")),
+    }
+}
+
+#[derive(Clone, Debug, clap::Args)]
+pub struct RuleCommand {
+    /// The error code to show.
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    pub code: Option<ErrorCode>,
+    /// Show all error codes.
+    #[arg(long)]
+    pub all: bool,
+    /// Output format.
+    #[arg(long, value_enum, default_value = "text")]
+    pub output_format: HelpFormat,
+    #[command(flatten)]
+    pub config: ConfigArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HelpFormat {
+    Text,
+    Json,
+}
+
+/// The format of the `--phase-timings` report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TimingFormat {
+    /// The totals and the slowest files, as a table.
+    Text,
+    /// Every file, with the times in milliseconds.
+    Json,
+}
+
+#[derive(Clone, Debug, clap::Args)]
+pub struct ConfigCommand {
+    /// The file or directory to show the settings of.
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    /// Output format.
+    #[arg(long, value_enum, default_value = "toml")]
+    pub output_format: ConfigFormat,
+    #[command(flatten)]
+    pub config: ConfigArgs,
+    /// The minimum Python version that should be supported.
+    #[arg(long, value_enum)]
+    pub target_version: Option<PythonVersion>,
+    /// The platform to assume when evaluating `sys.platform` checks.
+    #[arg(long, value_enum)]
+    pub platform: Option<PythonPlatform>,
+    /// Comma-separated list of error codes to report as errors.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "ERROR_CODE",
+        value_parser = ErrorCodeSelectorParser,
+        hide_possible_values = true
+    )]
+    pub error: Option<Vec<ErrorCodeSelector>>,
+    /// Comma-separated list of error codes to report as warnings.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "ERROR_CODE",
+        value_parser = ErrorCodeSelectorParser,
+        hide_possible_values = true
+    )]
+    pub warning: Option<Vec<ErrorCodeSelector>>,
+    /// Comma-separated list of error codes to disable.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "ERROR_CODE",
+        value_parser = ErrorCodeSelectorParser,
+        hide_possible_values = true
+    )]
+    pub ignore: Option<Vec<ErrorCodeSelector>>,
+    /// Enforce exclusions, even for the path passed on the command-line.
+    #[arg(long)]
+    pub force_exclude: bool,
+}
+
+impl ConfigCommand {
+    pub fn overrides(&self) -> CliOverrides {
+        CliOverrides {
+            error: self.error.clone(),
+            warning: self.warning.clone(),
+            ignore: self.ignore.clone(),
+            target_version: self.target_version,
+            platform: self.platform,
+            force_exclude: self.force_exclude.then_some(true),
+            ..CliOverrides::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+#[derive(Clone, Debug, clap::Args)]
+pub struct FindSymbolCommand {
+    /// The name (or qualified name) to search for.
+    pub pattern: String,
+    /// Files or directories to search (defaults to the current directory).
+    pub files: Vec<PathBuf>,
+    /// Match the pattern case-insensitively.
+    #[arg(long)]
+    pub ignore_case: bool,
+    #[command(flatten)]
+    pub config: ConfigArgs,
+}
+
+#[derive(Clone, Debug, clap::Args)]
+pub struct AnalyzeCommand {
+    /// Speak JSON-RPC over stdio.
+    #[arg(long, required = true)]
+    pub json_rpc: bool,
+    #[command(flatten)]
+    pub config: ConfigArgs,
+}
+
+#[derive(Clone, Debug, clap::Args)]
+pub struct CheckConfigCommand {
+    /// Files or directories to search for configuration files.
+    pub paths: Vec<PathBuf>,
+}
+
+#[derive(Clone, Debug, clap::Args)]
+pub struct CleanCommand {
+    /// Directories to search for caches.
+    pub paths: Vec<PathBuf>,
+    /// Print the caches that would be removed, without removing them.
+    #[arg(long)]
+    pub dry_run: bool,
+    #[command(flatten)]
+    pub config: ConfigArgs,
+}
+
+#[derive(Clone, Debug, clap::Args)]
+pub struct DaemonCommand {
+    /// The socket to listen on, instead of `daemon.sock` in the cache directory.
+    #[arg(long, value_name = "PATH")]
+    pub socket: Option<PathBuf>,
+    #[command(flatten)]
+    pub config: ConfigArgs,
+}
+
 // The `Parser` derive is for pyrogen_dev, for pyrogen_cli `Args` would be sufficient
 #[derive(Clone, Debug, clap::Parser)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct CheckCommand {
     /// List of files or directories to check.
     pub files: Vec<PathBuf>,
+    /// Also check the files and directories listed in the given file, one per line (or `-` to
+    /// read the list from stdin), like `git diff --name-only | pyrogen check --files-from -`.
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with = "stdin_filename",
+        help_heading = "File selection"
+    )]
+    pub files_from: Option<PathBuf>,
+    /// Separate the paths in the `--files-from` list by NUL characters instead of newlines, as
+    /// printed by `git diff -z` or `find -print0`.
+    #[arg(long, requires = "files_from", help_heading = "File selection")]
+    pub null_separated: bool,
+    /// Only check the files that changed relative to the given git ref (like `origin/main`),
+    /// including uncommitted and untracked files. Paths passed on the command line restrict the
+    /// check to the changed files below them.
+    #[arg(
+        long,
+        value_name = "REF",
+        conflicts_with_all = ["stdin_filename", "watch"],
+        help_heading = "File selection"
+    )]
+    pub diff_base: Option<String>,
+    /// With `--diff-base`, only report the violations that start on a changed line.
+    #[arg(long, requires = "diff_base", help_heading = "File selection")]
+    pub changed_lines_only: bool,
     /// The minimum Python version that should be supported.
     #[arg(long, value_enum)]
     pub target_version: Option<PythonVersion>,
+    /// The platform to assume when evaluating `sys.platform` checks.
+    #[arg(long, value_enum)]
+    pub platform: Option<PythonPlatform>,
+    /// The virtual environment (or Python interpreter) to resolve third-party imports in.
+    #[arg(long, value_name = "PATH")]
+    pub python_path: Option<PathBuf>,
     /// Path to the `pyproject.toml` or `pyrogen.toml` file to use for
     /// configuration.
     #[arg(long, conflicts_with = "isolated")]
@@ -86,6 +440,46 @@ pub struct CheckCommand {
         hide_possible_values = true
     )]
     pub extend_warning: Option<Vec<ErrorCodeSelector>>,
+    /// Comma-separated list of rule codes to report as informational messages.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "ERROR_CODE",
+        value_parser = ErrorCodeSelectorParser,
+        help_heading = "Error code selection",
+        hide_possible_values = true
+    )]
+    pub info: Option<Vec<ErrorCodeSelector>>,
+    /// Comma-separated list of rule codes to report as hints.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "ERROR_CODE",
+        value_parser = ErrorCodeSelectorParser,
+        help_heading = "Error code selection",
+        hide_possible_values = true
+    )]
+    pub hint: Option<Vec<ErrorCodeSelector>>,
+    /// Report the diagnostics that `type: ignore` comments suppress.
+    /// Use `--respect-type-ignore` to respect the comments again.
+    #[arg(
+        long,
+        overrides_with("respect_type_ignore"),
+        help_heading = "Error code selection"
+    )]
+    no_respect_type_ignore: bool,
+    #[clap(long, overrides_with("no_respect_type_ignore"), hide = true)]
+    respect_type_ignore: bool,
+    /// Enable the error codes in preview, which are new and may still change.
+    /// Use `--no-preview` to disable them.
+    #[arg(
+        long,
+        overrides_with("no_preview"),
+        help_heading = "Error code selection"
+    )]
+    preview: bool,
+    #[clap(long, overrides_with("preview"), hide = true)]
+    no_preview: bool,
     /// List of paths, used to omit files and/or directories from analysis.
     #[arg(
         long,
@@ -107,6 +501,99 @@ pub struct CheckCommand {
     #[arg(long, value_enum, env = "PYROGEN_OUTPUT_FORMAT")]
     pub output_format: Option<SerializationFormat>,
 
+    /// The line format of violations with `--output-format custom`, like
+    /// `"{path}:{row}: {code} {message}"`.
+    #[arg(long, env = "PYROGEN_OUTPUT_TEMPLATE")]
+    pub output_template: Option<String>,
+
+    /// The language of the messages of violations.
+    #[arg(long, value_enum, env = "PYROGEN_LOCALE")]
+    pub locale: Option<Locale>,
+
+    /// Write the violations to the given file instead of stdout, which only gets the summary.
+    ///
+    /// Missing parent directories are created, and the file is replaced atomically.
+    #[arg(long, value_name = "PATH", env = "PYROGEN_OUTPUT_FILE")]
+    pub output_file: Option<PathBuf>,
+
+    /// Print at most this many violations, followed by the number of the others. The summary
+    /// and the exit status still count every violation, and the JSON output format always has
+    /// all of them.
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub max_violations: Option<usize>,
+    /// Like --max-violations, but for the violations of each file.
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub max_violations_per_file: Option<usize>,
+    /// Fail the check if any code has more violations than recorded in this file, instead of if
+    /// there are any errors. The file is created with the current counts if it doesn't exist.
+    #[arg(long, value_name = "PATH", env = "PYROGEN_RATCHET")]
+    pub ratchet: Option<PathBuf>,
+    /// Lower the counts in the --ratchet file to the current counts where they went down.
+    #[arg(long, requires = "ratchet")]
+    pub update_ratchet: bool,
+    /// Report only a sample of the warnings, infos and hints, like `10%` of them or
+    /// `5-per-file`, to fix them a batch at a time. Errors are always reported.
+    ///
+    /// The sample is chosen by the fingerprints of the violations, so it stays the same from run
+    /// to run. The exit status only counts the reported violations.
+    #[arg(long, value_name = "PERCENT|N-per-file")]
+    pub sample: Option<Sample>,
+    /// Replace the workspace and home directories in the paths of the report with `<workspace>`
+    /// and `~`, for sharing it without revealing the layout of the machine or the user name.
+    #[arg(long, env = "PYROGEN_REDACT_PATHS")]
+    pub redact_paths: bool,
+
+    /// Apply the fixes of the violations that have one, and write the fixed files back.
+    ///
+    /// Only the violations that remain after fixing are reported. With `--stdin-filename`, the
+    /// fixed source is written to stdout.
+    #[arg(long)]
+    pub fix: bool,
+    /// Print a unified diff of the fixes to stdout instead of writing them back.
+    #[arg(long)]
+    pub diff: bool,
+    /// Write the fixes of the reported violations to this JSON file, as edits of byte ranges
+    /// along with the SHA-256 of each file, for tools that apply them selectively.
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["fix", "diff", "add_ignore", "watch"]
+    )]
+    pub fixes_out: Option<PathBuf>,
+    /// Print the number of violations of each code instead of the violations, in the text or
+    /// JSON output format.
+    #[arg(long, conflicts_with_all = ["diff", "watch", "output_file"])]
+    pub statistics: bool,
+    /// Print the `type: ignore` comments of the files, with the owners and expiry dates of their
+    /// `# pyrogen: owner=... expires=...` annotations, and the number of comments per owner,
+    /// instead of checking the files. Supports the text and JSON output formats.
+    #[arg(
+        long,
+        conflicts_with_all = ["add_ignore", "fix", "diff", "watch", "stdin_filename", "statistics", "show_files"]
+    )]
+    pub suppressions: bool,
+    /// Print the documentation of an error code and exit.
+    #[arg(long, value_name = "CODE")]
+    pub explain: Option<ErrorCode>,
+    /// Add a `type: ignore[...]` comment for every diagnostic, to adopt a stricter configuration
+    /// in an existing code base, and report nothing.
+    ///
+    /// The codes are added to an existing `type: ignore[...]` comment of the line. Files with
+    /// syntax errors are left alone.
+    #[arg(
+        long,
+        conflicts_with_all = ["fix", "diff", "watch", "stdin_filename", "output_file"]
+    )]
+    pub add_ignore: bool,
+
     /// Respect file exclusions via `.gitignore` and other standard ignore files.
     /// Use `--no-respect-gitignore` to disable.
     #[arg(
@@ -127,6 +614,17 @@ pub struct CheckCommand {
     force_exclude: bool,
     #[clap(long, overrides_with("force_exclude"), hide = true)]
     no_force_exclude: bool,
+    /// List the files and directories that were skipped during file discovery on stderr, along
+    /// with the reason (e.g., `exclude`, `.gitignore` or an unsupported extension).
+    #[arg(long, help_heading = "File selection")]
+    pub show_skipped: bool,
+    /// List the files that would be checked, sorted by path, instead of checking them.
+    #[arg(
+        long,
+        conflicts_with_all = ["add_ignore", "fix", "diff", "watch", "stdin_filename", "statistics"],
+        help_heading = "File selection"
+    )]
+    pub show_files: bool,
     /// Disable cache reads.
     #[arg(short, long, help_heading = "Miscellaneous")]
     pub no_cache: bool,
@@ -142,6 +640,85 @@ pub struct CheckCommand {
     /// Exit with status code "0", even upon detecting lint violations.
     #[arg(short, long, help_heading = "Miscellaneous")]
     pub exit_zero: bool,
+    /// Exit with status code "3" if there are warnings but no errors, so that scripts can tell
+    /// them apart from errors (status code "1").
+    #[arg(long, conflicts_with = "exit_zero", help_heading = "Miscellaneous")]
+    pub exit_non_zero_on_warning: bool,
+    /// Exit with status code "0" unless there are at least this many errors. Fewer errors are
+    /// treated like warnings.
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with = "exit_zero",
+        help_heading = "Miscellaneous"
+    )]
+    pub exit_zero_unless_error_count: Option<u64>,
+    /// Comma-separated list of rule codes that stop the run as soon as one of their diagnostics
+    /// is produced, reporting only those diagnostics (like `--fail-fast syntax-error`).
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "ERROR_CODE",
+        value_parser = ErrorCodeSelectorParser,
+        help_heading = "Miscellaneous",
+        hide_possible_values = true
+    )]
+    pub fail_fast: Option<Vec<ErrorCodeSelector>>,
+    /// Run in watch mode by re-running whenever files change.
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = ["stdin_filename", "output_file", "diff"],
+        help_heading = "Miscellaneous"
+    )]
+    pub watch: bool,
+    /// Write a profile of where time is spent (per file, phase and rule) to the given path, as
+    /// folded stacks for flamegraph tools such as `inferno-flamegraph`.
+    #[arg(long, value_name = "PATH", help_heading = "Miscellaneous")]
+    pub profile: Option<PathBuf>,
+    /// Report the rules that took the most time, summed over all files.
+    #[arg(long, help_heading = "Miscellaneous")]
+    pub timings: bool,
+    /// Report the time spent in each phase (discovery, tokenize, parse, bind, check and cache IO),
+    /// in total and for the slowest files. With `--phase-timings=json`, every file is reported,
+    /// in milliseconds.
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "text",
+        help_heading = "Miscellaneous"
+    )]
+    pub phase_timings: Option<TimingFormat>,
+    /// The number of threads to check files with (defaults to the number of CPUs). With `1`,
+    /// files are checked one after another in path order, which makes runs reproducible when
+    /// debugging issues that depend on the order of the checks.
+    #[arg(
+        long,
+        env = "PYROGEN_THREADS",
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        help_heading = "Miscellaneous"
+    )]
+    pub threads: Option<usize>,
+    /// Send the check to the daemon started with `pyrogen daemon` in the current directory, and
+    /// print its output.
+    #[arg(long, help_heading = "Miscellaneous")]
+    pub use_daemon: bool,
+    /// The socket of the daemon, instead of `daemon.sock` in the cache directory.
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "use_daemon",
+        help_heading = "Miscellaneous"
+    )]
+    pub daemon_socket: Option<PathBuf>,
+    /// Serve check requests from a build tool using Bazel's persistent worker protocol.
+    #[arg(long = "persistent_worker", hide = true)]
+    pub persistent_worker: bool,
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -196,12 +773,42 @@ impl CheckCommand {
     pub fn partition(self) -> (CheckArguments, CliOverrides) {
         (
             CheckArguments {
+                add_ignore: self.add_ignore,
+                changed_lines_only: self.changed_lines_only,
                 config: self.config,
+                diff: self.diff,
+                diff_base: self.diff_base,
+                exit_non_zero_on_warning: self.exit_non_zero_on_warning,
                 exit_zero: self.exit_zero,
+                exit_zero_unless_error_count: self.exit_zero_unless_error_count,
+                explain: self.explain,
+                fix: self.fix,
+                fixes_out: self.fixes_out,
                 files: self.files,
+                files_from: self.files_from,
                 no_cache: self.no_cache,
+                null_separated: self.null_separated,
                 isolated: self.isolated,
+                max_violations: self.max_violations,
+                max_violations_per_file: self.max_violations_per_file,
+                output_file: self.output_file,
+                profile: self.profile,
+                ratchet: self.ratchet,
+                redact_paths: self.redact_paths,
+                sample: self.sample,
+                show_files: self.show_files,
+                show_skipped: self.show_skipped,
+                statistics: self.statistics,
+                suppressions: self.suppressions,
                 stdin_filename: self.stdin_filename,
+                threads: self.threads,
+                phase_timings: self.phase_timings,
+                timings: self.timings,
+                update_ratchet: self.update_ratchet,
+                watch: self.watch,
+                use_daemon: self.use_daemon,
+                daemon_socket: self.daemon_socket,
+                persistent_worker: self.persistent_worker,
             },
             CliOverrides {
                 exclude: self.exclude,
@@ -214,12 +821,24 @@ impl CheckCommand {
                 extend_error: self.extend_error,
                 warning: self.warning,
                 extend_warning: self.extend_warning,
+                info: self.info,
+                hint: self.hint,
                 ignore: self.ignore,
                 target_version: self.target_version,
+                platform: self.platform,
+                python_path: self.python_path,
                 // TODO(charlie): Included in `pyproject.toml`, but not inherited.
                 cache_dir: self.cache_dir,
                 force_exclude: resolve_bool_arg(self.force_exclude, self.no_force_exclude),
                 output_format: self.output_format,
+                output_template: self.output_template,
+                locale: self.locale,
+                fail_fast: self.fail_fast,
+                respect_type_ignore: resolve_bool_arg(
+                    self.respect_type_ignore,
+                    self.no_respect_type_ignore,
+                ),
+                preview: resolve_bool_arg(self.preview, self.no_preview),
             },
         )
     }
@@ -238,12 +857,42 @@ fn resolve_bool_arg(yes: bool, no: bool) -> Option<bool> {
 /// etc.).
 #[allow(clippy::struct_excessive_bools)]
 pub struct CheckArguments {
+    pub add_ignore: bool,
+    pub changed_lines_only: bool,
     pub config: Option<PathBuf>,
+    pub diff: bool,
+    pub diff_base: Option<String>,
+    pub exit_non_zero_on_warning: bool,
     pub exit_zero: bool,
+    pub exit_zero_unless_error_count: Option<u64>,
+    pub explain: Option<ErrorCode>,
+    pub fix: bool,
+    pub fixes_out: Option<PathBuf>,
     pub files: Vec<PathBuf>,
+    pub files_from: Option<PathBuf>,
     pub isolated: bool,
+    pub max_violations: Option<usize>,
+    pub max_violations_per_file: Option<usize>,
     pub no_cache: bool,
+    pub null_separated: bool,
+    pub output_file: Option<PathBuf>,
+    pub profile: Option<PathBuf>,
+    pub ratchet: Option<PathBuf>,
+    pub redact_paths: bool,
+    pub sample: Option<Sample>,
+    pub show_files: bool,
+    pub show_skipped: bool,
+    pub statistics: bool,
+    pub suppressions: bool,
     pub stdin_filename: Option<PathBuf>,
+    pub threads: Option<usize>,
+    pub phase_timings: Option<TimingFormat>,
+    pub timings: bool,
+    pub update_ratchet: bool,
+    pub watch: bool,
+    pub use_daemon: bool,
+    pub daemon_socket: Option<PathBuf>,
+    pub persistent_worker: bool,
 }
 
 /// CLI settings that function as configuration overrides.
@@ -257,12 +906,21 @@ pub struct CliOverrides {
     pub extend_error: Option<Vec<ErrorCodeSelector>>,
     pub warning: Option<Vec<ErrorCodeSelector>>,
     pub extend_warning: Option<Vec<ErrorCodeSelector>>,
+    pub info: Option<Vec<ErrorCodeSelector>>,
+    pub hint: Option<Vec<ErrorCodeSelector>>,
     pub ignore: Option<Vec<ErrorCodeSelector>>,
     pub target_version: Option<PythonVersion>,
+    pub platform: Option<PythonPlatform>,
+    pub python_path: Option<PathBuf>,
     // TODO(charlie): Captured in pyproject.toml as a default, but not part of `Settings`.
     pub cache_dir: Option<PathBuf>,
     pub force_exclude: Option<bool>,
     pub output_format: Option<SerializationFormat>,
+    pub output_template: Option<String>,
+    pub locale: Option<Locale>,
+    pub fail_fast: Option<Vec<ErrorCodeSelector>>,
+    pub respect_type_ignore: Option<bool>,
+    pub preview: Option<bool>,
 }
 
 impl ConfigurationTransformer for CliOverrides {
@@ -280,12 +938,23 @@ impl ConfigurationTransformer for CliOverrides {
             error: self.error.clone(),
             warning: self.warning.clone(),
             ignore: self.ignore.iter().flatten().cloned().collect(),
+            severity: Vec::new(),
             extend_error: self.extend_error.clone().unwrap_or_default(),
             extend_warning: self.extend_warning.clone().unwrap_or_default(),
+            info: self.info.clone(),
+            hint: self.hint.clone(),
+            source: None,
+            locations: vec![],
         });
         if let Some(output_format) = &self.output_format {
             config.output_format = Some(*output_format);
         }
+        if let Some(output_template) = &self.output_template {
+            config.output_template = Some(output_template.clone());
+        }
+        if let Some(locale) = &self.locale {
+            config.locale = Some(*locale);
+        }
         if let Some(force_exclude) = &self.force_exclude {
             config.force_exclude = Some(*force_exclude);
         }
@@ -295,6 +964,21 @@ impl ConfigurationTransformer for CliOverrides {
         if let Some(target_version) = &self.target_version {
             config.target_version = Some(*target_version);
         }
+        if let Some(platform) = &self.platform {
+            config.platform = Some(*platform);
+        }
+        if let Some(python_path) = &self.python_path {
+            config.python_path = Some(python_path.clone());
+        }
+        if let Some(fail_fast) = &self.fail_fast {
+            config.fail_fast = Some(fail_fast.clone());
+        }
+        if let Some(respect_type_ignore) = &self.respect_type_ignore {
+            config.respect_type_ignore = Some(*respect_type_ignore);
+        }
+        if let Some(preview) = &self.preview {
+            config.preview = Some(*preview);
+        }
 
         config
     }
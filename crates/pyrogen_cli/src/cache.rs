@@ -1,30 +1,99 @@
 use std::collections::HashMap;
+use std::fmt;
 use std::fs::{self, File};
 use std::hash::Hasher;
-use std::io::{self, BufReader, BufWriter, Write};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use filetime::FileTime;
+use log::debug;
+use pyrogen_checker::fix::Fix;
+use pyrogen_checker::interface::ModuleInterface;
 use pyrogen_checker::registry::DiagnosticKind;
 use pyrogen_checker::settings::code_table::MessageKind;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-use pyrogen_cache::{CacheKey, CacheKeyHasher};
+use pyrogen_cache::{CacheInvalidation, CacheKey, CacheKeyHasher};
 use pyrogen_checker::message::Message;
 use pyrogen_checker::warn_user;
+use pyrogen_module_resolver::ModuleName;
 use pyrogen_python_ast::imports::ImportMap;
+use pyrogen_python_ast::PySourceType;
 use pyrogen_source_file::SourceFileBuilder;
 use pyrogen_workspace::Settings;
 use rustpython_parser::text_size::{TextRange, TextSize};
 
-use crate::diagnostics::Messages;
+use crate::diagnostics::{FileCacheKey, Messages};
+
+/// The clock and the file metadata that decide whether cached results are still valid, so that
+/// tests can control them.
+pub(crate) trait SystemAdapter: fmt::Debug + Send + Sync {
+    /// The current time.
+    fn now(&self) -> SystemTime;
+    /// The metadata of the file at `path` that its cache key is made of.
+    fn file_metadata(&self, path: &Path) -> io::Result<FileMetadata>;
+    /// A hash of the contents of the file at `path`, for `cache-invalidation = "content"`.
+    fn content_hash(&self, path: &Path) -> io::Result<u64>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FileMetadata {
+    pub(crate) last_modified: FileTime,
+    /// The permission bits on Unix, and whether the file is read-only on Windows.
+    pub(crate) permissions: u32,
+}
+
+/// The actual clock and file system.
+#[derive(Debug)]
+pub(crate) struct RealSystem;
+
+impl SystemAdapter for RealSystem {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn file_metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        let metadata = path.metadata()?;
+
+        #[cfg(unix)]
+        let permissions = metadata.permissions().mode();
+        #[cfg(windows)]
+        let permissions: u32 = metadata.permissions().readonly().into();
+
+        Ok(FileMetadata {
+            last_modified: FileTime::from_last_modification_time(&metadata),
+            permissions,
+        })
+    }
+
+    fn content_hash(&self, path: &Path) -> io::Result<u64> {
+        let mut hasher = CacheKeyHasher::new();
+        hasher.write(&fs::read(path)?);
+        Ok(hasher.finish())
+    }
+}
 
 /// Maximum duration for which we keep a file in cache that hasn't been seen.
 const MAX_LAST_SEEN: Duration = Duration::from_secs(30 * 24 * 60 * 60); // 30 days.
 
+/// The bytes that cache files start with.
+const MAGIC: &[u8; 8] = b"PYROGEN\0";
+
+/// The version of the layout of cache files, which follows [`MAGIC`]. Increment it when the
+/// serialized [`PackageCache`] or [`CachedInterface`] changes, so that older caches are
+/// discarded instead of being misread by a development build with the same package version.
+const FORMAT_VERSION: u32 = 3;
+
+/// The zstd compression level of cache files, which favors speed over size.
+const COMPRESSION_LEVEL: i32 = 1;
+
 /// [`Path`] that is relative to the package root in [`PackageCache`].
 pub(crate) type RelativePath = Path;
 /// [`PathBuf`] that is relative to the package root in [`PackageCache`].
@@ -53,6 +122,9 @@ pub(crate) struct Cache {
     /// The "current" timestamp used as cache for the updates of
     /// [`FileCache::last_seen`]
     last_seen_cache: u64,
+    /// Whether the files are keyed by their metadata or their contents.
+    invalidation: CacheInvalidation,
+    system: Arc<dyn SystemAdapter>,
 }
 
 impl Cache {
@@ -65,6 +137,15 @@ impl Cache {
     /// Finally `settings` is used to ensure we don't open a cache for different
     /// settings. It also defines the directory where to store the cache.
     pub(crate) fn open(package_root: PathBuf, settings: &Settings) -> Cache {
+        Cache::open_with_system(package_root, settings, Arc::new(RealSystem))
+    }
+
+    /// Like [`Cache::open`], but with the clock and file metadata of `system`.
+    pub(crate) fn open_with_system(
+        package_root: PathBuf,
+        settings: &Settings,
+        system: Arc<dyn SystemAdapter>,
+    ) -> Cache {
         debug_assert!(package_root.is_absolute(), "package root not canonicalized");
 
         let mut buf = itoa::Buffer::new();
@@ -75,19 +156,21 @@ impl Cache {
             Ok(file) => file,
             Err(err) if err.kind() == io::ErrorKind::NotFound => {
                 // No cache exist yet, return an empty cache.
-                return Cache::empty(path, package_root);
+                return Cache::empty(path, package_root, settings.cache_invalidation, system);
             }
             Err(err) => {
                 warn_user!("Failed to open cache file '{}': {err}", path.display());
-                return Cache::empty(path, package_root);
+                return Cache::empty(path, package_root, settings.cache_invalidation, system);
             }
         };
 
-        let mut package: PackageCache = match bincode::deserialize_from(BufReader::new(file)) {
+        let mut package: PackageCache = match read_cache_file(BufReader::new(file)) {
             Ok(package) => package,
             Err(err) => {
-                warn_user!("Failed parse cache file '{}': {err}", path.display());
-                return Cache::empty(path, package_root);
+                // Caches of other versions and truncated files are expected, and rebuilt on the
+                // next store.
+                debug!("Discarding cache file '{}': {err:#}", path.display());
+                return Cache::empty(path, package_root, settings.cache_invalidation, system);
             }
         };
 
@@ -100,30 +183,57 @@ impl Cache {
             );
             package.files.clear();
         }
-        Cache::new(path, package)
+        Cache::new(path, package, settings.cache_invalidation, system)
     }
 
     /// Create an empty `Cache`.
-    fn empty(path: PathBuf, package_root: PathBuf) -> Cache {
+    fn empty(
+        path: PathBuf,
+        package_root: PathBuf,
+        invalidation: CacheInvalidation,
+        system: Arc<dyn SystemAdapter>,
+    ) -> Cache {
         let package = PackageCache {
             package_root,
             files: HashMap::new(),
         };
-        Cache::new(path, package)
+        Cache::new(path, package, invalidation, system)
     }
 
     #[allow(clippy::cast_possible_truncation)]
-    fn new(path: PathBuf, package: PackageCache) -> Cache {
+    fn new(
+        path: PathBuf,
+        package: PackageCache,
+        invalidation: CacheInvalidation,
+        system: Arc<dyn SystemAdapter>,
+    ) -> Cache {
         Cache {
             path,
             package,
             new_files: Mutex::new(HashMap::new()),
             // SAFETY: this will be truncated to the year ~2554 (so don't use
             // this code after that!).
-            last_seen_cache: SystemTime::UNIX_EPOCH.elapsed().unwrap().as_millis() as u64,
+            last_seen_cache: system
+                .now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            invalidation,
+            system,
         }
     }
 
+    /// The key that the cached results of the file at `path` are valid for, made of its metadata
+    /// or its contents, depending on `cache-invalidation`.
+    pub(crate) fn file_key(&self, path: &Path) -> io::Result<FileCacheKey> {
+        Ok(match self.invalidation {
+            CacheInvalidation::Metadata => FileCacheKey::from(self.system.file_metadata(path)?),
+            CacheInvalidation::Content => FileCacheKey::Content {
+                hash: self.system.content_hash(path)?,
+            },
+        })
+    }
+
     /// Store the cache to disk, if it has been changed.
     #[allow(clippy::cast_possible_truncation)]
     pub(crate) fn store(mut self) -> Result<()> {
@@ -146,8 +256,7 @@ impl Cache {
 
         let file = File::create(&self.path)
             .with_context(|| format!("Failed to create cache file '{}'", self.path.display()))?;
-        let writer = BufWriter::new(file);
-        bincode::serialize_into(writer, &self.package).with_context(|| {
+        write_cache_file(BufWriter::new(file), &self.package).with_context(|| {
             format!(
                 "Failed to serialise cache to file '{}'",
                 self.path.display()
@@ -209,6 +318,7 @@ impl Cache {
                 CacheMessage {
                     diagnostic: msg.diagnostic.clone(),
                     range: msg.range,
+                    fix: msg.fix.clone(),
                     ignore_offset: msg.ignore_offset,
                     kind: msg.kind,
                 }
@@ -275,6 +385,7 @@ impl FileCache {
                 .map(|msg| Message {
                     diagnostic: msg.diagnostic.clone(),
                     range: msg.range,
+                    fix: msg.fix.clone(),
                     file: file.clone(),
                     ignore_offset: msg.ignore_offset,
                     kind: msg.kind,
@@ -291,10 +402,169 @@ struct CacheMessage {
     diagnostic: DiagnosticKind,
     /// Range into the message's [`FileCache::source`].
     range: TextRange,
+    fix: Option<Fix>,
     ignore_offset: TextSize,
     kind: MessageKind,
 }
 
+/// The interface caches that `pyrogen daemon` keeps in memory between checks, by path, or `None`
+/// if they're only kept on disk.
+static RETAINED_INTERFACES: Mutex<Option<HashMap<PathBuf, InterfaceCache>>> = Mutex::new(None);
+
+/// Keep the interface caches in memory once they're stored, so that later checks in the same
+/// process don't need to read them from disk.
+pub(crate) fn retain_interfaces() {
+    RETAINED_INTERFACES
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new);
+}
+
+/// The interfaces of modules, keyed by their source, so that building the module graph doesn't
+/// need to parse the files that didn't change.
+///
+/// Interfaces don't depend on the settings, so unlike [`Cache`], there's one per cache directory
+/// rather than one per package and settings. Modules whose interfaces changed invalidate the
+/// cached results of the modules that import them through [`ProjectModule::dependencies`].
+///
+/// [`ProjectModule::dependencies`]: crate::diagnostics::ProjectModule::dependencies
+#[derive(Debug)]
+pub(crate) struct InterfaceCache {
+    path: PathBuf,
+    modules: HashMap<u64, CachedInterface>,
+    /// Modules that were summarized because they aren't in `modules`.
+    new_modules: Mutex<HashMap<u64, CachedInterface>>,
+    /// The "current" timestamp used as cache for the updates of
+    /// [`CachedInterface::last_seen`]
+    last_seen_cache: u64,
+}
+
+impl InterfaceCache {
+    /// Open or create the interface cache in `cache_dir`.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn open(cache_dir: &Path) -> Self {
+        let mut hasher = CacheKeyHasher::new();
+        env!("CARGO_PKG_VERSION").cache_key(&mut hasher);
+        let mut buf = itoa::Buffer::new();
+        let path = PathBuf::from_iter([
+            cache_dir,
+            Path::new("interfaces"),
+            Path::new(buf.format(hasher.finish())),
+        ]);
+        let last_seen_cache = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("system clock before 1970")
+            .as_millis() as u64;
+
+        if let Some(mut cache) = RETAINED_INTERFACES
+            .lock()
+            .unwrap()
+            .as_mut()
+            .and_then(|retained| retained.remove(&path))
+        {
+            cache.last_seen_cache = last_seen_cache;
+            return cache;
+        }
+
+        let modules = match File::open(&path) {
+            Ok(file) => read_cache_file(BufReader::new(file)).unwrap_or_else(|err| {
+                debug!("Discarding cache file '{}': {err:#}", path.display());
+                HashMap::new()
+            }),
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    warn_user!("Failed to open cache file '{}': {err}", path.display());
+                }
+                HashMap::new()
+            }
+        };
+
+        Self {
+            path,
+            modules,
+            new_modules: Mutex::new(HashMap::new()),
+            last_seen_cache,
+        }
+    }
+
+    /// The key of the interface of `module`, whose file has the given `source`.
+    pub(crate) fn key(
+        source: &str,
+        source_type: PySourceType,
+        module: &ModuleName,
+        is_package: bool,
+    ) -> u64 {
+        let mut hasher = CacheKeyHasher::new();
+        source.cache_key(&mut hasher);
+        source_type.is_stub().cache_key(&mut hasher);
+        module.as_str().cache_key(&mut hasher);
+        is_package.cache_key(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The cached dependencies and interface of the module with `key`.
+    pub(crate) fn get(&self, key: u64) -> Option<(Vec<ModuleName>, ModuleInterface)> {
+        let module = self.modules.get(&key)?;
+        module
+            .last_seen
+            .store(self.last_seen_cache, Ordering::Relaxed);
+        Some((module.dependencies.clone(), module.interface.clone()))
+    }
+
+    pub(crate) fn insert(
+        &self,
+        key: u64,
+        dependencies: &[ModuleName],
+        interface: &ModuleInterface,
+    ) {
+        let module = CachedInterface {
+            last_seen: AtomicU64::new(self.last_seen_cache),
+            dependencies: dependencies.to_vec(),
+            interface: interface.clone(),
+        };
+        self.new_modules.lock().unwrap().insert(key, module);
+    }
+
+    /// Write the interfaces back to disk, dropping the ones that haven't been used for
+    /// [`MAX_LAST_SEEN`]. With [`retain_interfaces`], they're kept in memory as well.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn store(mut self) -> Result<()> {
+        let new_modules = std::mem::take(self.new_modules.get_mut().unwrap());
+        if !new_modules.is_empty() {
+            let now = self.last_seen_cache;
+            self.modules.retain(|_, module| {
+                (now - *module.last_seen.get_mut()) <= MAX_LAST_SEEN.as_millis() as u64
+            });
+            self.modules.extend(new_modules);
+
+            let file = File::create(&self.path).with_context(|| {
+                format!("Failed to create cache file '{}'", self.path.display())
+            })?;
+            write_cache_file(BufWriter::new(file), &self.modules).with_context(|| {
+                format!(
+                    "Failed to serialise cache to file '{}'",
+                    self.path.display()
+                )
+            })?;
+        }
+
+        if let Some(retained) = RETAINED_INTERFACES.lock().unwrap().as_mut() {
+            retained.insert(self.path.clone(), self);
+        }
+        Ok(())
+    }
+}
+
+/// On disk representation of the summary of a module in the [`InterfaceCache`].
+#[derive(Deserialize, Debug, Serialize)]
+struct CachedInterface {
+    /// Timestamp when the interface was last used, in milliseconds since the Unix epoch.
+    last_seen: AtomicU64,
+    /// The modules that the module imports.
+    dependencies: Vec<ModuleName>,
+    interface: ModuleInterface,
+}
+
 /// Returns a hash key based on the `package_root`, `settings` and the crate
 /// version.
 fn cache_key(package_root: &Path, settings: &Settings) -> u64 {
@@ -302,13 +572,62 @@ fn cache_key(package_root: &Path, settings: &Settings) -> u64 {
     env!("CARGO_PKG_VERSION").cache_key(&mut hasher);
     package_root.cache_key(&mut hasher);
     settings.cache_key(&mut hasher);
+    // Imports of third-party modules resolve differently once distributions are installed,
+    // upgraded or removed, which the settings don't reflect.
+    installed_distributions(&settings.checker.site_packages).cache_key(&mut hasher);
     hasher.finish()
 }
 
+/// The sorted names of the `*.dist-info` directories in the `site_packages` directories, which
+/// hold the name and the version of each installed distribution.
+fn installed_distributions(site_packages: &[PathBuf]) -> Vec<String> {
+    let mut names: Vec<String> = site_packages
+        .iter()
+        .filter_map(|root| root.read_dir().ok())
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".dist-info"))
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+/// Read a cache written by [`write_cache_file`], failing if it was written in another format or
+/// is corrupt.
+fn read_cache_file<T: DeserializeOwned>(mut reader: impl Read) -> Result<T> {
+    let mut header = [0; MAGIC.len() + 4];
+    reader
+        .read_exact(&mut header)
+        .context("The file is too short")?;
+    let (magic, version) = header.split_at(MAGIC.len());
+    if magic != MAGIC {
+        bail!("The file isn't a pyrogen cache");
+    }
+    let version = u32::from_le_bytes(version.try_into()?);
+    if version != FORMAT_VERSION {
+        bail!("The file has format version {version}, expected {FORMAT_VERSION}");
+    }
+    let decoder = zstd::Decoder::new(reader)?;
+    bincode::deserialize_from(decoder).context("The contents are corrupt")
+}
+
+/// Write the `cache` as a header of [`MAGIC`] and [`FORMAT_VERSION`], followed by the compressed
+/// contents.
+fn write_cache_file<T: Serialize>(mut writer: impl Write, cache: &T) -> Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    let mut encoder = zstd::Encoder::new(writer, COMPRESSION_LEVEL)?;
+    bincode::serialize_into(&mut encoder, cache)?;
+    encoder.finish()?.flush()?;
+    Ok(())
+}
+
 /// Initialize the cache at the specified `Path`.
 pub(crate) fn init(path: &Path) -> Result<()> {
     // Create the cache directories.
     fs::create_dir_all(path.join("content"))?;
+    fs::create_dir_all(path.join("interfaces"))?;
 
     // Add the CACHEDIR.TAG.
     if !cachedir::is_tagged(path)? {
@@ -328,20 +647,29 @@ pub(crate) fn init(path: &Path) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use filetime::{set_file_mtime, FileTime};
+    use std::collections::HashMap;
     use std::env::temp_dir;
     use std::fs;
     use std::io;
     use std::io::Write;
     use std::path::{Path, PathBuf};
-    use std::time::SystemTime;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime};
 
     use itertools::Itertools;
-    use pyrogen_cache::CACHE_DIR_NAME;
+    use pyrogen_cache::{CacheInvalidation, CACHE_DIR_NAME};
+    use pyrogen_checker::cancellation::CancellationToken;
+    use pyrogen_checker::interface::summarize_module;
     use pyrogen_checker::registry::ErrorCode;
     use pyrogen_checker::settings::flags;
+    use pyrogen_module_resolver::ModuleName;
+    use pyrogen_python_ast::PySourceType;
 
     use crate::cache::RelativePathBuf;
-    use crate::cache::{self, Cache, FileCache};
+    use crate::cache::{
+        self, cache_key, Cache, FileCache, FileMetadata, InterfaceCache, RealSystem, SystemAdapter,
+        FORMAT_VERSION, MAGIC, MAX_LAST_SEEN,
+    };
     use crate::diagnostics::{type_check_path, Messages};
 
     use std::sync::atomic::AtomicU64;
@@ -401,6 +729,9 @@ mod tests {
                     &settings.checker,
                     Some(&cache),
                     flags::TypeIgnore::Enabled,
+                    flags::FixMode::Generate,
+                    None,
+                    &CancellationToken::default(),
                 )
                 .unwrap();
                 if diagnostics
@@ -445,6 +776,9 @@ mod tests {
                 &settings.checker,
                 Some(&cache),
                 flags::TypeIgnore::Enabled,
+                flags::FixMode::Generate,
+                None,
+                &CancellationToken::default(),
             )
             .unwrap();
         }
@@ -640,6 +974,203 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cache_invalidated_with_fake_system() {
+        let source: &[u8] = b"a = 1\n\n__all__ = list([\"a\", \"b\"])\n";
+
+        let test_cache = TestCache::new("cache_invalidated_with_fake_system");
+        let system = Arc::new(FakeSystem::new());
+        let path = test_cache.write_source_file("source.py", source);
+        let metadata = FileMetadata {
+            last_modified: FileTime::from_unix_time(1_000_000, 0),
+            permissions: 0o644,
+        };
+        system.set_metadata(&path, metadata);
+        let lint = |expected_new_files: usize| {
+            let cache = test_cache.open_with_system(system.clone());
+            test_cache
+                .lint_file_with_cache("source.py", &cache)
+                .expect("Failed to lint test file");
+            assert_eq!(cache.new_files.lock().unwrap().len(), expected_new_files);
+            cache.store().unwrap();
+        };
+
+        lint(1);
+        // Nothing changed.
+        lint(0);
+
+        system.set_metadata(
+            &path,
+            FileMetadata {
+                last_modified: FileTime::from_unix_time(1_000_001, 0),
+                ..metadata
+            },
+        );
+        lint(1);
+        lint(0);
+
+        system.set_metadata(
+            &path,
+            FileMetadata {
+                last_modified: FileTime::from_unix_time(1_000_001, 0),
+                permissions: 0o755,
+            },
+        );
+        lint(1);
+
+        // Files that haven't been seen for longer than `MAX_LAST_SEEN` are removed.
+        system.advance(MAX_LAST_SEEN + Duration::from_secs(1));
+        let other = test_cache.write_source_file("other.py", source);
+        system.set_metadata(&other, metadata);
+        let cache = test_cache.open_with_system(system.clone());
+        test_cache
+            .lint_file_with_cache("other.py", &cache)
+            .expect("Failed to lint test file");
+        cache.store().unwrap();
+        let cache = test_cache.open_with_system(system.clone());
+        assert_eq!(
+            cache.package.files.keys().collect_vec(),
+            vec![&RelativePathBuf::from("other.py")]
+        );
+    }
+
+    #[test]
+    fn cache_discards_incompatible_files() {
+        let source: &[u8] = b"a = 1\n\n__all__ = list([\"a\", \"b\"])\n";
+
+        let test_cache = TestCache::new("cache_discards_incompatible_files");
+        test_cache.write_source_file("source.py", source);
+        let cache = test_cache.open();
+        let path = cache.path.clone();
+        test_cache
+            .lint_file_with_cache("source.py", &cache)
+            .expect("Failed to lint test file");
+        cache.store().unwrap();
+
+        let stored = fs::read(&path).unwrap();
+        assert!(stored.starts_with(MAGIC));
+        assert_eq!(test_cache.open().package.files.len(), 1);
+
+        let mut other_version = stored.clone();
+        other_version[MAGIC.len()..MAGIC.len() + 4]
+            .copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        for contents in [
+            &stored[..stored.len() / 2],
+            &other_version[..],
+            b"not a cache",
+            b"",
+        ] {
+            fs::write(&path, contents).unwrap();
+            assert!(test_cache.open().package.files.is_empty());
+        }
+    }
+
+    #[test]
+    fn interface_cache_round_trip() {
+        let test_cache = TestCache::new("interface_cache_round_trip");
+        let source = "from pkg import other\n\ndef f(x: int, *, y: str = '') -> int: ...\n";
+        let module = ModuleName::new("pkg.module").unwrap();
+        let summary =
+            summarize_module(source, PySourceType::Python, &module, false).expect("valid module");
+        let key = InterfaceCache::key(source, PySourceType::Python, &module, false);
+
+        let cache = InterfaceCache::open(&test_cache.settings.cache_dir);
+        assert_eq!(cache.get(key), None);
+        cache.insert(key, &summary.0, &summary.1);
+        cache.store().unwrap();
+
+        let cache = InterfaceCache::open(&test_cache.settings.cache_dir);
+        assert_eq!(cache.get(key), Some(summary));
+        // The same source in another module has its own interface.
+        let other = ModuleName::new("pkg").unwrap();
+        assert_eq!(
+            cache.get(InterfaceCache::key(
+                source,
+                PySourceType::Python,
+                &other,
+                true
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn cache_keyed_on_content() {
+        let source: &[u8] = b"a = 1\n\n__all__ = list([\"a\", \"b\"])\n";
+
+        let mut test_cache = TestCache::new("cache_keyed_on_content");
+        test_cache.settings.cache_invalidation = CacheInvalidation::Content;
+        let path = test_cache.write_source_file("source.py", source);
+        let lint = |expected_new_files: usize| {
+            let cache = test_cache.open();
+            test_cache
+                .lint_file_with_cache("source.py", &cache)
+                .expect("Failed to lint test file");
+            assert_eq!(cache.new_files.lock().unwrap().len(), expected_new_files);
+            cache.store().unwrap();
+        };
+
+        lint(1);
+
+        // A checkout touches the file without changing it.
+        set_file_mtime(
+            &path,
+            FileTime::from_system_time(SystemTime::now() + Duration::from_secs(1)),
+        )
+        .unwrap();
+        lint(0);
+
+        test_cache.write_source_file("source.py", b"a = 2\n");
+        lint(1);
+        lint(0);
+    }
+
+    /// A clock and file metadata that only change when the test changes them.
+    #[derive(Debug)]
+    struct FakeSystem {
+        now: Mutex<SystemTime>,
+        files: Mutex<HashMap<PathBuf, FileMetadata>>,
+    }
+
+    impl FakeSystem {
+        fn new() -> Self {
+            Self {
+                now: Mutex::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+                files: Mutex::new(HashMap::new()),
+            }
+        }
+
+        fn advance(&self, duration: Duration) {
+            *self.now.lock().unwrap() += duration;
+        }
+
+        fn set_metadata(&self, path: &Path, metadata: FileMetadata) {
+            self.files
+                .lock()
+                .unwrap()
+                .insert(path.to_path_buf(), metadata);
+        }
+    }
+
+    impl SystemAdapter for FakeSystem {
+        fn now(&self) -> SystemTime {
+            *self.now.lock().unwrap()
+        }
+
+        fn file_metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+            self.files
+                .lock()
+                .unwrap()
+                .get(path)
+                .copied()
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+        }
+
+        fn content_hash(&self, path: &Path) -> io::Result<u64> {
+            RealSystem.content_hash(path)
+        }
+    }
+
     struct TestCache {
         package_root: PathBuf,
         settings: Settings,
@@ -689,6 +1220,10 @@ mod tests {
             Cache::open(self.package_root.clone(), &self.settings)
         }
 
+        fn open_with_system(&self, system: Arc<dyn SystemAdapter>) -> Cache {
+            Cache::open_with_system(self.package_root.clone(), &self.settings, system)
+        }
+
         fn lint_file_with_cache(
             &self,
             path: &str,
@@ -700,6 +1235,9 @@ mod tests {
                 &self.settings.checker,
                 Some(cache),
                 flags::TypeIgnore::Enabled,
+                flags::FixMode::Generate,
+                None,
+                &CancellationToken::default(),
             )
         }
     }
@@ -709,4 +1247,25 @@ mod tests {
             let _ = fs::remove_dir_all(&self.settings.cache_dir);
         }
     }
+
+    #[test]
+    fn installed_distributions() -> Result<()> {
+        let site_packages = tempfile::TempDir::new()?;
+        let mut settings = Settings::default();
+        settings.checker.site_packages = vec![site_packages.path().to_path_buf()];
+        let package_root = Path::new("/project");
+
+        let empty = cache_key(package_root, &settings);
+        fs::create_dir(site_packages.path().join("PyYAML-6.0.1.dist-info"))?;
+        let installed = cache_key(package_root, &settings);
+        assert_ne!(installed, empty);
+
+        fs::rename(
+            site_packages.path().join("PyYAML-6.0.1.dist-info"),
+            site_packages.path().join("PyYAML-6.0.2.dist-info"),
+        )?;
+        assert_ne!(cache_key(package_root, &settings), installed);
+
+        Ok(())
+    }
 }
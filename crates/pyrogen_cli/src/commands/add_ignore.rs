@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use itertools::Itertools;
+use log::warn;
+use rustpython_parser::ast::Ranged;
+use rustpython_parser::text_size::TextSize;
+
+use pyrogen_checker::cancellation::CancellationToken;
+use pyrogen_checker::fs;
+use pyrogen_checker::registry::ErrorCode;
+use pyrogen_checker::settings::flags;
+use pyrogen_checker::type_ignore::type_ignore_codes_edit;
+use pyrogen_python_ast::SourceType;
+use pyrogen_source_file::Locator;
+use pyrogen_workspace::resolver::PyprojectConfig;
+
+use crate::args::CliOverrides;
+use crate::commands::check::check;
+
+/// Check the files and add a `type: ignore[...]` comment for the diagnostics on each line, on the
+/// line that suppresses them (the last line of a multi-line string, for example).
+///
+/// Returns the number of lines that got a new or extended comment.
+pub(crate) fn add_ignore(
+    files: &[PathBuf],
+    pyproject_config: &PyprojectConfig,
+    overrides: &CliOverrides,
+    cache: flags::Cache,
+    show_skipped: bool,
+) -> Result<usize> {
+    let diagnostics = check(
+        files,
+        pyproject_config,
+        overrides,
+        cache,
+        Some(flags::TypeIgnore::Enabled),
+        flags::FixMode::Generate,
+        show_skipped,
+        &CancellationToken::default(),
+    )?;
+
+    let mut modified = 0;
+    for (filename, messages) in &diagnostics
+        .messages
+        .iter()
+        .group_by(|message| message.file.name())
+    {
+        let path = Path::new(filename);
+        if !matches!(SourceType::from(path), SourceType::Python(_)) {
+            continue;
+        }
+        let messages = messages.collect::<Vec<_>>();
+        if messages
+            .iter()
+            .any(|message| message.diagnostic.error_code == ErrorCode::SyntaxError)
+        {
+            warn!(
+                "Not adding `type: ignore` comments to {}, since it has syntax errors",
+                fs::relativize_path(path)
+            );
+            continue;
+        }
+
+        let source = messages[0].file.source_text();
+        let locator = Locator::new(source);
+        let mut codes_by_line: BTreeMap<TextSize, Vec<ErrorCode>> = BTreeMap::new();
+        for message in &messages {
+            let code = message.diagnostic.error_code;
+            // Suppressing an unused `type: ignore` comment would only make it longer, and an
+            // expired one or the notice of truncated diagnostics can't be suppressed.
+            if matches!(
+                code,
+                ErrorCode::UnusedTypeIgnore
+                    | ErrorCode::ExpiredSuppression
+                    | ErrorCode::TooManyDiagnostics
+                    | ErrorCode::IOError
+            ) {
+                continue;
+            }
+            codes_by_line
+                .entry(locator.line_start(message.ignore_offset))
+                .or_default()
+                .push(code);
+        }
+
+        let edits = codes_by_line
+            .into_iter()
+            .filter_map(|(line_start, mut codes)| {
+                codes.sort_unstable_by_key(ErrorCode::to_str);
+                codes.dedup();
+                type_ignore_codes_edit(&codes, line_start, &locator)
+            })
+            .collect::<Vec<_>>();
+        if edits.is_empty() {
+            continue;
+        }
+
+        // There's at most one edit per line, so they don't overlap.
+        let mut output = String::with_capacity(source.len());
+        let mut last_pos = TextSize::default();
+        for edit in &edits {
+            output.push_str(&source[usize::from(last_pos)..usize::from(edit.start())]);
+            output.push_str(edit.content().unwrap_or_default());
+            last_pos = edit.end();
+        }
+        output.push_str(&source[usize::from(last_pos)..]);
+
+        std::fs::write(path, output)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        modified += edits.len();
+    }
+
+    Ok(modified)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use pyrogen_checker::registry::ErrorCode;
+    use pyrogen_checker::settings::{flags, CheckerSettings};
+    use pyrogen_workspace::resolver::{PyprojectConfig, PyprojectDiscoveryStrategy};
+    use pyrogen_workspace::Settings;
+
+    use crate::args::CliOverrides;
+
+    use super::add_ignore;
+
+    #[test]
+    fn add_type_ignores() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        let path = tempdir.path().join("module.py");
+        std::fs::write(
+            &path,
+            "_a = {1: 1, 1: 2}  # type: ignore[call-arg]\n_b = ({1: 1, 1: 2}, \"\"\"\n\"\"\")\n_c = 1\n",
+        )?;
+
+        let settings = Settings {
+            checker: CheckerSettings::for_rules(vec![ErrorCode::DuplicateKey]),
+            ..Settings::default()
+        };
+        let pyproject_config =
+            PyprojectConfig::new(PyprojectDiscoveryStrategy::Fixed, settings, None);
+        let modified = add_ignore(
+            &[tempdir.path().to_path_buf()],
+            &pyproject_config,
+            &CliOverrides::default(),
+            flags::Cache::Disabled,
+            false,
+        )?;
+
+        assert_eq!(modified, 2);
+        assert_eq!(
+            std::fs::read_to_string(&path)?,
+            "_a = {1: 1, 1: 2}  # type: ignore[call-arg, duplicate-key]\n_b = ({1: 1, 1: 2}, \"\"\"\n\"\"\")  # type: ignore[duplicate-key]\n_c = 1\n"
+        );
+        Ok(())
+    }
+}
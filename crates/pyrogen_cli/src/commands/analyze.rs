@@ -0,0 +1,342 @@
+//! A JSON-RPC 2.0 interface to the checker, for build systems and other tools that want
+//! diagnostics for individual files without speaking the full language server protocol.
+//!
+//! Requests and responses are exchanged over stdio as JSON objects, one per line. The supported
+//! methods are:
+//!
+//! - `check`, with `{"path": "..."}` to check a file on disk or `{"source": "..."}` to check the
+//!   given source code (optionally with a `path` to name it). The result is
+//!   `{"diagnostics": [...]}`, in the format of `--output-format json`. The settings are those
+//!   that `pyrogen check` uses for the path.
+//! - `shutdown`, which ends the session after responding with `null`.
+
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use pyrogen_checker::cancellation::CancellationToken;
+use pyrogen_checker::fs;
+use pyrogen_checker::message::messages_to_json_values;
+use pyrogen_checker::packaging;
+use pyrogen_checker::settings::flags;
+use pyrogen_workspace::resolver::{python_files_in_path, PyprojectConfig, Resolver};
+
+use crate::args::CliOverrides;
+use crate::diagnostics::{type_check_path, type_check_stdin, Messages};
+use crate::panic::catch_unwind;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+#[derive(Deserialize)]
+struct Request {
+    jsonrpc: String,
+    /// Absent for notifications, which don't get a response.
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CheckParams {
+    path: Option<PathBuf>,
+    source: Option<String>,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Serve JSON-RPC requests read from `reader` until it is exhausted or a `shutdown` request is
+/// received, writing the responses to `writer`.
+pub(crate) fn analyze_json_rpc(
+    pyproject_config: &PyprojectConfig,
+    overrides: &CliOverrides,
+    reader: impl BufRead,
+    mut writer: impl Write,
+) -> Result<()> {
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let request = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => request,
+            Err(err) => {
+                respond(
+                    &mut writer,
+                    &Value::Null,
+                    Err(RpcError::new(PARSE_ERROR, err.to_string())),
+                )?;
+                continue;
+            }
+        };
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let request = match serde_json::from_value::<Request>(request) {
+            Ok(request) if request.jsonrpc == "2.0" => request,
+            Ok(_) => {
+                let error = RpcError::new(INVALID_REQUEST, "Only JSON-RPC 2.0 is supported");
+                respond(&mut writer, &id, Err(error))?;
+                continue;
+            }
+            Err(err) => {
+                respond(
+                    &mut writer,
+                    &id,
+                    Err(RpcError::new(INVALID_REQUEST, err.to_string())),
+                )?;
+                continue;
+            }
+        };
+
+        let shutdown = request.method == "shutdown";
+        let result = match request.method.as_str() {
+            "check" => check(pyproject_config, overrides, request.params),
+            "shutdown" => Ok(Value::Null),
+            method => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method `{method}`"),
+            )),
+        };
+        if let Some(id) = &request.id {
+            respond(&mut writer, id, result)?;
+        }
+        if shutdown {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn check(
+    pyproject_config: &PyprojectConfig,
+    overrides: &CliOverrides,
+    params: Value,
+) -> Result<Value, RpcError> {
+    let params: CheckParams = serde_json::from_value(params)
+        .map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))?;
+    // Like `pyrogen check`, use the settings of the closest `pyproject.toml` of the path.
+    let resolver = match &params.path {
+        Some(path) => {
+            python_files_in_path(&[path.clone()], pyproject_config, overrides)
+                .map_err(|err| RpcError::new(INTERNAL_ERROR, format!("{err:#}")))?
+                .1
+        }
+        None => Resolver::default(),
+    };
+    let settings = match &params.path {
+        Some(path) => resolver.resolve(&fs::normalize_path(path), pyproject_config),
+        None => &pyproject_config.settings,
+    };
+    let package_root = |path: &Path| {
+        path.parent().and_then(|parent| {
+            packaging::detect_package_root(parent, &settings.checker.namespace_packages)
+        })
+    };
+
+    // A panic in the checker fails the request, but not the session.
+    let messages = match (params.path.as_deref(), params.source) {
+        (path, Some(source)) => catch_unwind(|| {
+            type_check_stdin(
+                path,
+                path.and_then(package_root),
+                source,
+                settings,
+                flags::TypeIgnore::Enabled,
+                flags::FixMode::Generate,
+            )
+        }),
+        (Some(path), None) => catch_unwind(|| {
+            type_check_path(
+                path,
+                package_root(path),
+                &settings.checker,
+                None,
+                flags::TypeIgnore::Enabled,
+                flags::FixMode::Generate,
+                None,
+                &CancellationToken::default(),
+            )
+        }),
+        (None, None) => {
+            return Err(RpcError::new(
+                INVALID_PARAMS,
+                "Expected a `path` or a `source`",
+            ));
+        }
+    };
+    let Messages { mut messages, .. } = messages
+        .map_err(|error| {
+            RpcError::new(
+                INTERNAL_ERROR,
+                format!("Panicked while checking: {}", error.info),
+            )
+        })?
+        .map_err(|err| RpcError::new(INTERNAL_ERROR, format!("{err:#}")))?;
+    messages.sort_unstable();
+
+    Ok(json!({
+        "diagnostics": messages_to_json_values(
+            &messages,
+            settings.column_encoding,
+            Some(&settings.checker.project_root),
+        ),
+    }))
+}
+
+fn respond(writer: &mut impl Write, id: &Value, result: Result<Value, RpcError>) -> Result<()> {
+    let response = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(RpcError { code, message }) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message },
+        }),
+    };
+    serde_json::to_writer(&mut *writer, &response)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use serde_json::{json, Value};
+    use tempfile::TempDir;
+
+    use pyrogen_checker::registry::ErrorCode;
+    use pyrogen_checker::settings::CheckerSettings;
+    use pyrogen_workspace::resolver::{PyprojectConfig, PyprojectDiscoveryStrategy};
+    use pyrogen_workspace::Settings;
+
+    use crate::args::CliOverrides;
+
+    use super::analyze_json_rpc;
+
+    #[test]
+    fn check_requests() -> Result<()> {
+        let settings = Settings {
+            checker: CheckerSettings::for_rules(vec![
+                ErrorCode::SyntaxError,
+                ErrorCode::GeneralTypeError,
+            ]),
+            ..Settings::default()
+        };
+        let pyproject_config =
+            PyprojectConfig::new(PyprojectDiscoveryStrategy::Fixed, settings, None);
+
+        let requests = r#"{"jsonrpc": "2.0", "id": 1, "method": "check", "params": {"source": "x: int = 'a'\n", "path": "a.py"}}
+{"jsonrpc": "2.0", "id": 2, "method": "check", "params": {}}
+{"jsonrpc": "2.0", "id": 3, "method": "lint"}
+not json
+{"jsonrpc": "2.0", "method": "check", "params": {"source": ""}}
+{"jsonrpc": "2.0", "id": 4, "method": "shutdown"}
+{"jsonrpc": "2.0", "id": 5, "method": "check", "params": {"source": ""}}
+"#;
+        let mut output = Vec::new();
+        analyze_json_rpc(
+            &pyproject_config,
+            &CliOverrides::default(),
+            requests.as_bytes(),
+            &mut output,
+        )?;
+
+        let responses = String::from_utf8(output)?
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<Value>, _>>()?;
+        assert_eq!(responses.len(), 5);
+
+        let diagnostics = responses[0]["result"]["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["code"], "general");
+        assert_eq!(diagnostics[0]["filename"], "a.py");
+
+        assert_eq!(responses[1]["error"]["code"], -32602);
+        assert_eq!(responses[2]["error"]["code"], -32601);
+        assert_eq!(responses[3]["error"]["code"], -32700);
+        assert_eq!(responses[4]["id"], 4);
+        assert_eq!(responses[4]["result"], Value::Null);
+
+        Ok(())
+    }
+
+    #[test]
+    fn settings_of_the_path() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        let sub = tempdir.path().join("sub");
+        std::fs::create_dir_all(&sub)?;
+        std::fs::write(
+            sub.join("pyproject.toml"),
+            "[tool.pyrogen]\nignore = [\"general\"]\n",
+        )?;
+
+        let settings = Settings {
+            checker: CheckerSettings::for_rules(vec![ErrorCode::GeneralTypeError]),
+            ..Settings::default()
+        };
+        let pyproject_config =
+            PyprojectConfig::new(PyprojectDiscoveryStrategy::Hierarchical, settings, None);
+
+        let requests = [tempdir.path().join("a.py"), sub.join("a.py")]
+            .iter()
+            .enumerate()
+            .map(|(id, path)| {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": "check",
+                    "params": {"source": "x: int = 'a'\n", "path": path},
+                })
+                .to_string()
+                    + "\n"
+            })
+            .collect::<String>();
+        let mut output = Vec::new();
+        analyze_json_rpc(
+            &pyproject_config,
+            &CliOverrides::default(),
+            requests.as_bytes(),
+            &mut output,
+        )?;
+
+        let responses = String::from_utf8(output)?
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<Value>, _>>()?;
+        let codes = |response: &Value| {
+            response["result"]["diagnostics"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|diagnostic| diagnostic["code"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(codes(&responses[0]), ["general"]);
+        // The `pyproject.toml` of `sub` ignores the code.
+        assert!(!codes(&responses[1]).contains(&"general".to_string()));
+
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write;
+use std::hash::{Hash, Hasher};
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
 
 use anyhow::Result;
@@ -9,40 +11,94 @@ use colored::Colorize;
 use ignore::Error;
 use itertools::Itertools;
 use log::{debug, error, warn};
+use pyrogen_cache::CacheKeyHasher;
+use pyrogen_checker::cancellation::CancellationToken;
+use pyrogen_checker::dependencies::{self, used_distributions, Distributions};
+use pyrogen_checker::interface::{summarize_module, ModuleContext, ModuleInterfaces};
 use pyrogen_checker::message::Message;
+use pyrogen_checker::references::{find_references, ModuleReferences, ProjectReferences};
 use pyrogen_checker::registry::{Diagnostic, DiagnosticKind, ErrorCode};
 use pyrogen_checker::settings::code_table::MessageKind;
+use pyrogen_checker::timing::RULE_TIMINGS;
+use pyrogen_module_resolver::{ModuleGraph, ModuleName};
 use pyrogen_source_file::SourceFileBuilder;
 #[cfg(not(target_family = "wasm"))]
 use rayon::prelude::*;
+use rustc_hash::{FxHashMap, FxHashSet};
 use rustpython_parser::text_size::{TextRange, TextSize};
 
 use pyrogen_checker::settings::{flags, CheckerSettings};
-use pyrogen_checker::{fs, warn_user_once};
+use pyrogen_checker::{fs, warn_user, warn_user_once};
 use pyrogen_python_ast::imports::ImportMap;
+use pyrogen_python_ast::SourceType;
 use pyrogen_workspace::resolver::{
-    python_files_in_path, PyprojectConfig, PyprojectDiscoveryStrategy,
+    python_files_in_path, python_files_in_path_with_skipped, PyprojectConfig,
+    PyprojectDiscoveryStrategy, Resolver,
 };
 
 use crate::args::CliOverrides;
-use crate::cache::{self, Cache};
-use crate::diagnostics::Messages;
+use crate::cache::{self, Cache, InterfaceCache};
+use crate::diagnostics::{read_source, report_configuration_diagnostics, Messages, ProjectModule};
 use crate::panic::catch_unwind;
 
 /// Run the checker over a collection of files.
+///
+/// `type: ignore` comments are respected according to the `respect-type-ignore` setting of each
+/// file, unless `respect_type_ignore` overrides it for all files.
+///
+/// With `show_skipped`, the paths that file discovery skipped are reported on stderr.
+///
+/// Fails with [`Cancelled`](pyrogen_checker::cancellation::Cancelled) if `cancellation` is cancelled before all files are checked.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn check(
     files: &[PathBuf],
     pyproject_config: &PyprojectConfig,
     overrides: &CliOverrides,
     cache: flags::Cache,
-    respect_type_ignore: flags::TypeIgnore,
+    respect_type_ignore: Option<flags::TypeIgnore>,
+    fix_mode: flags::FixMode,
+    show_skipped: bool,
+    cancellation: &CancellationToken,
 ) -> Result<Messages> {
+    let check_span = tracing::debug_span!("check");
+    let _entered = check_span.enter();
+
     // Collect all the Python files to check.
     let start = Instant::now();
-    let (paths, resolver) = python_files_in_path(files, pyproject_config, overrides)?;
+    let (mut paths, skipped, resolver) = tracing::debug_span!("discover_files").in_scope(|| {
+        if show_skipped {
+            python_files_in_path_with_skipped(files, pyproject_config, overrides)
+        } else {
+            python_files_in_path(files, pyproject_config, overrides)
+                .map(|(paths, resolver)| (paths, vec![], resolver))
+        }
+    })?;
+    // Files are discovered in parallel, in no particular order. A single thread checks them in
+    // dependency order and then in path order, so that its runs are reproducible.
+    if rayon::current_num_threads() == 1 {
+        paths.sort_by(|a, b| {
+            let a = a.as_ref().ok().map(ignore::DirEntry::path);
+            let b = b.as_ref().ok().map(ignore::DirEntry::path);
+            a.cmp(&b)
+        });
+    }
     let duration = start.elapsed();
     debug!("Identified files to lint in: {:?}", duration);
 
+    // Report the files that file discovery skipped, and why. This goes to stderr, so that it
+    // doesn't end up in machine-readable output on stdout.
+    for skipped in &skipped {
+        warn_user!(
+            "Skipped {}: {}",
+            fs::relativize_path(&skipped.path),
+            skipped.reason
+        );
+    }
+
+    report_configuration_diagnostics(
+        std::iter::once(&pyproject_config.settings).chain(resolver.settings()),
+    );
+
     if paths.is_empty() {
         warn_user_once!("No Python files found under the given path(s)");
         return Ok(Messages::default());
@@ -82,6 +138,7 @@ pub(crate) fn check(
 
     // Load the caches.
     let caches = bool::from(cache).then(|| {
+        let _span = tracing::debug_span!("load_caches").entered();
         package_roots
             .iter()
             .map(|(package, package_root)| package_root.unwrap_or(package))
@@ -95,98 +152,193 @@ pub(crate) fn check(
             .collect::<HashMap<&Path, Cache>>()
     });
 
+    // Only the checks of calls into other modules and of dead code need to know about the
+    // project's other modules.
+    let enabled = |code| {
+        std::iter::once(&pyproject_config.settings)
+            .chain(resolver.settings())
+            .any(|settings| settings.checker.table.enabled(code))
+    };
+    let dead_code = enabled(ErrorCode::DeadCode);
+    let modules = if enabled(ErrorCode::CallArgument) || dead_code {
+        let _span = tracing::debug_span!("module_interfaces").entered();
+        let start = Instant::now();
+        let interface_cache =
+            bool::from(cache).then(|| InterfaceCache::open(&pyproject_config.settings.cache_dir));
+        let modules = ProjectModules::build(
+            &paths,
+            &package_roots,
+            dead_code.then_some(|path: &Path| &resolver.resolve(path, pyproject_config).checker),
+            interface_cache.as_ref(),
+            cancellation,
+        );
+        cancellation.check()?;
+        debug!(
+            "Built the interfaces of {} modules in: {:?}",
+            modules.graph.len(),
+            start.elapsed()
+        );
+        if let Some(interface_cache) = interface_cache {
+            interface_cache.store()?;
+        }
+        modules
+    } else {
+        ProjectModules::default()
+    };
+
+    // A diagnostic with a `fail-fast` code stops the checking of the files that haven't started.
+    let is_fail_fast = |message: &Message| {
+        resolver
+            .resolve(Path::new(message.file.name()), pyproject_config)
+            .fail_fast
+            .contains(message.diagnostic.error_code)
+    };
+    let aborted = AtomicBool::new(false);
+
     let start = Instant::now();
-    let mut diagnostics: Messages = paths
-        .par_iter()
-        .map(|entry| {
-            match entry {
-                Ok(entry) => {
-                    let path = entry.path();
-                    let package = path
-                        .parent()
-                        .and_then(|parent| package_roots.get(parent))
-                        .and_then(|package| *package);
-
-                    let settings = resolver.resolve(path, pyproject_config);
-
-                    let cache_root = package.unwrap_or_else(|| path.parent().unwrap_or(path));
-                    let cache = caches.as_ref().and_then(|caches| {
-                        if let Some(cache) = caches.get(&cache_root) {
-                            Some(cache)
-                        } else {
-                            debug!("No cache found for {}", cache_root.display());
-                            None
-                        }
-                    });
-
-                    lint_path(path, package, &settings.checker, cache, respect_type_ignore).map_err(
-                        |e| {
-                            (Some(path.to_owned()), {
-                                let mut error = e.to_string();
-                                for cause in e.chain() {
-                                    write!(&mut error, "\n  Cause: {cause}").unwrap();
-                                }
-                                error
-                            })
-                        },
-                    )
-                }
-                Err(e) => Err((
-                    if let Error::WithPath { path, .. } = e {
-                        Some(path.clone())
+    let check_entry = |entry: &Result<ignore::DirEntry, Error>| {
+        if aborted.load(Ordering::Relaxed) || cancellation.is_cancelled() {
+            return Messages::default();
+        }
+        let messages = match entry {
+            Ok(entry) => {
+                let path = entry.path();
+                // Files are checked on other threads, so the parent has to be explicit.
+                let _span = tracing::debug_span!(
+                    parent: check_span.id(),
+                    "check_file",
+                    path = %fs::relativize_path(path)
+                )
+                .entered();
+                let package = path
+                    .parent()
+                    .and_then(|parent| package_roots.get(parent))
+                    .and_then(|package| *package);
+
+                let settings = resolver.resolve(path, pyproject_config);
+
+                let cache_root = package.unwrap_or_else(|| path.parent().unwrap_or(path));
+                let cache = caches.as_ref().and_then(|caches| {
+                    if let Some(cache) = caches.get(&cache_root) {
+                        Some(cache)
                     } else {
+                        debug!("No cache found for {}", cache_root.display());
                         None
-                    },
-                    e.io_error()
-                        .map_or_else(|| e.to_string(), io::Error::to_string),
-                )),
-            }
-            .unwrap_or_else(|(path, message)| {
-                if let Some(path) = &path {
-                    let settings = resolver.resolve(path, pyproject_config);
-                    if settings.checker.table.enabled(ErrorCode::IOError) {
-                        let dummy =
-                            SourceFileBuilder::new(path.to_string_lossy().as_ref(), "").finish();
-
-                        Messages::new(
-                            vec![Message::from_diagnostic(
-                                Diagnostic::new(
-                                    DiagnosticKind {
-                                        error_code: ErrorCode::IOError,
-                                        body: message,
-                                    },
-                                    TextRange::default(),
-                                ),
-                                dummy,
-                                TextSize::default(),
-                                MessageKind::Error,
-                            )],
-                            ImportMap::default(),
-                        )
-                    } else {
-                        warn!(
-                            "{}{}{} {message}",
-                            "Failed to lint ".bold(),
-                            fs::relativize_path(path).bold(),
-                            ":".bold()
-                        );
-                        Messages::default()
                     }
+                });
+
+                let module = modules.module(path);
+
+                lint_path(
+                    path,
+                    package,
+                    &settings.checker,
+                    cache,
+                    respect_type_ignore.unwrap_or_else(|| settings.respect_type_ignore.into()),
+                    fix_mode,
+                    module.as_ref(),
+                    cancellation,
+                )
+                .map_err(|e| {
+                    (Some(path.to_owned()), {
+                        let mut error = e.to_string();
+                        for cause in e.chain() {
+                            write!(&mut error, "\n  Cause: {cause}").unwrap();
+                        }
+                        error
+                    })
+                })
+            }
+            Err(e) => Err((
+                if let Error::WithPath { path, .. } = e {
+                    Some(path.clone())
+                } else {
+                    None
+                },
+                e.io_error()
+                    .map_or_else(|| e.to_string(), io::Error::to_string),
+            )),
+        }
+        .unwrap_or_else(|(path, message)| {
+            // The check of the file was cancelled, which is reported once for all files.
+            if cancellation.is_cancelled() {
+                return Messages::default();
+            }
+            if let Some(path) = &path {
+                let settings = resolver.resolve(path, pyproject_config);
+                if settings.checker.table.enabled(ErrorCode::IOError) {
+                    let dummy =
+                        SourceFileBuilder::new(path.to_string_lossy().as_ref(), "").finish();
+
+                    Messages::new(
+                        vec![Message::from_diagnostic(
+                            Diagnostic::new(
+                                DiagnosticKind::new(ErrorCode::IOError, message),
+                                TextRange::default(),
+                            ),
+                            dummy,
+                            TextSize::default(),
+                            MessageKind::Error,
+                        )],
+                        ImportMap::default(),
+                    )
                 } else {
-                    warn!("{} {message}", "Encountered error:".bold());
+                    warn!(
+                        "{}{}{} {message}",
+                        "Failed to lint ".bold(),
+                        fs::relativize_path(path).bold(),
+                        ":".bold()
+                    );
                     Messages::default()
                 }
-            })
-        })
-        .reduce(Messages::default, |mut acc, item| {
-            acc += item;
-            acc
+            } else {
+                warn!("{} {message}", "Encountered error:".bold());
+                Messages::default()
+            }
         });
+        if messages.messages.iter().any(is_fail_fast) {
+            aborted.store(true, Ordering::Relaxed);
+        }
+        messages
+    };
+
+    // Check the modules level by level in dependency order: a module is only checked once the
+    // modules that it imports have been (unless they import each other), and the other files after
+    // all modules.
+    let mut diagnostics = Messages::default();
+    for level in dependency_levels(&paths, &modules) {
+        diagnostics += level.par_iter().map(|entry| check_entry(entry)).reduce(
+            Messages::default,
+            |mut acc, item| {
+                acc += item;
+                acc
+            },
+        );
+    }
+    cancellation.check()?;
+
+    diagnostics.aborted = aborted.into_inner();
+    if diagnostics.aborted {
+        // Only the failing diagnostics are reported, the rest is likely noise.
+        diagnostics.messages.retain(is_fail_fast);
+    } else if enabled(ErrorCode::UnusedDependency) {
+        diagnostics
+            .messages
+            .extend(unused_dependencies(&paths, &resolver, pyproject_config));
+    }
 
     diagnostics.messages.sort();
 
-    // Store the caches.
-    if let Some(caches) = caches {
+    // Store the caches, unless rules were disabled for exceeding the `rule-timeout`, in which case
+    // the results are incomplete.
+    let disabled = RULE_TIMINGS.disabled();
+    if !disabled.is_empty() {
+        warn!(
+            "Not updating the cache, since these rules were disabled for exceeding the `rule-timeout`: {}",
+            disabled.iter().map(ErrorCode::to_str).join(", ")
+        );
+    } else if let Some(caches) = caches {
+        let _span = tracing::debug_span!("store_caches").entered();
         caches
             .into_par_iter()
             .try_for_each(|(_, cache)| cache.store())?;
@@ -195,9 +347,228 @@ pub(crate) fn check(
     let duration = start.elapsed();
     debug!("Checked {:?} files in: {:?}", paths.len(), duration);
 
+    if let (true, Some(first)) = (diagnostics.aborted, diagnostics.messages.first()) {
+        warn_user!(
+            "Stopped checking at `{}` in {}, since it's a `fail-fast` code",
+            first.diagnostic.error_code,
+            fs::relativize_path(first.file.name())
+        );
+    }
+
     Ok(diagnostics)
 }
 
+/// Report the declared dependencies of each project that none of the project's checked files
+/// import. A project is the directory of the `pyproject.toml` that its files are configured by.
+fn unused_dependencies(
+    paths: &[Result<ignore::DirEntry, Error>],
+    resolver: &Resolver,
+    pyproject_config: &PyprojectConfig,
+) -> Vec<Message> {
+    let _span = tracing::debug_span!("unused_dependencies").entered();
+    let mut projects: FxHashMap<&Path, (&CheckerSettings, Vec<&Path>)> = FxHashMap::default();
+    for path in paths.iter().flatten().map(ignore::DirEntry::path) {
+        if !matches!(SourceType::from(path), SourceType::Python(_)) {
+            continue;
+        }
+        let settings = &resolver.resolve(path, pyproject_config).checker;
+        if settings.declared_dependencies.is_none()
+            || !settings.table.enabled(ErrorCode::UnusedDependency)
+        {
+            continue;
+        }
+        projects
+            .entry(settings.project_root.as_path())
+            .or_insert_with(|| (settings, vec![]))
+            .1
+            .push(path);
+    }
+
+    projects
+        .into_iter()
+        .flat_map(|(project_root, (settings, files))| {
+            let pyproject = project_root.join("pyproject.toml");
+            let Ok(contents) = read_source(&pyproject) else {
+                return vec![];
+            };
+            let distributions = Distributions::new(&settings.site_packages);
+            let used = files
+                .par_iter()
+                .filter_map(|path| {
+                    let SourceType::Python(source_type) = SourceType::from(*path) else {
+                        return None;
+                    };
+                    let source = read_source(path).ok()?;
+                    used_distributions(&source, source_type, settings, &distributions)
+                })
+                .flatten()
+                .collect::<FxHashSet<_>>();
+            let source_file =
+                SourceFileBuilder::new(pyproject.to_string_lossy(), contents).finish();
+            dependencies::unused_dependencies(&source_file, &used, settings)
+        })
+        .collect()
+}
+
+/// The modules of the checked files, with their interfaces built in dependency order and, if
+/// dead code is checked, the references between them.
+#[derive(Default)]
+struct ProjectModules<'a> {
+    /// The module name of every file, and whether it's the `__init__` of a package.
+    files: FxHashMap<&'a Path, (ModuleName, bool)>,
+    graph: ModuleGraph,
+    /// The level of every module in the [`ModuleGraph::levels`], i.e. its position in dependency
+    /// order.
+    levels: FxHashMap<ModuleName, usize>,
+    interfaces: ModuleInterfaces,
+    references: ProjectReferences,
+}
+
+impl<'a> ProjectModules<'a> {
+    /// Build the interfaces of the modules at `paths` and, given the settings of each file, the
+    /// references between them. The interfaces of unchanged files are taken from the
+    /// `interface_cache`, if any. Once `cancellation` is cancelled, the remaining files are
+    /// skipped.
+    fn build<'s>(
+        paths: &'a [Result<ignore::DirEntry, Error>],
+        package_roots: &FxHashMap<&Path, Option<&Path>>,
+        dead_code: Option<impl Fn(&Path) -> &'s CheckerSettings + Sync>,
+        interface_cache: Option<&InterfaceCache>,
+        cancellation: &CancellationToken,
+    ) -> Self {
+        let mut summaries = paths
+            .par_iter()
+            .filter_map(|entry| {
+                if cancellation.is_cancelled() {
+                    return None;
+                }
+                let path = entry.as_ref().ok()?.path();
+                let SourceType::Python(source_type) = SourceType::from(path) else {
+                    return None;
+                };
+                let package = path
+                    .parent()
+                    .and_then(|parent| package_roots.get(parent))
+                    .and_then(|package| *package);
+                // Files outside of a package are top-level modules named after the file.
+                let root = match package {
+                    Some(package) => package.parent()?,
+                    None => path.parent()?,
+                };
+                let name = ModuleName::from_relative_path(path, root)?;
+                let is_package = path.file_stem().is_some_and(|stem| stem == "__init__");
+                // Files that can't be read or parsed are reported when they're checked.
+                let source = read_source(path).ok()?;
+                let key = InterfaceCache::key(&source, source_type, &name, is_package);
+                let (dependencies, interface) =
+                    match interface_cache.and_then(|cache| cache.get(key)) {
+                        Some(summary) => summary,
+                        None => {
+                            let (dependencies, interface) =
+                                summarize_module(&source, source_type, &name, is_package)?;
+                            if let Some(cache) = interface_cache {
+                                cache.insert(key, &dependencies, &interface);
+                            }
+                            (dependencies, interface)
+                        }
+                    };
+                let references = match &dead_code {
+                    Some(settings) => {
+                        find_references(&source, source_type, &name, is_package, settings(path))?
+                    }
+                    None => ModuleReferences::default(),
+                };
+                Some((path, name, is_package, dependencies, interface, references))
+            })
+            .collect::<Vec<_>>();
+
+        // If several files map to the same module (e.g. scripts in different directories), only
+        // the first one is treated as that module, but the references of all of them count.
+        summaries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        let references = ProjectReferences::new(
+            summaries
+                .iter_mut()
+                .map(|(_, name, .., references)| (name.clone(), std::mem::take(references))),
+        );
+        let mut seen = FxHashSet::default();
+        summaries.retain(|(_, name, ..)| seen.insert(name.clone()));
+
+        let mut files = FxHashMap::default();
+        let mut pending = FxHashMap::default();
+        let mut imports = Vec::with_capacity(summaries.len());
+        for (path, name, is_package, dependencies, interface, _) in summaries {
+            files.insert(path, (name.clone(), is_package));
+            pending.insert(name.clone(), interface);
+            imports.push((name, dependencies));
+        }
+
+        let graph = ModuleGraph::new(imports);
+        let mut interfaces = ModuleInterfaces::default();
+        let mut levels = FxHashMap::default();
+        for (index, level) in graph.levels().into_iter().enumerate() {
+            for name in level {
+                if let Some(interface) = pending.remove(name) {
+                    interfaces.insert(name.clone(), interface);
+                }
+                levels.insert(name.clone(), index);
+            }
+        }
+
+        Self {
+            files,
+            graph,
+            levels,
+            interfaces,
+            references,
+        }
+    }
+
+    /// The level of the module of the file at `path` in dependency order, if it's a module of the
+    /// project. The modules that a module imports have a lower level.
+    fn level(&self, path: &Path) -> Option<usize> {
+        let (name, _) = self.files.get(path)?;
+        self.levels.get(name).copied()
+    }
+
+    fn module(&self, path: &Path) -> Option<ProjectModule> {
+        let (name, is_package) = self.files.get(path)?;
+        Some(ProjectModule {
+            context: ModuleContext {
+                name,
+                is_package: *is_package,
+                interfaces: &self.interfaces,
+                references: &self.references,
+            },
+            dependencies: {
+                let mut hasher = CacheKeyHasher::new();
+                self.interfaces
+                    .fingerprint(self.graph.dependencies(name))
+                    .hash(&mut hasher);
+                self.references.unreferenced(name).hash(&mut hasher);
+                hasher.finish()
+            },
+        })
+    }
+}
+
+/// Group the files by the level of their module in dependency order, lowest first, followed by
+/// the files that aren't modules of the project.
+fn dependency_levels<'p>(
+    paths: &'p [Result<ignore::DirEntry, Error>],
+    modules: &ProjectModules,
+) -> Vec<Vec<&'p Result<ignore::DirEntry, Error>>> {
+    let mut levels: BTreeMap<usize, Vec<_>> = BTreeMap::new();
+    for entry in paths {
+        let level = entry
+            .as_ref()
+            .ok()
+            .and_then(|entry| modules.level(entry.path()))
+            .unwrap_or(usize::MAX);
+        levels.entry(level).or_default().push(entry);
+    }
+    levels.into_values().collect()
+}
+
 /// Wraps [`lint_path`](crate::diagnostics::lint_path) in a [`catch_unwind`](std::panic::catch_unwind) and emits
 /// a diagnostic if the linting the file panics.
 fn lint_path(
@@ -206,9 +577,22 @@ fn lint_path(
     settings: &CheckerSettings,
     cache: Option<&Cache>,
     noqa: flags::TypeIgnore,
+    fix_mode: flags::FixMode,
+    module: Option<&ProjectModule>,
+    cancellation: &CancellationToken,
 ) -> Result<Messages> {
-    let result =
-        catch_unwind(|| crate::diagnostics::type_check_path(path, package, settings, cache, noqa));
+    let result = catch_unwind(|| {
+        crate::diagnostics::type_check_path(
+            path,
+            package,
+            settings,
+            cache,
+            noqa,
+            fix_mode,
+            module,
+            cancellation,
+        )
+    });
 
     match result {
         Ok(inner) => inner,
@@ -241,15 +625,18 @@ mod test {
     use anyhow::Result;
     use tempfile::TempDir;
 
+    use pyrogen_checker::cancellation::{CancellationToken, Cancelled};
     use pyrogen_checker::message::{Emitter, TextEmitter};
-    use pyrogen_checker::registry::ErrorCode;
+    use pyrogen_checker::registry::{ErrorCode, ErrorCodeSet};
     use pyrogen_checker::settings::{flags, CheckerSettings};
-    use pyrogen_workspace::resolver::{PyprojectConfig, PyprojectDiscoveryStrategy};
+    use pyrogen_workspace::resolver::{
+        python_files_in_path, PyprojectConfig, PyprojectDiscoveryStrategy,
+    };
     use pyrogen_workspace::Settings;
 
     use crate::args::CliOverrides;
 
-    use super::check;
+    use super::{check, dependency_levels, ProjectModules};
 
     /// We check that regular python files and pyproject.toml all handle io errors gracefully.
     #[test]
@@ -285,7 +672,10 @@ mod test {
             &pyproject_config,
             &CliOverrides::default(),
             flags::Cache::Disabled,
-            flags::TypeIgnore::Disabled,
+            Some(flags::TypeIgnore::Disabled),
+            flags::FixMode::Generate,
+            false,
+            &CancellationToken::default(),
         )
         .unwrap();
         let mut output = Vec::new();
@@ -307,4 +697,159 @@ mod test {
         });
         Ok(())
     }
+
+    #[test]
+    fn fail_fast() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        fs::write(tempdir.path().join("broken.py"), "def broken(:\n")?;
+        fs::write(tempdir.path().join("keys.py"), "_a = {1: 1, 1: 2}\n")?;
+
+        let settings = Settings {
+            checker: CheckerSettings::for_rules(vec![
+                ErrorCode::SyntaxError,
+                ErrorCode::DuplicateKey,
+            ]),
+            fail_fast: ErrorCodeSet::from_error_code(ErrorCode::SyntaxError),
+            ..Settings::default()
+        };
+        let pyproject_config =
+            PyprojectConfig::new(PyprojectDiscoveryStrategy::Fixed, settings, None);
+        let diagnostics = check(
+            &[tempdir.path().to_path_buf()],
+            &pyproject_config,
+            &CliOverrides::default(),
+            flags::Cache::Disabled,
+            Some(flags::TypeIgnore::Disabled),
+            flags::FixMode::Generate,
+            false,
+            &CancellationToken::default(),
+        )?;
+
+        assert!(diagnostics.aborted);
+        assert!(!diagnostics.messages.is_empty());
+        assert!(diagnostics
+            .messages
+            .iter()
+            .all(|message| message.diagnostic.error_code == ErrorCode::SyntaxError));
+        Ok(())
+    }
+
+    #[test]
+    fn cancelled() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        fs::write(tempdir.path().join("keys.py"), "_a = {1: 1, 1: 2}\n")?;
+
+        let settings = Settings {
+            checker: CheckerSettings::for_rules(vec![ErrorCode::DuplicateKey]),
+            ..Settings::default()
+        };
+        let pyproject_config =
+            PyprojectConfig::new(PyprojectDiscoveryStrategy::Fixed, settings, None);
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        let err = check(
+            &[tempdir.path().to_path_buf()],
+            &pyproject_config,
+            &CliOverrides::default(),
+            flags::Cache::Disabled,
+            Some(flags::TypeIgnore::Disabled),
+            flags::FixMode::Generate,
+            false,
+            &cancellation,
+        )
+        .unwrap_err();
+
+        assert_eq!(err.downcast_ref::<Cancelled>(), Some(&Cancelled));
+        Ok(())
+    }
+
+    #[test]
+    fn respect_type_ignore_of_each_file() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        let audited = tempdir.path().join("audited");
+        fs::create_dir_all(&audited)?;
+        fs::write(
+            audited.join("pyproject.toml"),
+            "[tool.pyrogen]\npreview = true\nextend-error = [\"duplicate-key\"]\nrespect-type-ignore = false\n",
+        )?;
+        for directory in [tempdir.path(), audited.as_path()] {
+            fs::write(
+                directory.join("keys.py"),
+                "_a = {1: 1, 1: 2}  # type: ignore\n",
+            )?;
+        }
+
+        let settings = Settings {
+            checker: CheckerSettings::for_rules(vec![ErrorCode::DuplicateKey]),
+            ..Settings::default()
+        };
+        let pyproject_config =
+            PyprojectConfig::new(PyprojectDiscoveryStrategy::Hierarchical, settings, None);
+        let diagnostics = check(
+            &[tempdir.path().to_path_buf()],
+            &pyproject_config,
+            &CliOverrides::default(),
+            flags::Cache::Disabled,
+            None,
+            flags::FixMode::Generate,
+            false,
+            &CancellationToken::default(),
+        )?;
+
+        // Only the comment in `audited`, whose settings don't respect it, is ignored.
+        let files: Vec<_> = diagnostics
+            .messages
+            .iter()
+            .filter(|message| message.diagnostic.error_code == ErrorCode::DuplicateKey)
+            .map(|message| message.filename().to_string())
+            .collect();
+        assert_eq!(files, [audited.join("keys.py").to_string_lossy()]);
+        Ok(())
+    }
+
+    #[test]
+    fn dependency_order() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        fs::write(tempdir.path().join("app.py"), "from lib import helper\n")?;
+        fs::write(tempdir.path().join("lib.py"), "from util import f\n")?;
+        fs::write(tempdir.path().join("util.py"), "def f(): ...\n")?;
+
+        let pyproject_config =
+            PyprojectConfig::new(PyprojectDiscoveryStrategy::Fixed, Settings::default(), None);
+        let (paths, _) = python_files_in_path(
+            &[tempdir.path().to_path_buf()],
+            &pyproject_config,
+            &CliOverrides::default(),
+        )?;
+        let package_roots = paths
+            .iter()
+            .flatten()
+            .map(|entry| (entry.path().parent().unwrap(), None))
+            .collect();
+        let modules = ProjectModules::build(
+            &paths,
+            &package_roots,
+            None::<fn(&std::path::Path) -> &'static CheckerSettings>,
+            None,
+            &CancellationToken::default(),
+        );
+
+        let level = |name: &str| modules.level(&tempdir.path().join(name)).unwrap();
+        assert!(level("util.py") < level("lib.py"));
+        assert!(level("lib.py") < level("app.py"));
+
+        // Each module is checked in a later batch than the modules that it imports.
+        let batches = dependency_levels(&paths, &modules)
+            .into_iter()
+            .map(|level| {
+                level
+                    .into_iter()
+                    .flatten()
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(batches, [["util.py"], ["lib.py"], ["app.py"]]);
+        Ok(())
+    }
 }
@@ -0,0 +1,146 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use ignore::WalkBuilder;
+use itertools::Itertools;
+use path_absolutize::path_dedot;
+
+use pyrogen_checker::{fs, warn_user_once};
+use pyrogen_workspace::configuration::Configuration;
+use pyrogen_workspace::pyproject;
+
+use crate::ExitStatus;
+
+/// Validate every `pyproject.toml` with a `[tool.pyrogen]` section below `paths`, without
+/// checking any Python files.
+pub(crate) fn check_config(paths: &[PathBuf], writer: &mut impl Write) -> Result<ExitStatus> {
+    let roots = if paths.is_empty() {
+        vec![path_dedot::CWD.clone()]
+    } else {
+        paths.iter().map(fs::normalize_path).collect()
+    };
+
+    let mut builder = WalkBuilder::new(&roots[0]);
+    for root in &roots[1..] {
+        builder.add(root);
+    }
+    let config_files = builder
+        .build()
+        .flatten()
+        .map(ignore::DirEntry::into_path)
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| name == "pyproject.toml")
+        })
+        .sorted_unstable()
+        .dedup()
+        .collect::<Vec<_>>();
+
+    let mut checked = 0;
+    let mut invalid = 0;
+    for path in &config_files {
+        let problems = match pyproject::pyrogen_enabled(path) {
+            // Not a pyrogen configuration file.
+            Ok(false) => continue,
+            Ok(true) => validate(path),
+            Err(err) => vec![format!("{}: {err:#}", fs::relativize_path(path))],
+        };
+
+        checked += 1;
+        if problems.is_empty() {
+            writeln!(writer, "{}: ok", fs::relativize_path(path))?;
+        } else {
+            invalid += 1;
+            for problem in problems {
+                writeln!(writer, "{problem}")?;
+            }
+        }
+    }
+    writer.flush()?;
+
+    if checked == 0 {
+        warn_user_once!("No pyrogen configuration found under the given path(s)");
+    }
+
+    Ok(if invalid == 0 {
+        ExitStatus::Success
+    } else {
+        ExitStatus::Failure
+    })
+}
+
+/// Returns the problems with the configuration in the `pyproject.toml` at `path`, each prefixed
+/// with its location.
+fn validate(path: &Path) -> Vec<String> {
+    let project_root = path.parent().unwrap_or(path);
+    let settings =
+        pyproject::load_options_and_diagnostics(path).and_then(|(options, diagnostics)| {
+            let locations = pyproject::load_selector_locations(path)?;
+            let mut configuration =
+                Configuration::from_options(options, project_root)?.with_source(path, locations);
+            configuration.load_diagnostics = diagnostics;
+            configuration.into_settings(project_root)
+        });
+    match settings {
+        Ok(settings) => settings
+            .diagnostics
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        Err(err) => vec![format!("{}: {err:#}", fs::relativize_path(path))],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, write};
+
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use crate::ExitStatus;
+
+    use super::check_config;
+
+    #[test]
+    fn nested_configurations() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let root = tmp_dir.path();
+        write(
+            root.join("pyproject.toml"),
+            "[tool.pyrogen]\nsrc = [\"src\"]\n",
+        )?;
+        create_dir_all(root.join("unknown"))?;
+        write(
+            root.join("unknown/pyproject.toml"),
+            "[tool.pyrogen]\nunknown-option = true\n",
+        )?;
+        create_dir_all(root.join("conflict"))?;
+        write(
+            root.join("conflict/pyproject.toml"),
+            "[tool.pyrogen]\nerror = [\"unused-import\"]\nwarning = [\"unused-import\"]\n",
+        )?;
+        create_dir_all(root.join("glob"))?;
+        write(
+            root.join("glob/pyproject.toml"),
+            "[tool.pyrogen]\nexclude = [\"[\"]\n",
+        )?;
+        create_dir_all(root.join("other"))?;
+        write(root.join("other/pyproject.toml"), "[tool.black]\n")?;
+
+        let mut output = Vec::new();
+        let status = check_config(&[root.to_path_buf()], &mut output)?;
+        let output = String::from_utf8(output)?;
+
+        assert!(matches!(status, ExitStatus::Failure));
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4, "{output}");
+        assert!(lines[0].contains("conflict/pyproject.toml:2:10: `unused-import` is selected both"));
+        assert!(lines[1].contains("glob"));
+        assert!(lines[2].ends_with("pyproject.toml: ok"));
+        assert!(lines[3].contains("unknown-option"));
+
+        Ok(())
+    }
+}
@@ -1,13 +1,19 @@
+use std::hash::Hasher;
+use std::io;
 use std::path::Path;
 
 use anyhow::Result;
+use log::error;
 
+use pyrogen_cache::{CacheKey, CacheKeyHasher};
 use pyrogen_checker::packaging;
+use pyrogen_checker::registry::ErrorCode;
 use pyrogen_checker::settings::flags;
 use pyrogen_workspace::resolver::{python_file_at_path, PyprojectConfig};
 
 use crate::args::CliOverrides;
-use crate::diagnostics::{type_check_stdin, Messages};
+use crate::cache::{self, Cache};
+use crate::diagnostics::{report_configuration_diagnostics, type_check_stdin, Messages};
 use crate::stdin::read_from_stdin;
 
 /// Run the linter over a single file, read from `stdin`.
@@ -15,8 +21,35 @@ pub(crate) fn check_stdin(
     filename: Option<&Path>,
     pyproject_config: &PyprojectConfig,
     overrides: &CliOverrides,
+    cache: flags::Cache,
     respect_type_ignore: flags::TypeIgnore,
+    fix_mode: flags::FixMode,
 ) -> Result<Messages> {
+    check_contents(
+        filename,
+        read_from_stdin,
+        pyproject_config,
+        overrides,
+        cache,
+        respect_type_ignore,
+        fix_mode,
+    )
+}
+
+/// Run the linter over the contents of a single file, as returned by `read`.
+///
+/// With a `filename`, the results are cached by the hash of the contents, so that an editor that
+/// pipes an unchanged buffer again gets them from the cache.
+fn check_contents(
+    filename: Option<&Path>,
+    read: impl FnOnce() -> io::Result<String>,
+    pyproject_config: &PyprojectConfig,
+    overrides: &CliOverrides,
+    cache: flags::Cache,
+    respect_type_ignore: flags::TypeIgnore,
+    fix_mode: flags::FixMode,
+) -> Result<Messages> {
+    report_configuration_diagnostics([&pyproject_config.settings]);
     if let Some(filename) = filename {
         if !python_file_at_path(filename, pyproject_config, overrides)? {
             return Ok(Messages::default());
@@ -25,14 +58,131 @@ pub(crate) fn check_stdin(
     let package_root = filename.and_then(Path::parent).and_then(|path| {
         packaging::detect_package_root(path, &pyproject_config.settings.checker.namespace_packages)
     });
-    let stdin = read_from_stdin()?;
+    let stdin = read()?;
+
+    // Fixing changes the source, so the cache only applies if nothing is fixed.
+    let cache = match filename {
+        Some(filename)
+            if bool::from(cache)
+                && bool::from(respect_type_ignore)
+                && fix_mode == flags::FixMode::Generate =>
+        {
+            let settings = &pyproject_config.settings;
+            if let Err(e) = cache::init(&settings.cache_dir) {
+                error!(
+                    "Failed to initialize cache at {}: {e:?}",
+                    settings.cache_dir.display()
+                );
+            }
+            let cache_root = package_root.unwrap_or_else(|| filename.parent().unwrap_or(filename));
+            let cache = Cache::open(cache_root.to_path_buf(), settings);
+            let mut hasher = CacheKeyHasher::new();
+            stdin.cache_key(&mut hasher);
+            let content_hash = hasher.finish();
+            if let Some(relative_path) = cache.relative_path(filename) {
+                if let Some(cached) = cache.get(relative_path, &content_hash) {
+                    let mut diagnostics = cached.as_diagnostics(filename);
+                    diagnostics.messages.sort_unstable();
+                    return Ok(diagnostics);
+                }
+            }
+            Some((cache, filename, content_hash))
+        }
+        _ => None,
+    };
+
     let mut diagnostics = type_check_stdin(
         filename,
         package_root,
         stdin,
         &pyproject_config.settings,
         respect_type_ignore,
+        fix_mode,
     )?;
+
+    if let Some((cache, filename, content_hash)) = cache {
+        // We don't cache parsing errors.
+        if !diagnostics
+            .messages
+            .iter()
+            .any(|message| message.diagnostic.error_code == ErrorCode::SyntaxError)
+        {
+            if let Some(relative_path) = cache.relative_path(filename) {
+                cache.update(
+                    relative_path.to_owned(),
+                    content_hash,
+                    &diagnostics.messages,
+                    &diagnostics.imports,
+                );
+            }
+            cache.store()?;
+        }
+    }
+
     diagnostics.messages.sort_unstable();
     Ok(diagnostics)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::hash::Hasher;
+    use std::io;
+
+    use anyhow::{anyhow, Result};
+    use tempfile::TempDir;
+
+    use pyrogen_cache::{CacheKey, CacheKeyHasher};
+    use pyrogen_checker::registry::ErrorCode;
+    use pyrogen_checker::settings::{flags, CheckerSettings};
+    use pyrogen_workspace::resolver::{PyprojectConfig, PyprojectDiscoveryStrategy};
+    use pyrogen_workspace::Settings;
+
+    use crate::args::CliOverrides;
+    use crate::cache::Cache;
+
+    use super::check_contents;
+
+    #[test]
+    fn cached_by_content() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        let filename = tempdir.path().join("module.py");
+        let settings = Settings {
+            cache_dir: tempdir.path().join("cache"),
+            checker: CheckerSettings::for_rules(vec![ErrorCode::DuplicateKey]),
+            ..Settings::default()
+        };
+        let pyproject_config =
+            PyprojectConfig::new(PyprojectDiscoveryStrategy::Fixed, settings, None);
+        let contents = "_a = {1: 1, 1: 2}\n";
+        let check = |read: &dyn Fn() -> io::Result<String>| {
+            check_contents(
+                Some(&filename),
+                read,
+                &pyproject_config,
+                &CliOverrides::default(),
+                flags::Cache::Enabled,
+                flags::TypeIgnore::Enabled,
+                flags::FixMode::Generate,
+            )
+        };
+
+        let checked = check(&|| Ok(contents.to_string()))?;
+        assert_eq!(checked.messages.len(), 1);
+
+        let mut hasher = CacheKeyHasher::new();
+        contents.to_string().cache_key(&mut hasher);
+        let content_hash = hasher.finish();
+        let cache = Cache::open(tempdir.path().to_path_buf(), &pyproject_config.settings);
+        let cached = cache
+            .get(cache.relative_path(&filename).unwrap(), &content_hash)
+            .ok_or_else(|| anyhow!("the results weren't cached"))?;
+        assert_eq!(cached.as_diagnostics(&filename).messages, checked.messages);
+
+        assert_eq!(
+            check(&|| Ok(contents.to_string()))?.messages,
+            checked.messages
+        );
+        assert!(check(&|| Ok("_b = 1\n".to_string()))?.messages.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+use itertools::Itertools;
+use path_absolutize::path_dedot;
+
+use pyrogen_cache::CACHE_DIR_NAME;
+use pyrogen_checker::fs;
+use pyrogen_checker::warn_user;
+
+use crate::ExitStatus;
+
+/// The cache directories below `roots`, and `cache_dir` if it exists. Caches aren't searched for
+/// nested caches.
+///
+/// Only directories with the `CACHEDIR.TAG` that pyrogen writes when it creates a cache are
+/// returned, so that a directory which merely shares the name (or a mistyped `cache_dir`) is never
+/// removed.
+fn cache_dirs(roots: &[PathBuf], cache_dir: &Path) -> Vec<PathBuf> {
+    let mut builder = WalkBuilder::new(&roots[0]);
+    for root in &roots[1..] {
+        builder.add(root);
+    }
+    // Caches are usually hidden and listed in `.gitignore`.
+    builder.standard_filters(false).filter_entry(|entry| {
+        entry
+            .path()
+            .parent()
+            .and_then(Path::file_name)
+            .map_or(true, |parent| parent != CACHE_DIR_NAME)
+    });
+    let mut dirs = builder
+        .build()
+        .flatten()
+        .filter(|entry| {
+            entry.file_name() == CACHE_DIR_NAME
+                && entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_dir())
+        })
+        .map(ignore::DirEntry::into_path)
+        .collect::<Vec<_>>();
+    if cache_dir.is_dir() {
+        dirs.push(cache_dir.to_path_buf());
+    }
+    dirs.into_iter()
+        .sorted_unstable()
+        .dedup()
+        .filter(|dir| {
+            let tagged = cachedir::is_tagged(dir).unwrap_or(false);
+            if !tagged {
+                warn_user!(
+                    "Skipping {}, since it has no `CACHEDIR.TAG`",
+                    fs::relativize_path(dir)
+                );
+            }
+            tagged
+        })
+        .collect()
+}
+
+/// The total size of the files below `path`, in bytes.
+fn dir_size(path: &Path) -> u64 {
+    WalkBuilder::new(path)
+        .standard_filters(false)
+        .build()
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(std::fs::Metadata::is_file)
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Remove the cache directories below `paths` (or the current directory) and the configured
+/// `cache_dir`, printing each of them with its size. With `dry_run`, nothing is removed.
+pub(crate) fn clean(
+    paths: &[PathBuf],
+    cache_dir: &Path,
+    dry_run: bool,
+    writer: &mut impl Write,
+) -> Result<ExitStatus> {
+    let roots = if paths.is_empty() {
+        vec![path_dedot::CWD.clone()]
+    } else {
+        paths.iter().map(fs::normalize_path).collect()
+    };
+
+    let dirs = cache_dirs(&roots, cache_dir);
+    if dirs.is_empty() {
+        writeln!(writer, "No cache directories found")?;
+        writer.flush()?;
+        return Ok(ExitStatus::Success);
+    }
+
+    let mut total = 0;
+    for dir in &dirs {
+        let size = dir_size(dir);
+        total += size;
+        if dry_run {
+            writeln!(
+                writer,
+                "Would remove {} ({})",
+                fs::relativize_path(dir),
+                format_size(size)
+            )?;
+        } else {
+            std::fs::remove_dir_all(dir)
+                .with_context(|| format!("Failed to remove {}", fs::relativize_path(dir)))?;
+            writeln!(
+                writer,
+                "Removed {} ({})",
+                fs::relativize_path(dir),
+                format_size(size)
+            )?;
+        }
+    }
+    writeln!(
+        writer,
+        "{} {} cache {} ({})",
+        if dry_run { "Would remove" } else { "Removed" },
+        dirs.len(),
+        if dirs.len() == 1 {
+            "directory"
+        } else {
+            "directories"
+        },
+        format_size(total)
+    )?;
+    writer.flush()?;
+
+    Ok(ExitStatus::Success)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use pyrogen_cache::CACHE_DIR_NAME;
+
+    use crate::cache;
+
+    use super::{clean, format_size};
+
+    #[test]
+    fn sizes() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[test]
+    fn remove_caches() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        let root = tempdir.path().join("project");
+        let nested = root.join("sub").join(CACHE_DIR_NAME);
+        cache::init(&nested)?;
+        std::fs::write(nested.join("content").join("entry"), [0; 2048])?;
+        cache::init(&root.join(CACHE_DIR_NAME))?;
+        let configured = tempdir.path().join("elsewhere");
+        cache::init(&configured)?;
+        // A directory that shares the name but wasn't created by pyrogen.
+        let untagged = root.join("other").join(CACHE_DIR_NAME);
+        std::fs::create_dir_all(&untagged)?;
+        std::fs::write(untagged.join("keep"), [0; 10])?;
+
+        let mut output = Vec::new();
+        clean(&[root.clone()], &configured, true, &mut output)?;
+        let output = String::from_utf8(output)?;
+        // The size includes the `CACHEDIR.TAG` and `.gitignore` of each cache.
+        assert!(output.contains("Would remove 3 cache directories (2."));
+        assert!(!output.contains("other"));
+        assert!(nested.exists());
+
+        let mut output = Vec::new();
+        clean(&[root.clone()], &configured, false, &mut output)?;
+        assert!(!nested.exists());
+        assert!(!root.join(CACHE_DIR_NAME).exists());
+        assert!(!configured.exists());
+        assert!(root.join("sub").exists());
+        assert!(untagged.join("keep").exists());
+
+        let mut output = Vec::new();
+        clean(&[root], &configured, false, &mut output)?;
+        assert_eq!(String::from_utf8(output)?, "No cache directories found\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,315 @@
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::{Arg, Command, CommandFactory};
+use itertools::Itertools;
+
+use crate::args::Args;
+use crate::ExitStatus;
+
+const MARKDOWN_FILE: &str = "cli.md";
+const ROFF_FILE: &str = "pyrogen.1";
+
+/// The command-line interface with the global arguments propagated to the subcommands.
+fn cli_command() -> Command {
+    let mut command = Args::command();
+    command.build();
+    command
+}
+
+/// The commands that are shown in the help, each with its full name (like `pyrogen self update`).
+fn visible_commands(command: &Command) -> Vec<(String, &Command)> {
+    fn collect<'a>(name: &str, command: &'a Command, commands: &mut Vec<(String, &'a Command)>) {
+        let subcommands = command
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set() && subcommand.get_name() != "help");
+        for subcommand in subcommands {
+            let name = format!("{name} {}", subcommand.get_name());
+            commands.push((name.clone(), subcommand));
+            collect(&name, subcommand, commands);
+        }
+    }
+
+    let mut commands = vec![(command.get_name().to_string(), command)];
+    collect(command.get_name(), command, &mut commands);
+    commands
+}
+
+/// The visible arguments of `command`, grouped by their help heading in the order of the help.
+fn argument_sections(command: &Command) -> Vec<(&str, Vec<&Arg>)> {
+    let mut sections: Vec<(&str, Vec<&Arg>)> = vec![];
+    for arg in command.get_arguments().filter(|arg| !arg.is_hide_set()) {
+        let heading = arg.get_help_heading().unwrap_or(if arg.is_positional() {
+            "Arguments"
+        } else {
+            "Options"
+        });
+        match sections.iter_mut().find(|(name, _)| *name == heading) {
+            Some((_, args)) => args.push(arg),
+            None => sections.push((heading, vec![arg])),
+        }
+    }
+    // Like in the help, positional arguments come first.
+    sections.sort_by_key(|(heading, _)| *heading != "Arguments");
+    sections
+}
+
+/// How the argument is written on the command line, like `-o, --output-file <OUTPUT_FILE>`.
+fn argument_name(arg: &Arg) -> String {
+    match (arg.get_short(), arg.get_long()) {
+        (Some(short), Some(_)) => format!("-{short}, {arg}"),
+        _ => arg.to_string(),
+    }
+}
+
+/// The help of the argument, followed by its environment variable, default and possible values.
+fn argument_help(arg: &Arg) -> String {
+    let mut help = arg
+        .get_long_help()
+        .or_else(|| arg.get_help())
+        .map(ToString::to_string)
+        .unwrap_or_default();
+    let mut details = vec![];
+    if let Some(env) = arg.get_env() {
+        details.push(format!("env: {}", env.to_string_lossy()));
+    }
+    // Like in the help, the default of flags (`false`) isn't shown.
+    if arg.get_action().takes_values() && !arg.get_default_values().is_empty() {
+        details.push(format!(
+            "default: {}",
+            arg.get_default_values()
+                .iter()
+                .map(|value| value.to_string_lossy())
+                .join(",")
+        ));
+    }
+    let possible_values = arg.get_possible_values();
+    if !possible_values.is_empty() && !arg.is_hide_possible_values_set() {
+        details.push(format!(
+            "possible values: {}",
+            possible_values
+                .iter()
+                .filter(|value| !value.is_hide_set())
+                .map(clap::builder::PossibleValue::get_name)
+                .join(", ")
+        ));
+    }
+    if !details.is_empty() {
+        if !help.is_empty() {
+            help.push(' ');
+        }
+        let _ = write!(help, "[{}]", details.join("] ["));
+    }
+    help
+}
+
+fn usage(command: &Command) -> String {
+    let usage = command.clone().render_usage().to_string();
+    usage.strip_prefix("Usage: ").unwrap_or(&usage).to_string()
+}
+
+/// Render the command-line interface as Markdown, with a section per command.
+fn markdown(command: &Command) -> String {
+    let mut output = String::new();
+    output.push_str("# CLI reference\n\n");
+    output.push_str("<!-- Generated by `pyrogen dev generate-cli-docs`. Don't edit by hand. -->\n");
+
+    for (name, command) in visible_commands(command) {
+        let _ = write!(output, "\n## `{name}`\n\n");
+        if let Some(about) = command.get_long_about().or_else(|| command.get_about()) {
+            let _ = write!(output, "{about}\n\n");
+        }
+        let _ = writeln!(output, "```text\n{}\n```", usage(command));
+
+        for (heading, args) in argument_sections(command) {
+            let _ = write!(output, "\n### {heading}\n\n");
+            for arg in args {
+                let help = argument_help(arg);
+                let mut paragraphs = help.split("\n\n");
+                let _ = write!(output, "- `{}`", argument_name(arg));
+                match paragraphs.next() {
+                    Some(paragraph) if !paragraph.is_empty() => {
+                        let _ = writeln!(output, ": {}", paragraph.replace('\n', " "));
+                    }
+                    _ => output.push('\n'),
+                }
+                for paragraph in paragraphs {
+                    let _ = write!(output, "\n  {}\n", paragraph.replace('\n', " "));
+                }
+            }
+        }
+    }
+    output
+}
+
+/// Escape text for roff: backslashes and hyphens, and control characters at the start of lines.
+fn roff_escape(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{line}")
+            } else {
+                line
+            }
+        })
+        .join("\n")
+}
+
+/// Render the command-line interface as a man page, with a subsection per subcommand.
+fn roff(command: &Command) -> String {
+    fn arguments(output: &mut String, command: &Command, section: &str) {
+        for (heading, args) in argument_sections(command) {
+            let _ = writeln!(output, "{section} \"{}\"", heading.to_uppercase());
+            for arg in args {
+                let _ = writeln!(output, ".TP\n\\fB{}\\fR", roff_escape(&argument_name(arg)));
+                let help = argument_help(arg);
+                if !help.is_empty() {
+                    let _ = writeln!(
+                        output,
+                        "{}",
+                        help.split("\n\n").map(roff_escape).join("\n.IP\n")
+                    );
+                }
+            }
+        }
+    }
+
+    let mut output = String::new();
+    let _ = writeln!(
+        output,
+        ".\\\" Generated by `pyrogen dev generate-cli-docs`. Don't edit by hand.\n.TH PYROGEN 1 \"\" \"pyrogen {}\"",
+        env!("CARGO_PKG_VERSION")
+    );
+    let _ = writeln!(
+        output,
+        ".SH NAME\npyrogen \\- {}",
+        roff_escape(
+            &command
+                .get_about()
+                .map(ToString::to_string)
+                .unwrap_or_default()
+        )
+    );
+    let _ = writeln!(
+        output,
+        ".SH SYNOPSIS\n.nf\n{}\n.fi",
+        roff_escape(&usage(command))
+    );
+    if let Some(long_about) = command.get_long_about() {
+        let _ = writeln!(
+            output,
+            ".SH DESCRIPTION\n{}",
+            roff_escape(&long_about.to_string())
+        );
+    }
+    arguments(&mut output, command, ".SH");
+
+    let subcommands = visible_commands(command);
+    if subcommands.len() > 1 {
+        output.push_str(".SH COMMANDS\n");
+    }
+    for (name, subcommand) in subcommands.into_iter().skip(1) {
+        let _ = writeln!(output, ".SS \"{}\"", roff_escape(&name));
+        if let Some(about) = subcommand
+            .get_long_about()
+            .or_else(|| subcommand.get_about())
+        {
+            let _ = writeln!(
+                output,
+                "{}",
+                about
+                    .to_string()
+                    .split("\n\n")
+                    .map(roff_escape)
+                    .join("\n.PP\n")
+            );
+        }
+        let _ = writeln!(output, ".PP\n.nf\n{}\n.fi", roff_escape(&usage(subcommand)));
+        arguments(&mut output, subcommand, ".PP\n.B");
+    }
+    output
+}
+
+/// Write the Markdown reference and the man page of the command-line interface to `output_dir`.
+/// With `check`, nothing is written, and the exit status is a failure if the files are stale.
+pub(crate) fn generate_cli_docs(
+    output_dir: &Path,
+    check: bool,
+    writer: &mut impl Write,
+) -> Result<ExitStatus> {
+    let command = cli_command();
+    let mut status = ExitStatus::Success;
+    for (file_name, contents) in [
+        (MARKDOWN_FILE, markdown(&command)),
+        (ROFF_FILE, roff(&command)),
+    ] {
+        let path = output_dir.join(file_name);
+        if check {
+            let current = std::fs::read_to_string(&path).unwrap_or_default();
+            if current != contents {
+                writeln!(
+                    writer,
+                    "{} is out of date, run `pyrogen dev generate-cli-docs` to update it",
+                    path.display()
+                )?;
+                status = ExitStatus::Failure;
+            }
+        } else {
+            std::fs::create_dir_all(output_dir)
+                .with_context(|| format!("Failed to create {}", output_dir.display()))?;
+            std::fs::write(&path, contents)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            writeln!(writer, "Wrote {}", path.display())?;
+        }
+    }
+    writer.flush()?;
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{argument_sections, cli_command, markdown, roff, roff_escape, visible_commands};
+
+    /// The documentation is generated from the command tree, so it covers every command and
+    /// option that is shown in the help.
+    #[test]
+    fn cli_docs_cover_the_command_tree() {
+        let command = cli_command();
+        let markdown = markdown(&command);
+        let roff = roff(&command);
+
+        let commands = visible_commands(&command);
+        assert_eq!(commands[0].0, "pyrogen");
+        for (name, command) in &commands {
+            assert!(markdown.contains(&format!("## `{name}`")), "{name}");
+            assert!(roff.contains(&roff_escape(name)), "{name}");
+            for (heading, args) in argument_sections(command) {
+                assert!(markdown.contains(&format!("### {heading}")), "{heading}");
+                assert!(roff.contains(&heading.to_uppercase()), "{heading}");
+                for arg in args {
+                    if let Some(long) = arg.get_long() {
+                        assert!(markdown.contains(&format!("--{long}")), "{long}");
+                        assert!(roff.contains(&roff_escape(&format!("--{long}"))), "{long}");
+                    }
+                }
+            }
+        }
+        assert!(markdown.contains("### File selection"));
+        assert!(markdown.contains("## `pyrogen check-config`"));
+
+        // Hidden commands and options aren't documented.
+        assert!(!markdown.contains("## `pyrogen dev"));
+        assert!(!markdown.contains("persistent_worker"));
+        assert!(!roff.contains("persistent_worker"));
+    }
+
+    #[test]
+    fn roff_escapes() {
+        assert_eq!(roff_escape("--fix"), "\\-\\-fix");
+        assert_eq!(roff_escape("a\\b"), "a\\eb");
+        assert_eq!(roff_escape("x\n.y"), "x\n\\&.y");
+    }
+}
@@ -0,0 +1,230 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use pyrogen_checker::fs::{ignores_from_path, normalize_path};
+use pyrogen_checker::settings::code_table::MessageKind;
+use pyrogen_checker::settings::types::{PythonPlatform, PythonVersion, SerializationFormat};
+use pyrogen_checker::ErrorCodeSelector;
+use pyrogen_workspace::pyproject::settings_toml;
+use pyrogen_workspace::resolver::{python_files_in_path_with_skipped, PyprojectConfig};
+use pyrogen_workspace::Settings;
+
+use crate::args::{CliOverrides, ConfigFormat};
+use crate::ExitStatus;
+
+/// The settings of a path, in the shape of the configuration file. Tables come last, since TOML
+/// doesn't allow plain values after them.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct ResolvedConfig {
+    path: PathBuf,
+    /// Why the path is skipped when it's passed to `pyrogen check`, if it is.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped: Option<String>,
+    /// The configuration file the settings were read from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config_file: Option<PathBuf>,
+    cache_dir: PathBuf,
+    output_format: SerializationFormat,
+    files: FileSettings,
+    checker: CheckerView,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct FileSettings {
+    project_root: PathBuf,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    extend_exclude: Vec<String>,
+    force_exclude: bool,
+    respect_gitignore: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct CheckerView {
+    target_version: PythonVersion,
+    platform: PythonPlatform,
+    src: Vec<PathBuf>,
+    namespace_packages: Vec<PathBuf>,
+    site_packages: Vec<PathBuf>,
+    disallow_untyped_imports: bool,
+    extra_modules: Vec<String>,
+    entry_points: Vec<String>,
+    dead_code_keep: Vec<String>,
+    error: Vec<&'static str>,
+    warning: Vec<&'static str>,
+    info: Vec<&'static str>,
+    hint: Vec<&'static str>,
+    /// The enabled codes that `per-file-ignores` turns off for the path.
+    per_file_ignores: Vec<&'static str>,
+}
+
+impl ResolvedConfig {
+    fn new(
+        path: &Path,
+        skipped: Option<String>,
+        config_file: Option<PathBuf>,
+        settings: &Settings,
+    ) -> Self {
+        let checker = &settings.checker;
+        let codes = |kind: MessageKind| {
+            ErrorCodeSelector::All
+                .all_rules()
+                .filter(|code| checker.table.entry(*code) == Some(kind))
+                .map(|code| code.to_str())
+                .collect()
+        };
+        let ignored = ignores_from_path(path, &checker.per_file_ignores);
+        Self {
+            path: path.to_path_buf(),
+            skipped,
+            config_file,
+            cache_dir: settings.cache_dir.clone(),
+            output_format: settings.output_format,
+            files: FileSettings {
+                project_root: settings.file_resolver.project_root.clone(),
+                include: settings.file_resolver.include.patterns().to_vec(),
+                exclude: settings.file_resolver.exclude.patterns().to_vec(),
+                extend_exclude: settings.file_resolver.extend_exclude.patterns().to_vec(),
+                force_exclude: settings.file_resolver.force_exclude,
+                respect_gitignore: settings.file_resolver.respect_gitignore,
+            },
+            checker: CheckerView {
+                target_version: checker.target_version,
+                platform: checker.platform,
+                src: checker.src.clone(),
+                namespace_packages: checker.namespace_packages.clone(),
+                site_packages: checker.site_packages.clone(),
+                disallow_untyped_imports: checker.disallow_untyped_imports,
+                extra_modules: checker.extra_modules.clone(),
+                entry_points: checker.entry_points.clone(),
+                dead_code_keep: checker
+                    .dead_code_keep
+                    .iter()
+                    .map(|matcher| matcher.glob().glob().to_string())
+                    .collect(),
+                error: codes(MessageKind::Error),
+                warning: codes(MessageKind::Warning),
+                info: codes(MessageKind::Info),
+                hint: codes(MessageKind::Hint),
+                per_file_ignores: ErrorCodeSelector::All
+                    .all_rules()
+                    .filter(|code| checker.table.enabled(*code) && ignored.contains(*code))
+                    .map(|code| code.to_str())
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// Print the settings that `path` is checked with, after hierarchical discovery of the
+/// configuration files and the command-line `overrides`.
+pub(crate) fn config(
+    path: &Path,
+    pyproject_config: &PyprojectConfig,
+    overrides: &CliOverrides,
+    format: ConfigFormat,
+    writer: &mut impl Write,
+) -> Result<ExitStatus> {
+    let path = normalize_path(path);
+    let (_paths, skipped, resolver) =
+        python_files_in_path_with_skipped(&[path.clone()], pyproject_config, overrides)?;
+    let skipped = skipped
+        .iter()
+        .find(|skipped| skipped.path == path)
+        .map(|skipped| skipped.reason.to_string());
+    let settings = resolver.resolve(&path, pyproject_config);
+    let config_file = match resolver.settings_root(&path, pyproject_config) {
+        Some(root) => settings_toml(root)?,
+        None => pyproject_config.path.clone(),
+    };
+
+    let resolved = ResolvedConfig::new(&path, skipped, config_file, settings);
+    match format {
+        ConfigFormat::Toml => write!(writer, "{}", toml::to_string_pretty(&resolved)?)?,
+        ConfigFormat::Json => {
+            serde_json::to_writer_pretty(&mut *writer, &resolved)?;
+            writeln!(writer)?;
+        }
+    }
+    writer.flush()?;
+
+    Ok(ExitStatus::Success)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use pyrogen_workspace::resolver::{
+        resolve_root_settings, PyprojectConfig, PyprojectDiscoveryStrategy, Relativity,
+    };
+
+    use crate::args::{CliOverrides, ConfigFormat};
+
+    use super::config;
+
+    #[test]
+    fn resolved_settings() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        let pyproject = tempdir.path().join("pyproject.toml");
+        std::fs::write(
+            &pyproject,
+            r#"
+[tool.pyrogen]
+target-version = "py39"
+extend-exclude = ["generated"]
+preview = true
+warning = ["duplicate-key"]
+
+[tool.pyrogen.per-file-ignores]
+"tests/*" = ["duplicate-key"]
+"#,
+        )?;
+        std::fs::create_dir_all(tempdir.path().join("tests"))?;
+        let path = tempdir.path().join("tests").join("test_module.py");
+        std::fs::write(&path, "x = 1\n")?;
+
+        let overrides = CliOverrides::default();
+        let pyproject_config = PyprojectConfig::new(
+            PyprojectDiscoveryStrategy::Hierarchical,
+            resolve_root_settings(&pyproject, Relativity::Parent, &overrides)?,
+            Some(pyproject.clone()),
+        );
+        let mut output = Vec::new();
+        config(
+            &path,
+            &pyproject_config,
+            &overrides,
+            ConfigFormat::Json,
+            &mut output,
+        )?;
+        let resolved: serde_json::Value = serde_json::from_slice(&output)?;
+        assert_eq!(resolved["checker"]["target-version"], "py39");
+        assert_eq!(resolved["checker"]["warning"][0], "duplicate-key");
+        assert_eq!(resolved["checker"]["per-file-ignores"][0], "duplicate-key");
+        assert_eq!(resolved["files"]["extend-exclude"][0], "generated");
+        assert_eq!(
+            resolved["config-file"],
+            pyproject.to_string_lossy().as_ref()
+        );
+        assert!(resolved.get("skipped").is_none());
+
+        let mut output = Vec::new();
+        config(
+            &path,
+            &pyproject_config,
+            &overrides,
+            ConfigFormat::Toml,
+            &mut output,
+        )?;
+        assert!(String::from_utf8(output)?.contains("target-version = \"py39\""));
+        Ok(())
+    }
+}
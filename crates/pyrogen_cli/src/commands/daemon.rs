@@ -0,0 +1,283 @@
+//! `pyrogen daemon`, a long-running process that checks on behalf of `pyrogen --use-daemon`, so
+//! that repeated checks don't pay for starting up and for loading the caches from disk.
+//!
+//! The daemon listens on a Unix domain socket. For every check, the client connects, writes a
+//! [`DaemonRequest`] with its command-line arguments as a line of JSON, and reads back a
+//! [`DaemonResponse`] with the exit code and the output of the check. Requests are handled one at
+//! a time, in the order they arrive.
+//!
+//! The daemon keeps the interfaces of the project's modules in memory between checks. Settings
+//! are resolved for every check, so that changes to the configuration take effect right away.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ExitStatus;
+
+/// The name of the socket in the cache directory.
+const SOCKET_NAME: &str = "daemon.sock";
+
+/// A check, as sent by the client.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct DaemonRequest {
+    /// The working directory of the client, which must be the daemon's, since the paths of the
+    /// arguments and the output are relative to it.
+    working_directory: PathBuf,
+    /// The command-line arguments of the client, without the program name.
+    arguments: Vec<String>,
+    /// Whether the client's output supports colors.
+    color: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct DaemonResponse {
+    exit_code: u8,
+    output: String,
+}
+
+/// The socket of the daemon for the project with `cache_dir`.
+pub(crate) fn socket_path(cache_dir: &Path) -> PathBuf {
+    cache_dir.join(SOCKET_NAME)
+}
+
+fn exit_code(status: ExitStatus) -> u8 {
+    match status {
+        ExitStatus::Success => 0,
+        ExitStatus::Failure => 1,
+        ExitStatus::Error => 2,
+        ExitStatus::Warning => 3,
+    }
+}
+
+fn exit_status(code: u8) -> ExitStatus {
+    match code {
+        0 => ExitStatus::Success,
+        1 => ExitStatus::Failure,
+        3 => ExitStatus::Warning,
+        _ => ExitStatus::Error,
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::io::{BufRead, BufReader, Write};
+    use std::iter;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::Path;
+
+    use anyhow::{bail, Context, Result};
+    use clap::Parser;
+    use log::warn;
+
+    use pyrogen_checker::logging::LogLevel;
+
+    use super::{exit_code, exit_status, DaemonRequest, DaemonResponse};
+    use crate::args::Args;
+    use crate::{cache, check_in_long_lived_process, ExitStatus};
+
+    /// Serve checks on `socket` until the process is stopped.
+    pub(crate) fn serve(socket: &Path, log_level: LogLevel) -> Result<ExitStatus> {
+        if socket.exists() {
+            if UnixStream::connect(socket).is_ok() {
+                bail!("A daemon is already listening on `{}`", socket.display());
+            }
+            // Left behind by a daemon that didn't shut down cleanly.
+            std::fs::remove_file(socket)
+                .with_context(|| format!("Failed to remove `{}`", socket.display()))?;
+        }
+        if let Some(parent) = socket.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create `{}`", parent.display()))?;
+        }
+        let listener = UnixListener::bind(socket)
+            .with_context(|| format!("Failed to listen on `{}`", socket.display()))?;
+        let working_directory = std::env::current_dir()?;
+        cache::retain_interfaces();
+
+        if log_level >= LogLevel::Default {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("Listening on `{}`", socket.display());
+            }
+        }
+        for stream in listener.incoming() {
+            let result = stream
+                .map_err(anyhow::Error::from)
+                .and_then(|stream| handle(&stream, &working_directory));
+            if let Err(err) = result {
+                warn!("Failed to handle a request: {err:#}");
+            }
+        }
+        Ok(ExitStatus::Success)
+    }
+
+    fn handle(stream: &UnixStream, working_directory: &Path) -> Result<()> {
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line)?;
+        let request: DaemonRequest = serde_json::from_str(&line)?;
+
+        let mut output = Vec::new();
+        let status = match check_request(request, working_directory, &mut output) {
+            Ok(status) => status,
+            Err(err) => {
+                writeln!(output, "pyrogen failed")?;
+                for cause in err.chain() {
+                    writeln!(output, "  Cause: {cause}")?;
+                }
+                ExitStatus::Error
+            }
+        };
+
+        let response = DaemonResponse {
+            exit_code: exit_code(status),
+            output: String::from_utf8_lossy(&output).into_owned(),
+        };
+        let mut writer = stream;
+        serde_json::to_writer(&mut writer, &response)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Run the check described by the arguments of `request`.
+    fn check_request(
+        request: DaemonRequest,
+        working_directory: &Path,
+        output: &mut Vec<u8>,
+    ) -> Result<ExitStatus> {
+        if request.working_directory != working_directory {
+            bail!(
+                "The daemon checks `{}`, run `pyrogen daemon` in `{}` to check there",
+                working_directory.display(),
+                request.working_directory.display()
+            );
+        }
+        let args =
+            Args::try_parse_from(iter::once("pyrogen".to_string()).chain(request.arguments))?;
+        if args.command.is_some() || args.version {
+            bail!("The daemon only runs checks");
+        }
+        let log_level = LogLevel::from(&args.log_level_args);
+        let (cli, overrides) = args.checker_args.partition();
+
+        colored::control::set_override(request.color);
+        let status =
+            check_in_long_lived_process(cli, &overrides, log_level, "by the daemon", output);
+        colored::control::unset_override();
+        status
+    }
+
+    /// Send the check of this invocation to the daemon listening on `socket`, and write its
+    /// output to `writer`.
+    pub(crate) fn check_with_daemon(socket: &Path, writer: &mut impl Write) -> Result<ExitStatus> {
+        let arguments = argfile::expand_args_from(
+            wild::args_os().skip(1),
+            argfile::parse_fromfile,
+            argfile::PREFIX,
+        )?;
+        let request = DaemonRequest {
+            working_directory: std::env::current_dir()?,
+            arguments: arguments
+                .into_iter()
+                .map(|argument| argument.to_string_lossy().into_owned())
+                .collect(),
+            color: colored::control::SHOULD_COLORIZE.should_colorize(),
+        };
+
+        let stream = UnixStream::connect(socket).with_context(|| {
+            format!(
+                "Failed to connect to the daemon at `{}`, start it with `pyrogen daemon`",
+                socket.display()
+            )
+        })?;
+        let mut request_writer = &stream;
+        serde_json::to_writer(&mut request_writer, &request)?;
+        request_writer.write_all(b"\n")?;
+
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+        let response: DaemonResponse =
+            serde_json::from_str(&line).context("The daemon sent an invalid response")?;
+        writer.write_all(response.output.as_bytes())?;
+        writer.flush()?;
+        Ok(exit_status(response.exit_code))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::os::unix::net::UnixListener;
+        use std::thread;
+
+        use anyhow::Result;
+        use tempfile::TempDir;
+
+        use super::{handle, DaemonRequest, DaemonResponse};
+
+        #[test]
+        fn daemon_requests() -> Result<()> {
+            let tempdir = TempDir::new()?;
+            let working_directory = std::env::current_dir()?;
+            let invalid = tempdir.path().join("invalid.py");
+            std::fs::write(&invalid, "x: int = 'a'\n")?;
+
+            let socket = tempdir.path().join("daemon.sock");
+            let listener = UnixListener::bind(&socket)?;
+            let server = thread::spawn(move || -> Result<()> {
+                for stream in listener.incoming().take(2) {
+                    handle(&stream?, &working_directory)?;
+                }
+                Ok(())
+            });
+
+            let send = |request: &DaemonRequest| -> Result<DaemonResponse> {
+                let stream = std::os::unix::net::UnixStream::connect(&socket)?;
+                let mut writer = &stream;
+                serde_json::to_writer(&mut writer, request)?;
+                std::io::Write::write_all(&mut writer, b"\n")?;
+                Ok(serde_json::from_reader(&stream)?)
+            };
+
+            let response = send(&DaemonRequest {
+                working_directory: std::env::current_dir()?,
+                arguments: vec![
+                    "--isolated".to_string(),
+                    "--no-cache".to_string(),
+                    invalid.to_string_lossy().into_owned(),
+                ],
+                color: false,
+            })?;
+            assert_eq!(response.exit_code, 1);
+            assert!(response.output.contains("invalid.py"));
+
+            let response = send(&DaemonRequest {
+                working_directory: tempdir.path().to_path_buf(),
+                arguments: vec![],
+                color: false,
+            })?;
+            assert_eq!(response.exit_code, 2);
+            assert!(response.output.contains("The daemon checks"));
+
+            server.join().unwrap()
+        }
+    }
+}
+
+#[cfg(unix)]
+pub(crate) use unix::{check_with_daemon, serve};
+
+#[cfg(not(unix))]
+pub(crate) fn serve(
+    _socket: &Path,
+    _log_level: pyrogen_checker::logging::LogLevel,
+) -> anyhow::Result<ExitStatus> {
+    anyhow::bail!("The daemon is only supported on Unix")
+}
+
+#[cfg(not(unix))]
+pub(crate) fn check_with_daemon(
+    _socket: &Path,
+    _writer: &mut impl std::io::Write,
+) -> anyhow::Result<ExitStatus> {
+    anyhow::bail!("The daemon is only supported on Unix")
+}
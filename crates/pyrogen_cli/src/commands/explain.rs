@@ -0,0 +1,156 @@
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use itertools::Itertools;
+
+use pyrogen_checker::explain::{explain_line, Outcome};
+use pyrogen_checker::fs::{normalize_path, relativize_path};
+use pyrogen_checker::packaging;
+use pyrogen_checker::registry::ErrorCode;
+use pyrogen_checker::source_kind::SourceKind;
+use pyrogen_python_ast::PySourceType;
+use pyrogen_source_file::OneIndexed;
+use pyrogen_workspace::resolver::{python_files_in_path, PyprojectConfig};
+
+use crate::args::CliOverrides;
+use crate::diagnostics::read_source;
+use crate::ExitStatus;
+
+/// Print what happens to each diagnostic on `line` of the file at `path`, and which selected
+/// codes don't produce one there.
+pub(crate) fn explain(
+    path: &Path,
+    line: usize,
+    pyproject_config: &PyprojectConfig,
+    overrides: &CliOverrides,
+    writer: &mut impl Write,
+) -> Result<ExitStatus> {
+    let path = normalize_path(path);
+    // Only the resolver is needed: an excluded file can be explained, too.
+    let (_paths, resolver) = python_files_in_path(&[path.clone()], pyproject_config, overrides)?;
+    let settings = &resolver.resolve(&path, pyproject_config).checker;
+    let package = path
+        .parent()
+        .and_then(|parent| packaging::detect_package_root(parent, &settings.namespace_packages));
+
+    let source =
+        read_source(&path).with_context(|| format!("Failed to read {}", relativize_path(&path)))?;
+    let Some(explanation) = explain_line(
+        &path,
+        package,
+        settings,
+        &SourceKind::new(source),
+        PySourceType::from(path.as_path()),
+        None,
+        OneIndexed::from_zero_indexed(line.saturating_sub(1)),
+    ) else {
+        bail!("{} has fewer than {line} lines", relativize_path(&path));
+    };
+
+    writeln!(
+        writer,
+        "{}{}{} {}",
+        relativize_path(&path).bold(),
+        ":".cyan(),
+        explanation.line,
+        explanation.source_line.trim()
+    )?;
+    if let Some(directive) = &explanation.directive {
+        writeln!(writer, "  Directive on this line: `{directive}`")?;
+    }
+
+    if explanation.findings.is_empty() {
+        writeln!(writer, "  No diagnostics start on this line")?;
+    }
+    for finding in &explanation.findings {
+        let outcome = match &finding.outcome {
+            Outcome::Reported(kind) => format!("reported as {kind}"),
+            Outcome::Disabled => "not reported: the code isn't selected".to_string(),
+            Outcome::PerFileIgnored => "not reported: ignored by `per-file-ignores`".to_string(),
+            Outcome::FileExempted => {
+                "not reported: suppressed by a file-level `type: ignore` comment".to_string()
+            }
+            Outcome::Suppressed(directive) => format!("not reported: suppressed by `{directive}`"),
+        };
+        writeln!(
+            writer,
+            "  {}{}{} {} {}",
+            explanation.line,
+            ":".cyan(),
+            finding.column,
+            finding.code.to_string().red().bold(),
+            finding.body
+        )?;
+        writeln!(writer, "    {outcome}")?;
+    }
+
+    if !explanation.not_triggered.is_empty() {
+        writeln!(
+            writer,
+            "  Selected codes without a diagnostic on this line: {}",
+            explanation.not_triggered.iter().join(", ")
+        )?;
+    }
+    writer.flush()?;
+
+    Ok(ExitStatus::Success)
+}
+
+/// Print the documentation of `code`: what it reports, an example and whether it can be fixed.
+pub(crate) fn explain_code(code: ErrorCode, writer: &mut impl Write) -> Result<ExitStatus> {
+    let docs = code.docs();
+    writeln!(writer, "{} ({})", code.to_string().bold(), code.category())?;
+    writeln!(writer)?;
+    writeln!(writer, "{}", docs.summary)?;
+    writeln!(writer)?;
+    writeln!(writer, "{}", docs.explanation)?;
+    if let Some(example) = docs.example {
+        writeln!(writer)?;
+        writeln!(writer, "Example:")?;
+        writeln!(writer)?;
+        for line in example.lines() {
+            if line.is_empty() {
+                writeln!(writer)?;
+            } else {
+                writeln!(writer, "    {line}")?;
+            }
+        }
+    }
+    writeln!(writer)?;
+    writeln!(writer, "{}", docs.fix)?;
+    writer.flush()?;
+
+    Ok(ExitStatus::Success)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use pyrogen_checker::registry::ErrorCode;
+
+    use crate::ExitStatus;
+
+    use super::explain_code;
+
+    #[test]
+    fn error_code() -> Result<()> {
+        let mut output = Vec::new();
+        let status = explain_code(ErrorCode::UnusedTypeIgnore, &mut output)?;
+        let output = String::from_utf8(output)?;
+
+        assert_eq!(status, ExitStatus::Success);
+        let lines = output.lines().collect::<Vec<_>>();
+        assert!(lines[0].contains("unused-type-ignore"));
+        assert!(lines[0].ends_with(" (suppressions)"));
+        assert_eq!(
+            lines[2],
+            "A `type: ignore` comment that doesn't suppress anything."
+        );
+        assert!(output.contains("Example:\n\n    x = 1  # type: ignore\n"));
+        assert!(output.ends_with("\nAlways fixable\n"));
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use globset::{Glob, GlobMatcher};
+use itertools::Itertools;
+use path_absolutize::path_dedot;
+use rayon::prelude::*;
+
+use pyrogen_checker::definitions::{find_definitions, Definition};
+use pyrogen_checker::{fs, warn_user_once};
+use pyrogen_module_resolver::ModuleName;
+use pyrogen_python_ast::SourceType;
+use pyrogen_source_file::LineIndex;
+use pyrogen_workspace::resolver::{python_files_in_path, PyprojectConfig};
+
+use crate::args::CliOverrides;
+use crate::diagnostics::read_source;
+use crate::ExitStatus;
+
+/// A pattern for the names of definitions, with `*` and `?` wildcards.
+pub(crate) struct SymbolPattern {
+    matcher: GlobMatcher,
+    /// Whether the pattern is matched against qualified names rather than plain names.
+    qualified: bool,
+}
+
+impl SymbolPattern {
+    pub(crate) fn new(pattern: &str, ignore_case: bool) -> Result<Self> {
+        let matcher = globset::GlobBuilder::new(pattern)
+            .case_insensitive(ignore_case)
+            .build()
+            .map(|glob: Glob| glob.compile_matcher())
+            .with_context(|| format!("Invalid symbol pattern `{pattern}`"))?;
+        Ok(Self {
+            matcher,
+            qualified: pattern.contains('.'),
+        })
+    }
+
+    pub(crate) fn is_match(&self, definition: &Definition) -> bool {
+        self.matcher.is_match(if self.qualified {
+            &definition.qualified_name
+        } else {
+            &definition.name
+        })
+    }
+}
+
+/// Print the definitions below `files` whose names match `pattern`, sorted by location, as
+/// `path:line:column: kind qualified-name`.
+///
+/// Returns [`ExitStatus::Failure`] if nothing matched, so that scripts can tell the difference.
+pub(crate) fn find_symbol(
+    pattern: &SymbolPattern,
+    files: &[PathBuf],
+    pyproject_config: &PyprojectConfig,
+    overrides: &CliOverrides,
+    writer: &mut impl Write,
+) -> Result<ExitStatus> {
+    let files = if files.is_empty() {
+        vec![path_dedot::CWD.clone()]
+    } else {
+        files.to_vec()
+    };
+    let (paths, resolver) = python_files_in_path(&files, pyproject_config, overrides)?;
+    if paths.is_empty() {
+        warn_user_once!("No Python files found under the given path(s)");
+        return Ok(ExitStatus::Failure);
+    }
+
+    let paths = paths
+        .iter()
+        .flatten()
+        .map(ignore::DirEntry::path)
+        .collect::<Vec<_>>();
+    let package_roots = resolver.package_roots(&paths, pyproject_config);
+
+    let matches = paths
+        .par_iter()
+        .filter_map(|path| {
+            let SourceType::Python(source_type) = SourceType::from(path) else {
+                return None;
+            };
+            let package = path
+                .parent()
+                .and_then(|parent| package_roots.get(parent))
+                .and_then(|package| *package);
+            let module = module_name(path, package)?;
+            // Files that can't be read or parsed have no definitions to report.
+            let source = read_source(path).ok()?;
+            let definitions = find_definitions(&source, source_type, module.as_str())?
+                .into_iter()
+                .filter(|definition| pattern.is_match(definition))
+                .collect::<Vec<_>>();
+            if definitions.is_empty() {
+                return None;
+            }
+            let index = LineIndex::from_source_text(&source);
+            Some(
+                definitions
+                    .into_iter()
+                    .map(|definition| {
+                        let location = index.source_location(definition.range.start(), &source);
+                        (*path, location, definition)
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .flatten()
+        .collect::<Vec<_>>();
+
+    for (path, location, definition) in matches
+        .iter()
+        .sorted_by_key(|(path, location, _)| (*path, location.row, location.column))
+    {
+        writeln!(
+            writer,
+            "{}:{}:{}: {} {}",
+            fs::relativize_path(path),
+            location.row,
+            location.column,
+            definition.kind,
+            definition.qualified_name
+        )?;
+    }
+    writer.flush()?;
+
+    Ok(if matches.is_empty() {
+        ExitStatus::Failure
+    } else {
+        ExitStatus::Success
+    })
+}
+
+/// The name of the module at `path`, in the package rooted at `package`. Files outside of a
+/// package are top-level modules named after the file.
+fn module_name(path: &Path, package: Option<&Path>) -> Option<ModuleName> {
+    let root = match package {
+        Some(package) => package.parent()?,
+        None => path.parent()?,
+    };
+    ModuleName::from_relative_path(path, root)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir, write};
+
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use pyrogen_workspace::resolver::{PyprojectConfig, PyprojectDiscoveryStrategy};
+    use pyrogen_workspace::Settings;
+
+    use crate::args::CliOverrides;
+    use crate::ExitStatus;
+
+    use super::{find_symbol, SymbolPattern};
+
+    #[test]
+    fn find_definitions() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let root = tmp_dir.path();
+        create_dir(root.join("pkg"))?;
+        write(root.join("pkg/__init__.py"), "")?;
+        write(
+            root.join("pkg/shapes.py"),
+            "class Shape:\n    def area(self): ...\n\ndef shape_area(shape): ...\n",
+        )?;
+        write(
+            root.join("main.py"),
+            "from pkg.shapes import Shape\n\nshape = Shape()\n",
+        )?;
+
+        let pyproject_config =
+            PyprojectConfig::new(PyprojectDiscoveryStrategy::Fixed, Settings::default(), None);
+        let search = |pattern: &str, ignore_case: bool| -> Result<(ExitStatus, String)> {
+            let mut output = Vec::new();
+            let status = find_symbol(
+                &SymbolPattern::new(pattern, ignore_case)?,
+                &[root.to_path_buf()],
+                &pyproject_config,
+                &CliOverrides::default(),
+                &mut output,
+            )?;
+            Ok((status, String::from_utf8(output)?.replace('\\', "/")))
+        };
+
+        let (status, output) = search("shape*", true)?;
+        assert_eq!(status, ExitStatus::Success);
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("main.py:3:1: variable main.shape"));
+        assert!(lines[1].ends_with("pkg/shapes.py:1:7: class pkg.shapes.Shape"));
+        assert!(lines[2].ends_with("pkg/shapes.py:4:5: function pkg.shapes.shape_area"));
+
+        let (_, output) = search("pkg.*.area", false)?;
+        assert!(output.ends_with("pkg/shapes.py:2:9: method pkg.shapes.Shape.area\n"));
+
+        let (status, output) = search("missing", false)?;
+        assert_eq!(status, ExitStatus::Failure);
+        assert!(output.is_empty());
+
+        Ok(())
+    }
+}
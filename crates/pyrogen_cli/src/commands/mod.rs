@@ -1,2 +1,16 @@
+pub(crate) mod add_ignore;
+pub(crate) mod analyze;
 pub(crate) mod check;
+pub(crate) mod check_config;
 pub(crate) mod check_stdin;
+pub(crate) mod clean;
+pub(crate) mod cli_docs;
+pub(crate) mod config;
+pub(crate) mod daemon;
+pub(crate) mod explain;
+pub(crate) mod find_symbol;
+pub(crate) mod rule;
+pub(crate) mod show_files;
+pub(crate) mod suppressions;
+pub(crate) mod version;
+pub(crate) mod worker;
@@ -0,0 +1,157 @@
+use std::io::Write;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use pyrogen_checker::registry::{ErrorCode, FixAvailability};
+use pyrogen_checker::settings::code_table::{ErrorCodeTable, MessageKind};
+use pyrogen_checker::settings::types::{CheckingMode, PreviewMode};
+
+use crate::args::HelpFormat;
+use crate::ExitStatus;
+
+#[derive(Serialize)]
+struct Rule {
+    code: &'static str,
+    category: String,
+    summary: &'static str,
+    /// The severity in the default checking mode, or `None` if the code isn't reported there.
+    default_severity: Option<MessageKind>,
+    /// The severity under the configuration, or `None` if the code isn't enabled.
+    severity: Option<MessageKind>,
+    enabled: bool,
+    /// Whether the code is only enabled with `preview = true`.
+    preview: bool,
+    fixable: &'static str,
+}
+
+impl Rule {
+    fn new(code: ErrorCode, table: &ErrorCodeTable) -> Self {
+        let docs = code.docs();
+        Self {
+            code: code.to_str(),
+            category: code.category().to_string(),
+            summary: docs.summary,
+            default_severity: CheckingMode::default().default_kind(code, PreviewMode::default()),
+            severity: table.entry(code),
+            enabled: table.enabled(code),
+            preview: code.is_preview(),
+            fixable: match docs.fix {
+                FixAvailability::Always => "always",
+                FixAvailability::Sometimes => "sometimes",
+                FixAvailability::None => "never",
+            },
+        }
+    }
+}
+
+/// Print the error `codes` with their category, default severity and their severity under the
+/// configuration's code `table`.
+pub(crate) fn rule(
+    codes: impl IntoIterator<Item = ErrorCode>,
+    table: &ErrorCodeTable,
+    format: HelpFormat,
+    writer: &mut impl Write,
+) -> Result<ExitStatus> {
+    let rules = codes
+        .into_iter()
+        .map(|code| Rule::new(code, table))
+        .collect::<Vec<_>>();
+    match format {
+        HelpFormat::Text => {
+            let severity = |kind: Option<MessageKind>| {
+                kind.map_or_else(|| "off".to_string(), |kind| kind.to_string())
+            };
+            let width = rules.iter().map(|rule| rule.code.len()).max().unwrap_or(0);
+            writeln!(
+                writer,
+                "{:width$}  {:12}  {:8}  {:8}  summary",
+                "code", "category", "default", "current"
+            )?;
+            for rule in &rules {
+                writeln!(
+                    writer,
+                    "{:width$}  {:12}  {:8}  {:8}  {}",
+                    rule.code,
+                    rule.category,
+                    severity(rule.default_severity),
+                    severity(rule.severity),
+                    rule.summary
+                )?;
+            }
+        }
+        HelpFormat::Json => {
+            serde_json::to_writer_pretty(&mut *writer, &rules)?;
+            writeln!(writer)?;
+        }
+    }
+    writer.flush()?;
+
+    Ok(ExitStatus::Success)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use pyrogen_checker::registry::ErrorCode;
+    use pyrogen_checker::settings::code_table::ErrorCodeTable;
+    use pyrogen_checker::ErrorCodeSelector;
+
+    use crate::args::HelpFormat;
+
+    use super::rule;
+
+    #[test]
+    fn list_rules() -> Result<()> {
+        let mut table = ErrorCodeTable::empty();
+        table.enable_warning(ErrorCode::CallArgument);
+
+        let mut output = Vec::new();
+        rule(
+            ErrorCodeSelector::All.all_rules(),
+            &table,
+            HelpFormat::Json,
+            &mut output,
+        )?;
+        let rules: Vec<serde_json::Value> = serde_json::from_slice(&output)?;
+        assert_eq!(rules.len(), ErrorCodeSelector::All.all_rules().count());
+        let call_arg = rules
+            .iter()
+            .find(|rule| rule["code"] == "call-arg")
+            .unwrap();
+        assert_eq!(call_arg["category"], "typecheck");
+        // Codes in preview aren't reported by default.
+        assert_eq!(call_arg["default_severity"], serde_json::Value::Null);
+        assert_eq!(call_arg["severity"], "warning");
+        assert_eq!(call_arg["enabled"], true);
+        assert_eq!(call_arg["preview"], true);
+        let unused_import = rules
+            .iter()
+            .find(|rule| rule["code"] == "unused-import")
+            .unwrap();
+        assert_eq!(unused_import["default_severity"], "warning");
+        assert_eq!(unused_import["enabled"], false);
+        assert_eq!(unused_import["preview"], false);
+        let dead_code = rules
+            .iter()
+            .find(|rule| rule["code"] == "dead-code")
+            .unwrap();
+        assert_eq!(dead_code["default_severity"], serde_json::Value::Null);
+        assert_eq!(dead_code["enabled"], false);
+
+        let mut output = Vec::new();
+        rule(
+            [ErrorCode::UnusedTypeIgnore],
+            &table,
+            HelpFormat::Text,
+            &mut output,
+        )?;
+        let output = String::from_utf8(output)?;
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("code"));
+        assert!(lines[1].starts_with("unused-type-ignore  suppressions  off       off       "));
+        Ok(())
+    }
+}
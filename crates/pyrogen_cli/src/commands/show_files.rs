@@ -0,0 +1,83 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use itertools::Itertools;
+
+use pyrogen_checker::warn_user_once;
+use pyrogen_workspace::resolver::{python_files_in_path, PyprojectConfig};
+
+use crate::args::CliOverrides;
+
+/// Print the files that would be checked, sorted by path so that the output is stable across
+/// runs (file discovery itself is parallel and therefore unordered).
+pub(crate) fn show_files(
+    files: &[PathBuf],
+    pyproject_config: &PyprojectConfig,
+    overrides: &CliOverrides,
+    writer: &mut impl Write,
+) -> Result<()> {
+    // Collect all files in the hierarchy.
+    let (paths, _resolver) = python_files_in_path(files, pyproject_config, overrides)?;
+
+    if paths.is_empty() {
+        warn_user_once!("No Python files found under the given path(s)");
+        return Ok(());
+    }
+
+    // Print the list of files.
+    for path in paths
+        .into_iter()
+        .flatten()
+        .map(ignore::DirEntry::into_path)
+        .sorted_unstable()
+        .dedup()
+    {
+        writeln!(writer, "{}", path.to_string_lossy())?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir, File};
+
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use pyrogen_workspace::resolver::{PyprojectConfig, PyprojectDiscoveryStrategy};
+    use pyrogen_workspace::Settings;
+
+    use crate::args::CliOverrides;
+
+    use super::show_files;
+
+    #[test]
+    fn sorted_file_list() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let root = tmp_dir.path();
+        create_dir(root.join("pkg"))?;
+        File::create(root.join("pkg/b.py"))?;
+        File::create(root.join("pkg/a.py"))?;
+        File::create(root.join("main.py"))?;
+        File::create(root.join("notes.txt"))?;
+
+        let mut output = Vec::new();
+        show_files(
+            &[root.to_path_buf()],
+            &PyprojectConfig::new(PyprojectDiscoveryStrategy::Fixed, Settings::default(), None),
+            &CliOverrides::default(),
+            &mut output,
+        )?;
+
+        let expected = ["main.py", "pkg/a.py", "pkg/b.py"]
+            .iter()
+            .map(|path| format!("{}\n", root.join(path).to_string_lossy()))
+            .collect::<String>();
+        assert_eq!(String::from_utf8(output)?, expected);
+
+        Ok(())
+    }
+}
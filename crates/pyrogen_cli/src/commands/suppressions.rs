@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use itertools::Itertools;
+use log::warn;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use pyrogen_checker::fs::relativize_path;
+use pyrogen_checker::settings::types::SerializationFormat;
+use pyrogen_checker::type_ignore::{self, Suppression};
+use pyrogen_checker::warn_user_once;
+use pyrogen_python_ast::SourceType;
+use pyrogen_workspace::resolver::{python_files_in_path, PyprojectConfig};
+
+use crate::args::CliOverrides;
+use crate::diagnostics::read_source;
+
+#[derive(Serialize)]
+struct ExpandedSuppression {
+    filename: String,
+    line: usize,
+    codes: Vec<String>,
+    owner: Option<String>,
+    expires: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SuppressionReport {
+    suppressions: Vec<ExpandedSuppression>,
+    /// The number of suppressions of each owner.
+    owners: BTreeMap<String, usize>,
+    /// The number of suppressions without an owner.
+    unowned: usize,
+}
+
+impl SuppressionReport {
+    fn new(suppressions: Vec<(PathBuf, Suppression)>) -> Self {
+        let mut owners = BTreeMap::new();
+        let mut unowned = 0;
+        for (_, suppression) in &suppressions {
+            match &suppression.owner {
+                Some(owner) => *owners.entry(owner.clone()).or_default() += 1,
+                None => unowned += 1,
+            }
+        }
+        let suppressions = suppressions
+            .into_iter()
+            .map(|(path, suppression)| ExpandedSuppression {
+                filename: relativize_path(&path),
+                line: suppression.line.get(),
+                codes: suppression.codes,
+                owner: suppression.owner,
+                expires: suppression.expires.map(|date| date.to_string()),
+            })
+            .collect();
+        Self {
+            suppressions,
+            owners,
+            unowned,
+        }
+    }
+
+    fn write_text(&self, writer: &mut impl Write) -> Result<()> {
+        for suppression in &self.suppressions {
+            write!(
+                writer,
+                "{}{}{}",
+                suppression.filename.bold(),
+                ":".cyan(),
+                suppression.line
+            )?;
+            if suppression.codes.is_empty() {
+                write!(writer, " {}", "all codes".red().bold())?;
+            } else {
+                write!(writer, " {}", suppression.codes.join(", ").red().bold())?;
+            }
+            if let Some(owner) = &suppression.owner {
+                write!(writer, " owner={owner}")?;
+            }
+            if let Some(expires) = &suppression.expires {
+                write!(writer, " expires={expires}")?;
+            }
+            writeln!(writer)?;
+        }
+
+        let counts = self
+            .owners
+            .iter()
+            .map(|(owner, count)| (*count, owner.as_str().bold()))
+            .sorted_by_key(|(count, _)| std::cmp::Reverse(*count))
+            .chain((self.unowned > 0).then(|| (self.unowned, "(no owner)".dimmed())))
+            .collect::<Vec<_>>();
+        if let Some(width) = counts
+            .iter()
+            .map(|(count, _)| count.to_string().len())
+            .max()
+        {
+            writeln!(writer)?;
+            for (count, owner) in counts {
+                writeln!(writer, "{count:>width$}\t{owner}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Print the `type: ignore` comments of the Python files in `files`, with their annotations, and
+/// the number of comments per owner.
+pub(crate) fn suppressions(
+    files: &[PathBuf],
+    pyproject_config: &PyprojectConfig,
+    overrides: &CliOverrides,
+    writer: &mut impl Write,
+) -> Result<()> {
+    let (paths, _resolver) = python_files_in_path(files, pyproject_config, overrides)?;
+
+    if paths.is_empty() {
+        warn_user_once!("No Python files found under the given path(s)");
+        return Ok(());
+    }
+
+    let paths = paths
+        .into_iter()
+        .flatten()
+        .map(ignore::DirEntry::into_path)
+        .sorted_unstable()
+        .dedup()
+        .collect::<Vec<_>>();
+    let suppressions = paths
+        .par_iter()
+        .flat_map_iter(|path| file_suppressions(path))
+        .collect::<Vec<_>>();
+    let report = SuppressionReport::new(suppressions);
+
+    match pyproject_config.settings.output_format {
+        SerializationFormat::Text | SerializationFormat::Grouped => report.write_text(writer)?,
+        SerializationFormat::Json => {
+            writeln!(writer, "{}", serde_json::to_string_pretty(&report)?)?;
+        }
+        format => bail!("Unsupported serialization format for suppressions: {format:?}"),
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn file_suppressions(path: &Path) -> Vec<(PathBuf, Suppression)> {
+    let SourceType::Python(source_type) = SourceType::from(path) else {
+        return vec![];
+    };
+    match read_source(path) {
+        Ok(source) => type_ignore::suppressions(path, &source, source_type)
+            .into_iter()
+            .map(|suppression| (path.to_path_buf(), suppression))
+            .collect(),
+        Err(err) => {
+            warn!("Failed to read {}: {err}", relativize_path(path));
+            vec![]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use pyrogen_checker::settings::types::SerializationFormat;
+    use pyrogen_workspace::resolver::{PyprojectConfig, PyprojectDiscoveryStrategy};
+    use pyrogen_workspace::Settings;
+
+    use crate::args::CliOverrides;
+
+    use super::suppressions;
+
+    #[test]
+    fn suppressions_by_owner() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let root = tmp_dir.path();
+        std::fs::write(
+            root.join("a.py"),
+            "import os  # type: ignore  # pyrogen: owner=team-infra\n",
+        )?;
+        std::fs::write(
+            root.join("b.py"),
+            "x = 1  # type: ignore[call-arg]  # pyrogen: owner=team-infra expires=2025-12-31\n\
+             y = 2  # type: ignore[duplicate-key]\n",
+        )?;
+
+        let settings = Settings {
+            output_format: SerializationFormat::Json,
+            ..Settings::default()
+        };
+        let mut output = Vec::new();
+        suppressions(
+            &[root.to_path_buf()],
+            &PyprojectConfig::new(PyprojectDiscoveryStrategy::Fixed, settings, None),
+            &CliOverrides::default(),
+            &mut output,
+        )?;
+
+        let report: serde_json::Value = serde_json::from_slice(&output)?;
+        assert_eq!(report["owners"], serde_json::json!({"team-infra": 2}));
+        assert_eq!(report["unowned"], 1);
+        let suppressions = report["suppressions"].as_array().unwrap();
+        assert_eq!(suppressions.len(), 3);
+        assert_eq!(suppressions[0]["codes"], serde_json::json!([]));
+        assert_eq!(suppressions[1]["line"], 1);
+        assert_eq!(suppressions[1]["codes"], serde_json::json!(["call-arg"]));
+        assert_eq!(suppressions[1]["expires"], "2025-12-31");
+        assert_eq!(suppressions[2]["owner"], serde_json::Value::Null);
+        Ok(())
+    }
+}
@@ -0,0 +1,357 @@
+use std::fmt;
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::args::HelpFormat;
+use crate::ExitStatus;
+
+/// The latest release of pyrogen, as returned by the GitHub API.
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/tmke8/pyrogen/releases/latest";
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+    #[cfg_attr(not(feature = "self-update"), allow(dead_code))]
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+#[cfg_attr(not(feature = "self-update"), allow(dead_code))]
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    /// The version of the release, from a tag like `v0.2.0`.
+    fn version(&self) -> Result<Version> {
+        let tag = self.tag_name.trim_start_matches('v');
+        Version::parse(tag).with_context(|| format!("Invalid release tag `{}`", self.tag_name))
+    }
+}
+
+/// The version of pyrogen and how it was built, as embedded by the build script.
+#[derive(Debug, Serialize)]
+struct VersionInfo {
+    version: &'static str,
+    /// The commit the binary was built from, unless it was built outside of a git repository.
+    commit_hash: Option<&'static str>,
+    commit_date: Option<&'static str>,
+    build_date: &'static str,
+    target: &'static str,
+    features: Vec<&'static str>,
+}
+
+impl VersionInfo {
+    fn of_binary() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            commit_hash: option_env!("PYROGEN_COMMIT_HASH"),
+            commit_date: option_env!("PYROGEN_COMMIT_DATE"),
+            build_date: env!("PYROGEN_BUILD_DATE"),
+            target: env!("PYROGEN_TARGET"),
+            features: env!("PYROGEN_FEATURES")
+                .split(',')
+                .filter(|feature| !feature.is_empty())
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for VersionInfo {
+    /// Formats the version like `pyrogen 0.1.0 (0123456789ab 2023-11-02)`, followed by a line
+    /// for each detail of the build.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pyrogen {}", self.version)?;
+        match (self.commit_hash, self.commit_date) {
+            (Some(hash), Some(date)) => write!(f, " ({} {date})", &hash[..hash.len().min(12)])?,
+            (Some(hash), None) => write!(f, " ({})", &hash[..hash.len().min(12)])?,
+            _ => {}
+        }
+        writeln!(f)?;
+        writeln!(f, "build date: {}", self.build_date)?;
+        writeln!(f, "target: {}", self.target)?;
+        if self.features.is_empty() {
+            writeln!(f, "features: none")
+        } else {
+            writeln!(f, "features: {}", self.features.join(", "))
+        }
+    }
+}
+
+/// Print the version of pyrogen with the details of the build, as text or JSON.
+pub(crate) fn write_version(format: HelpFormat, writer: &mut impl Write) -> Result<ExitStatus> {
+    let info = VersionInfo::of_binary();
+    match format {
+        HelpFormat::Text => write!(writer, "{info}")?,
+        HelpFormat::Json => {
+            serde_json::to_writer_pretty(&mut *writer, &info)?;
+            writeln!(writer)?;
+        }
+    }
+    writer.flush()?;
+    Ok(ExitStatus::Success)
+}
+
+/// The version of the running binary.
+fn current_version() -> Version {
+    Version::parse(env!("CARGO_PKG_VERSION")).expect("the crate version to be valid semver")
+}
+
+/// Send a `GET` request to `url`, failing on error statuses.
+fn get(url: &str) -> Result<ureq::Response> {
+    ureq::get(url)
+        .set("User-Agent", concat!("pyrogen/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .with_context(|| format!("Failed to fetch {url}"))
+}
+
+fn latest_release() -> Result<Release> {
+    get(LATEST_RELEASE_URL)?
+        .into_json()
+        .context("Failed to parse the latest release")
+}
+
+/// The upgrade instructions if `latest` is newer than `current`.
+fn upgrade_message(current: &Version, latest: &Release) -> Result<Option<String>> {
+    let latest_version = latest.version()?;
+    if latest_version <= *current {
+        return Ok(None);
+    }
+    let mut message = format!(
+        "A newer version of pyrogen is available: {current} -> {latest_version}\n\nDownload it from {}",
+        latest.html_url
+    );
+    if cfg!(feature = "self-update") {
+        message.push_str(", or run `pyrogen self update`");
+    }
+    message.push('\n');
+    Ok(Some(message))
+}
+
+/// Print the version of pyrogen. With `check`, the latest release is looked up as well, and the
+/// exit status is a failure if it's newer than the running binary.
+pub(crate) fn version(
+    check: bool,
+    format: HelpFormat,
+    writer: &mut impl Write,
+) -> Result<ExitStatus> {
+    write_version(format, writer)?;
+    if !check {
+        return Ok(ExitStatus::Success);
+    }
+
+    let latest = latest_release()?;
+    let status = match upgrade_message(&current_version(), &latest)? {
+        Some(message) => {
+            writeln!(writer)?;
+            write!(writer, "{}", message.bold())?;
+            ExitStatus::Failure
+        }
+        None => {
+            writeln!(writer, "pyrogen is up to date")?;
+            ExitStatus::Success
+        }
+    };
+    writer.flush()?;
+    Ok(status)
+}
+
+/// The name of the release asset with the binary for the running platform, like
+/// `pyrogen-x86_64-linux`.
+#[cfg(feature = "self-update")]
+fn asset_name() -> String {
+    format!(
+        "pyrogen-{}-{}{}",
+        std::env::consts::ARCH,
+        std::env::consts::OS,
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+/// Check `binary` against the contents of a checksum file as written by `sha256sum`, i.e. the
+/// hex digest optionally followed by the file name.
+#[cfg(feature = "self-update")]
+fn verify_checksum(binary: &[u8], checksum_file: &str) -> Result<()> {
+    use sha2::{Digest, Sha256};
+
+    let Some(expected) = checksum_file.split_whitespace().next() else {
+        anyhow::bail!("The checksum file is empty");
+    };
+    let actual = format!("{:x}", Sha256::digest(binary));
+    if !actual.eq_ignore_ascii_case(expected) {
+        anyhow::bail!("The SHA-256 checksum of the download is {actual}, expected {expected}");
+    }
+    Ok(())
+}
+
+/// Remove the binary that a previous update on Windows moved out of the way, since it couldn't be
+/// deleted while it was running.
+#[cfg(feature = "self-update")]
+fn remove_previous_binary(exe: &std::path::Path) {
+    let previous = exe.with_extension("old.exe");
+    if cfg!(windows) && previous.exists() {
+        if let Err(err) = std::fs::remove_file(&previous) {
+            log::debug!("Failed to remove {}: {err}", previous.display());
+        }
+    }
+}
+
+/// Replace the running binary with the one of the latest release, if that's newer.
+///
+/// The download is verified against the `<asset>.sha256` file of the release before it replaces
+/// anything; releases without one aren't installed.
+#[cfg(feature = "self-update")]
+pub(crate) fn self_update(writer: &mut impl Write) -> Result<ExitStatus> {
+    use std::io::Read;
+
+    let exe = std::env::current_exe().context("Failed to locate the running binary")?;
+    remove_previous_binary(&exe);
+
+    let current = current_version();
+    let latest = latest_release()?;
+    let latest_version = latest.version()?;
+    if latest_version <= current {
+        writeln!(writer, "pyrogen {current} is up to date")?;
+        writer.flush()?;
+        return Ok(ExitStatus::Success);
+    }
+
+    let name = asset_name();
+    let Some(asset) = latest.assets.iter().find(|asset| asset.name == name) else {
+        anyhow::bail!(
+            "Release {} has no binary for this platform (`{name}`), download it from {}",
+            latest.tag_name,
+            latest.html_url
+        );
+    };
+    let checksum_name = format!("{name}.sha256");
+    let Some(checksum) = latest
+        .assets
+        .iter()
+        .find(|asset| asset.name == checksum_name)
+    else {
+        anyhow::bail!(
+            "Release {} has no checksum for `{name}`, download it from {}",
+            latest.tag_name,
+            latest.html_url
+        );
+    };
+
+    let checksum_file = get(&checksum.browser_download_url)?
+        .into_string()
+        .context("Failed to read the checksum")?;
+    let mut binary = vec![];
+    get(&asset.browser_download_url)?
+        .into_reader()
+        .read_to_end(&mut binary)
+        .context("Failed to download the binary")?;
+    verify_checksum(&binary, &checksum_file)
+        .with_context(|| format!("Refusing to install `{name}`"))?;
+
+    let dir = exe
+        .parent()
+        .context("The running binary has no parent directory")?;
+    // Write next to the binary, so that it can be renamed over it.
+    let mut download = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Failed to create a file in {}", dir.display()))?;
+    download.write_all(&binary)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(download.path(), std::fs::Permissions::from_mode(0o755))?;
+    }
+    // Windows doesn't allow replacing a running binary, but it can be renamed. The next update
+    // removes it.
+    #[cfg(windows)]
+    std::fs::rename(&exe, exe.with_extension("old.exe"))
+        .with_context(|| format!("Failed to move {}", exe.display()))?;
+    if let Err(err) = download.persist(&exe) {
+        #[cfg(windows)]
+        let _ = std::fs::rename(exe.with_extension("old.exe"), &exe);
+        return Err(err).with_context(|| format!("Failed to replace {}", exe.display()));
+    }
+
+    writeln!(writer, "Updated pyrogen from {current} to {latest_version}")?;
+    writer.flush()?;
+    Ok(ExitStatus::Success)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use semver::Version;
+
+    #[cfg(feature = "self-update")]
+    use super::verify_checksum;
+    use super::{upgrade_message, Release, VersionInfo};
+
+    fn release(tag_name: &str) -> Release {
+        Release {
+            tag_name: tag_name.to_string(),
+            html_url: format!("https://github.com/tmke8/pyrogen/releases/tag/{tag_name}"),
+            assets: vec![],
+        }
+    }
+
+    #[test]
+    fn upgrade_messages() -> Result<()> {
+        let current = Version::new(0, 2, 0);
+        assert_eq!(upgrade_message(&current, &release("v0.2.0"))?, None);
+        assert_eq!(upgrade_message(&current, &release("0.1.9"))?, None);
+        assert!(upgrade_message(&current, &release("v0.3.0-rc.1"))?.is_some());
+
+        let message = upgrade_message(&current, &release("v0.10.0"))?.unwrap();
+        assert!(message.starts_with("A newer version of pyrogen is available: 0.2.0 -> 0.10.0"));
+        assert!(message.contains("https://github.com/tmke8/pyrogen/releases/tag/v0.10.0"));
+
+        assert!(upgrade_message(&current, &release("latest")).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "self-update")]
+    #[test]
+    fn checksums() {
+        // `printf pyrogen | sha256sum`
+        let digest = "69fb267b8a38628c466f8c308251ac14370fe6909030499ea61b82f34cb3289a";
+        assert!(verify_checksum(b"pyrogen", &format!("{digest}  pyrogen-x86_64-linux\n")).is_ok());
+        assert!(verify_checksum(b"pyrogen", &digest.to_uppercase()).is_ok());
+        assert!(verify_checksum(b"pyrogen2", digest).is_err());
+        assert!(verify_checksum(b"pyrogen", "").is_err());
+    }
+
+    #[test]
+    fn version_info() {
+        let info = VersionInfo {
+            version: "0.1.0",
+            commit_hash: Some("0123456789abcdef0123456789abcdef01234567"),
+            commit_date: Some("2023-11-02"),
+            build_date: "2023-11-03",
+            target: "x86_64-unknown-linux-gnu",
+            features: vec!["self-update"],
+        };
+        assert_eq!(
+            info.to_string(),
+            "\
+pyrogen 0.1.0 (0123456789ab 2023-11-02)
+build date: 2023-11-03
+target: x86_64-unknown-linux-gnu
+features: self-update
+"
+        );
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(
+            json["commit_hash"],
+            "0123456789abcdef0123456789abcdef01234567"
+        );
+        assert_eq!(json["features"][0], "self-update");
+    }
+}
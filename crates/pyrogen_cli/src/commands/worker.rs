@@ -0,0 +1,374 @@
+//! Bazel's [persistent worker] protocol, which keeps a single `pyrogen` process alive across
+//! the check actions of a build.
+//!
+//! The build tool starts `pyrogen --persistent_worker` and writes `WorkRequest` messages to its
+//! stdin, each holding the arguments of a regular `pyrogen` invocation. For every request,
+//! `pyrogen` writes a `WorkResponse` with the exit code and the output of the check to stdout.
+//! Requests are handled one at a time, in the order they arrive.
+//!
+//! Only the default protobuf encoding of the messages is supported, not the JSON encoding
+//! (`--experimental_worker_allow_json_protocol`). The few fields we need are decoded by hand,
+//! rather than pulling in a protobuf code generator.
+//!
+//! [persistent worker]: https://bazel.build/remote/persistent
+
+use std::io::{self, BufRead, Read, Write};
+use std::iter;
+
+use anyhow::Result;
+use clap::Parser;
+
+use pyrogen_checker::logging::LogLevel;
+
+use crate::args::CheckCommand;
+use crate::{check_in_long_lived_process, ExitStatus};
+
+/// The fields of a `WorkRequest` that we use.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct WorkRequest {
+    arguments: Vec<String>,
+    request_id: i32,
+    /// Set for requests that cancel an earlier request. We don't advertise support for
+    /// cancellation, so these are never sent.
+    cancel: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct WorkResponse {
+    exit_code: i32,
+    output: String,
+    request_id: i32,
+}
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_FIXED64: u64 = 1;
+const WIRE_LENGTH_DELIMITED: u64 = 2;
+const WIRE_FIXED32: u64 = 5;
+
+/// Read a base 128 varint. Returns `None` if the reader is at the end of its input.
+fn read_varint(reader: &mut impl Read) -> io::Result<Option<u64>> {
+    let mut value = 0u64;
+    let mut byte = [0u8];
+    for shift in (0..64).step_by(7) {
+        if reader.read(&mut byte)? == 0 {
+            return if shift == 0 {
+                Ok(None)
+            } else {
+                Err(io::ErrorKind::UnexpectedEof.into())
+            };
+        }
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(value));
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "varint is too long",
+    ))
+}
+
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        #[allow(clippy::cast_possible_truncation)]
+        buffer.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    buffer.push(value as u8);
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+impl WorkRequest {
+    /// Read the next length-delimited request. Returns `None` once the build tool closed stdin.
+    fn read(reader: &mut impl Read) -> io::Result<Option<Self>> {
+        let Some(length) = read_varint(reader)? else {
+            return Ok(None);
+        };
+        let length = usize::try_from(length).map_err(|_| invalid_data("request is too large"))?;
+        let mut message = vec![0u8; length];
+        reader.read_exact(&mut message)?;
+        Self::decode(&message).map(Some)
+    }
+
+    fn decode(mut message: &[u8]) -> io::Result<Self> {
+        let mut request = Self::default();
+        while let Some(key) = read_varint(&mut message)? {
+            match (key >> 3, key & 0x7) {
+                (1, WIRE_LENGTH_DELIMITED) => {
+                    let argument = read_bytes(&mut message)?;
+                    request.arguments.push(
+                        String::from_utf8(argument)
+                            .map_err(|_| invalid_data("argument is not valid UTF-8"))?,
+                    );
+                }
+                (3, WIRE_VARINT) => {
+                    let value = read_varint(&mut message)?.ok_or_else(eof)?;
+                    // `int32` values are encoded sign-extended to 64 bits.
+                    #[allow(clippy::cast_possible_truncation)]
+                    {
+                        request.request_id = value as i32;
+                    }
+                }
+                (4, WIRE_VARINT) => {
+                    request.cancel = read_varint(&mut message)?.ok_or_else(eof)? != 0;
+                }
+                // Skip the fields we don't use, such as the input digests.
+                (_, WIRE_VARINT) => {
+                    read_varint(&mut message)?.ok_or_else(eof)?;
+                }
+                (_, WIRE_FIXED64) => skip(&mut message, 8)?,
+                (_, WIRE_LENGTH_DELIMITED) => {
+                    read_bytes(&mut message)?;
+                }
+                (_, WIRE_FIXED32) => skip(&mut message, 4)?,
+                (_, wire_type) => {
+                    return Err(invalid_data(&format!("unsupported wire type {wire_type}")));
+                }
+            }
+        }
+        Ok(request)
+    }
+}
+
+fn eof() -> io::Error {
+    io::ErrorKind::UnexpectedEof.into()
+}
+
+fn read_bytes(message: &mut &[u8]) -> io::Result<Vec<u8>> {
+    let length = read_varint(message)?.ok_or_else(eof)?;
+    let length = usize::try_from(length).map_err(|_| invalid_data("field is too large"))?;
+    let mut bytes = vec![0u8; length];
+    message.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn skip(message: &mut &[u8], length: usize) -> io::Result<()> {
+    if message.len() < length {
+        return Err(eof());
+    }
+    *message = &message[length..];
+    Ok(())
+}
+
+impl WorkResponse {
+    /// Write the response, prefixed with its length.
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mut message = Vec::new();
+        // Fields with default values are omitted, as in any proto3 encoding.
+        if self.exit_code != 0 {
+            write_varint(&mut message, (1 << 3) | WIRE_VARINT);
+            // Negative `int32` values are sign-extended to 64 bits.
+            #[allow(clippy::cast_sign_loss)]
+            write_varint(&mut message, i64::from(self.exit_code) as u64);
+        }
+        if !self.output.is_empty() {
+            write_varint(&mut message, (2 << 3) | WIRE_LENGTH_DELIMITED);
+            write_varint(&mut message, self.output.len() as u64);
+            message.extend_from_slice(self.output.as_bytes());
+        }
+        if self.request_id != 0 {
+            write_varint(&mut message, (3 << 3) | WIRE_VARINT);
+            #[allow(clippy::cast_sign_loss)]
+            write_varint(&mut message, i64::from(self.request_id) as u64);
+        }
+
+        let mut length = Vec::new();
+        write_varint(&mut length, message.len() as u64);
+        writer.write_all(&length)?;
+        writer.write_all(&message)?;
+        writer.flush()
+    }
+}
+
+/// Serve work requests read from `reader` until the build tool closes it, writing the responses
+/// to `writer`.
+pub(crate) fn run(
+    log_level: LogLevel,
+    mut reader: impl BufRead,
+    mut writer: impl Write,
+) -> Result<ExitStatus> {
+    while let Some(request) = WorkRequest::read(&mut reader)? {
+        if request.cancel {
+            continue;
+        }
+
+        let mut output = Vec::new();
+        let exit_status = match check_request(request.arguments, log_level, &mut output) {
+            Ok(exit_status) => exit_status,
+            Err(err) => {
+                writeln!(output, "pyrogen failed")?;
+                for cause in err.chain() {
+                    writeln!(output, "  Cause: {cause}")?;
+                }
+                ExitStatus::Error
+            }
+        };
+
+        WorkResponse {
+            exit_code: match exit_status {
+                ExitStatus::Success => 0,
+                ExitStatus::Failure => 1,
+                ExitStatus::Error => 2,
+                ExitStatus::Warning => 3,
+            },
+            output: String::from_utf8_lossy(&output).into_owned(),
+            request_id: request.request_id,
+        }
+        .write(&mut writer)?;
+    }
+
+    Ok(ExitStatus::Success)
+}
+
+/// Run the check described by the `arguments` of a work request.
+fn check_request(
+    arguments: Vec<String>,
+    log_level: LogLevel,
+    output: &mut Vec<u8>,
+) -> Result<ExitStatus> {
+    // Bazel passes long argument lists in `@file`s.
+    let arguments = argfile::expand_args_from(
+        arguments.into_iter().map(Into::into),
+        argfile::parse_fromfile,
+        argfile::PREFIX,
+    )?;
+    let (cli, overrides) =
+        CheckCommand::try_parse_from(iter::once("pyrogen".into()).chain(arguments))?.partition();
+
+    check_in_long_lived_process(
+        cli,
+        &overrides,
+        log_level,
+        "in persistent worker mode",
+        output,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use pyrogen_checker::logging::LogLevel;
+
+    use super::{
+        read_bytes, read_varint, run, write_varint, WorkRequest, WorkResponse,
+        WIRE_LENGTH_DELIMITED, WIRE_VARINT,
+    };
+
+    /// Encode a request the way a build tool would.
+    fn encode_request(arguments: &[&str], request_id: i32) -> Vec<u8> {
+        let mut message = Vec::new();
+        for argument in arguments {
+            write_varint(&mut message, (1 << 3) | WIRE_LENGTH_DELIMITED);
+            write_varint(&mut message, argument.len() as u64);
+            message.extend_from_slice(argument.as_bytes());
+        }
+        // An `inputs` entry, which the worker skips.
+        message.extend_from_slice(&[0x12, 0x03, 0x0a, 0x01, b'x']);
+        write_varint(&mut message, (3 << 3) | WIRE_VARINT);
+        #[allow(clippy::cast_sign_loss)]
+        write_varint(&mut message, i64::from(request_id) as u64);
+
+        let mut request = Vec::new();
+        write_varint(&mut request, message.len() as u64);
+        request.extend(message);
+        request
+    }
+
+    /// Decode a response, which has the same wire format as a request for the fields we test.
+    fn decode_response(reader: &mut Cursor<Vec<u8>>) -> Option<WorkResponse> {
+        let length = read_varint(reader).unwrap()?;
+        let mut message = vec![0u8; usize::try_from(length).unwrap()];
+        std::io::Read::read_exact(reader, &mut message).unwrap();
+
+        let mut response = WorkResponse::default();
+        let mut message = message.as_slice();
+        while let Some(key) = read_varint(&mut message).unwrap() {
+            match key {
+                0x08 => {
+                    #[allow(clippy::cast_possible_truncation)]
+                    {
+                        response.exit_code = read_varint(&mut message).unwrap().unwrap() as i32;
+                    }
+                }
+                0x12 => {
+                    let bytes = read_bytes(&mut message).unwrap();
+                    response.output = String::from_utf8(bytes).unwrap();
+                }
+                0x18 => {
+                    #[allow(clippy::cast_possible_truncation)]
+                    {
+                        response.request_id = read_varint(&mut message).unwrap().unwrap() as i32;
+                    }
+                }
+                _ => panic!("unexpected field {key:#x}"),
+            }
+        }
+        Some(response)
+    }
+
+    #[test]
+    fn request_round_trip() -> Result<()> {
+        let encoded = encode_request(&["--no-cache", "a.py"], -7);
+        let request = WorkRequest::read(&mut encoded.as_slice())?;
+        assert_eq!(
+            request,
+            Some(WorkRequest {
+                arguments: vec!["--no-cache".to_string(), "a.py".to_string()],
+                request_id: -7,
+                cancel: false,
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn work_requests() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        let valid = tempdir.path().join("valid.py");
+        std::fs::write(&valid, "x: int = 1\n")?;
+        let invalid = tempdir.path().join("invalid.py");
+        std::fs::write(&invalid, "x: int = 'a'\n")?;
+
+        let mut input = Vec::new();
+        for (arguments, request_id) in [
+            (vec!["--isolated", "--no-cache", valid.to_str().unwrap()], 1),
+            (
+                vec!["--isolated", "--no-cache", invalid.to_str().unwrap()],
+                2,
+            ),
+            (vec!["--unknown-flag"], 3),
+            (vec!["--isolated", "--watch", valid.to_str().unwrap()], 4),
+        ] {
+            input.extend(encode_request(&arguments, request_id));
+        }
+
+        let mut output = Vec::new();
+        run(LogLevel::Default, input.as_slice(), &mut output)?;
+
+        let mut output = Cursor::new(output);
+        let responses = std::iter::from_fn(|| decode_response(&mut output)).collect::<Vec<_>>();
+        assert_eq!(responses.len(), 4);
+
+        assert_eq!(responses[0].request_id, 1);
+        assert_eq!(responses[0].exit_code, 0);
+        assert_eq!(responses[1].request_id, 2);
+        assert_eq!(responses[1].exit_code, 1);
+        assert!(responses[1].output.contains("invalid.py"));
+        assert_eq!(responses[2].request_id, 3);
+        assert_eq!(responses[2].exit_code, 2);
+        assert!(responses[2].output.contains("--unknown-flag"));
+        assert_eq!(responses[3].request_id, 4);
+        assert_eq!(responses[3].exit_code, 2);
+        assert!(responses[3].output.contains("--watch"));
+
+        Ok(())
+    }
+}
@@ -1,20 +1,28 @@
 #![cfg_attr(target_family = "wasm", allow(dead_code))]
 
+use std::collections::BTreeMap;
 use std::io;
+use std::io::Write;
 use std::ops::AddAssign;
-#[cfg(unix)]
-use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
+use std::string::FromUtf8Error;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use colored::Colorize;
 use filetime::FileTime;
 use log::{debug, error, warn};
+use rustc_hash::{FxHashMap, FxHashSet};
 use rustpython_parser::text_size::{TextRange, TextSize};
+use similar::TextDiff;
 use thiserror::Error;
 
-use pyrogen_checker::checker::{lint_only, CheckerResult};
+use pyrogen_checker::cancellation::CancellationToken;
+use pyrogen_checker::checker::{lint_fix, lint_only, CheckerResult, FixerResult};
+use pyrogen_checker::fix::FixTable;
 use pyrogen_checker::fs;
+use pyrogen_checker::interface::ModuleContext;
+use pyrogen_checker::locale::Locale;
 use pyrogen_checker::logging::DisplayParseError;
 use pyrogen_checker::message::Message;
 use pyrogen_checker::pyproject_toml::lint_pyproject_toml;
@@ -27,30 +35,54 @@ use pyrogen_python_ast::{SourceType, TomlSourceType};
 use pyrogen_source_file::{LineIndex, SourceCode, SourceFileBuilder};
 use pyrogen_workspace::Settings;
 
-use crate::cache::Cache;
+use crate::cache::{Cache, FileMetadata};
 
 #[derive(CacheKey)]
-pub(crate) struct FileCacheKey {
-    /// Timestamp when the file was last modified before the (cached) check.
-    file_last_modified: FileTime,
-    /// Permissions of the file before the (cached) check.
-    file_permissions_mode: u32,
+pub(crate) enum FileCacheKey {
+    Metadata {
+        /// Timestamp when the file was last modified before the (cached) check.
+        file_last_modified: FileTime,
+        /// Permissions of the file before the (cached) check.
+        file_permissions_mode: u32,
+    },
+    Content {
+        /// Hash of the contents of the file before the (cached) check.
+        hash: u64,
+    },
 }
 
-impl FileCacheKey {
-    fn from_path(path: &Path) -> io::Result<FileCacheKey> {
-        // Construct a cache key for the file
-        let metadata = path.metadata()?;
+impl From<FileMetadata> for FileCacheKey {
+    fn from(metadata: FileMetadata) -> Self {
+        FileCacheKey::Metadata {
+            file_last_modified: metadata.last_modified,
+            file_permissions_mode: metadata.permissions,
+        }
+    }
+}
 
-        #[cfg(unix)]
-        let permissions = metadata.permissions().mode();
-        #[cfg(windows)]
-        let permissions: u32 = metadata.permissions().readonly().into();
+/// The module that a file is checked as.
+pub(crate) struct ProjectModule<'a> {
+    pub(crate) context: ModuleContext<'a>,
+    /// A fingerprint of the interfaces of the modules it imports and of its unreferenced
+    /// definitions, which invalidates the cached results when one of them changes.
+    pub(crate) dependencies: u64,
+}
 
-        Ok(FileCacheKey {
-            file_last_modified: FileTime::from_last_modification_time(&metadata),
-            file_permissions_mode: permissions,
-        })
+/// Report the problems found while loading the given configurations on stderr, each at most once.
+pub(crate) fn report_configuration_diagnostics<'a>(
+    settings: impl IntoIterator<Item = &'a Settings>,
+) {
+    let mut reported = FxHashSet::default();
+    for diagnostic in settings
+        .into_iter()
+        .flat_map(|settings| &settings.diagnostics)
+    {
+        if reported.insert(diagnostic) {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("{}{} {diagnostic}", "warning".yellow().bold(), ":".bold());
+            }
+        }
     }
 }
 
@@ -58,11 +90,30 @@ impl FileCacheKey {
 pub(crate) struct Messages {
     pub(crate) messages: Vec<Message>,
     pub(crate) imports: ImportMap,
+    /// The number of fixes applied to each file, by error code.
+    pub(crate) fixed: FxHashMap<String, FixTable>,
+    /// Whether the run stopped early at a diagnostic with a `fail-fast` code.
+    pub(crate) aborted: bool,
+    /// The unified diff of the fixes of each file with `--diff`, by file name.
+    pub(crate) diffs: BTreeMap<String, String>,
 }
 
 impl Messages {
     pub(crate) fn new(messages: Vec<Message>, imports: ImportMap) -> Self {
-        Self { messages, imports }
+        Self {
+            messages,
+            imports,
+            fixed: FxHashMap::default(),
+            aborted: false,
+            diffs: BTreeMap::default(),
+        }
+    }
+
+    /// Translate the messages into `locale`.
+    pub(crate) fn localize(&mut self, locale: Locale) {
+        for message in &mut self.messages {
+            message.diagnostic.localize(locale);
+        }
     }
 
     /// Generate [`Messages`] based on a [`SourceExtractionError`].
@@ -108,6 +159,16 @@ impl AddAssign for Messages {
     fn add_assign(&mut self, other: Self) {
         self.messages.extend(other.messages);
         self.imports.extend(other.imports);
+        self.aborted |= other.aborted;
+        self.diffs.extend(other.diffs);
+        for (filename, fixed) in other.fixed {
+            if !fixed.is_empty() {
+                let fixed_in_file = self.fixed.entry(filename).or_default();
+                for (code, count) in fixed {
+                    *fixed_in_file.entry(code).or_default() += count;
+                }
+            }
+        }
     }
 }
 
@@ -118,15 +179,22 @@ pub(crate) fn type_check_path(
     settings: &CheckerSettings,
     cache: Option<&Cache>,
     respect_type_ignore: flags::TypeIgnore,
+    fix_mode: flags::FixMode,
+    module: Option<&ProjectModule>,
+    cancellation: &CancellationToken,
 ) -> Result<Messages> {
-    // Check the cache.
+    // Check the cache. Fixing changes the file, so the cache only applies if nothing is fixed.
     let caching = match cache {
-        Some(cache) if respect_type_ignore.into() => {
+        Some(cache) if respect_type_ignore.into() && fix_mode == flags::FixMode::Generate => {
+            let _span = tracing::debug_span!("read_cache").entered();
             let relative_path = cache
                 .relative_path(path)
                 .expect("wrong package cache for file");
 
-            let cache_key = FileCacheKey::from_path(path).context("Failed to create cache key")?;
+            let cache_key = (
+                cache.file_key(path).context("Failed to create cache key")?,
+                module.map(|module| module.dependencies),
+            );
 
             if let Some(cache) = cache.get(relative_path, &cache_key) {
                 return Ok(cache.as_diagnostics(path));
@@ -148,13 +216,12 @@ pub(crate) fn type_check_path(
                 .iter_enabled()
                 .any(|rule_code| rule_code.lint_source().is_pyproject_toml())
             {
-                let contents =
-                    match std::fs::read_to_string(path).map_err(SourceExtractionError::Io) {
-                        Ok(contents) => contents,
-                        Err(err) => {
-                            return Ok(Messages::from_source_error(&err, Some(path), settings));
-                        }
-                    };
+                let contents = match read_source(path) {
+                    Ok(contents) => contents,
+                    Err(err) => {
+                        return Ok(Messages::from_source_error(&err, Some(path), settings));
+                    }
+                };
                 let source_file = SourceFileBuilder::new(path.to_string_lossy(), contents).finish();
                 lint_pyproject_toml(source_file, settings)
             } else {
@@ -179,24 +246,66 @@ pub(crate) fn type_check_path(
     };
     let source_kind = SourceKind::new(source_kind);
 
-    // Lint the file.
+    // Lint the file, and fix it if requested.
+    let mut diffs = BTreeMap::new();
+    let (result, source_kind, fixed) = if fix_mode == flags::FixMode::Generate {
+        let result = lint_only(
+            path,
+            package,
+            settings,
+            respect_type_ignore,
+            &source_kind,
+            source_type,
+            module.map(|module| &module.context),
+            cancellation,
+        )?;
+        (result, source_kind, FixTable::default())
+    } else {
+        let FixerResult {
+            result,
+            transformed,
+            fixed,
+        } = lint_fix(
+            path,
+            package,
+            settings,
+            respect_type_ignore,
+            &source_kind,
+            source_type,
+            module.map(|module| &module.context),
+            cancellation,
+        )?;
+        if !fixed.is_empty() {
+            match fix_mode {
+                flags::FixMode::Apply => {
+                    std::fs::write(path, transformed.original_source_code())
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                }
+                flags::FixMode::Diff => {
+                    let filename = fs::relativize_path(path);
+                    let diff = unified_diff(
+                        &filename,
+                        source_kind.original_source_code(),
+                        transformed.original_source_code(),
+                    );
+                    diffs.insert(filename, diff);
+                }
+                flags::FixMode::Generate => {}
+            }
+        }
+        (result, transformed, fixed)
+    };
     let CheckerResult {
         data: (messages, imports),
         error: parse_error,
-    } = lint_only(
-        path,
-        package,
-        settings,
-        respect_type_ignore,
-        &source_kind,
-        source_type,
-    );
+    } = result;
 
     let imports = imports.unwrap_or_default();
 
     if let Some((cache, relative_path, key)) = caching {
-        // We don't cache parsing errors.
-        if parse_error.is_none() {
+        // We don't cache parsing errors, nor the results of files with expiring `type: ignore`
+        // directives, which change with the date.
+        if parse_error.is_none() && !source_kind.source_code().contains("expires=") {
             cache.update(relative_path.to_owned(), key, &messages, &imports);
         }
     }
@@ -215,7 +324,13 @@ pub(crate) fn type_check_path(
         );
     }
 
-    Ok(Messages { messages, imports })
+    Ok(Messages {
+        messages,
+        imports,
+        fixed: FxHashMap::from_iter([(fs::relativize_path(path), fixed)]),
+        aborted: false,
+        diffs,
+    })
 }
 
 /// Generate `Diagnostic`s from source code content derived from
@@ -226,25 +341,70 @@ pub(crate) fn type_check_stdin(
     contents: String,
     settings: &Settings,
     noqa: flags::TypeIgnore,
+    fix_mode: flags::FixMode,
 ) -> Result<Messages> {
     let SourceType::Python(source_type) = path.map(SourceType::from).unwrap_or_default() else {
+        // The contents are passed through unchanged.
+        if fix_mode == flags::FixMode::Apply {
+            io::stdout().write_all(contents.as_bytes())?;
+        }
         return Ok(Messages::default());
     };
 
     let source_kind = SourceKind::new(contents);
-
-    // Lint the inputs.
+    let filename = path.unwrap_or_else(|| Path::new("-"));
+
+    // Lint the inputs, and fix them if requested.
+    let mut diffs = BTreeMap::new();
+    let (result, fixed) = if fix_mode == flags::FixMode::Generate {
+        let result = lint_only(
+            filename,
+            package,
+            &settings.checker,
+            noqa,
+            &source_kind,
+            source_type,
+            None,
+            &CancellationToken::default(),
+        )?;
+        (result, FixTable::default())
+    } else {
+        let FixerResult {
+            result,
+            transformed,
+            fixed,
+        } = lint_fix(
+            filename,
+            package,
+            &settings.checker,
+            noqa,
+            &source_kind,
+            source_type,
+            None,
+            &CancellationToken::default(),
+        )?;
+        match fix_mode {
+            // With `--fix`, the fixed source replaces the input on stdout.
+            flags::FixMode::Apply => {
+                io::stdout().write_all(transformed.original_source_code().as_bytes())?;
+            }
+            flags::FixMode::Diff if !fixed.is_empty() => {
+                let filename = fs::relativize_path(filename);
+                let diff = unified_diff(
+                    &filename,
+                    source_kind.original_source_code(),
+                    transformed.original_source_code(),
+                );
+                diffs.insert(filename, diff);
+            }
+            flags::FixMode::Diff | flags::FixMode::Generate => {}
+        }
+        (result, fixed)
+    };
     let CheckerResult {
         data: (messages, imports),
         error: parse_error,
-    } = lint_only(
-        path.unwrap_or_else(|| Path::new("-")),
-        package,
-        &settings.checker,
-        noqa,
-        &source_kind,
-        source_type,
-    );
+    } = result;
 
     let imports = imports.unwrap_or_default();
 
@@ -255,7 +415,19 @@ pub(crate) fn type_check_stdin(
         );
     }
 
-    Ok(Messages { messages, imports })
+    Ok(Messages {
+        messages,
+        imports,
+        fixed: FxHashMap::from_iter([(fs::relativize_path(filename), fixed)]),
+        aborted: false,
+        diffs,
+    })
+}
+
+/// A unified diff of the fixes to a file.
+fn unified_diff(filename: &str, original: &str, fixed: &str) -> String {
+    let diff = TextDiff::from_lines(original, fixed);
+    format!("{}\n", diff.unified_diff().header(filename, filename))
 }
 
 #[derive(Debug)]
@@ -265,29 +437,112 @@ impl LintSource {
     /// Extract the lint [`LintSource`] from the given file path.
     pub(crate) fn try_from_path(path: &Path) -> Result<Option<LintSource>, SourceExtractionError> {
         // This is tested by ruff_cli integration test `unreadable_file`
-        let contents = std::fs::read_to_string(path)?;
+        let contents = read_source(path)?;
         Ok(Some(LintSource(contents)))
     }
 }
 
+/// How often to retry reading a file that failed with a transient error.
+const READ_RETRIES: u32 = 3;
+
+/// Read the file at `path` as UTF-8.
+///
+/// Reads that fail with transient errors (like `EINTR` or `EAGAIN`, which network file systems
+/// can produce under load) are retried a few times, with a short backoff.
+pub(crate) fn read_source(path: &Path) -> Result<String, SourceExtractionError> {
+    let mut attempt = 0;
+    let contents = loop {
+        match std::fs::read(path) {
+            Ok(contents) => break contents,
+            Err(err) if attempt < READ_RETRIES && is_transient(&err) => {
+                attempt += 1;
+                debug!("Retrying to read {}: {err}", path.display());
+                std::thread::sleep(Duration::from_millis(10 * u64::from(attempt)));
+            }
+            Err(err) => return Err(err.into()),
+        }
+    };
+    Ok(String::from_utf8(contents)?)
+}
+
+fn is_transient(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
 #[derive(Error, Debug)]
 pub(crate) enum SourceExtractionError {
-    /// The extraction failed due to an [`io::Error`].
-    #[error(transparent)]
-    Io(#[from] io::Error),
+    /// The file doesn't exist (anymore).
+    #[error("File not found")]
+    NotFound(#[source] io::Error),
+    /// The file isn't readable by the current user.
+    #[error("Permission denied")]
+    PermissionDenied(#[source] io::Error),
+    /// The file isn't valid UTF-8.
+    #[error("Failed to decode file as UTF-8: {0}")]
+    Decode(#[from] FromUtf8Error),
+    /// The extraction failed due to any other [`io::Error`].
+    #[error("Failed to read file: {0}")]
+    Io(#[source] io::Error),
+}
+
+impl From<io::Error> for SourceExtractionError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::NotFound => Self::NotFound(err),
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied(err),
+            _ => Self::Io(err),
+        }
+    }
 }
 
 impl From<&SourceExtractionError> for Diagnostic {
     fn from(err: &SourceExtractionError) -> Self {
         match err {
             // IO errors.
-            SourceExtractionError::Io(_) => Diagnostic::new(
-                DiagnosticKind {
-                    error_code: ErrorCode::IOError,
-                    body: err.to_string(),
-                },
+            SourceExtractionError::NotFound(_)
+            | SourceExtractionError::PermissionDenied(_)
+            | SourceExtractionError::Decode(_)
+            | SourceExtractionError::Io(_) => Diagnostic::new(
+                DiagnosticKind::new(ErrorCode::IOError, err.to_string()),
                 TextRange::default(),
             ),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use super::{read_source, SourceExtractionError};
+
+    #[test]
+    fn classify_read_errors() -> Result<()> {
+        let tempdir = TempDir::new()?;
+
+        let missing = tempdir.path().join("missing.py");
+        assert!(matches!(
+            read_source(&missing),
+            Err(SourceExtractionError::NotFound(_))
+        ));
+
+        let latin1 = tempdir.path().join("latin1.py");
+        std::fs::write(&latin1, b"x = '\xe9'\n")?;
+        let err = read_source(&latin1).unwrap_err();
+        assert!(matches!(err, SourceExtractionError::Decode(_)));
+        assert_eq!(
+            err.to_string(),
+            "Failed to decode file as UTF-8: invalid utf-8 sequence of 1 bytes from index 5"
+        );
+
+        let valid = tempdir.path().join("valid.py");
+        std::fs::write(&valid, "x = 1\n")?;
+        assert_eq!(read_source(&valid)?, "x = 1\n");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+//! `--fixes-out`, which writes the fixes of the violations as edits of byte ranges, for tools that
+//! apply them on their own, like editors without a language server or code review bots.
+
+use std::path::Path;
+
+use anyhow::Result;
+use itertools::Itertools;
+use rustpython_parser::ast::Ranged;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use pyrogen_checker::message::{fingerprints, Message};
+
+use crate::write_output_file;
+
+/// The version of the format, which is raised on incompatible changes.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+struct FixesFile<'a> {
+    version: u32,
+    files: Vec<FileFixes<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct FileFixes<'a> {
+    filename: &'a str,
+    /// The SHA-256 of the contents that the byte ranges of the edits refer to, so that tools can
+    /// tell when the file changed since it was checked.
+    sha256: String,
+    fixes: Vec<ExpandedFix<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExpandedFix<'a> {
+    code: &'static str,
+    message: &'a str,
+    fingerprint: String,
+    title: &'a str,
+    edits: Vec<ExpandedEdit<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExpandedEdit<'a> {
+    /// The byte offset of the start of the replaced range.
+    start: u32,
+    /// The byte offset of the end of the replaced range, exclusive.
+    end: u32,
+    content: &'a str,
+}
+
+fn fixes_file<'a>(messages: &'a [Message], project_root: &Path) -> FixesFile<'a> {
+    let fingerprints = fingerprints(messages, Some(project_root));
+    let files = messages
+        .iter()
+        .zip(fingerprints)
+        .filter(|(message, _)| message.fix.is_some())
+        .group_by(|&(message, _)| message.filename())
+        .into_iter()
+        .map(|(filename, messages)| {
+            let messages = messages.collect::<Vec<_>>();
+            let source = messages[0].0.file.source_text();
+            FileFixes {
+                filename,
+                sha256: format!("{:x}", Sha256::digest(source.as_bytes())),
+                fixes: messages
+                    .into_iter()
+                    .filter_map(|(message, fingerprint)| {
+                        let fix = message.fix.as_ref()?;
+                        Some(ExpandedFix {
+                            code: message.diagnostic.error_code.to_str(),
+                            message: &message.diagnostic.body,
+                            fingerprint,
+                            title: fix.title(),
+                            edits: fix
+                                .edits()
+                                .iter()
+                                .map(|edit| ExpandedEdit {
+                                    start: edit.start().into(),
+                                    end: edit.end().into(),
+                                    content: edit.content().unwrap_or_default(),
+                                })
+                                .collect(),
+                        })
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    FixesFile {
+        version: FORMAT_VERSION,
+        files,
+    }
+}
+
+/// Write the fixes of `messages` to the JSON file at `path`. The messages must be sorted by file.
+pub(crate) fn write_fixes(path: &Path, messages: &[Message], project_root: &Path) -> Result<()> {
+    let mut contents = serde_json::to_string_pretty(&fixes_file(messages, project_root))?;
+    contents.push('\n');
+    write_output_file(path, contents.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use rustpython_parser::text_size::{TextRange, TextSize};
+
+    use pyrogen_checker::fix::{Edit, Fix};
+    use pyrogen_checker::message::Message;
+    use pyrogen_checker::registry::{Diagnostic, DiagnosticKind, ErrorCode};
+    use pyrogen_checker::settings::code_table::MessageKind;
+    use pyrogen_source_file::SourceFileBuilder;
+
+    use super::fixes_file;
+
+    #[test]
+    fn byte_ranges() {
+        let source = "x = 1  # type: ignore\ny = {1: 1, 1: 2}\n";
+        let file = SourceFileBuilder::new("module.py", source).finish();
+        let range = TextRange::new(TextSize::new(5), TextSize::new(21));
+        let messages = vec![
+            Message::from_diagnostic(
+                Diagnostic::new(
+                    DiagnosticKind::new(ErrorCode::UnusedTypeIgnore, "Unused type ignore"),
+                    range,
+                )
+                .with_fix(Fix::new("Remove", [Edit::range_deletion(range)])),
+                file.clone(),
+                TextSize::default(),
+                MessageKind::Warning,
+            ),
+            Message::from_diagnostic(
+                Diagnostic::new(
+                    DiagnosticKind::new(ErrorCode::DuplicateKey, "Repeated"),
+                    TextRange::new(TextSize::new(33), TextSize::new(34)),
+                ),
+                file,
+                TextSize::default(),
+                MessageKind::Warning,
+            ),
+        ];
+
+        let fixes = serde_json::to_value(fixes_file(&messages, Path::new(""))).unwrap();
+        assert_eq!(fixes["version"], 1);
+        let files = fixes["files"].as_array().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0]["filename"], "module.py");
+        assert_eq!(files[0]["sha256"].as_str().unwrap().len(), 64);
+        // Only the messages with a fix are written.
+        let fixes = files[0]["fixes"].as_array().unwrap();
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0]["code"], "unused-type-ignore");
+        assert_eq!(
+            fixes[0]["edits"],
+            serde_json::json!([{"start": 5, "end": 21, "content": ""}])
+        );
+    }
+}
@@ -0,0 +1,336 @@
+//! The files and lines that changed relative to a git ref, for `--diff-base`.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use rustc_hash::FxHashMap;
+
+/// The lines of each file that changed relative to a git ref, in the working tree.
+#[derive(Debug, Default)]
+pub(crate) struct ChangedLines {
+    /// The absolute paths of the changed files and their changed lines (as one-based ranges).
+    /// Untracked files are changed as a whole, which is represented as `None`.
+    files: FxHashMap<PathBuf, Option<Vec<Range<usize>>>>,
+}
+
+impl ChangedLines {
+    /// Ask git for the changes of the working tree relative to `base`, including untracked files.
+    pub(crate) fn since(base: &str) -> Result<Self> {
+        Self::since_in(Path::new("."), base)
+    }
+
+    /// Like [`ChangedLines::since`], but for the repository that contains `dir`.
+    fn since_in(dir: &Path, base: &str) -> Result<Self> {
+        // Git would take a `base` like `--output=file` for an option.
+        if base.starts_with('-') {
+            bail!("Invalid git ref `{base}`");
+        }
+        let commit = git(
+            dir,
+            &[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{base}^{{commit}}"),
+            ],
+        )
+        .with_context(|| format!("`{base}` doesn't name a git commit"))?;
+        let root = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?.trim_end());
+        // `from_diff` expects the paths relative to the root, with the default `a/` and `b/`
+        // prefixes, whatever the configuration of the user.
+        let diff = git(
+            dir,
+            &[
+                "-c",
+                "core.quotepath=false",
+                "-c",
+                "diff.noprefix=false",
+                "-c",
+                "diff.mnemonicPrefix=false",
+                "-c",
+                "diff.relative=false",
+                "diff",
+                "--unified=0",
+                "--no-color",
+                "--no-ext-diff",
+                "--diff-filter=d",
+                commit.trim_end(),
+                "--",
+            ],
+        )?;
+        let untracked = git(
+            dir,
+            &[
+                "ls-files",
+                "--others",
+                "--exclude-standard",
+                "--full-name",
+                "-z",
+            ],
+        )?;
+
+        let mut changed = Self::from_diff(&root, &diff);
+        for path in untracked.split('\0').filter(|path| !path.is_empty()) {
+            changed.files.insert(root.join(path), None);
+        }
+        Ok(changed)
+    }
+
+    /// Parse the output of `git diff --unified=0` that was run in the repository at `root`.
+    fn from_diff(root: &Path, diff: &str) -> Self {
+        let mut files: FxHashMap<PathBuf, Option<Vec<Range<usize>>>> = FxHashMap::default();
+        // The file that the following hunks belong to; `None` for deleted files.
+        let mut current = None;
+        // Whether the line is part of the header of a file, rather than of a hunk, where an added
+        // line can start with `+++ ` too.
+        let mut in_header = false;
+        for line in diff.lines() {
+            if line.starts_with("diff --git ") {
+                in_header = true;
+                current = None;
+            } else if let Some(path) = line.strip_prefix("+++ ").filter(|_| in_header) {
+                // Git appends a tab to paths with spaces.
+                let path = unquote(path.trim_end_matches('\t'));
+                current = path.strip_prefix("b/").map(|path| root.join(path));
+                if let Some(path) = &current {
+                    files
+                        .entry(path.clone())
+                        .or_insert_with(|| Some(Vec::new()));
+                }
+            } else if let Some(hunk) = line.strip_prefix("@@ ") {
+                in_header = false;
+                if let (Some(Some(ranges)), Some(range)) = (
+                    current.as_ref().and_then(|path| files.get_mut(path)),
+                    parse_hunk_header(hunk),
+                ) {
+                    ranges.push(range);
+                }
+            }
+        }
+        Self { files }
+    }
+
+    /// The absolute paths of the changed files.
+    pub(crate) fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+
+    /// Whether the one-based `line` of the file at the absolute `path` changed.
+    pub(crate) fn contains(&self, path: &Path, line: usize) -> bool {
+        match self.files.get(path) {
+            Some(Some(ranges)) => ranges.iter().any(|range| range.contains(&line)),
+            Some(None) => true,
+            None => false,
+        }
+    }
+}
+
+/// The range of new lines in a hunk header like `-12,3 +14,2 @@ def f():`, without the leading
+/// `@@ `. Hunks that only remove lines have no new lines.
+fn parse_hunk_header(hunk: &str) -> Option<Range<usize>> {
+    let new = hunk.split(' ').nth(1)?.strip_prefix('+')?;
+    let (start, count) = match new.split_once(',') {
+        Some((start, count)) => (start.parse::<usize>().ok()?, count.parse::<usize>().ok()?),
+        None => (new.parse::<usize>().ok()?, 1),
+    };
+    (count > 0).then_some(start..start + count)
+}
+
+/// A path of a diff header, which git puts in double quotes and escapes like a C string if it
+/// contains special characters.
+fn unquote(path: &str) -> String {
+    let Some(quoted) = path
+        .strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+    else {
+        return path.to_string();
+    };
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut rest = quoted.bytes();
+    while let Some(byte) = rest.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match rest.next() {
+            Some(b'a') => bytes.push(0x07),
+            Some(b'b') => bytes.push(0x08),
+            Some(b'f') => bytes.push(0x0c),
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b'r') => bytes.push(b'\r'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(b'v') => bytes.push(0x0b),
+            // Other bytes are escaped as three octal digits.
+            Some(digit @ b'0'..=b'3') => {
+                let mut value = digit - b'0';
+                for digit in rest.by_ref().take(2) {
+                    value = value * 8 + (digit - b'0');
+                }
+                bytes.push(value);
+            }
+            Some(byte) => bytes.push(byte),
+            None => {}
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Run git with `args` in `dir`, returning its standard output.
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+    String::from_utf8(output.stdout).context("git printed invalid UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use super::{git, parse_hunk_header, ChangedLines};
+
+    #[test]
+    fn hunk_headers() {
+        assert_eq!(parse_hunk_header("-12,3 +14,2 @@ def f():"), Some(14..16));
+        assert_eq!(parse_hunk_header("-1 +1 @@"), Some(1..2));
+        assert_eq!(parse_hunk_header("-5,2 +4,0 @@"), None);
+    }
+
+    #[test]
+    fn changed_lines() {
+        let diff = "\
+diff --git a/pkg/module.py b/pkg/module.py
+index 0000000..1111111 100644
+--- a/pkg/module.py
++++ b/pkg/module.py
+@@ -3 +3,2 @@ import os
+-x = 1
++x = 2
++y = 3
+@@ -10,2 +11,0 @@ def f():
+-    pass
+-    pass
+diff --git a/new.py b/new.py
+new file mode 100644
+--- /dev/null
++++ b/new.py
+@@ -0,0 +1 @@
++print()
+";
+        let root = Path::new("/repo");
+        let changed = ChangedLines::from_diff(root, diff);
+        let module = root.join("pkg/module.py");
+        assert!(!changed.contains(&module, 2));
+        assert!(changed.contains(&module, 3));
+        assert!(changed.contains(&module, 4));
+        assert!(!changed.contains(&module, 11));
+        assert!(changed.contains(&root.join("new.py"), 1));
+        assert!(!changed.contains(&root.join("other.py"), 1));
+
+        let mut files = changed.files().collect::<Vec<_>>();
+        files.sort_unstable();
+        assert_eq!(files, [root.join("new.py"), module]);
+    }
+
+    #[test]
+    fn added_lines_like_headers() {
+        // The added line `++ b/other.py` of a hunk starts with `+++ ` in the diff.
+        let diff = "\
+diff --git a/notes.py b/notes.py
+--- a/notes.py
++++ b/notes.py
+@@ -1,0 +2,2 @@
++++ b/other.py
++x = 1
+";
+        let root = Path::new("/repo");
+        let changed = ChangedLines::from_diff(root, diff);
+        assert!(changed.contains(&root.join("notes.py"), 3));
+        assert_eq!(changed.files().collect::<Vec<_>>(), [root.join("notes.py")]);
+    }
+
+    #[test]
+    fn quoted_paths() {
+        let diff = "\
+diff --git \"a/tab\\tthere.py\" \"b/tab\\tthere.py\"
+--- \"a/tab\\tthere.py\"
++++ \"b/tab\\tthere.py\"
+@@ -1 +1 @@
+-x = 1
++x = 2
+diff --git \"a/caf\\303\\251.py\" \"b/caf\\303\\251.py\"
+--- \"a/caf\\303\\251.py\"
++++ \"b/caf\\303\\251.py\"
+@@ -1 +1 @@
+-x = 1
++x = 2
+diff --git a/with space.py b/with space.py
+--- a/with space.py\t
++++ b/with space.py\t
+@@ -1 +1 @@
+-x = 1
++x = 2
+";
+        let root = Path::new("/repo");
+        let changed = ChangedLines::from_diff(root, diff);
+        assert!(changed.contains(&root.join("tab\tthere.py"), 1));
+        assert!(changed.contains(&root.join("café.py"), 1));
+        assert!(changed.contains(&root.join("with space.py"), 1));
+    }
+
+    #[test]
+    fn diff_configuration() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let root = tmp_dir.path().canonicalize()?;
+        let package = root.join("pkg");
+        fs::create_dir(&package)?;
+        fs::write(package.join("module.py"), "x = 1\n")?;
+        git(&root, &["init", "--quiet"])?;
+        git(&root, &["add", "."])?;
+        git(
+            &root,
+            &[
+                "-c",
+                "user.name=pyrogen",
+                "-c",
+                "user.email=pyrogen@example.com",
+                "commit",
+                "--quiet",
+                "--message=initial",
+            ],
+        )?;
+        // Settings that change the paths of `git diff`.
+        for (key, value) in [
+            ("diff.noprefix", "true"),
+            ("diff.mnemonicPrefix", "true"),
+            ("diff.relative", "true"),
+        ] {
+            git(&root, &["config", key, value])?;
+        }
+        fs::write(package.join("module.py"), "x = 1\ny = 2\n")?;
+
+        let changed = ChangedLines::since_in(&package, "HEAD")?;
+        assert_eq!(
+            changed.files().collect::<Vec<_>>(),
+            [package.join("module.py")]
+        );
+        assert!(changed.contains(&package.join("module.py"), 2));
+
+        Ok(())
+    }
+}
@@ -1,32 +1,51 @@
 use std::{
+    fs::File,
     io::{self, BufWriter, Write},
     path::{Path, PathBuf},
     process::ExitCode,
+    sync::mpsc::{channel, RecvTimeoutError},
+    time::Duration,
 };
 
-use anyhow::Result;
-use itertools::Itertools;
+use anyhow::{bail, Context, Result};
+use notify::{recommended_watcher, RecursiveMode, Watcher};
 use pyrogen_checker::{
+    cancellation::CancellationToken,
     fs,
     logging::{set_up_logging, LogLevel},
-    settings::code_table::MessageKind,
-    warn_user_once,
+    settings::{code_table::MessageKind, flags, types::SerializationFormat},
+    timing::RULE_TIMINGS,
+    warn_user, warn_user_once, ErrorCodeSelector,
 };
-use pyrogen_workspace::resolver::python_files_in_path;
+use pyrogen_workspace::resolver::{discoverable_python_files, PyprojectConfig};
 
-use crate::args::{Args, CheckCommand};
+use crate::args::{
+    AnalyzeCommand, Args, CheckArguments, CheckCommand, CleanCommand, CliOverrides, Command,
+    ConfigCommand, DaemonCommand, DevCommand, ExplainCommand, ExplainTarget, FindSymbolCommand,
+    HelpFormat, RuleCommand,
+};
+use crate::diagnostics::Messages;
+use crate::git::ChangedLines;
 use crate::printer::{Flags as PrinterFlags, Printer};
+use crate::profile::Profiler;
+use crate::redact::PathRedactor;
 
 pub mod args;
 mod cache;
 mod commands;
 mod diagnostics;
+mod fixes_out;
+mod git;
 mod panic;
 mod printer;
+mod profile;
+mod ratchet;
+mod redact;
 pub mod resolve;
+pub mod sample;
 mod stdin;
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ExitStatus {
     /// Linting was successful and there were no linting errors.
     Success,
@@ -34,6 +53,8 @@ pub enum ExitStatus {
     Failure,
     /// Linting failed.
     Error,
+    /// Linting was successful and there were only warnings, with `--exit-non-zero-on-warning`.
+    Warning,
 }
 
 impl From<ExitStatus> for ExitCode {
@@ -42,12 +63,13 @@ impl From<ExitStatus> for ExitCode {
             ExitStatus::Success => ExitCode::from(0),
             ExitStatus::Failure => ExitCode::from(1),
             ExitStatus::Error => ExitCode::from(2),
+            ExitStatus::Warning => ExitCode::from(3),
         }
     }
 }
 
 /// Returns true if the command should read from standard input.
-fn is_stdin(files: &[PathBuf], stdin_filename: Option<&Path>) -> bool {
+pub(crate) fn is_stdin(files: &[PathBuf], stdin_filename: Option<&Path>) -> bool {
     // If the user provided a `--stdin-filename`, always read from standard input.
     if stdin_filename.is_some() {
         if let Some(file) = files.iter().find(|file| file.as_path() != Path::new("-")) {
@@ -68,6 +90,8 @@ fn is_stdin(files: &[PathBuf], stdin_filename: Option<&Path>) -> bool {
 
 pub fn run(
     Args {
+        command,
+        version,
         checker_args,
         log_level_args,
     }: Args,
@@ -103,79 +127,817 @@ pub fn run(
     let log_level = LogLevel::from(&log_level_args);
     set_up_logging(&log_level)?;
 
-    check(checker_args, log_level)
+    if version {
+        let format = match checker_args.output_format {
+            Some(SerializationFormat::Json) => HelpFormat::Json,
+            _ => HelpFormat::Text,
+        };
+        let mut writer = BufWriter::new(io::stdout());
+        return commands::version::write_version(format, &mut writer);
+    }
+
+    match command {
+        Some(Command::Files(args)) => files(args),
+        Some(Command::CheckConfig(args)) => {
+            let mut writer = BufWriter::new(io::stdout());
+            commands::check_config::check_config(&args.paths, &mut writer)
+        }
+        Some(Command::Clean(args)) => clean(&args),
+        Some(Command::Analyze(args)) => analyze(&args),
+        Some(Command::FindSymbol(args)) => find_symbol(&args),
+        Some(Command::Explain(args)) => explain(&args),
+        Some(Command::Rule(args)) => rule(&args),
+        Some(Command::Config(args)) => config(&args),
+        Some(Command::Server) => {
+            pyrogen_server::run()?;
+            Ok(ExitStatus::Success)
+        }
+        Some(Command::Daemon(args)) => daemon(&args, log_level),
+        Some(Command::Version(args)) => {
+            let mut writer = BufWriter::new(io::stdout());
+            commands::version::version(args.check, args.output_format, &mut writer)
+        }
+        Some(Command::Dev(DevCommand::GenerateCliDocs(args))) => {
+            let mut writer = BufWriter::new(io::stdout());
+            commands::cli_docs::generate_cli_docs(&args.output_dir, args.check, &mut writer)
+        }
+        #[cfg(feature = "self-update")]
+        Some(Command::SelfCommand(args::SelfCommand::Update)) => {
+            let mut writer = BufWriter::new(io::stdout());
+            commands::version::self_update(&mut writer)
+        }
+        None => check(checker_args, log_level),
+    }
+}
+
+fn analyze(args: &AnalyzeCommand) -> Result<ExitStatus> {
+    let overrides = CliOverrides::default();
+    let pyproject_config = args.config.resolve(&overrides)?;
+
+    commands::analyze::analyze_json_rpc(
+        &pyproject_config,
+        &overrides,
+        io::stdin().lock(),
+        io::stdout(),
+    )?;
+
+    Ok(ExitStatus::Success)
+}
+
+fn find_symbol(args: &FindSymbolCommand) -> Result<ExitStatus> {
+    let pattern = commands::find_symbol::SymbolPattern::new(&args.pattern, args.ignore_case)?;
+    let overrides = CliOverrides::default();
+    let pyproject_config = args.config.resolve(&overrides)?;
+
+    let mut writer = BufWriter::new(io::stdout());
+    commands::find_symbol::find_symbol(
+        &pattern,
+        &args.files,
+        &pyproject_config,
+        &overrides,
+        &mut writer,
+    )
+}
+
+fn explain(args: &ExplainCommand) -> Result<ExitStatus> {
+    let (path, line) = match &args.target {
+        ExplainTarget::Code(code) => {
+            let mut writer = BufWriter::new(io::stdout());
+            return commands::explain::explain_code(*code, &mut writer);
+        }
+        ExplainTarget::Location(path, line) => (path, line),
+    };
+    let overrides = CliOverrides::default();
+    let pyproject_config = args.config.resolve(&overrides)?;
+
+    let mut writer = BufWriter::new(io::stdout());
+    commands::explain::explain(path, *line, &pyproject_config, &overrides, &mut writer)
+}
+
+fn rule(args: &RuleCommand) -> Result<ExitStatus> {
+    let pyproject_config = args.config.resolve(&CliOverrides::default())?;
+    let table = &pyproject_config.settings.checker.table;
+
+    let mut writer = BufWriter::new(io::stdout());
+    match args.code {
+        Some(code) => commands::rule::rule([code], table, args.output_format, &mut writer),
+        None => commands::rule::rule(
+            ErrorCodeSelector::All.all_rules(),
+            table,
+            args.output_format,
+            &mut writer,
+        ),
+    }
+}
+
+fn clean(args: &CleanCommand) -> Result<ExitStatus> {
+    let pyproject_config = args.config.resolve(&CliOverrides::default())?;
+
+    let mut writer = BufWriter::new(io::stdout());
+    commands::clean::clean(
+        &args.paths,
+        &pyproject_config.settings.cache_dir,
+        args.dry_run,
+        &mut writer,
+    )
+}
+
+fn daemon(args: &DaemonCommand, log_level: LogLevel) -> Result<ExitStatus> {
+    let socket = match &args.socket {
+        Some(socket) => socket.clone(),
+        None => {
+            let pyproject_config = args.config.resolve(&CliOverrides::default())?;
+            commands::daemon::socket_path(&pyproject_config.settings.cache_dir)
+        }
+    };
+    commands::daemon::serve(&socket, log_level)
+}
+
+fn config(args: &ConfigCommand) -> Result<ExitStatus> {
+    let overrides = args.overrides();
+    let pyproject_config = args.config.resolve(&overrides)?;
+
+    let mut writer = BufWriter::new(io::stdout());
+    commands::config::config(
+        &args.path,
+        &pyproject_config,
+        &overrides,
+        args.output_format,
+        &mut writer,
+    )
+}
+
+fn files(args: CheckCommand) -> Result<ExitStatus> {
+    let (mut cli, overrides) = args.partition();
+    if let Some(files_from) = &cli.files_from {
+        cli.files
+            .extend(read_files_from(files_from, cli.null_separated)?);
+    }
+
+    let pyproject_config = resolve::resolve(
+        cli.isolated,
+        cli.config.as_deref(),
+        &overrides,
+        cli.stdin_filename.as_deref(),
+    )?;
+
+    let mut writer = BufWriter::new(io::stdout());
+    commands::show_files::show_files(&cli.files, &pyproject_config, &overrides, &mut writer)?;
+
+    Ok(ExitStatus::Success)
 }
 
 pub fn check(args: CheckCommand, log_level: LogLevel) -> Result<ExitStatus> {
     let (cli, overrides) = args.partition();
 
+    if let Some(threads) = cli.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .context("Failed to set up the thread pool")?;
+    }
+
+    if cli.persistent_worker {
+        return commands::worker::run(log_level, io::stdin().lock(), io::stdout().lock());
+    }
+    if cli.use_daemon {
+        let socket = match cli.daemon_socket {
+            Some(socket) => socket,
+            None => {
+                let pyproject_config = resolve::resolve(
+                    cli.isolated,
+                    cli.config.as_deref(),
+                    &overrides,
+                    cli.stdin_filename.as_deref(),
+                )?;
+                commands::daemon::socket_path(&pyproject_config.settings.cache_dir)
+            }
+        };
+        let mut writer = BufWriter::new(io::stdout());
+        return commands::daemon::check_with_daemon(&socket, &mut writer);
+    }
+    if let Some(code) = cli.explain {
+        let mut writer = BufWriter::new(io::stdout());
+        return commands::explain::explain_code(code, &mut writer);
+    }
+
+    let mut writer = BufWriter::new(io::stdout());
+    check_with_writer(cli, &overrides, log_level, &mut writer)
+}
+
+/// Read the paths listed in the file at `path`, or in stdin for `-`.
+fn read_files_from(path: &Path, null_separated: bool) -> Result<Vec<PathBuf>> {
+    let contents = if path == Path::new("-") {
+        stdin::read_from_stdin().context("Failed to read the list of files from stdin")?
+    } else {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the list of files `{}`", path.display()))?
+    };
+    Ok(parse_file_list(&contents, null_separated))
+}
+
+/// Split a list of paths at newlines (`\n` or `\r\n`), or at NUL characters, skipping empty
+/// entries.
+fn parse_file_list(contents: &str, null_separated: bool) -> Vec<PathBuf> {
+    let entries: Vec<&str> = if null_separated {
+        contents.split('\0').collect()
+    } else {
+        contents.lines().collect()
+    };
+    entries
+        .into_iter()
+        .filter(|entry| !entry.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Replace the file at `path` with `contents`, creating its parent directories if needed. The
+/// contents are written to a temporary file that is then moved into place, so that readers never
+/// see a partially written file.
+fn write_output_file(path: &Path, contents: &[u8]) -> Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(parent)
+        .with_context(|| format!("Failed to create directory `{}`", parent.display()))?;
+    let mut file = tempfile::NamedTempFile::new_in(parent).with_context(|| {
+        format!(
+            "Failed to create a temporary file in `{}`",
+            parent.display()
+        )
+    })?;
+    file.write_all(contents)?;
+    file.persist(path)
+        .with_context(|| format!("Failed to write output file `{}`", path.display()))?;
+    Ok(())
+}
+
+/// How long to wait for further file changes before re-running the check, so that a burst of
+/// writes (like a `git checkout`) triggers a single run.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ChangeKind {
+    Configuration,
+    SourceFile,
+}
+
+/// Return the kind of the most significant change among the changed `paths`, or `None` if none of
+/// them affects the check.
+fn change_detected(paths: &[PathBuf]) -> Option<ChangeKind> {
+    if paths.iter().any(|path| {
+        path.file_name()
+            .is_some_and(|name| name == "pyproject.toml")
+    }) {
+        return Some(ChangeKind::Configuration);
+    }
+    if paths.iter().any(|path| {
+        path.extension()
+            .is_some_and(|extension| extension == "py" || extension == "pyi")
+    }) {
+        return Some(ChangeKind::SourceFile);
+    }
+    None
+}
+
+/// Check the files, and check them again whenever a source or configuration file changes.
+///
+/// Only returns on error.
+fn watch(
+    cli: &CheckArguments,
+    overrides: &CliOverrides,
+    mut pyproject_config: PyprojectConfig,
+    printer: &Printer,
+    cache: flags::Cache,
+    fix_mode: flags::FixMode,
+    writer: &mut dyn Write,
+) -> Result<ExitStatus> {
+    if pyproject_config.settings.output_format != SerializationFormat::Text {
+        warn_user!("`--output-format text` is always used in watch mode.");
+    }
+
+    let (tx, rx) = channel();
+    let mut watcher = recommended_watcher(tx)?;
+    for file in &cli.files {
+        watcher.watch(file, RecursiveMode::Recursive)?;
+    }
+    // The configuration file may live outside of the checked paths.
+    if let Some(path) = &pyproject_config.path {
+        watcher.watch(path, RecursiveMode::NonRecursive)?;
+    }
+
+    Printer::clear_screen()?;
+    printer.write_to_user("Starting checker in watch mode...\n");
+    let mut messages = commands::check::check(
+        &cli.files,
+        &pyproject_config,
+        overrides,
+        cache,
+        None,
+        fix_mode,
+        cli.show_skipped,
+        &CancellationToken::default(),
+    )?;
+    messages.localize(pyproject_config.settings.locale);
+    write_diffs(&messages, writer)?;
+    printer.write_continuously(writer, &messages)?;
+
+    loop {
+        let mut paths = rx.recv()??.paths;
+        // Collect the rest of the burst before deciding what to do.
+        loop {
+            match rx.recv_timeout(WATCH_DEBOUNCE) {
+                Ok(event) => paths.extend(event?.paths),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(err @ RecvTimeoutError::Disconnected) => return Err(err.into()),
+            }
+        }
+
+        let Some(change_kind) = change_detected(&paths) else {
+            continue;
+        };
+        if change_kind == ChangeKind::Configuration {
+            pyproject_config = resolve::resolve(
+                cli.isolated,
+                cli.config.as_deref(),
+                overrides,
+                cli.stdin_filename.as_deref(),
+            )?;
+        }
+
+        Printer::clear_screen()?;
+        printer.write_to_user("File change detected...\n");
+        let mut messages = commands::check::check(
+            &cli.files,
+            &pyproject_config,
+            overrides,
+            cache,
+            None,
+            fix_mode,
+            false,
+            &CancellationToken::default(),
+        )?;
+        messages.localize(pyproject_config.settings.locale);
+        write_diffs(&messages, writer)?;
+        printer.write_continuously(writer, &messages)?;
+    }
+}
+
+/// Run a check for a process that outlives it, like the persistent worker or the daemon, writing
+/// the diagnostics to `writer`. The options that don't make sense there are rejected, with errors
+/// that end in `mode`, like "by the daemon".
+pub(crate) fn check_in_long_lived_process(
+    cli: CheckArguments,
+    overrides: &CliOverrides,
+    log_level: LogLevel,
+    mode: &str,
+    writer: &mut dyn Write,
+) -> Result<ExitStatus> {
+    if cli.watch || cli.persistent_worker || cli.profile.is_some() {
+        bail!("`--watch`, `--persistent_worker` and `--profile` aren't supported {mode}");
+    }
+    // The stdin of the process doesn't belong to the check.
+    if is_stdin(&cli.files, cli.stdin_filename.as_deref())
+        || cli.files_from.as_deref() == Some(Path::new("-"))
+    {
+        bail!("Reading from stdin isn't supported {mode}");
+    }
+
+    // Each check is reported on as if it were a separate invocation.
+    RULE_TIMINGS.clear();
+
+    check_with_writer(cli, overrides, log_level, writer)
+}
+
+/// Run a check, writing the diagnostics to `writer`.
+pub(crate) fn check_with_writer(
+    mut cli: CheckArguments,
+    overrides: &CliOverrides,
+    log_level: LogLevel,
+    mut writer: &mut dyn Write,
+) -> Result<ExitStatus> {
+    if let Some(files_from) = &cli.files_from {
+        let listed = read_files_from(files_from, cli.null_separated)?;
+        if listed.is_empty() && cli.files.is_empty() {
+            warn_user_once!("No files listed in `--files-from`");
+            return Ok(ExitStatus::Success);
+        }
+        cli.files.extend(listed);
+    }
+
+    let profiler = (cli.profile.is_some() || cli.phase_timings.is_some())
+        .then(Profiler::install)
+        .transpose()?;
+
     // Construct the "default" settings. These are used when no `pyproject.toml`
     // files are present, or files are injected from outside of the hierarchy.
     let pyproject_config = resolve::resolve(
         cli.isolated,
         cli.config.as_deref(),
-        &overrides,
+        overrides,
         cli.stdin_filename.as_deref(),
     )?;
 
-    let mut writer: Box<dyn Write> = Box::new(BufWriter::new(io::stdout()));
+    // Only the changed files are checked, restricted to the given paths.
+    let changed_lines = if let Some(base) = &cli.diff_base {
+        let changed = ChangedLines::since(base)?;
+        let roots = cli.files.iter().map(fs::normalize_path).collect::<Vec<_>>();
+        let changed_files = changed
+            .files()
+            .filter(|path| path.is_file())
+            .filter(|path| roots.is_empty() || roots.iter().any(|root| path.starts_with(root)))
+            .map(Path::to_path_buf)
+            .collect::<Vec<_>>();
+        // The changed files are passed like explicit paths, which would skip the exclusions.
+        cli.files = discoverable_python_files(&changed_files, &pyproject_config, overrides)?;
+        cli.files.sort_unstable();
+        if cli.files.is_empty() {
+            warn_user_once!("No Python files changed relative to `{base}`");
+            return Ok(ExitStatus::Success);
+        }
+        cli.changed_lines_only.then_some(changed)
+    } else {
+        None
+    };
 
-    // Collect all files in the hierarchy.
-    let (paths, _resolver) = python_files_in_path(&cli.files, &pyproject_config, &overrides)?;
+    let is_stdin = is_stdin(&cli.files, cli.stdin_filename.as_deref());
 
-    if paths.is_empty() {
-        warn_user_once!("No Python files found under the given path(s)");
+    if cli.show_files {
+        commands::show_files::show_files(&cli.files, &pyproject_config, overrides, &mut writer)?;
         return Ok(ExitStatus::Success);
     }
 
-    // Print the list of files.
-    for entry in paths
-        .iter()
-        .flatten()
-        .sorted_by(|a, b| a.path().cmp(b.path()))
-    {
-        writeln!(writer, "{}", entry.path().to_string_lossy())?;
+    if cli.suppressions {
+        commands::suppressions::suppressions(
+            &cli.files,
+            &pyproject_config,
+            overrides,
+            &mut writer,
+        )?;
+        return Ok(ExitStatus::Success);
     }
 
-    let printer_flags = PrinterFlags::SHOW_VIOLATIONS;
+    let fix_mode = if cli.diff {
+        flags::FixMode::Diff
+    } else if cli.fix {
+        flags::FixMode::Apply
+    } else {
+        flags::FixMode::Generate
+    };
+
+    // With `--diff`, only the diffs are reported.
+    let printer_flags = match fix_mode {
+        flags::FixMode::Generate => PrinterFlags::SHOW_VIOLATIONS,
+        flags::FixMode::Apply => PrinterFlags::SHOW_VIOLATIONS | PrinterFlags::SHOW_FIX_SUMMARY,
+        flags::FixMode::Diff => PrinterFlags::SHOW_FIX_SUMMARY,
+    };
     let printer = Printer::new(
         pyproject_config.settings.output_format,
+        pyproject_config.settings.output_severity_mapping.clone(),
+        pyproject_config.settings.output_template.clone(),
+        pyproject_config.settings.column_encoding,
         log_level,
+        fix_mode,
         printer_flags,
-    );
+    )
+    .with_max_violations(cli.max_violations, cli.max_violations_per_file)
+    .with_project_root(pyproject_config.settings.checker.project_root.clone());
 
-    let is_stdin = is_stdin(&cli.files, cli.stdin_filename.as_deref());
     let cache = !cli.no_cache;
-    // TODO: make this configurable.
-    let respect_type_ignore = true;
+    let respect_type_ignore = pyproject_config.settings.respect_type_ignore;
+
+    if cli.add_ignore {
+        if is_stdin {
+            bail!("`--add-ignore` can't be used with standard input");
+        }
+        let modified = commands::add_ignore::add_ignore(
+            &cli.files,
+            &pyproject_config,
+            overrides,
+            cache.into(),
+            cli.show_skipped,
+        )?;
+        if modified > 0 && log_level >= LogLevel::Default {
+            let s = if modified == 1 { "" } else { "s" };
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("Added `type: ignore` comments to {modified} line{s}.");
+            }
+        }
+        return Ok(ExitStatus::Success);
+    }
+
+    if cli.watch {
+        if is_stdin {
+            bail!("Watch mode can't be used with standard input");
+        }
+        return watch(
+            &cli,
+            overrides,
+            pyproject_config,
+            &printer,
+            cache.into(),
+            fix_mode,
+            writer,
+        );
+    }
 
     // Generate lint violations.
-    let diagnostics = if is_stdin {
+    let mut diagnostics = if is_stdin {
         commands::check_stdin::check_stdin(
             cli.stdin_filename.map(fs::normalize_path).as_deref(),
             &pyproject_config,
-            &overrides,
+            overrides,
+            cache.into(),
             respect_type_ignore.into(),
+            fix_mode,
         )?
     } else {
         commands::check::check(
             &cli.files,
             &pyproject_config,
-            &overrides,
+            overrides,
             cache.into(),
-            respect_type_ignore.into(),
+            None,
+            fix_mode,
+            cli.show_skipped,
+            &CancellationToken::default(),
         )?
     };
-    printer.write_once(&diagnostics, &mut writer)?;
 
-    if !cli.exit_zero
-        && diagnostics
+    // The ratchet counts the violations of every checked file, before any of them are hidden.
+    let ratchet_increased = cli
+        .ratchet
+        .as_deref()
+        .map(|path| {
+            ratchet::check_ratchet(
+                path,
+                &diagnostics.messages,
+                cli.update_ratchet,
+                &mut io::stderr(),
+            )
+        })
+        .transpose()?;
+
+    if let Some(changed_lines) = &changed_lines {
+        diagnostics.messages.retain(|message| {
+            changed_lines.contains(
+                Path::new(message.filename()),
+                message.compute_start_location().row.get(),
+            )
+        });
+    }
+    if let Some(sample) = cli.sample {
+        let total = diagnostics.messages.len();
+        let skipped = sample.apply(
+            &mut diagnostics.messages,
+            &pyproject_config.settings.checker.project_root,
+        );
+        if skipped > 0 && log_level >= LogLevel::Default {
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!(
+                    "Reporting {} of {total} violations (--sample {sample}).",
+                    total - skipped
+                );
+            }
+        }
+    }
+    diagnostics.localize(pyproject_config.settings.locale);
+    if cli.redact_paths {
+        PathRedactor::for_project(&pyproject_config.settings.file_resolver.project_root)
+            .redact_messages(&mut diagnostics);
+    }
+    if let Some(fixes_out) = &cli.fixes_out {
+        fixes_out::write_fixes(
+            fixes_out,
+            &diagnostics.messages,
+            &pyproject_config.settings.checker.project_root,
+        )?;
+    }
+
+    // The writer gets the diffs with `--diff`, and stdout the fixed source with `--fix` on stdin,
+    // so the report goes to stderr.
+    write_diffs(&diagnostics, writer)?;
+    let mut stderr = io::stderr();
+    let writer: &mut dyn Write =
+        if fix_mode == flags::FixMode::Diff || (is_stdin && fix_mode == flags::FixMode::Apply) {
+            &mut stderr
+        } else {
+            writer
+        };
+    if cli.statistics {
+        printer.write_statistics(&diagnostics, writer)?;
+    } else if let Some(output_file) = &cli.output_file {
+        // Files get no colors, and stdout only the summary.
+        colored::control::set_override(false);
+        let mut output = Vec::new();
+        printer.write_messages(&diagnostics, &mut output)?;
+        write_output_file(output_file, &output)?;
+        printer.write_summary(&diagnostics, writer)?;
+    } else {
+        printer.write_once(&diagnostics, writer)?;
+    }
+
+    if cli.timings {
+        printer.write_rule_timings(&RULE_TIMINGS.slowest(10), &mut io::stderr())?;
+    }
+
+    if let (Some(profiler), Some(format)) = (&profiler, cli.phase_timings) {
+        printer.write_phase_timings(&profiler.phase_timings(), format, &mut io::stderr())?;
+    }
+
+    if let (Some(profiler), Some(path)) = (&profiler, &cli.profile) {
+        let mut file = BufWriter::new(
+            File::create(path)
+                .with_context(|| format!("Failed to create profile `{}`", path.display()))?,
+        );
+        profiler.write(&mut file)?;
+    }
+
+    Ok(match ratchet_increased {
+        Some(increased) if !cli.exit_zero && !diagnostics.aborted => {
+            if increased {
+                ExitStatus::Failure
+            } else {
+                ExitStatus::Success
+            }
+        }
+        _ => exit_status(&cli, &diagnostics, fix_mode),
+    })
+}
+
+/// Write the diffs of the fixes with `--diff`, sorted by file name.
+fn write_diffs(messages: &Messages, writer: &mut dyn Write) -> io::Result<()> {
+    for diff in messages.diffs.values() {
+        writer.write_all(diff.as_bytes())?;
+    }
+    writer.flush()
+}
+
+/// The exit status of a check that reported `diagnostics`.
+///
+/// Infos and hints never fail the check, and warnings only with `--exit-non-zero-on-warning`,
+/// unless they stopped it as `fail-fast` codes. Errors fail it once there are at least
+/// `--exit-zero-unless-error-count` of them; fewer are treated like warnings. With `--diff`, the
+/// check fails if there's anything to fix.
+fn exit_status(
+    cli: &CheckArguments,
+    diagnostics: &Messages,
+    fix_mode: flags::FixMode,
+) -> ExitStatus {
+    if cli.exit_zero {
+        return ExitStatus::Success;
+    }
+    if diagnostics.aborted {
+        return ExitStatus::Failure;
+    }
+    if fix_mode == flags::FixMode::Diff {
+        return if diagnostics.fixed.values().any(|fixed| !fixed.is_empty()) {
+            ExitStatus::Failure
+        } else {
+            ExitStatus::Success
+        };
+    }
+
+    let count = |kind: MessageKind| {
+        diagnostics
             .messages
-            .into_iter()
-            .any(|message| message.kind == MessageKind::Error)
-    {
-        return Ok(ExitStatus::Failure);
+            .iter()
+            .filter(|message| message.kind == kind)
+            .count() as u64
+    };
+    let errors = count(MessageKind::Error);
+    if errors > 0 && errors >= cli.exit_zero_unless_error_count.unwrap_or(1) {
+        ExitStatus::Failure
+    } else if cli.exit_non_zero_on_warning && errors + count(MessageKind::Warning) > 0 {
+        ExitStatus::Warning
+    } else {
+        ExitStatus::Success
     }
+}
 
-    Ok(ExitStatus::Success)
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+    use clap::Parser;
+    use rustpython_parser::text_size::{TextRange, TextSize};
+
+    use pyrogen_checker::message::Message;
+    use pyrogen_checker::registry::{Diagnostic, DiagnosticKind, ErrorCode};
+    use pyrogen_checker::settings::code_table::MessageKind;
+    use pyrogen_checker::settings::flags;
+    use pyrogen_python_ast::imports::ImportMap;
+    use pyrogen_source_file::SourceFileBuilder;
+
+    use crate::args::CheckCommand;
+    use crate::diagnostics::Messages;
+    use crate::ExitStatus;
+
+    use super::{change_detected, exit_status, parse_file_list, ChangeKind};
+
+    #[test]
+    fn file_lists() {
+        assert_eq!(
+            parse_file_list("src/a.py\r\n\nsrc/with space.py\n", false),
+            [
+                PathBuf::from("src/a.py"),
+                PathBuf::from("src/with space.py")
+            ]
+        );
+        assert_eq!(
+            parse_file_list("src/new\nline.py\0src/b.py\0", true),
+            [PathBuf::from("src/new\nline.py"), PathBuf::from("src/b.py")]
+        );
+        assert!(parse_file_list("", false).is_empty());
+    }
+
+    #[test]
+    fn exit_statuses() -> Result<()> {
+        let file = SourceFileBuilder::new("module.py", "").finish();
+        let messages = |kinds: &[MessageKind]| {
+            Messages::new(
+                kinds
+                    .iter()
+                    .map(|kind| {
+                        Message::from_diagnostic(
+                            Diagnostic::new(
+                                DiagnosticKind::new(ErrorCode::DuplicateKey, String::new()),
+                                TextRange::default(),
+                            ),
+                            file.clone(),
+                            TextSize::default(),
+                            *kind,
+                        )
+                    })
+                    .collect(),
+                ImportMap::default(),
+            )
+        };
+        let status = |arguments: &[&str], kinds: &[MessageKind]| -> Result<ExitStatus> {
+            let (cli, _) = CheckCommand::try_parse_from(
+                std::iter::once("pyrogen").chain(arguments.iter().copied()),
+            )?
+            .partition();
+            Ok(exit_status(
+                &cli,
+                &messages(kinds),
+                flags::FixMode::Generate,
+            ))
+        };
+
+        let warnings = [MessageKind::Warning, MessageKind::Info];
+        let errors = [MessageKind::Error, MessageKind::Error, MessageKind::Warning];
+        assert_eq!(status(&[], &warnings)?, ExitStatus::Success);
+        assert_eq!(status(&[], &errors)?, ExitStatus::Failure);
+        assert_eq!(
+            status(&["--exit-non-zero-on-warning"], &warnings)?,
+            ExitStatus::Warning
+        );
+        assert_eq!(
+            status(&["--exit-non-zero-on-warning"], &[MessageKind::Hint])?,
+            ExitStatus::Success
+        );
+        assert_eq!(
+            status(&["--exit-zero-unless-error-count", "3"], &errors)?,
+            ExitStatus::Success
+        );
+        assert_eq!(
+            status(
+                &[
+                    "--exit-zero-unless-error-count",
+                    "3",
+                    "--exit-non-zero-on-warning"
+                ],
+                &errors
+            )?,
+            ExitStatus::Warning
+        );
+        assert_eq!(
+            status(&["--exit-zero-unless-error-count", "2"], &errors)?,
+            ExitStatus::Failure
+        );
+        assert!(status(&["--exit-zero-unless-error-count", "0"], &errors).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn change_kinds() {
+        assert_eq!(change_detected(&[PathBuf::from("src/README.md")]), None);
+        assert_eq!(
+            change_detected(&[PathBuf::from("src/module.pyi")]),
+            Some(ChangeKind::SourceFile)
+        );
+        // A configuration change takes precedence over source changes in the same burst.
+        assert_eq!(
+            change_detected(&[
+                PathBuf::from("src/module.py"),
+                PathBuf::from("pyproject.toml")
+            ]),
+            Some(ChangeKind::Configuration)
+        );
+    }
 }
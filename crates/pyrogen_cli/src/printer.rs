@@ -1,7 +1,10 @@
 use std::cmp::Reverse;
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::hash::Hash;
 use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::Result;
 use bitflags::bitflags;
@@ -9,13 +12,24 @@ use colored::Colorize;
 use itertools::{iterate, Itertools};
 use serde::Serialize;
 
+use pyrogen_checker::fix::FixTable;
+use pyrogen_checker::fs::relativize_path;
 use pyrogen_checker::logging::LogLevel;
-use pyrogen_checker::message::{Emitter, GithubEmitter, JsonEmitter, TextEmitter};
+use pyrogen_checker::message::{
+    Emitter, GithubEmitter, GroupedEmitter, JsonEmitter, Message, MypyEmitter, OutputTemplate,
+    PylintEmitter, TemplateEmitter, TextEmitter,
+};
 use pyrogen_checker::notify_user;
 use pyrogen_checker::registry::{AsErrorCode, ErrorCode};
-use pyrogen_checker::settings::types::SerializationFormat;
+use pyrogen_checker::settings::code_table::MessageKind;
+use pyrogen_checker::settings::flags;
+use pyrogen_checker::settings::types::{SerializationFormat, SeverityMapping};
+use pyrogen_checker::timing::RuleTiming;
+use pyrogen_source_file::ColumnEncoding;
 
+use crate::args::TimingFormat;
 use crate::diagnostics::Messages;
+use crate::profile::{Phase, PhaseTimings};
 
 bitflags! {
     #[derive(Default, Debug, Copy, Clone)]
@@ -24,13 +38,16 @@ bitflags! {
         const SHOW_VIOLATIONS = 0b0000_0001;
         /// Whether to show the source code when emitting diagnostics.
         const SHOW_SOURCE = 0b000_0010;
+        /// Whether to show a summary of the fixed violations when emitting diagnostics.
+        const SHOW_FIX_SUMMARY = 0b0000_0100;
     }
 }
 
 #[derive(Serialize)]
-struct ExpandedStatistics<'a> {
+struct ExpandedStatistics {
     code: SerializeRuleAsCode,
-    message: &'a str,
+    severity: MessageKind,
+    message: &'static str,
     count: usize,
 }
 
@@ -59,23 +76,61 @@ impl From<ErrorCode> for SerializeRuleAsCode {
 
 pub(crate) struct Printer {
     format: SerializationFormat,
+    severity_mapping: SeverityMapping,
+    template: Option<OutputTemplate>,
+    column_encoding: ColumnEncoding,
     log_level: LogLevel,
+    fix_mode: flags::FixMode,
     flags: Flags,
+    /// The number of violations to print, in total and per file, with `--max-violations`.
+    max_violations: Option<usize>,
+    max_violations_per_file: Option<usize>,
+    /// The directory that the paths in the fingerprints of the JSON output are relative to.
+    project_root: Option<PathBuf>,
 }
 
 impl Printer {
-    pub(crate) const fn new(
+    pub(crate) fn new(
         format: SerializationFormat,
+        severity_mapping: SeverityMapping,
+        template: Option<OutputTemplate>,
+        column_encoding: ColumnEncoding,
         log_level: LogLevel,
+        fix_mode: flags::FixMode,
         flags: Flags,
     ) -> Self {
         Self {
             format,
+            severity_mapping,
+            template,
+            column_encoding,
             log_level,
+            fix_mode,
             flags,
+            max_violations: None,
+            max_violations_per_file: None,
+            project_root: None,
         }
     }
 
+    /// Print at most `total` violations, and at most `per_file` of each file.
+    #[must_use]
+    pub(crate) fn with_max_violations(
+        mut self,
+        total: Option<usize>,
+        per_file: Option<usize>,
+    ) -> Self {
+        self.max_violations = total;
+        self.max_violations_per_file = per_file;
+        self
+    }
+
+    #[must_use]
+    pub(crate) fn with_project_root(mut self, project_root: PathBuf) -> Self {
+        self.project_root = Some(project_root);
+        self
+    }
+
     pub(crate) fn write_to_user(&self, message: &str) {
         if self.log_level >= LogLevel::Default {
             notify_user!("{}", message);
@@ -90,6 +145,40 @@ impl Printer {
                 writeln!(writer, "Found {remaining} error{s}.")?;
             }
         }
+        if self.log_level >= LogLevel::Default && self.flags.intersects(Flags::SHOW_FIX_SUMMARY) {
+            let fixed = diagnostics
+                .fixed
+                .values()
+                .flat_map(FixTable::values)
+                .sum::<usize>();
+            if fixed > 0 {
+                let s = if fixed == 1 { "" } else { "s" };
+                if self.fix_mode == flags::FixMode::Diff {
+                    writeln!(writer, "Would fix {fixed} error{s}.")?;
+                } else {
+                    writeln!(writer, "Fixed {fixed} error{s}.")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the number of fixes applied to each file, by error code.
+    fn write_fix_summary(&self, writer: &mut dyn Write, diagnostics: &Messages) -> Result<()> {
+        let files = diagnostics
+            .fixed
+            .iter()
+            .filter(|(_, table)| !table.is_empty())
+            .sorted_by(|(a, _), (b, _)| a.cmp(b));
+        for (filename, table) in files {
+            writeln!(writer, "{}{}", filename.bold(), ":".cyan())?;
+            for (code, count) in table
+                .iter()
+                .sorted_by_key(|(code, count)| (Reverse(**count), code.to_str()))
+            {
+                writeln!(writer, "    {count:>3} × {}", code.to_str().red().bold())?;
+            }
+        }
         Ok(())
     }
 
@@ -98,31 +187,159 @@ impl Printer {
             return Ok(());
         }
 
-        if !self.flags.intersects(Flags::SHOW_VIOLATIONS) {
-            if matches!(self.format, SerializationFormat::Text) {
-                self.write_summary_text(writer, diagnostics)?;
+        if self.flags.intersects(Flags::SHOW_VIOLATIONS) {
+            self.write_messages(diagnostics, writer)?;
+        }
+        if matches!(
+            self.format,
+            SerializationFormat::Text | SerializationFormat::Grouped
+        ) {
+            if self.log_level >= LogLevel::Default
+                && self.fix_mode == flags::FixMode::Apply
+                && self.flags.intersects(Flags::SHOW_FIX_SUMMARY)
+            {
+                self.write_fix_summary(writer, diagnostics)?;
             }
+            self.write_summary_text(writer, diagnostics)?;
+        }
+
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Write the "Found N errors" summary, regardless of the output format.
+    pub(crate) fn write_summary(
+        &self,
+        diagnostics: &Messages,
+        writer: &mut dyn Write,
+    ) -> Result<()> {
+        if matches!(self.log_level, LogLevel::Silent) {
             return Ok(());
         }
 
+        self.write_summary_text(writer, diagnostics)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Write the violations in the output format, without a summary.
+    ///
+    /// With `--max-violations`, the violations beyond the limits are counted instead, except in
+    /// the JSON format, which is meant for tools and always has all of them.
+    pub(crate) fn write_messages(
+        &self,
+        diagnostics: &Messages,
+        writer: &mut dyn Write,
+    ) -> Result<()> {
+        if self.format == SerializationFormat::Json
+            || (self.max_violations.is_none() && self.max_violations_per_file.is_none())
+        {
+            self.emit(writer, &diagnostics.messages)?;
+        } else {
+            self.emit_limited(writer, &diagnostics.messages)?;
+        }
+
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Write the violations up to the limits of `--max-violations`, with a line saying how many
+    /// more there are. The messages are sorted, so those of a file are next to each other.
+    fn emit_limited(&self, writer: &mut dyn Write, messages: &[Message]) -> Result<()> {
+        let mut remaining = self.max_violations.unwrap_or(usize::MAX);
+        let mut hidden_files = 0;
+        let mut hidden_messages = 0;
+        let mut rest = messages;
+        while let Some(first) = rest.first() {
+            let len = rest
+                .iter()
+                .take_while(|message| message.file == first.file)
+                .count();
+            let (file, tail) = rest.split_at(len);
+            rest = tail;
+
+            let limit = self
+                .max_violations_per_file
+                .unwrap_or(usize::MAX)
+                .min(remaining);
+            let (shown, hidden) = file.split_at(limit.min(file.len()));
+            remaining -= shown.len();
+            if shown.is_empty() {
+                hidden_files += 1;
+                hidden_messages += hidden.len();
+                continue;
+            }
+            self.emit(writer, shown)?;
+            if !hidden.is_empty() {
+                writeln!(
+                    writer,
+                    "{} in {}",
+                    more_violations(hidden.len()),
+                    relativize_path(first.filename())
+                )?;
+            }
+        }
+        if hidden_files > 0 {
+            let s = if hidden_files == 1 { "" } else { "s" };
+            writeln!(
+                writer,
+                "{} in {hidden_files} more file{s}",
+                more_violations(hidden_messages)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write `messages` in the output format.
+    fn emit(&self, writer: &mut dyn Write, messages: &[Message]) -> Result<()> {
         match self.format {
             SerializationFormat::Text => {
                 TextEmitter::default()
                     .with_show_source(self.flags.intersects(Flags::SHOW_SOURCE))
-                    .emit(writer, &diagnostics.messages)?;
-
-                self.write_summary_text(writer, diagnostics)?;
+                    .with_column_encoding(self.column_encoding)
+                    .emit(writer, messages)?;
+            }
+            SerializationFormat::Grouped => {
+                GroupedEmitter::default()
+                    .with_show_source(self.flags.intersects(Flags::SHOW_SOURCE))
+                    .with_column_encoding(self.column_encoding)
+                    .emit(writer, messages)?;
             }
             SerializationFormat::Json => {
-                JsonEmitter.emit(writer, &diagnostics.messages)?;
+                let mut emitter = JsonEmitter::default()
+                    .with_severity_mapping(self.severity_mapping.clone())
+                    .with_column_encoding(self.column_encoding);
+                if let Some(project_root) = &self.project_root {
+                    emitter = emitter.with_project_root(project_root);
+                }
+                emitter.emit(writer, messages)?;
             }
             SerializationFormat::Github => {
-                GithubEmitter.emit(writer, &diagnostics.messages)?;
+                GithubEmitter::default()
+                    .with_severity_mapping(self.severity_mapping.clone())
+                    .with_column_encoding(self.column_encoding)
+                    .emit(writer, messages)?;
+            }
+            SerializationFormat::Pylint => {
+                PylintEmitter.emit(writer, messages)?;
+            }
+            SerializationFormat::Mypy => {
+                MypyEmitter::default()
+                    .with_severity_mapping(self.severity_mapping.clone())
+                    .emit(writer, messages)?;
+            }
+            SerializationFormat::Custom => {
+                let Some(template) = &self.template else {
+                    anyhow::bail!("The custom output format requires an output template");
+                };
+                TemplateEmitter::new(template.clone())
+                    .with_column_encoding(self.column_encoding)
+                    .emit(writer, messages)?;
             }
         }
-
-        writer.flush()?;
-
         Ok(())
     }
 
@@ -131,25 +348,22 @@ impl Printer {
         diagnostics: &Messages,
         writer: &mut dyn Write,
     ) -> Result<()> {
+        // The messages are sorted by location, so they're grouped by code first. Codes with the
+        // same count stay in alphabetical order.
         let statistics: Vec<ExpandedStatistics> = diagnostics
             .messages
             .iter()
-            .map(|message| (message.diagnostic.error_code(), &message.diagnostic.body))
-            .fold(vec![], |mut acc, (rule, body)| {
-                if let Some((prev_rule, _, count)) = acc.last_mut() {
-                    if *prev_rule == rule {
-                        *count += 1;
-                        return acc;
-                    }
+            .sorted_by_key(|message| message.diagnostic.error_code().to_str())
+            .group_by(|message| message.diagnostic.error_code())
+            .into_iter()
+            .map(|(code, messages)| {
+                let messages = messages.collect::<Vec<_>>();
+                ExpandedStatistics {
+                    code: code.into(),
+                    severity: self.severity_mapping.map(messages[0].kind),
+                    message: code.docs().summary,
+                    count: messages.len(),
                 }
-                acc.push((rule, body, 1));
-                acc
-            })
-            .iter()
-            .map(|(rule, message, count)| ExpandedStatistics {
-                code: (*rule).into(),
-                count: *count,
-                message,
             })
             .sorted_by_key(|statistic| Reverse(statistic.count))
             .collect();
@@ -159,7 +373,7 @@ impl Printer {
         }
 
         match self.format {
-            SerializationFormat::Text => {
+            SerializationFormat::Text | SerializationFormat::Grouped => {
                 // Compute the maximum number of digits in the count and code, for all messages,
                 // to enable pretty-printing.
                 let count_width = num_digits(
@@ -203,6 +417,155 @@ impl Printer {
         Ok(())
     }
 
+    /// Write the cumulative time spent in each of the given rules, as a table.
+    pub(crate) fn write_rule_timings(
+        &self,
+        timings: &[RuleTiming],
+        writer: &mut dyn Write,
+    ) -> Result<()> {
+        if matches!(self.log_level, LogLevel::Silent) || timings.is_empty() {
+            return Ok(());
+        }
+
+        let code_width = timings
+            .iter()
+            .map(|timing| timing.code.to_str().len())
+            .chain(["Rule".len()])
+            .max()
+            .unwrap();
+        writeln!(
+            writer,
+            "{:<code_width$}  {:>10}  {:>10}  {:>6}",
+            "Rule", "Total", "Max", "Files"
+        )?;
+        for timing in timings {
+            writeln!(
+                writer,
+                "{:<code_width$}  {:>10}  {:>10}  {:>6}",
+                timing.code.to_str(),
+                format!("{:.2?}", timing.total),
+                format!("{:.2?}", timing.max),
+                timing.files
+            )?;
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Write the time spent in each phase, followed by the slowest files. The JSON report has
+    /// every file, with the times in milliseconds.
+    pub(crate) fn write_phase_timings(
+        &self,
+        timings: &PhaseTimings,
+        format: TimingFormat,
+        writer: &mut dyn Write,
+    ) -> Result<()> {
+        if matches!(self.log_level, LogLevel::Silent) {
+            return Ok(());
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        fn millis(duration: Duration) -> f64 {
+            duration.as_nanos() as f64 / 1e6
+        }
+        fn phase_millis(phases: &BTreeMap<Phase, Duration>) -> BTreeMap<&'static str, f64> {
+            phases
+                .iter()
+                .map(|(phase, duration)| (phase.as_str(), millis(*duration)))
+                .collect()
+        }
+
+        #[derive(Serialize)]
+        struct ExpandedFileTiming<'a> {
+            path: &'a str,
+            total: f64,
+            phases: BTreeMap<&'static str, f64>,
+        }
+
+        #[derive(Serialize)]
+        struct ExpandedTimings<'a> {
+            total: BTreeMap<&'static str, f64>,
+            files: Vec<ExpandedFileTiming<'a>>,
+        }
+
+        match format {
+            TimingFormat::Json => {
+                let expanded = ExpandedTimings {
+                    total: phase_millis(&timings.total),
+                    files: timings
+                        .files
+                        .iter()
+                        .map(|(path, phases)| ExpandedFileTiming {
+                            path,
+                            total: millis(phases.values().sum()),
+                            phases: phase_millis(phases),
+                        })
+                        .collect(),
+                };
+                writeln!(writer, "{}", serde_json::to_string_pretty(&expanded)?)?;
+            }
+            TimingFormat::Text => {
+                if timings.total.is_empty() {
+                    return Ok(());
+                }
+                writeln!(writer, "{:<10}  {:>10}", "Phase", "Total")?;
+                for (phase, duration) in &timings.total {
+                    writeln!(
+                        writer,
+                        "{:<10}  {:>10}",
+                        phase.as_str(),
+                        format!("{duration:.2?}")
+                    )?;
+                }
+
+                let slowest = timings
+                    .files
+                    .iter()
+                    .map(|(path, phases)| (path, phases, phases.values().sum::<Duration>()))
+                    .sorted_by_key(|(path, _, total)| (Reverse(*total), *path))
+                    .take(10)
+                    .collect::<Vec<_>>();
+                if !slowest.is_empty() {
+                    let phases = slowest
+                        .iter()
+                        .flat_map(|(_, phases, _)| phases.keys().copied())
+                        .unique()
+                        .sorted()
+                        .collect::<Vec<_>>();
+                    let path_width = slowest
+                        .iter()
+                        .map(|(path, ..)| path.len())
+                        .chain(["File".len()])
+                        .max()
+                        .unwrap();
+                    writeln!(writer)?;
+                    write!(writer, "{:<path_width$}  {:>10}", "File", "Total")?;
+                    for phase in &phases {
+                        write!(writer, "  {:>10}", phase.as_str())?;
+                    }
+                    writeln!(writer)?;
+                    for (path, file_phases, total) in slowest {
+                        write!(
+                            writer,
+                            "{:<path_width$}  {:>10}",
+                            path,
+                            format!("{total:.2?}")
+                        )?;
+                        for phase in &phases {
+                            let duration = file_phases.get(phase).copied().unwrap_or_default();
+                            write!(writer, "  {:>10}", format!("{duration:.2?}"))?;
+                        }
+                        writeln!(writer)?;
+                    }
+                }
+            }
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+
     pub(crate) fn write_continuously(
         &self,
         writer: &mut dyn Write,
@@ -231,6 +594,7 @@ impl Printer {
 
             TextEmitter::default()
                 .with_show_source(self.flags.intersects(Flags::SHOW_SOURCE))
+                .with_column_encoding(self.column_encoding)
                 .emit(writer, &diagnostics.messages)?;
         }
         writer.flush()?;
@@ -245,9 +609,179 @@ impl Printer {
     }
 }
 
+/// The note for violations that aren't printed, like "… and 3 more violations".
+fn more_violations(count: usize) -> String {
+    let s = if count == 1 { "" } else { "s" };
+    format!("… and {count} more violation{s}")
+}
+
 fn num_digits(n: usize) -> usize {
     iterate(n, |&n| n / 10)
         .take_while(|&n| n > 0)
         .count()
         .max(1)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    use anyhow::Result;
+    use rustpython_parser::text_size::{TextRange, TextSize};
+
+    use pyrogen_checker::logging::LogLevel;
+    use pyrogen_checker::message::Message;
+    use pyrogen_checker::registry::{Diagnostic, DiagnosticKind, ErrorCode};
+    use pyrogen_checker::settings::code_table::MessageKind;
+    use pyrogen_checker::settings::flags;
+    use pyrogen_checker::settings::types::{SerializationFormat, SeverityMapping};
+    use pyrogen_python_ast::imports::ImportMap;
+    use pyrogen_source_file::{ColumnEncoding, SourceFile, SourceFileBuilder};
+
+    use crate::args::TimingFormat;
+    use crate::diagnostics::Messages;
+    use crate::profile::{Phase, PhaseTimings};
+
+    use super::{Flags, Printer};
+
+    #[test]
+    fn statistics() -> Result<()> {
+        let file = SourceFileBuilder::new("module.py", "").finish();
+        let message = |error_code| {
+            Message::from_diagnostic(
+                Diagnostic::new(
+                    DiagnosticKind::new(error_code, String::new()),
+                    TextRange::default(),
+                ),
+                file.clone(),
+                TextSize::default(),
+                MessageKind::Error,
+            )
+        };
+        // Messages are sorted by location, so codes are interleaved.
+        let diagnostics = Messages::new(
+            vec![
+                message(ErrorCode::DuplicateKey),
+                message(ErrorCode::ImportNotFound),
+                message(ErrorCode::DuplicateKey),
+            ],
+            ImportMap::default(),
+        );
+
+        let printer = Printer::new(
+            SerializationFormat::Json,
+            SeverityMapping::default(),
+            None,
+            ColumnEncoding::default(),
+            LogLevel::Default,
+            flags::FixMode::Generate,
+            Flags::SHOW_VIOLATIONS,
+        );
+        let mut output = Vec::new();
+        printer.write_statistics(&diagnostics, &mut output)?;
+        let statistics: serde_json::Value = serde_json::from_slice(&output)?;
+        assert_eq!(statistics[0]["code"], "duplicate-key");
+        assert_eq!(statistics[0]["count"], 2);
+        assert_eq!(statistics[1]["code"], "import-not-found");
+        assert_eq!(statistics[1]["count"], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn max_violations() -> Result<()> {
+        let message = |file: &SourceFile, offset: u32| {
+            Message::from_diagnostic(
+                Diagnostic::new(
+                    DiagnosticKind::new(ErrorCode::DuplicateKey, "Repeated"),
+                    TextRange::empty(TextSize::new(offset)),
+                ),
+                file.clone(),
+                TextSize::default(),
+                MessageKind::Error,
+            )
+        };
+        let a = SourceFileBuilder::new("a.py", "x\ny\nz\n").finish();
+        let b = SourceFileBuilder::new("b.py", "x\n").finish();
+        let c = SourceFileBuilder::new("c.py", "x\n").finish();
+        let diagnostics = Messages::new(
+            vec![
+                message(&a, 0),
+                message(&a, 2),
+                message(&a, 4),
+                message(&b, 0),
+                message(&c, 0),
+            ],
+            ImportMap::default(),
+        );
+        let output = |format, total, per_file| -> Result<String> {
+            let printer = Printer::new(
+                format,
+                SeverityMapping::default(),
+                None,
+                ColumnEncoding::default(),
+                LogLevel::Default,
+                flags::FixMode::Generate,
+                Flags::SHOW_VIOLATIONS,
+            )
+            .with_max_violations(total, per_file);
+            let mut output = Vec::new();
+            printer.write_messages(&diagnostics, &mut output)?;
+            Ok(String::from_utf8(output)?)
+        };
+
+        assert_eq!(
+            output(SerializationFormat::Pylint, Some(3), Some(2))?,
+            "\
+a.py:1: [duplicate-key] Repeated
+a.py:2: [duplicate-key] Repeated
+… and 1 more violation in a.py
+b.py:1: [duplicate-key] Repeated
+… and 1 more violation in 1 more file
+"
+        );
+
+        // The JSON format has every violation.
+        let json: serde_json::Value =
+            serde_json::from_str(&output(SerializationFormat::Json, Some(1), Some(1))?)?;
+        assert_eq!(json.as_array().map(Vec::len), Some(5));
+        Ok(())
+    }
+
+    #[test]
+    fn phase_timings() -> Result<()> {
+        let mut timings = PhaseTimings::default();
+        timings
+            .total
+            .insert(Phase::Discovery, Duration::from_millis(2));
+        timings.total.insert(Phase::Parse, Duration::from_millis(3));
+        timings.files.insert(
+            "module.py".to_string(),
+            BTreeMap::from([(Phase::Parse, Duration::from_millis(3))]),
+        );
+
+        let printer = Printer::new(
+            SerializationFormat::Text,
+            SeverityMapping::default(),
+            None,
+            ColumnEncoding::default(),
+            LogLevel::Default,
+            flags::FixMode::Generate,
+            Flags::SHOW_VIOLATIONS,
+        );
+        let mut output = Vec::new();
+        printer.write_phase_timings(&timings, TimingFormat::Json, &mut output)?;
+        let report: serde_json::Value = serde_json::from_slice(&output)?;
+        assert_eq!(report["total"]["discovery"], 2.0);
+        assert_eq!(report["files"][0]["path"], "module.py");
+        assert_eq!(report["files"][0]["total"], 3.0);
+        assert_eq!(report["files"][0]["phases"]["parse"], 3.0);
+
+        let mut output = Vec::new();
+        printer.write_phase_timings(&timings, TimingFormat::Text, &mut output)?;
+        let report = String::from_utf8(output)?;
+        assert!(report.starts_with("Phase"));
+        assert!(report.contains("module.py"));
+        Ok(())
+    }
+}
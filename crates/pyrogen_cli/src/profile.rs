@@ -0,0 +1,336 @@
+//! Folded-stack profiles of a run, as written by `--profile`.
+//!
+//! Every `tracing` span becomes a stack frame labelled with its name and field values (e.g.,
+//! `check_file(src/foo.py)` or `rule(unused-import)`). The time spent in a span itself, excluding
+//! its children, is attributed to the span's stack, in microseconds. The output has one
+//! `frame;frame;frame <microseconds>` line per stack, the format read by `inferno-flamegraph` and
+//! `flamegraph.pl`.
+//!
+//! The same stacks are summarized per phase of the check (and per file) for `--phase-timings`.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::io::Write;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use itertools::Itertools;
+use rustc_hash::FxHashMap;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{Layer, Registry};
+
+/// The self time recorded for each stack of spans, from the root.
+type Stacks = Arc<Mutex<FxHashMap<Vec<Frame>, Duration>>>;
+
+/// A span on a stack: its name and the values of its fields.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Frame {
+    name: &'static str,
+    values: Vec<String>,
+}
+
+impl Frame {
+    /// The frame as it's written in folded stacks, e.g. `rule(unused-import)`.
+    fn label(&self) -> String {
+        let label = if self.values.is_empty() {
+            self.name.to_string()
+        } else {
+            format!("{}({})", self.name, self.values.join(", "))
+        };
+        // `;` separates frames and the last space separates the stack from its count.
+        label.replace(';', ":").replace(' ', "_")
+    }
+}
+
+/// Records the time spent in every span and writes it out as folded stacks.
+#[derive(Clone, Default)]
+pub(crate) struct Profiler {
+    stacks: Stacks,
+}
+
+impl Profiler {
+    /// Install a profiler as the global `tracing` subscriber.
+    pub(crate) fn install() -> anyhow::Result<Self> {
+        let profiler = Self::default();
+        tracing::subscriber::set_global_default(profiler.subscriber())?;
+        Ok(profiler)
+    }
+
+    fn subscriber(&self) -> impl Subscriber {
+        Registry::default().with(ProfileLayer {
+            stacks: self.stacks.clone(),
+        })
+    }
+
+    /// Write the folded stacks recorded so far, sorted by stack. Stacks that took less than a
+    /// microsecond are omitted.
+    pub(crate) fn write(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        let stacks = self.stacks.lock().unwrap_or_else(PoisonError::into_inner);
+        let folded = stacks
+            .iter()
+            .map(|(stack, duration)| (stack.iter().map(Frame::label).join(";"), duration))
+            .sorted_unstable();
+        for (stack, duration) in folded {
+            let micros = duration.as_micros();
+            if micros > 0 {
+                writeln!(writer, "{stack} {micros}")?;
+            }
+        }
+        writer.flush()
+    }
+
+    /// The time recorded so far, summed per phase and per checked file.
+    pub(crate) fn phase_timings(&self) -> PhaseTimings {
+        let stacks = self.stacks.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut timings = PhaseTimings::default();
+        for (stack, duration) in stacks.iter() {
+            // The innermost span that belongs to a phase, e.g. the `check_ast` of a `rule`.
+            let Some(phase) = stack
+                .iter()
+                .rev()
+                .find_map(|frame| Phase::of_span(frame.name))
+            else {
+                continue;
+            };
+            *timings.total.entry(phase).or_default() += *duration;
+            let file = stack
+                .iter()
+                .find(|frame| frame.name == "check_file")
+                .and_then(|frame| frame.values.first());
+            if let Some(file) = file {
+                *timings
+                    .files
+                    .entry(file.clone())
+                    .or_default()
+                    .entry(phase)
+                    .or_default() += *duration;
+            }
+        }
+        timings
+    }
+}
+
+/// A phase of a check, made up of the spans that are recorded for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) enum Phase {
+    Discovery,
+    Tokenize,
+    Parse,
+    /// Building the interfaces of the project's modules, which binds their names.
+    Bind,
+    Check,
+    CacheIo,
+}
+
+impl Phase {
+    fn of_span(name: &str) -> Option<Self> {
+        match name {
+            "discover_files" => Some(Self::Discovery),
+            "tokenize" | "index" => Some(Self::Tokenize),
+            "parse" => Some(Self::Parse),
+            "module_interfaces" => Some(Self::Bind),
+            "check_ast" | "filesystem_rules" | "type_ignore" | "unused_dependencies" => {
+                Some(Self::Check)
+            }
+            "load_caches" | "read_cache" | "store_caches" => Some(Self::CacheIo),
+            _ => None,
+        }
+    }
+
+    pub(crate) const fn as_str(self) -> &'static str {
+        match self {
+            Self::Discovery => "discovery",
+            Self::Tokenize => "tokenize",
+            Self::Parse => "parse",
+            Self::Bind => "bind",
+            Self::Check => "check",
+            Self::CacheIo => "cache-io",
+        }
+    }
+}
+
+/// The time spent in each phase, in total and per checked file (by relative path). Phases that
+/// don't belong to a single file, like discovery, only count towards the total.
+#[derive(Debug, Default)]
+pub(crate) struct PhaseTimings {
+    pub(crate) total: BTreeMap<Phase, Duration>,
+    pub(crate) files: BTreeMap<String, BTreeMap<Phase, Duration>>,
+}
+
+/// The timing information stored with every span.
+struct Timing {
+    frame: Frame,
+    /// When the span was last entered, if it's currently entered.
+    entered: Option<Instant>,
+    /// The total time the span was entered.
+    busy: Duration,
+    /// The total time its children were entered.
+    children: Duration,
+}
+
+struct ProfileLayer {
+    stacks: Stacks,
+}
+
+impl<S> Layer<S> for ProfileLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut values = Values::default();
+        attrs.record(&mut values);
+        span.extensions_mut().insert(Timing {
+            frame: Frame {
+                name: attrs.metadata().name(),
+                values: values.0,
+            },
+            entered: None,
+            busy: Duration::ZERO,
+            children: Duration::ZERO,
+        });
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(timing) = span.extensions_mut().get_mut::<Timing>() {
+                timing.entered = Some(Instant::now());
+            }
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(timing) = span.extensions_mut().get_mut::<Timing>() {
+                if let Some(entered) = timing.entered.take() {
+                    timing.busy += entered.elapsed();
+                }
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some((busy, self_time)) = span
+            .extensions()
+            .get::<Timing>()
+            .map(|timing| (timing.busy, timing.busy.saturating_sub(timing.children)))
+        else {
+            return;
+        };
+
+        let stack = span
+            .scope()
+            .from_root()
+            .filter_map(|frame| {
+                frame
+                    .extensions()
+                    .get::<Timing>()
+                    .map(|timing| timing.frame.clone())
+            })
+            .collect();
+        *self
+            .stacks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(stack)
+            .or_default() += self_time;
+
+        if let Some(parent) = span.parent() {
+            if let Some(timing) = parent.extensions_mut().get_mut::<Timing>() {
+                timing.children += busy;
+            }
+        }
+    }
+}
+
+/// Collects the formatted field values of a span.
+#[derive(Default)]
+struct Values(Vec<String>);
+
+impl Visit for Values {
+    fn record_str(&mut self, _field: &Field, value: &str) {
+        self.0.push(value.to_string());
+    }
+
+    fn record_debug(&mut self, _field: &Field, value: &dyn fmt::Debug) {
+        let mut formatted = String::new();
+        let _ = write!(formatted, "{value:?}");
+        self.0.push(formatted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use super::{Phase, Profiler};
+
+    #[test]
+    fn folded_stacks() {
+        let profiler = Profiler::default();
+        tracing::subscriber::with_default(profiler.subscriber(), || {
+            let _check = tracing::debug_span!("check").entered();
+            for path in ["a.py", "b;c.py"] {
+                let _file = tracing::debug_span!("check_file", path).entered();
+                let _rule = tracing::trace_span!("rule", code = %"unused-import").entered();
+                sleep(Duration::from_millis(1));
+            }
+        });
+
+        let mut output = Vec::new();
+        profiler.write(&mut output).unwrap();
+        let stacks = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| line.rsplit_once(' ').unwrap().0.to_string())
+            .filter(|stack| stack.contains("rule"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            stacks,
+            [
+                "check;check_file(a.py);rule(unused-import)",
+                "check;check_file(b:c.py);rule(unused-import)",
+            ]
+        );
+    }
+
+    #[test]
+    fn phase_timings() {
+        let profiler = Profiler::default();
+        tracing::subscriber::with_default(profiler.subscriber(), || {
+            let _check = tracing::debug_span!("check").entered();
+            tracing::debug_span!("discover_files").in_scope(|| sleep(Duration::from_millis(1)));
+            for path in ["a.py", "b c.py"] {
+                let _file = tracing::debug_span!("check_file", path).entered();
+                tracing::debug_span!("parse").in_scope(|| sleep(Duration::from_millis(1)));
+                let _ast = tracing::debug_span!("check_ast").entered();
+                let _rule = tracing::trace_span!("rule", code = %"unused-import").entered();
+                sleep(Duration::from_millis(1));
+            }
+        });
+
+        let timings = profiler.phase_timings();
+        assert_eq!(
+            timings.total.keys().copied().collect::<Vec<_>>(),
+            [Phase::Discovery, Phase::Parse, Phase::Check]
+        );
+        assert_eq!(
+            timings.files.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["a.py", "b c.py"]
+        );
+        let file = &timings.files["a.py"];
+        assert!(file[&Phase::Parse] >= Duration::from_millis(1));
+        assert!(file[&Phase::Check] >= Duration::from_millis(1));
+        assert!(!file.contains_key(&Phase::Discovery));
+    }
+}
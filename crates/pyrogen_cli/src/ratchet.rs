@@ -0,0 +1,166 @@
+//! `--ratchet`, which fails the check when the number of violations of a code grows beyond the
+//! number recorded in a file, so that a codebase can only get better over time.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use pyrogen_checker::message::Message;
+
+use crate::write_output_file;
+
+/// The number of violations of each code, as recorded in the ratchet file.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct Ratchet {
+    counts: BTreeMap<String, usize>,
+}
+
+impl Ratchet {
+    pub(crate) fn from_messages(messages: &[Message]) -> Self {
+        let mut counts = BTreeMap::new();
+        for message in messages {
+            *counts
+                .entry(message.diagnostic.error_code.to_str().to_string())
+                .or_default() += 1;
+        }
+        Self { counts }
+    }
+
+    /// Read the ratchet file at `path`, or `None` if there's none yet.
+    pub(crate) fn read(path: &Path) -> Result<Option<Self>> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("Failed to read ratchet file `{}`", path.display()))
+            }
+        };
+        serde_json::from_str(&contents)
+            .map(Some)
+            .with_context(|| format!("Failed to parse ratchet file `{}`", path.display()))
+    }
+
+    pub(crate) fn write(&self, path: &Path) -> Result<()> {
+        let mut contents = serde_json::to_string_pretty(self)?;
+        contents.push('\n');
+        write_output_file(path, contents.as_bytes())
+    }
+
+    fn count(&self, code: &str) -> usize {
+        self.counts.get(code).copied().unwrap_or_default()
+    }
+
+    /// The codes with more violations in `current` than recorded, with both counts.
+    pub(crate) fn increases<'a>(&self, current: &'a Ratchet) -> Vec<(&'a str, usize, usize)> {
+        current
+            .counts
+            .iter()
+            .filter(|(code, count)| **count > self.count(code))
+            .map(|(code, count)| (code.as_str(), self.count(code), *count))
+            .collect()
+    }
+
+    /// The recorded counts, lowered to the counts of `current` where it has fewer violations.
+    /// Counts never go up, and codes without violations are dropped.
+    #[must_use]
+    pub(crate) fn lowered(&self, current: &Ratchet) -> Ratchet {
+        let counts = self
+            .counts
+            .iter()
+            .map(|(code, count)| (code.clone(), (*count).min(current.count(code))))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        Self { counts }
+    }
+}
+
+/// Compare the violations in `messages` to the ratchet file at `path`, which is created if it
+/// doesn't exist yet, and lowered with `update`. Returns whether the count of any code went up.
+pub(crate) fn check_ratchet(
+    path: &Path,
+    messages: &[Message],
+    update: bool,
+    writer: &mut dyn Write,
+) -> Result<bool> {
+    let current = Ratchet::from_messages(messages);
+    let Some(recorded) = Ratchet::read(path)? else {
+        current.write(path)?;
+        writeln!(
+            writer,
+            "Recorded the violations of {} codes in `{}`.",
+            current.counts.len(),
+            path.display()
+        )?;
+        return Ok(false);
+    };
+
+    let increases = recorded.increases(&current);
+    for (code, recorded, count) in &increases {
+        writeln!(
+            writer,
+            "{} {code}: {count} violations, up from {recorded}",
+            "Ratchet:".red().bold()
+        )?;
+    }
+
+    let lowered = recorded.lowered(&current);
+    if lowered != recorded {
+        if update {
+            lowered.write(path)?;
+            writeln!(writer, "Lowered the counts in `{}`.", path.display())?;
+        } else {
+            writeln!(
+                writer,
+                "Some codes have fewer violations than recorded, run with `--update-ratchet` to lower their counts in `{}`.",
+                path.display()
+            )?;
+        }
+    }
+    Ok(!increases.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::Ratchet;
+
+    fn ratchet(counts: &[(&str, usize)]) -> Ratchet {
+        Ratchet {
+            counts: counts
+                .iter()
+                .map(|(code, count)| ((*code).to_string(), *count))
+                .collect::<BTreeMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn increases() {
+        let recorded = ratchet(&[("duplicate-key", 3), ("dead-code", 2)]);
+        let current = ratchet(&[("duplicate-key", 4), ("dead-code", 1), ("final-class", 1)]);
+        assert_eq!(
+            recorded.increases(&current),
+            vec![("duplicate-key", 3, 4), ("final-class", 0, 1)]
+        );
+    }
+
+    #[test]
+    fn lowered() {
+        let recorded = ratchet(&[("duplicate-key", 3), ("dead-code", 2), ("final-class", 1)]);
+        let current = ratchet(&[("duplicate-key", 4), ("dead-code", 1)]);
+        assert_eq!(
+            recorded.lowered(&current),
+            ratchet(&[("duplicate-key", 3), ("dead-code", 1)])
+        );
+
+        let json = serde_json::to_string(&recorded).unwrap();
+        assert_eq!(json, r#"{"dead-code":2,"duplicate-key":3,"final-class":1}"#);
+        assert_eq!(serde_json::from_str::<Ratchet>(&json).unwrap(), recorded);
+    }
+}
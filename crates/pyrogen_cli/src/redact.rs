@@ -0,0 +1,89 @@
+//! The placeholders that `--redact-paths` puts in place of the workspace and home directories, so
+//! that reports can be shared without revealing the layout of the machine or the name of the user.
+
+use std::path::{Path, PathBuf};
+
+use pyrogen_source_file::{SourceFile, SourceFileBuilder};
+use rustc_hash::FxHashMap;
+
+use crate::diagnostics::Messages;
+
+const WORKSPACE: &str = "<workspace>";
+const HOME: &str = "~";
+
+/// Replaces the directories of paths with placeholders.
+#[derive(Debug)]
+pub(crate) struct PathRedactor {
+    /// The directories and their placeholders, the most specific first, so that a workspace in the
+    /// home directory is shown as the workspace.
+    prefixes: Vec<(PathBuf, &'static str)>,
+}
+
+impl PathRedactor {
+    pub(crate) fn new(workspace: &Path, home: Option<PathBuf>) -> Self {
+        let mut prefixes = vec![(workspace.to_path_buf(), WORKSPACE)];
+        prefixes.extend(home.map(|home| (home, HOME)));
+        prefixes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.components().count()));
+        Self { prefixes }
+    }
+
+    /// The redactor for the workspace at `project_root` and the home directory of the user.
+    pub(crate) fn for_project(project_root: &Path) -> Self {
+        Self::new(project_root, dirs::home_dir())
+    }
+
+    /// The `path` with its directory replaced by a placeholder, or `None` if it isn't in one of
+    /// the redacted directories.
+    pub(crate) fn redact(&self, path: &str) -> Option<String> {
+        self.prefixes.iter().find_map(|(prefix, placeholder)| {
+            let rest = Path::new(path).strip_prefix(prefix).ok()?;
+            Some(Path::new(placeholder).join(rest).display().to_string())
+        })
+    }
+
+    /// Redact the paths of the files that `messages` refer to.
+    pub(crate) fn redact_messages(&self, messages: &mut Messages) {
+        // Messages of the same file share its source, so each file is only renamed once.
+        let mut renamed: FxHashMap<String, SourceFile> = FxHashMap::default();
+        for message in &mut messages.messages {
+            if let Some(file) = renamed.get(message.filename()) {
+                message.file = file.clone();
+            } else if let Some(name) = self.redact(message.filename()) {
+                let file = SourceFileBuilder::new(name, message.file.source_text()).finish();
+                renamed.insert(message.filename().to_string(), file.clone());
+                message.file = file;
+            }
+        }
+        messages.fixed = std::mem::take(&mut messages.fixed)
+            .into_iter()
+            .map(|(path, table)| (self.redact(&path).unwrap_or(path), table))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::PathRedactor;
+
+    #[cfg(unix)]
+    #[test]
+    fn redacted_paths() {
+        let redactor = PathRedactor::new(
+            Path::new("/home/alice/work/project"),
+            Some(PathBuf::from("/home/alice")),
+        );
+        assert_eq!(
+            redactor.redact("/home/alice/work/project/pkg/module.py"),
+            Some("<workspace>/pkg/module.py".to_string())
+        );
+        assert_eq!(
+            redactor.redact("/home/alice/.venv/lib/site.py"),
+            Some("~/.venv/lib/site.py".to_string())
+        );
+        // Only whole directories are replaced.
+        assert_eq!(redactor.redact("/home/alice2/module.py"), None);
+        assert_eq!(redactor.redact("pkg/module.py"), None);
+    }
+}
@@ -91,7 +91,15 @@ pub fn resolve(
     // "closest" `pyproject.toml` file for every Python file later on, so these act
     // as the "default" settings.)
     debug!("Using Ruff default settings");
-    let config = overrides.transform(Configuration::default());
+    let mut config = overrides.transform(Configuration::default());
+    // A `pyproject.toml` without a `[tool.pyrogen]` section still declares the entry points.
+    let pyproject = path_dedot::CWD.join("pyproject.toml");
+    if pyproject.is_file() {
+        match pyproject::load_entry_points(&pyproject) {
+            Ok(entry_points) => config.entry_points = entry_points,
+            Err(err) => debug!("Failed to read the entry points from pyproject.toml: {err}"),
+        }
+    }
     let settings = config.into_settings(&path_dedot::CWD)?;
     Ok(PyprojectConfig::new(
         PyprojectDiscoveryStrategy::Hierarchical,
@@ -0,0 +1,185 @@
+//! `--sample`, which reports only a subset of the warnings, so that large codebases can adopt
+//! pyrogen by fixing a manageable number of them at a time.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use itertools::Itertools;
+use pyrogen_checker::message::{fingerprints, Message};
+use pyrogen_checker::settings::code_table::MessageKind;
+
+/// How many warnings to report. Errors are always reported.
+///
+/// The warnings are chosen by their fingerprints, so every run reports the same ones until they're
+/// fixed, and lines added or removed elsewhere don't change the selection.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Sample {
+    /// A percentage of the warnings, like `10%`.
+    Percent(u8),
+    /// At most this many warnings of each file, like `5-per-file`.
+    PerFile(usize),
+}
+
+impl FromStr for Sample {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if let Some(percent) = value.strip_suffix('%') {
+            match percent.parse::<u8>() {
+                Ok(percent) if percent <= 100 => Ok(Self::Percent(percent)),
+                _ => Err(format!("`{percent}` isn't a percentage from 0 to 100")),
+            }
+        } else if let Some(count) = value.strip_suffix("-per-file") {
+            count
+                .parse()
+                .map(Self::PerFile)
+                .map_err(|_| format!("`{count}` isn't a number of warnings"))
+        } else {
+            Err(format!(
+                "`{value}` isn't a percentage like `10%` or a number per file like `5-per-file`"
+            ))
+        }
+    }
+}
+
+impl fmt::Display for Sample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Percent(percent) => write!(f, "{percent}%"),
+            Self::PerFile(count) => write!(f, "{count}-per-file"),
+        }
+    }
+}
+
+impl Sample {
+    /// Remove the warnings (and infos and hints) of `messages` that aren't in the sample,
+    /// returning how many were removed. The messages must be sorted by file.
+    pub(crate) fn apply(self, messages: &mut Vec<Message>, project_root: &Path) -> usize {
+        let before = messages.len();
+        let mut keep = self.selection(messages, project_root).into_iter();
+        messages.retain(|_| keep.next().unwrap_or(true));
+        before - messages.len()
+    }
+
+    /// Whether each of the `messages` is in the sample.
+    fn selection(self, messages: &[Message], project_root: &Path) -> Vec<bool> {
+        let hashes = fingerprints(messages, Some(project_root))
+            .iter()
+            .map(|fingerprint| u64::from_str_radix(fingerprint, 16).unwrap_or_default())
+            .collect::<Vec<_>>();
+        match self {
+            Self::Percent(percent) => messages
+                .iter()
+                .zip(&hashes)
+                .map(|(message, hash)| {
+                    message.kind == MessageKind::Error || hash % 100 < u64::from(percent)
+                })
+                .collect(),
+            Self::PerFile(count) => {
+                let mut keep = vec![false; messages.len()];
+                let by_file = (0..messages.len()).group_by(|&index| messages[index].filename());
+                for (_, indices) in &by_file {
+                    let (errors, others): (Vec<usize>, Vec<usize>) =
+                        indices.partition(|&index| messages[index].kind == MessageKind::Error);
+                    let sampled = others
+                        .into_iter()
+                        .sorted_by_key(|&index| hashes[index])
+                        .take(count);
+                    for index in errors.into_iter().chain(sampled) {
+                        keep[index] = true;
+                    }
+                }
+                keep
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use rustpython_parser::text_size::{TextRange, TextSize};
+
+    use pyrogen_checker::message::Message;
+    use pyrogen_checker::registry::{Diagnostic, DiagnosticKind, ErrorCode};
+    use pyrogen_checker::settings::code_table::MessageKind;
+    use pyrogen_source_file::SourceFileBuilder;
+
+    use super::Sample;
+
+    #[test]
+    fn parse() {
+        assert_eq!("10%".parse(), Ok(Sample::Percent(10)));
+        assert_eq!("3-per-file".parse(), Ok(Sample::PerFile(3)));
+        assert!("101%".parse::<Sample>().is_err());
+        assert!("3".parse::<Sample>().is_err());
+        assert_eq!(Sample::PerFile(3).to_string(), "3-per-file");
+    }
+
+    fn messages() -> Vec<Message> {
+        let source = "x = 1\n".repeat(20);
+        let mut messages = vec![];
+        for name in ["a.py", "b.py"] {
+            let file = SourceFileBuilder::new(name, source.as_str()).finish();
+            for line in 0..20u32 {
+                let start = TextSize::new(line * 6);
+                let diagnostic = Diagnostic::new(
+                    DiagnosticKind::new(ErrorCode::DuplicateKey, format!("Problem {line}")),
+                    TextRange::new(start, start + TextSize::new(1)),
+                );
+                let kind = if line == 0 {
+                    MessageKind::Error
+                } else {
+                    MessageKind::Warning
+                };
+                messages.push(Message::from_diagnostic(
+                    diagnostic,
+                    file.clone(),
+                    TextSize::default(),
+                    kind,
+                ));
+            }
+        }
+        messages
+    }
+
+    #[test]
+    fn sample_per_file() {
+        let mut sampled = messages();
+        assert_eq!(Sample::PerFile(2).apply(&mut sampled, Path::new("")), 34);
+        for name in ["a.py", "b.py"] {
+            let of_file = sampled
+                .iter()
+                .filter(|message| message.filename() == name)
+                .collect::<Vec<_>>();
+            assert_eq!(of_file.len(), 3);
+            assert_eq!(
+                of_file
+                    .iter()
+                    .filter(|message| message.kind == MessageKind::Error)
+                    .count(),
+                1
+            );
+        }
+
+        // The sample is the same on every run.
+        let mut again = messages();
+        Sample::PerFile(2).apply(&mut again, Path::new(""));
+        assert_eq!(sampled, again);
+    }
+
+    #[test]
+    fn sample_percent() {
+        let mut sampled = messages();
+        Sample::Percent(0).apply(&mut sampled, Path::new(""));
+        assert!(sampled
+            .iter()
+            .all(|message| message.kind == MessageKind::Error));
+        assert_eq!(sampled.len(), 2);
+
+        let mut all = messages();
+        assert_eq!(Sample::Percent(100).apply(&mut all, Path::new("")), 0);
+    }
+}
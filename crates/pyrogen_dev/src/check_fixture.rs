@@ -0,0 +1,76 @@
+//! Check the Python files of a fixture with a single error code, and print the messages with their
+//! source, like the snapshots of the checker tests.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use anyhow::Result;
+
+use pyrogen_checker::cancellation::CancellationToken;
+use pyrogen_checker::checker::lint_only;
+use pyrogen_checker::message::{Emitter, Message, TextEmitter};
+use pyrogen_checker::registry::ErrorCode;
+use pyrogen_checker::settings::{flags, CheckerSettings};
+use pyrogen_checker::source_kind::SourceKind;
+use pyrogen_python_ast::PySourceType;
+
+#[derive(clap::Args)]
+pub(crate) struct Args {
+    /// The error code to check, like `unused-import`.
+    #[arg(long)]
+    code: ErrorCode,
+    /// The fixture file, or a directory whose Python files are checked.
+    path: PathBuf,
+}
+
+pub(crate) fn main(args: &Args) -> Result<ExitCode> {
+    let settings = CheckerSettings::for_rule(args.code);
+    let mut messages: Vec<Message> = vec![];
+    for path in python_files(&args.path)? {
+        let contents = fs::read_to_string(&path)?;
+        let result = lint_only(
+            &path,
+            None,
+            &settings,
+            flags::TypeIgnore::Enabled,
+            &SourceKind::new(contents),
+            PySourceType::from(path.as_path()),
+            None,
+            &CancellationToken::default(),
+        )?;
+        messages.extend(result.data.0);
+    }
+
+    TextEmitter::default()
+        .with_show_source(true)
+        .emit(&mut io::stdout().lock(), &messages)?;
+
+    Ok(if messages.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// The Python files at `path`, sorted, or `path` itself if it's a file.
+fn python_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = vec![];
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(python_files(&path)?);
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "py" || extension == "pyi")
+        {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
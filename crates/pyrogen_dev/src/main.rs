@@ -0,0 +1,41 @@
+//! Tools for developing pyrogen.
+
+use std::process::ExitCode;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+mod check_fixture;
+mod print_ast;
+mod print_tokens;
+mod update_snapshots;
+
+#[derive(Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the AST of a Python file.
+    PrintAst(print_ast::Args),
+    /// Print the tokens of a Python file.
+    PrintTokens(print_tokens::Args),
+    /// Check the Python files of a fixture file or directory with a single error code.
+    CheckFixture(check_fixture::Args),
+    /// Run the tests and accept all changed snapshots.
+    UpdateSnapshots(update_snapshots::Args),
+}
+
+fn main() -> Result<ExitCode> {
+    let args = Args::parse();
+    match &args.command {
+        Command::PrintAst(args) => print_ast::main(args)?,
+        Command::PrintTokens(args) => print_tokens::main(args)?,
+        Command::CheckFixture(args) => return check_fixture::main(args),
+        Command::UpdateSnapshots(args) => return update_snapshots::main(args),
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,27 @@
+//! Print the AST of a Python file.
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use rustpython_parser::parse;
+
+use pyrogen_python_ast::{AsMode, PySourceType};
+
+#[derive(clap::Args)]
+pub(crate) struct Args {
+    /// The Python file to parse.
+    file: PathBuf,
+}
+
+pub(crate) fn main(args: &Args) -> Result<()> {
+    let contents = fs::read_to_string(&args.file)?;
+    let source_type = PySourceType::from(args.file.as_path());
+    let module = parse(
+        &contents,
+        source_type.as_mode(),
+        &args.file.to_string_lossy(),
+    )?;
+    println!("{module:#?}");
+    Ok(())
+}
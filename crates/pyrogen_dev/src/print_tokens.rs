@@ -0,0 +1,36 @@
+//! Print the tokens of a Python file, with their byte ranges.
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use rustpython_parser::lexer::lex;
+
+use pyrogen_python_ast::{AsMode, PySourceType};
+
+#[derive(clap::Args)]
+pub(crate) struct Args {
+    /// The Python file to lex.
+    file: PathBuf,
+}
+
+pub(crate) fn main(args: &Args) -> Result<()> {
+    let contents = fs::read_to_string(&args.file)?;
+    let source_type = PySourceType::from(args.file.as_path());
+    for result in lex(&contents, source_type.as_mode()) {
+        let (token, range) = match result {
+            Ok(token) => token,
+            Err(error) => bail!(
+                "{} at byte offset {}",
+                error.error,
+                u32::from(error.location)
+            ),
+        };
+        println!(
+            "{start}..{end} {token:?}",
+            start = u32::from(range.start()),
+            end = u32::from(range.end())
+        );
+    }
+    Ok(())
+}
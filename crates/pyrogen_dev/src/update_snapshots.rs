@@ -0,0 +1,38 @@
+//! Run the tests with insta told to write every changed snapshot, instead of failing on it and
+//! leaving a `.snap.new` file to review with `cargo insta review`.
+
+use std::process::{Command, ExitCode};
+
+use anyhow::{Context, Result};
+
+#[derive(clap::Args)]
+pub(crate) struct Args {
+    /// The package whose tests are run, like `pyrogen_checker`. All packages by default.
+    #[arg(long, short)]
+    package: Option<String>,
+    /// Only run the tests whose names contain this filter.
+    filter: Option<String>,
+}
+
+pub(crate) fn main(args: &Args) -> Result<ExitCode> {
+    let mut command = Command::new("cargo");
+    command.arg("test");
+    match &args.package {
+        Some(package) => command.args(["--package", package]),
+        None => command.arg("--workspace"),
+    };
+    if let Some(filter) = &args.filter {
+        command.args(["--", filter]);
+    }
+    let status = command
+        .env("INSTA_UPDATE", "always")
+        .env("INSTA_FORCE_PASS", "1")
+        .status()
+        .context("Failed to run `cargo test`")?;
+
+    Ok(if status.success() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
@@ -4,6 +4,7 @@ use syn::{parse_macro_input, DeriveInput};
 
 mod cache_key;
 mod config;
+mod violation;
 
 #[proc_macro_derive(OptionsMetadata, attributes(option, doc, option_group))]
 pub fn derive_options_metadata(input: TokenStream) -> TokenStream {
@@ -27,3 +28,19 @@ pub fn cache_key(input: TokenStream) -> TokenStream {
 
     TokenStream::from(stream)
 }
+
+/// Generates a `Violation` implementation, and the conversion into a `DiagnosticKind`, for a
+/// struct whose named fields are the arguments of its message.
+///
+/// The struct is attributed with the `violation` attribute that supports:
+/// * `code = Name`: The `ErrorCode` variant of the diagnostics (required)
+/// * `message = "id"`: The message of the catalog, which defaults to the name of the code
+/// * `fix_title = "..."`: The title of the fix, a format string that can refer to the fields
+#[proc_macro_derive(Violation, attributes(violation))]
+pub fn derive_violation(input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as DeriveInput);
+
+    violation::derive_violation(&item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
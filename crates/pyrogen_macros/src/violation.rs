@@ -0,0 +1,159 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Error, Fields, Ident, LitStr};
+
+pub(crate) fn derive_violation(item: &DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Struct(item_struct) = &item.data else {
+        return Err(Error::new(
+            item.span(),
+            "Violation only supports structs with named fields",
+        ));
+    };
+    let field_names: Vec<&Ident> = match &item_struct.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|field| field.ident.as_ref().unwrap())
+            .collect(),
+        Fields::Unit => vec![],
+        Fields::Unnamed(fields) => {
+            return Err(Error::new(
+                fields.span(),
+                "Violation only supports structs with named fields",
+            ))
+        }
+    };
+
+    let attributes = violation_attributes(item)?;
+    let code = &attributes.code;
+
+    let message = match &attributes.message {
+        Some(message) => quote!(#message),
+        None => quote!(Self::CODE.to_str()),
+    };
+    let args = field_names.iter().map(|field| {
+        let name = field.unraw().to_string();
+        quote!(.arg(#name, &self.#field))
+    });
+
+    let fix_title = match &attributes.fix_title {
+        Some(fix_title) => {
+            let placeholders = placeholders(fix_title)?;
+            for placeholder in &placeholders {
+                if !field_names.iter().any(|field| *field == placeholder) {
+                    return Err(Error::new(
+                        fix_title.span(),
+                        format!("The fix title refers to the unknown field `{placeholder}`"),
+                    ));
+                }
+            }
+            quote! {
+                fn fix_title(&self) -> Option<String> {
+                    Some(format!(#fix_title, #(#placeholders = self.#placeholders),*))
+                }
+            }
+        }
+        None => quote!(),
+    };
+
+    let name = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = &item.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics pyrogen_checker::violation::Violation for #name #ty_generics #where_clause {
+            const CODE: pyrogen_checker::registry::ErrorCode =
+                pyrogen_checker::registry::ErrorCode::#code;
+
+            fn message(&self) -> pyrogen_checker::locale::MessageTemplate {
+                pyrogen_checker::locale::MessageTemplate::new(#message)#(#args)*
+            }
+
+            #fix_title
+        }
+
+        impl #impl_generics From<#name #ty_generics> for pyrogen_checker::registry::DiagnosticKind #where_clause {
+            fn from(violation: #name #ty_generics) -> Self {
+                use pyrogen_checker::violation::Violation;
+                Self::from_template(
+                    <#name #ty_generics as Violation>::CODE,
+                    violation.message(),
+                )
+            }
+        }
+    })
+}
+
+#[derive(Debug)]
+struct ViolationAttributes {
+    code: Ident,
+    message: Option<LitStr>,
+    fix_title: Option<LitStr>,
+}
+
+fn violation_attributes(item: &DeriveInput) -> syn::Result<ViolationAttributes> {
+    let Some(attribute) = item
+        .attrs
+        .iter()
+        .find(|attribute| attribute.path().is_ident("violation"))
+    else {
+        return Err(Error::new(
+            item.ident.span(),
+            "Expected a `#[violation(code = ...)]` attribute",
+        ));
+    };
+
+    let mut code = None;
+    let mut message = None;
+    let mut fix_title = None;
+    attribute.parse_nested_meta(|meta| {
+        if meta.path.is_ident("code") {
+            code = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("message") {
+            message = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("fix_title") {
+            fix_title = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("Unknown `violation` argument"));
+        }
+        Ok(())
+    })?;
+
+    Ok(ViolationAttributes {
+        code: code.ok_or_else(|| Error::new(attribute.span(), "Missing `code` argument"))?,
+        message,
+        fix_title,
+    })
+}
+
+/// The names of the fields that the format string `fix_title` refers to, like `name` in
+/// `"Rename to {name}"`, without duplicates.
+fn placeholders(fix_title: &LitStr) -> syn::Result<Vec<Ident>> {
+    let value = fix_title.value();
+    let mut placeholders: Vec<Ident> = vec![];
+    let mut rest = value.as_str();
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        // `{{` is an escaped brace.
+        if let Some(escaped) = rest.strip_prefix('{') {
+            rest = escaped;
+            continue;
+        }
+        let end = rest
+            .find('}')
+            .ok_or_else(|| Error::new(fix_title.span(), "Unclosed `{` in the fix title"))?;
+        let name = rest[..end].split(':').next().unwrap_or_default();
+        let placeholder = syn::parse_str::<Ident>(name).map_err(|_| {
+            Error::new(
+                fix_title.span(),
+                "The placeholders of the fix title must name a field",
+            )
+        })?;
+        if !placeholders.contains(&placeholder) {
+            placeholders.push(placeholder);
+        }
+        rest = &rest[end + 1..];
+    }
+    Ok(placeholders)
+}
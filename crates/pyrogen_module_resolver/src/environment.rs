@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+
+/// Find the `site-packages` directories of the Python environment at `python_path`.
+///
+/// `python_path` may point to a virtual environment (or any other `sys.prefix`), to the Python
+/// interpreter inside of one (e.g. `.venv/bin/python`), or directly to a `site-packages`
+/// directory. The interpreter is never executed; the directories are found by their layout:
+/// `lib/pythonX.Y/site-packages` on Unix and `Lib/site-packages` on Windows.
+pub fn find_site_packages(python_path: &Path) -> Vec<PathBuf> {
+    if python_path.ends_with("site-packages") && python_path.is_dir() {
+        return vec![python_path.to_path_buf()];
+    }
+
+    let prefix = if python_path.is_file() {
+        // `<prefix>/bin/python` or `<prefix>\Scripts\python.exe`.
+        match python_path.parent().and_then(Path::parent) {
+            Some(prefix) => prefix,
+            None => return vec![],
+        }
+    } else {
+        python_path
+    };
+
+    let mut site_packages = vec![];
+
+    let windows_site_packages = prefix.join("Lib").join("site-packages");
+    if windows_site_packages.is_dir() {
+        site_packages.push(windows_site_packages);
+    }
+
+    for lib in ["lib", "lib64"] {
+        let Ok(entries) = prefix.join(lib).read_dir() else {
+            continue;
+        };
+        let mut candidates = entries
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| name.starts_with("python"))
+            })
+            .map(|entry| entry.path().join("site-packages"))
+            .filter(|path| path.is_dir() && !site_packages.contains(path))
+            .collect::<Vec<_>>();
+        candidates.sort();
+        site_packages.extend(candidates);
+    }
+
+    if site_packages.is_empty() {
+        debug!(
+            "No `site-packages` directory found in `{}`",
+            python_path.display()
+        );
+    }
+    site_packages
+}
+
+/// Normalize the name of a distribution as in PEP 503: lowercase, with runs of `-`, `_` and `.`
+/// replaced by a single `-`.
+pub fn normalize_distribution_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for part in name.split(['-', '_', '.']).filter(|part| !part.is_empty()) {
+        if !normalized.is_empty() {
+            normalized.push('-');
+        }
+        normalized.push_str(&part.to_lowercase());
+    }
+    normalized
+}
+
+/// The top-level modules that the files listed in a `RECORD` file belong to.
+fn recorded_modules(record: &str) -> Vec<String> {
+    record
+        .lines()
+        .filter_map(|line| line.split(',').next())
+        .filter_map(|path| {
+            let (first, rest) = match path.split_once('/') {
+                Some((first, rest)) => (first, Some(rest)),
+                None => (path, None),
+            };
+            let module = match rest {
+                Some(_) => first,
+                None => first
+                    .strip_suffix(".py")
+                    .or_else(|| first.strip_suffix(".pyi"))?,
+            };
+            let is_module = !module.is_empty()
+                && module != "__pycache__"
+                && !module.ends_with(".dist-info")
+                && !module.ends_with(".data")
+                && !module.starts_with('.')
+                && module
+                    .chars()
+                    .all(|char| char.is_alphanumeric() || char == '_');
+            is_module.then(|| module.to_string())
+        })
+        .collect()
+}
+
+/// Map the top-level modules installed into the `site_packages` directories to the normalized
+/// names of the distributions that provide them, e.g. `yaml` to `pyyaml`.
+///
+/// The modules of a distribution are read from the `top_level.txt` of its `.dist-info` directory,
+/// or from its `RECORD` if there's none.
+pub fn installed_distributions(site_packages: &[PathBuf]) -> BTreeMap<String, Vec<String>> {
+    let mut distributions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for root in site_packages {
+        let Ok(entries) = root.read_dir() else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let Some(dist_info) = file_name
+                .to_str()
+                .and_then(|name| name.strip_suffix(".dist-info"))
+            else {
+                continue;
+            };
+            // `<name>-<version>.dist-info`, where the name can't contain a `-`.
+            let name = normalize_distribution_name(dist_info.split('-').next().unwrap_or_default());
+            let path = entry.path();
+            let modules = if let Ok(top_level) = std::fs::read_to_string(path.join("top_level.txt"))
+            {
+                top_level
+                    .lines()
+                    .map(str::trim)
+                    .filter(|module| !module.is_empty())
+                    // Modules in subdirectories are listed as `pkg/module`.
+                    .map(|module| module.split('/').next().unwrap_or(module).to_string())
+                    .collect()
+            } else if let Ok(record) = std::fs::read_to_string(path.join("RECORD")) {
+                recorded_modules(&record)
+            } else {
+                continue;
+            };
+            for module in modules {
+                let names = distributions.entry(module).or_default();
+                if !names.contains(&name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+    }
+    distributions
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, write, File};
+
+    use anyhow::Result;
+    use tempfile::TempDir;
+
+    use super::{find_site_packages, installed_distributions, normalize_distribution_name};
+
+    #[test]
+    fn virtual_environment() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let venv = tmp_dir.path().join(".venv");
+        let site_packages = venv.join("lib/python3.11/site-packages");
+        create_dir_all(&site_packages)?;
+        create_dir_all(venv.join("bin"))?;
+        File::create(venv.join("bin/python"))?;
+
+        assert_eq!(find_site_packages(&venv), vec![site_packages.clone()]);
+        assert_eq!(
+            find_site_packages(&venv.join("bin/python")),
+            vec![site_packages.clone()]
+        );
+        assert_eq!(find_site_packages(&site_packages), vec![site_packages]);
+        assert!(find_site_packages(&tmp_dir.path().join("missing")).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn distribution_names() {
+        assert_eq!(normalize_distribution_name("PyYAML"), "pyyaml");
+        assert_eq!(
+            normalize_distribution_name("typing_extensions"),
+            "typing-extensions"
+        );
+        assert_eq!(
+            normalize_distribution_name("zope.Interface"),
+            "zope-interface"
+        );
+        assert_eq!(normalize_distribution_name("a-_.b"), "a-b");
+    }
+
+    #[test]
+    fn distributions() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let site_packages = tmp_dir.path().join("site-packages");
+        let pyyaml = site_packages.join("PyYAML-6.0.1.dist-info");
+        create_dir_all(&pyyaml)?;
+        write(pyyaml.join("top_level.txt"), "_yaml\nyaml\n")?;
+        let attrs = site_packages.join("attrs-23.1.0.dist-info");
+        create_dir_all(&attrs)?;
+        write(
+            attrs.join("RECORD"),
+            "attr/__init__.py,sha256=abc,123\nattrs/__init__.py,sha256=def,456\nattrs-23.1.0.dist-info/RECORD,,\n",
+        )?;
+        let six = site_packages.join("six-1.16.0.dist-info");
+        create_dir_all(&six)?;
+        write(
+            six.join("RECORD"),
+            "six.py,sha256=abc,123\n__pycache__/six.cpython-311.pyc,,\n",
+        )?;
+
+        let distributions = installed_distributions(&[site_packages]);
+        assert_eq!(
+            distributions.into_iter().collect::<Vec<_>>(),
+            [
+                ("_yaml".to_string(), vec!["pyyaml".to_string()]),
+                ("attr".to_string(), vec!["attrs".to_string()]),
+                ("attrs".to_string(), vec!["attrs".to_string()]),
+                ("six".to_string(), vec!["six".to_string()]),
+                ("yaml".to_string(), vec!["pyyaml".to_string()]),
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+
+use crate::ModuleName;
+
+/// The import graph between the modules of a project.
+///
+/// Only imports between modules of the graph are tracked: imports of third-party or standard
+/// library modules, and of modules that don't exist, are dropped.
+#[derive(Debug, Default)]
+pub struct ModuleGraph {
+    modules: Vec<ModuleName>,
+    indices: HashMap<ModuleName, usize>,
+    /// The indices of the modules imported by each module.
+    dependencies: Vec<Vec<usize>>,
+}
+
+impl ModuleGraph {
+    /// Build the graph from each module's name and the names of the modules it imports.
+    ///
+    /// If a name occurs more than once, imports of it refer to its first occurrence.
+    pub fn new(modules: impl IntoIterator<Item = (ModuleName, Vec<ModuleName>)>) -> Self {
+        let (modules, imports): (Vec<_>, Vec<_>) = modules.into_iter().unzip();
+        let mut indices = HashMap::with_capacity(modules.len());
+        for (index, name) in modules.iter().enumerate() {
+            indices.entry(name.clone()).or_insert(index);
+        }
+
+        let dependencies = imports
+            .iter()
+            .enumerate()
+            .map(|(index, imports)| {
+                let mut dependencies = imports
+                    .iter()
+                    .filter_map(|import| indices.get(import).copied())
+                    .filter(|dependency| *dependency != index)
+                    .collect::<Vec<_>>();
+                dependencies.sort_unstable();
+                dependencies.dedup();
+                dependencies
+            })
+            .collect();
+
+        Self {
+            modules,
+            indices,
+            dependencies,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// The modules of the graph that `module` imports.
+    pub fn dependencies(&self, module: &ModuleName) -> impl Iterator<Item = &ModuleName> {
+        self.indices
+            .get(module)
+            .map(|index| self.dependencies[*index].as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|dependency| &self.modules[*dependency])
+    }
+
+    /// Group the modules into levels, such that the dependencies of every module are in an
+    /// earlier level.
+    ///
+    /// Modules that are part of an import cycle can't be ordered among each other and end up in
+    /// the same level. Within a level, modules are sorted by name.
+    pub fn levels(&self) -> Vec<Vec<&ModuleName>> {
+        let components = strongly_connected_components(&self.dependencies);
+
+        let mut component_of = vec![0; self.modules.len()];
+        for (component, members) in components.iter().enumerate() {
+            for member in members {
+                component_of[*member] = component;
+            }
+        }
+
+        // Components are emitted after all the components they depend on, so the levels of the
+        // dependencies are always known.
+        let mut component_levels = Vec::with_capacity(components.len());
+        for (component, members) in components.iter().enumerate() {
+            let level = members
+                .iter()
+                .flat_map(|member| &self.dependencies[*member])
+                .map(|dependency| component_of[*dependency])
+                .filter(|dependency| *dependency != component)
+                .map(|dependency| component_levels[dependency] + 1)
+                .max()
+                .unwrap_or(0);
+            component_levels.push(level);
+        }
+
+        let mut levels: Vec<Vec<&ModuleName>> =
+            vec![vec![]; component_levels.iter().max().map_or(0, |max| max + 1)];
+        for (index, module) in self.modules.iter().enumerate() {
+            levels[component_levels[component_of[index]]].push(module);
+        }
+        for level in &mut levels {
+            level.sort_unstable();
+        }
+        levels
+    }
+}
+
+/// Tarjan's algorithm, without recursion so that long import chains can't overflow the stack.
+///
+/// Returns the components in reverse topological order: every component comes after the
+/// components it has edges to.
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+
+    let mut index = vec![UNVISITED; edges.len()];
+    let mut lowlink = vec![0; edges.len()];
+    let mut on_stack = vec![false; edges.len()];
+    let mut stack = vec![];
+    let mut components = vec![];
+    let mut next_index = 0;
+
+    for root in 0..edges.len() {
+        if index[root] != UNVISITED {
+            continue;
+        }
+
+        // The nodes being visited, along with the position of the next edge to follow.
+        let mut visiting = vec![(root, 0)];
+        index[root] = next_index;
+        lowlink[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&(node, position)) = visiting.last() {
+            if let Some(&next) = edges[node].get(position) {
+                if let Some(top) = visiting.last_mut() {
+                    top.1 += 1;
+                }
+                if index[next] == UNVISITED {
+                    index[next] = next_index;
+                    lowlink[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    visiting.push((next, 0));
+                } else if on_stack[next] {
+                    lowlink[node] = lowlink[node].min(index[next]);
+                }
+                continue;
+            }
+
+            visiting.pop();
+            if let Some(&(parent, _)) = visiting.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[node]);
+            }
+            if lowlink[node] == index[node] {
+                let mut component = vec![];
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ModuleName;
+
+    use super::ModuleGraph;
+
+    fn graph(modules: &[(&str, &[&str])]) -> ModuleGraph {
+        ModuleGraph::new(modules.iter().map(|(name, imports)| {
+            (
+                ModuleName::new(name).unwrap(),
+                imports
+                    .iter()
+                    .map(|import| ModuleName::new(import).unwrap())
+                    .collect(),
+            )
+        }))
+    }
+
+    fn levels(graph: &ModuleGraph) -> Vec<Vec<&str>> {
+        graph
+            .levels()
+            .into_iter()
+            .map(|level| level.into_iter().map(ModuleName::as_str).collect())
+            .collect()
+    }
+
+    #[test]
+    fn dependency_order() {
+        let graph = graph(&[
+            ("app", &["pkg.helpers", "pkg", "os"]),
+            ("pkg", &["pkg.helpers"]),
+            ("pkg.helpers", &[]),
+            ("tests", &["app"]),
+        ]);
+        assert_eq!(
+            levels(&graph),
+            vec![vec!["pkg.helpers"], vec!["pkg"], vec!["app"], vec!["tests"]]
+        );
+        assert_eq!(
+            graph
+                .dependencies(&ModuleName::new("app").unwrap())
+                .map(ModuleName::as_str)
+                .collect::<Vec<_>>(),
+            vec!["pkg", "pkg.helpers"]
+        );
+    }
+
+    #[test]
+    fn import_cycles() {
+        let graph = graph(&[
+            ("a", &["b"]),
+            ("b", &["c", "b"]),
+            ("c", &["a", "base"]),
+            ("base", &[]),
+            ("main", &["c"]),
+        ]);
+        assert_eq!(
+            levels(&graph),
+            vec![vec!["base"], vec!["a", "b", "c"], vec!["main"]]
+        );
+    }
+}
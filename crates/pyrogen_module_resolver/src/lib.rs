@@ -0,0 +1,21 @@
+//! Resolution of Python imports to modules on disk.
+//!
+//! Given the configured search paths (stub roots, first-party `src` directories,
+//! `site-packages` and finally the vendored typeshed stubs), the [`ModuleResolver`] maps a module
+//! name like `foo.bar` to the file that defines it and classifies it as first-party, third-party
+//! or standard library.
+
+pub use environment::{find_site_packages, installed_distributions, normalize_distribution_name};
+pub use graph::ModuleGraph;
+pub use module_name::ModuleName;
+pub use resolver::{
+    ModuleKind, ModuleOrigin, ModuleResolver, ModuleResolverSettings, ResolvedModule, SearchPath,
+    SearchPathKind, VENDORED_TYPESHED_ROOT,
+};
+pub use stdlib::is_known_standard_library;
+
+mod environment;
+mod graph;
+mod module_name;
+mod resolver;
+mod stdlib;
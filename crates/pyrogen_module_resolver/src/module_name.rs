@@ -0,0 +1,196 @@
+use std::fmt;
+use std::path::Path;
+
+/// A fully-qualified, dotted Python module name, e.g. `foo.bar`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct ModuleName(String);
+
+impl ModuleName {
+    /// Create a new module name, returning `None` if `name` isn't a valid dotted name.
+    pub fn new(name: &str) -> Option<Self> {
+        if name.split('.').all(is_identifier) {
+            Some(Self(name.to_string()))
+        } else {
+            None
+        }
+    }
+
+    /// Create a module name from its components, e.g. `["foo", "bar"]` for `foo.bar`.
+    pub fn from_components<'a>(components: impl IntoIterator<Item = &'a str>) -> Option<Self> {
+        let mut name = String::new();
+        for component in components {
+            if !is_identifier(component) {
+                return None;
+            }
+            if !name.is_empty() {
+                name.push('.');
+            }
+            name.push_str(component);
+        }
+        if name.is_empty() {
+            None
+        } else {
+            Some(Self(name))
+        }
+    }
+
+    /// Derive the module name of the file or package directory at `path`, relative to `root`.
+    ///
+    /// `root/foo/bar.py` becomes `foo.bar`; `root/foo/__init__.py` becomes `foo`.
+    pub fn from_relative_path(path: &Path, root: &Path) -> Option<Self> {
+        let relative = path.strip_prefix(root).ok()?;
+        let mut components = relative
+            .components()
+            .map(|component| component.as_os_str().to_str())
+            .collect::<Option<Vec<_>>>()?;
+
+        let last = components.pop()?;
+        let stem = last
+            .strip_suffix(".pyi")
+            .or_else(|| last.strip_suffix(".py"))
+            .unwrap_or(last);
+        if stem != "__init__" {
+            components.push(stem);
+        }
+        Self::from_components(components)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The components of the module name, e.g. `["foo", "bar"]` for `foo.bar`.
+    pub fn components(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.0.split('.')
+    }
+
+    /// The top-level package of the module, e.g. `foo` for `foo.bar`.
+    pub fn first_component(&self) -> &str {
+        self.components().next().unwrap_or_default()
+    }
+
+    /// The parent package of the module, or `None` for a top-level module.
+    pub fn parent(&self) -> Option<Self> {
+        let (parent, _) = self.0.rsplit_once('.')?;
+        Some(Self(parent.to_string()))
+    }
+
+    /// Returns the name of the submodule `name` within this package.
+    #[must_use]
+    pub fn join(&self, name: &ModuleName) -> Self {
+        Self(format!("{}.{}", self.0, name.0))
+    }
+
+    /// Resolve the module imported by `from <dots><module> import ...` in this module.
+    ///
+    /// `level` is the number of leading dots and `is_package` whether this module is the
+    /// `__init__` of a package. Returns `None` if the import reaches beyond the top-level package.
+    pub fn resolve_import(
+        &self,
+        is_package: bool,
+        level: u32,
+        module: Option<&str>,
+    ) -> Option<Self> {
+        if level == 0 {
+            return Self::new(module?);
+        }
+
+        // A single dot refers to the package containing this module.
+        let mut package = if is_package {
+            self.clone()
+        } else {
+            self.parent()?
+        };
+        for _ in 1..level {
+            package = package.parent()?;
+        }
+        match module {
+            Some(module) => Some(package.join(&Self::new(module)?)),
+            None => Some(package),
+        }
+    }
+}
+
+impl fmt::Display for ModuleName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c == '_' || c.is_alphabetic())
+        && chars.all(|c| c == '_' || c.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::ModuleName;
+
+    #[test]
+    fn validation() {
+        assert!(ModuleName::new("foo.bar_baz").is_some());
+        assert!(ModuleName::new("").is_none());
+        assert!(ModuleName::new("foo..bar").is_none());
+        assert!(ModuleName::new("foo.1bar").is_none());
+        assert!(ModuleName::new("foo-bar").is_none());
+    }
+
+    #[test]
+    fn parent() {
+        let name = ModuleName::new("foo.bar.baz").unwrap();
+        assert_eq!(name.first_component(), "foo");
+        assert_eq!(name.parent(), ModuleName::new("foo.bar"));
+        assert_eq!(ModuleName::new("foo").unwrap().parent(), None);
+    }
+
+    #[test]
+    fn resolve_import() {
+        let module = ModuleName::new("pkg.sub.module").unwrap();
+        assert_eq!(
+            module.resolve_import(false, 0, Some("os.path")),
+            ModuleName::new("os.path")
+        );
+        assert_eq!(
+            module.resolve_import(false, 1, Some("helpers")),
+            ModuleName::new("pkg.sub.helpers")
+        );
+        assert_eq!(
+            module.resolve_import(false, 2, None),
+            ModuleName::new("pkg")
+        );
+        assert_eq!(
+            module.resolve_import(true, 1, None),
+            ModuleName::new("pkg.sub.module")
+        );
+        assert_eq!(module.resolve_import(false, 3, None), None);
+    }
+
+    #[test]
+    fn from_relative_path() {
+        let root = Path::new("/project/src");
+        assert_eq!(
+            ModuleName::from_relative_path(Path::new("/project/src/foo/bar.py"), root),
+            ModuleName::new("foo.bar")
+        );
+        assert_eq!(
+            ModuleName::from_relative_path(Path::new("/project/src/foo/__init__.pyi"), root),
+            ModuleName::new("foo")
+        );
+        assert_eq!(
+            ModuleName::from_relative_path(Path::new("/project/src/foo"), root),
+            ModuleName::new("foo")
+        );
+        assert_eq!(
+            ModuleName::from_relative_path(Path::new("/project/tests/foo.py"), root),
+            None
+        );
+    }
+}
@@ -0,0 +1,715 @@
+use std::borrow::Cow;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+
+use pyrogen_vendored::typeshed::{TypeshedVersions, VendoredFiles};
+
+use crate::module_name::ModuleName;
+use crate::stdlib::is_known_standard_library;
+
+/// Where a module comes from, for the purpose of classifying imports.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ModuleOrigin {
+    /// A module that is part of the project being checked.
+    FirstParty,
+    /// A module that is installed into the environment (or stubbed) but not part of the project.
+    ThirdParty,
+    /// A module of the Python standard library.
+    StandardLibrary,
+}
+
+/// The kind of a resolved module.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ModuleKind {
+    /// A single-file module, e.g. `foo.py`.
+    Module,
+    /// A regular package, i.e. a directory with an `__init__.py`.
+    Package,
+    /// An implicit namespace package (PEP 420), i.e. a directory without an `__init__.py`.
+    NamespacePackage,
+}
+
+/// The kind of a search path, in the order in which they're searched.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SearchPathKind {
+    /// A directory of user-provided stubs.
+    StubRoot,
+    /// A first-party source root (`src`).
+    FirstParty,
+    /// A `site-packages` directory of the Python environment.
+    SitePackages,
+    /// The typeshed standard library stubs that are embedded into the binary. Paths in this
+    /// search path don't exist on disk; they start with [`VENDORED_TYPESHED_ROOT`].
+    VendoredStdlib,
+}
+
+/// The (virtual) directory that the vendored typeshed stubs are mounted at.
+pub const VENDORED_TYPESHED_ROOT: &str = "<vendored>/typeshed";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchPath {
+    pub root: PathBuf,
+    pub kind: SearchPathKind,
+}
+
+impl SearchPath {
+    /// The origin of the module `name` if it was found in this search path.
+    fn origin(&self, name: &ModuleName) -> ModuleOrigin {
+        match self.kind {
+            SearchPathKind::FirstParty => ModuleOrigin::FirstParty,
+            SearchPathKind::SitePackages => ModuleOrigin::ThirdParty,
+            SearchPathKind::VendoredStdlib => ModuleOrigin::StandardLibrary,
+            SearchPathKind::StubRoot => {
+                if is_known_standard_library(name.first_component()) {
+                    ModuleOrigin::StandardLibrary
+                } else {
+                    ModuleOrigin::ThirdParty
+                }
+            }
+        }
+    }
+}
+
+/// A module that was found on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedModule {
+    pub name: ModuleName,
+    /// The file defining the module (the `__init__.py(i)` for packages, preferring stubs), or the
+    /// directory for namespace packages.
+    pub path: PathBuf,
+    pub kind: ModuleKind,
+    pub origin: ModuleOrigin,
+    /// Whether the module ships type information. Installed packages are untyped unless they
+    /// contain a `py.typed` marker or stubs; the contents of untyped modules are treated as `Any`.
+    pub is_typed: bool,
+}
+
+impl ResolvedModule {
+    /// Returns `true` if the module is a vendored typeshed stub rather than a file on disk.
+    pub fn is_vendored(&self) -> bool {
+        self.path.starts_with(VENDORED_TYPESHED_ROOT)
+    }
+}
+
+/// The settings used to construct a [`ModuleResolver`].
+#[derive(Debug, Clone, Default)]
+pub struct ModuleResolverSettings {
+    /// Directories containing user-provided stubs. These take precedence over everything else.
+    pub stub_roots: Vec<PathBuf>,
+    /// The first-party source roots.
+    pub src: Vec<PathBuf>,
+    /// Directories that are namespace packages. Their parent directories are searched for
+    /// first-party modules, unless they're already covered by `src`.
+    pub namespace_packages: Vec<PathBuf>,
+    /// The `site-packages` directories of the Python environment.
+    pub site_packages: Vec<PathBuf>,
+    /// Whether to fall back to the typeshed stubs embedded into the binary for standard library
+    /// modules.
+    pub vendored_typeshed: bool,
+    /// The targeted Python version as `(major, minor)`. Vendored standard library modules that
+    /// don't exist in this version are not resolved.
+    pub target_version: Option<(u8, u8)>,
+}
+
+/// Maps module names (e.g. from `import foo.bar`) to files on disk.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleResolver {
+    search_paths: Vec<SearchPath>,
+    vendored: VendoredFiles,
+    typeshed_versions: TypeshedVersions,
+    target_version: Option<(u8, u8)>,
+}
+
+impl ModuleResolver {
+    pub fn new(settings: &ModuleResolverSettings) -> Self {
+        Self::with_vendored_files(settings, VendoredFiles::typeshed())
+    }
+
+    /// Create a resolver that uses `vendored` instead of the embedded typeshed stubs.
+    pub fn with_vendored_files(settings: &ModuleResolverSettings, vendored: VendoredFiles) -> Self {
+        let mut search_paths = vec![];
+        let mut add = |root: &Path, kind: SearchPathKind| {
+            if !search_paths
+                .iter()
+                .any(|search_path: &SearchPath| search_path.root == root)
+            {
+                search_paths.push(SearchPath {
+                    root: root.to_path_buf(),
+                    kind,
+                });
+            }
+        };
+
+        for root in &settings.stub_roots {
+            add(root, SearchPathKind::StubRoot);
+        }
+        for root in &settings.src {
+            add(root, SearchPathKind::FirstParty);
+        }
+        for namespace_package in &settings.namespace_packages {
+            if settings
+                .src
+                .iter()
+                .any(|src| namespace_package.starts_with(src))
+            {
+                continue;
+            }
+            if let Some(parent) = namespace_package.parent() {
+                add(parent, SearchPathKind::FirstParty);
+            }
+        }
+        for root in &settings.site_packages {
+            add(root, SearchPathKind::SitePackages);
+        }
+        if settings.vendored_typeshed && !vendored.is_empty() {
+            add(
+                &Path::new(VENDORED_TYPESHED_ROOT).join("stdlib"),
+                SearchPathKind::VendoredStdlib,
+            );
+        }
+
+        Self {
+            search_paths,
+            typeshed_versions: vendored.versions(),
+            vendored,
+            target_version: settings.target_version,
+        }
+    }
+
+    /// The directories that are searched for modules, in order of precedence.
+    pub fn search_paths(&self) -> &[SearchPath] {
+        &self.search_paths
+    }
+
+    /// Resolve an absolute import of `name`.
+    ///
+    /// Search paths are tried in order. As in Python, a regular module or package found in a
+    /// later search path takes precedence over a namespace package found in an earlier one.
+    pub fn resolve(&self, name: &ModuleName) -> Option<ResolvedModule> {
+        let mut namespace_package = None;
+        for search_path in &self.search_paths {
+            let resolved = match search_path.kind {
+                SearchPathKind::SitePackages => resolve_installed(&search_path.root, name),
+                SearchPathKind::VendoredStdlib => {
+                    if !self.is_available_in_target(name) {
+                        continue;
+                    }
+                    resolve_in_directory(
+                        &search_path.root,
+                        name,
+                        FileSystem::Vendored(self.vendored),
+                    )
+                    .map(|(path, kind)| (path, kind, true))
+                }
+                SearchPathKind::StubRoot | SearchPathKind::FirstParty => {
+                    resolve_in_directory(&search_path.root, name, FileSystem::Os)
+                        .map(|(path, kind)| (path, kind, true))
+                }
+            };
+            let Some((path, kind, is_typed)) = resolved else {
+                continue;
+            };
+            let module = ResolvedModule {
+                name: name.clone(),
+                path,
+                kind,
+                origin: search_path.origin(name),
+                is_typed,
+            };
+            if kind == ModuleKind::NamespacePackage {
+                namespace_package.get_or_insert(module);
+            } else {
+                return Some(module);
+            }
+        }
+        if namespace_package.is_none() {
+            debug!("Unable to resolve module `{name}`");
+        }
+        namespace_package
+    }
+
+    /// Resolve a relative import like `from ..foo import bar` in `importing_file`.
+    ///
+    /// `level` is the number of leading dots; `module` is the (possibly absent) module name
+    /// following them.
+    pub fn resolve_relative(
+        &self,
+        importing_file: &Path,
+        level: u32,
+        module: Option<&ModuleName>,
+    ) -> Option<ResolvedModule> {
+        if level == 0 {
+            return self.resolve(module?);
+        }
+
+        // A single dot refers to the package containing the importing file.
+        let mut package = importing_file.parent()?;
+        for _ in 1..level {
+            package = package.parent()?;
+        }
+
+        let (path, kind) = match module {
+            Some(module) => resolve_in_directory(package, module, FileSystem::Os)?,
+            None => resolve_package(package, FileSystem::Os)?,
+        };
+        let name = self
+            .module_name_for_path(&path)
+            .or_else(|| module.cloned())?;
+        let origin = self.classify(&name);
+        Some(ResolvedModule {
+            name,
+            path,
+            kind,
+            origin,
+            is_typed: true,
+        })
+    }
+
+    /// Classify the module `name` as first-party, third-party or standard library.
+    ///
+    /// Modules that can't be found on disk are classified by name: known standard library
+    /// modules are [`ModuleOrigin::StandardLibrary`], everything else is
+    /// [`ModuleOrigin::ThirdParty`].
+    pub fn classify(&self, name: &ModuleName) -> ModuleOrigin {
+        if let Some(module) = self.resolve(name) {
+            return module.origin;
+        }
+        if is_known_standard_library(name.first_component()) {
+            ModuleOrigin::StandardLibrary
+        } else {
+            ModuleOrigin::ThirdParty
+        }
+    }
+
+    /// The module name of the file at `path`, if it lies within one of the search paths.
+    pub fn module_name_for_path(&self, path: &Path) -> Option<ModuleName> {
+        self.search_paths
+            .iter()
+            .filter(|search_path| path.starts_with(&search_path.root))
+            // Prefer the most specific search path, e.g. `src/` over the project root.
+            .max_by_key(|search_path| search_path.root.components().count())
+            .and_then(|search_path| ModuleName::from_relative_path(path, &search_path.root))
+    }
+
+    /// Read the source code of `module`, from disk or from the vendored stubs.
+    pub fn read_source(&self, module: &ResolvedModule) -> io::Result<Cow<'static, str>> {
+        if let Some(path) = vendored_path(&module.path) {
+            self.vendored
+                .file(&path)
+                .map(Cow::Borrowed)
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+        } else {
+            std::fs::read_to_string(&module.path).map(Cow::Owned)
+        }
+    }
+
+    /// Returns `true` if the vendored stub of `name` exists in the targeted Python version.
+    fn is_available_in_target(&self, name: &ModuleName) -> bool {
+        self.target_version.map_or(true, |version| {
+            self.typeshed_versions.is_available(name.as_str(), version)
+        })
+    }
+}
+
+/// The file system that a search path lives in.
+#[derive(Debug, Copy, Clone)]
+enum FileSystem {
+    Os,
+    Vendored(VendoredFiles),
+}
+
+impl FileSystem {
+    fn is_file(self, path: &Path) -> bool {
+        match self {
+            Self::Os => path.is_file(),
+            Self::Vendored(files) => {
+                vendored_path(path).is_some_and(|path| files.file(&path).is_some())
+            }
+        }
+    }
+
+    fn is_dir(self, path: &Path) -> bool {
+        match self {
+            Self::Os => path.is_dir(),
+            Self::Vendored(files) => {
+                vendored_path(path).is_some_and(|path| files.is_directory(&path))
+            }
+        }
+    }
+
+    /// The extensions of module files, in order of precedence: a stub (`.pyi`) takes precedence
+    /// over the implementation next to it, and typeshed only contains stubs.
+    fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::Os => &["pyi", "py"],
+            Self::Vendored(_) => &["pyi"],
+        }
+    }
+}
+
+/// The path of `path` within the vendored typeshed, e.g. `stdlib/os/__init__.pyi`.
+fn vendored_path(path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(VENDORED_TYPESHED_ROOT).ok()?;
+    let components = relative
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+    Some(components.join("/"))
+}
+
+/// Resolve `name` in the `site-packages` directory `root`, returning whether it's typed, too.
+///
+/// As specified by PEP 561, a stub-only `<package>-stubs` distribution takes precedence over the
+/// package itself, and a package is only typed if it contains a `py.typed` marker.
+fn resolve_installed(root: &Path, name: &ModuleName) -> Option<(PathBuf, ModuleKind, bool)> {
+    let mut components = name.components().collect::<Vec<_>>();
+    let package = components[0];
+
+    let stubs_package = format!("{package}-stubs");
+    components[0] = &stubs_package;
+    if let Some((path, kind)) = resolve_components(root, &components, FileSystem::Os) {
+        if kind != ModuleKind::NamespacePackage {
+            return Some((path, kind, true));
+        }
+    }
+
+    let (path, kind) = resolve_in_directory(root, name, FileSystem::Os)?;
+    let is_typed = path.extension().is_some_and(|extension| extension == "pyi")
+        || root.join(package).join("py.typed").is_file();
+    Some((path, kind, is_typed))
+}
+
+/// Resolve `name` relative to the directory `root`.
+fn resolve_in_directory(
+    root: &Path,
+    name: &ModuleName,
+    file_system: FileSystem,
+) -> Option<(PathBuf, ModuleKind)> {
+    resolve_components(root, &name.components().collect::<Vec<_>>(), file_system)
+}
+
+/// Resolve the module with the given name components relative to the directory `root`.
+fn resolve_components(
+    root: &Path,
+    components: &[&str],
+    file_system: FileSystem,
+) -> Option<(PathBuf, ModuleKind)> {
+    let (last, components) = components.split_last()?;
+
+    // Every parent must be a (regular or namespace) package.
+    let mut directory = root.to_path_buf();
+    for component in components {
+        directory.push(component);
+        if !file_system.is_dir(&directory) {
+            return None;
+        }
+    }
+
+    // A package takes precedence over a module of the same name.
+    let package = directory.join(last);
+    if let Some(resolved) = resolve_package(&package, file_system) {
+        return Some(resolved);
+    }
+
+    file_system.extensions().iter().find_map(|extension| {
+        let module = directory.join(format!("{last}.{extension}"));
+        file_system
+            .is_file(&module)
+            .then_some((module, ModuleKind::Module))
+    })
+}
+
+/// Resolve the package at `directory`, which is either a regular or a namespace package.
+fn resolve_package(directory: &Path, file_system: FileSystem) -> Option<(PathBuf, ModuleKind)> {
+    let init = file_system.extensions().iter().find_map(|extension| {
+        let init = directory.join(format!("__init__.{extension}"));
+        file_system.is_file(&init).then_some(init)
+    });
+    if let Some(init) = init {
+        Some((init, ModuleKind::Package))
+    } else if file_system.is_dir(directory) {
+        Some((directory.to_path_buf(), ModuleKind::NamespacePackage))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, File};
+    use std::path::Path;
+
+    use anyhow::Result;
+    use pyrogen_vendored::typeshed::VendoredFiles;
+    use tempfile::TempDir;
+
+    use crate::{
+        ModuleKind, ModuleName, ModuleOrigin, ModuleResolver, ModuleResolverSettings,
+        SearchPathKind,
+    };
+
+    fn touch(path: &Path) -> Result<()> {
+        create_dir_all(path.parent().unwrap())?;
+        File::create(path)?;
+        Ok(())
+    }
+
+    fn name(name: &str) -> ModuleName {
+        ModuleName::new(name).unwrap()
+    }
+
+    #[test]
+    fn resolve_modules_and_packages() -> Result<()> {
+        // Initialize the filesystem:
+        //   root
+        //   ├── src
+        //   │   ├── app
+        //   │   │   ├── __init__.py
+        //   │   │   └── models.py
+        //   │   └── ns
+        //   │       └── util.py
+        //   └── site-packages
+        //       ├── app.py
+        //       └── requests
+        //           └── __init__.py
+        let tmp_dir = TempDir::new()?;
+        let root = tmp_dir.path();
+        let src = root.join("src");
+        let site_packages = root.join("site-packages");
+        touch(&src.join("app/__init__.py"))?;
+        touch(&src.join("app/models.py"))?;
+        touch(&src.join("ns/util.py"))?;
+        touch(&site_packages.join("app.py"))?;
+        touch(&site_packages.join("requests/__init__.py"))?;
+
+        let resolver = ModuleResolver::new(&ModuleResolverSettings {
+            src: vec![src.clone()],
+            site_packages: vec![site_packages.clone()],
+            ..ModuleResolverSettings::default()
+        });
+
+        let app = resolver.resolve(&name("app")).unwrap();
+        assert_eq!(app.path, src.join("app/__init__.py"));
+        assert_eq!(app.kind, ModuleKind::Package);
+        assert_eq!(app.origin, ModuleOrigin::FirstParty);
+
+        let models = resolver.resolve(&name("app.models")).unwrap();
+        assert_eq!(models.path, src.join("app/models.py"));
+        assert_eq!(models.kind, ModuleKind::Module);
+
+        let ns = resolver.resolve(&name("ns")).unwrap();
+        assert_eq!(ns.kind, ModuleKind::NamespacePackage);
+        assert!(resolver.resolve(&name("ns.util")).is_some());
+
+        let requests = resolver.resolve(&name("requests")).unwrap();
+        assert_eq!(requests.origin, ModuleOrigin::ThirdParty);
+
+        assert!(resolver.resolve(&name("app.missing")).is_none());
+        assert_eq!(
+            resolver.classify(&name("os.path")),
+            ModuleOrigin::StandardLibrary
+        );
+        assert_eq!(resolver.classify(&name("numpy")), ModuleOrigin::ThirdParty);
+
+        Ok(())
+    }
+
+    #[test]
+    fn regular_package_beats_namespace_package() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let root = tmp_dir.path();
+        create_dir_all(root.join("first/pkg"))?;
+        touch(&root.join("second/pkg/__init__.py"))?;
+
+        let resolver = ModuleResolver::new(&ModuleResolverSettings {
+            src: vec![root.join("first"), root.join("second")],
+            ..ModuleResolverSettings::default()
+        });
+        let pkg = resolver.resolve(&name("pkg")).unwrap();
+        assert_eq!(pkg.kind, ModuleKind::Package);
+        assert_eq!(pkg.path, root.join("second/pkg/__init__.py"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn stub_beats_source() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let src = tmp_dir.path().join("src");
+        touch(&src.join("app/__init__.py"))?;
+        touch(&src.join("app/__init__.pyi"))?;
+        touch(&src.join("app/models.py"))?;
+        touch(&src.join("app/models.pyi"))?;
+        touch(&src.join("app/views.py"))?;
+
+        let resolver = ModuleResolver::new(&ModuleResolverSettings {
+            src: vec![src.clone()],
+            ..ModuleResolverSettings::default()
+        });
+        assert_eq!(
+            resolver.resolve(&name("app")).unwrap().path,
+            src.join("app/__init__.pyi")
+        );
+        assert_eq!(
+            resolver.resolve(&name("app.models")).unwrap().path,
+            src.join("app/models.pyi")
+        );
+        assert_eq!(
+            resolver.resolve(&name("app.views")).unwrap().path,
+            src.join("app/views.py")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_relative_imports() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let src = tmp_dir.path().join("src");
+        touch(&src.join("app/__init__.py"))?;
+        touch(&src.join("app/models.py"))?;
+        touch(&src.join("app/api/__init__.py"))?;
+        touch(&src.join("app/api/views.py"))?;
+
+        let resolver = ModuleResolver::new(&ModuleResolverSettings {
+            src: vec![src.clone()],
+            ..ModuleResolverSettings::default()
+        });
+        let views = src.join("app/api/views.py");
+
+        // from ..models import User
+        let models = resolver
+            .resolve_relative(&views, 2, Some(&name("models")))
+            .unwrap();
+        assert_eq!(models.name, name("app.models"));
+        assert_eq!(models.origin, ModuleOrigin::FirstParty);
+
+        // from . import views
+        let api = resolver.resolve_relative(&views, 1, None).unwrap();
+        assert_eq!(api.name, name("app.api"));
+        assert_eq!(api.kind, ModuleKind::Package);
+
+        assert_eq!(
+            resolver.module_name_for_path(&views),
+            Some(name("app.api.views"))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn namespace_package_roots() {
+        let resolver = ModuleResolver::new(&ModuleResolverSettings {
+            src: vec!["/project/src".into()],
+            namespace_packages: vec!["/project/src/ns".into(), "/project/plugins/ext".into()],
+            ..ModuleResolverSettings::default()
+        });
+        let roots = resolver
+            .search_paths()
+            .iter()
+            .map(|search_path| (search_path.root.as_path(), search_path.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            roots,
+            [
+                (Path::new("/project/src"), SearchPathKind::FirstParty),
+                (Path::new("/project/plugins"), SearchPathKind::FirstParty),
+            ]
+        );
+    }
+
+    #[test]
+    fn installed_packages() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let site_packages = tmp_dir.path().join("site-packages");
+        touch(&site_packages.join("typed/__init__.py"))?;
+        touch(&site_packages.join("typed/py.typed"))?;
+        touch(&site_packages.join("untyped/__init__.py"))?;
+        touch(&site_packages.join("untyped/sub.py"))?;
+        touch(&site_packages.join("requests/__init__.py"))?;
+        touch(&site_packages.join("requests/api.py"))?;
+        touch(&site_packages.join("requests-stubs/__init__.pyi"))?;
+        touch(&site_packages.join("requests-stubs/api.pyi"))?;
+        touch(&site_packages.join("single.py"))?;
+
+        let resolver = ModuleResolver::new(&ModuleResolverSettings {
+            site_packages: vec![site_packages.clone()],
+            ..ModuleResolverSettings::default()
+        });
+
+        assert!(resolver.resolve(&name("typed")).unwrap().is_typed);
+        assert!(!resolver.resolve(&name("untyped")).unwrap().is_typed);
+        assert!(!resolver.resolve(&name("untyped.sub")).unwrap().is_typed);
+        assert!(!resolver.resolve(&name("single")).unwrap().is_typed);
+
+        let api = resolver.resolve(&name("requests.api")).unwrap();
+        assert_eq!(api.path, site_packages.join("requests-stubs/api.pyi"));
+        assert!(api.is_typed);
+
+        Ok(())
+    }
+
+    #[test]
+    fn vendored_stdlib_stubs() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let site_packages = tmp_dir.path().join("site-packages");
+        touch(&site_packages.join("json/__init__.py"))?;
+
+        let vendored = VendoredFiles::from_sorted(&[
+            ("stdlib/VERSIONS", "json: 3.0-\nos: 3.0-\ntomllib: 3.11-\n"),
+            ("stdlib/json/__init__.pyi", ""),
+            ("stdlib/os/__init__.pyi", "sep: str\n"),
+            ("stdlib/os/path.pyi", ""),
+            ("stdlib/tomllib.pyi", ""),
+        ]);
+        let resolver = ModuleResolver::with_vendored_files(
+            &ModuleResolverSettings {
+                site_packages: vec![site_packages.clone()],
+                vendored_typeshed: true,
+                target_version: Some((3, 10)),
+                ..ModuleResolverSettings::default()
+            },
+            vendored,
+        );
+
+        let os = resolver.resolve(&name("os")).unwrap();
+        assert!(os.is_vendored());
+        assert_eq!(os.kind, ModuleKind::Package);
+        assert_eq!(os.origin, ModuleOrigin::StandardLibrary);
+        assert_eq!(resolver.read_source(&os)?, "sep: str\n");
+
+        let path = resolver.resolve(&name("os.path")).unwrap();
+        assert_eq!(path.kind, ModuleKind::Module);
+        assert!(path.path.ends_with("os/path.pyi"));
+
+        // Earlier search paths take precedence over the vendored stubs.
+        let json = resolver.resolve(&name("json")).unwrap();
+        assert_eq!(json.path, site_packages.join("json/__init__.py"));
+
+        // `tomllib` was added in Python 3.11.
+        assert!(resolver.resolve(&name("tomllib")).is_none());
+        assert!(resolver.resolve(&name("os.missing")).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn embedded_typeshed() -> Result<()> {
+        assert!(!VendoredFiles::typeshed().is_empty());
+
+        let resolver = ModuleResolver::new(&ModuleResolverSettings {
+            vendored_typeshed: true,
+            ..ModuleResolverSettings::default()
+        });
+        let os = resolver.resolve(&name("os")).unwrap();
+        assert!(os.is_vendored());
+        assert_eq!(os.kind, ModuleKind::Package);
+        assert_eq!(os.origin, ModuleOrigin::StandardLibrary);
+        assert!(resolver.read_source(&os)?.contains("def getcwd()"));
+        assert!(resolver.resolve(&name("os.path")).is_some());
+
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use rustc_hash::FxHashMap;
+use rustpython_ast::{self as ast, Expr};
 use rustpython_parser::text_size::TextRange;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -170,3 +171,24 @@ impl<'a> IntoIterator for &'a ImportMap {
         self.module_to_imports.iter()
     }
 }
+
+/// Whether the exception handler catches the `ImportError` raised by a failed import, as in the
+/// `try: import x` / `except ImportError:` idiom for optional dependencies.
+pub fn catches_import_error(handler: &ast::ExceptHandler) -> bool {
+    fn is_import_error(expr: &Expr) -> bool {
+        match expr {
+            Expr::Name(ast::ExprName { id, .. }) => matches!(
+                id.as_str(),
+                "ImportError" | "ModuleNotFoundError" | "Exception" | "BaseException"
+            ),
+            Expr::Attribute(ast::ExprAttribute { attr, .. }) => {
+                matches!(attr.as_str(), "ImportError" | "ModuleNotFoundError")
+            }
+            Expr::Tuple(ast::ExprTuple { elts, .. }) => elts.iter().any(is_import_error),
+            _ => false,
+        }
+    }
+
+    let ast::ExceptHandler::ExceptHandler(handler) = handler;
+    handler.type_.as_deref().map_or(true, is_import_error)
+}
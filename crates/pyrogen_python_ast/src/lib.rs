@@ -2,7 +2,7 @@ use rustpython_parser::Mode;
 use std::path::Path;
 
 pub mod imports;
-// pub mod visitor;
+pub mod visitor;
 
 /// The type of a source file.
 #[derive(Clone, Copy, Debug, PartialEq, is_macro::Is)]
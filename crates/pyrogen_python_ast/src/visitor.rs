@@ -1,19 +1,12 @@
 //! AST visitor trait and walk functions.
 
-use ast::{ArgWithDefault, ExprJoinedStr};
 use rustpython_ast::{
     self as ast, Alias, Arg, Arguments, BoolOp, CmpOp, Comprehension, ExceptHandler, Expr,
-    ExprContext, MatchCase, Operator, Pattern, Stmt, TypeParam, TypeParamTypeVar, UnaryOp,
-    WithItem,
+    ExprContext, Keyword, MatchCase, Operator, Pattern, Stmt, TypeParam, TypeParamTypeVar,
+    UnaryOp, WithItem,
 };
 
 /// A trait for AST visitors. Visits all nodes in the AST recursively in evaluation-order.
-///
-/// Prefer [`crate::statement_visitor::StatementVisitor`] for visitors that only need to visit
-/// statements.
-///
-/// Use the [`PreorderVisitor`](self::preorder::PreorderVisitor) if you want to visit the nodes
-/// in pre-order rather than evaluation order.
 pub trait Visitor<'a> {
     fn visit_stmt(&mut self, stmt: &'a Stmt) {
         walk_stmt(self, stmt);
@@ -51,16 +44,13 @@ pub trait Visitor<'a> {
     fn visit_format_spec(&mut self, format_spec: &'a Expr) {
         walk_format_spec(self, format_spec);
     }
-    fn visit_arguments(&mut self, arguments: &'a Vec<Expr>) {
-        walk_arguments(self, arguments);
-    }
-    fn visit_parameters(&mut self, parameters: &'a Parameters) {
+    fn visit_parameters(&mut self, parameters: &'a Arguments) {
         walk_parameters(self, parameters);
     }
     fn visit_parameter(&mut self, parameter: &'a Arg) {
         walk_parameter(self, parameter);
     }
-    fn visit_keyword(&mut self, keyword: &'a ArgWithDefault) {
+    fn visit_keyword(&mut self, keyword: &'a Keyword) {
         walk_keyword(self, keyword);
     }
     fn visit_alias(&mut self, alias: &'a Alias) {
@@ -69,7 +59,7 @@ pub trait Visitor<'a> {
     fn visit_with_item(&mut self, with_item: &'a WithItem) {
         walk_with_item(self, with_item);
     }
-    fn visit_type_params(&mut self, type_params: &'a Vec<TypeParam>) {
+    fn visit_type_params(&mut self, type_params: &'a [TypeParam]) {
         walk_type_params(self, type_params);
     }
     fn visit_type_param(&mut self, type_param: &'a TypeParam) {
@@ -81,12 +71,6 @@ pub trait Visitor<'a> {
     fn visit_pattern(&mut self, pattern: &'a Pattern) {
         walk_pattern(self, pattern);
     }
-    fn visit_pattern_arguments(&mut self, pattern_arguments: &'a Pattern) {
-        walk_pattern_arguments(self, pattern_arguments);
-    }
-    fn visit_pattern_keyword(&mut self, pattern_keyword: &'a PatternKeyword) {
-        walk_pattern_keyword(self, pattern_keyword);
-    }
     fn visit_body(&mut self, body: &'a [Stmt]) {
         walk_body(self, body);
     }
@@ -98,16 +82,6 @@ pub fn walk_body<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, body: &'a [Stmt])
     }
 }
 
-pub fn walk_elif_else_clause<'a, V: Visitor<'a> + ?Sized>(
-    visitor: &mut V,
-    elif_else_clause: &'a ElifElseClause,
-) {
-    if let Some(test) = &elif_else_clause.test {
-        visitor.visit_expr(test);
-    }
-    visitor.visit_body(&elif_else_clause.body);
-}
-
 pub fn walk_stmt<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, stmt: &'a Stmt) {
     match stmt {
         Stmt::FunctionDef(ast::StmtFunctionDef {
@@ -117,19 +91,28 @@ pub fn walk_stmt<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, stmt: &'a Stmt) {
             returns,
             type_params,
             ..
+        })
+        | Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef {
+            args,
+            body,
+            decorator_list,
+            returns,
+            type_params,
+            ..
         }) => {
             for decorator in decorator_list {
                 visitor.visit_decorator(decorator);
             }
             visitor.visit_type_params(type_params);
             visitor.visit_parameters(args);
-            for expr in returns {
+            if let Some(expr) = returns {
                 visitor.visit_annotation(expr);
             }
             visitor.visit_body(body);
         }
         Stmt::ClassDef(ast::StmtClassDef {
             bases,
+            keywords,
             body,
             decorator_list,
             type_params,
@@ -139,7 +122,12 @@ pub fn walk_stmt<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, stmt: &'a Stmt) {
                 visitor.visit_decorator(decorator);
             }
             visitor.visit_type_params(type_params);
-            visitor.visit_arguments(bases);
+            for expr in bases {
+                visitor.visit_expr(expr);
+            }
+            for keyword in keywords {
+                visitor.visit_keyword(keyword);
+            }
             visitor.visit_body(body);
         }
         Stmt::Return(ast::StmtReturn { value, range: _ }) => {
@@ -196,6 +184,13 @@ pub fn walk_stmt<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, stmt: &'a Stmt) {
             body,
             orelse,
             ..
+        })
+        | Stmt::AsyncFor(ast::StmtAsyncFor {
+            target,
+            iter,
+            body,
+            orelse,
+            ..
         }) => {
             visitor.visit_expr(iter);
             visitor.visit_expr(target);
@@ -220,14 +215,10 @@ pub fn walk_stmt<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, stmt: &'a Stmt) {
         }) => {
             visitor.visit_expr(test);
             visitor.visit_body(body);
-            for clause in orelse {
-                if let Some(test) = &clause.test {
-                    visitor.visit_expr(test);
-                }
-                walk_elif_else_clause(visitor, clause);
-            }
+            visitor.visit_body(orelse);
         }
-        Stmt::With(ast::StmtWith { items, body, .. }) => {
+        Stmt::With(ast::StmtWith { items, body, .. })
+        | Stmt::AsyncWith(ast::StmtAsyncWith { items, body, .. }) => {
             for with_item in items {
                 visitor.visit_with_item(with_item);
             }
@@ -261,6 +252,13 @@ pub fn walk_stmt<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, stmt: &'a Stmt) {
             orelse,
             finalbody,
             range: _,
+        })
+        | Stmt::TryStar(ast::StmtTryStar {
+            body,
+            handlers,
+            orelse,
+            finalbody,
+            range: _,
         }) => {
             visitor.visit_body(body);
             for except_handler in handlers {
@@ -301,7 +299,7 @@ pub fn walk_annotation<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expr: &'a E
 }
 
 pub fn walk_decorator<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, decorator: &'a Expr) {
-    visitor.visit_expr(&decorator);
+    visitor.visit_expr(decorator);
 }
 
 pub fn walk_expr<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expr: &'a Expr) {
@@ -347,9 +345,7 @@ pub fn walk_expr<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expr: &'a Expr) {
             body,
             range: _,
         }) => {
-            if let Some(parameters) = args {
-                visitor.visit_parameters(parameters);
-            }
+            visitor.visit_parameters(args);
             visitor.visit_expr(body);
         }
         Expr::IfExp(ast::ExprIfExp {
@@ -445,11 +441,19 @@ pub fn walk_expr<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expr: &'a Expr) {
         Expr::Call(ast::ExprCall {
             func,
             args,
-            range: _,
             keywords,
+            range: _,
         }) => {
+            // Note that there might be keywords before the last positional argument, e.g. in
+            // `f(*args, a=2, *args2, **kwargs)`, but we follow Python in evaluating first `args`
+            // and then `keywords`.
             visitor.visit_expr(func);
-            visitor.visit_arguments(args);
+            for expr in args {
+                visitor.visit_expr(expr);
+            }
+            for keyword in keywords {
+                visitor.visit_keyword(keyword);
+            }
         }
         Expr::FormattedValue(ast::ExprFormattedValue {
             value, format_spec, ..
@@ -459,7 +463,7 @@ pub fn walk_expr<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expr: &'a Expr) {
                 visitor.visit_format_spec(expr);
             }
         }
-        Expr::JoinedStr(ExprJoinedStr { values, .. }) => {
+        Expr::JoinedStr(ast::ExprJoinedStr { values, .. }) => {
             for expr in values {
                 visitor.visit_expr(expr);
             }
@@ -558,19 +562,7 @@ pub fn walk_format_spec<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, format_spe
     visitor.visit_expr(format_spec);
 }
 
-pub fn walk_arguments<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, arguments: &'a Arguments) {
-    // Note that the there might be keywords before the last arg, e.g. in
-    // f(*args, a=2, *args2, **kwargs)`, but we follow Python in evaluating first `args` and then
-    // `keywords`. See also [Arguments::arguments_source_order`].
-    for arg in &arguments.args {
-        visitor.visit_expr(arg);
-    }
-    for keyword in &arguments.kwonlyargs {
-        visitor.visit_keyword(keyword);
-    }
-}
-
-pub fn walk_parameters<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, parameters: &'a Parameters) {
+pub fn walk_parameters<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, parameters: &'a Arguments) {
     // Defaults are evaluated before annotations.
     for arg in &parameters.posonlyargs {
         if let Some(default) = &arg.default {
@@ -589,16 +581,16 @@ pub fn walk_parameters<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, parameters:
     }
 
     for arg in &parameters.posonlyargs {
-        visitor.visit_parameter(&arg.parameter);
+        visitor.visit_parameter(&arg.def);
     }
     for arg in &parameters.args {
-        visitor.visit_parameter(&arg.parameter);
+        visitor.visit_parameter(&arg.def);
     }
     if let Some(arg) = &parameters.vararg {
         visitor.visit_parameter(arg);
     }
     for arg in &parameters.kwonlyargs {
-        visitor.visit_parameter(&arg.parameter);
+        visitor.visit_parameter(&arg.def);
     }
     if let Some(arg) = &parameters.kwarg {
         visitor.visit_parameter(arg);
@@ -611,7 +603,7 @@ pub fn walk_parameter<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, parameter: &
     }
 }
 
-pub fn walk_keyword<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, keyword: &'a ArgWithDefault) {
+pub fn walk_keyword<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, keyword: &'a Keyword) {
     visitor.visit_expr(&keyword.value);
 }
 
@@ -624,7 +616,7 @@ pub fn walk_with_item<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, with_item: &
 
 pub fn walk_type_params<'a, V: Visitor<'a> + ?Sized>(
     visitor: &mut V,
-    type_params: &'a Vec<TypeParam>,
+    type_params: &'a [TypeParam],
 ) {
     for type_param in type_params {
         visitor.visit_type_param(type_param);
@@ -673,9 +665,19 @@ pub fn walk_pattern<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, pattern: &'a P
                 visitor.visit_pattern(pattern);
             }
         }
-        Pattern::MatchClass(ast::PatternMatchClass { cls, patterns, .. }) => {
+        Pattern::MatchClass(ast::PatternMatchClass {
+            cls,
+            patterns,
+            kwd_patterns,
+            ..
+        }) => {
             visitor.visit_expr(cls);
-            visitor.visit_pattern_arguments(patterns);
+            for pattern in patterns {
+                visitor.visit_pattern(pattern);
+            }
+            for pattern in kwd_patterns {
+                visitor.visit_pattern(pattern);
+            }
         }
         Pattern::MatchStar(_) => {}
         Pattern::MatchAs(ast::PatternMatchAs { pattern, .. }) => {
@@ -691,25 +693,6 @@ pub fn walk_pattern<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, pattern: &'a P
     }
 }
 
-pub fn walk_pattern_arguments<'a, V: Visitor<'a> + ?Sized>(
-    visitor: &mut V,
-    pattern_arguments: &'a Pattern,
-) {
-    for pattern in &pattern_arguments.patterns {
-        visitor.visit_pattern(pattern);
-    }
-    for keyword in &pattern_arguments.keywords {
-        visitor.visit_pattern_keyword(keyword);
-    }
-}
-
-pub fn walk_pattern_keyword<'a, V: Visitor<'a> + ?Sized>(
-    visitor: &mut V,
-    pattern_keyword: &'a PatternKeyword,
-) {
-    visitor.visit_pattern(&pattern_keyword.pattern);
-}
-
 #[allow(unused_variables)]
 pub fn walk_expr_context<'a, V: Visitor<'a> + ?Sized>(visitor: &V, expr_context: &'a ExprContext) {}
 
@@ -0,0 +1,167 @@
+//! Quick fixes for the diagnostics of a document.
+
+use std::collections::HashMap;
+
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Range, TextEdit, Url, WorkspaceEdit,
+};
+use rustpython_parser::ast::Ranged;
+
+use pyrogen_checker::fix::Edit;
+use pyrogen_checker::message::Message;
+use pyrogen_checker::registry::ErrorCode;
+use pyrogen_checker::type_ignore::type_ignore_edit;
+use pyrogen_source_file::{Locator, SourceCode};
+
+use crate::lint::{to_lsp_diagnostic, to_lsp_range};
+
+/// The quick fixes for the messages that overlap `range`: the message's own fix, if it has one,
+/// and a `type: ignore` comment that suppresses it.
+pub(crate) fn code_actions(
+    uri: &Url,
+    messages: &[Message],
+    range: Range,
+) -> Vec<CodeActionOrCommand> {
+    let mut actions = vec![];
+    for message in messages {
+        let source_code = message.file.to_source_code();
+        let diagnostic = to_lsp_diagnostic(message);
+        if diagnostic.range.end < range.start || range.end < diagnostic.range.start {
+            continue;
+        }
+
+        if let Some(fix) = &message.fix {
+            actions.push(quick_fix(
+                fix.title().to_string(),
+                uri,
+                fix.edits(),
+                &source_code,
+                &diagnostic,
+                true,
+            ));
+        }
+
+        let code = message.diagnostic.error_code;
+        // A syntax error, an expired `type: ignore` comment or the notice of truncated
+        // diagnostics can't be suppressed, and suppressing an unused `type: ignore` comment would
+        // only make it longer.
+        if matches!(
+            code,
+            ErrorCode::SyntaxError
+                | ErrorCode::UnusedTypeIgnore
+                | ErrorCode::ExpiredSuppression
+                | ErrorCode::TooManyDiagnostics
+        ) {
+            continue;
+        }
+        let locator = Locator::new(message.file.source_text());
+        if let Some(edit) = type_ignore_edit(code, message.ignore_offset, &locator) {
+            actions.push(quick_fix(
+                format!("Add `# type: ignore[{code}]` to this line"),
+                uri,
+                &[edit],
+                &source_code,
+                &diagnostic,
+                false,
+            ));
+        }
+    }
+    actions
+}
+
+fn quick_fix(
+    title: String,
+    uri: &Url,
+    edits: &[Edit],
+    source_code: &SourceCode,
+    diagnostic: &lsp_types::Diagnostic,
+    is_preferred: bool,
+) -> CodeActionOrCommand {
+    let edits = edits
+        .iter()
+        .map(|edit| TextEdit {
+            range: to_lsp_range(edit.range(), source_code),
+            new_text: edit.content().unwrap_or_default().to_string(),
+        })
+        .collect();
+
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), edits)])),
+            ..WorkspaceEdit::default()
+        }),
+        is_preferred: Some(is_preferred),
+        ..CodeAction::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use lsp_types::{CodeActionOrCommand, Position, Range, TextEdit, Url};
+
+    use pyrogen_checker::registry::ErrorCode;
+    use pyrogen_checker::settings::CheckerSettings;
+    use pyrogen_workspace::Settings;
+
+    use crate::lint::check;
+
+    use super::code_actions;
+
+    /// The titles and edits of the code actions for the given line of `source`.
+    fn actions(source: &str, line: u32) -> Vec<(String, Vec<TextEdit>)> {
+        let settings = Settings {
+            checker: CheckerSettings::for_rules(vec![
+                ErrorCode::DuplicateKey,
+                ErrorCode::UnusedTypeIgnore,
+            ]),
+            ..Settings::default()
+        };
+        let uri = Url::parse("file:///project/module.py").unwrap();
+        let messages = check(Path::new("/project/module.py"), source, &settings).unwrap();
+        let range = Range::new(Position::new(line, 0), Position::new(line, 0));
+        code_actions(&uri, &messages, range)
+            .into_iter()
+            .map(|action| {
+                let CodeActionOrCommand::CodeAction(action) = action else {
+                    panic!("expected a code action");
+                };
+                let mut changes = action.edit.unwrap().changes.unwrap();
+                (action.title, changes.remove(&uri).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn suppress() {
+        assert_eq!(
+            actions("_a = {1: 1, 1: 2}\n", 0),
+            [(
+                "Add `# type: ignore[duplicate-key]` to this line".to_string(),
+                vec![TextEdit {
+                    range: Range::new(Position::new(0, 17), Position::new(0, 17)),
+                    new_text: "  # type: ignore[duplicate-key]".to_string(),
+                }]
+            )]
+        );
+        assert!(actions("_a = {1: 1, 1: 2}\n_b = 1\n", 1).is_empty());
+    }
+
+    #[test]
+    fn remove_unused_type_ignore() {
+        assert_eq!(
+            actions("_a = 1  # type: ignore\n", 0),
+            [(
+                "Remove unused `type: ignore` directive".to_string(),
+                vec![TextEdit {
+                    range: Range::new(Position::new(0, 6), Position::new(0, 22)),
+                    new_text: String::new(),
+                }]
+            )]
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Hovers with the type and docstring of the name under the cursor.
+
+use std::path::Path;
+
+use lsp_types::{HoverContents, MarkupContent, MarkupKind, Position};
+
+use pyrogen_checker::hover::type_at_position;
+use pyrogen_python_ast::PySourceType;
+use pyrogen_source_file::{ColumnEncoding, LineIndex, OneIndexed, SourceCode, SourceLocation};
+
+use crate::lint::to_lsp_range;
+
+/// The hover for the name at `position` in the `contents` of the document at `path`.
+pub(crate) fn hover(path: &Path, contents: &str, position: Position) -> Option<lsp_types::Hover> {
+    let index = LineIndex::from_source_text(contents);
+    let source_code = SourceCode::new(contents, &index);
+    let location = SourceLocation {
+        row: OneIndexed::from_zero_indexed(position.line as usize),
+        column: OneIndexed::from_zero_indexed(position.character as usize),
+    };
+    let offset = source_code.offset(location, ColumnEncoding::Utf16);
+
+    let hover = type_at_position(contents, PySourceType::from(path), offset)?;
+    Some(lsp_types::Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: hover.markdown(),
+        }),
+        range: Some(to_lsp_range(hover.range, &source_code)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use lsp_types::{HoverContents, Position, Range};
+
+    use super::hover;
+
+    #[test]
+    fn markdown() {
+        let source = "label = '𝔘'\nprint(label)\n";
+        let label = hover(Path::new("module.py"), source, Position::new(1, 8)).unwrap();
+
+        let HoverContents::Markup(contents) = label.contents else {
+            panic!("expected Markdown contents");
+        };
+        assert_eq!(contents.value, "```python\nlabel: str\n```");
+        assert_eq!(
+            label.range,
+            Some(Range::new(Position::new(1, 6), Position::new(1, 11)))
+        );
+        assert!(hover(Path::new("module.py"), source, Position::new(1, 2)).is_none());
+    }
+}
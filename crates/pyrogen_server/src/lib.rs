@@ -0,0 +1,382 @@
+//! A language server for pyrogen, speaking the Language Server Protocol over stdio.
+//!
+//! Documents are checked when they're opened, changed or saved, and the diagnostics are
+//! published to the client, along with quick fixes that apply a diagnostic's fix or suppress it
+//! with a `type: ignore` comment. Hovers show the type and docstring of the name under the
+//! cursor. The settings come from the closest `pyproject.toml` of each document and are reloaded
+//! when a `pyproject.toml` changes.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use log::{debug, info, warn};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidChangeWatchedFiles, DidCloseTextDocument, DidOpenTextDocument,
+    DidSaveTextDocument, Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{CodeActionRequest, HoverRequest, RegisterCapability, Request as _};
+use lsp_types::{
+    CodeActionKind, CodeActionOptions, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
+    DidChangeWatchedFilesRegistrationOptions, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DidSaveTextDocumentParams, FileSystemWatcher, GlobPattern,
+    HoverParams, HoverProviderCapability, InitializeParams, InitializeResult,
+    PublishDiagnosticsParams, Registration, RegistrationParams, SaveOptions, ServerCapabilities,
+    ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
+    TextDocumentSyncSaveOptions, Url,
+};
+use serde::de::DeserializeOwned;
+
+use pyrogen_checker::VERSION;
+
+use crate::session::{Document, Session};
+
+mod code_action;
+mod hover;
+mod lint;
+mod session;
+
+/// Run the language server on stdin and stdout until the client shuts it down.
+pub fn run() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let (id, params) = connection.initialize_start()?;
+    let params: InitializeParams = serde_json::from_value(params)?;
+    let result = InitializeResult {
+        capabilities: server_capabilities(),
+        server_info: Some(ServerInfo {
+            name: "pyrogen".to_string(),
+            version: Some(VERSION.to_string()),
+        }),
+    };
+    connection.initialize_finish(id, serde_json::to_value(result)?)?;
+    info!("Started the pyrogen language server");
+
+    Server::new(&connection, &params).main_loop()?;
+
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+fn server_capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::FULL),
+                save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
+                    include_text: Some(false),
+                })),
+                ..TextDocumentSyncOptions::default()
+            },
+        )),
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+            ..CodeActionOptions::default()
+        })),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// The paths of the workspace folders, or of the root if the client doesn't support folders.
+#[allow(deprecated)]
+fn workspace_roots(params: &InitializeParams) -> Vec<PathBuf> {
+    if let Some(folders) = &params.workspace_folders {
+        return folders
+            .iter()
+            .filter_map(|folder| folder.uri.to_file_path().ok())
+            .collect();
+    }
+    params
+        .root_uri
+        .as_ref()
+        .and_then(|uri| uri.to_file_path().ok())
+        .into_iter()
+        .collect()
+}
+
+/// The parameters of the notification `method`, or `None` if they're invalid. Notifications
+/// don't get a response, so the error is only logged.
+fn notification_params<P: DeserializeOwned>(method: &str, params: serde_json::Value) -> Option<P> {
+    match serde_json::from_value(params) {
+        Ok(params) => Some(params),
+        Err(err) => {
+            warn!("Ignoring `{method}` notification with invalid parameters: {err}");
+            None
+        }
+    }
+}
+
+fn is_configuration(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == "pyproject.toml")
+}
+
+struct Server<'a> {
+    connection: &'a Connection,
+    session: Session,
+    /// Whether the client lets us register a watcher for `pyproject.toml` files.
+    watch_configuration: bool,
+    next_request_id: i32,
+}
+
+impl<'a> Server<'a> {
+    fn new(connection: &'a Connection, params: &InitializeParams) -> Self {
+        let watch_configuration = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.did_change_watched_files.as_ref())
+            .and_then(|capability| capability.dynamic_registration)
+            .unwrap_or_default();
+        Self {
+            connection,
+            session: Session::new(workspace_roots(params)),
+            watch_configuration,
+            next_request_id: 0,
+        }
+    }
+
+    fn main_loop(&mut self) -> Result<()> {
+        if self.watch_configuration {
+            self.register_configuration_watcher()?;
+        }
+
+        let connection = self.connection;
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    self.handle_request(request)?;
+                }
+                Message::Notification(notification) => {
+                    self.handle_notification(notification)?;
+                }
+                Message::Response(response) => {
+                    if let Some(error) = response.error {
+                        warn!("Request {} failed: {}", response.id, error.message);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<()> {
+        match request.method.as_str() {
+            CodeActionRequest::METHOD => {
+                let params: CodeActionParams = match serde_json::from_value(request.params) {
+                    Ok(params) => params,
+                    Err(err) => return self.respond_invalid_params(request.id, &err),
+                };
+                let actions = self.code_actions(&params.text_document.uri, params.range);
+                self.respond(Response::new_ok(request.id, actions))
+            }
+            HoverRequest::METHOD => {
+                let params: HoverParams = match serde_json::from_value(request.params) {
+                    Ok(params) => params,
+                    Err(err) => return self.respond_invalid_params(request.id, &err),
+                };
+                let position = params.text_document_position_params;
+                let hover = self.hover(&position.text_document.uri, position.position);
+                self.respond(Response::new_ok(request.id, hover))
+            }
+            method => self.respond(Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request: {method}"),
+            )),
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<()> {
+        let Notification { method, params } = notification;
+        match method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) =
+                    notification_params::<DidOpenTextDocumentParams>(&method, params)
+                else {
+                    return Ok(());
+                };
+                let document = params.text_document;
+                self.session.open(
+                    document.uri.clone(),
+                    Document {
+                        contents: document.text,
+                        version: document.version,
+                    },
+                );
+                self.publish_diagnostics(&document.uri)?;
+            }
+            DidChangeTextDocument::METHOD => {
+                let Some(params) =
+                    notification_params::<DidChangeTextDocumentParams>(&method, params)
+                else {
+                    return Ok(());
+                };
+                let document = params.text_document;
+                // With full synchronization, the last change holds the whole document.
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    if self
+                        .session
+                        .update(&document.uri, change.text, document.version)
+                    {
+                        self.publish_diagnostics(&document.uri)?;
+                    }
+                }
+            }
+            DidSaveTextDocument::METHOD => {
+                let Some(params) =
+                    notification_params::<DidSaveTextDocumentParams>(&method, params)
+                else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                if uri.to_file_path().is_ok_and(|path| is_configuration(&path)) {
+                    // Without a file watcher, a saved `pyproject.toml` is the only sign of a
+                    // configuration change.
+                    if !self.watch_configuration {
+                        self.reload_configuration()?;
+                    }
+                } else {
+                    self.publish_diagnostics(&uri)?;
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let Some(params) =
+                    notification_params::<DidCloseTextDocumentParams>(&method, params)
+                else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                self.session.close(&uri);
+                self.send_diagnostics(uri, vec![], None)?;
+            }
+            DidChangeWatchedFiles::METHOD => {
+                let Some(params) =
+                    notification_params::<DidChangeWatchedFilesParams>(&method, params)
+                else {
+                    return Ok(());
+                };
+                if params.changes.iter().any(|change| {
+                    change
+                        .uri
+                        .to_file_path()
+                        .is_ok_and(|path| is_configuration(&path))
+                }) {
+                    self.reload_configuration()?;
+                }
+            }
+            method => debug!("Ignoring notification: {method}"),
+        }
+        Ok(())
+    }
+
+    /// Ask the client to notify us of changes to `pyproject.toml` files.
+    fn register_configuration_watcher(&mut self) -> Result<()> {
+        let options = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![FileSystemWatcher {
+                glob_pattern: GlobPattern::String("**/pyproject.toml".to_string()),
+                kind: None,
+            }],
+        };
+        let params = RegistrationParams {
+            registrations: vec![Registration {
+                id: "pyrogen-configuration".to_string(),
+                method: DidChangeWatchedFiles::METHOD.to_string(),
+                register_options: Some(serde_json::to_value(options)?),
+            }],
+        };
+        self.next_request_id += 1;
+        let request = Request::new(
+            RequestId::from(self.next_request_id),
+            RegisterCapability::METHOD.to_string(),
+            params,
+        );
+        self.connection.sender.send(request.into())?;
+        Ok(())
+    }
+
+    /// Read the configuration again and re-check every open document.
+    fn reload_configuration(&mut self) -> Result<()> {
+        self.session.reload_settings();
+        let uris: Vec<Url> = self.session.document_uris().cloned().collect();
+        for uri in &uris {
+            self.publish_diagnostics(uri)?;
+        }
+        Ok(())
+    }
+
+    /// Check the open document at `uri` and send its diagnostics to the client.
+    fn publish_diagnostics(&mut self, uri: &Url) -> Result<()> {
+        let Some((messages, version)) = self.check(uri) else {
+            return Ok(());
+        };
+        let diagnostics = messages.iter().map(lint::to_lsp_diagnostic).collect();
+        self.send_diagnostics(uri.clone(), diagnostics, Some(version))
+    }
+
+    /// The quick fixes for the diagnostics of the open document at `uri` that overlap `range`.
+    fn code_actions(&mut self, uri: &Url, range: lsp_types::Range) -> Vec<CodeActionOrCommand> {
+        let Some((messages, _version)) = self.check(uri) else {
+            return vec![];
+        };
+        code_action::code_actions(uri, &messages, range)
+    }
+
+    /// The hover for the name at `position` in the open document at `uri`.
+    fn hover(&self, uri: &Url, position: lsp_types::Position) -> Option<lsp_types::Hover> {
+        let path = uri.to_file_path().ok()?;
+        let document = self.session.document(uri)?;
+        hover::hover(&path, &document.contents, position)
+    }
+
+    /// Check the open document at `uri`, returning its messages and the checked version.
+    fn check(&mut self, uri: &Url) -> Option<(Vec<pyrogen_checker::message::Message>, i32)> {
+        let Ok(path) = uri.to_file_path() else {
+            debug!("Not checking {uri}: only files are supported");
+            return None;
+        };
+        let settings = self.session.settings_for(&path);
+        let document = self.session.document(uri)?;
+        let messages = lint::check(&path, &document.contents, &settings)?;
+        Some((messages, document.version))
+    }
+
+    fn send_diagnostics(
+        &self,
+        uri: Url,
+        diagnostics: Vec<lsp_types::Diagnostic>,
+        version: Option<i32>,
+    ) -> Result<()> {
+        let notification = Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version,
+            },
+        );
+        self.connection.sender.send(notification.into())?;
+        Ok(())
+    }
+
+    fn respond(&self, response: Response) -> Result<()> {
+        self.connection.sender.send(response.into())?;
+        Ok(())
+    }
+
+    fn respond_invalid_params(&self, id: RequestId, error: &serde_json::Error) -> Result<()> {
+        self.respond(Response::new_err(
+            id,
+            ErrorCode::InvalidParams as i32,
+            format!("Invalid parameters: {error}"),
+        ))
+    }
+}
@@ -0,0 +1,143 @@
+//! Check documents and convert the messages to LSP diagnostics.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+
+use log::error;
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range};
+use rustpython_parser::ast::Ranged;
+use rustpython_parser::text_size::{TextRange, TextSize};
+
+use pyrogen_checker::cancellation::CancellationToken;
+use pyrogen_checker::checker::lint_only;
+use pyrogen_checker::message::Message;
+use pyrogen_checker::packaging;
+use pyrogen_checker::settings::code_table::MessageKind;
+use pyrogen_checker::settings::flags;
+use pyrogen_checker::source_kind::SourceKind;
+use pyrogen_python_ast::PySourceType;
+use pyrogen_source_file::{ColumnEncoding, SourceCode};
+use pyrogen_workspace::Settings;
+
+/// Check the `contents` of the document at `path`.
+///
+/// Returns `None` if the check was cancelled or panicked; a panic is logged, so that a bug in a
+/// rule doesn't take down the server.
+pub(crate) fn check(path: &Path, contents: &str, settings: &Settings) -> Option<Vec<Message>> {
+    let package = path.parent().and_then(|parent| {
+        packaging::detect_package_root(parent, &settings.checker.namespace_packages)
+    });
+    let source_kind = SourceKind::new(contents.to_string());
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        lint_only(
+            path,
+            package,
+            &settings.checker,
+            settings.respect_type_ignore.into(),
+            &source_kind,
+            PySourceType::from(path),
+            None,
+            &CancellationToken::default(),
+        )
+    }));
+    let (mut messages, _imports) = match result {
+        Ok(Ok(result)) => result.data,
+        Ok(Err(_cancelled)) => return None,
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<String>()
+                .map(String::as_str)
+                .or_else(|| panic.downcast_ref::<&str>().copied())
+                .unwrap_or("unknown panic");
+            error!("Panicked while checking {}: {message}", path.display());
+            return None;
+        }
+    };
+
+    for message in &mut messages {
+        message.diagnostic.localize(settings.locale);
+    }
+    Some(messages)
+}
+
+pub(crate) fn to_lsp_diagnostic(message: &Message) -> Diagnostic {
+    let source_code = message.file.to_source_code();
+    let severity = match message.kind {
+        MessageKind::Error => DiagnosticSeverity::ERROR,
+        MessageKind::Warning => DiagnosticSeverity::WARNING,
+        MessageKind::Info => DiagnosticSeverity::INFORMATION,
+        MessageKind::Hint => DiagnosticSeverity::HINT,
+    };
+
+    Diagnostic {
+        range: to_lsp_range(message.range(), &source_code),
+        severity: Some(severity),
+        code: Some(NumberOrString::String(
+            message.diagnostic.error_code.to_string(),
+        )),
+        source: Some("pyrogen".to_string()),
+        message: message.diagnostic.body.clone(),
+        ..Diagnostic::default()
+    }
+}
+
+pub(crate) fn to_lsp_range(range: TextRange, source_code: &SourceCode) -> Range {
+    Range::new(
+        to_position(range.start(), source_code),
+        to_position(range.end(), source_code),
+    )
+}
+
+/// The LSP position of `offset`, with the character counted in UTF-16 code units.
+fn to_position(offset: TextSize, source_code: &SourceCode) -> Position {
+    let location = source_code.source_location_with_encoding(offset, ColumnEncoding::Utf16);
+
+    Position::new(
+        u32::try_from(location.row.to_zero_indexed()).unwrap_or(u32::MAX),
+        u32::try_from(location.column.to_zero_indexed()).unwrap_or(u32::MAX),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use lsp_types::{DiagnosticSeverity, NumberOrString, Position, Range};
+
+    use pyrogen_checker::registry::ErrorCode;
+    use pyrogen_checker::settings::CheckerSettings;
+    use pyrogen_workspace::Settings;
+
+    use super::{check, to_lsp_diagnostic};
+
+    #[test]
+    fn diagnostics() {
+        let settings = Settings {
+            checker: CheckerSettings::for_rules(vec![ErrorCode::DuplicateKey]),
+            ..Settings::default()
+        };
+        let diagnostics: Vec<_> = check(
+            Path::new("module.py"),
+            "_a = {\"ä𝔘\": 1, \"ä𝔘\": 2}\n",
+            &settings,
+        )
+        .unwrap()
+        .iter()
+        .map(to_lsp_diagnostic)
+        .collect();
+
+        let [diagnostic] = diagnostics.as_slice() else {
+            panic!("expected a single diagnostic, got {diagnostics:?}");
+        };
+        assert_eq!(
+            diagnostic.code,
+            Some(NumberOrString::String("duplicate-key".to_string()))
+        );
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+        // `ä` is a single UTF-16 code unit and `𝔘` a surrogate pair.
+        assert_eq!(
+            diagnostic.range,
+            Range::new(Position::new(0, 16), Position::new(0, 21))
+        );
+    }
+}
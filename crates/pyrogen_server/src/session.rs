@@ -0,0 +1,128 @@
+//! The state of a language server session: the open documents and the settings that apply to
+//! them.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use log::{debug, error};
+use lsp_types::Url;
+use rustc_hash::FxHashMap;
+
+use pyrogen_workspace::configuration::Configuration;
+use pyrogen_workspace::pyproject;
+use pyrogen_workspace::resolver::{resolve_root_settings, ConfigurationTransformer, Relativity};
+use pyrogen_workspace::Settings;
+
+/// A document that is open in the editor.
+pub(crate) struct Document {
+    pub(crate) contents: String,
+    pub(crate) version: i32,
+}
+
+/// The server has no overrides of its own: the configuration comes from `pyproject.toml` files
+/// only.
+struct NoOverrides;
+
+impl ConfigurationTransformer for NoOverrides {
+    fn transform(&self, config: Configuration) -> Configuration {
+        config
+    }
+}
+
+pub(crate) struct Session {
+    /// The roots of the workspace folders, used to resolve the default settings of documents
+    /// without a `pyproject.toml`.
+    workspace_roots: Vec<PathBuf>,
+    documents: FxHashMap<Url, Document>,
+    /// The settings by the path of their `pyproject.toml`, or by the workspace root for the
+    /// default settings. Cleared when a `pyproject.toml` changes.
+    settings: FxHashMap<PathBuf, Arc<Settings>>,
+}
+
+impl Session {
+    pub(crate) fn new(workspace_roots: Vec<PathBuf>) -> Self {
+        Self {
+            workspace_roots,
+            documents: FxHashMap::default(),
+            settings: FxHashMap::default(),
+        }
+    }
+
+    pub(crate) fn open(&mut self, uri: Url, document: Document) {
+        self.documents.insert(uri, document);
+    }
+
+    /// Replace the contents of an open document. Returns `false` if it isn't open.
+    pub(crate) fn update(&mut self, uri: &Url, contents: String, version: i32) -> bool {
+        let Some(document) = self.documents.get_mut(uri) else {
+            return false;
+        };
+        document.contents = contents;
+        document.version = version;
+        true
+    }
+
+    pub(crate) fn close(&mut self, uri: &Url) {
+        self.documents.remove(uri);
+    }
+
+    pub(crate) fn document(&self, uri: &Url) -> Option<&Document> {
+        self.documents.get(uri)
+    }
+
+    pub(crate) fn document_uris(&self) -> impl Iterator<Item = &Url> {
+        self.documents.keys()
+    }
+
+    /// Forget the resolved settings, so that they're read again from the `pyproject.toml` files.
+    pub(crate) fn reload_settings(&mut self) {
+        debug!("Reloading the configuration");
+        self.settings.clear();
+    }
+
+    /// The settings for the file at `path`, from the closest `pyproject.toml` with a
+    /// `[tool.pyrogen]` section. If the configuration can't be read, the error is logged and the
+    /// default settings are used instead.
+    pub(crate) fn settings_for(&mut self, path: &Path) -> Arc<Settings> {
+        let pyproject = pyproject::find_settings_toml(path).unwrap_or_else(|err| {
+            error!(
+                "Failed to find the configuration of {}: {err:#}",
+                path.display()
+            );
+            None
+        });
+        let key = pyproject
+            .clone()
+            .unwrap_or_else(|| self.workspace_root(path));
+        if let Some(settings) = self.settings.get(&key) {
+            return settings.clone();
+        }
+
+        let settings = match &pyproject {
+            Some(pyproject) => {
+                debug!("Using pyproject.toml at {}", pyproject.display());
+                resolve_root_settings(pyproject, Relativity::Parent, &NoOverrides)
+            }
+            None => {
+                debug!("Using the default settings for {}", key.display());
+                Configuration::default().into_settings(&key)
+            }
+        };
+        let settings = Arc::new(settings.unwrap_or_else(|err| {
+            error!("Failed to resolve the configuration: {err:#}");
+            Settings::default()
+        }));
+        self.settings.insert(key, settings.clone());
+        settings
+    }
+
+    /// The root of the workspace folder that contains `path`.
+    fn workspace_root(&self, path: &Path) -> PathBuf {
+        self.workspace_roots
+            .iter()
+            .find(|root| path.starts_with(root))
+            .or(self.workspace_roots.first())
+            .cloned()
+            .unwrap_or_else(|| path.parent().unwrap_or(path).to_path_buf())
+    }
+}
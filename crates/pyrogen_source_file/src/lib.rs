@@ -13,7 +13,7 @@ pub mod newlines;
 // #[cfg(feature = "serde")]
 // pub mod serialize;
 
-pub use crate::line_index::{LineIndex, OneIndexed};
+pub use crate::line_index::{ColumnEncoding, LineIndex, OneIndexed};
 pub use locator::Locator;
 pub use newlines::{
     find_newline, Line, LineEnding, NewlineWithTrailingNewline, UniversalNewlineIterator,
@@ -41,6 +41,24 @@ impl<'src, 'index> SourceCode<'src, 'index> {
         self.index.source_location(offset, self.text)
     }
 
+    /// Computes the one indexed row and column numbers for `offset`, with the column counted in
+    /// the units of `encoding`.
+    #[inline]
+    pub fn source_location_with_encoding(
+        &self,
+        offset: TextSize,
+        encoding: ColumnEncoding,
+    ) -> SourceLocation {
+        self.index
+            .source_location_with_encoding(offset, self.text, encoding)
+    }
+
+    /// Computes the offset of `location`, with the column counted in the units of `encoding`.
+    #[inline]
+    pub fn offset(&self, location: SourceLocation, encoding: ColumnEncoding) -> TextSize {
+        self.index.offset(location, self.text, encoding)
+    }
+
     #[inline]
     pub fn line_index(&self, offset: TextSize) -> OneIndexed {
         self.index.line_index(offset)
@@ -10,6 +10,23 @@ use serde::{Deserialize, Serialize};
 
 use crate::SourceLocation;
 
+/// The unit in which the columns of a [`SourceLocation`] are counted.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum ColumnEncoding {
+    /// Bytes of the UTF-8 encoded line.
+    Utf8,
+    /// UTF-16 code units, as in the Language Server Protocol.
+    Utf16,
+    /// Unicode code points (characters).
+    #[default]
+    Codepoint,
+}
+
 /// Index for fast [byte offset](TextSize) to [`SourceLocation`] conversions.
 ///
 /// Cloning a [`LineIndex`] is cheap because it only requires bumping a reference count.
@@ -93,6 +110,21 @@ impl LineIndex {
     ///
     /// If the offset is out of bounds.
     pub fn source_location(&self, offset: TextSize, content: &str) -> SourceLocation {
+        self.source_location_with_encoding(offset, content, ColumnEncoding::default())
+    }
+
+    /// Like [`LineIndex::source_location`], but with the column counted in the units of
+    /// `encoding`.
+    ///
+    /// ## Panics
+    ///
+    /// If the offset is out of bounds.
+    pub fn source_location_with_encoding(
+        &self,
+        offset: TextSize,
+        content: &str,
+        encoding: ColumnEncoding,
+    ) -> SourceLocation {
         match self.line_starts().binary_search(&offset) {
             // Offset is at the start of a line
             Ok(row) => SourceLocation {
@@ -112,7 +144,12 @@ impl LineIndex {
                         line_start = '\u{feff}'.text_len();
                     }
 
-                    content[TextRange::new(line_start, offset)].chars().count()
+                    let line = &content[TextRange::new(line_start, offset)];
+                    match encoding {
+                        ColumnEncoding::Utf8 => line.len(),
+                        ColumnEncoding::Utf16 => line.encode_utf16().count(),
+                        ColumnEncoding::Codepoint => line.chars().count(),
+                    }
                 };
 
                 SourceLocation {
@@ -123,6 +160,42 @@ impl LineIndex {
         }
     }
 
+    /// The offset of `location`, with the column counted in the units of `encoding`: the inverse
+    /// of [`LineIndex::source_location_with_encoding`].
+    ///
+    /// Locations past the end of a line are clamped to the end of the line, and locations past
+    /// the last line to the end of the content.
+    pub fn offset(
+        &self,
+        location: SourceLocation,
+        content: &str,
+        encoding: ColumnEncoding,
+    ) -> TextSize {
+        if location.row.to_zero_indexed() >= self.line_count() {
+            return content.text_len();
+        }
+        let mut line_start = self.line_start(location.row, content);
+        if line_start == TextSize::from(0) && content.starts_with('\u{feff}') {
+            line_start = '\u{feff}'.text_len();
+        }
+        let line_end = self.line_end(location.row, content);
+        let line = content[TextRange::new(line_start, line_end)].trim_end_matches(['\n', '\r']);
+
+        let column = location.column.to_zero_indexed();
+        let mut units = 0;
+        for (index, c) in line.char_indices() {
+            if units >= column {
+                return line_start + TextSize::try_from(index).unwrap();
+            }
+            units += match encoding {
+                ColumnEncoding::Utf8 => c.len_utf8(),
+                ColumnEncoding::Utf16 => c.len_utf16(),
+                ColumnEncoding::Codepoint => 1,
+            };
+        }
+        line_start + line.text_len()
+    }
+
     /// Return the number of lines in the source code.
     pub fn line_count(&self) -> usize {
         self.line_starts().len()
@@ -313,9 +386,9 @@ const fn unwrap<T: Copy>(option: Option<T>) -> T {
 
 #[cfg(test)]
 mod tests {
-    use rustpython_parser::text_size::TextSize;
+    use rustpython_parser::text_size::{TextLen, TextSize};
 
-    use crate::line_index::LineIndex;
+    use crate::line_index::{ColumnEncoding, LineIndex};
     use crate::{OneIndexed, SourceLocation};
 
     #[test]
@@ -592,4 +665,46 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn column_encodings() {
+        let contents = "x = 'ä🫣'";
+        let index = LineIndex::from_source_text(contents);
+        let column = |encoding| {
+            index
+                .source_location_with_encoding(TextSize::from(11), contents, encoding)
+                .column
+                .to_zero_indexed()
+        };
+
+        // The closing quote.
+        assert_eq!(column(ColumnEncoding::Utf8), 11);
+        assert_eq!(column(ColumnEncoding::Utf16), 8);
+        assert_eq!(column(ColumnEncoding::Codepoint), 7);
+    }
+
+    #[test]
+    fn offset() {
+        let contents = "\u{feff}x = 'ä🫣'\r\ny = 1\n";
+        let index = LineIndex::from_source_text(contents);
+        let offset = |row, column, encoding| {
+            index.offset(
+                SourceLocation {
+                    row: OneIndexed::from_zero_indexed(row),
+                    column: OneIndexed::from_zero_indexed(column),
+                },
+                contents,
+                encoding,
+            )
+        };
+
+        // The closing quote, after the BOM.
+        assert_eq!(offset(0, 11, ColumnEncoding::Utf8), TextSize::from(14));
+        assert_eq!(offset(0, 8, ColumnEncoding::Utf16), TextSize::from(14));
+        assert_eq!(offset(0, 7, ColumnEncoding::Codepoint), TextSize::from(14));
+        // Past the end of a line and past the last line.
+        assert_eq!(offset(0, 20, ColumnEncoding::Utf16), TextSize::from(15));
+        assert_eq!(offset(1, 4, ColumnEncoding::Utf16), TextSize::from(21));
+        assert_eq!(offset(5, 0, ColumnEncoding::Utf16), contents.text_len());
+    }
 }
@@ -0,0 +1,68 @@
+//! Embeds the vendored typeshed stubs into the binary.
+//!
+//! Every file below `vendor/typeshed/stdlib` is included via `include_str!` into a sorted table,
+//! so that lookups don't touch the filesystem at runtime.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+const TYPESHED_DIR: &str = "vendor/typeshed";
+
+fn main() -> io::Result<()> {
+    println!("cargo:rerun-if-changed={TYPESHED_DIR}");
+
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let typeshed = manifest_dir.join(TYPESHED_DIR);
+
+    let mut files = vec![];
+    let stdlib = typeshed.join("stdlib");
+    if stdlib.is_dir() {
+        collect_files(&stdlib, &mut files)?;
+    }
+
+    // Sort by the `/`-separated path string (rather than by path components), since that's the
+    // order the lookups binary-search in.
+    let mut entries = files
+        .iter()
+        .map(|file| {
+            let relative = file
+                .strip_prefix(&typeshed)
+                .unwrap()
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            (relative, file)
+        })
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    let mut code = String::from("pub(crate) static TYPESHED_FILES: &[(&str, &str)] = &[\n");
+    for (relative, file) in &entries {
+        writeln!(
+            code,
+            "    ({relative:?}, include_str!({:?})),",
+            file.display()
+        )
+        .unwrap();
+    }
+    code.push_str("];\n");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(out_dir.join("typeshed.rs"), code)
+}
+
+fn collect_files(directory: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path.extension().is_some_and(|extension| extension == "pyi")
+            || path.file_name().is_some_and(|name| name == "VERSIONS")
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,7 @@
+//! Files that are embedded into the pyrogen binary.
+//!
+//! Currently this is limited to the standard library stubs of
+//! [typeshed](https://github.com/python/typeshed), which are synced into `vendor/typeshed` by
+//! `scripts/sync_typeshed.py`.
+
+pub mod typeshed;
@@ -0,0 +1,168 @@
+//! The vendored typeshed standard library stubs.
+//!
+//! Paths are relative to the typeshed root and always use `/` as separator, e.g.
+//! `stdlib/os/__init__.pyi`.
+
+use std::collections::HashMap;
+
+include!(concat!(env!("OUT_DIR"), "/typeshed.rs"));
+
+/// A read-only view of a set of vendored files.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct VendoredFiles {
+    /// `(path, contents)` pairs, sorted by path.
+    files: &'static [(&'static str, &'static str)],
+}
+
+impl VendoredFiles {
+    /// The typeshed stubs that were embedded into this build.
+    pub fn typeshed() -> Self {
+        Self {
+            files: TYPESHED_FILES,
+        }
+    }
+
+    /// A custom set of files, e.g. for tests. `files` must be sorted by path.
+    pub fn from_sorted(files: &'static [(&'static str, &'static str)]) -> Self {
+        debug_assert!(files.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        Self { files }
+    }
+
+    /// Returns the contents of the file at `path`.
+    pub fn file(&self, path: &str) -> Option<&'static str> {
+        self.files
+            .binary_search_by(|(file, _)| (*file).cmp(path))
+            .ok()
+            .map(|index| self.files[index].1)
+    }
+
+    /// Returns `true` if `path` is a directory containing at least one file.
+    pub fn is_directory(&self, path: &str) -> bool {
+        let path = path.trim_end_matches('/');
+        self.files.iter().any(|(file, _)| {
+            file.strip_prefix(path)
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The paths of all files, sorted.
+    pub fn paths(&self) -> impl Iterator<Item = &'static str> {
+        self.files.iter().map(|(path, _)| *path)
+    }
+
+    /// The module versions recorded in `stdlib/VERSIONS`, or an empty table if there's none.
+    pub fn versions(&self) -> TypeshedVersions {
+        self.file("stdlib/VERSIONS")
+            .and_then(|contents| TypeshedVersions::parse(contents).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// A `(major, minor)` Python version as used in typeshed's `VERSIONS` file.
+pub type Version = (u8, u8);
+
+/// The Python versions in which each standard library module is available, as recorded in
+/// typeshed's `stdlib/VERSIONS` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeshedVersions(HashMap<String, (Version, Option<Version>)>);
+
+impl TypeshedVersions {
+    /// Parse the contents of a `VERSIONS` file.
+    ///
+    /// Each line has the form `module: 3.7-` or `module: 3.7-3.9`; `#` starts a comment.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut versions = HashMap::new();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || format!("Invalid line {} in `VERSIONS`: `{line}`", index + 1);
+            let (module, range) = line.split_once(':').ok_or_else(invalid)?;
+            let (minimum, maximum) = range.trim().split_once('-').ok_or_else(invalid)?;
+            let minimum = parse_version(minimum).ok_or_else(invalid)?;
+            let maximum = if maximum.trim().is_empty() {
+                None
+            } else {
+                Some(parse_version(maximum).ok_or_else(invalid)?)
+            };
+            versions.insert(module.trim().to_string(), (minimum, maximum));
+        }
+        Ok(Self(versions))
+    }
+
+    /// Returns `true` if the module `name` (e.g. `asyncio.taskgroups`) exists in `version`.
+    ///
+    /// The most specific entry wins; modules without any entry are assumed to be available.
+    pub fn is_available(&self, name: &str, version: Version) -> bool {
+        let mut module = name;
+        loop {
+            if let Some((minimum, maximum)) = self.0.get(module) {
+                return *minimum <= version && maximum.map_or(true, |maximum| version <= maximum);
+            }
+            match module.rsplit_once('.') {
+                Some((parent, _)) => module = parent,
+                None => return true,
+            }
+        }
+    }
+}
+
+fn parse_version(version: &str) -> Option<Version> {
+    let (major, minor) = version.trim().split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TypeshedVersions, VendoredFiles};
+
+    #[test]
+    fn vendored_files() {
+        let files = VendoredFiles::from_sorted(&[
+            ("stdlib/VERSIONS", "json: 3.0-\n"),
+            (
+                "stdlib/json/__init__.pyi",
+                "def dumps(obj: object) -> str: ...\n",
+            ),
+            ("stdlib/re.pyi", ""),
+        ]);
+        assert!(files.file("stdlib/re.pyi").is_some());
+        assert!(files.file("stdlib/os.pyi").is_none());
+        assert!(files.is_directory("stdlib/json"));
+        assert!(!files.is_directory("stdlib/js"));
+        assert!(!files.is_directory("stdlib/re.pyi"));
+        assert!(files.versions().is_available("json", (3, 8)));
+    }
+
+    #[test]
+    fn versions() {
+        let versions = TypeshedVersions::parse(
+            "\
+# Comment
+asyncio: 3.4-
+asyncio.taskgroups: 3.11-
+distutils: 3.0-3.11  # Removed in 3.12
+",
+        )
+        .unwrap();
+
+        assert!(versions.is_available("asyncio", (3, 8)));
+        assert!(versions.is_available("asyncio.events", (3, 8)));
+        assert!(!versions.is_available("asyncio.taskgroups", (3, 10)));
+        assert!(versions.is_available("asyncio.taskgroups", (3, 11)));
+        assert!(versions.is_available("distutils.core", (3, 11)));
+        assert!(!versions.is_available("distutils", (3, 12)));
+        assert!(versions.is_available("unknown", (3, 12)));
+    }
+
+    #[test]
+    fn invalid_versions() {
+        assert!(TypeshedVersions::parse("asyncio 3.4-").is_err());
+        assert!(TypeshedVersions::parse("asyncio: 3-").is_err());
+    }
+}
@@ -0,0 +1,267 @@
+//! The documents that the playground keeps open, so that an edit sends only the changed text and
+//! an unchanged document isn't checked again.
+//!
+//! A document keeps its tokens and syntax tree between edits. An edit inside a single token only
+//! re-lexes that token, and keeps the syntax tree if the token is a comment; other edits re-lex
+//! the whole document.
+
+use std::convert::Infallible;
+
+use rustpython_ast::fold::Fold;
+use rustpython_ast::{Stmt, Suite};
+use rustpython_parser::lexer::{lex, lex_starts_at, LexResult};
+use rustpython_parser::text_size::{TextLen, TextRange, TextSize};
+use rustpython_parser::{Mode, Tok};
+use serde::{Deserialize, Serialize};
+
+use pyrogen_source_file::{ColumnEncoding, Locator, SourceLocation};
+
+use crate::ExpandedMessage;
+
+/// The replacement of the text between two locations of a document.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct TextEdit {
+    pub location: SourceLocation,
+    pub end_location: SourceLocation,
+    pub content: String,
+}
+
+pub(crate) struct Document {
+    pub(crate) text: String,
+    /// The tokens of `text`.
+    pub(crate) tokens: Vec<LexResult>,
+    /// The module parsed from `tokens`, until an edit changes it. `None` if it has to be parsed
+    /// again, or doesn't parse.
+    pub(crate) module: Option<Suite>,
+    /// The messages of the last check, until the document is changed.
+    pub(crate) messages: Option<Vec<ExpandedMessage>>,
+}
+
+impl Document {
+    pub(crate) fn new(text: String) -> Self {
+        Self {
+            tokens: lex(&text, Mode::Module).collect(),
+            text,
+            module: None,
+            messages: None,
+        }
+    }
+
+    /// Apply `edits` in order, with the columns of their locations counted in `encoding`.
+    pub(crate) fn apply(&mut self, edits: &[TextEdit], encoding: ColumnEncoding) {
+        let mut relex = false;
+        for edit in edits {
+            let locator = Locator::new(&self.text);
+            let source_code = locator.to_source_code();
+            let start = source_code.offset(edit.location.clone(), encoding);
+            let end = source_code.offset(edit.end_location.clone(), encoding);
+            let range = TextRange::new(start, end.max(start));
+            self.text
+                .replace_range(std::ops::Range::<usize>::from(range), edit.content.as_str());
+            // Once the tokens are out of date, they're lexed again after the last edit.
+            relex = relex || !self.relex_token(range, edit.content.text_len());
+        }
+        if relex {
+            self.tokens = lex(&self.text, Mode::Module).collect();
+            self.module = None;
+        }
+        if !edits.is_empty() {
+            self.messages = None;
+        }
+    }
+
+    /// Update the tokens (and the module) for the replacement of `range` by `len` bytes of text,
+    /// if the edit is strictly inside a single token and the new text of the token still lexes as
+    /// a single token of the same kind. Returns `false` if the whole document has to be lexed
+    /// again instead.
+    ///
+    /// Since the edit leaves the first and the last character of the token alone, it can't merge
+    /// the token with its neighbors or change how the rest of the document is lexed.
+    fn relex_token(&mut self, range: TextRange, len: TextSize) -> bool {
+        let Some((index, old_kind, old_range)) =
+            self.tokens.iter().enumerate().find_map(|(index, token)| {
+                let (kind, token_range) = token.as_ref().ok()?;
+                (token_range.start() < range.start() && range.end() < token_range.end())
+                    .then_some((index, std::mem::discriminant(kind), *token_range))
+            })
+        else {
+            return false;
+        };
+        let shift = |offset: TextSize| offset - range.len() + len;
+        let new_range = TextRange::new(old_range.start(), shift(old_range.end()));
+
+        let Some(Ok((token, token_range))) =
+            lex_starts_at(&self.text[new_range], Mode::Module, new_range.start()).next()
+        else {
+            return false;
+        };
+        if token_range != new_range || std::mem::discriminant(&token) != old_kind {
+            return false;
+        }
+        // Whether a soft keyword is a keyword depends on the tokens that follow it.
+        if matches!(&token, Tok::Name { name } if matches!(name.as_str(), "match" | "case" | "type"))
+        {
+            return false;
+        }
+
+        // The syntax tree doesn't contain comments, so only its ranges change.
+        self.module = if matches!(token, Tok::Comment(_)) {
+            self.module.take().map(|module| {
+                let mut shift_ranges = ShiftRanges {
+                    after: old_range.end(),
+                    removed: range.len(),
+                    added: len,
+                };
+                module
+                    .into_iter()
+                    .map(|stmt| shift_ranges.fold_stmt(stmt))
+                    .collect::<Result<Vec<Stmt>, Infallible>>()
+                    .unwrap_or_else(|never| match never {})
+            })
+        } else {
+            None
+        };
+
+        self.tokens[index] = Ok((token, token_range));
+        for token in &mut self.tokens[index + 1..] {
+            match token {
+                Ok((_, token_range)) => {
+                    *token_range =
+                        TextRange::new(shift(token_range.start()), shift(token_range.end()));
+                }
+                Err(error) => error.location = shift(error.location),
+            }
+        }
+        true
+    }
+}
+
+/// Moves the ranges that end after an edit of a comment by the change in length of the comment.
+struct ShiftRanges {
+    /// The end of the comment before the edit.
+    after: TextSize,
+    removed: TextSize,
+    added: TextSize,
+}
+
+impl ShiftRanges {
+    fn shift(&self, offset: TextSize) -> TextSize {
+        if offset >= self.after {
+            offset - self.removed + self.added
+        } else {
+            offset
+        }
+    }
+}
+
+impl Fold<TextRange> for ShiftRanges {
+    type TargetU = TextRange;
+    type Error = Infallible;
+    type UserContext = ();
+
+    fn will_map_user(&mut self, _user: &TextRange) -> Self::UserContext {}
+
+    fn map_user(
+        &mut self,
+        user: TextRange,
+        _context: Self::UserContext,
+    ) -> Result<Self::TargetU, Self::Error> {
+        Ok(TextRange::new(
+            self.shift(user.start()),
+            self.shift(user.end()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::lexer::{lex, LexResult};
+    use rustpython_parser::{parse_tokens, Mode};
+
+    use pyrogen_source_file::{ColumnEncoding, OneIndexed, SourceLocation};
+
+    use super::{Document, TextEdit};
+
+    fn location(row: usize, column: usize) -> SourceLocation {
+        SourceLocation {
+            row: OneIndexed::new(row).unwrap(),
+            column: OneIndexed::new(column).unwrap(),
+        }
+    }
+
+    #[test]
+    fn document_with_module(text: &str) -> Document {
+        let mut document = Document::new(text.to_string());
+        document.module = Some(
+            parse_tokens(document.tokens.clone(), Mode::Module, "<filename>")
+                .unwrap()
+                .expect_module()
+                .body,
+        );
+        document
+    }
+
+    fn edit(row: usize, column: usize, end_column: usize, content: &str) -> TextEdit {
+        TextEdit {
+            location: location(row, column),
+            end_location: location(row, end_column),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn apply_edits() {
+        let mut document = Document::new("x = 'ä'\ny = 2\n".to_string());
+        document.messages = Some(vec![]);
+        document.apply(
+            &[
+                // Replace the `ä`, which is a single UTF-16 code unit.
+                TextEdit {
+                    location: location(1, 6),
+                    end_location: location(1, 7),
+                    content: "bc".to_string(),
+                },
+                // Insert a line, at a location in the text after the first edit.
+                TextEdit {
+                    location: location(2, 1),
+                    end_location: location(2, 1),
+                    content: "z = 3\n".to_string(),
+                },
+            ],
+            ColumnEncoding::Utf16,
+        );
+        assert_eq!(document.text, "x = 'bc'\nz = 3\ny = 2\n");
+        assert!(document.messages.is_none());
+    }
+    #[test]
+    fn edit_in_comment_keeps_module() {
+        let mut document = document_with_module("x = 1  # one.\ny = x\n");
+        document.apply(&[edit(1, 10, 13, "three")], ColumnEncoding::Utf8);
+
+        let expected = document_with_module("x = 1  # three.\ny = x\n");
+        assert_eq!(document.tokens, expected.tokens);
+        assert_eq!(document.module, expected.module);
+    }
+
+    #[test]
+    fn edit_in_name_relexes_token() {
+        let mut document = document_with_module("value = 1\nprint(value)\n");
+        document.apply(&[edit(1, 2, 5, "ariabl")], ColumnEncoding::Utf8);
+
+        assert_eq!(document.text, "variable = 1\nprint(value)\n");
+        let tokens: Vec<LexResult> = lex(&document.text, Mode::Module).collect();
+        assert_eq!(document.tokens, tokens);
+        assert!(document.module.is_none());
+    }
+
+    #[test]
+    fn edit_across_tokens_relexes_document() {
+        let mut document = document_with_module("x = 1\ny = 2\n");
+        document.apply(&[edit(1, 3, 6, "= 10")], ColumnEncoding::Utf8);
+
+        assert_eq!(document.text, "x = 10\ny = 2\n");
+        let tokens: Vec<LexResult> = lex(&document.text, Mode::Module).collect();
+        assert_eq!(document.tokens, tokens);
+        assert!(document.module.is_none());
+    }
+}
@@ -0,0 +1,72 @@
+//! The fixes of the messages, as edits that the playground can apply with a click.
+
+use rustpython_parser::ast::Ranged;
+use serde::{Deserialize, Serialize};
+
+use pyrogen_checker::fix::Fix;
+use pyrogen_source_file::{ColumnEncoding, SourceCode};
+
+use crate::tokens::Range;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct ExpandedFix {
+    pub title: String,
+    pub edits: Vec<ExpandedEdit>,
+}
+
+/// The replacement of the text in `range` by `content`, which is empty for a deletion.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct ExpandedEdit {
+    pub range: Range,
+    pub content: String,
+}
+
+impl ExpandedFix {
+    pub fn new(fix: &Fix, source_code: &SourceCode, encoding: ColumnEncoding) -> Self {
+        Self {
+            title: fix.title().to_string(),
+            edits: fix
+                .edits()
+                .iter()
+                .map(|edit| ExpandedEdit {
+                    range: Range::new(edit.range(), source_code, encoding),
+                    content: edit.content().unwrap_or_default().to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustpython_parser::text_size::{TextRange, TextSize};
+
+    use pyrogen_checker::fix::{Edit, Fix};
+    use pyrogen_source_file::{ColumnEncoding, Locator};
+
+    use super::ExpandedFix;
+
+    #[test]
+    fn edit_ranges() {
+        let locator = Locator::new("x = 'ä'\nimport os\n");
+        let fix = Fix::new(
+            "Remove unused import",
+            [
+                Edit::range_deletion(TextRange::new(TextSize::new(9), TextSize::new(19))),
+                Edit::range_replacement(
+                    "'a'".to_string(),
+                    TextRange::new(TextSize::new(4), TextSize::new(8)),
+                ),
+            ],
+        );
+        let expanded = ExpandedFix::new(&fix, &locator.to_source_code(), ColumnEncoding::Utf16);
+        assert_eq!(expanded.title, "Remove unused import");
+        // The edits are sorted by their start.
+        assert_eq!(expanded.edits[0].content, "'a'");
+        assert_eq!(expanded.edits[0].range.location.column.get(), 5);
+        assert_eq!(expanded.edits[0].range.end_location.column.get(), 8);
+        assert_eq!(expanded.edits[1].content, "");
+        assert_eq!(expanded.edits[1].range.location.row.get(), 2);
+        assert_eq!(expanded.edits[1].range.end_location.row.get(), 3);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::Path;
 
 use js_sys::Error;
@@ -9,19 +10,32 @@ use rustpython_ast::{Mod, Ranged};
 use rustpython_parser::lexer::LexResult;
 use rustpython_parser::{parse_tokens, Mode};
 
-use pyrogen_checker::checker::{check_path, CheckerResult};
+use pyrogen_checker::cancellation::CancellationToken;
+use pyrogen_checker::checker::{check_parsed, check_path, lint_fix, CheckerResult, FixerResult};
 use pyrogen_checker::directives;
-use pyrogen_checker::settings::types::PythonVersion;
-use pyrogen_checker::settings::{flags, DEFAULT_ERRORS, DEFAULT_WARNINGS};
+use pyrogen_checker::hover::type_at_position;
+use pyrogen_checker::registry::Diagnostic;
+use pyrogen_checker::settings::types::{PythonPlatform, PythonVersion};
+use pyrogen_checker::settings::{DEFAULT_ERRORS, DEFAULT_WARNINGS};
 use pyrogen_checker::source_kind::SourceKind;
 use pyrogen_python_ast::{AsMode, PySourceType};
 use pyrogen_python_index::{CommentRangesBuilder, Indexer};
 use pyrogen_python_trivia::CommentRanges;
-use pyrogen_source_file::{Locator, SourceLocation};
+use pyrogen_source_file::{ColumnEncoding, Locator, OneIndexed, SourceLocation};
 use pyrogen_workspace::configuration::Configuration;
 use pyrogen_workspace::options::Options;
 use pyrogen_workspace::Settings;
 
+use crate::documents::{Document, TextEdit};
+use crate::fixes::ExpandedFix;
+use crate::syntax_tree::syntax_tree;
+use crate::tokens::{tokens, Range};
+
+mod documents;
+mod fixes;
+mod syntax_tree;
+mod tokens;
+
 #[wasm_bindgen(typescript_custom_section)]
 const TYPES: &'static str = r#"
 export interface Diagnostic {
@@ -35,7 +49,75 @@ export interface Diagnostic {
         row: number;
         column: number;
     };
-    kind: "error" | "warning";
+    kind: "error" | "warning" | "info" | "hint";
+    fix: Fix | null;
+};
+
+export interface Fix {
+    title: string;
+    edits: {
+        range: Range;
+        content: string;
+    }[];
+};
+
+export interface SyntaxNode {
+    kind: string;
+    value: string | null;
+    location: {
+        row: number;
+        column: number;
+    };
+    end_location: {
+        row: number;
+        column: number;
+    };
+    children: SyntaxNode[];
+};
+
+export interface Range {
+    location: {
+        row: number;
+        column: number;
+    };
+    end_location: {
+        row: number;
+        column: number;
+    };
+};
+
+export interface Token {
+    kind: string;
+    range: Range;
+    value: string;
+};
+
+export interface TextEdit {
+    location: {
+        row: number;
+        column: number;
+    };
+    end_location: {
+        row: number;
+        column: number;
+    };
+    content: string;
+};
+
+export interface Hover {
+    name: string;
+    kind: "function" | "method" | "class" | "variable";
+    type: string;
+    docstring: string | null;
+    markdown: string;
+    location: {
+        row: number;
+        column: number;
+    };
+    end_location: {
+        row: number;
+        column: number;
+    };
 };
 "#;
 
@@ -46,6 +128,19 @@ pub struct ExpandedMessage {
     pub location: SourceLocation,
     pub end_location: SourceLocation,
     pub kind: MessageKind,
+    pub fix: Option<ExpandedFix>,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct ExpandedHover {
+    pub name: String,
+    pub kind: String,
+    #[serde(rename = "type")]
+    pub inferred_type: String,
+    pub docstring: Option<String>,
+    pub markdown: String,
+    pub location: SourceLocation,
+    pub end_location: SourceLocation,
 }
 
 #[wasm_bindgen(start)]
@@ -67,6 +162,8 @@ pub fn run() {
 #[wasm_bindgen]
 pub struct Workspace {
     settings: Settings,
+    /// The open documents by their IDs.
+    documents: HashMap<String, Document>,
 }
 
 #[wasm_bindgen]
@@ -77,14 +174,22 @@ impl Workspace {
 
     #[wasm_bindgen(constructor)]
     pub fn new(options: JsValue) -> Result<Workspace, Error> {
-        let options: Options = serde_wasm_bindgen::from_value(options).map_err(into_error)?;
-        let configuration =
-            Configuration::from_options(options, Path::new(".")).map_err(into_error)?;
-        let settings = configuration
-            .into_settings(Path::new("."))
-            .map_err(into_error)?;
+        Ok(Workspace {
+            settings: settings_from_options(options)?,
+            documents: HashMap::new(),
+        })
+    }
 
-        Ok(Workspace { settings })
+    /// Replaces the settings with the ones of `options`, keeping the open documents. The
+    /// locations of the edits passed to `updateDocument` afterwards are counted in the new column
+    /// encoding.
+    #[wasm_bindgen(js_name = updateSettings)]
+    pub fn update_settings(&mut self, options: JsValue) -> Result<(), Error> {
+        self.settings = settings_from_options(options)?;
+        for document in self.documents.values_mut() {
+            document.messages = None;
+        }
+        Ok(())
     }
 
     #[wasm_bindgen(js_name = defaultSettings)]
@@ -94,7 +199,14 @@ impl Workspace {
             ignore: Some(Vec::default()),
             error: Some(DEFAULT_ERRORS.to_vec()),
             warning: Some(DEFAULT_WARNINGS.to_vec()),
+            info: Some(Vec::default()),
+            hint: Some(Vec::default()),
             target_version: Some(PythonVersion::default()),
+            platform: Some(PythonPlatform::default()),
+            disallow_untyped_imports: Some(false),
+            column_encoding: Some(ColumnEncoding::default()),
+            preview: Some(false),
+            respect_type_ignore: Some(true),
             // Ignore a bunch of options that don't make sense in a single-file editor.
             cache_dir: None,
             exclude: None,
@@ -105,6 +217,7 @@ impl Workspace {
             include: None,
             namespace_packages: None,
             per_file_ignores: None,
+            python_path: None,
             respect_gitignore: None,
             src: None,
             ..Options::default()
@@ -113,6 +226,185 @@ impl Workspace {
     }
 
     pub fn check(&self, contents: &str) -> Result<JsValue, Error> {
+        let messages = Self::messages(contents, &self.settings)?;
+
+        serde_wasm_bindgen::to_value(&messages).map_err(into_error)
+    }
+
+    /// Checks the content with the settings of `options` instead of the workspace's, which are
+    /// left unchanged.
+    #[wasm_bindgen(js_name = checkWithOptions)]
+    pub fn check_with_options(&self, contents: &str, options: JsValue) -> Result<JsValue, Error> {
+        let messages = Self::messages(contents, &settings_from_options(options)?)?;
+
+        serde_wasm_bindgen::to_value(&messages).map_err(into_error)
+    }
+
+    /// Applies the fixes of the messages of the content, and checks and fixes the result again
+    /// until nothing is left to fix. Returns the fixed content.
+    #[wasm_bindgen(js_name = applyFixes)]
+    pub fn apply_fixes(&self, contents: &str) -> Result<String, Error> {
+        let FixerResult { transformed, .. } = lint_fix(
+            Path::new("<filename>"),
+            None,
+            &self.settings.checker,
+            self.settings.respect_type_ignore.into(),
+            &SourceKind::new(contents.to_string()),
+            PySourceType::default(),
+            None,
+            &CancellationToken::default(),
+        )
+        .map_err(into_error)?;
+
+        Ok(transformed.source_code().to_string())
+    }
+
+    /// Opens the document `id` with the content `text`, replacing the document if it's open
+    /// already.
+    #[wasm_bindgen(js_name = openDocument)]
+    pub fn open_document(&mut self, id: String, text: String) {
+        self.documents.insert(id, Document::new(text));
+    }
+
+    /// Applies the `TextEdit`s to the open document `id`, in order, each to the content left by
+    /// the ones before it.
+    #[wasm_bindgen(js_name = updateDocument)]
+    pub fn update_document(&mut self, id: &str, edits: JsValue) -> Result<(), Error> {
+        let edits: Vec<TextEdit> = serde_wasm_bindgen::from_value(edits).map_err(into_error)?;
+        let encoding = self.settings.column_encoding;
+        self.document_mut(id)?.apply(&edits, encoding);
+        Ok(())
+    }
+
+    /// Checks the open document `id` with the tokens and the syntax tree kept from its last
+    /// check. The messages are kept until the document is changed, so checking it again without
+    /// changes is free.
+    #[wasm_bindgen(js_name = checkDocument)]
+    pub fn check_document(&mut self, id: &str) -> Result<JsValue, Error> {
+        let settings = &self.settings;
+        let document = self
+            .documents
+            .get_mut(id)
+            .ok_or_else(|| unknown_document(id))?;
+        if document.messages.is_none() {
+            let source_type = PySourceType::default();
+            // The checks change the syntax tree, so they get a copy of the kept one.
+            let parsed = if let Some(module) = &document.module {
+                Ok(module.clone())
+            } else {
+                let parsed =
+                    parse_tokens(document.tokens.clone(), source_type.as_mode(), "<filename>")
+                        .map(|python_ast| python_ast.expect_module().body);
+                document.module = parsed.as_ref().ok().cloned();
+                parsed
+            };
+
+            let locator = Locator::new(&document.text);
+            let indexer = Indexer::from_tokens(&document.tokens, &locator);
+            let directives =
+                directives::extract_noqa_line_for(&document.tokens, &locator, &indexer);
+
+            let CheckerResult {
+                data: (diagnostics, _imports),
+                ..
+            } = check_parsed(
+                Path::new("<filename>"),
+                None,
+                &document.tokens,
+                parsed,
+                &locator,
+                &indexer,
+                &directives,
+                &settings.checker,
+                settings.respect_type_ignore.into(),
+                source_type,
+                None,
+                &CancellationToken::default(),
+            )
+            .map_err(into_error)?;
+
+            document.messages = Some(Self::expand_messages(diagnostics, &locator, settings));
+        }
+
+        serde_wasm_bindgen::to_value(&document.messages).map_err(into_error)
+    }
+
+    /// Closes the document `id`.
+    #[wasm_bindgen(js_name = closeDocument)]
+    pub fn close_document(&mut self, id: &str) {
+        self.documents.remove(id);
+    }
+
+    /// The type and docstring of the name at the one-indexed `row` and `column`, with the column
+    /// counted in the configured column encoding. Returns `null` if there's no name there or its
+    /// type isn't known.
+    pub fn hover(&self, contents: &str, row: usize, column: usize) -> Result<JsValue, Error> {
+        let locator = Locator::new(contents);
+        let source_code = locator.to_source_code();
+        let location = SourceLocation {
+            row: OneIndexed::new(row).unwrap_or(OneIndexed::MIN),
+            column: OneIndexed::new(column).unwrap_or(OneIndexed::MIN),
+        };
+        let offset = source_code.offset(location, self.settings.column_encoding);
+
+        let hover = type_at_position(contents, PySourceType::default(), offset).map(|hover| {
+            ExpandedHover {
+                markdown: hover.markdown(),
+                location: source_code.source_location_with_encoding(
+                    hover.range.start(),
+                    self.settings.column_encoding,
+                ),
+                end_location: source_code.source_location_with_encoding(
+                    hover.range.end(),
+                    self.settings.column_encoding,
+                ),
+                name: hover.name,
+                kind: hover.kind.to_string(),
+                inferred_type: hover.inferred_type,
+                docstring: hover.docstring,
+            }
+        });
+
+        serde_wasm_bindgen::to_value(&hover).map_err(into_error)
+    }
+
+    /// Parses the content and returns the root `SyntaxNode` of its AST.
+    pub fn parse(&self, contents: &str) -> Result<JsValue, Error> {
+        let tree = syntax_tree(contents, self.settings.column_encoding).map_err(into_error)?;
+
+        serde_wasm_bindgen::to_value(&tree).map_err(into_error)
+    }
+
+    /// Lexes the content and returns its `Token`s.
+    pub fn tokens(&self, contents: &str) -> Result<JsValue, Error> {
+        let locator = Locator::new(contents);
+        let tokens = tokens(
+            contents,
+            &locator.to_source_code(),
+            self.settings.column_encoding,
+        );
+
+        serde_wasm_bindgen::to_value(&tokens).map_err(into_error)
+    }
+
+    /// Parses the content and returns the `Range`s of its comments.
+    #[wasm_bindgen(js_name = commentRanges)]
+    pub fn comment_ranges(&self, contents: &str) -> Result<JsValue, Error> {
+        let parsed = ParsedModule::from_source(contents)?;
+        let locator = Locator::new(parsed.source_code);
+        let source_code = locator.to_source_code();
+        let ranges: Vec<_> = parsed
+            .comment_ranges
+            .iter()
+            .map(|range| Range::new(*range, &source_code, self.settings.column_encoding))
+            .collect();
+
+        serde_wasm_bindgen::to_value(&ranges).map_err(into_error)
+    }
+}
+
+impl Workspace {
+    fn messages(contents: &str, settings: &Settings) -> Result<Vec<ExpandedMessage>, Error> {
         let source_type = PySourceType::default();
 
         // TODO(dhruvmanila): Support Jupyter Notebooks
@@ -143,19 +435,33 @@ impl Workspace {
             // &stylist,
             &indexer,
             &directives,
-            &self.settings.checker,
-            flags::TypeIgnore::Enabled,
+            &settings.checker,
+            settings.respect_type_ignore.into(),
             &source_kind,
             source_type,
-        );
+            None,
+            &CancellationToken::default(),
+        )
+        .map_err(into_error)?;
 
+        Ok(Self::expand_messages(diagnostics, &locator, settings))
+    }
+
+    /// The `ExpandedMessage`s of the `diagnostics` of the content of `locator`.
+    fn expand_messages(
+        diagnostics: Vec<Diagnostic>,
+        locator: &Locator,
+        settings: &Settings,
+    ) -> Vec<ExpandedMessage> {
         let source_code = locator.to_source_code();
 
-        let messages: Vec<ExpandedMessage> = diagnostics
+        diagnostics
             .into_iter()
             .map(|message| {
-                let start_location = source_code.source_location(message.start());
-                let end_location = source_code.source_location(message.end());
+                let start_location = source_code
+                    .source_location_with_encoding(message.start(), settings.column_encoding);
+                let end_location = source_code
+                    .source_location_with_encoding(message.end(), settings.column_encoding);
                 let code = message.kind.error_code;
 
                 ExpandedMessage {
@@ -163,30 +469,34 @@ impl Workspace {
                     message: message.kind.body,
                     location: start_location,
                     end_location,
-                    kind: if self.settings.checker.table.is_warning(code) {
-                        MessageKind::Warning
-                    } else {
-                        MessageKind::Error
-                    },
+                    kind: settings.checker.table.kind(code),
+                    fix: message
+                        .fix
+                        .as_ref()
+                        .map(|fix| ExpandedFix::new(fix, &source_code, settings.column_encoding)),
                 }
             })
-            .collect();
-
-        serde_wasm_bindgen::to_value(&messages).map_err(into_error)
+            .collect()
     }
 
-    /// Parses the content and returns its AST
-    pub fn parse(&self, contents: &str) -> Result<String, Error> {
-        let parsed = rustpython_parser::parse(contents, Mode::Module, ".").map_err(into_error)?;
-
-        Ok(format!("{parsed:#?}"))
+    fn document_mut(&mut self, id: &str) -> Result<&mut Document, Error> {
+        self.documents
+            .get_mut(id)
+            .ok_or_else(|| unknown_document(id))
     }
+}
 
-    pub fn tokens(&self, contents: &str) -> Result<String, Error> {
-        let tokens: Vec<_> = rustpython_parser::lexer::lex(contents, Mode::Module).collect();
+/// The settings of the `Options` object `options`.
+fn settings_from_options(options: JsValue) -> Result<Settings, Error> {
+    let options: Options = serde_wasm_bindgen::from_value(options).map_err(into_error)?;
+    let configuration = Configuration::from_options(options, Path::new(".")).map_err(into_error)?;
+    configuration
+        .into_settings(Path::new("."))
+        .map_err(into_error)
+}
 
-        Ok(format!("{tokens:#?}"))
-    }
+fn unknown_document(id: &str) -> Error {
+    Error::new(&format!("No document with the ID `{id}` is open"))
 }
 
 pub(crate) fn into_error<E: std::fmt::Display>(err: E) -> Error {
@@ -0,0 +1,285 @@
+//! The syntax tree of a module as nested nodes, which the playground shows as a collapsible tree.
+
+use rustpython_ast::{
+    self as ast, Alias, Arg, Arguments, Comprehension, ExceptHandler, Expr, Keyword, MatchCase,
+    Mod, Pattern, Ranged, Stmt, TypeParam, WithItem,
+};
+use rustpython_parser::text_size::{TextRange, TextSize};
+use rustpython_parser::{Mode, ParseError};
+use serde::{Deserialize, Serialize};
+
+use pyrogen_python_ast::visitor::{self, Visitor};
+use pyrogen_source_file::{ColumnEncoding, Locator, SourceCode, SourceLocation};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct SyntaxNode {
+    /// The kind of the node, like `FunctionDef` or `Name`.
+    pub kind: String,
+    /// The identifier or literal of the node, like the name of a `Name` or the source of a
+    /// `Constant`, if it has one.
+    pub value: Option<String>,
+    pub location: SourceLocation,
+    pub end_location: SourceLocation,
+    pub children: Vec<SyntaxNode>,
+}
+
+/// Parse `contents` and return the root `Module` node of its syntax tree, with the columns of the
+/// locations counted in `encoding`.
+pub fn syntax_tree(contents: &str, encoding: ColumnEncoding) -> Result<SyntaxNode, ParseError> {
+    let parsed = rustpython_parser::parse(contents, Mode::Module, ".")?;
+    let locator = Locator::new(contents);
+    let mut builder = TreeBuilder {
+        contents,
+        source_code: locator.to_source_code(),
+        encoding,
+        stack: vec![],
+    };
+    let range = TextRange::up_to(TextSize::of(contents));
+    builder.enter("Module", None, range);
+    if let Mod::Module(module) = &parsed {
+        builder.visit_body(&module.body);
+    }
+    Ok(builder
+        .stack
+        .pop()
+        .expect("the module node is on the stack"))
+}
+
+struct TreeBuilder<'a> {
+    contents: &'a str,
+    source_code: SourceCode<'a, 'a>,
+    encoding: ColumnEncoding,
+    /// The nodes that are being visited, from the root to the innermost one.
+    stack: Vec<SyntaxNode>,
+}
+
+impl TreeBuilder<'_> {
+    fn enter(&mut self, kind: &str, value: Option<&str>, range: TextRange) {
+        self.stack.push(SyntaxNode {
+            kind: kind.to_string(),
+            value: value.map(ToString::to_string),
+            location: self
+                .source_code
+                .source_location_with_encoding(range.start(), self.encoding),
+            end_location: self
+                .source_code
+                .source_location_with_encoding(range.end(), self.encoding),
+            children: vec![],
+        });
+    }
+
+    fn exit(&mut self) {
+        let node = self.stack.pop().expect("exit without enter");
+        self.stack
+            .last_mut()
+            .expect("the module node is never exited")
+            .children
+            .push(node);
+    }
+}
+
+impl<'a> Visitor<'a> for TreeBuilder<'_> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        let (kind, value) = match stmt {
+            Stmt::FunctionDef(ast::StmtFunctionDef { name, .. }) => {
+                ("FunctionDef", Some(name.as_str()))
+            }
+            Stmt::AsyncFunctionDef(ast::StmtAsyncFunctionDef { name, .. }) => {
+                ("AsyncFunctionDef", Some(name.as_str()))
+            }
+            Stmt::ClassDef(ast::StmtClassDef { name, .. }) => ("ClassDef", Some(name.as_str())),
+            Stmt::Return(_) => ("Return", None),
+            Stmt::Delete(_) => ("Delete", None),
+            Stmt::Assign(_) => ("Assign", None),
+            Stmt::TypeAlias(_) => ("TypeAlias", None),
+            Stmt::AugAssign(_) => ("AugAssign", None),
+            Stmt::AnnAssign(_) => ("AnnAssign", None),
+            Stmt::For(_) => ("For", None),
+            Stmt::AsyncFor(_) => ("AsyncFor", None),
+            Stmt::While(_) => ("While", None),
+            Stmt::If(_) => ("If", None),
+            Stmt::With(_) => ("With", None),
+            Stmt::AsyncWith(_) => ("AsyncWith", None),
+            Stmt::Match(_) => ("Match", None),
+            Stmt::Raise(_) => ("Raise", None),
+            Stmt::Try(_) => ("Try", None),
+            Stmt::TryStar(_) => ("TryStar", None),
+            Stmt::Assert(_) => ("Assert", None),
+            Stmt::Import(_) => ("Import", None),
+            Stmt::ImportFrom(ast::StmtImportFrom { module, .. }) => {
+                ("ImportFrom", module.as_ref().map(ast::Identifier::as_str))
+            }
+            Stmt::Global(_) => ("Global", None),
+            Stmt::Nonlocal(_) => ("Nonlocal", None),
+            Stmt::Expr(_) => ("Expr", None),
+            Stmt::Pass(_) => ("Pass", None),
+            Stmt::Break(_) => ("Break", None),
+            Stmt::Continue(_) => ("Continue", None),
+        };
+        self.enter(kind, value, stmt.range());
+        visitor::walk_stmt(self, stmt);
+        self.exit();
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        let contents = self.contents;
+        let (kind, value) = match expr {
+            Expr::BoolOp(_) => ("BoolOp", None),
+            Expr::NamedExpr(_) => ("NamedExpr", None),
+            Expr::BinOp(_) => ("BinOp", None),
+            Expr::UnaryOp(_) => ("UnaryOp", None),
+            Expr::Lambda(_) => ("Lambda", None),
+            Expr::IfExp(_) => ("IfExp", None),
+            Expr::Dict(_) => ("Dict", None),
+            Expr::Set(_) => ("Set", None),
+            Expr::ListComp(_) => ("ListComp", None),
+            Expr::SetComp(_) => ("SetComp", None),
+            Expr::DictComp(_) => ("DictComp", None),
+            Expr::GeneratorExp(_) => ("GeneratorExp", None),
+            Expr::Await(_) => ("Await", None),
+            Expr::Yield(_) => ("Yield", None),
+            Expr::YieldFrom(_) => ("YieldFrom", None),
+            Expr::Compare(_) => ("Compare", None),
+            Expr::Call(_) => ("Call", None),
+            Expr::FormattedValue(_) => ("FormattedValue", None),
+            Expr::JoinedStr(_) => ("JoinedStr", None),
+            Expr::Constant(_) => ("Constant", Some(&contents[expr.range()])),
+            Expr::Attribute(ast::ExprAttribute { attr, .. }) => ("Attribute", Some(attr.as_str())),
+            Expr::Subscript(_) => ("Subscript", None),
+            Expr::Starred(_) => ("Starred", None),
+            Expr::Name(ast::ExprName { id, .. }) => ("Name", Some(id.as_str())),
+            Expr::List(_) => ("List", None),
+            Expr::Tuple(_) => ("Tuple", None),
+            Expr::Slice(_) => ("Slice", None),
+        };
+        self.enter(kind, value, expr.range());
+        visitor::walk_expr(self, expr);
+        self.exit();
+    }
+
+    fn visit_comprehension(&mut self, comprehension: &'a Comprehension) {
+        self.enter("Comprehension", None, comprehension.range());
+        visitor::walk_comprehension(self, comprehension);
+        self.exit();
+    }
+
+    fn visit_except_handler(&mut self, except_handler: &'a ExceptHandler) {
+        let ExceptHandler::ExceptHandler(ast::ExceptHandlerExceptHandler { name, .. }) =
+            except_handler;
+        self.enter(
+            "ExceptHandler",
+            name.as_ref().map(ast::Identifier::as_str),
+            except_handler.range(),
+        );
+        visitor::walk_except_handler(self, except_handler);
+        self.exit();
+    }
+
+    fn visit_parameters(&mut self, parameters: &'a Arguments) {
+        self.enter("Arguments", None, parameters.range());
+        visitor::walk_parameters(self, parameters);
+        self.exit();
+    }
+
+    fn visit_parameter(&mut self, parameter: &'a Arg) {
+        self.enter("Arg", Some(parameter.arg.as_str()), parameter.range());
+        visitor::walk_parameter(self, parameter);
+        self.exit();
+    }
+
+    fn visit_keyword(&mut self, keyword: &'a Keyword) {
+        self.enter(
+            "Keyword",
+            keyword.arg.as_ref().map(ast::Identifier::as_str),
+            keyword.range(),
+        );
+        visitor::walk_keyword(self, keyword);
+        self.exit();
+    }
+
+    fn visit_alias(&mut self, alias: &'a Alias) {
+        self.enter("Alias", Some(alias.name.as_str()), alias.range());
+        self.exit();
+    }
+
+    fn visit_with_item(&mut self, with_item: &'a WithItem) {
+        self.enter("WithItem", None, with_item.range());
+        visitor::walk_with_item(self, with_item);
+        self.exit();
+    }
+
+    fn visit_type_param(&mut self, type_param: &'a TypeParam) {
+        let (kind, name) = match type_param {
+            TypeParam::TypeVar(ast::TypeParamTypeVar { name, .. }) => ("TypeVar", name),
+            TypeParam::ParamSpec(ast::TypeParamParamSpec { name, .. }) => ("ParamSpec", name),
+            TypeParam::TypeVarTuple(ast::TypeParamTypeVarTuple { name, .. }) => {
+                ("TypeVarTuple", name)
+            }
+        };
+        self.enter(kind, Some(name.as_str()), type_param.range());
+        visitor::walk_type_param(self, type_param);
+        self.exit();
+    }
+
+    fn visit_match_case(&mut self, match_case: &'a MatchCase) {
+        self.enter("MatchCase", None, match_case.range());
+        visitor::walk_match_case(self, match_case);
+        self.exit();
+    }
+
+    fn visit_pattern(&mut self, pattern: &'a Pattern) {
+        let contents = self.contents;
+        let (kind, value) = match pattern {
+            Pattern::MatchValue(_) => ("MatchValue", None),
+            Pattern::MatchSingleton(_) => ("MatchSingleton", Some(&contents[pattern.range()])),
+            Pattern::MatchSequence(_) => ("MatchSequence", None),
+            Pattern::MatchMapping(_) => ("MatchMapping", None),
+            Pattern::MatchClass(_) => ("MatchClass", None),
+            Pattern::MatchStar(ast::PatternMatchStar { name, .. }) => {
+                ("MatchStar", name.as_ref().map(ast::Identifier::as_str))
+            }
+            Pattern::MatchAs(ast::PatternMatchAs { name, .. }) => {
+                ("MatchAs", name.as_ref().map(ast::Identifier::as_str))
+            }
+            Pattern::MatchOr(_) => ("MatchOr", None),
+        };
+        self.enter(kind, value, pattern.range());
+        visitor::walk_pattern(self, pattern);
+        self.exit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrogen_source_file::ColumnEncoding;
+
+    use super::{syntax_tree, SyntaxNode};
+
+    fn kinds(node: &SyntaxNode) -> String {
+        let children = node.children.iter().map(kinds).collect::<Vec<_>>();
+        let label = match &node.value {
+            Some(value) => format!("{}:{value}", node.kind),
+            None => node.kind.clone(),
+        };
+        if children.is_empty() {
+            label
+        } else {
+            format!("{label}({})", children.join(" "))
+        }
+    }
+
+    #[test]
+    fn nested_nodes() {
+        let tree = syntax_tree("def f(x):\n    return x + 1\n", ColumnEncoding::Utf8).unwrap();
+        assert_eq!(
+            kinds(&tree),
+            "Module(FunctionDef:f(Arguments(Arg:x) Return(BinOp(Name:x Constant:1))))"
+        );
+        let function = &tree.children[0];
+        assert_eq!(function.location.row.get(), 1);
+        assert_eq!(function.end_location.row.get(), 2);
+        assert_eq!(function.end_location.column.get(), 17);
+
+        assert!(syntax_tree("def f(:\n", ColumnEncoding::Utf8).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+//! The tokens and comments of a module with their locations, which the playground highlights.
+
+use rustpython_parser::text_size::TextRange;
+use rustpython_parser::Mode;
+use serde::{Deserialize, Serialize};
+
+use pyrogen_source_file::{ColumnEncoding, SourceCode, SourceLocation};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Range {
+    pub location: SourceLocation,
+    pub end_location: SourceLocation,
+}
+
+impl Range {
+    pub fn new(range: TextRange, source_code: &SourceCode, encoding: ColumnEncoding) -> Self {
+        Self {
+            location: source_code.source_location_with_encoding(range.start(), encoding),
+            end_location: source_code.source_location_with_encoding(range.end(), encoding),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Token {
+    /// The kind of the token, like `Name`, `Def` or `Lpar`.
+    pub kind: String,
+    pub range: Range,
+    /// The source text of the token.
+    pub value: String,
+}
+
+/// The tokens of `contents`, with the columns of their locations counted in `encoding`. The
+/// tokens after a lexical error, like an unterminated string, are left out.
+pub fn tokens(contents: &str, source_code: &SourceCode, encoding: ColumnEncoding) -> Vec<Token> {
+    rustpython_parser::lexer::lex(contents, Mode::Module)
+        .map_while(Result::ok)
+        .map(|(token, range)| {
+            // The name of the variant, without its fields.
+            let debug = format!("{token:?}");
+            let kind = debug
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .next()
+                .unwrap_or_default();
+            Token {
+                kind: kind.to_string(),
+                range: Range::new(range, source_code, encoding),
+                value: contents[range].to_string(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrogen_source_file::{ColumnEncoding, Locator};
+
+    use super::tokens;
+
+    #[test]
+    fn kinds_and_values() {
+        let contents = "x = 'ä'  # comment\n";
+        let locator = Locator::new(contents);
+        let lexed = tokens(contents, &locator.to_source_code(), ColumnEncoding::Utf16);
+        let kinds: Vec<_> = lexed
+            .iter()
+            .map(|token| (token.kind.as_str(), token.value.as_str()))
+            .collect();
+        assert_eq!(
+            kinds[..5],
+            [
+                ("Name", "x"),
+                ("Equal", "="),
+                ("String", "'ä'"),
+                ("Comment", "# comment"),
+                ("Newline", "\n"),
+            ]
+        );
+        assert_eq!(lexed[3].range.location.column.get(), 10);
+
+        // The tokens up to a lexical error are kept.
+        let locator = Locator::new("x = 'a\n");
+        assert_eq!(
+            tokens("x = 'a\n", &locator.to_source_code(), ColumnEncoding::Utf16).len(),
+            2
+        );
+    }
+}
@@ -1,28 +1,38 @@
 use anyhow::{anyhow, Result};
 use glob::{glob, GlobError, Paths, PatternError};
+use globset::Glob;
+use once_cell::sync::OnceCell;
 use rustc_hash::FxHashMap;
 use shellexpand::LookupError;
 use std::{
     borrow::Cow,
     env::VarError,
+    fmt,
     path::{Path, PathBuf},
 };
 use strum::IntoEnumIterator;
 
-use pyrogen_cache::cache_dir;
-use pyrogen_checker::settings::types::{PythonVersion, SerializationFormat};
+use pyrogen_cache::{cache_dir, CacheInvalidation, CacheLocation};
+use pyrogen_checker::settings::types::{
+    CheckingMode, PreviewMode, PythonPlatform, PythonVersion, RuleTimeout, RuleTimeoutAction,
+    SerializationFormat, Severity,
+};
 use pyrogen_checker::{
     code_selector::Specificity,
     fs,
+    locale::Locale,
+    message::OutputTemplate,
     registry::{ErrorCode, ErrorCodeSet},
     settings::{
-        code_table::ErrorCodeTable,
+        code_table::{ErrorCodeTable, MessageKind},
         resolve_per_file_ignores,
         types::{FilePattern, FilePatternSet, PerFileIgnore},
-        CheckerSettings, DEFAULT_ERRORS, DEFAULT_WARNINGS,
+        CheckerSettings, DEFAULT_MAX_DIAGNOSTICS_PER_FILE,
     },
-    warn_user, ErrorCodeSelector,
+    ErrorCodeSelector,
 };
+use pyrogen_module_resolver::{find_site_packages, ModuleName};
+use pyrogen_source_file::{ColumnEncoding, SourceLocation};
 
 use crate::options::Options;
 use crate::settings::{FileResolverSettings, Settings, EXCLUDE, INCLUDE};
@@ -33,35 +43,195 @@ pub struct ErrorCodeSelection {
     pub extend_error: Vec<ErrorCodeSelector>,
     pub warning: Option<Vec<ErrorCodeSelector>>,
     pub extend_warning: Vec<ErrorCodeSelector>,
+    pub info: Option<Vec<ErrorCodeSelector>>,
+    pub hint: Option<Vec<ErrorCodeSelector>>,
     pub ignore: Vec<ErrorCodeSelector>,
+    /// The `severity` table, which overrides the other selectors of the selection.
+    pub severity: Vec<(ErrorCodeSelector, Severity)>,
+    /// The configuration file the selection was read from, or `None` for command-line options.
+    pub source: Option<PathBuf>,
+    /// The position of each selector that enables codes in `source`, in the order they're
+    /// written.
+    pub locations: Vec<(ErrorCodeSelector, SourceLocation)>,
+}
+
+impl ErrorCodeSelection {
+    /// The selectors that replace and those that extend the codes reported with the severity
+    /// `kind`.
+    fn level(&self, kind: MessageKind) -> (Option<&[ErrorCodeSelector]>, &[ErrorCodeSelector]) {
+        match kind {
+            MessageKind::Error => (self.error.as_deref(), &self.extend_error),
+            MessageKind::Warning => (self.warning.as_deref(), &self.extend_warning),
+            MessageKind::Info => (self.info.as_deref(), &[]),
+            MessageKind::Hint => (self.hint.as_deref(), &[]),
+        }
+    }
+
+    /// All selectors that enable codes, with any severity.
+    fn selectors(&self) -> impl Iterator<Item = &ErrorCodeSelector> {
+        MessageKind::ALL
+            .into_iter()
+            .flat_map(|kind| {
+                let (select, extend) = self.level(kind);
+                select.into_iter().flatten().chain(extend)
+            })
+            .chain(
+                self.severity
+                    .iter()
+                    .filter(|(_, severity)| *severity != Severity::Ignore)
+                    .map(|(selector, _)| selector),
+            )
+    }
+
+    /// Returns `true` if any selector of this selection enables `code`.
+    fn selects(&self, code: ErrorCode, preview: PreviewMode) -> bool {
+        self.selectors()
+            .any(|selector| selector.rules(preview).any(|rule| rule == code))
+    }
+
+    /// The position of the first selector in `source` that enables `code`, if known.
+    fn location(&self, code: ErrorCode) -> Option<SourceLocation> {
+        self.locations
+            .iter()
+            .find(|(selector, _)| selector.all_rules().any(|rule| rule == code))
+            .map(|(_, location)| location.clone())
+    }
+}
+
+/// A problem with the configuration that doesn't prevent loading it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConfigurationDiagnostic {
+    /// The configuration file containing the offending option, or `None` if it stems from
+    /// command-line options.
+    pub source: Option<PathBuf>,
+    /// The position of the offending option in `source`, if known.
+    pub location: Option<SourceLocation>,
+    pub kind: ConfigurationDiagnosticKind,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ConfigurationDiagnosticKind {
+    /// The code ends up selected both as an error and as a warning. It's treated as a warning.
+    ErrorAndWarning(ErrorCode),
+    /// The code ends up selected with several severities, including info or hint. It's reported
+    /// with the least severe one, `kind`.
+    ConflictingSeverities { code: ErrorCode, kind: MessageKind },
+    /// The code is selected and ignored by selectors of the same specificity. The ignore wins.
+    SelectedAndIgnored(ErrorCode),
+    /// The code is selected by name, but it's in preview and preview is disabled.
+    PreviewCode(ErrorCode),
+    /// The option has been renamed; its old name is still accepted for now.
+    DeprecatedOption {
+        old: &'static str,
+        new: &'static str,
+    },
+}
+
+impl fmt::Display for ConfigurationDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.source, &self.location) {
+            (Some(source), Some(location)) => write!(
+                f,
+                "{}:{}:{}: {}",
+                fs::relativize_path(source),
+                location.row,
+                location.column,
+                self.kind
+            ),
+            (Some(source), None) => write!(f, "{}: {}", fs::relativize_path(source), self.kind),
+            (None, _) => write!(f, "command line: {}", self.kind),
+        }
+    }
+}
+
+impl fmt::Display for ConfigurationDiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ErrorAndWarning(code) => write!(
+                f,
+                "`{code}` is selected both as an error and as a warning; treating it as a warning"
+            ),
+            Self::ConflictingSeverities { code, kind } => write!(
+                f,
+                "`{code}` is selected with several severities; treating it as {kind}"
+            ),
+            Self::SelectedAndIgnored(code) => {
+                write!(f, "`{code}` is both selected and ignored; ignoring it")
+            }
+            Self::PreviewCode(code) => write!(
+                f,
+                "`{code}` is in preview and only enabled with `preview = true`; ignoring it"
+            ),
+            Self::DeprecatedOption { old, new } => {
+                write!(f, "`{old}` is deprecated; use `{new}` instead")
+            }
+        }
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct Configuration {
+    pub mode: Option<CheckingMode>,
+    pub preview: Option<bool>,
     pub rule_selections: Vec<ErrorCodeSelection>,
     pub per_file_ignores: Option<Vec<PerFileIgnore>>,
     pub cache_dir: Option<PathBuf>,
+    pub cache_location: Option<CacheLocation>,
+    pub cache_invalidation: Option<CacheInvalidation>,
     pub exclude: Option<Vec<FilePattern>>,
     pub extend_exclude: Vec<FilePattern>,
     pub force_exclude: Option<bool>,
     pub output_format: Option<SerializationFormat>,
+    pub output_severity_mapping: Option<FxHashMap<MessageKind, MessageKind>>,
+    pub output_template: Option<String>,
+    pub column_encoding: Option<ColumnEncoding>,
+    pub locale: Option<Locale>,
     pub include: Option<Vec<FilePattern>>,
     pub respect_gitignore: Option<bool>,
     pub target_version: Option<PythonVersion>,
+    pub platform: Option<PythonPlatform>,
     pub namespace_packages: Option<Vec<PathBuf>>,
     pub src: Option<Vec<PathBuf>>,
+    pub python_path: Option<PathBuf>,
+    pub disallow_untyped_imports: Option<bool>,
+    pub extra_modules: Option<Vec<String>>,
+    pub rule_timeout: Option<u64>,
+    pub rule_timeout_action: Option<RuleTimeoutAction>,
+    pub max_diagnostics_per_file: Option<usize>,
+    pub dead_code_keep: Option<Vec<String>>,
+    pub fail_fast: Option<Vec<ErrorCodeSelector>>,
+    pub respect_type_ignore: Option<bool>,
+    /// The entry points declared in the `[project]` table of the `pyproject.toml`.
+    pub entry_points: Vec<String>,
+    /// The dependencies declared in the `[project]` table of the `pyproject.toml`.
+    pub declared_dependencies: Option<Vec<String>>,
+    /// Problems found while loading the options, such as deprecated option names.
+    pub load_diagnostics: Vec<ConfigurationDiagnostic>,
 }
 
 impl Configuration {
     pub fn into_settings(self, project_root: &Path) -> Result<Settings> {
         let target_version = self.target_version.unwrap_or_default();
+        let preview = self.preview();
         let rules = self.as_rule_table();
+        let diagnostics = self.diagnostics();
+        let output_format = self.output_format.unwrap_or_default();
+        let output_template = self
+            .output_template
+            .as_deref()
+            .map(str::parse::<OutputTemplate>)
+            .transpose()?;
+        if output_format == SerializationFormat::Custom && output_template.is_none() {
+            return Err(anyhow!(
+                "`output-format = \"custom\"` requires an `output-template`"
+            ));
+        }
 
         Ok(Settings {
-            cache_dir: self
-                .cache_dir
-                .clone()
-                .unwrap_or_else(|| cache_dir(project_root)),
+            cache_dir: self.cache_dir.clone().unwrap_or_else(|| {
+                cache_dir(project_root, self.cache_location.unwrap_or_default())
+            }),
+            cache_invalidation: self.cache_invalidation.unwrap_or_default(),
 
             file_resolver: FileResolverSettings {
                 exclude: FilePatternSet::try_from_iter(
@@ -85,21 +255,86 @@ impl Configuration {
                         .collect(),
                 )?,
                 target_version,
+                platform: self.platform.unwrap_or_default(),
                 namespace_packages: self.namespace_packages.unwrap_or_default(),
                 src: self.src.unwrap_or_else(|| vec![project_root.to_path_buf()]),
+                site_packages: self
+                    .python_path
+                    .or_else(|| std::env::var_os("VIRTUAL_ENV").map(PathBuf::from))
+                    .map(|python_path| find_site_packages(&python_path))
+                    .unwrap_or_default(),
+                disallow_untyped_imports: self.disallow_untyped_imports.unwrap_or(false),
+                extra_modules: self
+                    .extra_modules
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|module| {
+                        if ModuleName::new(&module).is_some() {
+                            Ok(module)
+                        } else {
+                            Err(anyhow!(
+                                "Invalid module name in `extra-modules`: `{module}`"
+                            ))
+                        }
+                    })
+                    .collect::<Result<_>>()?,
+                rule_timeout: self.rule_timeout.map(|milliseconds| RuleTimeout {
+                    milliseconds,
+                    action: self.rule_timeout_action.unwrap_or_default(),
+                }),
+                preview,
+                max_diagnostics_per_file: self
+                    .max_diagnostics_per_file
+                    .unwrap_or(DEFAULT_MAX_DIAGNOSTICS_PER_FILE),
+                dead_code_keep: self
+                    .dead_code_keep
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|pattern| {
+                        Glob::new(pattern)
+                            .map(|glob| glob.compile_matcher())
+                            .map_err(|e| anyhow!("Invalid `dead-code-keep` pattern: {e}"))
+                    })
+                    .collect::<Result<_>>()?,
+                entry_points: self.entry_points,
+                declared_dependencies: self.declared_dependencies,
+                module_resolver: OnceCell::new(),
             },
-            output_format: self.output_format.unwrap_or_default(),
+            output_format,
+            output_template,
+            column_encoding: self.column_encoding.unwrap_or_default(),
+            locale: self.locale.unwrap_or_default(),
+            fail_fast: self
+                .fail_fast
+                .iter()
+                .flatten()
+                .flat_map(|selector| selector.rules(preview))
+                .collect(),
+            respect_type_ignore: self.respect_type_ignore.unwrap_or(true),
+            output_severity_mapping: self
+                .output_severity_mapping
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+            diagnostics,
         })
     }
 
     pub fn from_options(options: Options, project_root: &Path) -> Result<Self> {
         Ok(Self {
+            mode: options.mode,
+            preview: options.preview,
             rule_selections: vec![ErrorCodeSelection {
                 error: options.error,
                 warning: options.warning,
+                info: options.info,
+                hint: options.hint,
                 ignore: options.ignore.into_iter().flatten().collect(),
+                severity: options.severity.into_iter().flatten().collect(),
                 extend_error: options.extend_error.unwrap_or_default(),
                 extend_warning: options.extend_warning.unwrap_or_default(),
+                source: None,
+                locations: vec![],
             }],
             per_file_ignores: options.per_file_ignores.map(|per_file_ignores| {
                 per_file_ignores
@@ -117,6 +352,8 @@ impl Configuration {
                 })
                 .transpose()
                 .map_err(|e| anyhow!("Invalid `cache-dir` value: {e}"))?,
+            cache_location: options.cache_location,
+            cache_invalidation: options.cache_invalidation,
             exclude: options.exclude.map(|paths| {
                 paths
                     .into_iter()
@@ -139,6 +376,10 @@ impl Configuration {
                 })
                 .unwrap_or_default(),
             output_format: options.output_format,
+            output_severity_mapping: options.output_severity_mapping,
+            output_template: options.output_template,
+            column_encoding: options.column_encoding,
+            locale: options.locale,
             force_exclude: options.force_exclude,
             include: options.include.map(|paths| {
                 paths
@@ -159,12 +400,46 @@ impl Configuration {
                 .transpose()?,
             respect_gitignore: options.respect_gitignore,
             target_version: options.target_version,
+            platform: options.platform,
+            python_path: options
+                .python_path
+                .map(|python_path| fs::normalize_path_to(python_path, project_root)),
+            disallow_untyped_imports: options.disallow_untyped_imports,
+            extra_modules: options.extra_modules,
+            rule_timeout: options.rule_timeout,
+            rule_timeout_action: options.rule_timeout_action,
+            max_diagnostics_per_file: options.max_diagnostics_per_file,
+            dead_code_keep: options.dead_code_keep,
+            fail_fast: options.fail_fast,
+            respect_type_ignore: options.respect_type_ignore,
+            entry_points: vec![],
+            declared_dependencies: None,
+            load_diagnostics: vec![],
         })
     }
 
+    /// Record `path` as the file that the rule selections of this configuration were read from,
+    /// and `locations` as the positions of their selectors in it.
+    #[must_use]
+    pub fn with_source(
+        mut self,
+        path: &Path,
+        locations: Vec<(ErrorCodeSelector, SourceLocation)>,
+    ) -> Self {
+        for selection in &mut self.rule_selections {
+            if selection.source.is_none() {
+                selection.source = Some(path.to_path_buf());
+                selection.locations = locations.clone();
+            }
+        }
+        self
+    }
+
     #[must_use]
     pub fn combine(self, config: Self) -> Self {
         Self {
+            mode: self.mode.or(config.mode),
+            preview: self.preview.or(config.preview),
             rule_selections: config
                 .rule_selections
                 .into_iter()
@@ -172,6 +447,8 @@ impl Configuration {
                 .collect(),
             per_file_ignores: self.per_file_ignores.or(config.per_file_ignores),
             cache_dir: self.cache_dir.or(config.cache_dir),
+            cache_location: self.cache_location.or(config.cache_location),
+            cache_invalidation: self.cache_invalidation.or(config.cache_invalidation),
             exclude: self.exclude.or(config.exclude),
             extend_exclude: config
                 .extend_exclude
@@ -179,26 +456,175 @@ impl Configuration {
                 .chain(self.extend_exclude)
                 .collect(),
             output_format: self.output_format.or(config.output_format),
+            output_severity_mapping: self
+                .output_severity_mapping
+                .or(config.output_severity_mapping),
+            output_template: self.output_template.or(config.output_template),
+            column_encoding: self.column_encoding.or(config.column_encoding),
+            locale: self.locale.or(config.locale),
             force_exclude: self.force_exclude.or(config.force_exclude),
             include: self.include.or(config.include),
             namespace_packages: self.namespace_packages.or(config.namespace_packages),
             respect_gitignore: self.respect_gitignore.or(config.respect_gitignore),
             src: self.src.or(config.src),
             target_version: self.target_version.or(config.target_version),
+            platform: self.platform.or(config.platform),
+            python_path: self.python_path.or(config.python_path),
+            disallow_untyped_imports: self
+                .disallow_untyped_imports
+                .or(config.disallow_untyped_imports),
+            extra_modules: self.extra_modules.or(config.extra_modules),
+            rule_timeout: self.rule_timeout.or(config.rule_timeout),
+            rule_timeout_action: self.rule_timeout_action.or(config.rule_timeout_action),
+            max_diagnostics_per_file: self
+                .max_diagnostics_per_file
+                .or(config.max_diagnostics_per_file),
+            dead_code_keep: self.dead_code_keep.or(config.dead_code_keep),
+            fail_fast: self.fail_fast.or(config.fail_fast),
+            respect_type_ignore: self.respect_type_ignore.or(config.respect_type_ignore),
+            entry_points: config
+                .entry_points
+                .into_iter()
+                .chain(self.entry_points)
+                .collect(),
+            declared_dependencies: self.declared_dependencies.or(config.declared_dependencies),
+            load_diagnostics: config
+                .load_diagnostics
+                .into_iter()
+                .chain(self.load_diagnostics)
+                .collect(),
         }
     }
 
+    /// Whether the codes in preview can be enabled.
+    pub fn preview(&self) -> PreviewMode {
+        PreviewMode::from(self.preview.unwrap_or(false))
+    }
+
     pub fn as_rule_table(&self) -> ErrorCodeTable {
-        // The select_set keeps track of which rules have been selected.
-        let mut error_set: ErrorCodeSet = DEFAULT_ERRORS
-            .iter()
-            .flat_map(|selector| selector.rules())
-            .collect();
+        let [error_set, warning_set, info_set, hint_set] = self.resolve_code_sets();
+
+        // Codes with several severities get the least severe one (and are reported by
+        // `diagnostics`), since each level overrides the ones enabled before it.
+        let mut table = ErrorCodeTable::empty();
+        for code in error_set {
+            table.enable_error(code);
+        }
+        for code in warning_set {
+            table.enable_warning(code);
+        }
+        for code in info_set {
+            table.enable_info(code);
+        }
+        for code in hint_set {
+            table.enable_hint(code);
+        }
+        table
+    }
 
-        let mut warning_set: ErrorCodeSet = DEFAULT_WARNINGS
+    /// Problems with the configuration: those found while loading it, codes in preview that are
+    /// selected without `preview = true`, codes that end up with several severities, and codes
+    /// that are selected and ignored at the same specificity.
+    pub fn diagnostics(&self) -> Vec<ConfigurationDiagnostic> {
+        let mut diagnostics = self.load_diagnostics.clone();
+        let preview = self.preview();
+
+        for selection in &self.rule_selections {
+            if !preview.is_enabled() {
+                let mut reported = ErrorCodeSet::empty();
+                for selector in selection.selectors() {
+                    if let ErrorCodeSelector::ErrorCode(code) = selector {
+                        if code.is_preview() && !reported.contains(*code) {
+                            reported.insert(*code);
+                            diagnostics.push(ConfigurationDiagnostic {
+                                source: selection.source.clone(),
+                                location: selection.location(*code),
+                                kind: ConfigurationDiagnosticKind::PreviewCode(*code),
+                            });
+                        }
+                    }
+                }
+            }
+
+            let mut reported = ErrorCodeSet::empty();
+            for spec in Specificity::iter() {
+                let ignored: ErrorCodeSet = selection
+                    .ignore
+                    .iter()
+                    .filter(|selector| selector.specificity() == spec)
+                    .flat_map(|selector| selector.rules(preview))
+                    .collect();
+                for code in selection
+                    .selectors()
+                    .filter(|selector| selector.specificity() == spec)
+                    .flat_map(|selector| selector.rules(preview))
+                {
+                    if ignored.contains(code) && !reported.contains(code) {
+                        reported.insert(code);
+                        diagnostics.push(ConfigurationDiagnostic {
+                            source: selection.source.clone(),
+                            location: selection.location(code),
+                            kind: ConfigurationDiagnosticKind::SelectedAndIgnored(code),
+                        });
+                    }
+                }
+            }
+        }
+
+        let code_sets = self.resolve_code_sets();
+        let selected = code_sets
             .iter()
-            .flat_map(|selector| selector.rules())
-            .collect();
+            .fold(ErrorCodeSet::empty(), |selected, set| selected.union(set));
+        for code in &selected {
+            let kinds = MessageKind::ALL
+                .into_iter()
+                .zip(&code_sets)
+                .filter_map(|(kind, set)| set.contains(code).then_some(kind))
+                .collect::<Vec<_>>();
+            let kind = match kinds.as_slice() {
+                [] | [_] => continue,
+                [MessageKind::Error, MessageKind::Warning] => {
+                    ConfigurationDiagnosticKind::ErrorAndWarning(code)
+                }
+                [.., least_severe] => ConfigurationDiagnosticKind::ConflictingSeverities {
+                    code,
+                    kind: *least_severe,
+                },
+            };
+            // Attribute the conflict to the selection with the highest precedence that
+            // mentions the code.
+            let selection = self
+                .rule_selections
+                .iter()
+                .rev()
+                .find(|selection| selection.selects(code, preview));
+            diagnostics.push(ConfigurationDiagnostic {
+                source: selection.and_then(|selection| selection.source.clone()),
+                location: selection.and_then(|selection| selection.location(code)),
+                kind,
+            });
+        }
+
+        diagnostics
+    }
+
+    /// Resolve the rule selections into the sets of codes reported with each severity, in the
+    /// order of [`MessageKind::ALL`], starting from the defaults of the [`CheckingMode`].
+    fn resolve_code_sets(&self) -> [ErrorCodeSet; 4] {
+        let (default_errors, default_warnings) = self.mode.unwrap_or_default().default_codes();
+        let preview = self.preview();
+
+        let mut code_sets = MessageKind::ALL.map(|kind| {
+            let defaults: &[ErrorCodeSelector] = match kind {
+                MessageKind::Error => default_errors,
+                MessageKind::Warning => default_warnings,
+                MessageKind::Info | MessageKind::Hint => &[],
+            };
+            defaults
+                .iter()
+                .flat_map(|selector| selector.rules(preview))
+                .collect::<ErrorCodeSet>()
+        });
 
         // Ignores normally only subtract from the current set of selected
         // rules.  By that logic the ignore in `select = [], ignore = ["E501"]`
@@ -209,132 +635,80 @@ impl Configuration {
         let mut carryover_ignores: Option<&[ErrorCodeSelector]> = None;
 
         for selection in &self.rule_selections {
-            // If a selection only specifies extend-select we cannot directly
-            // apply its rule selectors to the select_set because we firstly have
-            // to resolve the effectively selected rules within the current rule selection
-            // (taking specificity into account since more specific selectors take
-            // precedence over less specific selectors within a rule selection).
-            // We do this via the following HashMap where the bool indicates
-            // whether to enable or disable the given rule.
-            let mut error_map_updates: FxHashMap<ErrorCode, bool> = FxHashMap::default();
-            let mut warning_map_updates: FxHashMap<ErrorCode, bool> = FxHashMap::default();
-
             let carriedover_ignores = carryover_ignores.take();
 
-            for spec in Specificity::iter() {
-                // Iterate over rule selectors in order of specificity.
-                for selector in selection
-                    .error
-                    .iter()
-                    .flatten()
-                    .chain(selection.extend_error.iter())
-                    .filter(|s| s.specificity() == spec)
-                {
-                    for rule in selector.rules() {
-                        error_map_updates.insert(rule, true);
-                    }
-                }
-                for selector in selection
-                    .ignore
-                    .iter()
-                    .chain(carriedover_ignores.into_iter().flatten())
-                    .filter(|s| s.specificity() == spec)
-                {
-                    for rule in selector.rules() {
-                        error_map_updates.insert(rule, false);
+            for (kind, code_set) in MessageKind::ALL.into_iter().zip(&mut code_sets) {
+                let (select, extend) = selection.level(kind);
+
+                // If a selection only specifies extend-select we cannot directly
+                // apply its rule selectors to the select_set because we firstly have
+                // to resolve the effectively selected rules within the current rule selection
+                // (taking specificity into account since more specific selectors take
+                // precedence over less specific selectors within a rule selection).
+                // We do this via the following HashMap where the bool indicates
+                // whether to enable or disable the given rule.
+                let mut map_updates: FxHashMap<ErrorCode, bool> = FxHashMap::default();
+
+                for spec in Specificity::iter() {
+                    // Iterate over rule selectors in order of specificity.
+                    for selector in select
+                        .into_iter()
+                        .flatten()
+                        .chain(extend)
+                        .filter(|s| s.specificity() == spec)
+                    {
+                        for rule in selector.rules(preview) {
+                            map_updates.insert(rule, true);
+                        }
                     }
-                }
-                // Apply the same logic to `fixable` and `unfixable`.
-                for selector in selection
-                    .warning
-                    .iter()
-                    .flatten()
-                    .chain(selection.extend_warning.iter())
-                    .filter(|s| s.specificity() == spec)
-                {
-                    for rule in selector.rules() {
-                        warning_map_updates.insert(rule, true);
+                    for selector in selection
+                        .ignore
+                        .iter()
+                        .chain(carriedover_ignores.into_iter().flatten())
+                        .filter(|s| s.specificity() == spec)
+                    {
+                        for rule in selector.rules(preview) {
+                            map_updates.insert(rule, false);
+                        }
                     }
-                }
-                for selector in selection
-                    .ignore
-                    .iter()
-                    .chain(carriedover_ignores.into_iter().flatten())
-                    .filter(|s| s.specificity() == spec)
-                {
-                    for rule in selector.rules() {
-                        warning_map_updates.insert(rule, false);
+                    // The severity table wins over the lists, and moves codes out of the
+                    // other levels.
+                    for (selector, severity) in selection
+                        .severity
+                        .iter()
+                        .filter(|(s, _)| s.specificity() == spec)
+                    {
+                        for rule in selector.rules(preview) {
+                            map_updates.insert(rule, severity.message_kind() == Some(kind));
+                        }
                     }
                 }
-            }
 
-            if let Some(error) = &selection.error {
-                // If the `select` option is given we reassign the whole select_set
-                // (overriding everything that has been defined previously).
-                error_set = error_map_updates
-                    .into_iter()
-                    .filter_map(|(rule, enabled)| enabled.then_some(rule))
-                    .collect();
+                if let Some(select) = select {
+                    // If the `select` option is given we reassign the whole select_set
+                    // (overriding everything that has been defined previously).
+                    *code_set = map_updates
+                        .into_iter()
+                        .filter_map(|(rule, enabled)| enabled.then_some(rule))
+                        .collect();
 
-                if error.is_empty()
-                    && selection.extend_error.is_empty()
-                    && !selection.ignore.is_empty()
-                {
-                    carryover_ignores = Some(&selection.ignore);
-                }
-            } else {
-                // Otherwise we apply the updates on top of the existing select_set.
-                for (rule, enabled) in error_map_updates {
-                    if enabled {
-                        error_set.insert(rule);
-                    } else {
-                        error_set.remove(rule);
+                    if select.is_empty() && extend.is_empty() && !selection.ignore.is_empty() {
+                        carryover_ignores = Some(&selection.ignore);
                     }
-                }
-            }
-
-            // Apply the same logic for warnings.
-            if let Some(warning) = &selection.warning {
-                // If the `select` option is given we reassign the whole select_set
-                // (overriding everything that has been defined previously).
-                warning_set = warning_map_updates
-                    .into_iter()
-                    .filter_map(|(rule, enabled)| enabled.then_some(rule))
-                    .collect();
-
-                if warning.is_empty()
-                    && selection.extend_warning.is_empty()
-                    && !selection.ignore.is_empty()
-                {
-                    carryover_ignores = Some(&selection.ignore);
-                }
-            } else {
-                // Otherwise we apply the updates on top of the existing select_set.
-                for (rule, enabled) in warning_map_updates {
-                    if enabled {
-                        warning_set.insert(rule);
-                    } else {
-                        warning_set.remove(rule);
+                } else {
+                    // Otherwise we apply the updates on top of the existing select_set.
+                    for (rule, enabled) in map_updates {
+                        if enabled {
+                            code_set.insert(rule);
+                        } else {
+                            code_set.remove(rule);
+                        }
                     }
                 }
             }
         }
 
-        let mut table = ErrorCodeTable::empty();
-
-        for code in error_set {
-            if warning_set.contains(code) {
-                warn_user!(
-                    "Code `{}` is both an error and a warning. Treating as warning.",
-                    code
-                )
-            }
-            table.enable_error(code);
-        }
-        for code in warning_set {
-            table.enable_warning(code);
-        }
-        table
+        code_sets
     }
 }
 
@@ -357,3 +731,262 @@ pub fn resolve_src(src: &[String], project_root: &Path) -> Result<Vec<PathBuf>>
         .collect::<Result<Vec<PathBuf>, GlobError>>()?;
     Ok(paths)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pyrogen_checker::registry::ErrorCode;
+    use pyrogen_checker::settings::code_table::MessageKind;
+    use pyrogen_checker::settings::types::{CheckingMode, Severity};
+    use pyrogen_checker::ErrorCodeSelector;
+    use pyrogen_source_file::{OneIndexed, SourceLocation};
+
+    use crate::configuration::{
+        Configuration, ConfigurationDiagnostic, ConfigurationDiagnosticKind, ErrorCodeSelection,
+    };
+
+    #[test]
+    fn selection_diagnostics() {
+        let source = PathBuf::from("/project/pyproject.toml");
+        let location = |row, column| SourceLocation {
+            row: OneIndexed::from_zero_indexed(row - 1),
+            column: OneIndexed::from_zero_indexed(column - 1),
+        };
+        let configuration = Configuration {
+            rule_selections: vec![ErrorCodeSelection {
+                // `unused-import` is a warning by default.
+                error: Some(vec![ErrorCodeSelector::ErrorCode(ErrorCode::UnusedImport)]),
+                extend_error: vec![ErrorCodeSelector::ErrorCode(ErrorCode::UndefinedName)],
+                ignore: vec![ErrorCodeSelector::ErrorCode(ErrorCode::UndefinedName)],
+                source: Some(source.clone()),
+                locations: vec![
+                    (
+                        ErrorCodeSelector::ErrorCode(ErrorCode::UnusedImport),
+                        location(2, 10),
+                    ),
+                    (
+                        ErrorCodeSelector::ErrorCode(ErrorCode::UndefinedName),
+                        location(3, 17),
+                    ),
+                ],
+                ..ErrorCodeSelection::default()
+            }],
+            ..Configuration::default()
+        };
+
+        assert_eq!(
+            configuration.diagnostics(),
+            [
+                ConfigurationDiagnostic {
+                    source: Some(source.clone()),
+                    location: Some(location(3, 17)),
+                    kind: ConfigurationDiagnosticKind::SelectedAndIgnored(ErrorCode::UndefinedName),
+                },
+                ConfigurationDiagnostic {
+                    source: Some(source),
+                    location: Some(location(2, 10)),
+                    kind: ConfigurationDiagnosticKind::ErrorAndWarning(ErrorCode::UnusedImport),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn checking_modes() {
+        let configuration = |mode| Configuration {
+            mode: Some(mode),
+            preview: Some(true),
+            rule_selections: vec![ErrorCodeSelection {
+                extend_warning: vec![ErrorCodeSelector::ErrorCode(ErrorCode::UnusedTypeIgnore)],
+                ..ErrorCodeSelection::default()
+            }],
+            ..Configuration::default()
+        };
+
+        let basic = configuration(CheckingMode::Basic).as_rule_table();
+        assert_eq!(
+            basic.entry(ErrorCode::CallArgument),
+            Some(MessageKind::Error)
+        );
+        assert_eq!(
+            basic.entry(ErrorCode::InvalidAll),
+            Some(MessageKind::Warning)
+        );
+        assert!(!basic.enabled(ErrorCode::UnusedImport));
+        assert_eq!(
+            basic.entry(ErrorCode::UnusedTypeIgnore),
+            Some(MessageKind::Warning)
+        );
+
+        let standard = configuration(CheckingMode::Standard).as_rule_table();
+        assert_eq!(
+            standard.entry(ErrorCode::InvalidAll),
+            Some(MessageKind::Error)
+        );
+        assert_eq!(
+            standard.entry(ErrorCode::UnusedImport),
+            Some(MessageKind::Warning)
+        );
+
+        // The rule selections still apply on top of the mode.
+        let strict = configuration(CheckingMode::Strict).as_rule_table();
+        assert_eq!(
+            strict.entry(ErrorCode::UnusedImport),
+            Some(MessageKind::Error)
+        );
+        assert_eq!(
+            strict.entry(ErrorCode::PrivateExport),
+            Some(MessageKind::Error)
+        );
+        assert_eq!(
+            strict.entry(ErrorCode::UnusedTypeIgnore),
+            Some(MessageKind::Warning)
+        );
+    }
+
+    #[test]
+    fn info_and_hint() {
+        let configuration = Configuration {
+            preview: Some(true),
+            rule_selections: vec![ErrorCodeSelection {
+                // `unused-variable` is a warning by default.
+                info: Some(vec![ErrorCodeSelector::ErrorCode(
+                    ErrorCode::UnusedVariable,
+                )]),
+                hint: Some(vec![ErrorCodeSelector::ErrorCode(
+                    ErrorCode::UnusedTypeIgnore,
+                )]),
+                ..ErrorCodeSelection::default()
+            }],
+            ..Configuration::default()
+        };
+
+        let table = configuration.as_rule_table();
+        assert_eq!(
+            table.entry(ErrorCode::CallArgument),
+            Some(MessageKind::Error)
+        );
+        assert_eq!(
+            table.entry(ErrorCode::UnusedVariable),
+            Some(MessageKind::Info)
+        );
+        assert_eq!(
+            table.entry(ErrorCode::UnusedTypeIgnore),
+            Some(MessageKind::Hint)
+        );
+        assert_eq!(
+            configuration.diagnostics(),
+            [ConfigurationDiagnostic {
+                source: None,
+                location: None,
+                kind: ConfigurationDiagnosticKind::ConflictingSeverities {
+                    code: ErrorCode::UnusedVariable,
+                    kind: MessageKind::Info,
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn severity_table() {
+        let configuration = Configuration {
+            preview: Some(true),
+            rule_selections: vec![ErrorCodeSelection {
+                extend_warning: vec![ErrorCodeSelector::ErrorCode(ErrorCode::CallArgument)],
+                severity: vec![
+                    (
+                        ErrorCodeSelector::ErrorCode(ErrorCode::CallArgument),
+                        Severity::Hint,
+                    ),
+                    (
+                        ErrorCodeSelector::ErrorCode(ErrorCode::UnusedImport),
+                        Severity::Error,
+                    ),
+                    (
+                        ErrorCodeSelector::ErrorCode(ErrorCode::UnusedVariable),
+                        Severity::Ignore,
+                    ),
+                ],
+                ..ErrorCodeSelection::default()
+            }],
+            ..Configuration::default()
+        };
+
+        let table = configuration.as_rule_table();
+        assert_eq!(
+            table.entry(ErrorCode::CallArgument),
+            Some(MessageKind::Hint)
+        );
+        assert_eq!(
+            table.entry(ErrorCode::UnusedImport),
+            Some(MessageKind::Error)
+        );
+        assert!(!table.enabled(ErrorCode::UnusedVariable));
+        assert_eq!(table.entry(ErrorCode::InvalidAll), Some(MessageKind::Error));
+        // The table moves codes between levels instead of adding conflicting ones.
+        assert!(configuration.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn category_selectors() {
+        let configuration = Configuration {
+            preview: Some(true),
+            rule_selections: vec![ErrorCodeSelection {
+                error: Some(vec!["imports".parse().unwrap()]),
+                // Individual codes are more specific than their category.
+                ignore: vec!["unused-import".parse().unwrap()],
+                warning: Some(vec![]),
+                ..ErrorCodeSelection::default()
+            }],
+            ..Configuration::default()
+        };
+
+        let table = configuration.as_rule_table();
+        assert_eq!(
+            table.entry(ErrorCode::ImportNotFound),
+            Some(MessageKind::Error)
+        );
+        assert!(!table.enabled(ErrorCode::UnusedImport));
+        assert!(!table.enabled(ErrorCode::CallArgument));
+    }
+
+    #[test]
+    fn preview_codes() {
+        let configuration = |preview| Configuration {
+            preview,
+            rule_selections: vec![ErrorCodeSelection {
+                extend_error: vec![ErrorCodeSelector::ErrorCode(
+                    ErrorCode::BidiControlCharacter,
+                )],
+                ..ErrorCodeSelection::default()
+            }],
+            ..Configuration::default()
+        };
+
+        // Codes in preview are left out of the defaults and of explicit selections, which are
+        // reported.
+        let table = configuration(None).as_rule_table();
+        assert!(!table.enabled(ErrorCode::ConfusableCharacter));
+        assert!(!table.enabled(ErrorCode::BidiControlCharacter));
+        assert_eq!(
+            configuration(None).diagnostics(),
+            [ConfigurationDiagnostic {
+                source: None,
+                location: None,
+                kind: ConfigurationDiagnosticKind::PreviewCode(ErrorCode::BidiControlCharacter),
+            }]
+        );
+        assert!(configuration(Some(true)).diagnostics().is_empty());
+
+        let table = configuration(Some(true)).as_rule_table();
+        assert_eq!(
+            table.entry(ErrorCode::ConfusableCharacter),
+            Some(MessageKind::Warning)
+        );
+        assert_eq!(
+            table.entry(ErrorCode::BidiControlCharacter),
+            Some(MessageKind::Warning)
+        );
+    }
+}
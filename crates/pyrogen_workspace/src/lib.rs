@@ -1,4 +1,5 @@
 pub mod configuration;
+mod migration;
 pub mod options;
 pub mod pyproject;
 pub mod resolver;
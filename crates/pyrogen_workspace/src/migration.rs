@@ -0,0 +1,185 @@
+//! Migration of `[tool.pyrogen]` sections written for older versions of the configuration format.
+//!
+//! Options that have since been renamed are still accepted under their old name, as long as the
+//! section doesn't opt into a `config-version` that postdates the rename. They are rewritten to
+//! their new name before the section is deserialized, and reported as deprecated.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use rustpython_parser::text_size::TextSize;
+use toml::{Spanned, Table, Value};
+
+use pyrogen_source_file::LineIndex;
+
+use crate::configuration::{ConfigurationDiagnostic, ConfigurationDiagnosticKind};
+
+/// The current version of the configuration format.
+pub const CONFIG_VERSION: u32 = 1;
+
+struct Rename {
+    old: &'static str,
+    new: &'static str,
+    /// The first configuration version in which only the new name is accepted.
+    removed_in: u32,
+}
+
+/// Options that have been renamed, oldest first. None so far.
+const RENAMES: &[Rename] = &[];
+
+/// A `[tool.pyrogen]` section, with the span of every key in the source.
+pub(crate) type RawSection = BTreeMap<Spanned<String>, Value>;
+
+/// Rewrite the deprecated option names in `section` to their current names.
+///
+/// Returns the rewritten section together with a diagnostic for every deprecated option, or an
+/// error if the section requires a newer configuration version or sets both an option and its
+/// replacement.
+pub(crate) fn migrate(
+    section: RawSection,
+    contents: &str,
+    path: &Path,
+) -> Result<(Table, Vec<ConfigurationDiagnostic>)> {
+    rename_options(section, RENAMES, contents, path)
+}
+
+fn rename_options(
+    section: RawSection,
+    renames: &[Rename],
+    contents: &str,
+    path: &Path,
+) -> Result<(Table, Vec<ConfigurationDiagnostic>)> {
+    let version = match section.get("config-version") {
+        Some(Value::Integer(version)) => u32::try_from(*version).unwrap_or(u32::MAX),
+        // Leave reporting of invalid values to the deserialization of the options.
+        _ => 0,
+    };
+    if version > CONFIG_VERSION {
+        bail!(
+            "`config-version = {version}` requires a newer version of pyrogen (the latest supported configuration version is {CONFIG_VERSION})"
+        );
+    }
+
+    let index = LineIndex::from_source_text(contents);
+    let mut table = Table::new();
+    let mut diagnostics = vec![];
+    for (key, value) in &section {
+        let name = key.get_ref().as_str();
+        let Some(rename) = renames
+            .iter()
+            .find(|rename| rename.old == name && version < rename.removed_in)
+        else {
+            table.insert(name.to_string(), value.clone());
+            continue;
+        };
+        if section.contains_key(rename.new) {
+            bail!(
+                "`{}` is a deprecated alias of `{}`; only one of them may be set",
+                rename.old,
+                rename.new
+            );
+        }
+        let offset = TextSize::try_from(key.span().start).unwrap_or_default();
+        diagnostics.push(ConfigurationDiagnostic {
+            source: Some(path.to_path_buf()),
+            location: Some(index.source_location(offset, contents)),
+            kind: ConfigurationDiagnosticKind::DeprecatedOption {
+                old: rename.old,
+                new: rename.new,
+            },
+        });
+        table.insert(rename.new.to_string(), value.clone());
+    }
+
+    Ok((table, diagnostics))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use anyhow::Result;
+    use serde::Deserialize;
+
+    use super::{rename_options, RawSection, Rename};
+
+    const RENAMES: &[Rename] = &[Rename {
+        old: "select",
+        new: "error",
+        removed_in: 1,
+    }];
+
+    #[derive(Deserialize)]
+    struct Section {
+        pyrogen: RawSection,
+    }
+
+    fn migrate_str(contents: &str) -> Result<(toml::Table, Vec<String>)> {
+        let section: Section = toml::from_str(contents)?;
+        let (table, diagnostics) = rename_options(
+            section.pyrogen,
+            RENAMES,
+            contents,
+            Path::new("pyproject.toml"),
+        )?;
+        Ok((
+            table,
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    #[test]
+    fn renamed_options() -> Result<()> {
+        let (table, diagnostics) = migrate_str(
+            r#"
+[pyrogen]
+exclude = []
+  select = ["F401"]
+"#,
+        )?;
+        assert!(table.contains_key("error"));
+        assert!(!table.contains_key("select"));
+        assert_eq!(
+            diagnostics,
+            vec!["pyproject.toml:4:3: `select` is deprecated; use `error` instead"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn current_version_rejects_old_names() -> Result<()> {
+        let (table, diagnostics) = migrate_str(
+            r#"
+[pyrogen]
+config-version = 1
+select = ["F401"]
+"#,
+        )?;
+        assert!(table.contains_key("select"));
+        assert!(diagnostics.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn conflicts_and_future_versions() {
+        assert!(migrate_str(
+            r#"
+[pyrogen]
+select = ["F401"]
+error = ["F401"]
+"#
+        )
+        .is_err());
+        assert!(migrate_str(
+            r#"
+[pyrogen]
+config-version = 2
+"#
+        )
+        .is_err());
+    }
+}
@@ -2,15 +2,31 @@ use pyrogen_macros::OptionsMetadata;
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 
+use pyrogen_cache::{CacheInvalidation, CacheLocation};
 use pyrogen_checker::{
-    settings::types::{PythonVersion, SerializationFormat},
+    locale::Locale,
+    settings::code_table::MessageKind,
+    settings::types::{
+        CheckingMode, PythonPlatform, PythonVersion, RuleTimeoutAction, SerializationFormat,
+        Severity,
+    },
     ErrorCodeSelector,
 };
+use pyrogen_source_file::ColumnEncoding;
 
 #[derive(Debug, PartialEq, Eq, Default, OptionsMetadata, Serialize, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Options {
+    /// The version of the configuration format that this section is written
+    /// for.
+    ///
+    /// Sections without a `config-version` still accept options under the
+    /// names they had before being renamed, and report them as deprecated.
+    /// Setting the current version opts out of these aliases.
+    #[option(default = "0", value_type = "int", example = r#"config-version = 1"#)]
+    pub config_version: Option<u32>,
+
     /// A path to the cache directory.
     ///
     /// By default, Pyrogen stores cache results in a `.pyrogen_cache` directory in
@@ -28,6 +44,66 @@ pub struct Options {
     )]
     pub cache_dir: Option<String>,
 
+    /// Where the cache directory is placed if `cache-dir` isn't set:
+    /// `"project"` puts it in a `.pyrogen_cache` directory in the project
+    /// root, `"platform"` in the cache directory of the platform (like
+    /// `$XDG_CACHE_HOME` or `~/.cache` on Linux, `~/Library/Caches` on macOS
+    /// and `%LOCALAPPDATA%` on Windows), keyed by a hash of the project root,
+    /// which keeps the project tree clean.
+    #[option(
+        default = r#""project""#,
+        value_type = r#""project" | "platform""#,
+        example = r#"cache-location = "platform""#
+    )]
+    pub cache_location: Option<CacheLocation>,
+
+    /// What invalidates the cached results of a file: `"metadata"` checks
+    /// its modification time and permissions, `"content"` a hash of its
+    /// contents. Use `"content"` when files are checked out or generated
+    /// with new modification times, like by `git checkout` or build systems
+    /// that normalize them, at the cost of reading every file.
+    #[option(
+        default = r#""metadata""#,
+        value_type = r#""metadata" | "content""#,
+        example = r#"cache-invalidation = "content""#
+    )]
+    pub cache_invalidation: Option<CacheInvalidation>,
+
+    /// The strictness of the checks, which determines the codes that are
+    /// reported as errors and as warnings before `error`, `warning`, `ignore`
+    /// and their `extend-` variants are applied:
+    ///
+    /// - `"basic"` only reports definite type errors, and the problems with
+    ///   `__all__` and `TYPE_CHECKING` imports as warnings.
+    /// - `"standard"` reports all type errors, and unused variables and
+    ///   imports as warnings.
+    /// - `"strict"` additionally reports private names in `__all__` and
+    ///   unused `type: ignore` comments, and reports everything as an error.
+    #[option(
+        default = r#""standard""#,
+        value_type = r#""basic" | "standard" | "strict""#,
+        example = r#"
+            mode = "strict"
+        "#
+    )]
+    pub mode: Option<CheckingMode>,
+
+    /// Whether to enable the codes in preview, which are new and may still
+    /// change or report false positives. Codes in preview are left out of the
+    /// defaults of every `mode` and of the selectors in `error`, `warning`
+    /// and the other levels, even when they are selected by name (which is
+    /// reported as a warning), unless this is enabled.
+    ///
+    /// `pyrogen rule --format json` shows which codes are in preview.
+    #[option(
+        default = "false",
+        value_type = "bool",
+        example = r#"
+            preview = true
+        "#
+    )]
+    pub preview: Option<bool>,
+
     /// A list of rule codes or prefixes to ignore. Prefixes can specify exact
     /// rules (like `F841`), entire categories (like `F`), or anything in
     /// between.
@@ -103,6 +179,32 @@ pub struct Options {
     )]
     pub extend_error: Option<Vec<ErrorCodeSelector>>,
 
+    /// A list of rule codes or prefixes to report as informational messages.
+    /// Like warnings, they never make a check fail.
+    ///
+    /// A code that is selected with several severities is reported with the
+    /// least severe one.
+    #[option(
+        default = "[]",
+        value_type = "list[RuleSelector]",
+        example = r#"
+            # Report unused variables without drawing attention to them.
+            info = ["unused-variable"]
+        "#
+    )]
+    pub info: Option<Vec<ErrorCodeSelector>>,
+
+    /// A list of rule codes or prefixes to report as hints, the least severe
+    /// level. Editors typically show them unobtrusively.
+    #[option(
+        default = "[]",
+        value_type = "list[RuleSelector]",
+        example = r#"
+            hint = ["unused-type-ignore"]
+        "#
+    )]
+    pub hint: Option<Vec<ErrorCodeSelector>>,
+
     // Tables are required to go last.
     /// A list of mappings from file pattern to rule codes or prefixes to
     /// exclude, when considering any matching files.
@@ -118,6 +220,25 @@ pub struct Options {
     )]
     pub per_file_ignores: Option<FxHashMap<String, Vec<ErrorCodeSelector>>>,
 
+    /// A mapping from rule codes to the severity they're reported with:
+    /// `"error"`, `"warning"`, `"info"`, `"hint"`, or `"ignore"` to not report
+    /// them at all.
+    ///
+    /// This tunes individual codes without re-listing the whole `error` or
+    /// `warning` sets. It takes precedence over the other rule selections of
+    /// the same configuration file, but not over those of extending
+    /// configuration files or the command line.
+    #[option(
+        default = "{}",
+        value_type = r#"dict[RuleSelector, "error" | "warning" | "info" | "hint" | "ignore"]"#,
+        example = r#"
+            [tool.pyrogen.severity]
+            unused-import = "error"
+            unused-variable = "ignore"
+        "#
+    )]
+    pub severity: Option<FxHashMap<ErrorCodeSelector, Severity>>,
+
     /// A list of file patterns to exclude from linting.
     ///
     /// Exclusions are based on globs, and can be either:
@@ -145,12 +266,12 @@ pub struct Options {
 
     /// The style in which violation messages should be formatted: `"text"`
     /// (default), `"grouped"` (group messages by file), `"json"`
-    /// (machine-readable), `"junit"` (machine-readable XML), `"github"` (GitHub
-    /// Actions annotations), `"gitlab"` (GitLab CI code quality report),
-    /// `"pylint"` (Pylint text format) or `"azure"` (Azure Pipeline logging commands).
+    /// (machine-readable), `"github"` (GitHub Actions annotations), `"pylint"`
+    /// or `"mypy"` (the line formats of those tools, for existing integrations),
+    /// or `"custom"` (the format given by `output-template`).
     #[option(
         default = r#""text""#,
-        value_type = r#""text" | "json" | "junit" | "github" | "gitlab" | "pylint" | "azure""#,
+        value_type = r#""text" | "grouped" | "json" | "github" | "pylint" | "mypy" | "custom""#,
         example = r#"
             # Group violations by containing file.
             output-format = "grouped"
@@ -158,6 +279,68 @@ pub struct Options {
     )]
     pub output_format: Option<SerializationFormat>,
 
+    /// The line format of messages with `output-format = "custom"`.
+    ///
+    /// The placeholders `{path}`, `{row}`, `{column}`, `{end_row}`,
+    /// `{end_column}`, `{code}`, `{message}` and `{severity}` are replaced by
+    /// the corresponding part of each message. Literal braces are written as
+    /// `{{` and `}}`.
+    #[option(
+        default = "null",
+        value_type = "str",
+        example = r#"
+            output-format = "custom"
+            output-template = "{path}({row}): {severity} {code}: {message}"
+        "#
+    )]
+    pub output_template: Option<String>,
+
+    /// The severities that messages are presented with by the structured
+    /// output formats (`"json"`, `"github"` and `"mypy"`), keyed by their
+    /// actual severity. Unmapped severities are presented as they are.
+    ///
+    /// Only the presentation changes: whether the check fails still depends
+    /// on the actual severities.
+    #[option(
+        default = "{}",
+        value_type = r#"dict["error" | "warning" | "info" | "hint", "error" | "warning" | "info" | "hint"]"#,
+        example = r#"
+            # Make warnings fail GitHub Actions annotations, and show hints as warnings.
+            [tool.pyrogen.output-severity-mapping]
+            warning = "error"
+            hint = "warning"
+        "#
+    )]
+    pub output_severity_mapping: Option<FxHashMap<MessageKind, MessageKind>>,
+
+    /// The unit in which the columns of messages are counted: `"codepoint"`
+    /// (characters), `"utf16"` (UTF-16 code units, as editors speaking the
+    /// Language Server Protocol expect) or `"utf8"` (bytes).
+    ///
+    /// Applies to every output format. The columns only differ on lines with
+    /// non-ASCII characters before the location.
+    #[option(
+        default = r#""codepoint""#,
+        value_type = r#""codepoint" | "utf16" | "utf8""#,
+        example = r#"
+            # Report columns like an LSP client would count them.
+            column-encoding = "utf16"
+        "#
+    )]
+    pub column_encoding: Option<ColumnEncoding>,
+
+    /// The language of the messages of diagnostics: `"en"` (English) or
+    /// `"de"` (German). Messages that aren't translated yet are shown in
+    /// English.
+    #[option(
+        default = r#""en""#,
+        value_type = r#""en" | "de""#,
+        example = r#"
+            locale = "de"
+        "#
+    )]
+    pub locale: Option<Locale>,
+
     /// Whether to enforce `exclude` and `extend-exclude` patterns, even for
     /// paths that are passed to Pyrogen explicitly. Typically, Pyrogen will lint
     /// any paths passed in directly, even if they would typically be
@@ -300,4 +483,161 @@ pub struct Options {
         "#
     )]
     pub target_version: Option<PythonVersion>,
+
+    /// The platform to assume when evaluating `sys.platform` checks, e.g.,
+    /// `if sys.platform == "win32":`. Branches that can't be taken on the
+    /// given platform are not checked.
+    ///
+    /// By default, no platform is assumed and all branches are checked.
+    #[option(
+        default = r#""all""#,
+        value_type = r#""all" | "linux" | "darwin" | "win32""#,
+        example = r#"
+            # Only check code that runs on Linux.
+            platform = "linux"
+        "#
+    )]
+    pub platform: Option<PythonPlatform>,
+
+    /// The Python environment that third-party imports are resolved in: a
+    /// virtual environment, the Python interpreter inside of one, or a
+    /// `site-packages` directory.
+    ///
+    /// Installed packages are only considered typed if they contain a
+    /// `py.typed` marker or if stubs are installed for them (e.g. a
+    /// `requests-stubs` package for `requests`).
+    ///
+    /// If omitted, the virtual environment in the `VIRTUAL_ENV` environment
+    /// variable is used, if any.
+    #[option(
+        default = "null",
+        value_type = "str",
+        example = r#"
+            python-path = ".venv"
+        "#
+    )]
+    pub python_path: Option<String>,
+
+    /// Whether to report imports of installed packages that don't ship type
+    /// information (see [`python-path`](#python-path)). The contents of such
+    /// packages are treated as `Any`.
+    #[option(
+        default = "false",
+        value_type = "bool",
+        example = r#"
+            disallow-untyped-imports = true
+        "#
+    )]
+    pub disallow_untyped_imports: Option<bool>,
+
+    /// Modules that exist at runtime although they can't be resolved, such
+    /// as modules that are generated or injected by an embedding application,
+    /// or optional dependencies that aren't installed. Imports of them and of
+    /// their submodules aren't reported as
+    /// [`import-not-found`](#import-not-found).
+    ///
+    /// Imports in a `try` statement that catches `ImportError` are never
+    /// reported, so optional dependencies imported that way don't need to be
+    /// listed.
+    #[option(
+        default = "[]",
+        value_type = "list[str]",
+        example = r#"
+            extra-modules = ["bpy", "plugins.generated"]
+        "#
+    )]
+    pub extra_modules: Option<Vec<String>>,
+
+    /// The time budget, in milliseconds, of a single rule on a single file.
+    /// Rules that take longer are reported with a warning, or disabled for
+    /// the rest of the run (see [`rule-timeout-action`](#rule-timeout-action)).
+    ///
+    /// Run with `--timings` to see which rules take the most time.
+    #[option(
+        default = "null",
+        value_type = "int",
+        example = r#"
+            rule-timeout = 500
+        "#
+    )]
+    pub rule_timeout: Option<u64>,
+
+    /// What to do when a rule exceeds the [`rule-timeout`](#rule-timeout):
+    /// `"warn"` logs a warning, `"disable"` also stops running the rule for
+    /// the rest of the run.
+    #[option(
+        default = r#""warn""#,
+        value_type = r#""warn" | "disable""#,
+        example = r#"
+            rule-timeout = 500
+            rule-timeout-action = "disable"
+        "#
+    )]
+    pub rule_timeout_action: Option<RuleTimeoutAction>,
+
+    /// The maximum number of diagnostics reported for a single file. The
+    /// diagnostics beyond it are dropped, and replaced by a single
+    /// [`too-many-diagnostics`](#too-many-diagnostics) notice that says how
+    /// many were dropped. Errors are kept before warnings, infos and hints.
+    ///
+    /// This protects editors and output formats from files with thousands of
+    /// diagnostics, like generated code. `0` means no limit.
+    #[option(
+        default = "1000",
+        value_type = "int",
+        example = r#"
+            max-diagnostics-per-file = 200
+        "#
+    )]
+    pub max_diagnostics_per_file: Option<usize>,
+
+    /// Top-level functions and classes that are never reported as
+    /// [`dead-code`](#dead-code), such as ones that are only used by
+    /// frameworks or other projects. The patterns may contain the wildcards
+    /// `*` and `?`, and are matched against the qualified name (like
+    /// `pkg.module.function`) if they contain a `.`, and against the plain
+    /// name otherwise.
+    ///
+    /// The entry points in the `[project.scripts]`, `[project.gui-scripts]`
+    /// and `[project.entry-points]` tables of the `pyproject.toml`, and the
+    /// code in `if __name__ == "__main__":` blocks, are always considered
+    /// used.
+    #[option(
+        default = "[]",
+        value_type = "list[str]",
+        example = r#"
+            # Keep test functions and everything in the plugins package.
+            dead-code-keep = ["test_*", "Test*", "pkg.plugins.*"]
+        "#
+    )]
+    pub dead_code_keep: Option<Vec<String>>,
+
+    /// Codes that abort the run as soon as one of their diagnostics is
+    /// produced. Only the diagnostics with these codes are reported then,
+    /// since the rest of the output is often noise (for example, in a file
+    /// that doesn't parse).
+    ///
+    /// Files are checked in parallel, so the files checked at the same time
+    /// as the failing one may be reported too.
+    #[option(
+        default = "[]",
+        value_type = "list[RuleSelector]",
+        example = r#"
+            # Stop at the first file with a syntax error, like in a pre-commit hook.
+            fail-fast = ["syntax-error"]
+        "#
+    )]
+    pub fail_fast: Option<Vec<ErrorCodeSelector>>,
+
+    /// Whether `type: ignore` comments suppress the diagnostics on their
+    /// line. Disable this to see everything that the comments hide, e.g.,
+    /// when auditing a code base. Enabled by default.
+    #[option(
+        default = "true",
+        value_type = "bool",
+        example = r#"
+            respect-type-ignore = false
+        "#
+    )]
+    pub respect_type_ignore: Option<bool>,
 }
@@ -1,13 +1,22 @@
 //! Utilities for locating (and extracting configuration from) a pyproject.toml.
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use pep440_rs::VersionSpecifiers;
+use rustpython_parser::text_size::TextSize;
 use serde::{Deserialize, Serialize};
+use toml::Spanned;
 
-use pyrogen_checker::settings::types::PythonVersion;
+use pyrogen_checker::dependencies::requirement_name;
+use pyrogen_checker::settings::types::{PythonVersion, Severity};
+use pyrogen_checker::ErrorCodeSelector;
+use pyrogen_source_file::{LineIndex, SourceLocation};
 
+use crate::configuration::ConfigurationDiagnostic;
+use crate::migration::{self, RawSection};
 use crate::options::Options;
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -19,6 +28,54 @@ struct Tools {
 struct Project {
     #[serde(alias = "requires-python", alias = "requires_python")]
     requires_python: Option<VersionSpecifiers>,
+    #[serde(default)]
+    scripts: BTreeMap<String, String>,
+    #[serde(default, alias = "gui-scripts")]
+    gui_scripts: BTreeMap<String, String>,
+    /// The entry points of plugins, grouped by the framework that loads them.
+    #[serde(default, alias = "entry-points")]
+    entry_points: BTreeMap<String, BTreeMap<String, String>>,
+    dependencies: Option<Vec<String>>,
+    #[serde(default, alias = "optional-dependencies")]
+    optional_dependencies: BTreeMap<String, Vec<String>>,
+    /// The fields that the build backend fills in, like `dependencies`.
+    #[serde(default)]
+    dynamic: Vec<String>,
+}
+
+impl Project {
+    /// The normalized names of the distributions that the project depends on, including the
+    /// optional dependencies, or `None` if the dependencies are dynamic.
+    fn dependency_names(&self) -> Option<Vec<String>> {
+        if self.dynamic.iter().any(|field| field == "dependencies") {
+            return None;
+        }
+        let mut names = self
+            .dependencies
+            .iter()
+            .flatten()
+            .chain(self.optional_dependencies.values().flatten())
+            .filter_map(|requirement| requirement_name(requirement))
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+        Some(names)
+    }
+
+    /// The qualified names of the top-level objects that the entry points refer to, e.g.
+    /// `pkg.cli.App` for `pkg.cli:App.run [extra]`.
+    fn entry_point_targets(&self) -> impl Iterator<Item = String> + '_ {
+        self.scripts
+            .values()
+            .chain(self.gui_scripts.values())
+            .chain(self.entry_points.values().flat_map(BTreeMap::values))
+            .filter_map(|entry_point| {
+                let entry_point = entry_point.split('[').next().unwrap_or_default();
+                let (module, object) = entry_point.split_once(':')?;
+                let object = object.trim().split('.').next().unwrap_or_default();
+                Some(format!("{}.{object}", module.trim()))
+            })
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -45,14 +102,53 @@ impl Pyproject {
 // }
 
 /// Parse a `pyproject.toml` file.
+#[cfg(test)]
 fn parse_pyproject_toml<P: AsRef<Path>>(path: P) -> Result<Pyproject> {
     let contents = std::fs::read_to_string(path)?;
     toml::from_str(&contents).map_err(Into::into)
 }
 
+#[derive(Debug, Deserialize)]
+struct RawTools {
+    pyrogen: Option<RawSection>,
+}
+
+/// A `pyproject.toml` whose `[tool.pyrogen]` section hasn't been migrated and deserialized yet.
+#[derive(Debug, Deserialize)]
+struct RawPyproject {
+    tool: Option<RawTools>,
+    project: Option<Project>,
+}
+
+/// The selectors of a `[tool.pyrogen]` section that enable codes, with the span of each in the
+/// source.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct SpannedSelectors {
+    error: Vec<Spanned<String>>,
+    extend_error: Vec<Spanned<String>>,
+    warning: Vec<Spanned<String>>,
+    extend_warning: Vec<Spanned<String>>,
+    info: Vec<Spanned<String>>,
+    hint: Vec<Spanned<String>>,
+    severity: BTreeMap<Spanned<String>, Severity>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpannedTools {
+    pyrogen: Option<SpannedSelectors>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpannedPyproject {
+    tool: Option<SpannedTools>,
+}
+
 /// Return `true` if a `pyproject.toml` contains a `[tool.pyrogen]` section.
 pub fn pyrogen_enabled<P: AsRef<Path>>(path: P) -> Result<bool> {
-    let pyproject = parse_pyproject_toml(path)?;
+    // Only look at the raw section: options that need migrating shouldn't hide the section.
+    let contents = std::fs::read_to_string(path)?;
+    let pyproject: RawPyproject = toml::from_str(&contents)?;
     Ok(pyproject.tool.and_then(|tool| tool.pyrogen).is_some())
 }
 
@@ -121,13 +217,101 @@ pub fn find_user_settings_toml() -> Option<PathBuf> {
     None
 }
 
+/// Load the qualified names of the objects that the entry points in the `[project.scripts]`,
+/// `[project.gui-scripts]` and `[project.entry-points]` tables of a `pyproject.toml` file refer
+/// to, e.g. `pkg.cli.main` for `pkg.cli:main`.
+pub fn load_entry_points<P: AsRef<Path>>(path: P) -> Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)?;
+    let pyproject: RawPyproject = toml::from_str(&contents)?;
+    Ok(pyproject
+        .project
+        .map(|project| project.entry_point_targets().collect())
+        .unwrap_or_default())
+}
+
+/// Load the normalized names of the distributions that the `[project]` table of a
+/// `pyproject.toml` file declares as (optional) dependencies. Returns `None` if there's no
+/// `[project]` table or its dependencies are dynamic.
+pub fn load_dependencies<P: AsRef<Path>>(path: P) -> Result<Option<Vec<String>>> {
+    let contents = std::fs::read_to_string(path)?;
+    let pyproject: RawPyproject = toml::from_str(&contents)?;
+    Ok(pyproject
+        .project
+        .and_then(|project| project.dependency_names()))
+}
+
+/// Load the position of every selector that enables codes in the `[tool.pyrogen]` section of a
+/// `pyproject.toml` file, in the order they're written.
+pub fn load_selector_locations<P: AsRef<Path>>(
+    path: P,
+) -> Result<Vec<(ErrorCodeSelector, SourceLocation)>> {
+    let contents = std::fs::read_to_string(path)?;
+    let pyproject: SpannedPyproject = toml::from_str(&contents)?;
+    let Some(section) = pyproject.tool.and_then(|tool| tool.pyrogen) else {
+        return Ok(vec![]);
+    };
+    let mut selectors = section
+        .error
+        .iter()
+        .chain(&section.extend_error)
+        .chain(&section.warning)
+        .chain(&section.extend_warning)
+        .chain(&section.info)
+        .chain(&section.hint)
+        .chain(
+            section
+                .severity
+                .iter()
+                .filter(|(_, severity)| **severity != Severity::Ignore)
+                .map(|(selector, _)| selector),
+        )
+        .collect::<Vec<_>>();
+    selectors.sort_by_key(|selector| selector.span().start);
+
+    let index = LineIndex::from_source_text(&contents);
+    Ok(selectors
+        .into_iter()
+        .filter_map(|selector| {
+            // Invalid selectors are reported when loading the options.
+            let code = ErrorCodeSelector::from_str(selector.get_ref()).ok()?;
+            let offset = TextSize::try_from(selector.span().start).unwrap_or_default();
+            Some((code, index.source_location(offset, &contents)))
+        })
+        .collect())
+}
+
 /// Load `Options` from a `pyproject.toml` file.
 pub fn load_options<P: AsRef<Path>>(path: P) -> Result<Options> {
-    let pyproject = parse_pyproject_toml(&path)?;
-    let mut pyrogen = pyproject
-        .tool
-        .and_then(|tool| tool.pyrogen)
-        .unwrap_or_default();
+    load_options_and_diagnostics(path).map(|(options, _)| options)
+}
+
+/// Load `Options` from a `pyproject.toml` file, migrating deprecated option names.
+///
+/// Returns the options together with a diagnostic for every deprecated option that was used.
+pub fn load_options_and_diagnostics<P: AsRef<Path>>(
+    path: P,
+) -> Result<(Options, Vec<ConfigurationDiagnostic>)> {
+    let contents = std::fs::read_to_string(&path)?;
+    let pyproject: RawPyproject = toml::from_str(&contents)?;
+    let (mut pyrogen, diagnostics) = match pyproject.tool.and_then(|tool| tool.pyrogen) {
+        Some(section) => {
+            let (table, diagnostics) = migration::migrate(section, &contents, path.as_ref())?;
+            let options = if diagnostics.is_empty() {
+                // Deserialize from the source rather than the table, so that errors point at the
+                // offending line.
+                toml::from_str::<Pyproject>(&contents)?
+                    .tool
+                    .and_then(|tool| tool.pyrogen)
+                    .unwrap_or_default()
+            } else {
+                toml::Value::Table(table)
+                    .try_into()
+                    .context("Invalid `[tool.pyrogen]` section")?
+            };
+            (options, diagnostics)
+        }
+        None => (Options::default(), vec![]),
+    };
     if pyrogen.target_version.is_none() {
         if let Some(project) = pyproject.project {
             if let Some(requires_python) = project.requires_python {
@@ -136,7 +320,7 @@ pub fn load_options<P: AsRef<Path>>(path: P) -> Result<Options> {
             }
         }
     }
-    Ok(pyrogen)
+    Ok((pyrogen, diagnostics))
     // else {
     //     let pyrogen = parse_pyrogen_toml(path);
     //     if let Ok(pyrogen) = &pyrogen {
@@ -150,13 +334,20 @@ pub fn load_options<P: AsRef<Path>>(path: P) -> Result<Options> {
 
 #[cfg(test)]
 mod tests {
+    use std::fs::write;
+
     use anyhow::Result;
     use pyrogen_checker::registry::ErrorCode;
+    use pyrogen_checker::settings::types::Severity;
     use pyrogen_checker::ErrorCodeSelector;
     use rustc_hash::FxHashMap;
+    use tempfile::TempDir;
 
     use crate::options::Options;
-    use crate::pyproject::{find_settings_toml, parse_pyproject_toml, Pyproject, Tools};
+    use crate::pyproject::{
+        find_settings_toml, load_dependencies, load_entry_points, load_options_and_diagnostics,
+        load_selector_locations, parse_pyproject_toml, Pyproject, Tools,
+    };
     use crate::tests::test_resource_path;
 
     #[test]
@@ -202,6 +393,37 @@ exclude = ["foo.py"]
             })
         );
 
+        let pyproject: Pyproject = toml::from_str(
+            r#"
+[tool.pyrogen.severity]
+ALL = "warning"
+unused-import = "ignore"
+"#,
+        )?;
+        assert_eq!(
+            pyproject.tool,
+            Some(Tools {
+                pyrogen: Some(Options {
+                    severity: Some(FxHashMap::from_iter([
+                        (ErrorCodeSelector::All, Severity::Warning),
+                        (
+                            ErrorCodeSelector::ErrorCode(ErrorCode::UnusedImport),
+                            Severity::Ignore
+                        ),
+                    ])),
+                    ..Options::default()
+                })
+            })
+        );
+
+        assert!(toml::from_str::<Pyproject>(
+            r#"
+[tool.pyrogen.severity]
+unused-import = "fatal"
+"#,
+        )
+        .is_err());
+
         assert!(toml::from_str::<Pyproject>(
             r#"
 [tool.black]
@@ -259,4 +481,137 @@ other-attribute = 1
 
         Ok(())
     }
+
+    #[test]
+    fn selector_locations() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join("pyproject.toml");
+        write(
+            &path,
+            r#"
+[tool.pyrogen]
+error = ["unused-import"]
+ignore = ["undefined-name"]
+
+[tool.pyrogen.severity]
+call-arg = "hint"
+undefined-name = "ignore"
+"#,
+        )?;
+
+        let locations = load_selector_locations(&path)?
+            .into_iter()
+            .map(|(selector, location)| (selector, location.row.get(), location.column.get()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            locations,
+            [
+                (ErrorCodeSelector::ErrorCode(ErrorCode::UnusedImport), 3, 10),
+                (ErrorCodeSelector::ErrorCode(ErrorCode::CallArgument), 7, 1),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_versioned_options() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join("pyproject.toml");
+        write(
+            &path,
+            r#"
+[tool.pyrogen]
+config-version = 1
+extend-error = ["unused-import"]
+"#,
+        )?;
+
+        let (options, diagnostics) = load_options_and_diagnostics(&path)?;
+        assert_eq!(
+            options,
+            Options {
+                config_version: Some(1),
+                extend_error: Some(vec![ErrorCodeSelector::ErrorCode(ErrorCode::UnusedImport)]),
+                ..Options::default()
+            }
+        );
+        assert!(diagnostics.is_empty());
+
+        // `extend-select` was never an option, so it isn't accepted as an alias.
+        write(
+            &path,
+            r#"
+[tool.pyrogen]
+extend-select = ["unused-import"]
+"#,
+        )?;
+        assert!(load_options_and_diagnostics(&path).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn entry_points() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join("pyproject.toml");
+        write(
+            &path,
+            r#"
+[project]
+name = "pkg"
+
+[project.scripts]
+pkg = "pkg.cli:main"
+
+[project.gui-scripts]
+pkg-gui = "pkg.gui:App.run [gui]"
+
+[project.entry-points."pytest11"]
+pkg = "pkg.plugin"
+
+"#,
+        )?;
+
+        assert_eq!(
+            load_entry_points(&path)?,
+            ["pkg.cli.main".to_string(), "pkg.gui.App".to_string()]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn dependencies() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join("pyproject.toml");
+        write(
+            &path,
+            r#"
+[project]
+name = "pkg"
+dependencies = ["PyYAML>=6", "requests[socks] ; python_version >= '3.8'", "typing_extensions"]
+
+[project.optional-dependencies]
+test = ["pytest~=7.4"]
+"#,
+        )?;
+        assert_eq!(
+            load_dependencies(&path)?,
+            Some(vec![
+                "pytest".to_string(),
+                "pyyaml".to_string(),
+                "requests".to_string(),
+                "typing-extensions".to_string(),
+            ])
+        );
+
+        write(
+            &path,
+            "[project]\nname = \"pkg\"\ndynamic = [\"dependencies\"]\n",
+        )?;
+        assert_eq!(load_dependencies(&path)?, None);
+
+        Ok(())
+    }
 }
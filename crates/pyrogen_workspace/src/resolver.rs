@@ -2,6 +2,7 @@
 //! filesystem.
 
 use std::collections::BTreeMap;
+use std::fmt;
 use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
@@ -119,6 +120,24 @@ impl Resolver {
         }
     }
 
+    /// Return the directory of the discovered configuration file whose [`Settings`] apply to
+    /// `path`, or `None` if the settings of the [`PyprojectConfig`] apply.
+    pub fn settings_root<'a>(
+        &'a self,
+        path: &Path,
+        pyproject_config: &PyprojectConfig,
+    ) -> Option<&'a Path> {
+        match pyproject_config.strategy {
+            PyprojectDiscoveryStrategy::Fixed => None,
+            PyprojectDiscoveryStrategy::Hierarchical => self
+                .settings
+                .keys()
+                .rev()
+                .find(|root| path.starts_with(root))
+                .map(PathBuf::as_path),
+        }
+    }
+
     /// Return a mapping from Python package to its package root.
     pub fn package_roots<'a>(
         &'a self,
@@ -217,11 +236,20 @@ fn resolve_configuration(
         }
 
         // Resolve the current path.
-        let options = pyproject::load_options(&path)
+        let (options, diagnostics) = pyproject::load_options_and_diagnostics(&path)
+            .map_err(|err| anyhow!("Failed to parse `{}`: {}", path.display(), err))?;
+
+        let locations = pyproject::load_selector_locations(&path)
             .map_err(|err| anyhow!("Failed to parse `{}`: {}", path.display(), err))?;
 
         let project_root = relativity.resolve(&path);
-        let configuration = Configuration::from_options(options, &project_root)?;
+        let mut configuration =
+            Configuration::from_options(options, &project_root)?.with_source(&path, locations);
+        configuration.load_diagnostics = diagnostics;
+        configuration.entry_points = pyproject::load_entry_points(&path)
+            .map_err(|err| anyhow!("Failed to parse `{}`: {}", path.display(), err))?;
+        configuration.declared_dependencies = pyproject::load_dependencies(&path)
+            .map_err(|err| anyhow!("Failed to parse `{}`: {}", path.display(), err))?;
 
         // // If extending, continue to collect.
         // next = configuration.extend.as_ref().map(|extend| {
@@ -272,12 +300,78 @@ pub fn resolve_root_settings(
     Ok(settings)
 }
 
+/// The reason a path was skipped during file discovery.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The path matched a pattern in `exclude`.
+    Exclude,
+    /// The path matched a pattern in `extend-exclude`.
+    ExtendExclude,
+    /// The path was passed directly, but excluded because `force-exclude` is enabled.
+    ForceExclude,
+    /// The path is ignored by a `.gitignore` or other standard ignore file.
+    Gitignore,
+    /// The file is not matched by `include` (e.g., it has an unsupported extension).
+    NotIncluded,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Exclude => f.write_str("matched `exclude`"),
+            SkipReason::ExtendExclude => f.write_str("matched `extend-exclude`"),
+            SkipReason::ForceExclude => f.write_str("excluded via `force-exclude`"),
+            SkipReason::Gitignore => f.write_str("ignored by `.gitignore` (`respect-gitignore`)"),
+            SkipReason::NotIncluded => f.write_str("not matched by `include`"),
+        }
+    }
+}
+
+/// A path that was skipped during file discovery, along with the reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedPath {
+    pub path: PathBuf,
+    pub reason: SkipReason,
+}
+
 /// Find all Python (`.py`, `.pyi` and `.ipynb` files) in a set of paths.
 pub fn python_files_in_path(
     paths: &[PathBuf],
     pyproject_config: &PyprojectConfig,
     transformer: &dyn ConfigurationTransformer,
 ) -> Result<(Vec<Result<DirEntry, ignore::Error>>, Resolver)> {
+    let (files, _skipped, resolver) =
+        walk_python_files(paths, pyproject_config, transformer, false)?;
+    Ok((files, resolver))
+}
+
+/// Like [`python_files_in_path`], but additionally return every path that was skipped during
+/// discovery, sorted by path.
+pub fn python_files_in_path_with_skipped(
+    paths: &[PathBuf],
+    pyproject_config: &PyprojectConfig,
+    transformer: &dyn ConfigurationTransformer,
+) -> Result<(
+    Vec<Result<DirEntry, ignore::Error>>,
+    Vec<SkippedPath>,
+    Resolver,
+)> {
+    let (files, mut skipped, resolver) =
+        walk_python_files(paths, pyproject_config, transformer, true)?;
+    skipped.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok((files, skipped, resolver))
+}
+
+fn walk_python_files(
+    paths: &[PathBuf],
+    pyproject_config: &PyprojectConfig,
+    transformer: &dyn ConfigurationTransformer,
+    track_skipped: bool,
+) -> Result<(
+    Vec<Result<DirEntry, ignore::Error>>,
+    Vec<SkippedPath>,
+    Resolver,
+)> {
     // Normalize every path (e.g., convert from relative to absolute).
     let mut paths: Vec<PathBuf> = paths.iter().map(fs::normalize_path).unique().collect();
 
@@ -299,10 +393,23 @@ pub fn python_files_in_path(
     }
 
     // Check if the paths themselves are excluded.
+    let mut skipped = vec![];
     if pyproject_config.settings.file_resolver.force_exclude {
-        paths.retain(|path| !is_file_excluded(path, &resolver, pyproject_config));
+        paths.retain(|path| {
+            if is_file_excluded(path, &resolver, pyproject_config) {
+                if track_skipped {
+                    skipped.push(SkippedPath {
+                        path: path.clone(),
+                        reason: SkipReason::ForceExclude,
+                    });
+                }
+                false
+            } else {
+                true
+            }
+        });
         if paths.is_empty() {
-            return Ok((vec![], resolver));
+            return Ok((vec![], skipped, resolver));
         }
     }
 
@@ -324,6 +431,15 @@ pub fn python_files_in_path(
     let resolver: RwLock<Resolver> = RwLock::new(resolver);
     let files: std::sync::Mutex<Vec<Result<DirEntry, ignore::Error>>> =
         std::sync::Mutex::new(vec![]);
+    let skipped: std::sync::Mutex<Vec<SkippedPath>> = std::sync::Mutex::new(skipped);
+    let skip = |path: &Path, reason: SkipReason| {
+        if track_skipped {
+            skipped.lock().unwrap().push(SkippedPath {
+                path: path.to_path_buf(),
+                reason,
+            });
+        }
+    };
     walker.run(|| {
         Box::new(|result| {
             // Respect our own exclusion behavior.
@@ -337,6 +453,7 @@ pub fn python_files_in_path(
                             && match_exclusion(path, file_name, &settings.file_resolver.exclude)
                         {
                             debug!("Ignored path via `exclude`: {:?}", path);
+                            skip(path, SkipReason::Exclude);
                             return WalkState::Skip;
                         } else if !settings.file_resolver.extend_exclude.is_empty()
                             && match_exclusion(
@@ -346,6 +463,7 @@ pub fn python_files_in_path(
                             )
                         {
                             debug!("Ignored path via `extend-exclude`: {:?}", path);
+                            skip(path, SkipReason::ExtendExclude);
                             return WalkState::Skip;
                         }
                     } else {
@@ -406,6 +524,7 @@ pub fn python_files_in_path(
                     //     debug!("Included path via `extend-include`: {:?}", path);
                     //     true
                     } else {
+                        skip(path, SkipReason::NotIncluded);
                         false
                     }
                 }
@@ -419,7 +538,74 @@ pub fn python_files_in_path(
 
     error.into_inner().unwrap()?;
 
-    Ok((files.into_inner().unwrap(), resolver.into_inner().unwrap()))
+    let files = files.into_inner().unwrap();
+    let mut skipped = skipped.into_inner().unwrap();
+    let resolver = resolver.into_inner().unwrap();
+
+    if track_skipped && pyproject_config.settings.file_resolver.respect_gitignore {
+        skipped.extend(gitignored_python_files(
+            &paths,
+            &files,
+            &resolver,
+            pyproject_config,
+        ));
+    }
+
+    Ok((files, skipped, resolver))
+}
+
+/// Find the Python files that would have been checked if not for `.gitignore` and the other
+/// standard ignore files.
+///
+/// The `ignore` walker silently drops such entries, so we re-walk the given paths without the
+/// standard filters and diff the result against the files that were actually found.
+fn gitignored_python_files(
+    paths: &[PathBuf],
+    files: &[Result<DirEntry, ignore::Error>],
+    resolver: &Resolver,
+    pyproject_config: &PyprojectConfig,
+) -> Vec<SkippedPath> {
+    let Some((first, rest)) = paths.split_first() else {
+        return vec![];
+    };
+    let found: FxHashSet<&Path> = files.iter().flatten().map(DirEntry::path).collect();
+
+    let mut builder = WalkBuilder::new(first);
+    for path in rest {
+        builder.add(path);
+    }
+    builder.standard_filters(false);
+    builder.hidden(false);
+    // Don't descend into directories that are excluded anyway (e.g., `.git` or `.venv`).
+    let exclude: globset::GlobSet = (*pyproject_config.settings.file_resolver.exclude).clone();
+    let extend_exclude: globset::GlobSet =
+        (*pyproject_config.settings.file_resolver.extend_exclude).clone();
+    builder.filter_entry(move |entry| {
+        entry.depth() == 0
+            || !entry.file_type().is_some_and(|ft| ft.is_dir())
+            || !(match_exclusion(entry.path(), entry.file_name(), &exclude)
+                || match_exclusion(entry.path(), entry.file_name(), &extend_exclude))
+    });
+
+    let mut skipped = vec![];
+    for entry in builder.build().flatten() {
+        if entry.depth() == 0 || entry.file_type().map_or(true, |ft| ft.is_dir()) {
+            continue;
+        }
+        let path = entry.path();
+        if found.contains(path) || is_file_excluded(path, resolver, pyproject_config) {
+            continue;
+        }
+        let settings = resolver.resolve(path, pyproject_config);
+        if settings.file_resolver.include.is_match(path) {
+            debug!("Ignored path via `respect-gitignore`: {:?}", path);
+            skipped.push(SkippedPath {
+                path: path.to_path_buf(),
+                reason: SkipReason::Gitignore,
+            });
+        }
+    }
+    skipped
 }
 
 /// Return `true` if the Python file at [`Path`] is _not_ excluded.
@@ -451,6 +637,47 @@ pub fn python_file_at_path(
     Ok(!is_file_excluded(&path, &resolver, pyproject_config))
 }
 
+/// Return the Python files among `paths` that the discovery of a directory containing them would
+/// find: the files that are included by the settings that apply to them, and that aren't excluded
+/// themselves or through one of their parents, regardless of `force-exclude`.
+pub fn discoverable_python_files(
+    paths: &[PathBuf],
+    pyproject_config: &PyprojectConfig,
+    transformer: &dyn ConfigurationTransformer,
+) -> Result<Vec<PathBuf>> {
+    let paths: Vec<PathBuf> = paths.iter().map(fs::normalize_path).unique().collect();
+
+    // Search for `pyproject.toml` files in all parent directories.
+    let mut resolver = Resolver::default();
+    let mut seen = FxHashSet::default();
+    if pyproject_config.strategy.is_hierarchical() {
+        for path in &paths {
+            for ancestor in path.ancestors() {
+                if seen.insert(ancestor) {
+                    if let Some(pyproject) = settings_toml(ancestor)? {
+                        let (root, settings) =
+                            resolve_scoped_settings(&pyproject, Relativity::Parent, transformer)?;
+                        resolver.add(root, settings);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(paths
+        .iter()
+        .filter(|path| {
+            resolver
+                .resolve(path, pyproject_config)
+                .file_resolver
+                .include
+                .is_match(path)
+                && !is_file_excluded(path, &resolver, pyproject_config)
+        })
+        .cloned()
+        .collect())
+}
+
 /// Return `true` if the given top-level [`Path`] should be excluded.
 fn is_file_excluded(
     path: &Path,
@@ -514,9 +741,9 @@ mod tests {
     use crate::configuration::Configuration;
     use crate::pyproject::find_settings_toml;
     use crate::resolver::{
-        is_file_excluded, match_exclusion, python_files_in_path, resolve_root_settings,
-        ConfigurationTransformer, PyprojectConfig, PyprojectDiscoveryStrategy, Relativity,
-        Resolver,
+        discoverable_python_files, is_file_excluded, match_exclusion, python_files_in_path,
+        python_files_in_path_with_skipped, resolve_root_settings, ConfigurationTransformer,
+        PyprojectConfig, PyprojectDiscoveryStrategy, Relativity, Resolver, SkipReason, SkippedPath,
     };
     use crate::settings::Settings;
     use crate::tests::test_resource_path;
@@ -595,6 +822,141 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn find_skipped_files() -> Result<()> {
+        // Initialize the filesystem:
+        //   root
+        //   ├── file.py
+        //   ├── README.md
+        //   └── venv
+        //       └── lib.py
+        let tmp_dir = TempDir::new()?;
+        let root = tmp_dir.path();
+        let venv = root.join("venv");
+        File::create(root.join("file.py"))?;
+        File::create(root.join("README.md"))?;
+        create_dir(&venv)?;
+        File::create(venv.join("lib.py"))?;
+
+        let (paths, skipped, _) = python_files_in_path_with_skipped(
+            &[root.to_path_buf()],
+            &PyprojectConfig::new(PyprojectDiscoveryStrategy::Fixed, Settings::default(), None),
+            &NoOpTransformer,
+        )?;
+        let paths = paths
+            .iter()
+            .flatten()
+            .map(ignore::DirEntry::path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, &[root.join("file.py")]);
+        assert_eq!(
+            skipped,
+            &[
+                SkippedPath {
+                    path: root.join("README.md"),
+                    reason: SkipReason::NotIncluded,
+                },
+                SkippedPath {
+                    path: venv,
+                    reason: SkipReason::Exclude,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn find_gitignored_files() -> Result<()> {
+        // Initialize the filesystem:
+        //   root
+        //   ├── .git
+        //   ├── .gitignore (generated.py)
+        //   ├── file.py
+        //   └── generated.py
+        let tmp_dir = TempDir::new()?;
+        let root = tmp_dir.path();
+        create_dir(root.join(".git"))?;
+        std::fs::write(root.join(".gitignore"), "generated.py\n")?;
+        File::create(root.join("file.py"))?;
+        File::create(root.join("generated.py"))?;
+
+        let (paths, skipped, _) = python_files_in_path_with_skipped(
+            &[root.to_path_buf()],
+            &PyprojectConfig::new(PyprojectDiscoveryStrategy::Fixed, Settings::default(), None),
+            &NoOpTransformer,
+        )?;
+        let paths = paths
+            .iter()
+            .flatten()
+            .map(ignore::DirEntry::path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, &[root.join("file.py")]);
+        let gitignored = skipped
+            .into_iter()
+            .filter(|skipped| skipped.reason == SkipReason::Gitignore)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            gitignored,
+            &[SkippedPath {
+                path: root.join("generated.py"),
+                reason: SkipReason::Gitignore,
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn discoverable_files() -> Result<()> {
+        // Initialize the filesystem:
+        //   root
+        //   ├── file.py
+        //   ├── README.md
+        //   ├── venv
+        //   │   └── lib.py
+        //   └── sub
+        //       ├── pyproject.toml (extend-exclude = ["generated"])
+        //       ├── module.py
+        //       └── generated
+        //           └── schema.py
+        let tmp_dir = TempDir::new()?;
+        let root = tmp_dir.path();
+        let venv = root.join("venv");
+        let sub = root.join("sub");
+        let generated = sub.join("generated");
+        create_dir(&venv)?;
+        create_dir(&sub)?;
+        create_dir(&generated)?;
+        let paths = [
+            root.join("file.py"),
+            root.join("README.md"),
+            venv.join("lib.py"),
+            sub.join("module.py"),
+            generated.join("schema.py"),
+        ];
+        for path in &paths {
+            File::create(path)?;
+        }
+        std::fs::write(
+            sub.join("pyproject.toml"),
+            "[tool.pyrogen]\nextend-exclude = [\"generated\"]\n",
+        )?;
+
+        let files = discoverable_python_files(
+            &paths,
+            &PyprojectConfig::new(
+                PyprojectDiscoveryStrategy::Hierarchical,
+                Settings::default(),
+                None,
+            ),
+            &NoOpTransformer,
+        )?;
+        assert_eq!(files, &[root.join("file.py"), sub.join("module.py")]);
+
+        Ok(())
+    }
+
     fn make_exclusion(file_pattern: FilePattern) -> GlobSet {
         let mut builder = globset::GlobSetBuilder::new();
         file_pattern.add_to(&mut builder).unwrap();
@@ -1,32 +1,65 @@
 use std::path::{Path, PathBuf};
 
 use path_absolutize::path_dedot;
-use pyrogen_cache::cache_dir;
+use pyrogen_cache::{cache_dir, CacheInvalidation, CacheLocation};
+use pyrogen_checker::locale::Locale;
+use pyrogen_checker::message::OutputTemplate;
+use pyrogen_checker::registry::ErrorCodeSet;
 use pyrogen_checker::settings::{
-    types::{FilePattern, FilePatternSet, SerializationFormat},
+    types::{FilePattern, FilePatternSet, SerializationFormat, SeverityMapping},
     CheckerSettings,
 };
 use pyrogen_macros::CacheKey;
+use pyrogen_source_file::ColumnEncoding;
+
+use crate::configuration::ConfigurationDiagnostic;
 
 #[derive(Debug, CacheKey)]
 pub struct Settings {
     #[cache_key(ignore)]
     pub cache_dir: PathBuf,
     #[cache_key(ignore)]
+    pub cache_invalidation: CacheInvalidation,
+    #[cache_key(ignore)]
     pub output_format: SerializationFormat,
+    #[cache_key(ignore)]
+    pub output_severity_mapping: SeverityMapping,
+    #[cache_key(ignore)]
+    pub output_template: Option<OutputTemplate>,
+    #[cache_key(ignore)]
+    pub column_encoding: ColumnEncoding,
+    #[cache_key(ignore)]
+    pub locale: Locale,
+    /// The codes whose diagnostics abort the run.
+    #[cache_key(ignore)]
+    pub fail_fast: ErrorCodeSet,
+    /// Whether `type: ignore` comments suppress diagnostics.
+    #[cache_key(ignore)]
+    pub respect_type_ignore: bool,
 
     pub file_resolver: FileResolverSettings,
     pub checker: CheckerSettings,
+    /// Problems found while loading the configuration.
+    #[cache_key(ignore)]
+    pub diagnostics: Vec<ConfigurationDiagnostic>,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         let project_root = path_dedot::CWD.as_path();
         Self {
-            cache_dir: cache_dir(project_root),
+            cache_dir: cache_dir(project_root, CacheLocation::default()),
+            cache_invalidation: CacheInvalidation::default(),
             checker: CheckerSettings::new(project_root),
             file_resolver: FileResolverSettings::new(project_root),
             output_format: SerializationFormat::default(),
+            output_severity_mapping: SeverityMapping::default(),
+            output_template: None,
+            column_encoding: ColumnEncoding::default(),
+            locale: Locale::default(),
+            fail_fast: ErrorCodeSet::empty(),
+            respect_type_ignore: true,
+            diagnostics: vec![],
         }
     }
 }
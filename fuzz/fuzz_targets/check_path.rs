@@ -0,0 +1,27 @@
+//! Check arbitrary sources with all error codes, including the ones in preview.
+#![no_main]
+
+use std::path::Path;
+
+use libfuzzer_sys::fuzz_target;
+
+use pyrogen_checker::cancellation::CancellationToken;
+use pyrogen_checker::checker::lint_only;
+use pyrogen_checker::settings::{flags, CheckerSettings};
+use pyrogen_checker::source_kind::SourceKind;
+use pyrogen_checker::ErrorCodeSelector;
+use pyrogen_python_ast::PySourceType;
+
+fuzz_target!(|source: &str| {
+    let settings = CheckerSettings::for_rules(ErrorCodeSelector::All.all_rules());
+    let _ = lint_only(
+        Path::new("fuzz.py"),
+        None,
+        &settings,
+        flags::TypeIgnore::Enabled,
+        &SourceKind::new(source.to_string()),
+        PySourceType::Python,
+        None,
+        &CancellationToken::default(),
+    );
+});
@@ -0,0 +1,20 @@
+//! Check arbitrary `pyproject.toml` files.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use pyrogen_checker::pyproject_toml::lint_pyproject_toml;
+use pyrogen_checker::registry::ErrorCode;
+use pyrogen_checker::settings::CheckerSettings;
+use pyrogen_source_file::SourceFileBuilder;
+
+fuzz_target!(|contents: &str| {
+    let settings = CheckerSettings::for_rules([
+        ErrorCode::InvalidPyprojectToml,
+        ErrorCode::PyprojectRequiresPython,
+        ErrorCode::PyprojectClassifier,
+        ErrorCode::PyprojectDependency,
+    ]);
+    let source_file = SourceFileBuilder::new("pyproject.toml", contents).finish();
+    let _ = lint_pyproject_toml(source_file, &settings);
+});
@@ -0,0 +1,8 @@
+//! Extract the `type: ignore` directive of arbitrary comments.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    pyrogen_checker::test::try_extract_type_ignore(text);
+});
@@ -0,0 +1,51 @@
+"""Sync the standard library stubs of typeshed into `crates/pyrogen_vendored`.
+
+Usage: python scripts/sync_typeshed.py [<commit>]
+"""
+
+from __future__ import annotations
+
+import argparse
+import io
+import shutil
+import tarfile
+import urllib.request
+from pathlib import Path
+
+ROOT = Path(__file__).resolve().parent.parent
+TARGET = ROOT / "crates" / "pyrogen_vendored" / "vendor" / "typeshed"
+URL = "https://github.com/python/typeshed/archive/{commit}.tar.gz"
+
+
+def main() -> None:
+    parser = argparse.ArgumentParser(description=__doc__)
+    parser.add_argument("commit", nargs="?", default="main")
+    args = parser.parse_args()
+
+    with urllib.request.urlopen(URL.format(commit=args.commit)) as response:
+        archive = tarfile.open(fileobj=io.BytesIO(response.read()), mode="r:gz")
+
+    shutil.rmtree(TARGET / "stdlib", ignore_errors=True)
+    for member in archive.getmembers():
+        # Strip the `typeshed-<commit>/` prefix.
+        _, _, name = member.name.partition("/")
+        if not member.isfile():
+            continue
+        if not (name.startswith("stdlib/") or name == "LICENSE"):
+            continue
+        if name.startswith("stdlib/@tests/"):
+            continue
+        destination = TARGET / name
+        destination.parent.mkdir(parents=True, exist_ok=True)
+        extracted = archive.extractfile(member)
+        assert extracted is not None
+        destination.write_bytes(extracted.read())
+
+    # `archive.pax_headers["comment"]` holds the resolved commit for GitHub archives.
+    commit = archive.pax_headers.get("comment", args.commit)
+    (TARGET / "source_commit.txt").write_text(f"{commit}\n")
+    print(f"Synced typeshed {commit} into {TARGET.relative_to(ROOT)}")
+
+
+if __name__ == "__main__":
+    main()